@@ -21,10 +21,28 @@ pub struct Cli {
     /// Verbose output
     #[arg(short, long, global = true)]
     pub verbose: bool,
+
+    /// Open the index read-only: commands that write fail and MCP hides mutating tools
+    #[arg(
+        long,
+        global = true,
+        env = "AGENTROOT_READ_ONLY",
+        value_parser = clap::builder::BoolishValueParser::new()
+    )]
+    pub read_only: bool,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
+    /// Create a project-local index (.agentroot/) in a directory
+    Init(InitArgs),
+
+    /// Encrypt the index at rest (needs the `encryption` build feature)
+    Encrypt(EncryptArgs),
+
+    /// Decrypt an encrypted index back to plain SQLite
+    Decrypt,
+
     /// Manage collections
     Collection(CollectionArgs),
 
@@ -40,8 +58,11 @@ pub enum Commands {
     /// Get multiple documents
     MultiGet(MultiGetArgs),
 
+    /// Remove a document from the index (the next update adds it back if its source still exists)
+    Rm(RmArgs),
+
     /// Show index status
-    Status,
+    Status(StatusArgs),
 
     /// Update collections
     Update(UpdateArgs),
@@ -61,17 +82,98 @@ pub enum Commands {
     /// Smart natural language search with auto fallback
     Smart(SearchArgs),
 
+    /// Interactive search prompt with history, numbered results and follow-up commands
+    Repl(ReplArgs),
+
+    /// Answer a question from the index with cited sources (needs an LLM service)
+    Answer(AnswerArgs),
+
     /// Database cleanup
     Cleanup,
 
+    /// Check the index for problems and print how to fix them
+    Doctor(DoctorArgs),
+
     /// Manage LLM-generated metadata
     Metadata(MetadataArgs),
 
     /// Compute PageRank scores for documents
     Pagerank,
 
+    /// List documents that link to a document
+    Backlinks(BacklinksArgs),
+
+    /// List a document's outgoing and incoming links with their types
+    Links(LinksArgs),
+
+    /// Recommend documents related to a document
+    Related(RelatedArgs),
+
+    /// Find documents similar to a document, without a query
+    Similar(SimilarArgs),
+
+    /// Compare two documents for similarity and duplicated passages
+    Compare(CompareArgs),
+
+    /// Resurface a weighted random sample of documents
+    Discover(DiscoverArgs),
+
+    /// Find where a function, struct or class is defined
+    Def(DefArgs),
+
+    /// Find code that calls a function or names a type
+    Refs(RefsArgs),
+
+    /// Search document bodies with a regular expression, line by line
+    Grep(GrepArgs),
+
+    /// List or search date-named notes ("postgres in March")
+    Journal(JournalArgs),
+
+    /// Browse topic clusters
+    Topics(TopicsArgs),
+
+    /// Inspect and curate the concept glossary
+    Glossary(GlossaryArgs),
+
+    /// Report links pointing at documents not in the index
+    DeadLinks(DeadLinksArgs),
+
+    /// Export the link graph around a document
+    Graph(GraphArgs),
+
+    /// Manage search sessions
+    Session(SessionArgs),
+
+    /// Record relevance feedback on search results
+    Feedback(FeedbackArgs),
+
+    /// Pin documents so they rank first (or get a boost) for matching queries
+    Pin(PinArgs),
+
+    /// Score search modes against golden queries (recall@k, MRR, NDCG)
+    Eval(EvalArgs),
+
+    /// Manage the background job queue
+    Jobs(JobsArgs),
+
+    /// Export the index (tags files, or a portable archive to a .tar.zst path)
+    Export(ExportArgs),
+
+    /// Add the collections of an archive written by `export` to the index
+    Import(ImportArgs),
+
+    /// Keep indexes fresh in the foreground
+    Watch(WatchArgs),
+
     /// Start MCP server
-    Mcp,
+    Mcp(McpArgs),
+
+    /// Start the gRPC API server
+    Grpc(GrpcArgs),
+
+    /// Start the HTTP API server (retriever endpoints for agent frameworks)
+    Serve(ServeArgs),
 }
 
 #[derive(Args)]
@@ -95,7 +197,7 @@ pub enum CollectionAction {
         #[arg(long, default_value = "**/*.md")]
         mask: String,
 
-        /// Provider type: file, github, url, etc. (defaults to 'file')
+        /// Provider type: file, obsidian, github, url, email, etc. (defaults to 'file')
         #[arg(long, default_value = "file")]
         provider: String,
 
@@ -168,6 +270,74 @@ pub struct SearchArgs {
     /// Search at chunk-level instead of document-level
     #[arg(long)]
     pub chunks: bool,
+
+    /// Match literal tokens without stemming ("tests" does not match "test")
+    #[arg(long)]
+    pub exact: bool,
+
+    /// Tolerate typos: misspelled words also match close indexed words
+    #[arg(long)]
+    pub fuzzy: bool,
+
+    /// Hybrid fusion weight of keywords vs meaning: 1 = BM25 only, 0 = vectors only
+    #[arg(long, value_parser = parse_alpha)]
+    pub alpha: Option<f64>,
+
+    /// Rank without link-graph boosts (PageRank importance and centrality)
+    #[arg(long)]
+    pub no_pagerank: bool,
+
+    /// Attach to a session (ID or name) shared with MCP clients and the TUI
+    #[arg(long, env = "AGENTROOT_SESSION")]
+    pub session: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ReplArgs {
+    /// Search mode to start in; `mode <name>` switches at the prompt
+    #[arg(long, value_enum, default_value = "smart")]
+    pub mode: SearchMode,
+
+    /// Number of results per query
+    #[arg(short = 'n', default_value = "10")]
+    pub limit: usize,
+
+    /// Filter by collection
+    #[arg(short, long)]
+    pub collection: Option<String>,
+
+    /// Session (ID or name) the queries are logged to, created when missing
+    #[arg(long, env = "AGENTROOT_SESSION", default_value = "repl")]
+    pub session: String,
+}
+
+fn parse_alpha(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(alpha) if (0.0..=1.0).contains(&alpha) => Ok(alpha),
+        _ => Err("must be a number between 0 and 1".to_string()),
+    }
+}
+
+#[derive(Args)]
+pub struct AnswerArgs {
+    /// Question to answer
+    pub query: Vec<String>,
+
+    /// Maximum estimated tokens of context given to the LLM
+    #[arg(long, default_value = "4000")]
+    pub budget: usize,
+
+    /// Filter by collection
+    #[arg(short, long)]
+    pub collection: Option<String>,
+
+    /// Minimum BM25 score of retrieved chunks
+    #[arg(long, default_value = "0")]
+    pub min_score: f64,
+
+    /// Match literal tokens without stemming ("tests" does not match "test")
+    #[arg(long)]
+    pub exact: bool,
 }
 
 #[derive(Args)]
@@ -186,6 +356,26 @@ pub struct GetArgs {
     /// Include line numbers
     #[arg(long)]
     pub line_numbers: bool,
+
+    /// Mark lines matching this query and report the match ranges
+    #[arg(long)]
+    pub highlight: Option<String>,
+
+    /// Highlight the latest query of this session (ID or name)
+    #[arg(long, env = "AGENTROOT_SESSION")]
+    pub session: Option<String>,
+
+    /// Show an earlier version: its number from --history, or its docid
+    #[arg(long)]
+    pub version: Option<String>,
+
+    /// Show a unified diff from this version to --version (default: latest)
+    #[arg(long)]
+    pub diff: Option<String>,
+
+    /// List the recorded versions of the document
+    #[arg(long, conflicts_with_all = ["version", "diff"])]
+    pub history: bool,
 }
 
 #[derive(Args)]
@@ -197,9 +387,13 @@ pub struct MultiGetArgs {
     #[arg(short, long = "max-lines")]
     pub l: Option<usize>,
 
-    /// Maximum file size in bytes
-    #[arg(long, default_value = "10240")]
-    pub max_bytes: usize,
+    /// Total estimated tokens across all files (default: 8000)
+    #[arg(long)]
+    pub max_tokens: Option<usize>,
+
+    /// Total bytes across all files
+    #[arg(long)]
+    pub max_bytes: Option<usize>,
 
     /// Include line numbers
     #[arg(long)]
@@ -219,11 +413,661 @@ pub struct UpdateArgs {
     pub pull: bool,
 }
 
+#[derive(Args)]
+pub struct InitArgs {
+    /// Project root (default: current directory)
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+}
+
+#[derive(Args)]
+pub struct EncryptArgs {
+    /// Do not save the key in the OS credential store
+    #[arg(long)]
+    pub no_store: bool,
+}
+
+#[derive(Args)]
+pub struct DoctorArgs {
+    /// Skip contacting the configured LLM and embedding services
+    #[arg(long)]
+    pub offline: bool,
+}
+
+#[derive(Args)]
+pub struct StatusArgs {
+    /// Show queries slower than AGENTROOT_SLOW_QUERY_MS instead
+    #[arg(long)]
+    pub slow_queries: bool,
+
+    /// Number of slow queries to show
+    #[arg(short = 'n', default_value = "20", requires = "slow_queries")]
+    pub limit: usize,
+
+    /// Delete the slow query log
+    #[arg(long, requires = "slow_queries")]
+    pub clear: bool,
+}
+
 #[derive(Args)]
 pub struct EmbedArgs {
     /// Force re-embedding of all documents
     #[arg(short, long)]
     pub force: bool,
+
+    /// With --force, start over instead of resuming an interrupted run
+    #[arg(long, requires = "force")]
+    pub restart: bool,
+
+    /// Check stored embeddings against the current model and repair only what is broken
+    #[arg(long, conflicts_with = "force")]
+    pub verify: bool,
+
+    /// With --verify, report problems without repairing them
+    #[arg(long, requires = "verify")]
+    pub dry_run: bool,
+
+    /// Then build the approximate nearest-neighbour index used by vector search
+    #[arg(long, conflicts_with = "verify")]
+    pub optimize: bool,
+
+    /// Re-embed everything with another model, switching over once it is done
+    #[arg(long, value_name = "MODEL", conflicts_with_all = ["force", "verify"])]
+    pub migrate_to: Option<String>,
+
+    /// Continue an interrupted re-embedding run or migration where it stopped
+    #[arg(long, conflicts_with_all = ["force", "verify", "migrate_to"])]
+    pub resume: bool,
+
+    /// Then delete the full-precision copies kept behind quantized vectors
+    #[arg(long, conflicts_with = "verify")]
+    pub drop_full_precision: bool,
+}
+
+#[derive(Args)]
+pub struct RmArgs {
+    /// Document docid (#abc123) or collection/path
+    pub docid: String,
+}
+
+#[derive(Args)]
+pub struct BacklinksArgs {
+    /// Document path or docid (#abc123)
+    pub docid: String,
+}
+
+#[derive(Args)]
+pub struct LinksArgs {
+    /// Document path or docid (#abc123)
+    pub docid: String,
+
+    /// Only links from this document
+    #[arg(long, conflicts_with = "incoming")]
+    pub outgoing: bool,
+
+    /// Only links to this document
+    #[arg(long)]
+    pub incoming: bool,
+}
+
+#[derive(Args)]
+pub struct RelatedArgs {
+    /// Document path or docid (#abc123)
+    pub docid: String,
+
+    /// Number of results
+    #[arg(short = 'n', default_value = "10")]
+    pub limit: usize,
+}
+
+#[derive(Args)]
+pub struct SimilarArgs {
+    /// Document path or docid (#abc123)
+    pub docid: String,
+
+    /// Number of results
+    #[arg(short = 'n', default_value = "10")]
+    pub limit: usize,
+}
+
+#[derive(Args)]
+pub struct CompareArgs {
+    /// First document path or docid (#abc123)
+    pub a: String,
+
+    /// Second document path or docid
+    pub b: String,
+}
+
+#[derive(Args)]
+pub struct DiscoverArgs {
+    /// Restrict to a collection
+    #[arg(short, long)]
+    pub collection: Option<String>,
+
+    /// Number of documents
+    #[arg(short = 'n', default_value = "10")]
+    pub limit: usize,
+
+    /// Only documents no search has returned yet
+    #[arg(long)]
+    pub never_retrieved: bool,
+
+    /// Seed for a reproducible sample
+    #[arg(long)]
+    pub seed: Option<u64>,
+}
+
+#[derive(Args)]
+pub struct DefArgs {
+    /// Symbol name, bare ("parse") or qualified ("Parser::parse")
+    pub name: String,
+
+    /// Restrict to a collection
+    #[arg(short, long)]
+    pub collection: Option<String>,
+
+    /// Maximum definitions
+    #[arg(short = 'n', default_value = "10")]
+    pub limit: usize,
+
+    /// Print the source of each definition
+    #[arg(long)]
+    pub body: bool,
+}
+
+#[derive(Args)]
+pub struct RefsArgs {
+    /// Symbol name; qualified names match on their last segment
+    pub name: String,
+
+    /// Only calls or only type uses
+    #[arg(long, value_enum)]
+    pub kind: Option<RefKindArg>,
+
+    /// Restrict to a collection
+    #[arg(short, long)]
+    pub collection: Option<String>,
+
+    /// Maximum references
+    #[arg(short = 'n', default_value = "50")]
+    pub limit: usize,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum RefKindArg {
+    Call,
+    Type,
+}
+
+#[derive(Args)]
+pub struct GrepArgs {
+    /// Regular expression (Rust regex syntax)
+    pub pattern: String,
+
+    /// Restrict to a collection
+    #[arg(short, long)]
+    pub collection: Option<String>,
+
+    /// Only documents whose path matches this glob ("src/**/*.rs")
+    #[arg(short, long)]
+    pub glob: Option<String>,
+
+    /// Match case-insensitively
+    #[arg(short, long)]
+    pub ignore_case: bool,
+
+    /// Lines of context around each match
+    #[arg(short = 'C', long, default_value = "0")]
+    pub context: usize,
+
+    /// Maximum matching lines (0 for no limit)
+    #[arg(short = 'n', default_value = "100")]
+    pub limit: usize,
+
+    /// Print only the paths of matching documents
+    #[arg(short = 'l', long)]
+    pub files_with_matches: bool,
+}
+
+#[derive(Args)]
+pub struct JournalArgs {
+    /// Search terms, optionally with a date phrase ("in March", "last week")
+    pub query: Vec<String>,
+
+    /// First day (YYYY-MM-DD)
+    #[arg(long)]
+    pub from: Option<String>,
+
+    /// Last day (YYYY-MM-DD)
+    #[arg(long)]
+    pub to: Option<String>,
+
+    /// Restrict to a collection
+    #[arg(short, long)]
+    pub collection: Option<String>,
+
+    /// Maximum notes
+    #[arg(short = 'n', default_value = "20")]
+    pub limit: usize,
+}
+
+#[derive(Args)]
+pub struct GraphArgs {
+    /// Document path or docid (#abc123)
+    pub docid: String,
+
+    /// Hops to follow from the document (max 3)
+    #[arg(long, default_value = "1")]
+    pub depth: usize,
+
+    /// Only follow these link types (markdown_link, wikilink, imports)
+    #[arg(long = "edge-type")]
+    pub edge_types: Vec<String>,
+
+    /// Emit Graphviz DOT instead of text
+    #[arg(long)]
+    pub dot: bool,
+}
+
+#[derive(Args)]
+pub struct DeadLinksArgs {
+    /// Filter by collection
+    #[arg(short, long)]
+    pub collection: Option<String>,
+}
+
+#[derive(Args)]
+pub struct TopicsArgs {
+    #[command(subcommand)]
+    pub action: Option<TopicsAction>,
+}
+
+#[derive(Subcommand)]
+pub enum TopicsAction {
+    /// Detect topic clusters (replaces existing topics)
+    Build {
+        /// Generate topic labels with the configured LLM service
+        #[arg(long)]
+        llm: bool,
+    },
+    /// List topics (default)
+    List,
+    /// Show documents in a topic
+    Show {
+        /// Topic ID
+        id: i64,
+
+        /// Number of documents
+        #[arg(short = 'n', default_value = "20")]
+        limit: usize,
+    },
+}
+
+#[derive(Args)]
+pub struct GlossaryArgs {
+    #[command(subcommand)]
+    pub action: Option<GlossaryAction>,
+}
+
+#[derive(Subcommand)]
+pub enum GlossaryAction {
+    /// List concepts by linked chunk count (default)
+    List {
+        /// Number of concepts
+        #[arg(short = 'n', default_value = "50")]
+        limit: usize,
+    },
+    /// Show the chunks linked to a concept
+    Show {
+        /// Concept ID or term
+        concept: String,
+
+        /// Number of chunks
+        #[arg(short = 'n', default_value = "20")]
+        limit: usize,
+    },
+    /// Map the corpus by concept: top concepts and what they co-occur with
+    Browse {
+        /// Concept ID or term to drill into (omit for the top concepts)
+        concept: Option<String>,
+
+        /// Number of concepts, documents and related concepts
+        #[arg(short = 'n', default_value = "20")]
+        limit: usize,
+    },
+    /// Add a concept and link it to chunks that mention it
+    Add {
+        /// Concept term
+        term: String,
+    },
+    /// Merge a synonym into another concept
+    Merge {
+        /// Concept ID or term to merge away
+        from: String,
+
+        /// Concept ID or term to keep
+        into: String,
+    },
+    /// Delete a concept and its chunk links
+    Delete {
+        /// Concept ID or term
+        concept: String,
+    },
+}
+
+#[derive(Args)]
+pub struct SessionArgs {
+    #[command(subcommand)]
+    pub action: Option<SessionAction>,
+}
+
+#[derive(Subcommand)]
+pub enum SessionAction {
+    /// List live sessions (default)
+    List,
+    /// Start a new session
+    Start {
+        /// Name to resume the session by
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Time-to-live in seconds since last activity
+        #[arg(long, default_value = "3600", conflicts_with = "persistent")]
+        ttl: i64,
+
+        /// Never expire the session
+        #[arg(long)]
+        persistent: bool,
+    },
+    /// Resume a session by ID or name and show its history
+    Resume {
+        /// Session ID or name
+        session: String,
+
+        /// Disable expiry for the resumed session
+        #[arg(long)]
+        persistent: bool,
+    },
+    /// Rename a session
+    Rename {
+        /// Session ID or name
+        session: String,
+
+        /// New session name
+        name: String,
+    },
+    /// End a session and discard its history
+    End {
+        /// Session ID or name
+        session: String,
+    },
+    /// Summarize session queries, zero-result queries and top documents
+    Stats {
+        /// Restrict the report to one session (ID or name)
+        session: Option<String>,
+
+        /// Number of zero-result queries and documents to show
+        #[arg(short = 'n', default_value = "10")]
+        limit: usize,
+    },
+}
+
+#[derive(Args)]
+pub struct WatchArgs {
+    /// Reindex file collections as their files change
+    #[arg(long)]
+    pub files: bool,
+
+    /// Milliseconds without edits before a changed collection is reindexed
+    #[arg(long, default_value = "500")]
+    pub debounce_ms: u64,
+
+    /// Run collection updates on the schedules set in the config file
+    #[arg(long)]
+    pub schedule: bool,
+}
+
+#[derive(Args)]
+pub struct McpArgs {
+    /// Serve over streamable HTTP on this address instead of stdio
+    #[arg(
+        long,
+        env = "AGENTROOT_MCP_ADDR",
+        value_name = "ADDR",
+        num_args = 0..=1,
+        default_missing_value = agentroot_mcp::http::DEFAULT_ADDR
+    )]
+    pub http: Option<std::net::SocketAddr>,
+
+    /// Requests served concurrently over HTTP (one database connection each)
+    #[arg(long, default_value_t = agentroot_mcp::http::DEFAULT_WORKERS)]
+    pub workers: usize,
+
+    /// HTTP sessions kept open at once; `initialize` is refused beyond that
+    #[arg(
+        long,
+        env = "AGENTROOT_MCP_MAX_SESSIONS",
+        default_value_t = agentroot_mcp::http::DEFAULT_MAX_SESSIONS
+    )]
+    pub max_sessions: usize,
+}
+
+#[derive(Args)]
+pub struct GrpcArgs {
+    /// Address to listen on
+    #[arg(long, env = "AGENTROOT_GRPC_ADDR", default_value = agentroot_grpc::DEFAULT_ADDR)]
+    pub addr: std::net::SocketAddr,
+
+    /// Requests served concurrently (one database connection each)
+    #[arg(long, default_value_t = agentroot_grpc::DEFAULT_WORKERS)]
+    pub workers: usize,
+}
+
+#[derive(Args)]
+pub struct ServeArgs {
+    /// Address to listen on
+    #[arg(long, env = "AGENTROOT_HTTP_ADDR", default_value = agentroot_http::DEFAULT_ADDR)]
+    pub addr: std::net::SocketAddr,
+
+    /// Requests served concurrently (one database connection each)
+    #[arg(long, default_value_t = agentroot_http::DEFAULT_WORKERS)]
+    pub workers: usize,
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// Only export this collection
+    #[arg(short, long)]
+    pub collection: Option<String>,
+
+    /// Output file (default: tags for ctags, TAGS for etags; - for stdout).
+    /// A .tar.zst path writes a portable archive with content and embeddings
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct ImportArgs {
+    /// Archive written by `agentroot export ... out.tar.zst`
+    pub archive: PathBuf,
+
+    /// Import the archive's collection under another name
+    #[arg(long, value_name = "NAME")]
+    pub rename: Option<String>,
+
+    /// Source directory of the collection on this machine
+    #[arg(long)]
+    pub path: Option<PathBuf>,
+
+    /// Replace collections that already exist
+    #[arg(long)]
+    pub replace: bool,
+}
+
+#[derive(Args)]
+pub struct EvalArgs {
+    /// YAML file of golden queries with expected docids or paths
+    pub file: PathBuf,
+
+    /// Search modes to evaluate (default: all)
+    #[arg(long = "mode", value_enum, value_delimiter = ',')]
+    pub modes: Vec<EvalModeArg>,
+
+    /// Metric cutoff (default: the file's k, or 10)
+    #[arg(short)]
+    pub k: Option<usize>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum EvalModeArg {
+    Bm25,
+    Vector,
+    Hybrid,
+    Smart,
+}
+
+#[derive(Args)]
+pub struct JobsArgs {
+    #[command(subcommand)]
+    pub action: Option<JobsAction>,
+}
+
+#[derive(Subcommand)]
+pub enum JobsAction {
+    /// List recent jobs (default)
+    List {
+        /// Only show jobs in this state
+        #[arg(long, value_enum)]
+        status: Option<JobStatusArg>,
+
+        /// Number of jobs
+        #[arg(short = 'n', default_value = "20")]
+        limit: usize,
+    },
+    /// Queue a job for the background worker
+    Add {
+        /// Work to queue
+        #[arg(value_enum)]
+        kind: JobKindArg,
+
+        /// Restrict reindex or metadata jobs to one collection
+        #[arg(short, long)]
+        collection: Option<String>,
+
+        /// Re-embed all documents (embed jobs)
+        #[arg(short, long)]
+        force: bool,
+
+        /// Higher priorities run first
+        #[arg(short, long, default_value = "0", allow_hyphen_values = true)]
+        priority: i32,
+    },
+    /// Run queued jobs in the foreground until the queue is empty
+    Run {
+        /// Maximum number of jobs to run
+        #[arg(short = 'n', default_value = "100")]
+        max: usize,
+    },
+    /// Cancel a queued job
+    Cancel {
+        /// Job ID
+        id: i64,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum JobKindArg {
+    Reindex,
+    Embed,
+    Metadata,
+    Pagerank,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum JobStatusArg {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Args)]
+pub struct FeedbackArgs {
+    #[command(subcommand)]
+    pub action: Option<FeedbackAction>,
+}
+
+#[derive(Subcommand)]
+pub enum FeedbackAction {
+    /// Mark a document as relevant for a query
+    Good {
+        /// Document ID (#abc123) or path
+        docid: String,
+
+        /// Query the document was returned for
+        #[arg(short, long)]
+        query: String,
+
+        /// Session the judgment belongs to
+        #[arg(long)]
+        session: Option<String>,
+    },
+    /// Mark a document as irrelevant for a query
+    Bad {
+        /// Document ID (#abc123) or path
+        docid: String,
+
+        /// Query the document was returned for
+        #[arg(short, long)]
+        query: String,
+
+        /// Session the judgment belongs to
+        #[arg(long)]
+        session: Option<String>,
+    },
+    /// List recent judgments (default)
+    List {
+        /// Number of judgments
+        #[arg(short = 'n', default_value = "20")]
+        limit: usize,
+    },
+    /// Delete all judgments
+    Clear,
+}
+
+#[derive(Args)]
+pub struct PinArgs {
+    #[command(subcommand)]
+    pub action: Option<PinAction>,
+}
+
+#[derive(Subcommand)]
+pub enum PinAction {
+    /// Pin a document, replacing any earlier pin on it
+    Add {
+        /// Document ID (#abc123) or path
+        docid: String,
+
+        /// Only apply to queries containing this phrase (repeatable)
+        #[arg(short, long = "query")]
+        queries: Vec<String>,
+
+        /// Multiply the score instead of placing the document first
+        #[arg(long)]
+        boost: Option<f64>,
+    },
+    /// Remove a document's pin
+    Remove {
+        /// Document ID (#abc123) or path
+        docid: String,
+    },
+    /// List pinned documents (default)
+    List {
+        /// Only pins in this collection
+        #[arg(short, long)]
+        collection: Option<String>,
+    },
 }
 
 #[derive(Args)]
@@ -326,12 +1170,42 @@ pub enum MetadataAction {
     },
 }
 
+/// Retrieval strategy of the interactive prompt
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SearchMode {
+    /// Full-text keyword search
+    Bm25,
+    /// Vector similarity search
+    Vec,
+    /// BM25 and vectors fused, with expansion and reranking when an LLM is configured
+    Hybrid,
+    /// Natural language search with automatic fallback
+    Smart,
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
 pub enum OutputFormat {
     Cli,
+    /// Aligned columns (score, docid, collection, title, path) sized to the terminal
+    Table,
     Json,
     Csv,
     Md,
     Xml,
     Files,
+    /// Alfred/Raycast script-filter JSON
+    Alfred,
+    /// `path:line:col: snippet` lines for editor quickfix lists
+    Grep,
+    /// Vim/universal-ctags tags file
+    Ctags,
+    /// Emacs TAGS file
+    Etags,
+}
+
+impl OutputFormat {
+    /// Formats that print on-disk paths for local collections
+    pub fn uses_local_paths(self) -> bool {
+        matches!(self, Self::Grep | Self::Ctags | Self::Etags)
+    }
 }
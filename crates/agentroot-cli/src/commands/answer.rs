@@ -0,0 +1,84 @@
+//! Answer command
+
+use crate::app::{AnswerArgs, OutputFormat};
+use agentroot_core::llm::AnswerGenerator;
+use agentroot_core::{
+    build_context, build_hybrid_context, default_embedder, Database, SearchOptions,
+};
+use anyhow::Result;
+
+pub async fn run(args: AnswerArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    let query = args.query.join(" ");
+    if query.trim().is_empty() {
+        anyhow::bail!("Provide a question to answer");
+    }
+
+    let generator = AnswerGenerator::from_env()?;
+
+    let options = SearchOptions {
+        min_score: args.min_score,
+        collection: args.collection,
+        exact: args.exact,
+        ..Default::default()
+    };
+
+    // Hybrid retrieval when there are embeddings to search, BM25 otherwise
+    let embedder = if db.has_vector_index() {
+        default_embedder().await.ok()
+    } else {
+        None
+    };
+    let pack = match embedder {
+        Some(embedder) => {
+            build_hybrid_context(db, &query, args.budget, &options, embedder.as_ref()).await?
+        }
+        None => build_context(db, &query, args.budget, &options)?,
+    };
+
+    let answer = generator.answer(&query, &pack).await?;
+
+    match format {
+        OutputFormat::Json => {
+            let sources: Vec<_> = answer
+                .sources
+                .iter()
+                .map(|c| {
+                    serde_json::json!({
+                        "citation": c.citation,
+                        "docid": format!("#{}", c.docid),
+                        "file": c.path,
+                        "lines": format!("{}-{}", c.start_line, c.end_line),
+                        "breadcrumb": c.breadcrumb,
+                        "score": c.score,
+                        "cited": answer.cited.contains(&c.citation)
+                    })
+                })
+                .collect();
+            let output = serde_json::json!({
+                "query": query,
+                "answer": answer.answer,
+                "sources": sources
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        _ => {
+            println!("{}", answer.answer);
+            if !answer.sources.is_empty() {
+                println!();
+                println!("Sources:");
+                for c in &answer.sources {
+                    let marker = if answer.cited.contains(&c.citation) {
+                        ""
+                    } else {
+                        "  (not cited)"
+                    };
+                    println!(
+                        "  [{}] #{} {}:{}-{}{}",
+                        c.citation, c.docid, c.path, c.start_line, c.end_line, marker
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,44 @@
+//! Backlinks command
+
+use crate::app::{BacklinksArgs, OutputFormat};
+use agentroot_core::Database;
+use anyhow::Result;
+
+pub async fn run(args: BacklinksArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    let backlinks = db.get_backlinks(&args.docid)?;
+
+    match format {
+        OutputFormat::Json => {
+            let output: Vec<_> = backlinks
+                .iter()
+                .map(|l| {
+                    serde_json::json!({
+                        "docid": format!("#{}", l.docid),
+                        "file": format!("{}/{}", l.collection, l.path),
+                        "title": l.title,
+                        "link_type": l.link_type,
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        _ => {
+            if backlinks.is_empty() {
+                println!("No documents link to {}", args.docid);
+                if db.count_document_links()? == 0 {
+                    println!("Link graph is empty. Run 'agentroot pagerank' to build it.");
+                }
+                return Ok(());
+            }
+
+            println!("{} documents link to {}:", backlinks.len(), args.docid);
+            for l in &backlinks {
+                println!(
+                    "  #{}  {}/{}  {} ({})",
+                    l.docid, l.collection, l.path, l.title, l.link_type
+                );
+            }
+        }
+    }
+    Ok(())
+}
@@ -10,6 +10,24 @@ pub async fn run(db: &Database) -> Result<()> {
     let vectors = db.cleanup_orphaned_vectors()?;
     println!("Removed {} orphaned vectors", vectors);
 
+    let chunks = db.cleanup_orphaned_chunks()?;
+    println!("Removed chunks of {} deleted documents", chunks);
+
+    let drift = db.repair_search_index()?;
+    if !drift.is_consistent() {
+        println!(
+            "Repaired full-text index: {} missing, {} stale rows",
+            drift.missing, drift.extra
+        );
+    }
+
+    let report = db.optimize_indexes()?;
+    println!(
+        "Optimized {} full-text indexes in {:.0} ms",
+        report.fts_tables.len(),
+        report.duration_ms
+    );
+
     db.vacuum()?;
     println!("Database vacuumed");
 
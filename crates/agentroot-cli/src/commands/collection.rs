@@ -20,8 +20,10 @@ pub async fn run(args: CollectionArgs, db: &Database) -> Result<()> {
                     .to_string()
             });
 
-            let base_path = if provider == "file" {
-                // For file provider, canonicalize the path
+            let local_mail = provider == "email"
+                && !agentroot_core::providers::email::is_imap_url(&path.to_string_lossy());
+            let base_path = if matches!(provider.as_str(), "file" | "obsidian") || local_mail {
+                // For local providers, canonicalize the path
                 path.canonicalize()?.to_string_lossy().to_string()
             } else {
                 // For other providers (GitHub, URL, etc.), use path as-is
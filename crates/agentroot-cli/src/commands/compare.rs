@@ -0,0 +1,54 @@
+//! Compare command
+
+use crate::app::{CompareArgs, OutputFormat};
+use agentroot_core::Database;
+use anyhow::Result;
+
+pub async fn run(args: CompareArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    let cmp = db.compare(&args.a, &args.b)?;
+
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&cmp)?);
+        }
+        _ => {
+            println!(
+                "A  #{}  {}/{}  {}",
+                cmp.a.docid, cmp.a.collection, cmp.a.path, cmp.a.title
+            );
+            println!(
+                "B  #{}  {}/{}  {}",
+                cmp.b.docid, cmp.b.collection, cmp.b.path, cmp.b.title
+            );
+            println!();
+            match cmp.embedding_similarity {
+                Some(sim) => println!("Embedding similarity: {:.2}", sim),
+                None => println!("Embedding similarity: - (not embedded)"),
+            }
+            println!("Text overlap:         {:.2}", cmp.text_overlap);
+            println!("Concept overlap:      {:.2}", cmp.concept_overlap);
+            if !cmp.shared_concepts.is_empty() {
+                println!("Shared concepts:      {}", cmp.shared_concepts.join(", "));
+            }
+
+            if cmp.overlapping_chunks.is_empty() {
+                println!("\nNo overlapping passages");
+                return Ok(());
+            }
+            println!("\nOverlapping passages:");
+            for o in &cmp.overlapping_chunks {
+                println!(
+                    "  {:.2}{}  A:{}-{}  B:{}-{}  {}",
+                    o.similarity,
+                    if o.identical { " =" } else { "  " },
+                    o.a_start_line,
+                    o.a_end_line,
+                    o.b_start_line,
+                    o.b_end_line,
+                    o.preview
+                );
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,51 @@
+//! Dead and dangling link report command
+
+use crate::app::{DeadLinksArgs, OutputFormat};
+use agentroot_core::Database;
+use anyhow::Result;
+
+pub async fn run(args: DeadLinksArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    let dangling = db.find_dangling_links(args.collection.as_deref())?;
+
+    match format {
+        OutputFormat::Json => {
+            let output: Vec<_> = dangling
+                .iter()
+                .map(|l| {
+                    serde_json::json!({
+                        "docid": format!("#{}", l.docid),
+                        "file": format!("{}/{}", l.collection, l.path),
+                        "target": l.target,
+                        "link_type": l.link_type,
+                        "exists_on_disk": l.exists_on_disk,
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        _ => {
+            if dangling.is_empty() {
+                println!("No dead links found");
+                return Ok(());
+            }
+
+            let mut current_file = String::new();
+            for l in &dangling {
+                let file = format!("{}/{}", l.collection, l.path);
+                if file != current_file {
+                    println!("{} #{}", file, l.docid);
+                    current_file = file;
+                }
+                let note = if l.exists_on_disk {
+                    " (exists on disk, not indexed)"
+                } else {
+                    ""
+                };
+                println!("  -> {} [{}]{}", l.target, l.link_type, note);
+            }
+            println!();
+            println!("{} dead links", dangling.len());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,45 @@
+//! Def command
+
+use crate::app::{DefArgs, OutputFormat};
+use agentroot_core::Database;
+use anyhow::Result;
+
+pub async fn run(args: DefArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    let definitions = db.find_definitions(&args.name, args.collection.as_deref(), args.limit)?;
+
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&definitions)?);
+        }
+        _ => {
+            if definitions.is_empty() {
+                println!("No definition found for '{}'", args.name);
+                return Ok(());
+            }
+
+            for d in &definitions {
+                println!(
+                    "{}  {}/{}:{}-{}  #{}{}",
+                    d.name,
+                    d.collection,
+                    d.path,
+                    d.start_line,
+                    d.end_line,
+                    d.docid,
+                    d.chunk_type
+                        .as_deref()
+                        .map(|t| format!(" ({})", t))
+                        .unwrap_or_default()
+                );
+                if args.body {
+                    println!();
+                    for line in d.content.lines() {
+                        println!("    {}", line);
+                    }
+                    println!();
+                }
+            }
+        }
+    }
+    Ok(())
+}
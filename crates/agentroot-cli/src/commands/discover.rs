@@ -0,0 +1,36 @@
+//! Discover command
+
+use crate::app::{DiscoverArgs, OutputFormat};
+use agentroot_core::db::DiscoverOptions;
+use agentroot_core::Database;
+use anyhow::Result;
+
+pub async fn run(args: DiscoverArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    let documents = db.discover(&DiscoverOptions {
+        collection: args.collection,
+        limit: args.limit,
+        never_retrieved: args.never_retrieved,
+        seed: args.seed,
+    })?;
+
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&documents)?);
+        }
+        _ => {
+            if documents.is_empty() {
+                println!("No documents to discover");
+                return Ok(());
+            }
+
+            for d in &documents {
+                let modified = d.modified_at.get(..10).unwrap_or(&d.modified_at);
+                println!(
+                    "#{}  {}  {}/{}  {}",
+                    d.docid, modified, d.collection, d.path, d.title
+                );
+            }
+        }
+    }
+    Ok(())
+}
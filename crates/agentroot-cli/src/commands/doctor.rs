@@ -0,0 +1,32 @@
+//! Doctor command
+
+use crate::app::{DoctorArgs, OutputFormat};
+use agentroot_core::doctor::{diagnose, Severity};
+use agentroot_core::Database;
+use anyhow::Result;
+
+pub async fn run(args: DoctorArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    let findings = diagnose(db, !args.offline).await?;
+
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&findings)?),
+        _ => {
+            for finding in &findings {
+                let mark = match finding.severity {
+                    Severity::Ok => "ok",
+                    Severity::Warning => "warn",
+                    Severity::Error => "FAIL",
+                };
+                println!("[{:>4}] {:<13} {}", mark, finding.check, finding.message);
+                if let Some(fix) = &finding.fix {
+                    println!("       {:<13} fix: {}", "", fix);
+                }
+            }
+        }
+    }
+
+    if findings.iter().any(|f| f.severity == Severity::Error) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
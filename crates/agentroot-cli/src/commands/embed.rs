@@ -1,30 +1,40 @@
 //! Embed command
 
 use crate::app::EmbedArgs;
-use agentroot_core::index::{embed_documents, EmbedProgress};
-use agentroot_core::{Database, Embedder, HttpEmbedder};
+use crate::progress::CliProgress;
+use agentroot_core::db::Quantization;
+use agentroot_core::index::{
+    embed_documents, migrate_embeddings, verify_embeddings, MigrationReport, VerifyReport,
+};
+use agentroot_core::{default_embedder, embedder_for_model, Database, Embedder};
 use anyhow::Result;
 use std::sync::Arc;
 
-pub async fn run(args: EmbedArgs, db: &Database) -> Result<()> {
+pub async fn run(mut args: EmbedArgs, db: &Database) -> Result<()> {
     // Run migration to ensure schema is up to date
     db.migrate()?;
 
-    // Get HTTP embedder from environment variables
-    let embedder: Arc<dyn Embedder> = match HttpEmbedder::from_env() {
-        Ok(http_embedder) => {
-            println!(
-                "Using HTTP embedding service: {}",
-                http_embedder.model_name()
-            );
-            println!("Dimensions: {}", http_embedder.dimensions());
-            Arc::new(http_embedder)
-        }
-        Err(_) => {
-            eprintln!("Error: No embedding service configured");
+    // An interrupted migration is resumed first, whatever model is configured
+    let checkpoints = db.embed_checkpoints()?;
+    if args.resume {
+        args.migrate_to = checkpoints
+            .iter()
+            .find_map(|c| c.migration_target())
+            .map(str::to_string);
+    }
+
+    // The embedding service when configured, otherwise the local model
+    let loaded = match &args.migrate_to {
+        Some(model) => embedder_for_model(model).await,
+        None => default_embedder().await,
+    };
+    let embedder: Arc<dyn Embedder> = match loaded {
+        Ok(embedder) => Arc::from(embedder),
+        Err(e) => {
+            eprintln!("Error: Could not load an embedding model: {}", e);
             eprintln!();
-            eprintln!("AgentRoot requires an external embedding service.");
-            eprintln!("Configure one by setting environment variables:");
+            eprintln!("Without an embedding service, agentroot runs a local model downloaded");
+            eprintln!("on first use. To use an external service instead, set:");
             eprintln!();
             eprintln!("  export AGENTROOT_EMBEDDING_URL=\"https://your-service.com/v1\"");
             eprintln!("  export AGENTROOT_EMBEDDING_MODEL=\"intfloat/e5-mistral-7b-instruct\"");
@@ -37,7 +47,7 @@ pub async fn run(args: EmbedArgs, db: &Database) -> Result<()> {
             eprintln!("  - Any OpenAI-compatible API");
             eprintln!();
             eprintln!("See VLLM_SETUP.md for detailed instructions.");
-            return Err(anyhow::anyhow!("No embedding service configured"));
+            return Err(anyhow::anyhow!("No embedding model available"));
         }
     };
 
@@ -47,33 +57,68 @@ pub async fn run(args: EmbedArgs, db: &Database) -> Result<()> {
         embedder.dimensions()
     );
 
-    let model_name = embedder.model_name().to_string();
+    let model_name = match &args.migrate_to {
+        Some(model) => model.clone(),
+        None => embedder.model_name().to_string(),
+    };
+
+    let checkpoint = |key: &str| checkpoints.iter().find(|c| c.key == key);
+    if args.resume && args.migrate_to.is_none() {
+        if checkpoint(&model_name).is_some() {
+            args.force = true;
+        } else {
+            println!("No interrupted run to resume; embedding documents without vectors");
+        }
+    }
 
-    // Run embedding pipeline
+    if let Some(model) = &args.migrate_to {
+        if let Some(done) = checkpoint(&Database::migration_checkpoint_key(model)) {
+            println!(
+                "Resuming interrupted migration to {} ({} documents done)",
+                model, done.documents
+            );
+        }
+        let report =
+            migrate_embeddings(db, embedder.as_ref(), model, &CliProgress::new("Migrating"))
+                .await?;
+        print_migration_report(&report, model);
+    }
+
+    if args.verify {
+        let report = verify_embeddings(
+            db,
+            embedder.as_ref(),
+            &model_name,
+            args.dry_run,
+            &CliProgress::new("Verifying"),
+        )
+        .await?;
+        print_verify_report(&report, args.dry_run);
+        return Ok(());
+    }
+
+    if args.force {
+        if args.restart {
+            db.clear_embed_checkpoint(&model_name)?;
+        } else if let Some(done) = checkpoint(&model_name) {
+            println!(
+                "Resuming interrupted re-embedding run ({} documents done; use --restart to start over)",
+                done.documents
+            );
+        }
+    }
+
+    // Run embedding pipeline; after a migration this only picks up documents
+    // added meanwhile
     let stats = embed_documents(
         db,
         embedder.as_ref(),
         &model_name,
         args.force,
-        Some(Box::new(|progress: EmbedProgress| {
-            let cache_pct = if progress.processed_chunks > 0 {
-                progress.cached_chunks as f64 / progress.processed_chunks as f64 * 100.0
-            } else {
-                0.0
-            };
-            eprint!(
-                "\rProcessing: {}/{} docs, {}/{} chunks ({:.0}% cached)   ",
-                progress.processed_docs,
-                progress.total_docs,
-                progress.processed_chunks,
-                progress.total_chunks,
-                cache_pct
-            );
-        })),
+        &CliProgress::new("Embedding"),
     )
     .await?;
 
-    eprintln!();
     println!("Embedding complete:");
     println!(
         "  Documents: {}/{}",
@@ -87,5 +132,83 @@ pub async fn run(args: EmbedArgs, db: &Database) -> Result<()> {
         println!("  Cache hit rate: {:.1}%", stats.cache_hit_rate());
     }
 
+    // Vectors stored before AGENTROOT_VECTOR_QUANTIZATION changed
+    let quantization = Quantization::from_env();
+    let requantized = db.quantize_stored_embeddings(quantization)?;
+    if requantized > 0 {
+        println!(
+            "  Re-encoded {} vectors as {}",
+            requantized,
+            quantization.as_str()
+        );
+    }
+    if args.drop_full_precision {
+        if quantization == Quantization::None {
+            println!("  Vectors are stored at full precision; no copies to drop");
+        } else {
+            let dropped = db.drop_full_precision_embeddings()?;
+            println!(
+                "  Dropped {} full-precision chunk embeddings (run `agentroot cleanup` to reclaim the space)",
+                dropped
+            );
+        }
+    }
+
+    if args.optimize {
+        match db.optimize_vector_index()? {
+            Some(count) => println!("ANN index built over {} embeddings", count),
+            None => println!(
+                "ANN index not built: vector search scans exactly below 1000 embeddings, \
+                 or when the index would exceed AGENTROOT_VECTOR_MEMORY_MB"
+            ),
+        }
+    }
+
     Ok(())
 }
+
+fn print_migration_report(report: &MigrationReport, model: &str) {
+    println!("Migration to {} complete:", model);
+    if !report.previous_models.is_empty() {
+        println!("  Replaced:  {}", report.previous_models.join(", "));
+    }
+    println!(
+        "  Staged:    {} documents ({} chunks computed, {} cached)",
+        report.staged.embedded_documents,
+        report.staged.computed_chunks,
+        report.staged.cached_chunks
+    );
+    println!(
+        "  Switched:  {} documents",
+        report.switched.embedded_documents
+    );
+    println!(
+        "Set AGENTROOT_EMBEDDING_MODEL={} so queries and new documents use it",
+        model
+    );
+}
+
+fn print_verify_report(report: &VerifyReport, dry_run: bool) {
+    println!("Verified {} documents:", report.checked_documents);
+    println!("  Missing embeddings:  {}", report.missing);
+    println!("  Other model:         {}", report.wrong_model);
+    println!("  Wrong dimensions:    {}", report.bad_dimensions);
+    println!("  Stale chunks:        {}", report.stale);
+    println!("  Orphaned vectors:    {}", report.orphaned_vectors);
+
+    if dry_run {
+        if report.broken_documents() + report.orphaned_vectors > 0 {
+            println!("Run `agentroot embed --verify` to repair");
+        }
+    } else if report.broken_documents() + report.orphaned_vectors == 0 {
+        println!("All embeddings are up to date");
+    } else {
+        println!(
+            "Repaired {} documents ({} chunks, {} cached), removed {} orphaned vectors",
+            report.repaired_documents,
+            report.repaired_chunks,
+            report.cached_chunks,
+            report.orphaned_vectors
+        );
+    }
+}
@@ -0,0 +1,62 @@
+//! Encrypt and decrypt commands
+
+use crate::app::EncryptArgs;
+use agentroot_core::db::encryption::{
+    database_key, decrypt_database, encrypt_database, forget_key, generate_key, store_key,
+    DB_KEY_ENV,
+};
+use anyhow::{bail, Result};
+use std::path::Path;
+
+pub fn run(args: &EncryptArgs, db_path: &Path, read_only: bool) -> Result<()> {
+    if read_only {
+        bail!("Read-only index: writes are disabled (--read-only)");
+    }
+
+    let env_key = std::env::var(DB_KEY_ENV).ok().filter(|k| !k.is_empty());
+    let generated = env_key.is_none();
+    let key = match env_key {
+        Some(key) => key,
+        None => generate_key()?,
+    };
+
+    encrypt_database(db_path, &key)?;
+    println!("Encrypted {}", db_path.display());
+
+    let stored = if args.no_store {
+        false
+    } else {
+        match store_key(db_path, &key) {
+            Ok(()) => {
+                println!("Key saved in the OS credential store");
+                true
+            }
+            Err(e) => {
+                eprintln!("Warning: could not save the key: {}", e);
+                false
+            }
+        }
+    };
+    if generated && !stored {
+        println!("Key: {}", key);
+        println!(
+            "Keep it safe: the index cannot be opened without it. Set {} to use it.",
+            DB_KEY_ENV
+        );
+    }
+    Ok(())
+}
+
+pub fn run_decrypt(db_path: &Path, read_only: bool) -> Result<()> {
+    if read_only {
+        bail!("Read-only index: writes are disabled (--read-only)");
+    }
+
+    let Some(key) = database_key(db_path)? else {
+        bail!("No key for {}; set {}", db_path.display(), DB_KEY_ENV);
+    };
+    decrypt_database(db_path, &key)?;
+    forget_key(db_path)?;
+    println!("Decrypted {}", db_path.display());
+    Ok(())
+}
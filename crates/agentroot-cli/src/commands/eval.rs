@@ -0,0 +1,84 @@
+//! Relevance evaluation command
+
+use crate::app::{EvalArgs, EvalModeArg, OutputFormat};
+use agentroot_core::search::eval::{evaluate, EvalMode, EvalSet, ModeEval};
+use agentroot_core::{default_embedder, Database};
+use anyhow::Result;
+
+pub async fn run(args: EvalArgs, db: &Database, format: OutputFormat, verbose: bool) -> Result<()> {
+    let set = EvalSet::load(&args.file)?;
+    let k = args.k.unwrap_or(set.k);
+    let modes: Vec<EvalMode> = if args.modes.is_empty() {
+        EvalMode::ALL.to_vec()
+    } else {
+        args.modes.iter().map(|m| eval_mode(*m)).collect()
+    };
+
+    let embedder = default_embedder().await.ok();
+    let reports = evaluate(db, &set, &modes, k, embedder.as_deref()).await?;
+
+    match format {
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "file": args.file.display().to_string(),
+                "k": k,
+                "queries": set.queries.len(),
+                "modes": reports,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        _ => print_reports(&reports, k, set.queries.len(), verbose),
+    }
+    Ok(())
+}
+
+fn eval_mode(mode: EvalModeArg) -> EvalMode {
+    match mode {
+        EvalModeArg::Bm25 => EvalMode::Bm25,
+        EvalModeArg::Vector => EvalMode::Vector,
+        EvalModeArg::Hybrid => EvalMode::Hybrid,
+        EvalModeArg::Smart => EvalMode::Smart,
+    }
+}
+
+fn print_reports(reports: &[ModeEval], k: usize, queries: usize, verbose: bool) {
+    println!("{} queries, k = {}", queries, k);
+    println!();
+    println!(
+        "{:<8} {:>9} {:>7} {:>9}",
+        "Mode",
+        format!("Recall@{}", k),
+        "MRR",
+        format!("NDCG@{}", k)
+    );
+    for report in reports {
+        if let Some(reason) = &report.skipped {
+            println!("{:<8} skipped: {}", report.mode.as_str(), reason);
+            continue;
+        }
+        println!(
+            "{:<8} {:>9.3} {:>7.3} {:>9.3}",
+            report.mode.as_str(),
+            report.recall,
+            report.mrr,
+            report.ndcg
+        );
+    }
+
+    if !verbose {
+        return;
+    }
+    for report in reports.iter().filter(|r| r.skipped.is_none()) {
+        println!();
+        println!("{}:", report.mode.as_str());
+        for q in &report.queries {
+            println!(
+                "  {:>5.2} {:>5.2} {:>5.2}  {}",
+                q.recall, q.reciprocal_rank, q.ndcg, q.query
+            );
+            if !q.missing.is_empty() {
+                println!("                     missing: {}", q.missing.join(", "));
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! Export command
+
+use crate::app::{ExportArgs, OutputFormat};
+use crate::output::{local_collection_roots, tags, FormatOptions};
+use agentroot_core::Database;
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+pub async fn run(args: ExportArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    if let Some(output) = args.output.as_deref().filter(|p| is_archive_path(p)) {
+        return export_archive(db, args.collection.as_deref(), output, format);
+    }
+    if let Some(name) = &args.collection {
+        if db.get_collection(name)?.is_none() {
+            anyhow::bail!("Collection not found: {}", name);
+        }
+    }
+    let chunks = db.list_chunk_tags(args.collection.as_deref())?;
+    let options = FormatOptions {
+        full: false,
+        query: None,
+        line_numbers: false,
+        collection_roots: local_collection_roots(db)?,
+    };
+
+    let (content, default_path) = match format {
+        OutputFormat::Cli | OutputFormat::Ctags => (
+            tags::format_ctags(tags::from_chunks(&chunks, &options)),
+            "tags",
+        ),
+        OutputFormat::Etags => (
+            tags::format_etags(&tags::from_chunks(&chunks, &options)),
+            "TAGS",
+        ),
+        OutputFormat::Json => (serde_json::to_string_pretty(&chunks)? + "\n", "tags.json"),
+        _ => anyhow::bail!("export supports --format ctags, etags or json"),
+    };
+
+    let output = args.output.unwrap_or_else(|| PathBuf::from(default_path));
+    if output.as_os_str() == "-" {
+        print!("{}", content);
+    } else {
+        std::fs::write(&output, content)?;
+        eprintln!(
+            "Exported {} definitions to {}",
+            chunks.len(),
+            output.display()
+        );
+    }
+    Ok(())
+}
+
+fn is_archive_path(path: &Path) -> bool {
+    path.to_string_lossy().ends_with(".tar.zst")
+}
+
+fn export_archive(
+    db: &Database,
+    collection: Option<&str>,
+    output: &Path,
+    format: OutputFormat,
+) -> Result<()> {
+    let manifest = db.export_archive(collection, output)?;
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&manifest)?),
+        _ => eprintln!(
+            "Exported {} documents, {} chunks and {} embeddings from {} to {}",
+            manifest.documents,
+            manifest.chunks,
+            manifest.embeddings,
+            manifest.collections.join(", "),
+            output.display()
+        ),
+    }
+    Ok(())
+}
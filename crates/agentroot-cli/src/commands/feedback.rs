@@ -0,0 +1,77 @@
+//! Relevance feedback command
+
+use crate::app::{FeedbackAction, FeedbackArgs, OutputFormat};
+use agentroot_core::db::FeedbackEntry;
+use agentroot_core::Database;
+use anyhow::Result;
+
+pub async fn run(args: FeedbackArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    match args.action.unwrap_or(FeedbackAction::List { limit: 20 }) {
+        FeedbackAction::Good {
+            docid,
+            query,
+            session,
+        } => record(db, &query, &docid, true, session.as_deref(), format),
+        FeedbackAction::Bad {
+            docid,
+            query,
+            session,
+        } => record(db, &query, &docid, false, session.as_deref(), format),
+        FeedbackAction::List { limit } => {
+            let entries = db.list_feedback(limit)?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&entries)?),
+                _ => {
+                    if entries.is_empty() {
+                        println!("No feedback recorded");
+                    }
+                    for e in &entries {
+                        print_entry(e);
+                    }
+                }
+            }
+            Ok(())
+        }
+        FeedbackAction::Clear => {
+            let removed = db.clear_feedback()?;
+            if format != OutputFormat::Json {
+                println!("Removed {} judgments", removed);
+            }
+            Ok(())
+        }
+    }
+}
+
+fn record(
+    db: &Database,
+    query: &str,
+    docid: &str,
+    relevant: bool,
+    session: Option<&str>,
+    format: OutputFormat,
+) -> Result<()> {
+    let session_id = match session {
+        Some(s) => Some(
+            db.find_session(s)?
+                .ok_or_else(|| anyhow::anyhow!("Session not found: {}", s))?
+                .id,
+        ),
+        None => None,
+    };
+    let entry = db.record_feedback(query, docid, relevant, session_id.as_deref())?;
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&entry)?),
+        _ => print_entry(&entry),
+    }
+    Ok(())
+}
+
+fn print_entry(e: &FeedbackEntry) {
+    println!(
+        "{}  {}/{}  \"{}\"",
+        if e.relevant { "+" } else { "-" },
+        e.collection,
+        e.path,
+        e.query
+    );
+}
@@ -1,11 +1,31 @@
 //! Get document command
 
 use crate::app::{GetArgs, MultiGetArgs, OutputFormat};
+use agentroot_core::db::MultiGetOptions;
+use agentroot_core::search::{find_match_ranges, MatchRange};
 use agentroot_core::Database;
 use anyhow::Result;
 
 pub async fn run(args: GetArgs, db: &Database, format: OutputFormat) -> Result<()> {
-    let content = db.get_document(&args.file)?;
+    if args.history {
+        return print_history(&args, db, format);
+    }
+    if let Some(from) = &args.diff {
+        let diff = db.diff_document_versions(&args.file, from, args.version.as_deref())?;
+        match format {
+            OutputFormat::Json => {
+                let output = serde_json::json!({ "file": args.file, "diff": diff });
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            }
+            _ => print!("{}", diff),
+        }
+        return Ok(());
+    }
+
+    let content = match &args.version {
+        Some(version) => db.get_document_version(&args.file, version)?.1,
+        None => db.get_document(&args.file)?,
+    };
 
     let lines: Vec<&str> = content.lines().collect();
     let start = args.from.unwrap_or(1).saturating_sub(1);
@@ -18,22 +38,47 @@ pub async fn run(args: GetArgs, db: &Database, format: OutputFormat) -> Result<(
         .copied()
         .collect();
 
+    let highlight = highlight_query(&args, db)?;
+    let matches: Vec<MatchRange> = highlight
+        .as_deref()
+        .map(|query| find_match_ranges(&content, query))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|m| m.line > start && m.line <= start + selected.len())
+        .collect();
+
     match format {
         OutputFormat::Json => {
-            let output = serde_json::json!({
+            let mut output = serde_json::json!({
                 "file": args.file,
                 "content": selected.join("\n"),
                 "start_line": start + 1,
                 "line_count": selected.len()
             });
+            if let Some(version) = &args.version {
+                output["version"] = serde_json::json!(version);
+            }
+            if let Some(query) = &highlight {
+                output["highlight"] = serde_json::json!(query);
+                output["matches"] = serde_json::to_value(&matches)?;
+            }
             println!("{}", serde_json::to_string_pretty(&output)?);
         }
         _ => {
             for (i, line) in selected.iter().enumerate() {
+                let line_no = start + i + 1;
+                let marker = match (
+                    highlight.is_some(),
+                    matches.iter().any(|m| m.line == line_no),
+                ) {
+                    (false, _) => "",
+                    (true, true) => "> ",
+                    (true, false) => "  ",
+                };
                 if args.line_numbers {
-                    println!("{:>4} {}", start + i + 1, line);
+                    println!("{}{:>4} {}", marker, line_no, line);
                 } else {
-                    println!("{}", line);
+                    println!("{}{}", marker, line);
                 }
             }
         }
@@ -41,32 +86,57 @@ pub async fn run(args: GetArgs, db: &Database, format: OutputFormat) -> Result<(
     Ok(())
 }
 
+fn print_history(args: &GetArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    let history = db.document_history(&args.file)?;
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&history)?),
+        _ => {
+            println!("{}/{}", history.collection, history.path);
+            for v in history.versions.iter().rev() {
+                println!(
+                    "  v{:<3} #{}  {}  {}{}",
+                    v.version,
+                    v.docid,
+                    v.recorded_at,
+                    v.title,
+                    if v.current { "  (current)" } else { "" }
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Query to highlight: the explicit one, else the session's latest search
+fn highlight_query(args: &GetArgs, db: &Database) -> Result<Option<String>> {
+    if let Some(query) = &args.highlight {
+        return Ok(Some(query.clone()));
+    }
+    let Some(session) = &args.session else {
+        return Ok(None);
+    };
+    let Some(info) = db.find_session(session)? else {
+        return Ok(None);
+    };
+    Ok(db.get_session_queries(&info.id)?.pop().map(|q| q.query))
+}
+
 pub async fn run_multi(args: MultiGetArgs, db: &Database, format: OutputFormat) -> Result<()> {
-    let docs = db.get_documents_by_pattern(&args.pattern)?;
+    let options = MultiGetOptions {
+        max_tokens: args.max_tokens,
+        max_bytes: args.max_bytes,
+        max_lines: args.l,
+    };
+    let result = db.multi_get(&args.pattern, &options)?;
 
     match format {
         OutputFormat::Json => {
-            let output: Vec<_> = docs
-                .into_iter()
-                .map(|d| {
-                    serde_json::json!({
-                        "path": d.path,
-                        "content": d.content,
-                    })
-                })
-                .collect();
-            println!("{}", serde_json::to_string_pretty(&output)?);
+            println!("{}", serde_json::to_string_pretty(&result)?);
         }
         _ => {
-            for doc in docs {
-                if doc.content.len() > args.max_bytes {
-                    eprintln!("Skipping {} (exceeds max-bytes)", doc.path);
-                    continue;
-                }
+            for doc in &result.documents {
                 println!("--- {} ---", doc.path);
-                let lines: Vec<&str> = doc.content.lines().collect();
-                let limit = args.l.unwrap_or(lines.len());
-                for (i, line) in lines.iter().take(limit).enumerate() {
+                for (i, line) in doc.content.lines().enumerate() {
                     if args.line_numbers {
                         println!("{:>4} {}", i + 1, line);
                     } else {
@@ -74,6 +144,9 @@ pub async fn run_multi(args: MultiGetArgs, db: &Database, format: OutputFormat)
                     }
                 }
             }
+            for path in &result.omitted {
+                eprintln!("Skipping {} (exceeds budget)", path);
+            }
         }
     }
     Ok(())
@@ -0,0 +1,203 @@
+//! Glossary command
+
+use crate::app::{GlossaryAction, GlossaryArgs, OutputFormat};
+use agentroot_core::db::ConceptInfo;
+use agentroot_core::Database;
+use anyhow::Result;
+
+pub async fn run(args: GlossaryArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    match args.action.unwrap_or(GlossaryAction::List { limit: 50 }) {
+        GlossaryAction::List { limit } => run_list(db, limit, format),
+        GlossaryAction::Show { concept, limit } => run_show(db, &concept, limit, format),
+        GlossaryAction::Browse { concept, limit } => match concept {
+            Some(concept) => run_browse_concept(db, &concept, limit, format),
+            None => run_browse(db, limit, format),
+        },
+        GlossaryAction::Add { term } => {
+            let (concept, linked) = db.add_concept(&term)?;
+            print_concept_change(format, &concept, || {
+                println!(
+                    "Added concept {} '{}' ({} new chunk links, {} total)",
+                    concept.id, concept.term, linked, concept.chunk_count
+                );
+            })
+        }
+        GlossaryAction::Merge { from, into } => {
+            let from = find(db, &from)?;
+            let into = find(db, &into)?;
+            let moved = db.merge_concepts(from.id, into.id)?;
+            let merged = find(db, &into.id.to_string())?;
+            print_concept_change(format, &merged, || {
+                println!(
+                    "Merged '{}' into '{}' ({} chunk links moved, {} total)",
+                    from.term, merged.term, moved, merged.chunk_count
+                );
+            })
+        }
+        GlossaryAction::Delete { concept } => {
+            let concept = find(db, &concept)?;
+            db.delete_concept(concept.id)?;
+            print_concept_change(format, &concept, || {
+                println!("Deleted concept {} '{}'", concept.id, concept.term);
+            })
+        }
+    }
+}
+
+fn find(db: &Database, concept: &str) -> Result<ConceptInfo> {
+    db.find_concept(concept)?
+        .ok_or_else(|| anyhow::anyhow!("Concept not found: {}", concept))
+}
+
+fn print_concept_change(
+    format: OutputFormat,
+    concept: &ConceptInfo,
+    text: impl FnOnce(),
+) -> Result<()> {
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(concept)?),
+        _ => text(),
+    }
+    Ok(())
+}
+
+fn run_list(db: &Database, limit: usize, format: OutputFormat) -> Result<()> {
+    let concepts = db.top_concepts(limit)?;
+
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&concepts)?);
+        }
+        _ => {
+            let (total, links) = db.get_concept_stats()?;
+            if total == 0 {
+                println!("No concepts yet. They are extracted during 'agentroot update' with an LLM service, or added with 'agentroot glossary add'.");
+                return Ok(());
+            }
+            println!("{} concepts, {} chunk links", total, links);
+            println!();
+            for c in &concepts {
+                println!("{:>5}  {:>5} chunks  {}", c.id, c.chunk_count, c.term);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_show(db: &Database, concept: &str, limit: usize, format: OutputFormat) -> Result<()> {
+    let concept = find(db, concept)?;
+    let mut chunks = db.get_chunks_for_concept(concept.id)?;
+    chunks.truncate(limit);
+
+    match format {
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "concept": concept,
+                "chunks": chunks,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        _ => {
+            println!(
+                "{} '{}' ({} chunks)",
+                concept.id, concept.term, concept.chunk_count
+            );
+            for c in &chunks {
+                println!();
+                println!("  {}  {}", c.document_path, c.document_title);
+                if !c.snippet.is_empty() {
+                    println!("    {}", c.snippet.replace('\n', " ").trim());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Related concepts listed under each top concept
+const BROWSE_RELATED: usize = 5;
+
+fn run_browse(db: &Database, limit: usize, format: OutputFormat) -> Result<()> {
+    let concepts = db.top_concepts(limit)?;
+    let mut entries = Vec::new();
+    for concept in concepts {
+        let related = db.get_cooccurring_concepts(concept.id, BROWSE_RELATED)?;
+        entries.push((concept, related));
+    }
+
+    match format {
+        OutputFormat::Json => {
+            let output: Vec<_> = entries
+                .iter()
+                .map(|(concept, related)| {
+                    serde_json::json!({
+                        "concept": concept,
+                        "related": related,
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        _ => {
+            if entries.is_empty() {
+                println!("No concepts yet. They are extracted during 'agentroot update' with an LLM service, or added with 'agentroot glossary add'.");
+                return Ok(());
+            }
+            for (concept, related) in &entries {
+                println!(
+                    "{:>5}  {:>5} chunks  {}",
+                    concept.id, concept.chunk_count, concept.term
+                );
+                if !related.is_empty() {
+                    let terms: Vec<_> = related.iter().map(|r| r.term.as_str()).collect();
+                    println!("                     with: {}", terms.join(", "));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_browse_concept(
+    db: &Database,
+    concept: &str,
+    limit: usize,
+    format: OutputFormat,
+) -> Result<()> {
+    let concept = find(db, concept)?;
+    let documents = db.get_documents_for_concept(concept.id, limit)?;
+    let related = db.get_cooccurring_concepts(concept.id, limit)?;
+
+    match format {
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "concept": concept,
+                "documents": documents,
+                "related": related,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        _ => {
+            println!(
+                "{} '{}' ({} chunks)",
+                concept.id, concept.term, concept.chunk_count
+            );
+            println!();
+            println!("Documents:");
+            for d in &documents {
+                println!(
+                    "  #{}  {}/{}  {} ({} chunks)",
+                    d.docid, d.collection, d.path, d.title, d.chunk_count
+                );
+            }
+            if !related.is_empty() {
+                println!();
+                println!("Co-occurring concepts:");
+                for r in &related {
+                    println!("  {:>5}  {:>5} shared  {}", r.id, r.shared_chunks, r.term);
+                }
+            }
+        }
+    }
+    Ok(())
+}
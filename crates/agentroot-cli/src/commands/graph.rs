@@ -0,0 +1,67 @@
+//! Link graph export command
+
+use crate::app::{GraphArgs, OutputFormat};
+use agentroot_core::db::GraphNeighborhood;
+use agentroot_core::Database;
+use anyhow::Result;
+
+pub async fn run(args: GraphArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    let hood = db.graph_neighborhood(&args.docid, args.depth, &args.edge_types)?;
+
+    if args.dot {
+        print!("{}", to_dot(&hood));
+        return Ok(());
+    }
+
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&hood)?);
+        }
+        _ => {
+            println!(
+                "{} nodes, {} edges{}",
+                hood.nodes.len(),
+                hood.edges.len(),
+                if hood.truncated { " (truncated)" } else { "" }
+            );
+            println!();
+            for n in &hood.nodes {
+                println!(
+                    "{}#{}  {}/{}  {} ({:.2})",
+                    "  ".repeat(n.depth),
+                    n.docid,
+                    n.collection,
+                    n.path,
+                    n.title,
+                    n.importance
+                );
+            }
+            if db.count_document_links()? == 0 {
+                println!();
+                println!("Link graph is empty. Run 'agentroot pagerank' to build it.");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn to_dot(hood: &GraphNeighborhood) -> String {
+    let mut out = String::from("digraph agentroot {\n");
+    for n in &hood.nodes {
+        let shape = if n.id == hood.root { "box" } else { "ellipse" };
+        out.push_str(&format!(
+            "  n{} [label=\"{}\", shape={}];\n",
+            n.id,
+            n.path.replace('"', "\\\""),
+            shape
+        ));
+    }
+    for e in &hood.edges {
+        out.push_str(&format!(
+            "  n{} -> n{} [label=\"{}\"];\n",
+            e.source, e.target, e.link_type
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
@@ -0,0 +1,73 @@
+//! Grep command
+
+use crate::app::{GrepArgs, OutputFormat};
+use agentroot_core::db::RegexSearchOptions;
+use agentroot_core::Database;
+use anyhow::Result;
+
+pub async fn run(args: GrepArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    let options = RegexSearchOptions {
+        collection: args.collection.clone(),
+        path_glob: args.glob.clone(),
+        ignore_case: args.ignore_case,
+        context: args.context,
+        limit: args.limit,
+    };
+    let found = db.regex_search(&args.pattern, &options)?;
+
+    if format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&found)?);
+        return Ok(());
+    }
+
+    if args.files_with_matches {
+        let mut last = None;
+        for m in &found {
+            let file = format!("{}/{}", m.collection, m.path);
+            if last.as_ref() != Some(&file) {
+                println!("{}", file);
+                last = Some(file);
+            }
+        }
+        return Ok(());
+    }
+
+    // ripgrep layout: `file:line:column:text` for matches, `file-line-text`
+    // for context, `--` between separate groups
+    let mut printed: Option<(String, usize)> = None;
+    for (i, m) in found.iter().enumerate() {
+        let file = format!("{}/{}", m.collection, m.path);
+        let last_line = printed
+            .as_ref()
+            .filter(|(f, _)| *f == file)
+            .map(|(_, l)| *l);
+        let first = m.line - m.before.len();
+        if args.context > 0 && printed.is_some() && last_line.is_none_or(|l| first > l + 1) {
+            println!("--");
+        }
+        for (k, text) in m.before.iter().enumerate() {
+            let n = first + k;
+            if last_line.is_none_or(|l| n > l) {
+                println!("{}-{}-{}", file, n, text);
+            }
+        }
+        println!("{}:{}:{}:{}", file, m.line, m.column, m.text);
+
+        // Context running into the next match is printed with that match
+        let next_line = found
+            .get(i + 1)
+            .filter(|n| n.collection == m.collection && n.path == m.path)
+            .map(|n| n.line);
+        let mut last = m.line;
+        for (k, text) in m.after.iter().enumerate() {
+            let n = m.line + 1 + k;
+            if next_line.is_some_and(|next| n >= next) {
+                break;
+            }
+            println!("{}-{}-{}", file, n, text);
+            last = n;
+        }
+        printed = Some((file, last));
+    }
+    Ok(())
+}
@@ -0,0 +1,36 @@
+//! Import command
+
+use crate::app::{ImportArgs, OutputFormat};
+use agentroot_core::db::ImportOptions;
+use agentroot_core::Database;
+use anyhow::Result;
+
+pub async fn run(args: ImportArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    let path = match &args.path {
+        Some(path) => Some(path.canonicalize()?.to_string_lossy().to_string()),
+        None => None,
+    };
+    let options = ImportOptions {
+        rename: args.rename,
+        path,
+        replace: args.replace,
+    };
+    let stats = db.import_archive(&args.archive, &options)?;
+
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&stats)?),
+        _ => {
+            println!(
+                "Imported {} documents, {} chunks and {} embeddings into {}",
+                stats.documents,
+                stats.chunks,
+                stats.embeddings,
+                stats.collections.join(", ")
+            );
+            if args.path.is_none() {
+                println!("Set the source directory with --path before running 'agentroot update'.");
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,30 @@
+//! Init command
+
+use crate::app::InitArgs;
+use agentroot_core::project::{init_project, PROJECT_DB_NAME};
+use agentroot_core::Database;
+use anyhow::{bail, Result};
+
+pub async fn run(args: &InitArgs, read_only: bool) -> Result<()> {
+    if read_only {
+        bail!("Read-only index: writes are disabled (--read-only)");
+    }
+    if !args.path.is_dir() {
+        bail!("Not a directory: {}", args.path.display());
+    }
+
+    let root = args.path.canonicalize()?;
+    let dir = init_project(&root)?;
+    let db = Database::open(dir.join(PROJECT_DB_NAME))?;
+    db.initialize()?;
+
+    println!("Initialized project index in {}", dir.display());
+    if std::env::var_os("AGENTROOT_DB").is_some() {
+        eprintln!("Warning: AGENTROOT_DB is set and overrides the project index");
+    }
+    println!(
+        "Add collections from anywhere in {} with `agentroot collection add <PATH>`",
+        root.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,114 @@
+//! Background job queue command
+
+use crate::app::{JobKindArg, JobStatusArg, JobsAction, JobsArgs, OutputFormat};
+use agentroot_core::db::{Job, JobKind, JobStatus};
+use agentroot_core::Database;
+use anyhow::Result;
+
+pub async fn run(args: JobsArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    match args.action.unwrap_or(JobsAction::List {
+        status: None,
+        limit: 20,
+    }) {
+        JobsAction::List { status, limit } => {
+            let jobs = db.list_jobs(status.map(job_status), limit)?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&jobs)?),
+                _ => {
+                    if jobs.is_empty() {
+                        println!("No jobs");
+                    }
+                    for job in &jobs {
+                        print_job(job);
+                    }
+                }
+            }
+            Ok(())
+        }
+        JobsAction::Add {
+            kind,
+            collection,
+            force,
+            priority,
+        } => {
+            let kind = match kind {
+                JobKindArg::Reindex => JobKind::Reindex { collection },
+                JobKindArg::Embed => JobKind::Embed { force },
+                JobKindArg::Metadata => JobKind::MetadataBackfill { collection },
+                JobKindArg::Pagerank => JobKind::Pagerank,
+            };
+            let id = db.enqueue_job(&kind, priority)?;
+            match format {
+                OutputFormat::Json => {
+                    println!("{}", serde_json::to_string_pretty(&db.get_job(id)?)?)
+                }
+                _ => println!("Queued job {} ({})", id, kind.name()),
+            }
+            Ok(())
+        }
+        JobsAction::Run { max } => {
+            let finished = agentroot_core::jobs::drain_jobs(db, max).await?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&finished)?),
+                _ => {
+                    if finished.is_empty() {
+                        println!("No runnable jobs");
+                    }
+                    for job in &finished {
+                        print_job(job);
+                    }
+                }
+            }
+            Ok(())
+        }
+        JobsAction::Cancel { id } => {
+            if !db.cancel_job(id)? {
+                anyhow::bail!("Job {} is not queued", id);
+            }
+            if format != OutputFormat::Json {
+                println!("Cancelled job {}", id);
+            }
+            Ok(())
+        }
+    }
+}
+
+fn job_status(status: JobStatusArg) -> JobStatus {
+    match status {
+        JobStatusArg::Queued => JobStatus::Queued,
+        JobStatusArg::Running => JobStatus::Running,
+        JobStatusArg::Succeeded => JobStatus::Succeeded,
+        JobStatusArg::Failed => JobStatus::Failed,
+        JobStatusArg::Cancelled => JobStatus::Cancelled,
+    }
+}
+
+fn print_job(job: &Job) {
+    let target = match &job.kind {
+        JobKind::Reindex { collection } | JobKind::MetadataBackfill { collection } => collection
+            .as_deref()
+            .unwrap_or("all collections")
+            .to_string(),
+        JobKind::Embed { force: true } => "all documents".to_string(),
+        JobKind::Embed { force: false } => "new documents".to_string(),
+        JobKind::Pagerank => "link graph".to_string(),
+    };
+    println!(
+        "#{:<5} {:<10} {:<18} {:<16} attempts {}/{}  priority {}",
+        job.id,
+        job.status.as_str(),
+        job.kind.name(),
+        target,
+        job.attempts,
+        job.max_attempts,
+        job.priority
+    );
+    match (&job.result, &job.last_error) {
+        (Some(result), _) if job.status == JobStatus::Succeeded => println!("       {}", result),
+        (_, Some(error)) => println!("       error: {}", error),
+        _ => {}
+    }
+    if job.status == JobStatus::Queued && job.attempts > 0 {
+        println!("       retry after {}", job.run_after);
+    }
+}
@@ -0,0 +1,104 @@
+//! Journal command
+
+use crate::app::{JournalArgs, OutputFormat};
+use agentroot_core::{journal_search, parse_date_phrase, Database, DateRange, SearchOptions};
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+
+fn parse_day(value: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .with_context(|| format!("Invalid date '{}', expected YYYY-MM-DD", value))
+}
+
+pub async fn run(args: JournalArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    let today = chrono::Local::now().date_naive();
+    let (terms, phrase) = parse_date_phrase(&args.query.join(" "), today);
+    let from = args.from.as_deref().map(parse_day).transpose()?;
+    let to = args.to.as_deref().map(parse_day).transpose()?;
+
+    // Explicit bounds override the date phrase
+    let range = match (from, to, phrase) {
+        (None, None, phrase) => phrase,
+        // Open ends stay four-digit years, as dates compare as text
+        (from, to, phrase) => Some(DateRange {
+            from: from
+                .or(phrase.map(|r| r.from))
+                .unwrap_or(NaiveDate::from_ymd_opt(1, 1, 1).unwrap()),
+            to: to
+                .or(phrase.map(|r| r.to))
+                .unwrap_or(NaiveDate::from_ymd_opt(9999, 12, 31).unwrap()),
+        }),
+    };
+
+    if terms.trim().is_empty() {
+        let entries = db.journal_entries(
+            range.map(|r| r.from),
+            range.map(|r| r.to),
+            args.collection.as_deref(),
+            args.limit,
+        )?;
+        match format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&entries)?),
+            _ => {
+                if entries.is_empty() {
+                    println!("No dated notes");
+                }
+                for e in &entries {
+                    println!(
+                        "{}  #{}  {}/{}  {}",
+                        e.date, e.docid, e.collection, e.path, e.title
+                    );
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    let options = SearchOptions {
+        limit: args.limit,
+        collection: args.collection.clone(),
+        ..Default::default()
+    };
+    let results = journal_search(db, &terms, range, &options)?;
+    let hashes: Vec<&str> = results.iter().map(|r| r.hash.as_str()).collect();
+    let dates = db.document_dates(&hashes)?;
+
+    match format {
+        OutputFormat::Json => {
+            let notes: Vec<_> = results
+                .iter()
+                .map(|r| {
+                    serde_json::json!({
+                        "date": dates.get(&r.hash).map(|d| d.to_string()),
+                        "docid": r.docid,
+                        "collection": r.collection_name,
+                        "path": r.display_path,
+                        "title": r.title,
+                        "score": r.score,
+                        "snippet": r.context,
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&notes)?);
+        }
+        _ => {
+            if results.is_empty() {
+                println!("No dated notes match '{}'", terms);
+            }
+            for r in &results {
+                let date = dates.get(&r.hash).map(|d| d.to_string());
+                println!(
+                    "{}  #{}  {}  {}",
+                    date.as_deref().unwrap_or("----------"),
+                    r.docid,
+                    r.display_path,
+                    r.title
+                );
+                if let Some(snippet) = &r.context {
+                    println!("    {}", snippet.replace('\n', " ").trim());
+                }
+            }
+        }
+    }
+    Ok(())
+}
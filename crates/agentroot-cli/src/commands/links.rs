@@ -0,0 +1,73 @@
+//! Links command
+
+use crate::app::{LinksArgs, OutputFormat};
+use agentroot_core::db::LinkedDocument;
+use agentroot_core::Database;
+use anyhow::Result;
+
+pub async fn run(args: LinksArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    let outlinks = if args.incoming {
+        Vec::new()
+    } else {
+        db.get_outlinks(&args.docid)?
+    };
+    let backlinks = if args.outgoing {
+        Vec::new()
+    } else {
+        db.get_backlinks(&args.docid)?
+    };
+
+    match format {
+        OutputFormat::Json => {
+            let mut output = serde_json::json!({ "docid": args.docid });
+            if !args.incoming {
+                output["outgoing"] = links_json(&outlinks);
+            }
+            if !args.outgoing {
+                output["incoming"] = links_json(&backlinks);
+            }
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        _ => {
+            if outlinks.is_empty() && backlinks.is_empty() {
+                println!("No links to or from {}", args.docid);
+                if db.count_document_links()? == 0 {
+                    println!("Link graph is empty. Run 'agentroot pagerank' to build it.");
+                }
+                return Ok(());
+            }
+
+            if !args.incoming {
+                print_links(&format!("{} links to", args.docid), &outlinks);
+            }
+            if !args.outgoing {
+                print_links(&format!("Linking to {}", args.docid), &backlinks);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_links(heading: &str, links: &[LinkedDocument]) {
+    println!("{} ({}):", heading, links.len());
+    for l in links {
+        println!(
+            "  #{}  {}/{}  {} ({})",
+            l.docid, l.collection, l.path, l.title, l.link_type
+        );
+    }
+}
+
+fn links_json(links: &[LinkedDocument]) -> serde_json::Value {
+    links
+        .iter()
+        .map(|l| {
+            serde_json::json!({
+                "docid": format!("#{}", l.docid),
+                "file": format!("{}/{}", l.collection, l.path),
+                "title": l.title,
+                "link_type": l.link_type,
+            })
+        })
+        .collect()
+}
@@ -177,6 +177,7 @@ fn fetch_metadata_for_hash(
     Ok(None)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn run_add(
     db: &Database,
     docid: &str,
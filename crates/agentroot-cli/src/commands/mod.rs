@@ -1,13 +1,41 @@
 //! CLI command handlers
 
+pub mod answer;
+pub mod backlinks;
 pub mod cleanup;
 pub mod collection;
+pub mod compare;
 pub mod context;
+pub mod dead_links;
+pub mod def;
+pub mod discover;
+pub mod doctor;
 pub mod embed;
+pub mod encrypt;
+pub mod eval;
+pub mod export;
+pub mod feedback;
 pub mod get;
+pub mod glossary;
+pub mod graph;
+pub mod grep;
+pub mod import;
+pub mod init;
+pub mod jobs;
+pub mod journal;
+pub mod links;
 pub mod ls;
 pub mod metadata;
 pub mod pagerank;
+pub mod pin;
+pub mod refs;
+pub mod related;
+pub mod repl;
+pub mod rm;
 pub mod search;
+pub mod session;
+pub mod similar;
 pub mod status;
+pub mod topics;
 pub mod update;
+pub mod watch;
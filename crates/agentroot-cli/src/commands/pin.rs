@@ -0,0 +1,64 @@
+//! Document pin command
+
+use crate::app::{OutputFormat, PinAction, PinArgs};
+use agentroot_core::db::PinnedDocument;
+use agentroot_core::Database;
+use anyhow::Result;
+
+pub async fn run(args: PinArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    match args.action.unwrap_or(PinAction::List { collection: None }) {
+        PinAction::Add {
+            docid,
+            queries,
+            boost,
+        } => {
+            let pin = db.pin_document(&docid, &queries, boost)?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&pin)?),
+                _ => {
+                    print!("Pinned ");
+                    print_pin(&pin);
+                }
+            }
+        }
+        PinAction::Remove { docid } => {
+            let removed = db.unpin_document(&docid)?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::json!({ "removed": removed })),
+                _ if removed => println!("Unpinned {}", docid),
+                _ => println!("{} was not pinned", docid),
+            }
+        }
+        PinAction::List { collection } => {
+            let pins = db.list_pins(collection.as_deref())?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&pins)?),
+                _ => {
+                    if pins.is_empty() {
+                        println!("No pinned documents");
+                    }
+                    for pin in &pins {
+                        print_pin(pin);
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_pin(pin: &PinnedDocument) {
+    let effect = match pin.boost {
+        Some(boost) => format!("boost x{}", boost),
+        None => "first".to_string(),
+    };
+    let scope = if pin.queries.is_empty() {
+        "all queries".to_string()
+    } else {
+        format!("queries: {}", pin.queries.join(", "))
+    };
+    println!(
+        "#{}  {}/{}  ({}, {})",
+        pin.docid, pin.collection, pin.path, effect, scope
+    );
+}
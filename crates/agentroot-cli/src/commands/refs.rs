@@ -0,0 +1,38 @@
+//! Refs command
+
+use crate::app::{OutputFormat, RefKindArg, RefsArgs};
+use agentroot_core::{Database, ReferenceKind};
+use anyhow::Result;
+
+pub async fn run(args: RefsArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    let kind = args.kind.map(|k| match k {
+        RefKindArg::Call => ReferenceKind::Call,
+        RefKindArg::Type => ReferenceKind::Type,
+    });
+    let usages = db.find_references(&args.name, kind, args.collection.as_deref(), args.limit)?;
+
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&usages)?);
+        }
+        _ => {
+            if usages.is_empty() {
+                println!("No references to '{}'", args.name);
+                return Ok(());
+            }
+
+            for u in &usages {
+                println!(
+                    "{}/{}:{}  {}  [{}]  {}",
+                    u.collection,
+                    u.path,
+                    u.line,
+                    u.caller.as_deref().unwrap_or("-"),
+                    u.kind.as_str(),
+                    u.line_text
+                );
+            }
+        }
+    }
+    Ok(())
+}
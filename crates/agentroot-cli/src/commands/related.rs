@@ -0,0 +1,47 @@
+//! Related documents command
+
+use crate::app::{OutputFormat, RelatedArgs};
+use agentroot_core::Database;
+use anyhow::Result;
+
+pub async fn run(args: RelatedArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    let related = db.related(&args.docid, args.limit)?;
+
+    match format {
+        OutputFormat::Json => {
+            let output: Vec<_> = related
+                .iter()
+                .map(|r| {
+                    serde_json::json!({
+                        "docid": format!("#{}", r.docid),
+                        "file": format!("{}/{}", r.collection, r.path),
+                        "title": r.title,
+                        "score": r.score,
+                        "link_score": r.link_score,
+                        "concept_score": r.concept_score,
+                        "embedding_score": r.embedding_score,
+                        "shared_concepts": r.shared_concepts,
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        _ => {
+            if related.is_empty() {
+                println!("No related documents found for {}", args.docid);
+                return Ok(());
+            }
+
+            for r in &related {
+                println!(
+                    "{:.2}  #{}  {}/{}  {}",
+                    r.score, r.docid, r.collection, r.path, r.title
+                );
+                if !r.shared_concepts.is_empty() {
+                    println!("      concepts: {}", r.shared_concepts.join(", "));
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,329 @@
+//! Interactive search prompt
+
+use crate::app::{ReplArgs, SearchMode};
+use crate::commands::search::{load_query_expander, load_reranker};
+use crate::output::{local_collection_roots, FormatOptions};
+use agentroot_core::config::editor::editor_command;
+use agentroot_core::search::{hybrid_search, session_aware};
+use agentroot_core::{
+    default_embedder, smart_search, Database, DetailLevel, Embedder, QueryExpander, Reranker,
+    SearchOptions, SearchResult,
+};
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+use std::path::Path;
+
+/// Query history, kept next to the index it was typed against
+const HISTORY_FILE: &str = "repl_history";
+
+const HELP: &str = "\
+Type a query to search. Commands:
+  show N        print result N
+  open N        open result N in $VISUAL or $EDITOR
+  mode [NAME]   show or switch the search mode (bm25, vec, hybrid, smart)
+  help          show this help
+  quit          leave the prompt (or Ctrl-D)";
+
+pub async fn run(args: ReplArgs, db: &Database, db_path: &Path, read_only: bool) -> Result<()> {
+    // Queries and seen results are only logged where the index is writable
+    let session_id = if read_only {
+        None
+    } else {
+        Some(open_session(db, &args.session)?)
+    };
+
+    let mut repl = Repl {
+        db,
+        mode: args.mode,
+        options: SearchOptions {
+            limit: args.limit,
+            collection: args.collection.clone(),
+            detail: DetailLevel::L1,
+            ..Default::default()
+        },
+        session_id,
+        embedder: None,
+        expander: None,
+        reranker: None,
+        llm_loaded: false,
+        results: Vec::new(),
+        paths: FormatOptions {
+            full: false,
+            query: None,
+            line_numbers: false,
+            collection_roots: local_collection_roots(db)?,
+        },
+    };
+    repl.set_mode(args.mode).await;
+
+    let mut editor = DefaultEditor::new()?;
+    let history = db_path.with_file_name(HISTORY_FILE);
+    // There is no history before the first run
+    let _ = editor.load_history(&history);
+
+    match &repl.session_id {
+        Some(id) => println!(
+            "Session {} ({}). Type 'help' for commands.",
+            args.session, id
+        ),
+        None => println!("Read-only index, queries are not logged. Type 'help' for commands."),
+    }
+
+    loop {
+        let line = match editor.readline(&format!("{}> ", mode_name(repl.mode))) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+        if !line.trim().is_empty() {
+            let _ = editor.add_history_entry(line.as_str());
+        }
+
+        let outcome = match parse_input(&line) {
+            Input::Empty => Ok(()),
+            Input::Help => {
+                println!("{}", HELP);
+                Ok(())
+            }
+            Input::Quit => break,
+            Input::Mode(None) => {
+                println!("{}", mode_name(repl.mode));
+                Ok(())
+            }
+            Input::Mode(Some(mode)) => {
+                repl.set_mode(mode).await;
+                Ok(())
+            }
+            Input::Show(n) => repl.show(n),
+            Input::Open(n) => repl.open(n),
+            Input::Query(query) => repl.search(query).await,
+        };
+        if let Err(e) = outcome {
+            eprintln!("Error: {}", e);
+        }
+    }
+
+    if let Err(e) = editor.save_history(&history) {
+        eprintln!(
+            "Warning: could not save history to {}: {}",
+            history.display(),
+            e
+        );
+    }
+    Ok(())
+}
+
+/// Resume the session by ID or name, or start a persistent one under that name
+fn open_session(db: &Database, session: &str) -> Result<String> {
+    match db.find_session(session)? {
+        Some(info) => Ok(db.resume_session(&info.id)?.id),
+        None => Ok(db.create_named_session(Some(session), Some(0))?),
+    }
+}
+
+/// A line typed at the prompt
+#[derive(Debug, PartialEq)]
+enum Input<'a> {
+    Empty,
+    Help,
+    Quit,
+    Mode(Option<SearchMode>),
+    Show(usize),
+    Open(usize),
+    Query(&'a str),
+}
+
+/// Commands win only when their argument parses, so "open source" is a query
+fn parse_input(line: &str) -> Input<'_> {
+    let line = line.trim();
+    if line.is_empty() {
+        return Input::Empty;
+    }
+    let words: Vec<&str> = line.split_whitespace().collect();
+    match words.as_slice() {
+        ["help" | "?"] => Input::Help,
+        ["quit" | "exit"] => Input::Quit,
+        ["mode"] => Input::Mode(None),
+        ["mode", name] => match SearchMode::from_str(name, true) {
+            Ok(mode) => Input::Mode(Some(mode)),
+            Err(_) => Input::Query(line),
+        },
+        ["show", n] => n.parse().map(Input::Show).unwrap_or(Input::Query(line)),
+        ["open", n] => n.parse().map(Input::Open).unwrap_or(Input::Query(line)),
+        _ => Input::Query(line),
+    }
+}
+
+fn mode_name(mode: SearchMode) -> &'static str {
+    match mode {
+        SearchMode::Bm25 => "bm25",
+        SearchMode::Vec => "vec",
+        SearchMode::Hybrid => "hybrid",
+        SearchMode::Smart => "smart",
+    }
+}
+
+struct Repl<'a> {
+    db: &'a Database,
+    mode: SearchMode,
+    options: SearchOptions,
+    session_id: Option<String>,
+    /// Loaded on the first switch to vec or hybrid mode
+    embedder: Option<Box<dyn Embedder>>,
+    expander: Option<Box<dyn QueryExpander>>,
+    reranker: Option<Box<dyn Reranker>>,
+    llm_loaded: bool,
+    /// Results of the last search, numbered from 1
+    results: Vec<SearchResult>,
+    paths: FormatOptions,
+}
+
+impl Repl<'_> {
+    /// Switch modes, falling back to bm25 when vectors are unavailable
+    async fn set_mode(&mut self, mode: SearchMode) {
+        self.mode = mode;
+        if matches!(mode, SearchMode::Vec | SearchMode::Hybrid) && self.embedder.is_none() {
+            let loaded = if self.db.has_vector_index() {
+                default_embedder()
+                    .await
+                    .map_err(|e| format!("could not load embedding model: {}", e))
+            } else {
+                Err("no vector embeddings found, run 'agentroot embed' first".to_string())
+            };
+            match loaded {
+                Ok(embedder) => self.embedder = Some(embedder),
+                Err(reason) => {
+                    eprintln!("Vector search unavailable ({}); using bm25 mode.", reason);
+                    self.mode = SearchMode::Bm25;
+                    return;
+                }
+            }
+        }
+        if self.mode == SearchMode::Hybrid && !self.llm_loaded {
+            self.expander = load_query_expander();
+            self.reranker = load_reranker();
+            self.llm_loaded = true;
+        }
+    }
+
+    async fn search(&mut self, query: &str) -> Result<()> {
+        let mut results = match (self.mode, self.embedder.as_deref()) {
+            (SearchMode::Smart, _) => match smart_search(self.db, query, &self.options).await {
+                Ok(results) => results,
+                Err(e) => {
+                    eprintln!("Smart search unavailable ({}); using BM25.", e);
+                    self.db.search_fts(query, &self.options)?
+                }
+            },
+            (SearchMode::Vec, Some(embedder)) => {
+                self.db.search_vec(query, embedder, &self.options).await?
+            }
+            (SearchMode::Hybrid, Some(embedder)) => {
+                hybrid_search(
+                    self.db,
+                    query,
+                    &self.options,
+                    embedder,
+                    self.expander.as_ref().map(|e| e.as_ref()),
+                    self.reranker.as_ref().map(|r| r.as_ref()),
+                )
+                .await?
+            }
+            _ => self.db.search_fts(query, &self.options)?,
+        };
+
+        if let Some(session_id) = &self.session_id {
+            session_aware::apply_session_awareness(self.db, &mut results, session_id)?;
+            session_aware::log_session_results(self.db, session_id, query, &results, "L1")?;
+        }
+
+        self.results = results;
+        self.print_results();
+        Ok(())
+    }
+
+    fn print_results(&self) {
+        if self.results.is_empty() {
+            println!("No results.");
+            return;
+        }
+        for (i, result) in self.results.iter().enumerate() {
+            let line = match (result.is_chunk, result.chunk_start_line) {
+                (true, Some(start)) => format!(":{}", start),
+                _ => String::new(),
+            };
+            println!(
+                "{:>3}. {:>3}% {}{} #{}",
+                i + 1,
+                (result.score * 100.0) as u32,
+                result.display_path,
+                line,
+                result.docid
+            );
+            if let Some(context) = &result.context {
+                let snippet = context.split_whitespace().collect::<Vec<_>>().join(" ");
+                if snippet.chars().count() > 100 {
+                    println!("     {}...", snippet.chars().take(100).collect::<String>());
+                } else {
+                    println!("     {}", snippet);
+                }
+            }
+        }
+    }
+
+    fn result(&self, n: usize) -> Result<&SearchResult> {
+        self.results.get(n.wrapping_sub(1)).ok_or_else(|| {
+            anyhow!(
+                "No result {}; the last search returned {}",
+                n,
+                self.results.len()
+            )
+        })
+    }
+
+    /// Print a result: the matched lines of a chunk, the whole of a document
+    fn show(&self, n: usize) -> Result<()> {
+        let result = self.result(n)?;
+        let content = self.db.get_document(&format!("#{}", result.docid))?;
+        let (start, end) = match (result.chunk_start_line, result.chunk_end_line) {
+            (Some(start), Some(end)) if result.is_chunk => (start.max(1) as usize, end as usize),
+            _ => (1, usize::MAX),
+        };
+
+        println!("{} #{}", result.display_path, result.docid);
+        for (i, line) in content.lines().enumerate() {
+            let line_no = i + 1;
+            if (start..=end).contains(&line_no) {
+                println!("{:>4} {}", line_no, line);
+            }
+        }
+        Ok(())
+    }
+
+    /// Open a result in the user's editor; results without a local file are shown
+    fn open(&self, n: usize) -> Result<()> {
+        let result = self.result(n)?;
+        let path = self.paths.result_path(result);
+        if !Path::new(&path).is_file() {
+            println!("{} is not a local file.", result.display_path);
+            return self.show(n);
+        }
+
+        let line = result
+            .chunk_start_line
+            .filter(|_| result.is_chunk)
+            .map(|line| line as usize);
+        let mut command = editor_command(Path::new(&path), line);
+        let program = command.get_program().to_string_lossy().into_owned();
+        let status = command
+            .status()
+            .map_err(|e| anyhow!("Could not run {}: {}", program, e))?;
+        if !status.success() {
+            eprintln!("{} exited with {}", program, status);
+        }
+        Ok(())
+    }
+}
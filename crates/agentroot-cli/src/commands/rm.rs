@@ -0,0 +1,17 @@
+//! Document remove command
+
+use crate::app::{OutputFormat, RmArgs};
+use agentroot_core::Database;
+use anyhow::Result;
+
+pub async fn run(args: RmArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    let deleted = db.delete_document_by_docid(&args.docid)?;
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&deleted)?),
+        _ => println!(
+            "Removed #{}  {}/{}",
+            deleted.docid, deleted.collection, deleted.path
+        ),
+    }
+    Ok(())
+}
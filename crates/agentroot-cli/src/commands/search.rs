@@ -1,21 +1,23 @@
 //! Search commands
 
 use crate::app::{OutputFormat, SearchArgs};
-use crate::output::{format_search_results, FormatOptions};
+use crate::output::{format_search_results, local_collection_roots, FormatOptions};
+use agentroot_core::search::session_aware;
 use agentroot_core::{
-    smart_search, unified_search, Database, DetailLevel, Embedder, HttpEmbedder, HttpQueryExpander,
-    HttpReranker, QueryExpander, Reranker, SearchOptions,
+    default_embedder, smart_search, unified_search, Database, DetailLevel, HttpQueryExpander,
+    HttpReranker, QueryExpander, RankingWeights, Reranker, SearchOptions, SearchResult,
 };
 use anyhow::Result;
+use std::collections::HashMap;
 
 /// Intelligent search - tries LLM orchestration first, falls back to unified search
 pub async fn run_bm25(args: SearchArgs, db: &Database, format: OutputFormat) -> Result<()> {
     let query = args.query.join(" ");
-    let options = build_options(&args);
+    let options = build_options(&args, format);
 
     // Try LLM orchestrated search first (production default)
     // This provides best results by planning optimal multi-step workflows
-    let results = match agentroot_core::orchestrated_search(db, &query, &options).await {
+    let mut results = match agentroot_core::orchestrated_search(db, &query, &options).await {
         Ok(results) => {
             tracing::info!("Using LLM orchestrated search");
             results
@@ -30,12 +32,9 @@ pub async fn run_bm25(args: SearchArgs, db: &Database, format: OutputFormat) ->
             unified_search(db, &query, &options).await?
         }
     };
+    attach_session(db, args.session.as_deref(), &query, &mut results, args.full)?;
 
-    let format_opts = FormatOptions {
-        full: args.full,
-        query: Some(query),
-        line_numbers: args.line_numbers,
-    };
+    let format_opts = format_options(&args, query, db, format)?;
 
     print!("{}", format_search_results(&results, format, &format_opts));
     Ok(())
@@ -48,7 +47,7 @@ pub async fn run_vector(args: SearchArgs, db: &Database, format: OutputFormat) -
     eprintln!();
 
     let query = args.query.join(" ");
-    let options = build_options(&args);
+    let options = build_options(&args, format);
 
     // Check if vector index exists
     if !db.has_vector_index() {
@@ -58,7 +57,7 @@ pub async fn run_vector(args: SearchArgs, db: &Database, format: OutputFormat) -
     }
 
     // Load embedder
-    let embedder = match load_embedder() {
+    let embedder = match default_embedder().await {
         Ok(e) => e,
         Err(e) => {
             eprintln!("Warning: Could not load embedding model: {}", e);
@@ -67,13 +66,10 @@ pub async fn run_vector(args: SearchArgs, db: &Database, format: OutputFormat) -
         }
     };
 
-    let results = db.search_vec(&query, embedder.as_ref(), &options).await?;
+    let mut results = db.search_vec(&query, embedder.as_ref(), &options).await?;
+    attach_session(db, args.session.as_deref(), &query, &mut results, args.full)?;
 
-    let format_opts = FormatOptions {
-        full: args.full,
-        query: Some(query),
-        line_numbers: args.line_numbers,
-    };
+    let format_opts = format_options(&args, query, db, format)?;
 
     print!("{}", format_search_results(&results, format, &format_opts));
     Ok(())
@@ -86,7 +82,7 @@ pub async fn run_hybrid(args: SearchArgs, db: &Database, format: OutputFormat) -
     eprintln!();
 
     let query = args.query.join(" ");
-    let options = build_options(&args);
+    let options = build_options(&args, format);
 
     // Check if vector index exists
     if !db.has_vector_index() {
@@ -96,7 +92,7 @@ pub async fn run_hybrid(args: SearchArgs, db: &Database, format: OutputFormat) -
     }
 
     // Load embedder
-    let embedder = match load_embedder() {
+    let embedder = match default_embedder().await {
         Ok(e) => e,
         Err(e) => {
             eprintln!("Warning: Could not load embedding model: {}", e);
@@ -112,7 +108,7 @@ pub async fn run_hybrid(args: SearchArgs, db: &Database, format: OutputFormat) -
     let reranker = load_reranker();
 
     // Run full hybrid search with query expansion and reranking
-    let results = agentroot_core::search::hybrid_search(
+    let mut results = agentroot_core::search::hybrid_search(
         db,
         &query,
         &options,
@@ -121,61 +117,90 @@ pub async fn run_hybrid(args: SearchArgs, db: &Database, format: OutputFormat) -
         reranker.as_ref().map(|r| r.as_ref()),
     )
     .await?;
+    attach_session(db, args.session.as_deref(), &query, &mut results, args.full)?;
 
-    let format_opts = FormatOptions {
-        full: args.full,
-        query: Some(query),
-        line_numbers: args.line_numbers,
-    };
+    let format_opts = format_options(&args, query, db, format)?;
 
     print!("{}", format_search_results(&results, format, &format_opts));
     Ok(())
 }
 
-fn build_options(args: &SearchArgs) -> SearchOptions {
+fn format_options(
+    args: &SearchArgs,
+    query: String,
+    db: &Database,
+    format: OutputFormat,
+) -> Result<FormatOptions> {
+    // Only local collections have on-disk paths
+    let collection_roots = if format.uses_local_paths() {
+        local_collection_roots(db)?
+    } else {
+        HashMap::new()
+    };
+    Ok(FormatOptions {
+        full: args.full,
+        query: Some(query),
+        line_numbers: args.line_numbers,
+        collection_roots,
+    })
+}
+
+fn build_options(args: &SearchArgs, format: OutputFormat) -> SearchOptions {
     SearchOptions {
         limit: if args.all { usize::MAX } else { args.limit },
         min_score: args.min_score,
         collection: args.collection.clone(),
         provider: None,
         metadata_filters: Vec::new(),
-        detail: if args.full {
+        // grep output locates matches in the body
+        detail: if args.full || format == OutputFormat::Grep {
             DetailLevel::L2
         } else {
             DetailLevel::L1
         },
+        exact: args.exact,
+        fuzzy: args.fuzzy,
+        alpha: args.alpha,
+        weights: args
+            .no_pagerank
+            .then(|| RankingWeights::from_config(args.collection.as_deref()).without_pagerank()),
         ..Default::default()
     }
 }
 
 pub async fn run_smart(args: SearchArgs, db: &Database, format: OutputFormat) -> Result<()> {
     let query = args.query.join(" ");
-    let options = build_options(&args);
+    let options = build_options(&args, format);
 
     // Smart search handles fallbacks internally
-    let results = smart_search(db, &query, &options).await?;
+    let mut results = smart_search(db, &query, &options).await?;
+    attach_session(db, args.session.as_deref(), &query, &mut results, args.full)?;
 
-    let format_opts = FormatOptions {
-        full: args.full,
-        query: Some(query),
-        line_numbers: args.line_numbers,
-    };
+    let format_opts = format_options(&args, query, db, format)?;
 
     print!("{}", format_search_results(&results, format, &format_opts));
     Ok(())
 }
 
-fn load_embedder() -> Result<Box<dyn Embedder>> {
-    // Get HTTP embedder from environment variables
-    match HttpEmbedder::from_env() {
-        Ok(http_embedder) => Ok(Box::new(http_embedder)),
-        Err(_) => Err(anyhow::anyhow!(
-            "No embedding service configured. Set AGENTROOT_EMBEDDING_URL, AGENTROOT_EMBEDDING_MODEL, and AGENTROOT_EMBEDDING_DIMS environment variables. See VLLM_SETUP.md for details."
-        )),
-    }
+/// Demote results already seen in the session and log this query to it
+fn attach_session(
+    db: &Database,
+    session: Option<&str>,
+    query: &str,
+    results: &mut [SearchResult],
+    full: bool,
+) -> Result<()> {
+    let Some(session) = session else {
+        return Ok(());
+    };
+    let session_id = db.resume_session(session)?.id;
+    session_aware::apply_session_awareness(db, results, &session_id)?;
+    let detail = if full { "L2" } else { "L1" };
+    session_aware::log_session_results(db, &session_id, query, results, detail)?;
+    Ok(())
 }
 
-fn load_query_expander() -> Option<Box<dyn QueryExpander>> {
+pub(crate) fn load_query_expander() -> Option<Box<dyn QueryExpander>> {
     match HttpQueryExpander::from_env() {
         Ok(expander) => {
             eprintln!("Query expansion enabled with {}", expander.model_name());
@@ -188,7 +213,7 @@ fn load_query_expander() -> Option<Box<dyn QueryExpander>> {
     }
 }
 
-fn load_reranker() -> Option<Box<dyn Reranker>> {
+pub(crate) fn load_reranker() -> Option<Box<dyn Reranker>> {
     match HttpReranker::from_env() {
         Ok(reranker) => {
             eprintln!("Reranking enabled with {}", reranker.model_name());
@@ -0,0 +1,206 @@
+//! Session management command
+
+use crate::app::{OutputFormat, SessionAction, SessionArgs};
+use agentroot_core::db::{SessionInfo, SessionStats};
+use agentroot_core::Database;
+use anyhow::Result;
+
+pub async fn run(args: SessionArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    match args.action.unwrap_or(SessionAction::List) {
+        SessionAction::List => run_list(db, format),
+        SessionAction::Start {
+            name,
+            ttl,
+            persistent,
+        } => {
+            let ttl = if persistent { 0 } else { ttl };
+            let id = db.create_named_session(name.as_deref(), Some(ttl))?;
+            let session = db
+                .get_session(&id)?
+                .ok_or_else(|| anyhow::anyhow!("Session not found: {}", id))?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&session)?),
+                _ => println!("Session started: {}", describe(&session)),
+            }
+            Ok(())
+        }
+        SessionAction::Resume {
+            session,
+            persistent,
+        } => run_resume(db, &session, persistent, format),
+        SessionAction::Rename { session, name } => {
+            let info = find(db, &session)?;
+            db.set_session_name(&info.id, Some(&name))?;
+            if format != OutputFormat::Json {
+                println!("Renamed session {} to {}", info.id, name);
+            }
+            Ok(())
+        }
+        SessionAction::End { session } => {
+            let info = find(db, &session)?;
+            db.delete_session(&info.id)?;
+            if format != OutputFormat::Json {
+                println!("Session ended: {}", describe(&info));
+            }
+            Ok(())
+        }
+        SessionAction::Stats { session, limit } => {
+            let session_id = match session {
+                Some(s) => Some(find(db, &s)?.id),
+                None => None,
+            };
+            let stats = db.session_stats(session_id.as_deref(), limit)?;
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&stats)?),
+                _ => print_stats(&stats),
+            }
+            Ok(())
+        }
+    }
+}
+
+fn find(db: &Database, session: &str) -> Result<SessionInfo> {
+    db.find_session(session)?
+        .ok_or_else(|| anyhow::anyhow!("Session not found: {}", session))
+}
+
+fn describe(session: &SessionInfo) -> String {
+    match &session.name {
+        Some(name) => format!("{} ({})", name, session.id),
+        None => session.id.clone(),
+    }
+}
+
+fn format_ttl(ttl_seconds: i64) -> String {
+    if ttl_seconds <= 0 {
+        "persistent".to_string()
+    } else {
+        format!("ttl {}s", ttl_seconds)
+    }
+}
+
+fn run_list(db: &Database, format: OutputFormat) -> Result<()> {
+    let sessions = db.list_sessions()?;
+
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&sessions)?);
+        }
+        _ => {
+            if sessions.is_empty() {
+                println!(
+                    "No active sessions. Start one with 'agentroot session start --name <name>'."
+                );
+                return Ok(());
+            }
+            for s in &sessions {
+                println!(
+                    "{}  {}  last active {}  {}",
+                    s.id,
+                    s.name.as_deref().unwrap_or("-"),
+                    s.last_active_at,
+                    format_ttl(s.ttl_seconds)
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_resume(db: &Database, session: &str, persistent: bool, format: OutputFormat) -> Result<()> {
+    let mut info = db.resume_session(session)?;
+    if persistent {
+        db.set_session_ttl(&info.id, 0)?;
+        info.ttl_seconds = 0;
+    }
+    let queries = db.get_session_queries(&info.id)?;
+    let seen = db.get_seen_hashes(&info.id)?;
+
+    match format {
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "session": info,
+                "queries": queries,
+                "seen_count": seen.len(),
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        _ => {
+            println!("Resumed session {}", describe(&info));
+            println!(
+                "Created {}  {}  {} seen documents",
+                info.created_at,
+                format_ttl(info.ttl_seconds),
+                seen.len()
+            );
+            if !info.context.is_empty() {
+                println!("\nContext:");
+                let mut keys: Vec<_> = info.context.keys().collect();
+                keys.sort();
+                for key in keys {
+                    println!("  {} = {}", key, info.context[key]);
+                }
+            }
+            if !queries.is_empty() {
+                println!("\nQueries:");
+                for q in &queries {
+                    println!(
+                        "  {}  {} ({} results)",
+                        q.created_at, q.query, q.result_count
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn format_score(score: Option<f64>) -> String {
+    score
+        .map(|s| format!("{:.2}", s))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn print_stats(stats: &SessionStats) {
+    println!(
+        "{} sessions, {} queries ({:.1} per session), {} with no results",
+        stats.session_count,
+        stats.query_count,
+        stats.avg_queries_per_session,
+        stats.zero_result_count
+    );
+    println!(
+        "Average result score: {}",
+        format_score(stats.avg_result_score)
+    );
+
+    if !stats.sessions.is_empty() {
+        println!("\nSessions:");
+        for s in &stats.sessions {
+            println!(
+                "  {}  {:>4} queries  {:>3} empty  avg score {}",
+                s.name.as_deref().unwrap_or(&s.id),
+                s.query_count,
+                s.zero_result_count,
+                format_score(s.avg_result_score)
+            );
+        }
+    }
+
+    if !stats.zero_result_queries.is_empty() {
+        println!("\nZero-result queries:");
+        for q in &stats.zero_result_queries {
+            println!("  {:>4}x  {}", q.count, q.query);
+        }
+    }
+
+    if !stats.top_documents.is_empty() {
+        println!("\nMost retrieved documents:");
+        for d in &stats.top_documents {
+            match (&d.collection, &d.path) {
+                (Some(c), Some(p)) => println!("  {:>4}x  #{}  {}/{}", d.count, d.docid, c, p),
+                _ => println!("  {:>4}x  #{}  (no longer indexed)", d.count, d.docid),
+            }
+        }
+    }
+}
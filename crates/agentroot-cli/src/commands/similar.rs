@@ -0,0 +1,29 @@
+//! Similar documents command
+
+use crate::app::{OutputFormat, SimilarArgs};
+use crate::output::{format_search_results, local_collection_roots, FormatOptions};
+use agentroot_core::Database;
+use anyhow::Result;
+use std::collections::HashMap;
+
+pub async fn run(args: SimilarArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    let results = db.similar_to(&args.docid, args.limit)?;
+
+    if results.is_empty() && !matches!(format, OutputFormat::Json) {
+        println!("No similar documents found for {}", args.docid);
+        return Ok(());
+    }
+
+    let format_opts = FormatOptions {
+        full: false,
+        query: None,
+        line_numbers: false,
+        collection_roots: if format.uses_local_paths() {
+            local_collection_roots(db)?
+        } else {
+            HashMap::new()
+        },
+    };
+    print!("{}", format_search_results(&results, format, &format_opts));
+    Ok(())
+}
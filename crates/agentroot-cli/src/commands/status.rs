@@ -1,10 +1,15 @@
 //! Status command
 
-use crate::app::OutputFormat;
+use crate::app::{OutputFormat, StatusArgs};
+use agentroot_core::db::slow_query_threshold_ms;
 use agentroot_core::Database;
 use anyhow::Result;
 
-pub async fn run(db: &Database, format: OutputFormat) -> Result<()> {
+pub async fn run(args: StatusArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    if args.slow_queries {
+        return slow_queries(args, db, format);
+    }
+
     let stats = db.get_stats()?;
 
     match format {
@@ -22,7 +27,57 @@ pub async fn run(db: &Database, format: OutputFormat) -> Result<()> {
             println!("Metadata:");
             println!("  Generated:     {}", stats.metadata_count);
             println!("  Pending:       {}", stats.pending_metadata);
+
+            let maintenance = db.maintenance_status()?;
+            println!();
+            println!("Maintenance:");
+            println!(
+                "  Last optimized: {}",
+                maintenance.last_optimized_at.as_deref().unwrap_or("never")
+            );
+            println!("  Changes since: {}", maintenance.pending_changes);
+        }
+    }
+    Ok(())
+}
+
+fn slow_queries(args: StatusArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    if args.clear {
+        let removed = db.clear_slow_queries()?;
+        if format != OutputFormat::Json {
+            println!("Removed {} slow queries", removed);
+        }
+        return Ok(());
+    }
+
+    let entries = db.list_slow_queries(args.limit)?;
+    if format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    match slow_query_threshold_ms() {
+        Some(ms) => println!("Threshold: {} ms (AGENTROOT_SLOW_QUERY_MS)", ms),
+        None => println!("Slow query logging is off (AGENTROOT_SLOW_QUERY_MS=off)"),
+    }
+    if entries.is_empty() {
+        println!("No slow queries recorded");
+    }
+    for e in &entries {
+        println!();
+        println!(
+            "{:>8.1} ms  [{}]  \"{}\"  {} results  {}",
+            e.total_ms, e.mode, e.query, e.result_count, e.created_at
+        );
+        let stages: Vec<String> = e
+            .timings
+            .iter()
+            .map(|t| format!("{} {:.1}", t.stage, t.ms))
+            .collect();
+        if !stages.is_empty() {
+            println!("             {}", stages.join(" | "));
         }
+        println!("             options: {}", e.options);
     }
     Ok(())
 }
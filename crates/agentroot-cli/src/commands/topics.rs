@@ -0,0 +1,98 @@
+//! Topic clustering command
+
+use crate::app::{OutputFormat, TopicsAction, TopicsArgs};
+use agentroot_core::db::TopicInfo;
+use agentroot_core::llm::TopicLabeler;
+use agentroot_core::Database;
+use anyhow::Result;
+
+pub async fn run(args: TopicsArgs, db: &Database, format: OutputFormat) -> Result<()> {
+    match args.action.unwrap_or(TopicsAction::List) {
+        TopicsAction::Build { llm } => run_build(db, llm, format).await,
+        TopicsAction::List => {
+            let topics = db.list_topics()?;
+            if topics.is_empty() && format != OutputFormat::Json {
+                println!("No topics found. Run 'agentroot topics build' to detect them.");
+                return Ok(());
+            }
+            print_topics(&topics, format)
+        }
+        TopicsAction::Show { id, limit } => run_show(db, id, limit, format),
+    }
+}
+
+async fn run_build(db: &Database, llm: bool, format: OutputFormat) -> Result<()> {
+    let mut topics = db.build_topics()?;
+
+    if llm {
+        let labeler = TopicLabeler::from_env()?;
+        for topic in &mut topics {
+            let titles: Vec<String> = db
+                .get_topic_documents(topic.id, 20)?
+                .into_iter()
+                .map(|m| m.title)
+                .collect();
+            if let Some(label) = labeler.label_topic(&titles, &topic.keywords).await {
+                db.set_topic_label(topic.id, &label)?;
+                topic.label = label;
+            }
+        }
+    }
+
+    if format != OutputFormat::Json {
+        println!("Detected {} topics", topics.len());
+        if db.count_document_links()? == 0 {
+            println!("Tip: run 'agentroot pagerank' first so document links inform clustering.");
+        }
+        println!();
+    }
+    print_topics(&topics, format)
+}
+
+fn print_topics(topics: &[TopicInfo], format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(topics)?);
+        }
+        _ => {
+            for t in topics {
+                println!("{:>4}  {:>4} docs  {}", t.id, t.size, t.label);
+                if !t.keywords.is_empty() {
+                    println!("                 {}", t.keywords.join(", "));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_show(db: &Database, id: i64, limit: usize, format: OutputFormat) -> Result<()> {
+    let members = db.get_topic_documents(id, limit)?;
+
+    match format {
+        OutputFormat::Json => {
+            let output: Vec<_> = members
+                .iter()
+                .map(|m| {
+                    serde_json::json!({
+                        "docid": format!("#{}", m.docid),
+                        "file": format!("{}/{}", m.collection, m.path),
+                        "title": m.title,
+                        "importance": m.importance,
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        _ => {
+            if members.is_empty() {
+                println!("No documents in topic {}", id);
+                return Ok(());
+            }
+            for m in &members {
+                println!("#{}  {}/{}  {}", m.docid, m.collection, m.path, m.title);
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,10 +1,11 @@
 //! Update command
 
 use crate::app::UpdateArgs;
-use crate::progress::ProgressReporter;
+use crate::progress::CliProgress;
 use agentroot_core::{Database, HttpMetadataGenerator, MetadataGenerator};
 use anyhow::Result;
 use std::sync::Arc;
+use std::time::Instant;
 
 pub async fn run(args: UpdateArgs, db: &Database, verbose: bool) -> Result<()> {
     let collections = db.list_collections()?;
@@ -32,7 +33,7 @@ pub async fn run(args: UpdateArgs, db: &Database, verbose: bool) -> Result<()> {
         };
 
     let total_docs_before: usize = collections.iter().map(|c| c.document_count).sum();
-    let mut progress = ProgressReporter::new(collections.len()).with_percentage(true);
+    let start = Instant::now();
 
     println!(
         "Updating {} collections ({} documents)...",
@@ -41,11 +42,12 @@ pub async fn run(args: UpdateArgs, db: &Database, verbose: bool) -> Result<()> {
     );
 
     let mut total_updated = 0;
+    let mut total_deleted = 0;
+    let mut total_skipped = 0;
+    let mut total_secrets = 0;
     let mut total_errors = 0;
 
     for coll in &collections {
-        progress.set_message(&format!("Updating {} ({})", coll.name, coll.provider_type));
-
         if args.pull {
             if verbose {
                 eprintln!("Running git pull in {}...", coll.path);
@@ -62,22 +64,43 @@ pub async fn run(args: UpdateArgs, db: &Database, verbose: bool) -> Result<()> {
         }
 
         // Use reindex_collection_with_metadata to generate metadata if service is configured
+        let progress = CliProgress::new(format!("Updating {} ({})", coll.name, coll.provider_type));
         match db
-            .reindex_collection_with_metadata(
+            .reindex_collection_with_metadata_progress(
                 &coll.name,
                 metadata_generator.as_ref().map(|g| g.as_ref()),
+                &progress,
             )
             .await
         {
-            Ok(updated) => {
-                progress.increment();
-                if updated > 0 || verbose {
-                    println!("{}: {} files updated", coll.name, updated);
+            Ok(stats) => {
+                if stats.changes() > 0
+                    || stats.skipped > 0
+                    || stats.secrets > 0
+                    || stats.duplicates > 0
+                    || verbose
+                {
+                    let mut line = format!("{}: {} files updated", coll.name, stats.updated);
+                    if stats.deleted > 0 {
+                        line.push_str(&format!(", {} removed", stats.deleted));
+                    }
+                    if stats.skipped > 0 {
+                        line.push_str(&format!(", {} skipped", stats.skipped));
+                    }
+                    if stats.secrets > 0 {
+                        line.push_str(&format!(", {} possible secrets", stats.secrets));
+                    }
+                    if stats.duplicates > 0 {
+                        line.push_str(&format!(", {} duplicate pages", stats.duplicates));
+                    }
+                    println!("{}", line);
                 }
-                total_updated += updated;
+                total_updated += stats.updated;
+                total_deleted += stats.deleted;
+                total_skipped += stats.skipped;
+                total_secrets += stats.secrets;
             }
             Err(e) => {
-                progress.increment();
                 eprintln!("Error updating {}: {}", coll.name, e);
                 total_errors += 1;
             }
@@ -88,9 +111,13 @@ pub async fn run(args: UpdateArgs, db: &Database, verbose: bool) -> Result<()> {
     let total_docs_after: usize = collections_after.iter().map(|c| c.document_count).sum();
 
     if total_errors > 0 {
-        progress.finish_with_message(&format!("Completed with {} errors", total_errors));
+        eprintln!(
+            "Completed with {} errors in {:.1}s",
+            total_errors,
+            start.elapsed().as_secs_f64()
+        );
     } else {
-        progress.finish();
+        eprintln!("Done in {:.1}s", start.elapsed().as_secs_f64());
     }
 
     println!();
@@ -99,15 +126,28 @@ pub async fn run(args: UpdateArgs, db: &Database, verbose: bool) -> Result<()> {
         total_updated, total_docs_after
     );
 
-    if total_docs_after > total_docs_before {
+    if total_docs_after + total_deleted > total_docs_before {
         println!(
             "  {} new documents added",
-            total_docs_after - total_docs_before
+            total_docs_after + total_deleted - total_docs_before
+        );
+    }
+    if total_deleted > 0 {
+        println!(
+            "  {} documents removed (source files deleted)",
+            total_deleted
         );
-    } else if total_docs_after < total_docs_before {
+    }
+    if total_skipped > 0 {
+        println!(
+            "  {} files skipped (binary or over the size limit)",
+            total_skipped
+        );
+    }
+    if total_secrets > 0 {
         println!(
-            "  {} documents removed",
-            total_docs_before - total_docs_after
+            "  {} possible secrets found (locations logged above)",
+            total_secrets
         );
     }
 
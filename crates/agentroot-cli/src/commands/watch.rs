@@ -0,0 +1,126 @@
+//! Watch command
+
+use crate::app::WatchArgs;
+use agentroot_core::index::watcher::{
+    reindex_changed, watched_collections, CollectionWatcher, WatchUpdate,
+};
+use agentroot_core::schedule::{scheduled_collections, spawn_scheduler};
+use agentroot_core::{default_embedder, Config, Database};
+use anyhow::{bail, Result};
+use std::path::Path;
+use std::time::Duration;
+
+pub async fn run(args: WatchArgs, db: &Database, db_path: &Path, read_only: bool) -> Result<()> {
+    if !args.files && !args.schedule {
+        bail!(
+            "Nothing to watch: pass --files to reindex on file changes or --schedule to run scheduled updates"
+        );
+    }
+    if read_only {
+        bail!("Watched updates write to the index and cannot run with --read-only");
+    }
+
+    if args.schedule {
+        start_scheduler(db, db_path)?;
+    }
+    if args.files {
+        return watch_files(db, db_path, Duration::from_millis(args.debounce_ms)).await;
+    }
+    eprintln!("Watching; press Ctrl-C to stop");
+    tokio::signal::ctrl_c().await?;
+    Ok(())
+}
+
+fn start_scheduler(db: &Database, db_path: &Path) -> Result<()> {
+    let scheduled = scheduled_collections(&Config::load()?)?;
+    if scheduled.is_empty() {
+        bail!(
+            "No collection has a schedule; add `schedule:` entries under `collections:` in {}",
+            Config::default_path().display()
+        );
+    }
+
+    let now = chrono::Local::now().naive_local();
+    for s in &scheduled {
+        if db.get_collection(&s.collection)?.is_none() {
+            eprintln!(
+                "Warning: {} is scheduled but not indexed; add it with `collection add`",
+                s.collection
+            );
+        }
+        match s.schedule.next_after(now) {
+            Some(next) => println!(
+                "{}: next update {}",
+                s.collection,
+                next.format("%Y-%m-%d %H:%M")
+            ),
+            None => println!("{}: schedule never fires", s.collection),
+        }
+    }
+
+    agentroot_core::jobs::spawn_worker(db_path.to_path_buf())?;
+    spawn_scheduler(db_path.to_path_buf(), scheduled)?;
+    Ok(())
+}
+
+async fn watch_files(db: &Database, db_path: &Path, debounce: Duration) -> Result<()> {
+    let collections = watched_collections(db)?;
+    if collections.is_empty() {
+        bail!("No file collections to watch; add one with `collection add`");
+    }
+    // Keep embeddings fresh once there are some; never start embedding unasked
+    let embedder = if db.has_vector_index() {
+        default_embedder().await.ok()
+    } else {
+        None
+    };
+
+    let mut watcher = CollectionWatcher::new(collections, debounce)?.ignore(db_path);
+    for coll in watcher.collections() {
+        println!("{}: watching {}", coll.name, coll.root.display());
+    }
+    eprintln!("Watching; press Ctrl-C to stop");
+
+    loop {
+        let batch = tokio::select! {
+            batch = watcher.next_batch() => batch,
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        };
+        let Some(batch) = batch else {
+            bail!("File watcher stopped");
+        };
+        for name in batch {
+            let embedder = embedder.as_deref();
+            match reindex_changed(db, &name, embedder).await {
+                Ok(update) => print_update(&update),
+                Err(e) => eprintln!("Error updating {}: {}", name, e),
+            }
+        }
+    }
+}
+
+fn print_update(update: &WatchUpdate) {
+    if update.stats.changes() == 0 {
+        return;
+    }
+    let mut line = format!(
+        "{}: {} files updated",
+        update.collection, update.stats.updated
+    );
+    if update.stats.deleted > 0 {
+        line.push_str(&format!(", {} removed", update.stats.deleted));
+    }
+    if let Some(embed) = &update.embed {
+        line.push_str(&format!(
+            ", {} chunks embedded ({} cached)",
+            embed.embedded_chunks, embed.cached_chunks
+        ));
+    }
+    println!("{}", line);
+    if let Some(error) = &update.embed_error {
+        eprintln!(
+            "Warning: embedding {} failed: {}; run `agentroot embed` later",
+            update.collection, error
+        );
+    }
+}
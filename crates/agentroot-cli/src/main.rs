@@ -10,46 +10,148 @@ mod app;
 mod commands;
 mod output;
 mod progress;
+mod telemetry;
 
 use app::{Cli, Commands};
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive(tracing::Level::WARN.into()),
-        )
-        .init();
+    let telemetry = telemetry::init();
 
     let cli = Cli::parse();
 
+    // Runs before the index is opened so it can create the project's own
+    if let Commands::Init(args) = &cli.command {
+        return commands::init::run(args, cli.read_only).await;
+    }
+
     // Open database (use AGENTROOT_DB env var if set, otherwise use default)
     let db_path = std::env::var("AGENTROOT_DB")
         .map(std::path::PathBuf::from)
         .unwrap_or_else(|_| Database::default_path());
-    let db = Database::open(&db_path)?;
+
+    // These rewrite the index file, so no connection may hold it open
+    match &cli.command {
+        Commands::Encrypt(args) => return commands::encrypt::run(args, &db_path, cli.read_only),
+        Commands::Decrypt => return commands::encrypt::run_decrypt(&db_path, cli.read_only),
+        _ => {}
+    }
+
+    let db = if cli.read_only {
+        Database::open_read_only(&db_path)?
+    } else {
+        Database::open(&db_path)?
+    };
     db.initialize()?;
 
     let result = match cli.command {
+        Commands::Init(_) | Commands::Encrypt(_) | Commands::Decrypt => {
+            unreachable!("handled before the index is opened")
+        }
         Commands::Collection(args) => commands::collection::run(args, &db).await,
         Commands::Context(args) => commands::context::run(args, &db).await,
         Commands::Ls(args) => commands::ls::run(args, &db, cli.format).await,
         Commands::Get(args) => commands::get::run(args, &db, cli.format).await,
         Commands::MultiGet(args) => commands::get::run_multi(args, &db, cli.format).await,
-        Commands::Status => commands::status::run(&db, cli.format).await,
+        Commands::Rm(args) => commands::rm::run(args, &db, cli.format).await,
+        Commands::Status(args) => commands::status::run(args, &db, cli.format).await,
         Commands::Update(args) => commands::update::run(args, &db, cli.verbose).await,
         Commands::Embed(args) => commands::embed::run(args, &db).await,
         Commands::Search(args) => commands::search::run_bm25(args, &db, cli.format).await,
         Commands::Vsearch(args) => commands::search::run_vector(args, &db, cli.format).await,
         Commands::Query(args) => commands::search::run_hybrid(args, &db, cli.format).await,
         Commands::Smart(args) => commands::search::run_smart(args, &db, cli.format).await,
+        Commands::Repl(args) => commands::repl::run(args, &db, &db_path, cli.read_only).await,
+        Commands::Answer(args) => commands::answer::run(args, &db, cli.format).await,
         Commands::Cleanup => commands::cleanup::run(&db).await,
+        Commands::Doctor(args) => commands::doctor::run(args, &db, cli.format).await,
         Commands::Metadata(args) => commands::metadata::run(args, &db, cli.format).await,
         Commands::Pagerank => commands::pagerank::run(&db).await,
-        Commands::Mcp => agentroot_mcp::start_server(&db).await,
+        Commands::Backlinks(args) => commands::backlinks::run(args, &db, cli.format).await,
+        Commands::Links(args) => commands::links::run(args, &db, cli.format).await,
+        Commands::Related(args) => commands::related::run(args, &db, cli.format).await,
+        Commands::Similar(args) => commands::similar::run(args, &db, cli.format).await,
+        Commands::Compare(args) => commands::compare::run(args, &db, cli.format).await,
+        Commands::Discover(args) => commands::discover::run(args, &db, cli.format).await,
+        Commands::Def(args) => commands::def::run(args, &db, cli.format).await,
+        Commands::Refs(args) => commands::refs::run(args, &db, cli.format).await,
+        Commands::Grep(args) => commands::grep::run(args, &db, cli.format).await,
+        Commands::Journal(args) => commands::journal::run(args, &db, cli.format).await,
+        Commands::Topics(args) => commands::topics::run(args, &db, cli.format).await,
+        Commands::Glossary(args) => commands::glossary::run(args, &db, cli.format).await,
+        Commands::DeadLinks(args) => commands::dead_links::run(args, &db, cli.format).await,
+        Commands::Graph(args) => commands::graph::run(args, &db, cli.format).await,
+        Commands::Session(args) => commands::session::run(args, &db, cli.format).await,
+        Commands::Feedback(args) => commands::feedback::run(args, &db, cli.format).await,
+        Commands::Pin(args) => commands::pin::run(args, &db, cli.format).await,
+        Commands::Eval(args) => commands::eval::run(args, &db, cli.format, cli.verbose).await,
+        Commands::Jobs(args) => commands::jobs::run(args, &db, cli.format).await,
+        Commands::Export(args) => commands::export::run(args, &db, cli.format).await,
+        Commands::Import(args) => commands::import::run(args, &db, cli.format).await,
+        Commands::Watch(args) => commands::watch::run(args, &db, &db_path, cli.read_only).await,
+        Commands::Mcp(args) => {
+            // Heavy work queued through MCP tools runs on its own connection
+            start_job_worker(&db_path, cli.read_only);
+            start_scheduler(&db_path, cli.read_only);
+            match args.http {
+                Some(addr) => {
+                    eprintln!("MCP server listening on http://{}/mcp", addr);
+                    agentroot_mcp::http::serve(
+                        db_path.clone(),
+                        addr,
+                        args.workers,
+                        args.max_sessions,
+                        cli.read_only,
+                    )
+                    .await
+                }
+                None => agentroot_mcp::start_server(&db).await,
+            }
+        }
+        Commands::Grpc(args) => {
+            start_job_worker(&db_path, cli.read_only);
+            start_scheduler(&db_path, cli.read_only);
+            eprintln!("gRPC server listening on {}", args.addr);
+            agentroot_grpc::serve(db_path.clone(), args.addr, args.workers, cli.read_only).await
+        }
+        Commands::Serve(args) => {
+            start_job_worker(&db_path, cli.read_only);
+            start_scheduler(&db_path, cli.read_only);
+            eprintln!("HTTP server listening on {}", args.addr);
+            agentroot_http::serve(db_path.clone(), args.addr, args.workers, cli.read_only).await
+        }
     };
 
+    telemetry.shutdown().await;
     result
 }
+
+/// Jobs write to the index, so a read-only server runs none
+fn start_job_worker(db_path: &std::path::Path, read_only: bool) {
+    if read_only {
+        return;
+    }
+    if let Err(e) = agentroot_core::jobs::spawn_worker(db_path.to_path_buf()) {
+        eprintln!("Warning: job worker not started: {}", e);
+    }
+}
+
+/// Queue scheduled collection updates for the job worker to run
+fn start_scheduler(db_path: &std::path::Path, read_only: bool) {
+    if read_only {
+        return;
+    }
+    let scheduled = match agentroot_core::Config::load()
+        .and_then(|config| agentroot_core::schedule::scheduled_collections(&config))
+    {
+        Ok(scheduled) if scheduled.is_empty() => return,
+        Ok(scheduled) => scheduled,
+        Err(e) => {
+            eprintln!("Warning: update scheduler not started: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = agentroot_core::schedule::spawn_scheduler(db_path.to_path_buf(), scheduled) {
+        eprintln!("Warning: update scheduler not started: {}", e);
+    }
+}
@@ -0,0 +1,42 @@
+//! Alfred/Raycast script-filter output formatter
+
+use agentroot_core::SearchResult;
+
+pub fn format_results(results: &[SearchResult]) -> String {
+    let items: Vec<serde_json::Value> = if results.is_empty() {
+        // Launchers show an empty list as a generic fallback; say why instead
+        vec![serde_json::json!({
+            "title": "No results",
+            "subtitle": "Try a different query",
+            "valid": false,
+        })]
+    } else {
+        results
+            .iter()
+            .map(|r| {
+                let detail = r.llm_summary.as_deref().or(r.context.as_deref());
+                let subtitle = match detail {
+                    Some(detail) => format!("{} · {}", r.display_path, one_line(detail)),
+                    None => r.display_path.clone(),
+                };
+                serde_json::json!({
+                    "uid": r.docid,
+                    "title": r.title,
+                    "subtitle": subtitle,
+                    "arg": r.docid,
+                    "autocomplete": r.title,
+                    "text": {
+                        "copy": r.display_path,
+                        "largetype": r.title,
+                    },
+                })
+            })
+            .collect()
+    };
+
+    serde_json::to_string(&serde_json::json!({ "items": items })).unwrap_or_default() + "\n"
+}
+
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
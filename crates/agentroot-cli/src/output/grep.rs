@@ -0,0 +1,65 @@
+//! grep-style `path:line:col: snippet` output for editor quickfix lists
+
+use super::FormatOptions;
+use agentroot_core::SearchResult;
+
+pub fn format_results(results: &[SearchResult], options: &FormatOptions) -> String {
+    let terms = query_terms(options.query.as_deref().unwrap_or(""));
+    let mut output = String::new();
+
+    for r in results {
+        let path = options.result_path(r);
+        let first_line = if r.is_chunk {
+            r.chunk_start_line.unwrap_or(1).max(1) as usize
+        } else {
+            1
+        };
+
+        let (line, col, snippet) = match r.body.as_deref().and_then(|b| find_match(b, &terms)) {
+            Some((offset, col, text)) => (first_line + offset, col, text.trim().to_string()),
+            None => {
+                let snippet = r.context.as_deref().unwrap_or(&r.title);
+                (first_line, 1, one_line(snippet))
+            }
+        };
+        output.push_str(&format!("{}:{}:{}: {}\n", path, line, col, snippet));
+    }
+
+    output
+}
+
+fn query_terms(query: &str) -> Vec<String> {
+    query
+        .split_whitespace()
+        .filter(|t| !t.contains(':'))
+        .map(|t| {
+            t.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|t| t.len() > 1)
+        .collect()
+}
+
+/// First line containing any term: (line offset, 1-based byte column, line)
+fn find_match<'a>(body: &'a str, terms: &[String]) -> Option<(usize, usize, &'a str)> {
+    body.lines().enumerate().find_map(|(i, line)| {
+        let lower = line.to_lowercase();
+        terms
+            .iter()
+            .filter_map(|t| lower.find(t.as_str()))
+            .min()
+            // Lowercasing can change byte lengths; only trust offsets when it didn't
+            .map(|pos| {
+                if lower.len() == line.len() {
+                    pos + 1
+                } else {
+                    1
+                }
+            })
+            .map(|col| (i, col, line))
+    })
+}
+
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -1,21 +1,57 @@
 //! Output formatters
 
+pub mod alfred;
 pub mod csv;
 pub mod files;
+pub mod grep;
 pub mod json;
 pub mod markdown;
+pub mod table;
+pub mod tags;
 pub mod terminal;
 pub mod xml;
 
 use crate::app::OutputFormat;
-use agentroot_core::SearchResult;
+use agentroot_core::{Database, SearchResult};
+use std::collections::HashMap;
+use std::path::PathBuf;
 
 /// Format options
 pub struct FormatOptions {
     pub full: bool,
-    #[allow(dead_code)]
     pub query: Option<String>,
     pub line_numbers: bool,
+    /// Root directories of local collections, for formats that print real paths
+    pub collection_roots: HashMap<String, PathBuf>,
+}
+
+impl FormatOptions {
+    /// On-disk path for a document in a local collection, `collection/path` otherwise
+    pub fn local_path(&self, collection: &str, path: &str) -> String {
+        match self.collection_roots.get(collection) {
+            Some(root) => root.join(path).to_string_lossy().into_owned(),
+            None => format!("{}/{}", collection, path),
+        }
+    }
+
+    /// [`Self::local_path`] for a search result
+    pub fn result_path(&self, result: &SearchResult) -> String {
+        let prefix = format!("{}/", result.collection_name);
+        match result.display_path.strip_prefix(&prefix) {
+            Some(path) => self.local_path(&result.collection_name, path),
+            None => result.display_path.clone(),
+        }
+    }
+}
+
+/// Root directories of collections backed by the local filesystem
+pub fn local_collection_roots(db: &Database) -> agentroot_core::Result<HashMap<String, PathBuf>> {
+    Ok(db
+        .list_collections()?
+        .into_iter()
+        .filter(|c| matches!(c.provider_type.as_str(), "file" | "obsidian"))
+        .map(|c| (c.name, PathBuf::from(c.path)))
+        .collect())
 }
 
 /// Format search results
@@ -30,6 +66,11 @@ pub fn format_search_results(
         OutputFormat::Xml => xml::format_results(results, options),
         OutputFormat::Md => markdown::format_results(results, options),
         OutputFormat::Files => files::format_results(results),
+        OutputFormat::Alfred => alfred::format_results(results),
+        OutputFormat::Grep => grep::format_results(results, options),
+        OutputFormat::Ctags => tags::format_ctags(tags::from_results(results, options)),
+        OutputFormat::Etags => tags::format_etags(&tags::from_results(results, options)),
         OutputFormat::Cli => terminal::format_results(results, options),
+        OutputFormat::Table => table::format_results(results, options),
     }
 }
@@ -0,0 +1,141 @@
+//! Aligned table output formatter
+
+use super::FormatOptions;
+use agentroot_core::SearchResult;
+use std::io::IsTerminal;
+
+/// Width used when neither `COLUMNS` nor the terminal says otherwise
+const DEFAULT_WIDTH: usize = 120;
+/// Collection names longer than this are truncated
+const MAX_COLLECTION_WIDTH: usize = 20;
+/// Title and path never shrink below this
+const MIN_COLUMN_WIDTH: usize = 12;
+const SEPARATOR: &str = "  ";
+
+pub fn format_results(results: &[SearchResult], _options: &FormatOptions) -> String {
+    format_with_width(results, terminal_width())
+}
+
+/// Render rows to fit `width` columns, shrinking title and path as needed
+pub fn format_with_width(results: &[SearchResult], width: usize) -> String {
+    if results.is_empty() {
+        return String::new();
+    }
+
+    let rows: Vec<[String; 5]> = results.iter().map(row).collect();
+    let headers = ["SCORE", "DOCID", "COLLECTION", "TITLE", "PATH"];
+    let natural = |i: usize| {
+        rows.iter()
+            .map(|r| r[i].chars().count())
+            .chain(std::iter::once(headers[i].len()))
+            .max()
+            .unwrap_or(0)
+    };
+
+    let score_w = natural(0);
+    let docid_w = natural(1);
+    let collection_w = natural(2).min(MAX_COLLECTION_WIDTH);
+    let fixed = score_w + docid_w + collection_w + SEPARATOR.len() * 4;
+    let available = width.saturating_sub(fixed).max(MIN_COLUMN_WIDTH * 2);
+    let (title_w, path_w) = split_width(natural(3), natural(4), available);
+
+    let mut output = String::new();
+    let mut push_line = |cells: [&str; 5]| {
+        let line = format!(
+            "{:>score_w$}{sep}{:<docid_w$}{sep}{:<collection_w$}{sep}{:<title_w$}{sep}{}",
+            cells[0],
+            cells[1],
+            truncate_end(cells[2], collection_w),
+            truncate_end(cells[3], title_w),
+            truncate_start(cells[4], path_w),
+            sep = SEPARATOR,
+        );
+        output.push_str(line.trim_end());
+        output.push('\n');
+    };
+    push_line(headers);
+    for r in &rows {
+        push_line([&r[0], &r[1], &r[2], &r[3], &r[4]]);
+    }
+    output
+}
+
+fn row(result: &SearchResult) -> [String; 5] {
+    let prefix = format!("{}/", result.collection_name);
+    let path = result
+        .display_path
+        .strip_prefix(&prefix)
+        .unwrap_or(&result.display_path);
+    let (title, path) = match (&result.chunk_breadcrumb, result.is_chunk) {
+        (Some(breadcrumb), true) => (
+            breadcrumb.clone(),
+            format!(
+                "{}:{}-{}",
+                path,
+                result.chunk_start_line.unwrap_or(0),
+                result.chunk_end_line.unwrap_or(0)
+            ),
+        ),
+        _ => (result.title.clone(), path.to_string()),
+    };
+    [
+        format!("{}%", (result.score * 100.0) as u32),
+        format!("#{}", result.docid),
+        result.collection_name.clone(),
+        title.replace(['\n', '\r', '\t'], " "),
+        path,
+    ]
+}
+
+/// Share `available` columns between title and path
+///
+/// Both keep their natural width when they fit; otherwise the wider one
+/// gives way first, down to an even split.
+fn split_width(title: usize, path: usize, available: usize) -> (usize, usize) {
+    if title + path <= available {
+        return (title, path);
+    }
+    let half = available / 2;
+    if title <= half {
+        (title, available - title)
+    } else if path <= half {
+        (available - path, path)
+    } else {
+        (half, available - half)
+    }
+}
+
+fn truncate_end(s: &str, width: usize) -> String {
+    if s.chars().count() <= width {
+        return s.to_string();
+    }
+    let kept: String = s.chars().take(width.saturating_sub(1)).collect();
+    format!("{}…", kept)
+}
+
+/// Keep the end of a path, where the file name is
+fn truncate_start(s: &str, width: usize) -> String {
+    let len = s.chars().count();
+    if len <= width {
+        return s.to_string();
+    }
+    let kept: String = s.chars().skip(len + 1 - width.max(1)).collect();
+    format!("…{}", kept)
+}
+
+/// `COLUMNS`, else the width of the terminal on stdout
+fn terminal_width() -> usize {
+    if let Some(columns) = std::env::var("COLUMNS")
+        .ok()
+        .and_then(|c| c.parse::<usize>().ok())
+        .filter(|c| *c > 0)
+    {
+        return columns;
+    }
+    if std::io::stdout().is_terminal() {
+        if let Ok((columns, _)) = crossterm::terminal::size() {
+            return columns as usize;
+        }
+    }
+    DEFAULT_WIDTH
+}
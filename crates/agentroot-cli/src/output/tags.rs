@@ -0,0 +1,120 @@
+//! ctags/etags output for editor jump-to-definition
+
+use super::FormatOptions;
+use agentroot_core::db::ChunkTag;
+use agentroot_core::SearchResult;
+
+/// One tags file entry
+pub struct Tag {
+    pub name: String,
+    pub path: String,
+    pub line: usize,
+    pub kind: Option<String>,
+    /// Text of the defining line, searched for by Emacs
+    pub pattern: String,
+}
+
+/// Tags for indexed chunks: the bare name plus the qualified breadcrumb
+pub fn from_chunks(chunks: &[ChunkTag], options: &FormatOptions) -> Vec<Tag> {
+    chunks
+        .iter()
+        .flat_map(|c| {
+            entries(
+                &c.name,
+                options.local_path(&c.collection, &c.path),
+                c.start_line,
+                c.chunk_type.as_deref(),
+                &c.line_text,
+            )
+        })
+        .collect()
+}
+
+/// Tags for the chunk hits of a search
+pub fn from_results(results: &[SearchResult], options: &FormatOptions) -> Vec<Tag> {
+    results
+        .iter()
+        .filter(|r| r.is_chunk)
+        .filter_map(|r| {
+            let name = r.chunk_breadcrumb.as_deref()?;
+            let pattern = r
+                .body
+                .as_deref()
+                .and_then(|b| b.lines().next())
+                .unwrap_or(name);
+            Some(entries(
+                name,
+                options.result_path(r),
+                r.chunk_start_line.unwrap_or(1),
+                r.chunk_type.as_deref(),
+                pattern,
+            ))
+        })
+        .flatten()
+        .collect()
+}
+
+fn entries(
+    breadcrumb: &str,
+    path: String,
+    line: i32,
+    kind: Option<&str>,
+    pattern: &str,
+) -> Vec<Tag> {
+    let tag = |name: &str| Tag {
+        name: name.to_string(),
+        path: path.clone(),
+        line: line.max(1) as usize,
+        kind: kind.map(|k| k.to_lowercase()),
+        pattern: pattern.trim_end().to_string(),
+    };
+    // Editors look up the identifier under the cursor, so the bare name comes first
+    match breadcrumb.rsplit_once("::") {
+        Some((_, leaf)) if !leaf.is_empty() => vec![tag(leaf), tag(breadcrumb)],
+        _ => vec![tag(breadcrumb)],
+    }
+}
+
+/// Sorted ctags file (`name<TAB>path<TAB>line;"<TAB>kind:...`)
+pub fn format_ctags(mut tags: Vec<Tag>) -> String {
+    tags.sort_by(|a, b| {
+        (a.name.as_bytes(), &a.path, a.line).cmp(&(b.name.as_bytes(), &b.path, b.line))
+    });
+
+    let mut output = String::from(
+        "!_TAG_FILE_FORMAT\t2\t/extended format/\n\
+         !_TAG_FILE_SORTED\t1\t/0=unsorted, 1=sorted, 2=foldcase/\n\
+         !_TAG_PROGRAM_NAME\tagentroot\t//\n",
+    );
+    for tag in &tags {
+        output.push_str(&format!("{}\t{}\t{};\"", tag.name, tag.path, tag.line));
+        if let Some(kind) = &tag.kind {
+            output.push_str(&format!("\tkind:{}", kind));
+        }
+        output.push_str(&format!("\tline:{}\n", tag.line));
+    }
+    output
+}
+
+/// Emacs TAGS file: one section per file, in first-seen order
+pub fn format_etags(tags: &[Tag]) -> String {
+    let mut sections: Vec<(&str, String)> = Vec::new();
+    for tag in tags {
+        let entry = format!(
+            "{}\x7f{}\x01{},\n",
+            tag.pattern.replace(['\x7f', '\x01'], ""),
+            tag.name,
+            tag.line
+        );
+        match sections.iter_mut().find(|(path, _)| *path == tag.path) {
+            Some((_, body)) => body.push_str(&entry),
+            None => sections.push((&tag.path, entry)),
+        }
+    }
+
+    let mut output = String::new();
+    for (path, body) in sections {
+        output.push_str(&format!("\x0c\n{},{}\n{}", path, body.len(), body));
+    }
+    output
+}
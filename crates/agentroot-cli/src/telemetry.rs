@@ -0,0 +1,87 @@
+//! Logging and OpenTelemetry trace export
+//!
+//! Spans from search, workflow, provider and LLM code are always recorded by
+//! `tracing`. When `OTEL_EXPORTER_OTLP_ENDPOINT` (or the traces-specific
+//! variant) is set they are also exported over OTLP/HTTP, so a slow query can
+//! be broken down into BM25, embedding and rerank time in a trace viewer.
+
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::SpanExporter;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+const ENDPOINT_VARS: [&str; 2] = [
+    "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+    "OTEL_EXPORTER_OTLP_ENDPOINT",
+];
+
+/// Flushes exported spans on shutdown
+pub struct Telemetry {
+    provider: Option<SdkTracerProvider>,
+}
+
+impl Telemetry {
+    /// Flush pending spans and stop the exporter
+    pub async fn shutdown(self) {
+        let Some(provider) = self.provider else {
+            return;
+        };
+        // The blocking HTTP client must not be dropped on an async worker
+        let result = tokio::task::spawn_blocking(move || provider.shutdown()).await;
+        if let Ok(Err(e)) = result {
+            eprintln!("Warning: failed to flush traces: {}", e);
+        }
+    }
+}
+
+/// Install the global subscriber: warnings to stderr, plus OTLP export if configured
+pub fn init() -> Telemetry {
+    let fmt = tracing_subscriber::fmt::layer()
+        .with_filter(EnvFilter::from_default_env().add_directive(tracing::Level::WARN.into()));
+
+    let provider = match otlp_enabled().then(build_provider) {
+        Some(Ok(provider)) => Some(provider),
+        Some(Err(e)) => {
+            eprintln!("Warning: OpenTelemetry export disabled: {}", e);
+            None
+        }
+        None => None,
+    };
+
+    let otel = provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer()
+            .with_tracer(provider.tracer("agentroot"))
+            .with_filter(EnvFilter::new(
+                std::env::var("AGENTROOT_TRACE").unwrap_or_else(|_| "agentroot_core=info".into()),
+            ))
+    });
+
+    tracing_subscriber::registry().with(fmt).with(otel).init();
+
+    Telemetry { provider }
+}
+
+fn otlp_enabled() -> bool {
+    ENDPOINT_VARS
+        .iter()
+        .any(|var| std::env::var(var).is_ok_and(|value| !value.trim().is_empty()))
+}
+
+/// The exporter reads the endpoint, headers and timeout from the standard
+/// `OTEL_EXPORTER_OTLP_*` variables
+fn build_provider() -> anyhow::Result<SdkTracerProvider> {
+    let exporter = SpanExporter::builder().with_http().build()?;
+
+    let mut resource = Resource::builder();
+    if std::env::var("OTEL_SERVICE_NAME").is_err() {
+        resource = resource.with_service_name("agentroot");
+    }
+
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(resource.build())
+        .build())
+}
@@ -6,7 +6,7 @@ use std::fs;
 use tempfile::TempDir;
 
 fn agentroot_cmd() -> Command {
-    Command::cargo_bin("agentroot").unwrap()
+    assert_cmd::cargo::cargo_bin_cmd!("agentroot")
 }
 
 fn create_test_files(dir: &TempDir) -> Vec<String> {
@@ -219,3 +219,44 @@ fn test_collection_add_with_provider() {
         .success()
         .stdout(predicate::str::contains("Added collection"));
 }
+
+#[test]
+fn test_doctor_reports_missing_collection_directory() {
+    let test_dir = TempDir::new().unwrap();
+    create_test_files(&test_dir);
+    let db_dir = TempDir::new().unwrap();
+    let db_path = db_dir.path().join("test.sqlite");
+
+    let mut add_cmd = agentroot_cmd();
+    add_cmd
+        .env("AGENTROOT_DB", db_path.to_str().unwrap())
+        .arg("collection")
+        .arg("add")
+        .arg(test_dir.path())
+        .arg("--name")
+        .arg("testproject");
+    add_cmd.assert().success();
+
+    let mut doctor_cmd = agentroot_cmd();
+    doctor_cmd
+        .env("AGENTROOT_DB", db_path.to_str().unwrap())
+        .arg("doctor")
+        .arg("--offline");
+    doctor_cmd
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("integrity"))
+        .stdout(predicate::str::contains("FAIL").not());
+
+    drop(test_dir);
+
+    let mut doctor_cmd = agentroot_cmd();
+    doctor_cmd
+        .env("AGENTROOT_DB", db_path.to_str().unwrap())
+        .arg("doctor")
+        .arg("--offline");
+    doctor_cmd
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("collection remove testproject"));
+}
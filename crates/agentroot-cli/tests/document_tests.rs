@@ -6,7 +6,7 @@ use std::fs;
 use tempfile::TempDir;
 
 fn agentroot_cmd() -> Command {
-    Command::cargo_bin("agentroot").unwrap()
+    assert_cmd::cargo::cargo_bin_cmd!("agentroot")
 }
 
 fn setup_indexed_collection() -> (TempDir, TempDir) {
@@ -125,6 +125,24 @@ fn test_get_with_line_numbers() {
     cmd.assert().success().stdout(predicate::str::contains("1"));
 }
 
+#[test]
+fn test_get_with_highlight() {
+    let (_test_dir, db_dir) = setup_indexed_collection();
+    let db_path = db_dir.path().join("test.sqlite");
+
+    let mut cmd = agentroot_cmd();
+    cmd.env("AGENTROOT_DB", db_path.to_str().unwrap())
+        .arg("get")
+        .arg("testproject/docs/api.md")
+        .arg("--highlight")
+        .arg("numbers");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("> Adds two numbers."))
+        .stdout(predicate::str::contains("  # API Documentation"));
+}
+
 #[test]
 fn test_ls_collection() {
     let (_test_dir, db_dir) = setup_indexed_collection();
@@ -244,3 +262,31 @@ fn test_multi_get_empty_pattern() {
         .success()
         .stdout(predicate::str::contains("No documents").or(predicate::str::is_empty()));
 }
+
+#[test]
+fn test_rm_removes_document_from_search() {
+    let (_test_dir, db_dir) = setup_indexed_collection();
+    let db_path = db_dir.path().join("test.sqlite");
+
+    agentroot_cmd()
+        .env("AGENTROOT_DB", db_path.to_str().unwrap())
+        .arg("rm")
+        .arg("testproject/docs/api.md")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("testproject/docs/api.md"));
+
+    agentroot_cmd()
+        .env("AGENTROOT_DB", db_path.to_str().unwrap())
+        .arg("search")
+        .arg("documentation")
+        .assert()
+        .stdout(predicate::str::contains("api.md").not());
+
+    agentroot_cmd()
+        .env("AGENTROOT_DB", db_path.to_str().unwrap())
+        .arg("rm")
+        .arg("testproject/docs/api.md")
+        .assert()
+        .failure();
+}
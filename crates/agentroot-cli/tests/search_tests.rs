@@ -6,7 +6,7 @@ use std::fs;
 use tempfile::TempDir;
 
 fn agentroot_cmd() -> Command {
-    Command::cargo_bin("agentroot").unwrap()
+    assert_cmd::cargo::cargo_bin_cmd!("agentroot")
 }
 
 fn setup_test_collection() -> (TempDir, TempDir) {
@@ -160,6 +160,60 @@ fn test_search_output_markdown() {
     cmd.assert().success();
 }
 
+#[test]
+fn test_search_output_table() {
+    let (_test_dir, db_dir) = setup_test_collection();
+    let db_path = db_dir.path().join("test.sqlite");
+
+    let mut cmd = agentroot_cmd();
+    cmd.env("AGENTROOT_DB", db_path.to_str().unwrap())
+        .env("COLUMNS", "80")
+        .arg("--format")
+        .arg("table")
+        .arg("search")
+        .arg("error");
+
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let text = String::from_utf8(output).unwrap();
+    let mut lines = text.lines();
+    let header = lines.next().unwrap();
+    assert!(header.contains("SCORE") && header.contains("PATH"));
+    assert!(text.contains("error_handling.md"));
+    assert!(text.lines().all(|l| l.chars().count() <= 80));
+}
+
+#[test]
+fn test_search_fuzzy_matches_typos() {
+    let (_test_dir, db_dir) = setup_test_collection();
+    let db_path = db_dir.path().join("test.sqlite");
+
+    let mut cmd = agentroot_cmd();
+    cmd.env("AGENTROOT_DB", db_path.to_str().unwrap())
+        .arg("search")
+        .arg("comprehensve")
+        .arg("--fuzzy");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("testing.md"));
+}
+
+#[test]
+fn test_grep_prints_matching_lines() {
+    let (_test_dir, db_dir) = setup_test_collection();
+    let db_path = db_dir.path().join("test.sqlite");
+
+    let mut cmd = agentroot_cmd();
+    cmd.env("AGENTROOT_DB", db_path.to_str().unwrap())
+        .arg("grep")
+        .arg("-i")
+        .arg(r"result<\w+");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::is_match(r"error_handling\.md:\d+:\d+:").unwrap());
+}
+
 #[test]
 fn test_query_runs_successfully() {
     let (_test_dir, db_dir) = setup_test_collection();
@@ -186,3 +240,27 @@ fn test_status_shows_collections() {
         .success()
         .stdout(predicate::str::is_match("Collections:\\s+1").unwrap());
 }
+
+#[test]
+fn test_repl_numbers_results_and_shows_them() {
+    let (_test_dir, db_dir) = setup_test_collection();
+    let db_path = db_dir.path().join("test.sqlite");
+
+    let mut cmd = agentroot_cmd();
+    cmd.env("AGENTROOT_DB", db_path.to_str().unwrap())
+        .arg("repl")
+        .arg("--mode")
+        .arg("bm25")
+        .write_stdin("async\nshow 1\nshow 7\nmode\nquit\n");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("1.").and(predicate::str::contains("async.md")))
+        .stdout(predicate::str::contains("   3 Use async/await"))
+        .stdout(predicate::str::contains("bm25"))
+        .stderr(predicate::str::contains("No result 7"));
+
+    // The queries are kept for the next run
+    let history = fs::read_to_string(db_dir.path().join("repl_history")).unwrap();
+    assert!(history.contains("async"));
+}
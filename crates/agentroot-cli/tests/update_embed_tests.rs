@@ -6,7 +6,7 @@ use std::fs;
 use tempfile::TempDir;
 
 fn agentroot_cmd() -> Command {
-    Command::cargo_bin("agentroot").unwrap()
+    assert_cmd::cargo::cargo_bin_cmd!("agentroot")
 }
 
 fn setup_collection() -> (TempDir, TempDir) {
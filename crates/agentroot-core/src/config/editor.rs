@@ -0,0 +1,64 @@
+//! The user's text editor, for opening search results
+
+use std::path::Path;
+use std::process::Command;
+
+/// Used when neither `$VISUAL` nor `$EDITOR` is set
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Editors that take `+LINE` to jump to a line
+const LINE_AWARE_EDITORS: &[&str] = &["vi", "vim", "nvim", "nano", "emacs"];
+
+/// Command opening `path` in `$VISUAL` or `$EDITOR`, at `line` when the editor supports it
+pub fn editor_command(path: &Path, line: Option<usize>) -> Command {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string());
+    command_for(&editor, path, line)
+}
+
+/// `editor` may carry arguments, as in `code --wait`
+fn command_for(editor: &str, path: &Path, line: Option<usize>) -> Command {
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or(DEFAULT_EDITOR);
+    let mut command = Command::new(program);
+    command.args(parts);
+
+    let name = Path::new(program)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(program);
+    if let Some(line) = line.filter(|_| LINE_AWARE_EDITORS.contains(&name)) {
+        command.arg(format!("+{}", line));
+    }
+    command.arg(path);
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(command: &Command) -> Vec<String> {
+        command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_line_is_passed_to_line_aware_editors() {
+        let command = command_for("/usr/bin/nvim", Path::new("notes/a.md"), Some(12));
+        assert_eq!(command.get_program(), "/usr/bin/nvim");
+        assert_eq!(args(&command), ["+12", "notes/a.md"]);
+    }
+
+    #[test]
+    fn test_editor_arguments_are_kept() {
+        let command = command_for("code --wait", Path::new("a.md"), Some(3));
+        assert_eq!(command.get_program(), "code");
+        assert_eq!(args(&command), ["--wait", "a.md"]);
+    }
+}
@@ -1,8 +1,9 @@
 //! Configuration management
 
+pub mod editor;
 pub mod virtual_path;
 
-use crate::error::Result;
+use crate::error::{AgentRootError, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -21,11 +22,144 @@ pub struct Config {
     /// LLM service configuration
     #[serde(default)]
     pub llm_service: LLMServiceConfig,
+
+    /// Ranking signal weights for hybrid search
+    #[serde(default)]
+    pub ranking: RankingWeights,
+}
+
+/// How hybrid search blends its ranking signals
+///
+/// Prose tends to favour `vector`, code and tickets `bm25`. Set globally
+/// under `ranking:` in the config file or per collection.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RankingWeights {
+    /// Weight of the BM25 ranking in reciprocal rank fusion
+    pub bm25: f64,
+    /// Weight of the vector ranking in reciprocal rank fusion
+    pub vector: f64,
+    /// Weight of the reranker score against the fused score (0 ignores it)
+    pub reranker: f64,
+    /// Maximum relative boost for documents central in the link graph
+    pub pagerank: f64,
+    /// Strength of the PageRank importance boost in BM25, vector and fused
+    /// rankings: 1 multiplies scores by the importance, 0 ignores it
+    pub importance: f64,
+    /// Maximum relative boost for recently modified documents
+    pub recency: f64,
+    /// Rank offset in reciprocal rank fusion; larger values flatten the curve
+    pub rrf_k: f64,
+    /// Relevance share in Maximal Marginal Relevance re-ranking; 1 keeps
+    /// the fused order, lower values push near-duplicates down
+    pub mmr_lambda: f64,
+}
+
+impl Default for RankingWeights {
+    fn default() -> Self {
+        Self {
+            bm25: 2.0,
+            vector: 1.0,
+            reranker: 1.0,
+            pagerank: 0.3,
+            importance: 1.0,
+            recency: 0.0,
+            rrf_k: 60.0,
+            mmr_lambda: 0.7,
+        }
+    }
+}
+
+impl RankingWeights {
+    /// Weights from the config file, preferring the collection's own
+    pub fn from_config(collection: Option<&str>) -> Self {
+        let Ok(config) = Config::load() else {
+            return Self::default();
+        };
+        collection
+            .and_then(|name| config.collections.get(name))
+            .and_then(|c| c.ranking)
+            .unwrap_or(config.ranking)
+    }
+
+    /// Split the combined BM25 and vector weight by `alpha`, the lexical
+    /// share: 1 ranks by BM25 only, 0 by vectors only
+    pub fn with_alpha(self, alpha: f64) -> Self {
+        let alpha = alpha.clamp(0.0, 1.0);
+        let total = self.bm25 + self.vector;
+        Self {
+            bm25: total * alpha,
+            vector: total * (1.0 - alpha),
+            ..self
+        }
+    }
+
+    /// These weights without any link-graph boost
+    pub fn without_pagerank(self) -> Self {
+        Self {
+            pagerank: 0.0,
+            importance: 0.0,
+            ..self
+        }
+    }
+}
+
+/// API spoken by the chat service
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LLMProvider {
+    /// OpenAI-compatible chat completions (vLLM, OpenAI, Basilica, ...)
+    #[default]
+    #[serde(alias = "vllm")]
+    OpenAi,
+    /// Anthropic Messages API
+    Anthropic,
+}
+
+impl LLMProvider {
+    /// `AGENTROOT_LLM_PROVIDER` (`openai` or `anthropic`); OpenAI-compatible
+    /// when unset or invalid
+    pub fn from_env() -> Self {
+        match std::env::var("AGENTROOT_LLM_PROVIDER") {
+            Ok(value) => value.parse().unwrap_or_else(|e| {
+                tracing::warn!("{}; using an OpenAI-compatible service", e);
+                Self::OpenAi
+            }),
+            Err(_) => Self::OpenAi,
+        }
+    }
+
+    /// Where the provider's API lives when no URL is configured
+    fn default_url(self) -> &'static str {
+        match self {
+            Self::OpenAi => "http://localhost:8000",
+            Self::Anthropic => "https://api.anthropic.com",
+        }
+    }
+}
+
+impl std::str::FromStr for LLMProvider {
+    type Err = AgentRootError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "" | "openai" | "vllm" => Ok(Self::OpenAi),
+            "anthropic" => Ok(Self::Anthropic),
+            other => Err(AgentRootError::InvalidInput(format!(
+                "Unknown LLM provider '{}' (expected openai or anthropic)",
+                other
+            ))),
+        }
+    }
 }
 
 /// LLM service configuration for external inference
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LLMServiceConfig {
+    /// API of the chat service; embeddings always use the OpenAI-compatible API
+    #[serde(default = "LLMProvider::from_env")]
+    pub provider: LLMProvider,
+
     /// Base URL of the LLM service for chat/completions
     pub url: String,
 
@@ -45,6 +179,14 @@ pub struct LLMServiceConfig {
     #[serde(default)]
     pub embedding_dimensions: Option<usize>,
 
+    /// Embedding requests allowed per minute (unlimited if not specified)
+    #[serde(default)]
+    pub embedding_rpm: Option<u32>,
+
+    /// Embedding input tokens allowed per minute, at about 4 characters a token
+    #[serde(default)]
+    pub embedding_tpm: Option<u32>,
+
     /// API key (optional, for authenticated services)
     #[serde(default)]
     pub api_key: Option<String>,
@@ -52,6 +194,38 @@ pub struct LLMServiceConfig {
     /// Request timeout in seconds
     #[serde(default = "default_timeout")]
     pub timeout_secs: u64,
+
+    /// Personal data to mask in requests to the service (off by default)
+    #[serde(default = "PiiMaskConfig::from_env")]
+    pub pii_mask: PiiMaskConfig,
+}
+
+/// Personal data masked before text leaves for an LLM service
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PiiMaskConfig {
+    /// Built-in detectors to enable: `email`, `phone`
+    #[serde(default)]
+    pub kinds: Vec<String>,
+
+    /// Extra regular expressions, e.g. names of people, masked as `[PII]`
+    #[serde(default)]
+    pub patterns: Vec<String>,
+}
+
+impl PiiMaskConfig {
+    /// Read `AGENTROOT_PII_MASK` (comma-separated kinds) and `AGENTROOT_PII_PATTERNS`
+    pub fn from_env() -> Self {
+        Self {
+            kinds: std::env::var("AGENTROOT_PII_MASK")
+                .map(|kinds| kinds.split(',').map(|k| k.trim().to_string()).collect())
+                .unwrap_or_default(),
+            patterns: std::env::var("AGENTROOT_PII_PATTERNS")
+                .ok()
+                .filter(|p| !p.is_empty())
+                .into_iter()
+                .collect(),
+        }
+    }
 }
 
 impl LLMServiceConfig {
@@ -65,6 +239,7 @@ impl LLMServiceConfig {
         // Check if ENV vars are set (primary source)
         if std::env::var("AGENTROOT_LLM_URL").is_ok()
             || std::env::var("AGENTROOT_EMBEDDING_URL").is_ok()
+            || std::env::var("AGENTROOT_LLM_PROVIDER").is_ok()
         {
             // At least one URL is set via ENV - use ENV vars
             return Self::default();
@@ -82,17 +257,26 @@ impl LLMServiceConfig {
 
 impl Default for LLMServiceConfig {
     fn default() -> Self {
+        let provider = LLMProvider::from_env();
         Self {
+            provider,
             url: std::env::var("AGENTROOT_LLM_URL")
-                .unwrap_or_else(|_| "http://localhost:8000".to_string()),
+                .unwrap_or_else(|_| provider.default_url().to_string()),
             model: default_chat_model(),
             embedding_url: std::env::var("AGENTROOT_EMBEDDING_URL").ok(),
             embedding_model: default_embedding_model(),
             embedding_dimensions: std::env::var("AGENTROOT_EMBEDDING_DIMS")
                 .ok()
                 .and_then(|s| s.parse().ok()),
+            embedding_rpm: std::env::var("AGENTROOT_EMBEDDING_RPM")
+                .ok()
+                .and_then(|s| s.parse().ok()),
+            embedding_tpm: std::env::var("AGENTROOT_EMBEDDING_TPM")
+                .ok()
+                .and_then(|s| s.parse().ok()),
             api_key: std::env::var("AGENTROOT_LLM_API_KEY").ok(),
             timeout_secs: default_timeout(),
+            pii_mask: PiiMaskConfig::from_env(),
         }
     }
 }
@@ -128,6 +312,18 @@ pub struct CollectionConfig {
     /// Command to run before updating (e.g., git pull)
     #[serde(default)]
     pub update: Option<String>,
+
+    /// Ranking weights for searches limited to this collection
+    #[serde(default)]
+    pub ranking: Option<RankingWeights>,
+
+    /// Cron expression for automatic updates (e.g. `0 */6 * * *`)
+    #[serde(default)]
+    pub schedule: Option<String>,
+
+    /// Shell command run when a scheduled update fails
+    #[serde(default)]
+    pub on_failure: Option<String>,
 }
 
 fn default_pattern() -> String {
@@ -159,7 +355,13 @@ impl Config {
     }
 
     /// Get default config path
+    ///
+    /// A project's `.agentroot/config.yml` when run inside one, otherwise the
+    /// user config directory.
     pub fn default_path() -> PathBuf {
+        if let Some(dir) = crate::project::project_dir() {
+            return dir.join(crate::project::PROJECT_CONFIG_NAME);
+        }
         dirs::config_dir()
             .unwrap_or_else(|| PathBuf::from("."))
             .join(crate::CONFIG_DIR_NAME)
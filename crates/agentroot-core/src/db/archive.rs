@@ -0,0 +1,592 @@
+//! Portable index archives
+//!
+//! An archive is a zstd-compressed tar with two entries: `manifest.json`,
+//! describing what it holds, and `index.sqlite`, a trimmed index with the
+//! exported collections' documents, content, chunks, labels, symbols, LLM
+//! metadata and embeddings. Importing copies those rows into another index,
+//! so it can search the collections, vectors included, without reading the
+//! sources or calling the embedding model again.
+//!
+//! Full-text tables are not shipped: the importing index's triggers fill
+//! them as rows arrive. History, the link graph and PageRank scores stay
+//! behind; `agentroot pagerank` rebuilds the latter two.
+
+use super::schema::SCHEMA_VERSION;
+use super::Database;
+use crate::error::{AgentRootError, Result};
+use chrono::Utc;
+use rusqlite::{params, Params};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Version of the archive layout, bumped on incompatible changes
+pub const ARCHIVE_FORMAT: u32 = 1;
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const INDEX_ENTRY: &str = "index.sqlite";
+const ZSTD_LEVEL: i32 = 9;
+
+/// Tables carried by an archive, in insert order, with the rows an export
+/// takes once `collections` is filled
+const ARCHIVED_TABLES: &[(&str, &str)] = &[
+    (
+        "content",
+        "hash IN (SELECT hash FROM main.documents
+                  WHERE active = 1 AND collection IN (SELECT name FROM archive.collections))",
+    ),
+    (
+        "documents",
+        "active = 1 AND collection IN (SELECT name FROM archive.collections)",
+    ),
+    (
+        "chunks",
+        "document_hash IN (SELECT hash FROM archive.content)",
+    ),
+    (
+        "chunk_labels",
+        "chunk_hash IN (SELECT hash FROM archive.chunks)",
+    ),
+    ("symbols", "chunk_hash IN (SELECT hash FROM archive.chunks)"),
+    (
+        "symbol_refs",
+        "chunk_hash IN (SELECT hash FROM archive.chunks)",
+    ),
+    (
+        "content_vectors",
+        "hash IN (SELECT hash FROM archive.content)",
+    ),
+    (
+        "embeddings",
+        "hash_seq IN (SELECT hash || '_' || seq FROM archive.content_vectors)",
+    ),
+    (
+        "chunk_embeddings",
+        "chunk_hash IN (SELECT chunk_hash FROM archive.content_vectors
+                        UNION SELECT hash FROM archive.chunks)",
+    ),
+    (
+        "model_metadata",
+        "model IN (SELECT model FROM archive.content_vectors
+                   UNION SELECT model FROM archive.chunk_embeddings)",
+    ),
+];
+
+/// What an archive holds, stored as `manifest.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub format: u32,
+    /// Schema of the bundled index; older ones are migrated on import
+    pub schema_version: i32,
+    pub agentroot_version: String,
+    pub created_at: String,
+    pub collections: Vec<String>,
+    pub documents: usize,
+    pub chunks: usize,
+    /// Embedded chunks
+    pub embeddings: usize,
+    pub models: Vec<ArchiveModel>,
+}
+
+/// Embedding model used by an archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveModel {
+    pub model: String,
+    pub dimensions: usize,
+}
+
+/// How to import an archive
+#[derive(Debug, Clone, Default)]
+pub struct ImportOptions {
+    /// Import the archive's only collection under this name
+    pub rename: Option<String>,
+    /// Point the archive's only collection at this source directory
+    pub path: Option<String>,
+    /// Replace collections that already exist instead of failing
+    pub replace: bool,
+}
+
+/// Rows added by an import
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportStats {
+    pub collections: Vec<String>,
+    pub documents: usize,
+    pub chunks: usize,
+    pub embeddings: usize,
+}
+
+impl Database {
+    /// Write one collection, or all of them, to an archive at `output`
+    pub fn export_archive(
+        &self,
+        collection: Option<&str>,
+        output: &Path,
+    ) -> Result<ArchiveManifest> {
+        let collections: Vec<String> = match collection {
+            Some(name) => {
+                if self.get_collection(name)?.is_none() {
+                    return Err(AgentRootError::CollectionNotFound(name.to_string()));
+                }
+                vec![name.to_string()]
+            }
+            None => self
+                .list_collections()?
+                .into_iter()
+                .map(|c| c.name)
+                .collect(),
+        };
+        if collections.is_empty() {
+            return Err(AgentRootError::InvalidInput(
+                "no collections to export".to_string(),
+            ));
+        }
+
+        let staging = staging_path("export");
+        let result = self
+            .fill_archive_index(&staging, &collections)
+            .and_then(|manifest| {
+                pack(&staging, output, &manifest).inspect_err(|_| {
+                    let _ = std::fs::remove_file(output);
+                })?;
+                Ok(manifest)
+            });
+        remove_staging(&staging);
+        result
+    }
+
+    /// Add the collections of the archive at `archive` to this index
+    pub fn import_archive(&self, archive: &Path, options: &ImportOptions) -> Result<ImportStats> {
+        self.ensure_writable()?;
+        let staging = staging_path("import");
+        let result = self.import_staged(archive, &staging, options);
+        remove_staging(&staging);
+        result
+    }
+
+    fn fill_archive_index(
+        &self,
+        staging: &Path,
+        collections: &[String],
+    ) -> Result<ArchiveManifest> {
+        create_archive_index(staging)?;
+        self.attach_archive(staging)?;
+        let filled = self.copy_into_archive(collections);
+        let _ = self.conn.execute("DETACH DATABASE archive", []);
+        filled
+    }
+
+    fn copy_into_archive(&self, collections: &[String]) -> Result<ArchiveManifest> {
+        // One read transaction, so the archive is a consistent snapshot
+        self.conn.execute("SAVEPOINT archive_export", [])?;
+        let copied = (|| {
+            self.copy_rows(
+                "main",
+                "archive",
+                "collections",
+                "INSERT",
+                &[],
+                Some("name IN (SELECT value FROM json_each(?1))"),
+                params![serde_json::to_string(collections)?],
+            )?;
+            for (table, filter) in ARCHIVED_TABLES {
+                self.copy_rows("main", "archive", table, "INSERT", &[], Some(filter), [])?;
+            }
+            self.archive_manifest(collections)
+        })();
+        if copied.is_err() {
+            let _ = self.conn.execute("ROLLBACK TO archive_export", []);
+        }
+        self.conn.execute("RELEASE archive_export", [])?;
+        copied
+    }
+
+    fn archive_manifest(&self, collections: &[String]) -> Result<ArchiveManifest> {
+        let count = |sql: &str| -> Result<usize> {
+            Ok(self.conn.query_row(sql, [], |row| row.get::<_, i64>(0))? as usize)
+        };
+        let mut stmt = self
+            .conn
+            .prepare("SELECT model, dimensions FROM archive.model_metadata ORDER BY model")?;
+        let models = stmt
+            .query_map([], |row| {
+                Ok(ArchiveModel {
+                    model: row.get(0)?,
+                    dimensions: row.get::<_, i64>(1)? as usize,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(ArchiveManifest {
+            format: ARCHIVE_FORMAT,
+            schema_version: SCHEMA_VERSION,
+            agentroot_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: Utc::now().to_rfc3339(),
+            collections: collections.to_vec(),
+            documents: count("SELECT COUNT(*) FROM archive.documents")?,
+            chunks: count("SELECT COUNT(*) FROM archive.chunks")?,
+            embeddings: count("SELECT COUNT(*) FROM archive.content_vectors")?,
+            models,
+        })
+    }
+
+    fn import_staged(
+        &self,
+        archive: &Path,
+        staging: &Path,
+        options: &ImportOptions,
+    ) -> Result<ImportStats> {
+        let manifest = unpack(archive, staging)?;
+        if manifest.format > ARCHIVE_FORMAT || manifest.schema_version > SCHEMA_VERSION {
+            return Err(AgentRootError::InvalidInput(format!(
+                "{} was written by a newer agentroot ({}); upgrade to import it",
+                archive.display(),
+                manifest.agentroot_version
+            )));
+        }
+        let collections = prepare_import(staging, options)?;
+
+        for name in &collections {
+            if !options.replace && self.get_collection(name)?.is_some() {
+                return Err(AgentRootError::InvalidInput(format!(
+                    "collection '{}' already exists; import it with --rename or --replace",
+                    name
+                )));
+            }
+        }
+        for model in &manifest.models {
+            match self.get_model_dimensions(&model.model)? {
+                Some(dimensions) if dimensions != model.dimensions => {
+                    return Err(AgentRootError::InvalidInput(format!(
+                        "archive embeddings from {} have {} dimensions, this index has {}",
+                        model.model, model.dimensions, dimensions
+                    )));
+                }
+                _ => {}
+            }
+        }
+
+        self.ensure_vec_table(0)?;
+        self.attach_archive(staging)?;
+        let imported = self.in_transaction(|| {
+            let mut stats = ImportStats {
+                collections: collections.clone(),
+                ..Default::default()
+            };
+            for name in &collections {
+                if options.replace {
+                    self.remove_collection(name)?;
+                }
+            }
+            self.copy_rows("archive", "main", "collections", "INSERT", &[], None, [])?;
+            for name in &collections {
+                self.create_fts_partition(name)?;
+            }
+            for (table, _) in ARCHIVED_TABLES {
+                // Documents get fresh ids; a removed document at the same
+                // path is replaced
+                let added = match *table {
+                    "documents" => self.copy_rows(
+                        "archive",
+                        "main",
+                        table,
+                        "INSERT OR REPLACE",
+                        &["id"],
+                        None,
+                        [],
+                    )?,
+                    _ => {
+                        self.copy_rows("archive", "main", table, "INSERT OR IGNORE", &[], None, [])?
+                    }
+                };
+                match *table {
+                    "documents" => stats.documents = added,
+                    "chunks" => stats.chunks = added,
+                    "content_vectors" => stats.embeddings = added,
+                    _ => {}
+                }
+            }
+            for name in &collections {
+                self.rebuild_vocabulary(name)?;
+            }
+            Ok(stats)
+        });
+        let _ = self.conn.execute("DETACH DATABASE archive", []);
+        imported
+    }
+
+    fn attach_archive(&self, staging: &Path) -> Result<()> {
+        // An empty key keeps the archive plain when this index is encrypted
+        self.conn.execute(
+            "ATTACH DATABASE ?1 AS archive KEY ''",
+            params![staging.to_string_lossy()],
+        )?;
+        Ok(())
+    }
+
+    /// Copy rows of `table` between attached schemas, by the columns both
+    /// have, so archives from older schemas line up
+    #[allow(clippy::too_many_arguments)]
+    fn copy_rows(
+        &self,
+        from: &str,
+        to: &str,
+        table: &str,
+        insert: &str,
+        skip: &[&str],
+        filter: Option<&str>,
+        params: impl Params,
+    ) -> Result<usize> {
+        let source = self.table_columns(from, table)?;
+        let columns: Vec<String> = self
+            .table_columns(to, table)?
+            .into_iter()
+            .filter(|c| source.contains(c) && !skip.contains(&c.as_str()))
+            .collect();
+        if columns.is_empty() {
+            return Ok(0);
+        }
+
+        let columns = columns.join(", ");
+        let filter = filter.map(|f| format!(" WHERE {}", f)).unwrap_or_default();
+        let sql = format!(
+            "{insert} INTO {to}.{table} ({columns}) SELECT {columns} FROM {from}.{table}{filter}"
+        );
+        Ok(self.conn.execute(&sql, params)?)
+    }
+
+    fn table_columns(&self, schema: &str, table: &str) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT name FROM pragma_table_info(?1, ?2)")?;
+        let columns = stmt
+            .query_map(params![table, schema], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(columns)
+    }
+}
+
+/// Create the empty index an export is copied into
+fn create_archive_index(path: &Path) -> Result<()> {
+    let db = Database::open_with_key(path, None)?;
+    db.initialize()?;
+    db.ensure_vec_table(0)?;
+
+    // Without triggers the archive carries no search tables or history
+    let triggers: Vec<String> = db
+        .conn
+        .prepare("SELECT name FROM sqlite_master WHERE type = 'trigger'")?
+        .query_map([], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    for trigger in triggers {
+        db.conn
+            .execute(&format!("DROP TRIGGER \"{}\"", trigger), [])?;
+    }
+    // A single file, so it can be packed once the connection closes
+    db.conn
+        .query_row("PRAGMA journal_mode = DELETE", [], |_| Ok(()))?;
+    Ok(())
+}
+
+/// Migrate an unpacked archive index and apply rename and path; returns the
+/// collections it will add
+fn prepare_import(path: &Path, options: &ImportOptions) -> Result<Vec<String>> {
+    let db = Database::open_with_key(path, None)?;
+    db.initialize()?;
+    let mut collections: Vec<String> = db.list_collections()?.into_iter().map(|c| c.name).collect();
+
+    if options.rename.is_some() || options.path.is_some() {
+        let [name] = collections.as_slice() else {
+            return Err(AgentRootError::InvalidInput(format!(
+                "--rename and --path need an archive with one collection, this one has {}",
+                collections.len()
+            )));
+        };
+        let mut name = name.clone();
+        if let Some(new_name) = &options.rename {
+            db.rename_collection(&name, new_name)?;
+            name = new_name.clone();
+        }
+        if let Some(dir) = &options.path {
+            db.conn.execute(
+                "UPDATE collections SET path = ?2 WHERE name = ?1",
+                params![name, dir],
+            )?;
+        }
+        collections = vec![name];
+    }
+    db.conn
+        .query_row("PRAGMA journal_mode = DELETE", [], |_| Ok(()))?;
+    Ok(collections)
+}
+
+fn pack(index: &Path, output: &Path, manifest: &ArchiveManifest) -> Result<()> {
+    let encoder = zstd::Encoder::new(File::create(output)?, ZSTD_LEVEL)?;
+    let mut tar = tar::Builder::new(encoder);
+
+    let json = serde_json::to_vec_pretty(manifest)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(json.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    tar.append_data(&mut header, MANIFEST_ENTRY, json.as_slice())?;
+    tar.append_path_with_name(index, INDEX_ENTRY)?;
+
+    tar.into_inner()?.finish()?;
+    Ok(())
+}
+
+fn unpack(archive: &Path, index: &Path) -> Result<ArchiveManifest> {
+    let not_an_archive = || {
+        AgentRootError::InvalidInput(format!("{} is not an agentroot archive", archive.display()))
+    };
+    let decoder = zstd::Decoder::new(File::open(archive)?).map_err(|_| not_an_archive())?;
+    let mut tar = tar::Archive::new(decoder);
+
+    let mut manifest = None;
+    let mut has_index = false;
+    for entry in tar.entries().map_err(|_| not_an_archive())? {
+        let mut entry = entry.map_err(|_| not_an_archive())?;
+        let name = entry.path()?.to_string_lossy().into_owned();
+        match name.as_str() {
+            MANIFEST_ENTRY => {
+                let mut json = String::new();
+                entry.read_to_string(&mut json)?;
+                manifest = Some(serde_json::from_str(&json).map_err(|_| not_an_archive())?);
+            }
+            INDEX_ENTRY => {
+                std::io::copy(&mut entry, &mut File::create(index)?)?;
+                has_index = true;
+            }
+            _ => {}
+        }
+    }
+    match manifest {
+        Some(manifest) if has_index => Ok(manifest),
+        _ => Err(not_an_archive()),
+    }
+}
+
+fn staging_path(purpose: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "agentroot-{}-{}-{:x}.sqlite",
+        purpose,
+        std::process::id(),
+        fastrand::u64(..)
+    ))
+}
+
+fn remove_staging(path: &Path) {
+    let _ = std::fs::remove_file(path);
+    for suffix in ["-wal", "-shm", "-journal"] {
+        let mut name = path.as_os_str().to_os_string();
+        name.push(suffix);
+        let _ = std::fs::remove_file(PathBuf::from(name));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fts_partitions::fts_table;
+
+    fn indexed_notes() -> Database {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("notes", "/home/a/notes", "**/*.md", "file", None)
+            .unwrap();
+        db.add_collection("other", "/home/a/other", "**/*.md", "file", None)
+            .unwrap();
+        for (collection, hash, path, body) in [
+            (
+                "notes",
+                "aaa111",
+                "plan.md",
+                "# Plan\nship the archive format",
+            ),
+            ("other", "bbb222", "todo.md", "# Todo\nnothing to see"),
+        ] {
+            db.insert_content(hash, body).unwrap();
+            db.insert_document(collection, path, path, hash, &now, &now, "file", None)
+                .unwrap();
+        }
+        db.ensure_vec_table(2).unwrap();
+        db.register_model("m", 2).unwrap();
+        db.insert_chunk_embedding("aaa111", 0, 0, "chunk1", "m", &[0.6, 0.8])
+            .unwrap();
+        db
+    }
+
+    #[test]
+    fn test_archive_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("notes.tar.zst");
+        let manifest = indexed_notes()
+            .export_archive(Some("notes"), &archive)
+            .unwrap();
+        assert_eq!(manifest.collections, vec!["notes"]);
+        assert_eq!(manifest.documents, 1);
+        assert_eq!(manifest.embeddings, 1);
+        assert_eq!(manifest.models[0].dimensions, 2);
+
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let options = ImportOptions {
+            rename: Some("shared".to_string()),
+            path: Some("/srv/notes".to_string()),
+            replace: false,
+        };
+        let stats = db.import_archive(&archive, &options).unwrap();
+        assert_eq!(stats.collections, vec!["shared"]);
+        assert_eq!((stats.documents, stats.embeddings), (1, 1));
+
+        assert_eq!(
+            db.get_collection("shared").unwrap().unwrap().path,
+            "/srv/notes"
+        );
+        assert!(db.get_collection("other").unwrap().is_none());
+        assert!(db
+            .find_active_document("shared", "plan.md")
+            .unwrap()
+            .is_some());
+        assert_eq!(db.get_embeddings_for_collection("shared").unwrap().len(), 1);
+        assert_eq!(db.count_cached_embeddings("m").unwrap(), 1);
+        // Search tables are filled by the importing index
+        let hits: i64 = db
+            .conn
+            .query_row(
+                &format!(
+                    "SELECT COUNT(*) FROM {0} WHERE {0} MATCH 'archive'",
+                    fts_table("shared")
+                ),
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(hits, 1);
+
+        // A second import collides unless asked to replace
+        assert!(db.import_archive(&archive, &options).is_err());
+        let replace = ImportOptions {
+            replace: true,
+            ..options
+        };
+        assert_eq!(db.import_archive(&archive, &replace).unwrap().documents, 1);
+    }
+
+    #[test]
+    fn test_import_rejects_other_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let bogus = dir.path().join("bogus.tar.zst");
+        std::fs::write(&bogus, "not an archive").unwrap();
+
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let err = db
+            .import_archive(&bogus, &ImportOptions::default())
+            .unwrap_err();
+        assert!(err.to_string().contains("not an agentroot archive"));
+    }
+}
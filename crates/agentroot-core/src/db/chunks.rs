@@ -3,6 +3,7 @@
 //! Manages individual code/text chunks with LLM-generated metadata,
 //! labels, and full-text search indexing.
 
+use super::content::docid_from_hash;
 use crate::db::Database;
 use crate::error::Result;
 use rusqlite::params;
@@ -32,8 +33,66 @@ pub struct ChunkInfo {
     pub created_at: String,
 }
 
+/// A named chunk's location, as needed for editor tag files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkTag {
+    /// Breadcrumb, e.g. "MyClass::my_method"
+    pub name: String,
+    pub collection: String,
+    pub path: String,
+    pub chunk_type: Option<String>,
+    pub language: Option<String>,
+    pub start_line: i32,
+    pub end_line: i32,
+    /// First line of the chunk, used as the search pattern by etags
+    pub line_text: String,
+}
+
+/// The chunk that defines a symbol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolDefinition {
+    /// Full breadcrumb, e.g. "Parser::parse"
+    pub name: String,
+    pub collection: String,
+    pub path: String,
+    pub docid: String,
+    pub chunk_hash: String,
+    pub chunk_type: Option<String>,
+    pub language: Option<String>,
+    pub start_line: i32,
+    pub end_line: i32,
+    pub content: String,
+}
+
+/// Strip the "[n]" suffix oversized chunks get when split into strides
+///
+/// Returns false for every stride but the first, which holds the definition.
+pub(super) fn strip_stride(name: &mut String) -> bool {
+    if let Some(open) = name.rfind('[').filter(|_| name.ends_with(']')) {
+        if &name[open..] != "[0]" {
+            return false;
+        }
+        name.truncate(open);
+    }
+    true
+}
+
+/// Whether a breadcrumb names the symbol, bare or as its last path segment
+fn names_symbol(breadcrumb: &str, symbol: &str, ignore_case: bool) -> bool {
+    let (breadcrumb, symbol) = if ignore_case {
+        (breadcrumb.to_lowercase(), symbol.to_lowercase())
+    } else {
+        (breadcrumb.to_string(), symbol.to_string())
+    };
+    breadcrumb == symbol
+        || breadcrumb
+            .strip_suffix(symbol.as_str())
+            .is_some_and(|head| head.ends_with("::"))
+}
+
 impl Database {
     /// Insert a new chunk with metadata
+    #[allow(clippy::too_many_arguments)]
     pub fn insert_chunk(
         &self,
         hash: &str,
@@ -213,6 +272,114 @@ impl Database {
         Ok(chunks)
     }
 
+    /// List named chunks of active documents, ordered by file and line
+    ///
+    /// Strides of oversized chunks share their parent's name; only the first
+    /// one (which holds the definition) is listed.
+    pub fn list_chunk_tags(&self, collection: Option<&str>) -> Result<Vec<ChunkTag>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT c.breadcrumb, d.collection, d.path, c.chunk_type, c.language,
+                    c.start_line, c.end_line, c.content
+             FROM chunks c
+             JOIN documents d ON d.hash = c.document_hash AND d.active = 1
+             WHERE c.breadcrumb IS NOT NULL AND c.breadcrumb != ''
+               AND (?1 IS NULL OR d.collection = ?1)
+             ORDER BY d.collection, d.path, c.start_line, c.seq",
+        )?;
+
+        let rows = stmt.query_map(params![collection], |row| {
+            let content: String = row.get(7)?;
+            Ok(ChunkTag {
+                name: row.get(0)?,
+                collection: row.get(1)?,
+                path: row.get(2)?,
+                chunk_type: row.get(3)?,
+                language: row.get(4)?,
+                start_line: row.get::<_, Option<i32>>(5)?.unwrap_or(1),
+                end_line: row.get::<_, Option<i32>>(6)?.unwrap_or(1),
+                line_text: content.lines().next().unwrap_or_default().to_string(),
+            })
+        })?;
+
+        let mut tags = Vec::new();
+        for tag in rows {
+            let mut tag = tag?;
+            if strip_stride(&mut tag.name) {
+                tags.push(tag);
+            }
+        }
+        Ok(tags)
+    }
+
+    /// Find the chunks defining a symbol in active documents
+    ///
+    /// `symbol` matches a whole breadcrumb ("Parser::parse") or its trailing
+    /// segments ("parse"). Case is only ignored when nothing matches exactly.
+    /// Rust impl blocks carry the bare type name, so they are listed after
+    /// the type's own definition.
+    pub fn find_definitions(
+        &self,
+        symbol: &str,
+        collection: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<SymbolDefinition>> {
+        let symbol = symbol.trim();
+        if symbol.is_empty() || limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT c.breadcrumb, d.collection, d.path, d.hash, c.hash, c.chunk_type,
+                    c.language, c.start_line, c.end_line, c.content
+             FROM chunks c
+             JOIN documents d ON d.hash = c.document_hash AND d.active = 1
+             WHERE instr(lower(c.breadcrumb), lower(?1)) > 0
+               AND (?2 IS NULL OR d.collection = ?2)
+             ORDER BY d.collection, d.path, c.start_line, c.seq",
+        )?;
+        let rows = stmt.query_map(params![symbol, collection], |row| {
+            Ok(SymbolDefinition {
+                name: row.get(0)?,
+                collection: row.get(1)?,
+                path: row.get(2)?,
+                docid: docid_from_hash(&row.get::<_, String>(3)?),
+                chunk_hash: row.get(4)?,
+                chunk_type: row.get(5)?,
+                language: row.get(6)?,
+                start_line: row.get::<_, Option<i32>>(7)?.unwrap_or(1),
+                end_line: row.get::<_, Option<i32>>(8)?.unwrap_or(1),
+                content: row.get(9)?,
+            })
+        })?;
+
+        let mut candidates = Vec::new();
+        for def in rows {
+            let mut def = def?;
+            if strip_stride(&mut def.name) {
+                candidates.push(def);
+            }
+        }
+
+        let mut found: Vec<_> = candidates
+            .iter()
+            .filter(|d| names_symbol(&d.name, symbol, false))
+            .cloned()
+            .collect();
+        if found.is_empty() {
+            found = candidates
+                .into_iter()
+                .filter(|d| names_symbol(&d.name, symbol, true))
+                .collect();
+        }
+
+        let is_impl_block = |d: &SymbolDefinition| {
+            d.chunk_type.as_deref() == Some("Method") && !d.name.contains("::")
+        };
+        found.sort_by_key(|d| (d.name.len() != symbol.len(), is_impl_block(d)));
+        found.truncate(limit);
+        Ok(found)
+    }
+
     /// Search chunks using full-text search
     pub fn search_chunks_fts(&self, query: &str, limit: usize) -> Result<Vec<ChunkInfo>> {
         let mut stmt = self.conn.prepare(
@@ -319,6 +486,8 @@ impl Database {
 
     /// Delete all chunks for a document
     pub fn delete_chunks_for_document(&self, document_hash: &str) -> Result<()> {
+        self.delete_symbols_for_document(document_hash)?;
+
         self.conn.execute(
             "DELETE FROM chunk_labels WHERE chunk_hash IN 
              (SELECT hash FROM chunks WHERE document_hash = ?1)",
@@ -543,9 +712,9 @@ mod tests {
             None,
             None,
             None,
-            &vec![],
+            &[],
             &labels1,
-            &vec![],
+            &[],
             None,
             None,
             "2024-01-01T00:00:00Z",
@@ -565,9 +734,9 @@ mod tests {
             None,
             None,
             None,
-            &vec![],
+            &[],
             &labels2,
-            &vec![],
+            &[],
             None,
             None,
             "2024-01-01T00:00:00Z",
@@ -667,4 +836,79 @@ mod tests {
         assert_eq!(prev.unwrap().hash, "chunk1");
         assert_eq!(next.unwrap().hash, "chunk3");
     }
+
+    #[tokio::test]
+    async fn test_list_chunk_tags() {
+        let src = tempfile::tempdir().unwrap();
+        std::fs::write(
+            src.path().join("lib.rs"),
+            "pub struct Parser {\n    pos: usize,\n}\n\nimpl Parser {\n    pub fn parse(&self) -> usize {\n        self.pos\n    }\n}\n",
+        )
+        .unwrap();
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.add_collection(
+            "code",
+            src.path().to_str().unwrap(),
+            "**/*.rs",
+            "file",
+            None,
+        )
+        .unwrap();
+        db.reindex_collection_with_metadata("code", None)
+            .await
+            .unwrap();
+
+        let tags = db.list_chunk_tags(Some("code")).unwrap();
+        let parse = tags.iter().find(|t| t.name.ends_with("parse")).unwrap();
+        assert_eq!(parse.path, "lib.rs");
+        assert_eq!(parse.start_line, 6);
+        assert!(parse.line_text.contains("fn parse"));
+        assert!(tags.iter().any(|t| t.name == "Parser"));
+        assert!(db.list_chunk_tags(Some("other")).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_find_definitions() {
+        let src = tempfile::tempdir().unwrap();
+        std::fs::write(
+            src.path().join("lib.rs"),
+            "impl Parser {\n    pub fn parse(&self) -> usize {\n        self.pos\n    }\n}\n\npub struct Parser {\n    pos: usize,\n}\n\npub fn parse_all() {}\n",
+        )
+        .unwrap();
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.add_collection(
+            "code",
+            src.path().to_str().unwrap(),
+            "**/*.rs",
+            "file",
+            None,
+        )
+        .unwrap();
+        db.reindex_collection_with_metadata("code", None)
+            .await
+            .unwrap();
+
+        let parse = db.find_definitions("parse", None, 10).unwrap();
+        assert_eq!(parse.len(), 1);
+        assert_eq!(parse[0].name, "Parser::parse");
+        assert_eq!(parse[0].start_line, 2);
+        assert!(parse[0].content.contains("fn parse"));
+
+        // The struct comes before its impl block
+        let parser = db.find_definitions("Parser", None, 10).unwrap();
+        assert!(parser[0].content.starts_with("pub struct Parser"));
+        assert!(parser.len() >= 2);
+
+        assert_eq!(
+            db.find_definitions("parser::PARSE", None, 10).unwrap()[0].name,
+            "Parser::parse"
+        );
+        assert!(db.find_definitions("arse", None, 10).unwrap().is_empty());
+        assert!(db
+            .find_definitions("parse", Some("other"), 10)
+            .unwrap()
+            .is_empty());
+    }
 }
@@ -2,8 +2,18 @@
 
 use super::Database;
 use crate::error::Result;
+use crate::progress::{
+    check_cancelled, CancellationToken, NoProgress, ProgressSink, ProgressTracker,
+};
 use chrono::Utc;
+use futures::StreamExt;
 use rusqlite::params;
+use tracing::Instrument;
+
+/// Source type of documents added one at a time with [`Database::ingest_item`]
+///
+/// Their provider never lists them, so reindexing leaves them in place.
+pub const INGESTED_SOURCE_TYPE: &str = "ingested";
 
 /// Collection info
 #[derive(Debug, Clone, serde::Serialize)]
@@ -18,6 +28,67 @@ pub struct CollectionInfo {
     pub provider_config: Option<String>,
 }
 
+/// What a reindex changed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct ReindexStats {
+    /// Documents added or whose content changed
+    pub updated: usize,
+    /// Documents deactivated because their source is gone
+    pub deleted: usize,
+    /// Source items the provider left out (binary or oversized files)
+    pub skipped: usize,
+    /// Credentials detected in the content read this run
+    pub secrets: usize,
+    /// Web pages left out because their canonical URL is already indexed
+    pub duplicates: usize,
+}
+
+impl ReindexStats {
+    /// Total number of documents touched
+    pub fn changes(&self) -> usize {
+        self.updated + self.deleted
+    }
+}
+
+/// Normalize provider items, redact credentials and extract frontmatter
+///
+/// Returns the items and the number of credentials found, each of which is
+/// logged with its location (never its value). Items are processed on the
+/// indexing workers and come back in their original order.
+fn prepare_items(
+    collection: &str,
+    items: Vec<crate::providers::SourceItem>,
+    scanner: &crate::index::SecretScanner,
+) -> (Vec<crate::providers::SourceItem>, usize) {
+    let prepared = crate::index::map_parallel(items, crate::index::index_workers(), |item| {
+        let (item, found) = item.normalized().with_secrets_redacted(scanner);
+        (item.with_frontmatter_extracted(), found)
+    });
+
+    let mut secrets = 0;
+    let items = prepared
+        .into_iter()
+        .map(|(item, found)| {
+            for secret in &found {
+                tracing::warn!(
+                    "Possible {} in {}/{}:{}{}",
+                    secret.kind,
+                    collection,
+                    item.uri,
+                    secret.line,
+                    match scanner.mode {
+                        crate::index::SecretMode::Redact => " (redacted)",
+                        _ => "",
+                    }
+                );
+            }
+            secrets += found.len();
+            item
+        })
+        .collect();
+    (items, secrets)
+}
+
 impl Database {
     /// Add a new collection
     pub fn add_collection(
@@ -29,47 +100,82 @@ impl Database {
         provider_config: Option<&str>,
     ) -> Result<()> {
         let now = Utc::now().to_rfc3339();
-        self.conn.execute(
-            "INSERT INTO collections (name, path, pattern, created_at, updated_at, provider_type, provider_config)
-             VALUES (?1, ?2, ?3, ?4, ?4, ?5, ?6)",
-            params![name, path, pattern, now, provider_type, provider_config],
-        )?;
-        Ok(())
+        self.in_transaction(|| {
+            self.conn.execute(
+                "INSERT INTO collections (name, path, pattern, created_at, updated_at, provider_type, provider_config)
+                 VALUES (?1, ?2, ?3, ?4, ?4, ?5, ?6)",
+                params![name, path, pattern, now, provider_type, provider_config],
+            )?;
+            self.create_fts_partition(name)
+        })
     }
 
     /// Remove a collection and its documents
+    ///
+    /// Its full-text tables are dropped first, so deactivating the documents
+    /// does not touch the search index row by row.
     pub fn remove_collection(&self, name: &str) -> Result<bool> {
-        // Deactivate all documents
-        self.conn.execute(
-            "UPDATE documents SET active = 0 WHERE collection = ?1",
-            params![name],
-        )?;
+        self.in_transaction(|| {
+            self.drop_fts_partition(name)?;
 
-        // Remove collection
-        let rows = self
-            .conn
-            .execute("DELETE FROM collections WHERE name = ?1", params![name])?;
+            // Deactivate all documents
+            self.conn.execute(
+                "UPDATE documents SET active = 0 WHERE collection = ?1",
+                params![name],
+            )?;
+            self.conn.execute(
+                "DELETE FROM vocabulary WHERE collection = ?1",
+                params![name],
+            )?;
+            self.conn.execute(
+                "DELETE FROM document_versions WHERE collection = ?1",
+                params![name],
+            )?;
 
-        Ok(rows > 0)
+            // Remove collection
+            let rows = self
+                .conn
+                .execute("DELETE FROM collections WHERE name = ?1", params![name])?;
+
+            Ok(rows > 0)
+        })
     }
 
     /// Rename a collection
+    ///
+    /// Its full-text tables are named after it, so they are rebuilt under
+    /// the new name.
     pub fn rename_collection(&self, old_name: &str, new_name: &str) -> Result<bool> {
         let now = Utc::now().to_rfc3339();
 
-        // Update documents
-        self.conn.execute(
-            "UPDATE documents SET collection = ?2 WHERE collection = ?1",
-            params![old_name, new_name],
-        )?;
+        self.in_transaction(|| {
+            self.drop_fts_partition(old_name)?;
 
-        // Update collection
-        let rows = self.conn.execute(
-            "UPDATE collections SET name = ?2, updated_at = ?3 WHERE name = ?1",
-            params![old_name, new_name, now],
-        )?;
+            // Update documents
+            self.conn.execute(
+                "UPDATE documents SET collection = ?2 WHERE collection = ?1",
+                params![old_name, new_name],
+            )?;
+            self.conn.execute(
+                "UPDATE vocabulary SET collection = ?2 WHERE collection = ?1",
+                params![old_name, new_name],
+            )?;
+            self.conn.execute(
+                "UPDATE document_versions SET collection = ?2 WHERE collection = ?1",
+                params![old_name, new_name],
+            )?;
 
-        Ok(rows > 0)
+            // Update collection
+            let rows = self.conn.execute(
+                "UPDATE collections SET name = ?2, updated_at = ?3 WHERE name = ?1",
+                params![old_name, new_name, now],
+            )?;
+
+            if rows > 0 {
+                self.create_fts_partition(new_name)?;
+            }
+            Ok(rows > 0)
+        })
     }
 
     /// List all collections with document counts
@@ -139,7 +245,37 @@ impl Database {
     }
 
     /// Reindex a collection using the provider system
+    ///
+    /// Returns the number of new or changed documents.
     pub async fn reindex_collection(&self, name: &str) -> Result<usize> {
+        let stats = self
+            .reindex_collection_with_progress(name, &NoProgress)
+            .await?;
+        Ok(stats.updated)
+    }
+
+    /// Reindex a collection, reporting per-item progress
+    pub async fn reindex_collection_with_progress(
+        &self,
+        name: &str,
+        progress: &dyn ProgressSink,
+    ) -> Result<ReindexStats> {
+        self.reindex_collection_cancellable(name, progress, &CancellationToken::new())
+            .await
+    }
+
+    /// Reindex a collection, stopping with a `Cancelled` error once `cancel`
+    /// is triggered
+    ///
+    /// Items are written in one transaction, so a cancelled reindex leaves the
+    /// collection as it was.
+    pub async fn reindex_collection_cancellable(
+        &self,
+        name: &str,
+        progress: &dyn ProgressSink,
+        cancel: &CancellationToken,
+    ) -> Result<ReindexStats> {
+        self.ensure_writable()?;
         let coll = self
             .get_collection(name)?
             .ok_or_else(|| crate::error::AgentRootError::CollectionNotFound(name.to_string()))?;
@@ -164,37 +300,267 @@ impl Database {
                 }
             }
         }
+        let config = config.with_known_stamps(self.known_stamps(name, false)?);
+        let scanner = crate::index::SecretScanner::from_config(&config)?;
+
+        let listing = provider
+            .scan(&config)
+            .instrument(tracing::info_span!(
+                "provider_fetch",
+                collection = name,
+                provider = coll.provider_type.as_str()
+            ))
+            .await?;
+        check_cancelled(cancel, "reindex")?;
+        let skipped = listing.skipped.len();
+        let unchanged = listing.unchanged;
+        let (items, secrets) = prepare_items(name, listing.items, &scanner);
+        let (items, duplicates) = self.drop_duplicate_urls(name, items)?;
+        let mut tracker = ProgressTracker::new(progress, "index");
+        tracker.discovered(items.len());
+
+        // One transaction, so searches never see a half-updated collection
+        let stats = self.in_transaction(|| {
+            let mut updated = 0;
+            for item in &items {
+                check_cancelled(cancel, "reindex")?;
+                match self.index_item(name, item) {
+                    Ok(changed) => {
+                        if changed {
+                            updated += 1;
+                        }
+                        tracker.processed(item.content.len() as u64, Some(item.uri.clone()));
+                    }
+                    Err(e) => {
+                        tracker.failed(Some(item.uri.clone()));
+                        return Err(e);
+                    }
+                }
+            }
+            let deleted = self.deactivate_missing(name, &items, &unchanged)?;
+            if updated + deleted > 0 {
+                self.rebuild_vocabulary(name)?;
+            }
+            self.store_file_stamps(name, &items)?;
+            self.store_canonical_urls(name, &items)?;
+            self.touch_collection(name)?;
+            Ok(ReindexStats {
+                updated,
+                deleted,
+                skipped,
+                secrets,
+                duplicates,
+            })
+        })?;
 
-        let items = provider.list_items(&config).await?;
-        let mut updated = 0;
+        tracker.finish();
+        self.note_index_changes(stats.changes())?;
+        Ok(stats)
+    }
+
+    /// Deactivate documents whose source no longer appears in the provider listing
+    fn deactivate_missing(
+        &self,
+        name: &str,
+        items: &[crate::providers::SourceItem],
+        unchanged: &[String],
+    ) -> Result<usize> {
+        let listed: std::collections::HashSet<&str> = items
+            .iter()
+            .map(|item| item.uri.as_str())
+            .chain(unchanged.iter().map(String::as_str))
+            .collect();
+        let mut stmt = self.conn.prepare(
+            "SELECT path FROM documents
+             WHERE collection = ?1 AND active = 1 AND source_type != ?2",
+        )?;
+        let missing: Vec<String> = stmt
+            .query_map(params![name, INGESTED_SOURCE_TYPE], |row| {
+                row.get::<_, String>(0)
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|path| !listed.contains(path.as_str()))
+            .collect();
 
+        for path in &missing {
+            self.deactivate_document(name, path)?;
+        }
+        if !missing.is_empty() {
+            tracing::info!(
+                collection = name,
+                count = missing.len(),
+                "Deactivated removed documents"
+            );
+        }
+        Ok(missing.len())
+    }
+
+    /// Stamps of active documents, for skipping unchanged files on reindex
+    ///
+    /// With `require_metadata`, documents still missing LLM metadata are left
+    /// out so they are read again and backfilled.
+    fn known_stamps(
+        &self,
+        name: &str,
+        require_metadata: bool,
+    ) -> Result<std::collections::HashMap<String, crate::providers::FileStamp>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT path, source_mtime, source_size FROM documents
+             WHERE collection = ?1 AND active = 1
+               AND source_mtime IS NOT NULL AND source_size IS NOT NULL
+               AND (?2 = 0 OR llm_model IS NOT NULL)",
+        )?;
+        let stamps = stmt
+            .query_map(params![name, require_metadata], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    crate::providers::FileStamp {
+                        mtime: row.get(1)?,
+                        size: row.get::<_, i64>(2)? as u64,
+                    },
+                ))
+            })?
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(stamps)
+    }
+
+    /// Record the stamps providers attached to the items just indexed
+    fn store_file_stamps(&self, name: &str, items: &[crate::providers::SourceItem]) -> Result<()> {
+        use crate::providers::{MTIME_KEY, SIZE_KEY};
+
+        let mut stmt = self.conn.prepare_cached(
+            "UPDATE documents SET source_mtime = ?3, source_size = ?4
+             WHERE collection = ?1 AND path = ?2 AND active = 1",
+        )?;
         for item in items {
-            let now = Utc::now().to_rfc3339();
+            let mtime = item
+                .metadata
+                .get(MTIME_KEY)
+                .and_then(|v| v.parse::<i64>().ok());
+            let size = item
+                .metadata
+                .get(SIZE_KEY)
+                .and_then(|v| v.parse::<i64>().ok());
+            if let (Some(mtime), Some(size)) = (mtime, size) {
+                stmt.execute(params![name, item.uri, mtime, size])?;
+            }
+        }
+        Ok(())
+    }
 
-            if let Some(existing) = self.find_active_document(name, &item.uri)? {
-                if existing.hash != item.hash {
-                    self.insert_content(&item.hash, &item.content)?;
-                    self.update_document(existing.id, &item.title, &item.hash, &now)?;
-                    updated += 1;
+    /// Leave out web pages whose canonical URL another document already has
+    ///
+    /// The document indexed first keeps the URL, so a page reached through
+    /// several addresses or collections is stored once. Returns the kept
+    /// items and how many were left out.
+    fn drop_duplicate_urls(
+        &self,
+        name: &str,
+        items: Vec<crate::providers::SourceItem>,
+    ) -> Result<(Vec<crate::providers::SourceItem>, usize)> {
+        use crate::providers::CANONICAL_URL_KEY;
+
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT COUNT(*) > 0 FROM documents
+             WHERE canonical_url = ?1 AND active = 1
+               AND NOT (collection = ?2 AND path = ?3)",
+        )?;
+        let mut seen = std::collections::HashSet::new();
+        let mut kept = Vec::with_capacity(items.len());
+        let mut dropped = 0;
+        for item in items {
+            if let Some(canonical) = item.metadata.get(CANONICAL_URL_KEY) {
+                let indexed_elsewhere: bool =
+                    stmt.query_row(params![canonical, name, item.uri], |row| row.get(0))?;
+                if indexed_elsewhere || !seen.insert(canonical.clone()) {
+                    tracing::info!(
+                        "Skipping {}/{}: {} is already indexed",
+                        name,
+                        item.uri,
+                        canonical
+                    );
+                    dropped += 1;
+                    continue;
                 }
-            } else {
-                self.insert_content(&item.hash, &item.content)?;
-                self.insert_document(
-                    name,
-                    &item.uri,
-                    &item.title,
-                    &item.hash,
-                    &now,
-                    &now,
-                    &item.source_type,
-                    item.metadata.get("source_uri").map(|s| s.as_str()),
-                )?;
-                updated += 1;
+            }
+            kept.push(item);
+        }
+        Ok((kept, dropped))
+    }
+
+    /// Record the canonical URLs providers attached to the items just indexed
+    fn store_canonical_urls(
+        &self,
+        name: &str,
+        items: &[crate::providers::SourceItem],
+    ) -> Result<()> {
+        use crate::providers::CANONICAL_URL_KEY;
+
+        let mut stmt = self.conn.prepare_cached(
+            "UPDATE documents SET canonical_url = ?3
+             WHERE collection = ?1 AND path = ?2 AND active = 1",
+        )?;
+        for item in items {
+            if let Some(canonical) = item.metadata.get(CANONICAL_URL_KEY) {
+                stmt.execute(params![name, item.uri, canonical])?;
             }
         }
+        Ok(())
+    }
 
-        self.touch_collection(name)?;
-        Ok(updated)
+    /// Store one source item; returns whether it was new or changed
+    fn index_item(&self, name: &str, item: &crate::providers::SourceItem) -> Result<bool> {
+        let now = Utc::now().to_rfc3339();
+
+        let id = if let Some(existing) = self.find_active_document(name, &item.uri)? {
+            if existing.hash == item.hash {
+                return Ok(false);
+            }
+            self.insert_content(&item.hash, &item.content)?;
+            self.update_document(existing.id, &item.title, &item.hash, &now)?;
+            existing.id
+        } else {
+            self.insert_content(&item.hash, &item.content)?;
+            self.insert_document(
+                name,
+                &item.uri,
+                &item.title,
+                &item.hash,
+                &now,
+                &now,
+                &item.source_type,
+                item.metadata.get("source_uri").map(|s| s.as_str()),
+            )?
+        };
+        self.store_frontmatter(id, item)?;
+        self.refresh_note_date(id)?;
+        Ok(true)
+    }
+
+    /// Merge an item's extracted frontmatter into the document's user metadata
+    ///
+    /// Keys added by hand with `metadata add` survive; frontmatter keys are
+    /// overwritten on every reindex.
+    fn store_frontmatter(&self, doc_id: i64, item: &crate::providers::SourceItem) -> Result<()> {
+        let Some(json) = item.metadata.get(crate::providers::FRONTMATTER_KEY) else {
+            return Ok(());
+        };
+        let frontmatter = crate::db::UserMetadata::from_json(json)?;
+        let existing: Option<String> = self.conn.query_row(
+            "SELECT user_metadata FROM documents WHERE id = ?1",
+            params![doc_id],
+            |row| row.get(0),
+        )?;
+        let mut combined = existing
+            .and_then(|json| crate::db::UserMetadata::from_json(&json).ok())
+            .unwrap_or_default();
+        combined.merge(&frontmatter);
+        self.conn.execute(
+            "UPDATE documents SET user_metadata = ?1 WHERE id = ?2",
+            params![combined.to_json()?, doc_id],
+        )?;
+        Ok(())
     }
 
     /// Generate or fetch metadata from cache
@@ -280,6 +646,7 @@ impl Database {
     }
 
     /// Insert document with metadata
+    #[allow(clippy::too_many_arguments)]
     fn insert_document_with_metadata(
         &self,
         collection: &str,
@@ -417,56 +784,68 @@ impl Database {
         Ok(())
     }
 
-    /// Process and store chunks with LLM-generated metadata
-    async fn process_chunks_with_metadata(
+    /// Chunk a document and generate chunk metadata when an LLM client is given
+    async fn prepare_chunks(
         &self,
-        doc_hash: &str,
         content: &str,
         path: &str,
         chunk_generator: Option<&dyn crate::llm::LLMClient>,
-    ) -> Result<usize> {
+    ) -> Result<PreparedChunks> {
         use crate::index::ast_chunker::{language::Language, SemanticChunker};
-        use crate::llm::{generate_batch_chunk_metadata, ChunkMetadata};
+        use crate::llm::generate_batch_chunk_metadata;
         use std::path::Path;
 
+        // Parsing and hashing chunks is CPU-bound; run it on the blocking
+        // pool so the documents staged at once are chunked in parallel
+        let (owned_content, owned_path) = (content.to_string(), path.to_string());
+        let chunks = tokio::task::spawn_blocking(move || {
+            SemanticChunker::new().chunk(&owned_content, Path::new(&owned_path))
+        })
+        .await
+        .map_err(|e| {
+            crate::error::AgentRootError::Index(format!("Chunking {} failed: {}", path, e))
+        })??;
+
+        // Generate metadata for all chunks if LLM client is provided
+        let metadata = match chunk_generator {
+            Some(client) if !chunks.is_empty() => {
+                let language = Language::from_path(Path::new(path)).map(|l| l.as_str());
+                match generate_batch_chunk_metadata(&chunks, path, language, client).await {
+                    Ok(meta) => Some(meta),
+                    Err(e) => {
+                        tracing::warn!("Failed to generate chunk metadata for {}: {}", path, e);
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        Ok(PreparedChunks { chunks, metadata })
+    }
+
+    /// Replace a document's stored chunks
+    fn store_chunks(&self, doc_hash: &str, prepared: &PreparedChunks) -> Result<usize> {
+        use crate::llm::ChunkMetadata;
+
         // Delete old chunks for this document (in case of re-indexing)
         self.delete_chunks_for_document(doc_hash)?;
 
-        // Create semantic chunks
-        let chunker = SemanticChunker::new();
-        let semantic_chunks = chunker.chunk(content, Path::new(path))?;
-
-        if semantic_chunks.is_empty() {
+        if prepared.chunks.is_empty() {
             tracing::debug!("No chunks created for document {}", doc_hash);
             return Ok(0);
         }
 
         let now = Utc::now().to_rfc3339();
         let mut chunks_inserted = 0;
-
-        // Generate metadata for all chunks if LLM client is provided
-        let metadata_list: Option<Vec<ChunkMetadata>> = if let Some(client) = chunk_generator {
-            let language = Language::from_path(Path::new(path)).map(|l| l.as_str());
-
-            match generate_batch_chunk_metadata(&semantic_chunks, path, language, client).await {
-                Ok(meta) => Some(meta),
-                Err(e) => {
-                    tracing::warn!("Failed to generate chunk metadata for {}: {}", path, e);
-                    None
-                }
-            }
-        } else {
-            None
-        };
+        let metadata_list = prepared.metadata.as_ref();
 
         // Insert chunks with or without metadata
-        for (seq, chunk) in semantic_chunks.iter().enumerate() {
+        for (seq, chunk) in prepared.chunks.iter().enumerate() {
             let chunk_hash = chunk.chunk_hash.clone();
 
             // Get metadata for this chunk if available
-            let chunk_meta = metadata_list
-                .as_ref()
-                .and_then(|list: &Vec<ChunkMetadata>| list.get(seq));
+            let chunk_meta = metadata_list.and_then(|list: &Vec<ChunkMetadata>| list.get(seq));
 
             // Extract metadata fields
             let (summary, purpose, concepts, labels, model_name) = if let Some(meta) = chunk_meta {
@@ -497,7 +876,7 @@ impl Database {
                 purpose,
                 concepts,
                 labels,
-                &vec![], // related_to - can be populated later via semantic analysis
+                &[], // related_to - can be populated later via semantic analysis
                 model_name,
                 if chunk_meta.is_some() {
                     Some(&now)
@@ -506,6 +885,7 @@ impl Database {
                 },
                 &now,
             )?;
+            self.store_chunk_symbols(chunk)?;
 
             chunks_inserted += 1;
         }
@@ -526,6 +906,20 @@ impl Database {
         name: &str,
         generator: Option<&dyn crate::llm::MetadataGenerator>,
     ) -> Result<usize> {
+        let stats = self
+            .reindex_collection_with_metadata_progress(name, generator, &NoProgress)
+            .await?;
+        Ok(stats.updated)
+    }
+
+    /// Reindex with optional metadata generation, reporting per-item progress
+    pub async fn reindex_collection_with_metadata_progress(
+        &self,
+        name: &str,
+        generator: Option<&dyn crate::llm::MetadataGenerator>,
+        progress: &dyn ProgressSink,
+    ) -> Result<ReindexStats> {
+        self.ensure_writable()?;
         let coll = self
             .get_collection(name)?
             .ok_or_else(|| crate::error::AgentRootError::CollectionNotFound(name.to_string()))?;
@@ -550,133 +944,233 @@ impl Database {
                 }
             }
         }
+        let config = config.with_known_stamps(self.known_stamps(name, generator.is_some())?);
+        let scanner = crate::index::SecretScanner::from_config(&config)?;
+
+        let listing = provider
+            .scan(&config)
+            .instrument(tracing::info_span!(
+                "provider_fetch",
+                collection = name,
+                provider = coll.provider_type.as_str()
+            ))
+            .await?;
+        let skipped = listing.skipped.len();
+        let unchanged = listing.unchanged;
+        let (items, secrets) = prepare_items(name, listing.items, &scanner);
+        let (items, duplicates) = self.drop_duplicate_urls(name, items)?;
+        let mut tracker = ProgressTracker::new(progress, "index");
+        tracker.discovered(items.len());
+
+        // Stage first: metadata and chunk generation can take minutes, and
+        // nothing is written until every item is ready. Up to the worker
+        // count of items are staged at once, so LLM calls overlap and their
+        // chunking runs on that many blocking threads.
+        let coll = &coll;
+        let mut staging = futures::stream::iter(&items)
+            .map(|item| async move { (item, self.stage_item(name, coll, item, generator).await) })
+            .buffered(crate::index::index_workers());
+        let mut staged = Vec::new();
+        while let Some((item, result)) = staging.next().await {
+            match result {
+                Ok(item_staged) => {
+                    staged.extend(item_staged);
+                    tracker.processed(item.content.len() as u64, Some(item.uri.clone()));
+                }
+                Err(e) => {
+                    tracker.failed(Some(item.uri.clone()));
+                    return Err(e);
+                }
+            }
+        }
 
-        let items = provider.list_items(&config).await?;
-        let mut updated = 0;
-
-        for item in items {
-            let now = Utc::now().to_rfc3339();
+        // Then apply everything at once, so searches never see a half-updated collection
+        let model_name = generator.map(|g| g.model_name());
+        let deleted = self.in_transaction(|| {
+            for item in &staged {
+                self.apply_staged_item(name, item, model_name)?;
+            }
+            let deleted = self.deactivate_missing(name, &items, &unchanged)?;
+            if !staged.is_empty() || deleted > 0 {
+                self.rebuild_vocabulary(name)?;
+            }
+            self.store_file_stamps(name, &items)?;
+            self.store_canonical_urls(name, &items)?;
+            self.touch_collection(name)?;
+            Ok(deleted)
+        })?;
 
-            if let Some(existing) = self.find_active_document(name, &item.uri)? {
-                let content_changed = existing.hash != item.hash;
-                let needs_metadata = existing.llm_model.is_none() && generator.is_some();
+        let stats = ReindexStats {
+            updated: staged.len(),
+            deleted,
+            skipped,
+            secrets,
+            duplicates,
+        };
+        tracker.finish();
+        self.note_index_changes(stats.changes())?;
+        Ok(stats)
+    }
 
-                if content_changed || needs_metadata {
-                    if content_changed {
-                        self.insert_content(&item.hash, &item.content)?;
-                    }
+    /// Index one item into a collection right away, outside a reindex
+    ///
+    /// The item goes through the same redaction, frontmatter and chunking as
+    /// during a reindex and replaces any active document at `item.uri`.
+    /// Returns false when that document already has the same content.
+    pub async fn ingest_item(
+        &self,
+        name: &str,
+        item: crate::providers::SourceItem,
+    ) -> Result<bool> {
+        self.ensure_writable()?;
+        let coll = self
+            .get_collection(name)?
+            .ok_or_else(|| crate::error::AgentRootError::CollectionNotFound(name.to_string()))?;
 
-                    let metadata_opt = if generator.is_some() {
-                        let context = self.build_metadata_context(&item, name, &coll);
-                        self.generate_or_fetch_metadata(
-                            &item.hash,
-                            &item.content,
-                            context,
-                            generator,
-                        )
-                        .await?
-                    } else {
-                        None
-                    };
-
-                    if let Some(metadata) = metadata_opt {
-                        self.update_document_with_metadata(
-                            existing.id,
-                            &item.title,
-                            &item.hash,
-                            &now,
-                            &metadata,
-                            generator.unwrap().model_name(),
-                        )?;
-
-                        // Process chunks with LLM metadata
-                        let llm_client = generator.and_then(|g| g.llm_client());
-                        self.process_chunks_with_metadata(
-                            &item.hash,
-                            &item.content,
-                            &item.uri,
-                            llm_client,
-                        )
-                        .await?;
-
-                        // Extract and link concepts to chunks
-                        self.extract_and_link_concepts(&item.hash, &metadata)?;
-                    } else {
-                        self.update_document(existing.id, &item.title, &item.hash, &now)?;
-
-                        // Still create chunks without LLM metadata
-                        self.process_chunks_with_metadata(
-                            &item.hash,
-                            &item.content,
-                            &item.uri,
-                            None,
-                        )
-                        .await?;
-                    }
-                    updated += 1;
+        let mut config =
+            crate::providers::ProviderConfig::new(coll.path.clone(), coll.pattern.clone());
+        if let Some(provider_config) = &coll.provider_config {
+            if let Ok(config_map) =
+                serde_json::from_str::<std::collections::HashMap<String, String>>(provider_config)
+            {
+                for (key, value) in config_map {
+                    config = config.with_option(key, value);
                 }
-            } else {
-                self.insert_content(&item.hash, &item.content)?;
+            }
+        }
+        let scanner = crate::index::SecretScanner::from_config(&config)?;
+        let (items, _) = prepare_items(name, vec![item], &scanner);
 
-                let metadata_opt = if generator.is_some() {
-                    let context = self.build_metadata_context(&item, name, &coll);
-                    self.generate_or_fetch_metadata(&item.hash, &item.content, context, generator)
-                        .await?
-                } else {
-                    None
-                };
+        let Some(staged) = self.stage_item(name, &coll, &items[0], None).await? else {
+            return Ok(false);
+        };
+        self.in_transaction(|| {
+            self.apply_staged_item(name, &staged, None)?;
+            self.rebuild_vocabulary(name)?;
+            self.touch_collection(name)
+        })?;
+        self.note_index_changes(1)?;
+        Ok(true)
+    }
 
-                if let Some(metadata) = metadata_opt {
-                    self.insert_document_with_metadata(
-                        name,
-                        &item.uri,
-                        &item.title,
-                        &item.hash,
-                        &now,
-                        &now,
-                        &item.source_type,
-                        item.metadata.get("source_uri").map(|s| s.as_str()),
-                        &metadata,
-                        generator.unwrap().model_name(),
-                    )?;
-
-                    // Process chunks with LLM metadata
-                    let llm_client = generator.and_then(|g| g.llm_client());
-                    self.process_chunks_with_metadata(
-                        &item.hash,
-                        &item.content,
-                        &item.uri,
-                        llm_client,
-                    )
-                    .await?;
-
-                    // Extract and link concepts to chunks
-                    self.extract_and_link_concepts(&item.hash, &metadata)?;
-                } else {
-                    self.insert_document(
-                        name,
-                        &item.uri,
-                        &item.title,
-                        &item.hash,
-                        &now,
-                        &now,
-                        &item.source_type,
-                        item.metadata.get("source_uri").map(|s| s.as_str()),
-                    )?;
-
-                    // Still create chunks without LLM metadata
-                    self.process_chunks_with_metadata(&item.hash, &item.content, &item.uri, None)
-                        .await?;
-                }
-                updated += 1;
+    /// Generate metadata and chunks for a new or changed item without writing it
+    ///
+    /// Returns None when the item is unchanged and already has metadata.
+    async fn stage_item<'a>(
+        &self,
+        name: &str,
+        coll: &CollectionInfo,
+        item: &'a crate::providers::SourceItem,
+        generator: Option<&dyn crate::llm::MetadataGenerator>,
+    ) -> Result<Option<StagedItem<'a>>> {
+        let existing = self.find_active_document(name, &item.uri)?;
+        if let Some(existing) = &existing {
+            let content_changed = existing.hash != item.hash;
+            let needs_metadata = existing.llm_model.is_none() && generator.is_some();
+            if !content_changed && !needs_metadata {
+                return Ok(None);
             }
         }
 
-        self.touch_collection(name)?;
-        Ok(updated)
+        let metadata = if generator.is_some() {
+            let context = self.build_metadata_context(item, name, coll);
+            self.generate_or_fetch_metadata(&item.hash, &item.content, context, generator)
+                .await?
+        } else {
+            None
+        };
+
+        // Chunk metadata is only generated alongside document metadata
+        let chunk_client = generator
+            .filter(|_| metadata.is_some())
+            .and_then(|g| g.llm_client());
+        let chunks = self
+            .prepare_chunks(&item.content, &item.uri, chunk_client)
+            .await?;
+
+        Ok(Some(StagedItem {
+            item,
+            existing_id: existing.map(|d| d.id),
+            metadata,
+            chunks,
+        }))
+    }
+
+    /// Write a staged item's content, document row, chunks and concepts
+    fn apply_staged_item(
+        &self,
+        name: &str,
+        staged: &StagedItem,
+        model_name: Option<&str>,
+    ) -> Result<()> {
+        let item = staged.item;
+        let now = Utc::now().to_rfc3339();
+        self.insert_content(&item.hash, &item.content)?;
+
+        let id = match (staged.existing_id, &staged.metadata) {
+            (Some(id), Some(metadata)) => {
+                self.update_document_with_metadata(
+                    id,
+                    &item.title,
+                    &item.hash,
+                    &now,
+                    metadata,
+                    model_name.unwrap_or_default(),
+                )?;
+                id
+            }
+            (Some(id), None) => {
+                self.update_document(id, &item.title, &item.hash, &now)?;
+                id
+            }
+            (None, Some(metadata)) => self.insert_document_with_metadata(
+                name,
+                &item.uri,
+                &item.title,
+                &item.hash,
+                &now,
+                &now,
+                &item.source_type,
+                item.metadata.get("source_uri").map(|s| s.as_str()),
+                metadata,
+                model_name.unwrap_or_default(),
+            )?,
+            (None, None) => self.insert_document(
+                name,
+                &item.uri,
+                &item.title,
+                &item.hash,
+                &now,
+                &now,
+                &item.source_type,
+                item.metadata.get("source_uri").map(|s| s.as_str()),
+            )?,
+        };
+        self.store_frontmatter(id, item)?;
+        self.refresh_note_date(id)?;
+
+        self.store_chunks(&item.hash, &staged.chunks)?;
+        if let Some(metadata) = &staged.metadata {
+            self.extract_and_link_concepts(&item.hash, metadata)?;
+        }
+        Ok(())
     }
 }
 
+/// A new or changed item with everything generated for it, ready to write
+struct StagedItem<'a> {
+    item: &'a crate::providers::SourceItem,
+    existing_id: Option<i64>,
+    metadata: Option<crate::llm::DocumentMetadata>,
+    chunks: PreparedChunks,
+}
+
+/// Semantic chunks of a document and their optional LLM metadata
+struct PreparedChunks {
+    chunks: Vec<crate::index::ast_chunker::SemanticChunk>,
+    metadata: Option<Vec<crate::llm::ChunkMetadata>>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -934,6 +1428,73 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_cancelled_reindex_changes_nothing() {
+        let temp = tempfile::TempDir::new().unwrap();
+        for i in 0..3 {
+            std::fs::write(temp.path().join(format!("doc{}.md", i)), "# Doc").unwrap();
+        }
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.add_collection(
+            "test",
+            &temp.path().to_string_lossy(),
+            "**/*.md",
+            "file",
+            None,
+        )
+        .unwrap();
+
+        let cancel = CancellationToken::new();
+        let stop_after_first = |u: &crate::progress::ProgressUpdate| {
+            if u.processed == 1 {
+                cancel.cancel();
+            }
+        };
+        let result = db
+            .reindex_collection_cancellable("test", &stop_after_first, &cancel)
+            .await;
+        assert!(matches!(
+            result,
+            Err(crate::error::AgentRootError::Cancelled(_))
+        ));
+        assert_eq!(db.list_collections().unwrap()[0].document_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_ingested_item_survives_reindex() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp.path().join("doc.md"), "# Doc").unwrap();
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.add_collection(
+            "test",
+            &temp.path().to_string_lossy(),
+            "**/*.md",
+            "file",
+            None,
+        )
+        .unwrap();
+
+        let content = "# Meeting notes\n\nWe agreed to ship on Friday.".to_string();
+        let hash = crate::db::hash_content(&content);
+        let item = crate::providers::SourceItem::new(
+            "notes/meeting.md".to_string(),
+            "Meeting notes".to_string(),
+            content,
+            hash.clone(),
+            INGESTED_SOURCE_TYPE.to_string(),
+        );
+        assert!(db.ingest_item("test", item.clone()).await.unwrap());
+        assert!(!db.ingest_item("test", item).await.unwrap());
+        assert!(!db.get_chunks_for_document(&hash).unwrap().is_empty());
+
+        db.reindex_collection("test").await.unwrap();
+        let doc = db.find_active_document("test", "notes/meeting.md").unwrap();
+        assert_eq!(doc.unwrap().hash, hash);
+        assert_eq!(db.list_collections().unwrap()[0].document_count, 2);
+    }
+
     #[tokio::test]
     async fn test_reindex_invalid_provider_type() {
         let db = Database::open_in_memory().unwrap();
@@ -1009,4 +1570,475 @@ mod tests {
             "Should succeed despite malformed JSON config (uses defaults)"
         );
     }
+
+    #[tokio::test]
+    async fn test_reindex_reports_progress() {
+        use crate::progress::ProgressUpdate;
+        use std::sync::Mutex;
+
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp.path().join("a.md"), "# A\nalpha").unwrap();
+        std::fs::write(temp.path().join("b.md"), "# B\nbeta").unwrap();
+
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.add_collection(
+            "notes",
+            temp.path().to_str().unwrap(),
+            "**/*.md",
+            "file",
+            None,
+        )
+        .unwrap();
+
+        let last: Mutex<Option<ProgressUpdate>> = Mutex::new(None);
+        let sink = |u: &ProgressUpdate| *last.lock().unwrap() = Some(u.clone());
+
+        let stats = db
+            .reindex_collection_with_metadata_progress("notes", None, &sink)
+            .await
+            .unwrap();
+        assert_eq!(stats.updated, 2);
+
+        let last = last.lock().unwrap().clone().unwrap();
+        assert_eq!(last.operation, "index");
+        assert_eq!(last.discovered, 2);
+        assert_eq!(last.processed, 2);
+        assert_eq!(last.failed, 0);
+        assert!(last.bytes > 0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_reindex_leaves_index_unchanged() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("a.md"), "# A\n\nfirst version").unwrap();
+        std::fs::write(temp.path().join("b.md"), "# B\n\nfirst version").unwrap();
+
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.add_collection(
+            "notes",
+            temp.path().to_str().unwrap(),
+            "**/*.md",
+            "file",
+            None,
+        )
+        .unwrap();
+        db.reindex_collection("notes").await.unwrap();
+        let before = db.find_active_document("notes", "a.md").unwrap().unwrap();
+
+        std::fs::write(temp.path().join("a.md"), "# A\n\nsecond version").unwrap();
+        std::fs::write(temp.path().join("b.md"), "# B\n\nsecond version").unwrap();
+        db.conn
+            .execute_batch(
+                "CREATE TEMP TRIGGER fail_b BEFORE UPDATE ON documents WHEN old.path = 'b.md'
+                 BEGIN SELECT RAISE(ABORT, 'simulated failure'); END;",
+            )
+            .unwrap();
+
+        assert!(db.reindex_collection("notes").await.is_err());
+        assert!(db
+            .reindex_collection_with_metadata("notes", None)
+            .await
+            .is_err());
+
+        // Neither run left a.md half-updated
+        let after = db.find_active_document("notes", "a.md").unwrap().unwrap();
+        assert_eq!(after.hash, before.hash);
+
+        db.conn.execute_batch("DROP TRIGGER fail_b").unwrap();
+        assert_eq!(db.reindex_collection("notes").await.unwrap(), 2);
+    }
+
+    struct FixedMetadata;
+
+    #[async_trait::async_trait]
+    impl crate::llm::MetadataGenerator for FixedMetadata {
+        async fn generate_metadata(
+            &self,
+            _content: &str,
+            _context: &crate::llm::MetadataContext,
+        ) -> Result<crate::llm::DocumentMetadata> {
+            Ok(crate::llm::DocumentMetadata::new())
+        }
+
+        fn model_name(&self) -> &str {
+            "fixed"
+        }
+
+        fn llm_client(&self) -> Option<&dyn crate::llm::LLMClient> {
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_metadata_reindex_writes_nothing() {
+        let temp = tempfile::tempdir().unwrap();
+        let total = 20;
+        for i in 0..total {
+            std::fs::write(
+                temp.path().join(format!("doc{:02}.md", i)),
+                format!("# Doc {}", i),
+            )
+            .unwrap();
+        }
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.add_collection(
+            "notes",
+            temp.path().to_str().unwrap(),
+            "**/*.md",
+            "file",
+            None,
+        )
+        .unwrap();
+        let last = format!("doc{:02}.md", total - 1);
+        db.conn
+            .execute_batch(&format!(
+                "CREATE TEMP TRIGGER fail_last BEFORE INSERT ON documents WHEN new.path = '{}'
+                 BEGIN SELECT RAISE(ABORT, 'simulated failure'); END;",
+                last
+            ))
+            .unwrap();
+
+        let with_metadata = |db: &Database| -> usize {
+            db.conn
+                .query_row(
+                    "SELECT COUNT(*) FROM documents WHERE llm_model IS NOT NULL AND active = 1",
+                    [],
+                    |row| row.get::<_, i64>(0),
+                )
+                .unwrap() as usize
+        };
+        assert!(db
+            .reindex_collection_with_metadata("notes", Some(&FixedMetadata))
+            .await
+            .is_err());
+        assert_eq!(with_metadata(&db), 0);
+
+        db.conn.execute_batch("DROP TRIGGER fail_last").unwrap();
+        let updated = db
+            .reindex_collection_with_metadata("notes", Some(&FixedMetadata))
+            .await
+            .unwrap();
+        assert_eq!(updated, total);
+        assert_eq!(with_metadata(&db), total);
+    }
+
+    #[tokio::test]
+    async fn test_reindex_deactivates_removed_files() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("a.md"), "# A\n\nalpha").unwrap();
+        std::fs::write(temp.path().join("b.md"), "# B\n\nbeta").unwrap();
+        std::fs::write(temp.path().join("c.md"), "# C\n\ngamma").unwrap();
+
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.add_collection(
+            "notes",
+            temp.path().to_str().unwrap(),
+            "**/*.md",
+            "file",
+            None,
+        )
+        .unwrap();
+        db.reindex_collection("notes").await.unwrap();
+
+        std::fs::remove_file(temp.path().join("b.md")).unwrap();
+        let stats = db
+            .reindex_collection_with_progress("notes", &NoProgress)
+            .await
+            .unwrap();
+        assert_eq!(
+            stats,
+            ReindexStats {
+                updated: 0,
+                deleted: 1,
+                ..Default::default()
+            }
+        );
+        assert!(db.find_active_document("notes", "b.md").unwrap().is_none());
+        assert!(db.find_active_document("notes", "a.md").unwrap().is_some());
+
+        std::fs::remove_file(temp.path().join("c.md")).unwrap();
+        let stats = db
+            .reindex_collection_with_metadata_progress("notes", None, &NoProgress)
+            .await
+            .unwrap();
+        assert_eq!(
+            stats,
+            ReindexStats {
+                updated: 0,
+                deleted: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            db.get_collection("notes").unwrap().unwrap().document_count,
+            1
+        );
+
+        // A restored file comes back as a new document
+        std::fs::write(temp.path().join("b.md"), "# B\n\nbeta").unwrap();
+        let stats = db
+            .reindex_collection_with_progress("notes", &NoProgress)
+            .await
+            .unwrap();
+        assert_eq!(
+            stats,
+            ReindexStats {
+                updated: 1,
+                deleted: 0,
+                ..Default::default()
+            }
+        );
+        assert!(db.find_active_document("notes", "b.md").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_drop_duplicate_urls() {
+        use crate::providers::{SourceItem, CANONICAL_URL_KEY};
+
+        let page = |uri: &str, canonical: &str| {
+            let mut item = SourceItem::new(
+                uri.to_string(),
+                "Post".to_string(),
+                "body".to_string(),
+                crate::db::hash_content(uri),
+                "url".to_string(),
+            );
+            item.metadata
+                .insert(CANONICAL_URL_KEY.to_string(), canonical.to_string());
+            item
+        };
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        for name in ["news", "blog"] {
+            db.add_collection(name, "https://example.com", "", "url", None)
+                .unwrap();
+        }
+
+        let first = page(
+            "https://example.com/post?utm_source=rss",
+            "https://example.com/post",
+        );
+        db.index_item("news", &first).unwrap();
+        db.store_canonical_urls("news", std::slice::from_ref(&first))
+            .unwrap();
+
+        // Reindexing the same page is not a duplicate
+        let (kept, dropped) = db.drop_duplicate_urls("news", vec![first]).unwrap();
+        assert_eq!((kept.len(), dropped), (1, 0));
+
+        // The same page under another address or collection is
+        let (kept, dropped) = db
+            .drop_duplicate_urls(
+                "blog",
+                vec![
+                    page("https://example.com/post/", "https://example.com/post"),
+                    page("https://example.com/other", "https://example.com/other"),
+                    page("https://example.com/other#top", "https://example.com/other"),
+                ],
+            )
+            .unwrap();
+        assert_eq!(dropped, 2);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].uri, "https://example.com/other");
+    }
+
+    #[tokio::test]
+    async fn test_reindex_stores_frontmatter_as_user_metadata() {
+        use crate::db::{MetadataBuilder, MetadataFilter, MetadataValue};
+
+        let temp = tempfile::tempdir().unwrap();
+        let note = temp.path().join("note.md");
+        std::fs::write(
+            &note,
+            "---\ntags: [rust]\nstatus: draft\n---\n# Note\n\nbody",
+        )
+        .unwrap();
+
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.add_collection(
+            "notes",
+            temp.path().to_str().unwrap(),
+            "**/*.md",
+            "file",
+            None,
+        )
+        .unwrap();
+        db.reindex_collection("notes").await.unwrap();
+
+        let doc = db
+            .find_active_document("notes", "note.md")
+            .unwrap()
+            .unwrap();
+        assert_eq!(doc.title, "Note");
+        let body = db.get_content(&doc.hash).unwrap().unwrap();
+        assert_eq!(body, "# Note\n\nbody");
+        let found = db
+            .find_by_metadata(
+                &MetadataFilter::TagsContain("tags".into(), "rust".into()),
+                10,
+            )
+            .unwrap();
+        assert_eq!(found.len(), 1);
+
+        // Hand-added keys survive; frontmatter edits alone trigger a reindex
+        let docid = &doc.hash[..6];
+        db.add_metadata(docid, &MetadataBuilder::new().text("owner", "me").build())
+            .unwrap();
+        std::fs::write(
+            &note,
+            "---\ntags: [rust]\nstatus: done\n---\n# Note\n\nbody",
+        )
+        .unwrap();
+        let stats = db
+            .reindex_collection_with_metadata_progress("notes", None, &NoProgress)
+            .await
+            .unwrap();
+        assert_eq!(stats.updated, 1);
+        let doc = db
+            .find_active_document("notes", "note.md")
+            .unwrap()
+            .unwrap();
+        let metadata = db.get_metadata(&doc.hash[..6]).unwrap().unwrap();
+        assert_eq!(
+            metadata.get("status"),
+            Some(&MetadataValue::Text("done".into()))
+        );
+        assert_eq!(
+            metadata.get("owner"),
+            Some(&MetadataValue::Text("me".into()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reindex_skips_files_with_unchanged_stamp() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("a.md");
+        std::fs::write(&path, "# A\n\nfirst").unwrap();
+        std::fs::write(temp.path().join("b.md"), "# B\n\nother").unwrap();
+
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.add_collection(
+            "notes",
+            temp.path().to_str().unwrap(),
+            "**/*.md",
+            "file",
+            None,
+        )
+        .unwrap();
+        assert_eq!(db.reindex_collection("notes").await.unwrap(), 2);
+        assert_eq!(db.known_stamps("notes", false).unwrap().len(), 2);
+
+        // Same size and mtime: the new content is never read
+        let mtime = std::fs::metadata(&path).unwrap().modified().unwrap();
+        std::fs::write(&path, "# A\n\nfresh").unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(mtime).unwrap();
+        let stats = db
+            .reindex_collection_with_progress("notes", &NoProgress)
+            .await
+            .unwrap();
+        assert_eq!(stats.changes(), 0);
+
+        // A newer mtime is picked up
+        file.set_modified(mtime + std::time::Duration::from_secs(5))
+            .unwrap();
+        let stats = db
+            .reindex_collection_with_metadata_progress("notes", None, &NoProgress)
+            .await
+            .unwrap();
+        assert_eq!(stats.updated, 1);
+        let doc = db.find_active_document("notes", "a.md").unwrap().unwrap();
+        assert!(db
+            .get_content(&doc.hash)
+            .unwrap()
+            .unwrap()
+            .contains("fresh"));
+    }
+
+    #[tokio::test]
+    async fn test_reindex_skips_binary_files() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("notes.md"), "# Notes\n\ntext").unwrap();
+        std::fs::write(
+            temp.path().join("logo.png"),
+            b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR",
+        )
+        .unwrap();
+
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.add_collection("all", temp.path().to_str().unwrap(), "**/*", "file", None)
+            .unwrap();
+
+        let stats = db
+            .reindex_collection_with_metadata_progress("all", None, &NoProgress)
+            .await
+            .unwrap();
+        assert_eq!(stats.updated, 1);
+        assert_eq!(stats.skipped, 1);
+        assert!(db
+            .find_active_document("all", "logo.png")
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_reindex_redacts_secrets() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp.path().join("deploy.md"),
+            "# Deploy
+
+aws_access_key_id = AKIAZ7Q3EXAMPLEKEY12
+",
+        )
+        .unwrap();
+
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let path = temp.path().to_str().unwrap();
+        db.add_collection("redact", path, "**/*.md", "file", None)
+            .unwrap();
+        db.add_collection(
+            "report",
+            path,
+            "**/*.md",
+            "file",
+            Some(r#"{"secrets": "report"}"#),
+        )
+        .unwrap();
+
+        let stats = db
+            .reindex_collection_with_progress("redact", &NoProgress)
+            .await
+            .unwrap();
+        assert_eq!(stats.secrets, 1);
+        let doc = db
+            .find_active_document("redact", "deploy.md")
+            .unwrap()
+            .unwrap();
+        let body = db.get_content(&doc.hash).unwrap().unwrap();
+        assert_eq!(
+            body,
+            "# Deploy\n\naws_access_key_id = [REDACTED:aws_access_key_id]\n"
+        );
+
+        let stats = db
+            .reindex_collection_with_metadata_progress("report", None, &NoProgress)
+            .await
+            .unwrap();
+        assert_eq!(stats.secrets, 1);
+        let doc = db
+            .find_active_document("report", "deploy.md")
+            .unwrap()
+            .unwrap();
+        let body = db.get_content(&doc.hash).unwrap().unwrap();
+        assert!(body.contains("AKIAZ7Q3EXAMPLEKEY12"));
+    }
 }
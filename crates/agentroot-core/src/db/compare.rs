@@ -0,0 +1,356 @@
+//! Pairwise document comparison
+//!
+//! Reports how alike two documents are: cosine similarity of their
+//! chunk-embedding centroids, the glossary concepts they share, and the
+//! passages of one that reappear (verbatim or lightly edited) in the other.
+//! Passages are AST chunks for supported code and paragraphs otherwise, and
+//! are matched by word-trigram Jaccard similarity.
+
+use super::content::docid_from_hash;
+use super::vectors::{bytes_to_embedding, cosine_similarity};
+use super::Database;
+use crate::error::{AgentRootError, Result};
+use crate::index::ast_chunker::{chunk_semantic, is_supported};
+use rusqlite::params;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Passages below this similarity are not reported as overlapping
+const OVERLAP_THRESHOLD: f64 = 0.5;
+
+/// Passages with fewer words (headings, braces) are ignored
+const MIN_PASSAGE_WORDS: usize = 8;
+
+/// One side of a comparison
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ComparedDocument {
+    pub docid: String,
+    pub collection: String,
+    pub path: String,
+    pub title: String,
+}
+
+/// A passage of the first document that reappears in the second
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ChunkOverlap {
+    pub a_start_line: usize,
+    pub a_end_line: usize,
+    pub b_start_line: usize,
+    pub b_end_line: usize,
+    /// Word-trigram Jaccard similarity, 1.0 for identical text
+    pub similarity: f64,
+    /// Same text up to whitespace and case
+    pub identical: bool,
+    /// First line of the passage in the first document
+    pub preview: String,
+}
+
+/// How alike two documents are
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DocumentComparison {
+    pub a: ComparedDocument,
+    pub b: ComparedDocument,
+    /// Cosine similarity of chunk-embedding centroids; None unless both are embedded
+    pub embedding_similarity: Option<f64>,
+    /// Word-trigram Jaccard similarity of the whole texts
+    pub text_overlap: f64,
+    /// Jaccard overlap of glossary concepts
+    pub concept_overlap: f64,
+    pub shared_concepts: Vec<String>,
+    /// Best match in the second document for each passage of the first, most similar first
+    pub overlapping_chunks: Vec<ChunkOverlap>,
+}
+
+struct Passage {
+    start_line: usize,
+    end_line: usize,
+    normalized: String,
+    shingles: HashSet<String>,
+    preview: String,
+}
+
+struct LoadedDocument {
+    info: ComparedDocument,
+    hash: String,
+    body: String,
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn shingles(words: &[String]) -> HashSet<String> {
+    if words.len() < 3 {
+        return words.iter().cloned().collect();
+    }
+    words.windows(3).map(|w| w.join(" ")).collect()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+fn passage(text: &str, start_line: usize, end_line: usize) -> Option<Passage> {
+    let words = words(text);
+    (words.len() >= MIN_PASSAGE_WORDS).then(|| Passage {
+        start_line,
+        end_line,
+        normalized: words.join(" "),
+        shingles: shingles(&words),
+        preview: text
+            .lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty())
+            .unwrap_or_default()
+            .to_string(),
+    })
+}
+
+/// Split a document into comparable passages
+fn passages(body: &str, path: &str) -> Vec<Passage> {
+    let path = Path::new(path);
+    if is_supported(path) {
+        if let Ok(chunks) = chunk_semantic(body, path) {
+            return chunks
+                .iter()
+                .filter_map(|c| passage(&c.text, c.metadata.start_line, c.metadata.end_line))
+                .collect();
+        }
+    }
+
+    let mut result = Vec::new();
+    let mut start = 0;
+    let mut block: Vec<&str> = Vec::new();
+    for (i, line) in body.lines().enumerate() {
+        if line.trim().is_empty() {
+            if !block.is_empty() {
+                result.extend(passage(&block.join("\n"), start + 1, i));
+                block.clear();
+            }
+            continue;
+        }
+        if block.is_empty() {
+            start = i;
+        }
+        block.push(line);
+    }
+    if !block.is_empty() {
+        result.extend(passage(&block.join("\n"), start + 1, start + block.len()));
+    }
+    result
+}
+
+impl Database {
+    /// Compare two documents given by docid, virtual path or collection/path
+    pub fn compare(&self, docid_a: &str, docid_b: &str) -> Result<DocumentComparison> {
+        let a = self.load_compared_document(docid_a)?;
+        let b = self.load_compared_document(docid_b)?;
+
+        let embedding_similarity = match (
+            self.document_centroid(&a.hash)?,
+            self.document_centroid(&b.hash)?,
+        ) {
+            (Some(ca), Some(cb)) if ca.len() == cb.len() => {
+                Some(cosine_similarity(&ca, &cb) as f64)
+            }
+            _ => None,
+        };
+
+        let concepts_a: HashSet<String> = self
+            .get_concepts_for_document(&a.hash)?
+            .into_iter()
+            .collect();
+        let concepts_b: HashSet<String> = self
+            .get_concepts_for_document(&b.hash)?
+            .into_iter()
+            .collect();
+        let mut shared_concepts: Vec<String> =
+            concepts_a.intersection(&concepts_b).cloned().collect();
+        shared_concepts.sort();
+
+        let text_overlap = jaccard(&shingles(&words(&a.body)), &shingles(&words(&b.body)));
+
+        let passages_b = passages(&b.body, &b.info.path);
+        let mut overlapping_chunks: Vec<ChunkOverlap> = passages(&a.body, &a.info.path)
+            .iter()
+            .filter_map(|pa| {
+                passages_b
+                    .iter()
+                    .map(|pb| (pb, jaccard(&pa.shingles, &pb.shingles)))
+                    .filter(|(_, similarity)| *similarity >= OVERLAP_THRESHOLD)
+                    .max_by(|x, y| x.1.total_cmp(&y.1))
+                    .map(|(pb, similarity)| ChunkOverlap {
+                        a_start_line: pa.start_line,
+                        a_end_line: pa.end_line,
+                        b_start_line: pb.start_line,
+                        b_end_line: pb.end_line,
+                        similarity,
+                        identical: pa.normalized == pb.normalized,
+                        preview: pa.preview.clone(),
+                    })
+            })
+            .collect();
+        overlapping_chunks.sort_by(|x, y| {
+            y.similarity
+                .total_cmp(&x.similarity)
+                .then(x.a_start_line.cmp(&y.a_start_line))
+        });
+
+        Ok(DocumentComparison {
+            a: a.info,
+            b: b.info,
+            embedding_similarity,
+            text_overlap,
+            concept_overlap: jaccard(&concepts_a, &concepts_b),
+            shared_concepts,
+            overlapping_chunks,
+        })
+    }
+
+    fn load_compared_document(&self, query: &str) -> Result<LoadedDocument> {
+        let id = self
+            .resolve_document_id(query)?
+            .ok_or_else(|| AgentRootError::DocumentNotFound(query.to_string()))?;
+        Ok(self.conn.query_row(
+            "SELECT d.hash, d.collection, d.path, d.title, c.doc
+             FROM documents d JOIN content c ON c.hash = d.hash
+             WHERE d.id = ?1",
+            params![id],
+            |row| {
+                let hash: String = row.get(0)?;
+                Ok(LoadedDocument {
+                    info: ComparedDocument {
+                        docid: docid_from_hash(&hash),
+                        collection: row.get(1)?,
+                        path: row.get(2)?,
+                        title: row.get(3)?,
+                    },
+                    hash,
+                    body: row.get(4)?,
+                })
+            },
+        )?)
+    }
+
+    /// Mean chunk embedding of one document
+    pub(crate) fn document_centroid(&self, hash: &str) -> Result<Option<Vec<f32>>> {
+        if !self.has_vector_index() {
+            return Ok(None);
+        }
+        let mut stmt = self.conn.prepare(
+            "SELECT e.embedding
+             FROM content_vectors cv
+             JOIN embeddings e ON e.hash_seq = cv.hash || '_' || cv.seq
+             WHERE cv.hash = ?1",
+        )?;
+        let mut sum: Vec<f32> = Vec::new();
+        let mut count = 0;
+        for bytes in stmt.query_map(params![hash], |row| row.get::<_, Vec<u8>>(0))? {
+            let embedding = bytes_to_embedding(&bytes?);
+            if sum.is_empty() {
+                sum = vec![0.0; embedding.len()];
+            }
+            if embedding.len() != sum.len() {
+                continue;
+            }
+            for (acc, v) in sum.iter_mut().zip(&embedding) {
+                *acc += v;
+            }
+            count += 1;
+        }
+        Ok((count > 0).then(|| sum.into_iter().map(|v| v / count as f32).collect()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_compare_documents() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+
+        let shared = "Rotate the signing keys every ninety days and publish the new key set before retiring the old one.";
+        let edited = "Rotate the signing keys every ninety days and publish the new key set before retiring the previous one.";
+        for (hash, path, body) in [
+            (
+                "aaa111",
+                "old.md",
+                format!("# Keys\n\n{}\n\nTokens are cached for five minutes by every gateway replica in the cluster.\n", shared),
+            ),
+            (
+                "bbb222",
+                "new.md",
+                format!("# Key rotation\n\n{}\n\nAlerts page the on-call engineer when a rotation job fails twice in a row.\n\n{}\n", edited, shared),
+            ),
+        ] {
+            db.insert_content(hash, &body).unwrap();
+            db.insert_document("notes", path, path, hash, &now, &now, "file", None)
+                .unwrap();
+        }
+        for hash in ["aaa111", "bbb222"] {
+            let concept = db.upsert_concept("signing keys").unwrap();
+            db.link_concept_to_chunk(concept, &format!("{}-chunk", hash), hash, "")
+                .unwrap();
+        }
+        let concept = db.upsert_concept("gateway").unwrap();
+        db.link_concept_to_chunk(concept, "aaa111-chunk", "aaa111", "")
+            .unwrap();
+
+        let cmp = db.compare("#aaa111", "notes/new.md").unwrap();
+        assert_eq!(cmp.a.path, "old.md");
+        assert_eq!(cmp.b.docid, "bbb222");
+        assert_eq!(cmp.embedding_similarity, None);
+        assert_eq!(cmp.shared_concepts, vec!["signing keys".to_string()]);
+        assert!((cmp.concept_overlap - 0.5).abs() < 1e-9);
+        assert!(cmp.text_overlap > 0.2 && cmp.text_overlap < 1.0);
+
+        // The copied paragraph matches its verbatim copy, not the edited one
+        assert_eq!(cmp.overlapping_chunks.len(), 1);
+        let overlap = &cmp.overlapping_chunks[0];
+        assert!(overlap.identical);
+        assert_eq!((overlap.a_start_line, overlap.b_start_line), (3, 7));
+        assert!(overlap.preview.starts_with("Rotate the signing keys"));
+
+        let same = db.compare("#aaa111", "#aaa111").unwrap();
+        assert_eq!(same.text_overlap, 1.0);
+        assert!(db.compare("#aaa111", "notes/missing.md").is_err());
+    }
+
+    #[test]
+    fn test_compare_embedding_similarity() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+        for (hash, path) in [("aaa111", "a.md"), ("bbb222", "b.md")] {
+            db.insert_content(hash, path).unwrap();
+            db.insert_document("notes", path, path, hash, &now, &now, "file", None)
+                .unwrap();
+        }
+        db.ensure_vec_table(2).unwrap();
+        db.insert_embedding("aaa111", 0, 0, "m", &[1.0, 0.0])
+            .unwrap();
+        db.insert_embedding("bbb222", 0, 0, "m", &[0.0, 1.0])
+            .unwrap();
+        db.insert_embedding("bbb222", 1, 50, "m", &[1.0, 0.0])
+            .unwrap();
+
+        let cmp = db.compare("notes/a.md", "notes/b.md").unwrap();
+        let similarity = cmp.embedding_similarity.unwrap();
+        assert!((similarity - std::f64::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+    }
+}
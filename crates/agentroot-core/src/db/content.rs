@@ -22,10 +22,12 @@ impl Database {
     /// Insert content if not exists (content-addressable)
     pub fn insert_content(&self, hash: &str, content: &str) -> Result<bool> {
         let now = Utc::now().to_rfc3339();
-        let rows = self.conn.execute(
-            "INSERT OR IGNORE INTO content (hash, doc, created_at) VALUES (?1, ?2, ?3)",
-            params![hash, content, now],
-        )?;
+        let rows = self
+            .conn
+            .prepare_cached(
+                "INSERT OR IGNORE INTO content (hash, doc, created_at) VALUES (?1, ?2, ?3)",
+            )?
+            .execute(params![hash, content, now])?;
         Ok(rows > 0)
     }
 
@@ -44,10 +46,13 @@ impl Database {
     }
 
     /// Delete orphaned content (not referenced by any active document)
+    ///
+    /// Content of earlier document versions is kept, see `history`.
     pub fn cleanup_orphaned_content(&self) -> Result<usize> {
         let rows = self.conn.execute(
             "DELETE FROM content WHERE hash NOT IN
-             (SELECT DISTINCT hash FROM documents WHERE active = 1)",
+             (SELECT DISTINCT hash FROM documents WHERE active = 1)
+             AND hash NOT IN (SELECT hash FROM document_versions)",
             [],
         )?;
         Ok(rows)
@@ -6,6 +6,9 @@ use chrono::Utc;
 use rusqlite::params;
 use std::collections::HashMap;
 
+/// Document row used for directory aggregation: (path, category, concepts)
+type DirFileRow = (String, Option<String>, Option<String>);
+
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct DirectoryInfo {
     pub path: String,
@@ -135,7 +138,7 @@ impl Database {
         collection: Option<&str>,
         limit: usize,
     ) -> Result<Vec<DirectoryInfo>> {
-        let sanitized = crate::search::sanitize_fts5_query(query);
+        let sanitized = crate::search::sanitize_fts5_query(query)?;
         if sanitized.is_empty() {
             return Ok(vec![]);
         }
@@ -205,8 +208,7 @@ impl Database {
             .collect::<std::result::Result<Vec<_>, _>>()?;
 
         // Aggregate by directory
-        let mut dir_files: HashMap<String, Vec<(String, Option<String>, Option<String>)>> =
-            HashMap::new();
+        let mut dir_files: HashMap<String, Vec<DirFileRow>> = HashMap::new();
 
         for (path, category, concepts) in &docs {
             let dir = match path.rsplit_once('/') {
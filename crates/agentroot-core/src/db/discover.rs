@@ -0,0 +1,212 @@
+//! Discovery mode
+//!
+//! Draws a weighted random sample of documents so forgotten notes resurface.
+//! Each document's weight is its importance (PageRank) times a recency factor
+//! that halves every `RECENCY_HALF_LIFE_DAYS` but never drops below half, so
+//! old notes stay in the draw. Sampling is without replacement
+//! (Efraimidis-Spirakis keys).
+
+use super::content::docid_from_hash;
+use super::Database;
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+
+const RECENCY_HALF_LIFE_DAYS: f64 = 90.0;
+
+/// Floor for importance so unlinked documents can still be drawn
+const MIN_IMPORTANCE: f64 = 0.05;
+
+/// What to sample from
+#[derive(Debug, Clone, Default)]
+pub struct DiscoverOptions {
+    pub collection: Option<String>,
+    pub limit: usize,
+    /// Only documents no search has returned yet
+    pub never_retrieved: bool,
+    /// Fixed seed for a reproducible sample
+    pub seed: Option<u64>,
+}
+
+/// A document drawn by discovery
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DiscoveredDocument {
+    pub docid: String,
+    pub collection: String,
+    pub path: String,
+    pub title: String,
+    pub modified_at: String,
+    pub last_retrieved_at: Option<String>,
+    /// Sampling weight (importance x recency)
+    pub weight: f64,
+}
+
+fn recency(modified_at: &str, now: DateTime<Utc>) -> f64 {
+    let age_days = DateTime::parse_from_rfc3339(modified_at)
+        .map(|t| (now - t.with_timezone(&Utc)).num_seconds().max(0) as f64 / 86_400.0)
+        .unwrap_or(RECENCY_HALF_LIFE_DAYS * 4.0);
+    0.5 + 0.5 * 0.5f64.powf(age_days / RECENCY_HALF_LIFE_DAYS)
+}
+
+impl Database {
+    /// Weighted random sample of active documents
+    pub fn discover(&self, options: &DiscoverOptions) -> Result<Vec<DiscoveredDocument>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT hash, collection, path, title, modified_at, last_retrieved_at,
+                    COALESCE(importance_score, 1.0)
+             FROM documents
+             WHERE active = 1
+               AND (?1 IS NULL OR collection = ?1)
+               AND (?2 = 0 OR last_retrieved_at IS NULL)",
+        )?;
+        let now = Utc::now();
+        let candidates = stmt
+            .query_map(
+                params![options.collection, options.never_retrieved],
+                |row| {
+                    let modified_at: String = row.get(4)?;
+                    let importance: f64 = row.get(6)?;
+                    Ok(DiscoveredDocument {
+                        docid: docid_from_hash(&row.get::<_, String>(0)?),
+                        collection: row.get(1)?,
+                        path: row.get(2)?,
+                        title: row.get(3)?,
+                        weight: importance.max(MIN_IMPORTANCE) * recency(&modified_at, now),
+                        modified_at,
+                        last_retrieved_at: row.get(5)?,
+                    })
+                },
+            )?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let mut rng = match options.seed {
+            Some(seed) => fastrand::Rng::with_seed(seed),
+            None => fastrand::Rng::new(),
+        };
+        // Largest ln(u)/w wins, the log form of u^(1/w)
+        let mut keyed: Vec<(f64, DiscoveredDocument)> = candidates
+            .into_iter()
+            .map(|doc| {
+                let u = rng.f64().max(f64::MIN_POSITIVE);
+                (u.ln() / doc.weight, doc)
+            })
+            .collect();
+        keyed.sort_by(|a, b| b.0.total_cmp(&a.0));
+        keyed.truncate(options.limit);
+
+        Ok(keyed.into_iter().map(|(_, doc)| doc).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Database {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+        db.add_collection("code", "/code", "**/*.rs", "file", None)
+            .unwrap();
+        for (coll, hash, path) in [
+            ("notes", "aaa111", "hub.md"),
+            ("notes", "bbb222", "leaf.md"),
+            ("notes", "ccc333", "old.md"),
+            ("code", "ddd444", "main.rs"),
+        ] {
+            db.insert_content(hash, path).unwrap();
+            db.insert_document(coll, path, path, hash, &now, &now, "file", None)
+                .unwrap();
+        }
+        db.conn
+            .execute(
+                "UPDATE documents SET importance_score = 20.0 WHERE hash = 'aaa111'",
+                [],
+            )
+            .unwrap();
+        db
+    }
+
+    #[test]
+    fn test_discover_weights_by_importance() {
+        let db = setup_db();
+        let options = DiscoverOptions {
+            collection: Some("notes".to_string()),
+            limit: 1,
+            ..Default::default()
+        };
+
+        let mut hub_first = 0;
+        for seed in 0..200 {
+            let sample = db
+                .discover(&DiscoverOptions {
+                    seed: Some(seed),
+                    ..options.clone()
+                })
+                .unwrap();
+            assert_eq!(sample.len(), 1);
+            assert_eq!(sample[0].collection, "notes");
+            if sample[0].path == "hub.md" {
+                hub_first += 1;
+            }
+        }
+        // 20 / (20 + 1 + 1) of draws in expectation
+        assert!(hub_first > 150, "hub drawn first {} times", hub_first);
+
+        // Same seed, same sample
+        let seeded = DiscoverOptions {
+            limit: 4,
+            seed: Some(7),
+            ..Default::default()
+        };
+        let a: Vec<_> = db.discover(&seeded).unwrap();
+        let b: Vec<_> = db.discover(&seeded).unwrap();
+        assert_eq!(a.len(), 4);
+        assert_eq!(
+            a.iter().map(|d| &d.docid).collect::<Vec<_>>(),
+            b.iter().map(|d| &d.docid).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_discover_never_retrieved() {
+        let db = setup_db();
+        let session = db.create_session(None).unwrap();
+        db.mark_seen(&session, "aaa111", None, "L1").unwrap();
+
+        let sample = db
+            .discover(&DiscoverOptions {
+                limit: 10,
+                never_retrieved: true,
+                ..Default::default()
+            })
+            .unwrap();
+        let mut paths: Vec<_> = sample.iter().map(|d| d.path.as_str()).collect();
+        paths.sort();
+        assert_eq!(paths, vec!["leaf.md", "main.rs", "old.md"]);
+
+        // Retrieval outlives the session
+        db.conn.execute("DELETE FROM session_seen", []).unwrap();
+        assert_eq!(
+            db.discover(&DiscoverOptions {
+                limit: 10,
+                never_retrieved: true,
+                ..Default::default()
+            })
+            .unwrap()
+            .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_recency_factor() {
+        let now = Utc::now();
+        assert!((recency(&now.to_rfc3339(), now) - 1.0).abs() < 1e-6);
+        let old = (now - chrono::Duration::days(90)).to_rfc3339();
+        assert!((recency(&old, now) - 0.75).abs() < 1e-6);
+        assert!(recency("not a date", now) >= 0.5);
+    }
+}
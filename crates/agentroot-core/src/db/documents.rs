@@ -3,7 +3,7 @@
 use super::content::docid_from_hash;
 use super::Database;
 use crate::config::virtual_path::{is_virtual_path, parse_virtual_path};
-use crate::error::Result;
+use crate::error::{AgentRootError, Result};
 use rusqlite::params;
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -24,6 +24,15 @@ pub struct Document {
     pub llm_model: Option<String>,
 }
 
+/// A document removed with [`Database::delete_document_by_docid`]
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DeletedDocument {
+    pub docid: String,
+    pub collection: String,
+    pub path: String,
+    pub title: String,
+}
+
 /// Document result with content
 #[derive(Debug, Clone)]
 pub struct DocumentResult {
@@ -42,34 +51,40 @@ pub struct DocumentResult {
 impl Database {
     /// Insert new document using struct parameters
     pub fn insert_doc(&self, doc: &DocumentInsert) -> Result<i64> {
-        self.conn.execute(
+        // A file that was deleted and later restored still has its deactivated row
+        self.conn
+            .prepare_cached(
+                "DELETE FROM documents WHERE collection = ?1 AND path = ?2 AND active = 0",
+            )?
+            .execute(params![doc.collection, doc.path])?;
+        let mut insert = self.conn.prepare_cached(
             "INSERT INTO documents (
                 collection, path, title, hash, created_at, modified_at, active, source_type, source_uri,
                 llm_summary, llm_title, llm_keywords, llm_category, llm_intent, llm_concepts,
                 llm_difficulty, llm_queries, llm_metadata_generated_at, llm_model
              )
              VALUES (?1, ?2, ?3, ?4, ?5, ?6, 1, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
-            params![
-                doc.collection,
-                doc.path,
-                doc.title,
-                doc.hash,
-                doc.created_at,
-                doc.modified_at,
-                doc.source_type,
-                doc.source_uri,
-                doc.llm_summary,
-                doc.llm_title,
-                doc.llm_keywords,
-                doc.llm_category,
-                doc.llm_intent,
-                doc.llm_concepts,
-                doc.llm_difficulty,
-                doc.llm_queries,
-                doc.llm_metadata_generated_at,
-                doc.llm_model,
-            ],
         )?;
+        insert.execute(params![
+            doc.collection,
+            doc.path,
+            doc.title,
+            doc.hash,
+            doc.created_at,
+            doc.modified_at,
+            doc.source_type,
+            doc.source_uri,
+            doc.llm_summary,
+            doc.llm_title,
+            doc.llm_keywords,
+            doc.llm_category,
+            doc.llm_intent,
+            doc.llm_concepts,
+            doc.llm_difficulty,
+            doc.llm_queries,
+            doc.llm_metadata_generated_at,
+            doc.llm_model,
+        ])?;
         Ok(self.conn.last_insert_rowid())
     }
 
@@ -117,10 +132,11 @@ impl Database {
         hash: &str,
         modified_at: &str,
     ) -> Result<()> {
-        self.conn.execute(
-            "UPDATE documents SET title = ?2, hash = ?3, modified_at = ?4 WHERE id = ?1",
-            params![id, title, hash, modified_at],
-        )?;
+        self.conn
+            .prepare_cached(
+                "UPDATE documents SET title = ?2, hash = ?3, modified_at = ?4 WHERE id = ?1",
+            )?
+            .execute(params![id, title, hash, modified_at])?;
         Ok(())
     }
 
@@ -142,28 +158,97 @@ impl Database {
         Ok(rows > 0)
     }
 
+    /// Remove one document, given as a docid, `#docid` or `collection/path`
+    ///
+    /// The document is deactivated as if its source were gone. Its chunks,
+    /// embeddings and concept links are deleted too, unless another active
+    /// document has the same content.
+    pub fn delete_document_by_docid(&self, docid: &str) -> Result<DeletedDocument> {
+        self.ensure_writable()?;
+        let id = self
+            .resolve_document_id(docid)?
+            .ok_or_else(|| AgentRootError::DocumentNotFound(docid.to_string()))?;
+        let (collection, path, title, hash, body): (String, String, String, String, String) =
+            self.conn.query_row(
+                "SELECT d.collection, d.path, d.title, d.hash, c.doc
+                 FROM documents d
+                 JOIN content c ON c.hash = d.hash
+                 WHERE d.id = ?1",
+                params![id],
+                |row| {
+                    Ok((
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                    ))
+                },
+            )?;
+
+        let has_vectors = self.has_vector_index();
+        self.in_transaction(|| {
+            self.deactivate_document(&collection, &path)?;
+            let shared: bool = self.conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM documents WHERE hash = ?1 AND active = 1)",
+                params![hash],
+                |row| row.get(0),
+            )?;
+            if !shared {
+                self.delete_chunks_for_document(&hash)?;
+                self.delete_concepts_for_document(&hash)?;
+                // Cached chunk embeddings go with the last content using them
+                self.conn.execute(
+                    "DELETE FROM chunk_embeddings WHERE chunk_hash IN
+                     (SELECT chunk_hash FROM content_vectors WHERE hash = ?1)
+                     AND chunk_hash NOT IN
+                     (SELECT chunk_hash FROM content_vectors
+                      WHERE hash != ?1 AND chunk_hash IS NOT NULL)",
+                    params![hash],
+                )?;
+                if has_vectors {
+                    self.conn.execute(
+                        "DELETE FROM embeddings WHERE hash_seq GLOB ?1",
+                        params![format!("{}_*", hash)],
+                    )?;
+                }
+                self.conn
+                    .execute("DELETE FROM content_vectors WHERE hash = ?1", params![hash])?;
+            }
+            self.remove_document_vocabulary(&collection, &title, &body)?;
+            Ok(())
+        })?;
+        self.note_index_changes(1)?;
+
+        Ok(DeletedDocument {
+            docid: docid_from_hash(&hash),
+            collection,
+            path,
+            title,
+        })
+    }
+
     /// Find active document by collection and path
     pub fn find_active_document(&self, collection: &str, path: &str) -> Result<Option<Document>> {
-        let result = self.conn.query_row(
+        let mut stmt = self.conn.prepare_cached(
             "SELECT id, collection, path, title, hash, created_at, modified_at, active, source_type, source_uri, llm_model
              FROM documents WHERE collection = ?1 AND path = ?2 AND active = 1",
-            params![collection, path],
-            |row| {
-                Ok(Document {
-                    id: row.get(0)?,
-                    collection: row.get(1)?,
-                    path: row.get(2)?,
-                    title: row.get(3)?,
-                    hash: row.get(4)?,
-                    created_at: row.get(5)?,
-                    modified_at: row.get(6)?,
-                    active: row.get::<_, i32>(7)? == 1,
-                    source_type: row.get(8)?,
-                    source_uri: row.get(9)?,
-                    llm_model: row.get(10)?,
-                })
-            },
-        );
+        )?;
+        let result = stmt.query_row(params![collection, path], |row| {
+            Ok(Document {
+                id: row.get(0)?,
+                collection: row.get(1)?,
+                path: row.get(2)?,
+                title: row.get(3)?,
+                hash: row.get(4)?,
+                created_at: row.get(5)?,
+                modified_at: row.get(6)?,
+                active: row.get::<_, i32>(7)? == 1,
+                source_type: row.get(8)?,
+                source_uri: row.get(9)?,
+                llm_model: row.get(10)?,
+            })
+        });
         match result {
             Ok(doc) => Ok(Some(doc)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
@@ -343,6 +428,15 @@ impl Database {
 
     /// Get document content by query (docid, virtual path, etc)
     pub fn get_document(&self, query: &str) -> Result<String> {
+        self.find_document_hash(query)?
+            .and_then(|hash| self.get_content(&hash).transpose())
+            .transpose()?
+            .ok_or_else(|| crate::error::AgentRootError::DocumentNotFound(query.trim().to_string()))
+    }
+
+    /// Content hash of the active document a docid, virtual path or
+    /// collection/path refers to
+    fn find_document_hash(&self, query: &str) -> Result<Option<String>> {
         let query = query.trim();
 
         // Docid lookup
@@ -350,9 +444,7 @@ impl Database {
             || (query.len() == 6 && query.chars().all(|c| c.is_ascii_hexdigit()))
         {
             if let Some(doc) = self.find_by_docid(query)? {
-                return doc.body.ok_or_else(|| {
-                    crate::error::AgentRootError::DocumentNotFound(query.to_string())
-                });
+                return Ok(Some(doc.hash));
             }
         }
 
@@ -360,9 +452,7 @@ impl Database {
         if is_virtual_path(query) {
             if let Ok((collection, path)) = parse_virtual_path(query) {
                 if let Some(doc) = self.find_active_document(&collection, &path)? {
-                    if let Some(content) = self.get_content(&doc.hash)? {
-                        return Ok(content);
-                    }
+                    return Ok(Some(doc.hash));
                 }
             }
         }
@@ -372,16 +462,12 @@ impl Database {
             let parts: Vec<&str> = query.splitn(2, '/').collect();
             if parts.len() == 2 {
                 if let Some(doc) = self.find_active_document(parts[0], parts[1])? {
-                    if let Some(content) = self.get_content(&doc.hash)? {
-                        return Ok(content);
-                    }
+                    return Ok(Some(doc.hash));
                 }
             }
         }
 
-        Err(crate::error::AgentRootError::DocumentNotFound(
-            query.to_string(),
-        ))
+        Ok(None)
     }
 
     /// List documents by prefix
@@ -409,6 +495,46 @@ impl Database {
         Ok(results)
     }
 
+    /// Up to `limit` active documents after `cursor` ("collection/path"),
+    /// in `collection/path` order
+    pub fn list_documents_after(
+        &self,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<DocumentListItem>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT d.collection, d.path, d.title, d.hash
+             FROM documents d
+             WHERE d.active = 1 AND (?1 IS NULL OR (d.collection || '/' || d.path) > ?1)
+             ORDER BY d.collection || '/' || d.path
+             LIMIT ?2",
+        )?;
+
+        let results = stmt
+            .query_map(params![cursor, limit as i64], |row| {
+                Ok(DocumentListItem {
+                    path: format!("{}/{}", row.get::<_, String>(0)?, row.get::<_, String>(1)?),
+                    title: row.get(2)?,
+                    docid: docid_from_hash(&row.get::<_, String>(3)?),
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(results)
+    }
+
+    /// Changes whenever documents or collections are added or removed
+    pub fn document_list_fingerprint(&self) -> Result<(i64, i64, i64)> {
+        let fingerprint = self.conn.query_row(
+            "SELECT (SELECT COUNT(*) FROM documents WHERE active = 1),
+                    (SELECT COALESCE(MAX(id), 0) FROM documents WHERE active = 1),
+                    (SELECT COUNT(*) FROM collections)",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+        Ok(fingerprint)
+    }
+
     /// Get multiple documents by pattern
     pub fn get_documents_by_pattern(&self, pattern: &str) -> Result<Vec<DocumentContent>> {
         // Handle comma-separated list of docids
@@ -416,9 +542,13 @@ impl Database {
             let mut results = Vec::new();
             for part in pattern.split(',') {
                 let part = part.trim();
-                if let Ok(content) = self.get_document(part) {
+                let Some(hash) = self.find_document_hash(part)? else {
+                    continue;
+                };
+                if let Some(content) = self.get_content(&hash)? {
                     results.push(DocumentContent {
                         path: part.to_string(),
+                        hash,
                         content,
                     });
                 }
@@ -429,7 +559,7 @@ impl Database {
         // Glob pattern matching
         let pattern = glob::Pattern::new(pattern)?;
         let mut stmt = self.conn.prepare(
-            "SELECT d.collection, d.path, c.doc
+            "SELECT d.collection, d.path, d.hash, c.doc
              FROM documents d
              JOIN content c ON c.hash = d.hash
              WHERE d.active = 1",
@@ -438,11 +568,14 @@ impl Database {
         let results = stmt
             .query_map([], |row| {
                 let path = format!("{}/{}", row.get::<_, String>(0)?, row.get::<_, String>(1)?);
-                Ok((path, row.get::<_, String>(2)?))
+                Ok(DocumentContent {
+                    path,
+                    hash: row.get(2)?,
+                    content: row.get(3)?,
+                })
             })?
             .filter_map(|r| r.ok())
-            .filter(|(path, _)| pattern.matches(path))
-            .map(|(path, content)| DocumentContent { path, content })
+            .filter(|doc| pattern.matches(&doc.path))
             .collect();
 
         Ok(results)
@@ -491,6 +624,7 @@ pub struct DocumentListItem {
 #[derive(Debug, Clone)]
 pub struct DocumentContent {
     pub path: String,
+    pub hash: String,
     pub content: String,
 }
 
@@ -580,6 +714,7 @@ impl<'a> DocumentInsert<'a> {
     }
 
     /// Set LLM metadata JSON strings (pre-serialized)
+    #[allow(clippy::too_many_arguments)]
     pub fn with_llm_metadata_strings(
         mut self,
         summary: &'a str,
@@ -606,3 +741,92 @@ impl<'a> DocumentInsert<'a> {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::ChunkEmbedding;
+
+    #[tokio::test]
+    async fn test_delete_document_by_docid_drops_its_index_data() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp.path().join("keep.md"), "# Keep\n\nStays indexed.").unwrap();
+        std::fs::write(temp.path().join("drop.md"), "# Drop\n\nGoes away.").unwrap();
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.add_collection(
+            "notes",
+            &temp.path().to_string_lossy(),
+            "**/*.md",
+            "file",
+            None,
+        )
+        .unwrap();
+        db.reindex_collection_with_metadata("notes", None)
+            .await
+            .unwrap();
+
+        let doc = db
+            .find_active_document("notes", "drop.md")
+            .unwrap()
+            .unwrap();
+        assert!(!db.get_chunks_for_document(&doc.hash).unwrap().is_empty());
+        let keep = db
+            .find_active_document("notes", "keep.md")
+            .unwrap()
+            .unwrap();
+        db.ensure_vec_table(2).unwrap();
+        for (hash, chunk_hash) in [(&doc.hash, "chunk-drop"), (&keep.hash, "chunk-keep")] {
+            let chunk = ChunkEmbedding {
+                seq: 0,
+                pos: 0,
+                chunk_hash: chunk_hash.to_string(),
+                embedding: vec![1.0, 0.0],
+            };
+            db.replace_document_embeddings(hash, "test-model", &[chunk])
+                .unwrap();
+        }
+        let count = |sql: &str| -> i64 { db.conn.query_row(sql, [], |row| row.get(0)).unwrap() };
+        let vocabulary = "SELECT COUNT(*) FROM vocabulary WHERE term = 'goes'";
+        assert_eq!(count(vocabulary), 1);
+        assert_eq!(
+            count("SELECT COUNT(*) FROM vocabulary_trigrams WHERE term = 'goes'"),
+            1
+        );
+
+        let deleted = db
+            .delete_document_by_docid(&format!("#{}", docid_from_hash(&doc.hash)))
+            .unwrap();
+        assert_eq!(deleted.path, "drop.md");
+        assert!(db
+            .find_active_document("notes", "drop.md")
+            .unwrap()
+            .is_none());
+        assert!(db.get_chunks_for_document(&doc.hash).unwrap().is_empty());
+        assert_eq!(count("SELECT COUNT(*) FROM embeddings"), 1);
+        assert_eq!(count("SELECT COUNT(*) FROM content_vectors"), 1);
+        assert_eq!(
+            count("SELECT COUNT(*) FROM chunk_embeddings WHERE chunk_hash = 'chunk-keep'"),
+            1
+        );
+        assert_eq!(count("SELECT COUNT(*) FROM chunk_embeddings"), 1);
+        assert_eq!(count(vocabulary), 0);
+        assert_eq!(
+            count("SELECT COUNT(*) FROM vocabulary_trigrams WHERE term = 'goes'"),
+            0
+        );
+        assert_eq!(
+            count("SELECT COUNT(*) FROM vocabulary WHERE term = 'stays'"),
+            1
+        );
+        assert!(db
+            .find_active_document("notes", "keep.md")
+            .unwrap()
+            .is_some());
+
+        assert!(matches!(
+            db.delete_document_by_docid("notes/drop.md"),
+            Err(AgentRootError::DocumentNotFound(_))
+        ));
+    }
+}
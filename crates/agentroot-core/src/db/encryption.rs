@@ -0,0 +1,312 @@
+//! Encryption at rest with SQLCipher
+//!
+//! An index is encrypted when its file does not start with the plain SQLite
+//! header. Opening one needs its key, taken from `AGENTROOT_DB_KEY` or, in
+//! builds with the `encryption` feature, from the OS credential store where
+//! `agentroot encrypt` saves it. A new index is created encrypted when
+//! `AGENTROOT_DB_KEY` is set. Builds without SQLCipher refuse encrypted
+//! indexes and keys instead of silently ignoring the key.
+
+use super::Database;
+use crate::error::{AgentRootError, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Environment variable holding the index key
+pub const DB_KEY_ENV: &str = "AGENTROOT_DB_KEY";
+
+/// First bytes of every unencrypted SQLite file
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+/// Credential store service the keys are saved under, one per index path
+#[cfg(feature = "encryption")]
+const KEYRING_SERVICE: &str = "agentroot";
+
+impl Database {
+    /// Open (or create) an encrypted database at `path` with `key`
+    pub fn open_encrypted(path: impl AsRef<Path>, key: &str) -> Result<Self> {
+        let path = path.as_ref();
+        if path.exists() && !is_encrypted(path) {
+            return Err(AgentRootError::Config(format!(
+                "{} is not encrypted; run `agentroot encrypt` first",
+                path.display()
+            )));
+        }
+        Self::open_with_key(path, Some(key))
+    }
+}
+
+/// Whether the file at `path` is an encrypted index
+pub fn is_encrypted(path: &Path) -> bool {
+    let mut header = [0u8; 16];
+    match std::fs::File::open(path).and_then(|mut f| f.read_exact(&mut header)) {
+        Ok(()) => &header != SQLITE_HEADER,
+        // Missing and empty files are not databases yet
+        Err(_) => false,
+    }
+}
+
+/// Key to open the index at `path` with, if it needs one
+///
+/// Encrypted indexes need a key; a new index gets one when
+/// `AGENTROOT_DB_KEY` is set; plain indexes are opened as they are.
+pub(crate) fn key_for(path: &Path) -> Result<Option<String>> {
+    if is_encrypted(path) {
+        return match database_key(path)? {
+            Some(key) => Ok(Some(key)),
+            None => Err(AgentRootError::Config(format!(
+                "{} is encrypted and no key was found; set {} to its key",
+                path.display(),
+                DB_KEY_ENV
+            ))),
+        };
+    }
+    let env_key = std::env::var(DB_KEY_ENV).ok().filter(|k| !k.is_empty());
+    if path.exists() && env_key.is_some() {
+        tracing::warn!(
+            "{} is set but {} is not encrypted; run `agentroot encrypt` to encrypt it",
+            DB_KEY_ENV,
+            path.display()
+        );
+        return Ok(None);
+    }
+    Ok(env_key)
+}
+
+/// Key for the index at `path`: `AGENTROOT_DB_KEY`, else the credential store
+pub fn database_key(path: &Path) -> Result<Option<String>> {
+    if let Some(key) = std::env::var(DB_KEY_ENV).ok().filter(|k| !k.is_empty()) {
+        return Ok(Some(key));
+    }
+    stored_key(path)
+}
+
+/// Set the key of a connection and check that it opens the database
+pub(crate) fn unlock(conn: &Connection, key: &str) -> Result<()> {
+    ensure_sqlcipher(conn)?;
+    conn.pragma_update(None, "key", key)?;
+    // SQLCipher only notices a wrong key on the first read
+    conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| {
+        row.get::<_, i64>(0)
+    })
+    .map_err(|_| AgentRootError::Config("wrong key for encrypted index".to_string()))?;
+    Ok(())
+}
+
+fn ensure_sqlcipher(conn: &Connection) -> Result<()> {
+    let version: Option<String> = conn
+        .query_row("PRAGMA cipher_version", [], |row| row.get(0))
+        .optional()?;
+    if version.is_none() {
+        return Err(not_supported());
+    }
+    Ok(())
+}
+
+/// Encrypt the plain index at `path` in place
+///
+/// The index is copied into an encrypted file next to it, which then
+/// replaces the original. Other processes must not have it open.
+pub fn encrypt_database(path: &Path, key: &str) -> Result<()> {
+    if key.is_empty() {
+        return Err(AgentRootError::Config("empty encryption key".to_string()));
+    }
+    if is_encrypted(path) {
+        return Err(AgentRootError::Config(format!(
+            "{} is already encrypted",
+            path.display()
+        )));
+    }
+    let conn = open_existing(path)?;
+    ensure_sqlcipher(&conn)?;
+    export(conn, path, key)
+}
+
+/// Decrypt the index at `path` in place; see [`encrypt_database`]
+pub fn decrypt_database(path: &Path, key: &str) -> Result<()> {
+    if !is_encrypted(path) {
+        return Err(AgentRootError::Config(format!(
+            "{} is not encrypted",
+            path.display()
+        )));
+    }
+    let conn = open_existing(path)?;
+    unlock(&conn, key)?;
+    export(conn, path, "")
+}
+
+fn open_existing(path: &Path) -> Result<Connection> {
+    if !path.exists() {
+        return Err(AgentRootError::Config(format!(
+            "no index at {}",
+            path.display()
+        )));
+    }
+    Ok(Connection::open(path)?)
+}
+
+/// Copy the database into a file keyed with `key` (empty for plain) and
+/// swap it in
+fn export(conn: Connection, path: &Path, key: &str) -> Result<()> {
+    let target = sibling(path, "export");
+    let _ = std::fs::remove_file(&target);
+
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+    conn.execute(
+        "ATTACH DATABASE ?1 AS target KEY ?2",
+        params![target.to_string_lossy(), key],
+    )?;
+    let exported = conn
+        .query_row("SELECT sqlcipher_export('target')", [], |_| Ok(()))
+        .and_then(|_| conn.execute("DETACH DATABASE target", []));
+    drop(conn);
+    if let Err(e) = exported {
+        let _ = std::fs::remove_file(&target);
+        return Err(e.into());
+    }
+
+    for suffix in ["wal", "shm"] {
+        let _ = std::fs::remove_file(sibling(path, suffix));
+    }
+    std::fs::rename(&target, path)?;
+    Ok(())
+}
+
+/// `index.sqlite` -> `index.sqlite-<suffix>`, as SQLite names its own files
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!("-{}", suffix));
+    PathBuf::from(name)
+}
+
+/// A random 256-bit key, hex encoded
+#[cfg(feature = "encryption")]
+pub fn generate_key() -> Result<String> {
+    let mut bytes = [0u8; 32];
+    getrandom::fill(&mut bytes)
+        .map_err(|e| AgentRootError::Config(format!("no randomness for a key: {}", e)))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Save the key of the index at `path` in the OS credential store
+#[cfg(feature = "encryption")]
+pub fn store_key(path: &Path, key: &str) -> Result<()> {
+    keyring_entry(path)?
+        .set_password(key)
+        .map_err(|e| AgentRootError::Config(format!("credential store: {}", e)))
+}
+
+/// Remove a saved key; a missing one is not an error
+#[cfg(feature = "encryption")]
+pub fn forget_key(path: &Path) -> Result<()> {
+    match keyring_entry(path)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(AgentRootError::Config(format!("credential store: {}", e))),
+    }
+}
+
+#[cfg(feature = "encryption")]
+fn stored_key(path: &Path) -> Result<Option<String>> {
+    match keyring_entry(path)?.get_password() {
+        Ok(key) => Ok(Some(key)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => {
+            tracing::debug!("Credential store unavailable: {}", e);
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(not(feature = "encryption"))]
+fn stored_key(_path: &Path) -> Result<Option<String>> {
+    Ok(None)
+}
+
+#[cfg(not(feature = "encryption"))]
+pub fn generate_key() -> Result<String> {
+    Err(not_supported())
+}
+
+#[cfg(not(feature = "encryption"))]
+pub fn store_key(_path: &Path, _key: &str) -> Result<()> {
+    Err(not_supported())
+}
+
+#[cfg(not(feature = "encryption"))]
+pub fn forget_key(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+fn not_supported() -> AgentRootError {
+    AgentRootError::Config(
+        "this build cannot encrypt indexes; rebuild with `--features encryption`".to_string(),
+    )
+}
+
+#[cfg(feature = "encryption")]
+fn keyring_entry(path: &Path) -> Result<keyring::Entry> {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    keyring::Entry::new(KEYRING_SERVICE, &path.to_string_lossy())
+        .map_err(|e| AgentRootError::Config(format!("credential store: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_index_needs_no_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.sqlite");
+        assert!(!is_encrypted(&path));
+
+        let db = Database::open(&path).unwrap();
+        db.initialize().unwrap();
+        drop(db);
+        assert!(!is_encrypted(&path));
+        assert!(matches!(
+            decrypt_database(&path, "secret"),
+            Err(AgentRootError::Config(_))
+        ));
+    }
+
+    #[cfg(not(feature = "encryption"))]
+    #[test]
+    fn test_encryption_needs_sqlcipher() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.sqlite");
+        let err = Database::open_encrypted(&path, "secret").err().unwrap();
+        assert!(err.to_string().contains("--features encryption"));
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encrypt_and_decrypt_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.sqlite");
+        let db = Database::open(&path).unwrap();
+        db.initialize().unwrap();
+        db.insert_content("h1", "confidential plans").unwrap();
+        drop(db);
+
+        encrypt_database(&path, "secret").unwrap();
+        assert!(is_encrypted(&path));
+        let raw = std::fs::read(&path).unwrap();
+        assert!(!raw.windows(12).any(|w| w == b"confidential"));
+
+        assert!(Database::open_encrypted(&path, "wrong").is_err());
+        let db = Database::open_encrypted(&path, "secret").unwrap();
+        db.initialize().unwrap();
+        assert_eq!(
+            db.get_content("h1").unwrap().as_deref(),
+            Some("confidential plans")
+        );
+        drop(db);
+
+        decrypt_database(&path, "secret").unwrap();
+        assert!(!is_encrypted(&path));
+        let db = Database::open(&path).unwrap();
+        assert!(db.get_content("h1").unwrap().is_some());
+    }
+}
@@ -0,0 +1,228 @@
+//! Relevance feedback on search results
+//!
+//! Judgments are keyed by collection/path so they survive document edits.
+//! They feed two ranking adjustments:
+//! - a document prior from all judgments on the document
+//! - a term boost from judgments whose query shares terms with the current one
+
+use super::Database;
+use crate::error::{AgentRootError, Result};
+use chrono::Utc;
+use rusqlite::params;
+use std::collections::{HashMap, HashSet};
+
+const PRIOR_WEIGHT: f64 = 0.2;
+const TERM_WEIGHT: f64 = 0.6;
+const MIN_MULTIPLIER: f64 = 0.1;
+
+/// A stored relevance judgment
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FeedbackEntry {
+    pub id: i64,
+    pub query: String,
+    pub collection: String,
+    pub path: String,
+    pub document_hash: String,
+    pub relevant: bool,
+    pub session_id: Option<String>,
+    pub created_at: String,
+}
+
+impl Database {
+    /// Record whether a document was relevant for a query
+    pub fn record_feedback(
+        &self,
+        query: &str,
+        docid: &str,
+        relevant: bool,
+        session_id: Option<&str>,
+    ) -> Result<FeedbackEntry> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Err(AgentRootError::InvalidInput(
+                "Feedback query must not be empty".to_string(),
+            ));
+        }
+
+        let doc_id = self
+            .resolve_document_id(docid)?
+            .ok_or_else(|| AgentRootError::DocumentNotFound(docid.to_string()))?;
+        let (collection, path, hash): (String, String, String) = self.conn.query_row(
+            "SELECT collection, path, hash FROM documents WHERE id = ?1",
+            params![doc_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+
+        let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT INTO search_feedback
+             (query, collection, path, document_hash, relevant, session_id, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![query, collection, path, hash, relevant, session_id, now],
+        )?;
+
+        Ok(FeedbackEntry {
+            id: self.conn.last_insert_rowid(),
+            query: query.to_string(),
+            collection,
+            path,
+            document_hash: hash,
+            relevant,
+            session_id: session_id.map(String::from),
+            created_at: now,
+        })
+    }
+
+    /// List recent judgments, newest first
+    pub fn list_feedback(&self, limit: usize) -> Result<Vec<FeedbackEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, query, collection, path, document_hash, relevant, session_id, created_at
+             FROM search_feedback ORDER BY id DESC LIMIT ?1",
+        )?;
+        let entries = stmt
+            .query_map(params![limit as i64], |row| {
+                Ok(FeedbackEntry {
+                    id: row.get(0)?,
+                    query: row.get(1)?,
+                    collection: row.get(2)?,
+                    path: row.get(3)?,
+                    document_hash: row.get(4)?,
+                    relevant: row.get(5)?,
+                    session_id: row.get(6)?,
+                    created_at: row.get(7)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+
+    /// Delete all judgments, returning how many were removed
+    pub fn clear_feedback(&self) -> Result<usize> {
+        Ok(self.conn.execute("DELETE FROM search_feedback", [])?)
+    }
+
+    /// Score multipliers for a query, keyed by "collection/path"
+    ///
+    /// Documents without feedback are absent from the map.
+    pub fn feedback_adjustments(&self, query: &str) -> Result<HashMap<String, f64>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT query, collection, path, relevant FROM search_feedback")?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    format!("{}/{}", row.get::<_, String>(1)?, row.get::<_, String>(2)?),
+                    row.get::<_, bool>(3)?,
+                ))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        if rows.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let terms = query_terms(query);
+        // (positive, negative, term signal) per document
+        let mut signals: HashMap<String, (f64, f64, f64)> = HashMap::new();
+        for (judged_query, key, relevant) in rows {
+            let sign = if relevant { 1.0 } else { -1.0 };
+            let overlap = jaccard(&terms, &query_terms(&judged_query));
+            let entry = signals.entry(key).or_default();
+            if relevant {
+                entry.0 += 1.0;
+            } else {
+                entry.1 += 1.0;
+            }
+            entry.2 += sign * overlap;
+        }
+
+        Ok(signals
+            .into_iter()
+            .map(|(key, (pos, neg, term))| {
+                let prior = (pos - neg) / (pos + neg + 2.0);
+                let multiplier = 1.0 + PRIOR_WEIGHT * prior + TERM_WEIGHT * term.clamp(-1.0, 1.0);
+                (key, multiplier.max(MIN_MULTIPLIER))
+            })
+            .collect())
+    }
+}
+
+fn query_terms(query: &str) -> HashSet<String> {
+    query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| t.chars().count() >= 2)
+        .map(|t| t.to_lowercase())
+        .collect()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Database {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+        for (hash, path) in [("aaa111", "a.md"), ("bbb222", "b.md")] {
+            db.insert_content(hash, path).unwrap();
+            db.insert_document("notes", path, path, hash, &now, &now, "file", None)
+                .unwrap();
+        }
+        db
+    }
+
+    #[test]
+    fn test_record_and_list_feedback() {
+        let db = setup_db();
+        let entry = db
+            .record_feedback("oauth flow", "notes/a.md", true, None)
+            .unwrap();
+        assert_eq!(entry.path, "a.md");
+        assert_eq!(entry.document_hash, "aaa111");
+
+        db.record_feedback("oauth flow", "#bbb222", false, Some("s1"))
+            .unwrap();
+        let entries = db.list_feedback(10).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].path, "b.md");
+        assert!(!entries[0].relevant);
+
+        assert!(db
+            .record_feedback("q", "notes/missing.md", true, None)
+            .is_err());
+        assert!(db.record_feedback("  ", "notes/a.md", true, None).is_err());
+
+        assert_eq!(db.clear_feedback().unwrap(), 2);
+        assert!(db.list_feedback(10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_feedback_adjustments() {
+        let db = setup_db();
+        assert!(db.feedback_adjustments("oauth").unwrap().is_empty());
+
+        db.record_feedback("oauth token refresh", "notes/a.md", true, None)
+            .unwrap();
+        db.record_feedback("oauth token refresh", "notes/b.md", false, None)
+            .unwrap();
+
+        let same = db.feedback_adjustments("OAuth token refresh").unwrap();
+        assert!(same["notes/a.md"] > 1.5);
+        assert!(same["notes/b.md"] < 0.5);
+
+        // Unrelated queries only see the weaker document prior
+        let other = db.feedback_adjustments("deployment").unwrap();
+        assert!(other["notes/a.md"] > 1.0 && other["notes/a.md"] < 1.1);
+        assert!(other["notes/b.md"] < 1.0 && other["notes/b.md"] > 0.9);
+    }
+}
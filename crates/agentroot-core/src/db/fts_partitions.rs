@@ -0,0 +1,356 @@
+//! Per-collection full-text tables
+//!
+//! Every collection has its own stemmed and exact FTS tables, kept in step
+//! with its documents by triggers that fire only for that collection's rows.
+//! A collection-scoped search reads one collection's tables and an unscoped
+//! one unions them, so BM25 statistics are per collection. Removing a
+//! collection drops its tables instead of deleting its rows one at a time.
+//!
+//! Chunks and embeddings are stored once per content hash and shared by every
+//! collection holding that content, so they are not partitioned: removing a
+//! collection leaves them for `cleanup`, and scoped vector scans look them up
+//! by the collection's hashes (see `vector_scan`).
+
+use super::Database;
+use crate::error::Result;
+use rusqlite::params;
+
+/// Columns of the stemmed tables, in the order their BM25 weights are given
+const FTS_COLUMNS: &str = "filepath, title, body, llm_summary, llm_title, llm_keywords, \
+                           llm_intent, llm_concepts, user_metadata, modified_at";
+
+/// Columns of the exact tables
+const EXACT_COLUMNS: &str = "filepath, title, body";
+
+/// Suffix of a collection's table names: its name in hex, so any name is a
+/// valid identifier. Must match `partition_key_sql`.
+fn partition_key(collection: &str) -> String {
+    let mut key = String::with_capacity(1 + collection.len() * 2);
+    key.push('c');
+    for byte in collection.bytes() {
+        key.push_str(&format!("{:02x}", byte));
+    }
+    key
+}
+
+/// `partition_key` of a collection name column, computed in SQL
+fn partition_key_sql(column: &str) -> String {
+    format!("'c' || lower(hex({}))", column)
+}
+
+/// Stemmed full-text table of a collection
+pub(crate) fn fts_table(collection: &str) -> String {
+    format!("documents_fts_{}", partition_key(collection))
+}
+
+/// Unstemmed full-text table of a collection, for exact queries
+pub(crate) fn exact_fts_table(collection: &str) -> String {
+    format!("documents_fts_exact_{}", partition_key(collection))
+}
+
+/// A collection name as an SQL string literal, for trigger conditions
+fn sql_literal(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+impl Database {
+    /// Create a collection's full-text tables and their triggers
+    ///
+    /// Tables that already exist are kept; new ones are filled from the
+    /// collection's active documents. Triggers are always recreated, so this
+    /// also repairs a partition whose triggers were lost.
+    pub(crate) fn create_fts_partition(&self, collection: &str) -> Result<()> {
+        let table = fts_table(collection);
+        let exact = exact_fts_table(collection);
+        let name = sql_literal(collection);
+        self.drop_fts_triggers(collection)?;
+
+        if !self.table_exists(&table)? {
+            self.conn.execute_batch(&format!(
+                "CREATE VIRTUAL TABLE {table} USING fts5(
+                    {FTS_COLUMNS},
+                    tokenize='porter unicode61 remove_diacritics 2'
+                );"
+            ))?;
+            self.conn.execute(
+                &format!(
+                    "INSERT INTO {table}(rowid, {FTS_COLUMNS})
+                     SELECT d.id, d.collection || '/' || d.path, d.title, c.doc,
+                            d.llm_summary, d.llm_title, d.llm_keywords, d.llm_intent,
+                            d.llm_concepts, d.user_metadata, d.modified_at
+                     FROM documents d
+                     JOIN content c ON c.hash = d.hash
+                     WHERE d.collection = ?1 AND d.active = 1"
+                ),
+                params![collection],
+            )?;
+        }
+
+        // Contentless, so the text is not stored a third time, and covering
+        // only the document's own text, not LLM or user metadata
+        if !self.table_exists(&exact)? {
+            self.conn.execute_batch(&format!(
+                "CREATE VIRTUAL TABLE {exact} USING fts5(
+                    {EXACT_COLUMNS},
+                    content='',
+                    contentless_delete=1,
+                    tokenize='unicode61 remove_diacritics 2'
+                );"
+            ))?;
+            self.conn.execute(
+                &format!(
+                    "INSERT INTO {exact}(rowid, {EXACT_COLUMNS})
+                     SELECT d.id, d.collection || '/' || d.path, d.title, c.doc
+                     FROM documents d
+                     JOIN content c ON c.hash = d.hash
+                     WHERE d.collection = ?1 AND d.active = 1"
+                ),
+                params![collection],
+            )?;
+        }
+
+        self.conn.execute_batch(&format!(
+            "CREATE TRIGGER {table}_ai
+            AFTER INSERT ON documents
+            WHEN new.active = 1 AND new.collection = {name}
+            BEGIN
+                INSERT INTO {table}(rowid, {FTS_COLUMNS})
+                SELECT
+                    new.id,
+                    new.collection || '/' || new.path,
+                    new.title,
+                    (SELECT doc FROM content WHERE hash = new.hash),
+                    new.llm_summary,
+                    new.llm_title,
+                    new.llm_keywords,
+                    new.llm_intent,
+                    new.llm_concepts,
+                    new.user_metadata,
+                    new.modified_at;
+            END;
+
+            CREATE TRIGGER {table}_au
+            AFTER UPDATE ON documents
+            WHEN old.collection = {name} OR new.collection = {name}
+            BEGIN
+                DELETE FROM {table} WHERE rowid = old.id;
+                INSERT INTO {table}(rowid, {FTS_COLUMNS})
+                SELECT
+                    new.id,
+                    new.collection || '/' || new.path,
+                    new.title,
+                    (SELECT doc FROM content WHERE hash = new.hash),
+                    new.llm_summary,
+                    new.llm_title,
+                    new.llm_keywords,
+                    new.llm_intent,
+                    new.llm_concepts,
+                    new.user_metadata,
+                    new.modified_at
+                WHERE new.active = 1 AND new.collection = {name};
+            END;
+
+            CREATE TRIGGER {table}_ad
+            AFTER DELETE ON documents
+            WHEN old.collection = {name}
+            BEGIN
+                DELETE FROM {table} WHERE rowid = old.id;
+            END;
+
+            CREATE TRIGGER {exact}_ai
+            AFTER INSERT ON documents
+            WHEN new.active = 1 AND new.collection = {name}
+            BEGIN
+                INSERT INTO {exact}(rowid, {EXACT_COLUMNS})
+                SELECT
+                    new.id,
+                    new.collection || '/' || new.path,
+                    new.title,
+                    (SELECT doc FROM content WHERE hash = new.hash);
+            END;
+
+            CREATE TRIGGER {exact}_au
+            AFTER UPDATE OF collection, path, title, hash, active ON documents
+            WHEN old.collection = {name} OR new.collection = {name}
+            BEGIN
+                DELETE FROM {exact} WHERE rowid = old.id;
+                INSERT INTO {exact}(rowid, {EXACT_COLUMNS})
+                SELECT
+                    new.id,
+                    new.collection || '/' || new.path,
+                    new.title,
+                    (SELECT doc FROM content WHERE hash = new.hash)
+                WHERE new.active = 1 AND new.collection = {name};
+            END;
+
+            CREATE TRIGGER {exact}_ad
+            AFTER DELETE ON documents
+            WHEN old.collection = {name}
+            BEGIN
+                DELETE FROM {exact} WHERE rowid = old.id;
+            END;"
+        ))?;
+        Ok(())
+    }
+
+    /// Drop a collection's full-text tables and their triggers
+    pub(crate) fn drop_fts_partition(&self, collection: &str) -> Result<()> {
+        self.drop_fts_triggers(collection)?;
+        self.conn.execute_batch(&format!(
+            "DROP TABLE IF EXISTS {};
+             DROP TABLE IF EXISTS {};",
+            fts_table(collection),
+            exact_fts_table(collection)
+        ))?;
+        Ok(())
+    }
+
+    fn drop_fts_triggers(&self, collection: &str) -> Result<()> {
+        for table in [fts_table(collection), exact_fts_table(collection)] {
+            self.conn.execute_batch(&format!(
+                "DROP TRIGGER IF EXISTS {0}_ai;
+                 DROP TRIGGER IF EXISTS {0}_au;
+                 DROP TRIGGER IF EXISTS {0}_ad;",
+                table
+            ))?;
+        }
+        Ok(())
+    }
+
+    /// Existing full-text tables of the collections a search covers
+    ///
+    /// `collection` and `provider` narrow the collections; the exact tables
+    /// are returned when `exact` is set.
+    pub(crate) fn fts_partitions(
+        &self,
+        exact: bool,
+        collection: Option<&str>,
+        provider: Option<&str>,
+    ) -> Result<Vec<String>> {
+        let prefix = if exact {
+            "documents_fts_exact_"
+        } else {
+            "documents_fts_"
+        };
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT m.name
+             FROM collections coll
+             JOIN sqlite_master m ON m.type = 'table' AND m.name = ?1 || {}
+             WHERE (?2 IS NULL OR coll.name = ?2)
+               AND (?3 IS NULL OR coll.provider_type = ?3)
+             ORDER BY coll.name",
+            partition_key_sql("coll.name")
+        ))?;
+        let tables = stmt
+            .query_map(params![prefix, collection, provider], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(tables)
+    }
+
+    pub(crate) fn table_exists(&self, name: &str) -> Result<bool> {
+        Ok(self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+            params![name],
+            |row| row.get(0),
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn db_with_notes() -> Database {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        for (coll, hash) in [("notes", "aaa111"), ("it's", "bbb222")] {
+            db.add_collection(coll, "/tmp", "**/*.md", "file", None)
+                .unwrap();
+            db.insert_content(hash, "# Tokio\n\ntokio runtime notes")
+                .unwrap();
+            db.insert_document(coll, "tokio.md", "Tokio", hash, &now, &now, "file", None)
+                .unwrap();
+        }
+        db
+    }
+
+    fn rows(db: &Database, table: &str) -> i64 {
+        db.conn
+            .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+                row.get(0)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_partition_key_matches_sql() {
+        let db = Database::open_in_memory().unwrap();
+        let key: String = db
+            .conn
+            .query_row(
+                &format!("SELECT {}", partition_key_sql("?1")),
+                ["my-notés"],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(key, partition_key("my-notés"));
+    }
+
+    #[test]
+    fn test_documents_land_in_their_collection_table() {
+        let db = db_with_notes();
+        for coll in ["notes", "it's"] {
+            assert_eq!(rows(&db, &fts_table(coll)), 1);
+            assert_eq!(rows(&db, &exact_fts_table(coll)), 1);
+        }
+        assert_eq!(
+            db.fts_partitions(false, None, None).unwrap(),
+            vec![fts_table("it's"), fts_table("notes")]
+        );
+        assert_eq!(
+            db.fts_partitions(true, Some("notes"), None).unwrap(),
+            vec![exact_fts_table("notes")]
+        );
+        assert!(db
+            .fts_partitions(false, None, Some("url"))
+            .unwrap()
+            .is_empty());
+
+        db.deactivate_document("notes", "tokio.md").unwrap();
+        assert_eq!(rows(&db, &fts_table("notes")), 0);
+        assert_eq!(rows(&db, &fts_table("it's")), 1);
+    }
+
+    #[test]
+    fn test_removing_a_collection_drops_its_tables() {
+        let db = db_with_notes();
+        assert!(db.remove_collection("notes").unwrap());
+        assert!(!db.table_exists(&fts_table("notes")).unwrap());
+        assert!(!db.table_exists(&exact_fts_table("notes")).unwrap());
+        assert_eq!(rows(&db, &fts_table("it's")), 1);
+
+        // Its triggers are gone with it
+        let now = Utc::now().to_rfc3339();
+        db.insert_document("notes", "b.md", "B", "aaa111", &now, &now, "file", None)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_renamed_collection_moves_to_a_new_table() {
+        let db = db_with_notes();
+        assert!(db.rename_collection("notes", "journal").unwrap());
+        assert!(!db.table_exists(&fts_table("notes")).unwrap());
+        let filepath: String = db
+            .conn
+            .query_row(
+                &format!("SELECT filepath FROM {}", fts_table("journal")),
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(filepath, "journal/tokio.md");
+        assert_eq!(rows(&db, &exact_fts_table("journal")), 1);
+    }
+}
@@ -3,14 +3,15 @@
 //! Manages extraction, storage, and retrieval of concepts from indexed content.
 //! Concepts are linked to specific chunks for granular search and discovery.
 
-use crate::error::Result;
+use crate::error::{AgentRootError, Result};
 use chrono::Utc;
-use rusqlite::{params, Row};
+use rusqlite::{params, OptionalExtension, Row};
 
+use super::content::docid_from_hash;
 use super::Database;
 
 /// Concept information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct ConceptInfo {
     pub id: i64,
     pub term: String,
@@ -19,7 +20,7 @@ pub struct ConceptInfo {
 }
 
 /// Concept-chunk linkage information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct ConceptChunkInfo {
     pub concept_term: String,
     pub chunk_hash: String,
@@ -29,6 +30,27 @@ pub struct ConceptChunkInfo {
     pub snippet: String,
 }
 
+/// A document linked to a concept
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ConceptDocument {
+    pub docid: String,
+    pub collection: String,
+    pub path: String,
+    pub title: String,
+    /// Chunks of this document linked to the concept
+    pub chunk_count: usize,
+}
+
+/// A concept appearing in the same chunks as another
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RelatedConcept {
+    pub id: i64,
+    pub term: String,
+    pub chunk_count: usize,
+    /// Chunks linked to both concepts
+    pub shared_chunks: usize,
+}
+
 impl Database {
     /// Insert or get existing concept
     /// Returns concept ID
@@ -204,6 +226,176 @@ impl Database {
 
         Ok(results)
     }
+
+    /// Concepts with the most linked chunks
+    pub fn top_concepts(&self, limit: usize) -> Result<Vec<ConceptInfo>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, term, normalized, chunk_count
+             FROM concepts
+             ORDER BY chunk_count DESC, term
+             LIMIT ?1",
+        )?;
+
+        let results = stmt
+            .query_map(params![limit as i64], map_concept_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(results)
+    }
+
+    /// Active documents linked to a concept, most linked chunks first
+    pub fn get_documents_for_concept(
+        &self,
+        concept_id: i64,
+        limit: usize,
+    ) -> Result<Vec<ConceptDocument>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT d.hash, d.collection, d.path, d.title, COUNT(DISTINCT cc.chunk_hash) AS n
+             FROM concept_chunks cc
+             JOIN documents d ON d.hash = cc.document_hash AND d.active = 1
+             WHERE cc.concept_id = ?1
+             GROUP BY d.id
+             ORDER BY n DESC, d.path
+             LIMIT ?2",
+        )?;
+
+        let documents = stmt
+            .query_map(params![concept_id, limit as i64], |row| {
+                Ok(ConceptDocument {
+                    docid: docid_from_hash(&row.get::<_, String>(0)?),
+                    collection: row.get(1)?,
+                    path: row.get(2)?,
+                    title: row.get(3)?,
+                    chunk_count: row.get(4)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(documents)
+    }
+
+    /// Concepts that share chunks with a concept, most shared first
+    pub fn get_cooccurring_concepts(
+        &self,
+        concept_id: i64,
+        limit: usize,
+    ) -> Result<Vec<RelatedConcept>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT c.id, c.term, c.chunk_count, COUNT(DISTINCT other.chunk_hash) AS shared
+             FROM concept_chunks cc
+             JOIN concept_chunks other
+               ON other.chunk_hash = cc.chunk_hash AND other.concept_id != cc.concept_id
+             JOIN concepts c ON c.id = other.concept_id
+             WHERE cc.concept_id = ?1
+             GROUP BY c.id
+             ORDER BY shared DESC, c.chunk_count DESC, c.term
+             LIMIT ?2",
+        )?;
+
+        let related = stmt
+            .query_map(params![concept_id, limit as i64], |row| {
+                Ok(RelatedConcept {
+                    id: row.get(0)?,
+                    term: row.get(1)?,
+                    chunk_count: row.get(2)?,
+                    shared_chunks: row.get(3)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(related)
+    }
+
+    /// Find a concept by numeric ID or by term (case and spacing insensitive)
+    pub fn find_concept(&self, id_or_term: &str) -> Result<Option<ConceptInfo>> {
+        let by_id = id_or_term.trim().parse::<i64>().ok();
+        let concept = self
+            .conn
+            .query_row(
+                "SELECT id, term, normalized, chunk_count
+                 FROM concepts
+                 WHERE id = ?1 OR normalized = ?2
+                 ORDER BY id = ?1 DESC
+                 LIMIT 1",
+                params![by_id, normalize_term(id_or_term.trim())],
+                map_concept_row,
+            )
+            .optional()?;
+
+        Ok(concept)
+    }
+
+    /// Add a concept by hand and link it to every active chunk mentioning it
+    ///
+    /// Returns the concept and how many chunks were newly linked.
+    pub fn add_concept(&self, term: &str) -> Result<(ConceptInfo, usize)> {
+        let term = term.trim();
+        if term.is_empty() {
+            return Err(AgentRootError::InvalidInput(
+                "Concept term cannot be empty".to_string(),
+            ));
+        }
+        let phrase = format!("content:\"{}\"", term.replace('"', "\"\""));
+
+        let (concept_id, linked) = self.in_transaction(|| {
+            let concept_id = self.upsert_concept(term)?;
+            let linked = self.conn.execute(
+                "INSERT OR IGNORE INTO concept_chunks
+                 (concept_id, chunk_hash, document_hash, snippet, created_at)
+                 SELECT ?1, c.hash, c.document_hash,
+                        snippet(chunks_fts, 0, '', '', '...', 16), ?3
+                 FROM chunks_fts
+                 JOIN chunks c ON c.rowid = chunks_fts.rowid
+                 JOIN documents d ON d.hash = c.document_hash AND d.active = 1
+                 WHERE chunks_fts MATCH ?2",
+                params![concept_id, phrase, Utc::now().to_rfc3339()],
+            )?;
+            self.update_concept_stats(concept_id)?;
+            Ok((concept_id, linked))
+        })?;
+
+        let concept = self.conn.query_row(
+            "SELECT id, term, normalized, chunk_count FROM concepts WHERE id = ?1",
+            params![concept_id],
+            map_concept_row,
+        )?;
+        Ok((concept, linked))
+    }
+
+    /// Merge a synonym into another concept, moving its chunk links
+    ///
+    /// Returns the number of links moved; links the target already had are
+    /// dropped along with the merged concept.
+    pub fn merge_concepts(&self, from_id: i64, into_id: i64) -> Result<usize> {
+        if from_id == into_id {
+            return Err(AgentRootError::InvalidInput(
+                "Cannot merge a concept into itself".to_string(),
+            ));
+        }
+
+        self.in_transaction(|| {
+            let moved = self.conn.execute(
+                "UPDATE OR IGNORE concept_chunks SET concept_id = ?2 WHERE concept_id = ?1",
+                params![from_id, into_id],
+            )?;
+            self.delete_concept(from_id)?;
+            self.update_concept_stats(into_id)?;
+            Ok(moved)
+        })
+    }
+
+    /// Delete a concept and its chunk links
+    pub fn delete_concept(&self, concept_id: i64) -> Result<bool> {
+        self.conn.execute(
+            "DELETE FROM concept_chunks WHERE concept_id = ?1",
+            params![concept_id],
+        )?;
+        let deleted = self
+            .conn
+            .execute("DELETE FROM concepts WHERE id = ?1", params![concept_id])?;
+
+        Ok(deleted > 0)
+    }
 }
 
 /// Normalize term for search
@@ -382,4 +574,91 @@ mod tests {
         assert_eq!(total_concepts, 2);
         assert_eq!(total_links, 3);
     }
+
+    #[test]
+    fn test_curate_glossary() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+        db.insert_content("h1", "Vector databases store embeddings for search.")
+            .unwrap();
+        db.insert_document(
+            "notes",
+            "vectors.md",
+            "Vectors",
+            "h1",
+            &now,
+            &now,
+            "file",
+            None,
+        )
+        .unwrap();
+        db.insert_chunk(
+            "c1",
+            "h1",
+            0,
+            0,
+            "Vector databases store embeddings for search.",
+            None,
+            None,
+            1,
+            1,
+            None,
+            None,
+            None,
+            &[],
+            &Default::default(),
+            &[],
+            None,
+            None,
+            &now,
+        )
+        .unwrap();
+
+        let (concept, linked) = db.add_concept("Vector Databases").unwrap();
+        assert_eq!(linked, 1);
+        assert_eq!(concept.chunk_count, 1);
+        assert_eq!(
+            db.find_concept("vector databases").unwrap().unwrap().id,
+            concept.id
+        );
+        assert_eq!(
+            db.find_concept(&concept.id.to_string())
+                .unwrap()
+                .unwrap()
+                .term,
+            "Vector Databases"
+        );
+
+        let synonym = db.upsert_concept("vector store").unwrap();
+        db.link_concept_to_chunk(synonym, "c1", "h1", "snippet")
+            .unwrap();
+        db.link_concept_to_chunk(synonym, "c2", "h1", "snippet")
+            .unwrap();
+        assert_eq!(db.merge_concepts(synonym, concept.id).unwrap(), 1);
+        assert!(db.find_concept("vector store").unwrap().is_none());
+        assert_eq!(db.top_concepts(10).unwrap()[0].chunk_count, 2);
+        assert!(db.merge_concepts(concept.id, concept.id).is_err());
+
+        let documents = db.get_documents_for_concept(concept.id, 10).unwrap();
+        assert_eq!(documents.len(), 1);
+        assert_eq!(documents[0].path, "vectors.md");
+        assert_eq!(documents[0].chunk_count, 2);
+
+        let other = db.upsert_concept("embeddings").unwrap();
+        db.link_concept_to_chunk(other, "c1", "h1", "snippet")
+            .unwrap();
+        db.update_concept_stats(other).unwrap();
+        let related = db.get_cooccurring_concepts(concept.id, 10).unwrap();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].term, "embeddings");
+        assert_eq!(related[0].shared_chunks, 1);
+
+        assert!(db.delete_concept(other).unwrap());
+        assert!(db.delete_concept(concept.id).unwrap());
+        assert!(!db.delete_concept(concept.id).unwrap());
+        assert_eq!(db.get_concept_stats().unwrap(), (0, 0));
+    }
 }
@@ -0,0 +1,244 @@
+//! Regex search over document bodies
+//!
+//! Reads the stored content of active documents line by line, the way
+//! ripgrep reads files, so any pattern the `regex` crate accepts works
+//! without an index. Each matching line is tied to the innermost chunk that
+//! covers it, when the document has been chunked.
+
+use super::content::docid_from_hash;
+use super::Database;
+use crate::error::{AgentRootError, Result};
+use regex::{Regex, RegexBuilder};
+use rusqlite::params;
+use serde::Serialize;
+
+/// Upper bound on the compiled size of a pattern
+const MAX_PATTERN_SIZE: usize = 10 * 1024 * 1024;
+
+/// What to scan and how much to return
+#[derive(Debug, Clone, Default)]
+pub struct RegexSearchOptions {
+    pub collection: Option<String>,
+    /// Glob on document paths within their collection ("src/**/*.rs")
+    pub path_glob: Option<String>,
+    pub ignore_case: bool,
+    /// Lines of context around each match
+    pub context: usize,
+    /// Maximum matching lines; 0 for no limit
+    pub limit: usize,
+}
+
+/// A line matching the pattern
+#[derive(Debug, Clone, Serialize)]
+pub struct RegexMatch {
+    pub collection: String,
+    pub path: String,
+    pub docid: String,
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based byte column of the first match in the line
+    pub column: usize,
+    pub text: String,
+    /// Byte ranges of every match in `text`
+    pub matches: Vec<(usize, usize)>,
+    /// Up to `context` lines before and after, nearest last and first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub before: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub after: Vec<String>,
+    /// Innermost chunk covering the line, and its breadcrumb
+    pub chunk_hash: Option<String>,
+    pub breadcrumb: Option<String>,
+}
+
+/// A chunk's line span
+struct ChunkSpan {
+    hash: String,
+    breadcrumb: Option<String>,
+    start: usize,
+    end: usize,
+}
+
+impl Database {
+    /// Lines of active documents matching a regular expression, ordered by
+    /// collection, path and line
+    pub fn regex_search(
+        &self,
+        pattern: &str,
+        options: &RegexSearchOptions,
+    ) -> Result<Vec<RegexMatch>> {
+        let regex = compile(pattern, options.ignore_case)?;
+        let path_glob = options
+            .path_glob
+            .as_deref()
+            .map(glob::Pattern::new)
+            .transpose()?;
+        let limit = if options.limit == 0 {
+            usize::MAX
+        } else {
+            options.limit
+        };
+
+        let mut stmt = self.conn.prepare(
+            "SELECT d.collection, d.path, d.hash, c.doc
+             FROM documents d
+             JOIN content c ON c.hash = d.hash
+             WHERE d.active = 1 AND (?1 IS NULL OR d.collection = ?1)
+             ORDER BY d.collection, d.path",
+        )?;
+        let mut rows = stmt.query(params![options.collection])?;
+
+        let mut found = Vec::new();
+        while let Some(row) = rows.next()? {
+            let collection: String = row.get(0)?;
+            let path: String = row.get(1)?;
+            if path_glob.as_ref().is_some_and(|g| !g.matches(&path)) {
+                continue;
+            }
+            let body: String = row.get(3)?;
+            if !regex.is_match(&body) {
+                continue;
+            }
+            let hash: String = row.get(2)?;
+            let spans = self.chunk_spans(&hash)?;
+
+            let lines: Vec<&str> = body.lines().collect();
+            for (i, text) in lines.iter().enumerate() {
+                let matches: Vec<(usize, usize)> = regex
+                    .find_iter(text)
+                    .filter(|m| !m.is_empty())
+                    .map(|m| (m.start(), m.end()))
+                    .collect();
+                let Some(&(first, _)) = matches.first() else {
+                    continue;
+                };
+                let line = i + 1;
+                let chunk = spans
+                    .iter()
+                    .filter(|s| s.start <= line && line <= s.end)
+                    .min_by_key(|s| s.end - s.start);
+                found.push(RegexMatch {
+                    collection: collection.clone(),
+                    path: path.clone(),
+                    docid: docid_from_hash(&hash),
+                    line,
+                    column: first + 1,
+                    text: text.to_string(),
+                    matches,
+                    before: lines[i.saturating_sub(options.context)..i]
+                        .iter()
+                        .map(|l| l.to_string())
+                        .collect(),
+                    after: lines[i + 1..(i + 1 + options.context).min(lines.len())]
+                        .iter()
+                        .map(|l| l.to_string())
+                        .collect(),
+                    chunk_hash: chunk.map(|c| c.hash.clone()),
+                    breadcrumb: chunk.and_then(|c| c.breadcrumb.clone()),
+                });
+                if found.len() >= limit {
+                    return Ok(found);
+                }
+            }
+        }
+        Ok(found)
+    }
+
+    fn chunk_spans(&self, doc_hash: &str) -> Result<Vec<ChunkSpan>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT hash, breadcrumb, start_line, end_line FROM chunks
+             WHERE document_hash = ?1 AND start_line IS NOT NULL AND end_line IS NOT NULL",
+        )?;
+        let spans = stmt
+            .query_map(params![doc_hash], |row| {
+                // Every stride of a split chunk carries the same breadcrumb
+                let breadcrumb = row.get::<_, Option<String>>(1)?.map(|mut name| {
+                    if let Some(open) = name.rfind('[').filter(|_| name.ends_with(']')) {
+                        name.truncate(open);
+                    }
+                    name
+                });
+                Ok(ChunkSpan {
+                    hash: row.get(0)?,
+                    breadcrumb,
+                    start: row.get::<_, i64>(2)?.max(0) as usize,
+                    end: row.get::<_, i64>(3)?.max(0) as usize,
+                })
+            })?
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(spans)
+    }
+}
+
+fn compile(pattern: &str, ignore_case: bool) -> Result<Regex> {
+    if pattern.is_empty() {
+        return Err(AgentRootError::InvalidInput(
+            "Empty regex pattern".to_string(),
+        ));
+    }
+    RegexBuilder::new(pattern)
+        .case_insensitive(ignore_case)
+        .size_limit(MAX_PATTERN_SIZE)
+        .build()
+        .map_err(|e| AgentRootError::InvalidInput(format!("Invalid regex pattern: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_regex_search_reports_lines_and_chunks() {
+        let src = tempfile::tempdir().unwrap();
+        std::fs::write(
+            src.path().join("lib.rs"),
+            "pub fn parse(s: &str) -> u32 {\n    // TODO: handle errors\n    s.parse().unwrap()\n}\n\npub fn lex(s: &str) {\n    let _ = s; // todo later\n}\n",
+        )
+        .unwrap();
+        std::fs::write(src.path().join("notes.md"), "# Notes\n\nTODO: write docs\n").unwrap();
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.add_collection("code", src.path().to_str().unwrap(), "**/*", "file", None)
+            .unwrap();
+        db.reindex_collection_with_metadata("code", None)
+            .await
+            .unwrap();
+
+        let found = db
+            .regex_search(r"TODO:?\s+\w+", &RegexSearchOptions::default())
+            .unwrap();
+        let lines: Vec<_> = found
+            .iter()
+            .map(|m| (m.path.as_str(), m.line, m.column))
+            .collect();
+        assert_eq!(lines, vec![("lib.rs", 2, 8), ("notes.md", 3, 1)]);
+        assert_eq!(found[0].text, "    // TODO: handle errors");
+        assert_eq!(found[0].matches, vec![(7, 19)]);
+        assert_eq!(found[0].breadcrumb.as_deref(), Some("parse"));
+        assert!(found[0].chunk_hash.is_some());
+
+        let options = RegexSearchOptions {
+            ignore_case: true,
+            path_glob: Some("*.rs".to_string()),
+            context: 1,
+            ..Default::default()
+        };
+        let found = db.regex_search("todo", &options).unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[1].line, 7);
+        assert_eq!(found[1].breadcrumb.as_deref(), Some("lex"));
+        assert_eq!(found[1].before, vec!["pub fn lex(s: &str) {"]);
+        assert_eq!(found[1].after, vec!["}"]);
+
+        let limited = RegexSearchOptions {
+            ignore_case: true,
+            limit: 1,
+            ..Default::default()
+        };
+        assert_eq!(db.regex_search("todo", &limited).unwrap().len(), 1);
+        assert!(matches!(
+            db.regex_search("(unclosed", &RegexSearchOptions::default()),
+            Err(AgentRootError::InvalidInput(_))
+        ));
+    }
+}
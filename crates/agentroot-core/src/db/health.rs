@@ -0,0 +1,311 @@
+//! Index consistency checks and repairs
+//!
+//! The full-text indexes are kept in step with `documents` by triggers, and
+//! chunks and vectors are keyed by content hash. Crashes, manual edits or
+//! older versions can leave them disagreeing; these checks find such drift
+//! and `cleanup` repairs it.
+
+use super::fts_partitions::{exact_fts_table, fts_table};
+use super::vectors::stored_dimensions;
+use super::Database;
+use crate::error::Result;
+use rusqlite::params;
+
+/// How far the full-text indexes have drifted from the active documents
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct SearchIndexDrift {
+    /// Active documents missing from a full-text index
+    pub missing: usize,
+    /// Full-text rows of documents that are gone or inactive
+    pub extra: usize,
+}
+
+impl SearchIndexDrift {
+    pub fn is_consistent(&self) -> bool {
+        self.missing == 0 && self.extra == 0
+    }
+}
+
+/// Stored vectors whose size does not match their model's dimensions
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct DimensionMismatch {
+    pub model: String,
+    /// Dimensions registered for the model
+    pub expected: usize,
+    /// Vectors of any other size
+    pub vectors: usize,
+}
+
+impl Database {
+    /// Problems reported by `PRAGMA integrity_check`; empty when the file is sound
+    pub fn integrity_problems(&self) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare("PRAGMA integrity_check")?;
+        let rows = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(rows.into_iter().filter(|row| row != "ok").collect())
+    }
+
+    /// Compare the full-text indexes with the active documents
+    ///
+    /// Each collection's documents are checked against its own tables; a
+    /// missing table counts all of the collection's documents as missing.
+    pub fn search_index_drift(&self) -> Result<SearchIndexDrift> {
+        let mut drift = SearchIndexDrift::default();
+        for collection in self.collection_names()? {
+            for table in [fts_table(&collection), exact_fts_table(&collection)] {
+                let (missing, extra): (i64, i64) = if self.table_exists(&table)? {
+                    self.conn.query_row(
+                        &format!(
+                            "SELECT
+                                (SELECT COUNT(*) FROM documents
+                                 WHERE collection = ?1 AND active = 1
+                                   AND id NOT IN (SELECT rowid FROM {0})),
+                                (SELECT COUNT(*) FROM {0}
+                                 WHERE rowid NOT IN (SELECT id FROM documents
+                                                     WHERE collection = ?1 AND active = 1))",
+                            table
+                        ),
+                        params![collection],
+                        |row| Ok((row.get(0)?, row.get(1)?)),
+                    )?
+                } else {
+                    let missing = self.conn.query_row(
+                        "SELECT COUNT(*) FROM documents WHERE collection = ?1 AND active = 1",
+                        params![collection],
+                        |row| row.get(0),
+                    )?;
+                    (missing, 0)
+                };
+                drift.missing += missing as usize;
+                drift.extra += extra as usize;
+            }
+        }
+        Ok(drift)
+    }
+
+    /// Drop full-text rows of inactive documents and add the missing ones
+    ///
+    /// Lost tables and triggers are recreated. Returns the drift that was
+    /// repaired.
+    pub fn repair_search_index(&self) -> Result<SearchIndexDrift> {
+        let drift = self.search_index_drift()?;
+        if drift.is_consistent() {
+            return Ok(drift);
+        }
+        let collections = self.collection_names()?;
+        self.in_transaction(|| {
+            for collection in &collections {
+                self.create_fts_partition(collection)?;
+                let tables = [fts_table(collection), exact_fts_table(collection)];
+                for table in &tables {
+                    self.conn.execute(
+                        &format!(
+                            "DELETE FROM {} WHERE rowid NOT IN
+                             (SELECT id FROM documents WHERE collection = ?1 AND active = 1)",
+                            table
+                        ),
+                        params![collection],
+                    )?;
+                }
+                // Touching the title runs the update triggers, which reinsert the rows
+                self.conn.execute(
+                    &format!(
+                        "UPDATE documents SET title = title
+                         WHERE collection = ?1 AND active = 1
+                           AND (id NOT IN (SELECT rowid FROM {}) OR id NOT IN (SELECT rowid FROM {}))",
+                        tables[0], tables[1]
+                    ),
+                    params![collection],
+                )?;
+            }
+            Ok(())
+        })?;
+        Ok(drift)
+    }
+
+    fn collection_names(&self) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT name FROM collections ORDER BY name")?;
+        let names = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(names)
+    }
+
+    /// Content hashes with chunks but no active document
+    fn orphaned_chunk_documents(&self) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT document_hash FROM chunks
+             WHERE document_hash NOT IN (SELECT hash FROM documents WHERE active = 1)",
+        )?;
+        let hashes = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(hashes)
+    }
+
+    /// Chunks no active document uses
+    pub fn count_orphaned_chunks(&self) -> Result<usize> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM chunks
+             WHERE document_hash NOT IN (SELECT hash FROM documents WHERE active = 1)",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    /// Delete chunks (with their labels and symbols) no active document uses
+    ///
+    /// Returns the number of documents whose chunks were removed.
+    pub fn cleanup_orphaned_chunks(&self) -> Result<usize> {
+        let hashes = self.orphaned_chunk_documents()?;
+        if hashes.is_empty() {
+            return Ok(0);
+        }
+        self.in_transaction(|| {
+            for hash in &hashes {
+                self.delete_chunks_for_document(hash)?;
+            }
+            Ok(hashes.len())
+        })
+    }
+
+    /// Models with stored vectors of a size other than their registered one
+    pub fn dimension_mismatches(&self) -> Result<Vec<DimensionMismatch>> {
+        let table_exists: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'embeddings')",
+            [],
+            |row| row.get(0),
+        )?;
+        if !table_exists {
+            return Ok(Vec::new());
+        }
+
+        let mut mismatches = Vec::new();
+        for model in self.embedding_models()? {
+            let mut stmt = self.conn.prepare(
+                "SELECT substr(embedding, 1, 8), length(embedding) FROM embeddings
+                 WHERE model = ?1",
+            )?;
+            let mut rows = stmt.query(params![model.model])?;
+            let mut vectors = 0;
+            while let Some(row) = rows.next()? {
+                let head: Vec<u8> = row.get(0)?;
+                let len: i64 = row.get(1)?;
+                if stored_dimensions(&head, len as usize) != Some(model.dimensions) {
+                    vectors += 1;
+                }
+            }
+            if vectors > 0 {
+                mismatches.push(DimensionMismatch {
+                    model: model.model,
+                    expected: model.dimensions,
+                    vectors,
+                });
+            }
+        }
+        Ok(mismatches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn indexed_db() -> (tempfile::TempDir, Database) {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp.path().join("a.md"), "# Alpha\n\nFirst note.").unwrap();
+        std::fs::write(temp.path().join("b.md"), "# Beta\n\nSecond note.").unwrap();
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.add_collection(
+            "notes",
+            &temp.path().to_string_lossy(),
+            "**/*.md",
+            "file",
+            None,
+        )
+        .unwrap();
+        db.reindex_collection_with_metadata("notes", None)
+            .await
+            .unwrap();
+        (temp, db)
+    }
+
+    #[tokio::test]
+    async fn test_healthy_index_has_no_findings() {
+        let (_temp, db) = indexed_db().await;
+        assert!(db.integrity_problems().unwrap().is_empty());
+        assert!(db.search_index_drift().unwrap().is_consistent());
+        assert_eq!(db.count_orphaned_chunks().unwrap(), 0);
+        assert!(db.dimension_mismatches().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_index_drift_is_repaired() {
+        let (_temp, db) = indexed_db().await;
+        let id: i64 = db
+            .conn
+            .query_row("SELECT id FROM documents WHERE path = 'a.md'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        let table = fts_table("notes");
+        db.conn
+            .execute(
+                &format!("DELETE FROM {} WHERE rowid = ?1", table),
+                params![id],
+            )
+            .unwrap();
+        db.conn
+            .execute(
+                &format!("INSERT INTO {}(rowid, title) VALUES (9999, 'ghost')", table),
+                [],
+            )
+            .unwrap();
+
+        let drift = db.search_index_drift().unwrap();
+        assert_eq!(
+            drift,
+            SearchIndexDrift {
+                missing: 1,
+                extra: 1
+            }
+        );
+        assert_eq!(db.repair_search_index().unwrap(), drift);
+        assert!(db.search_index_drift().unwrap().is_consistent());
+        let found: i64 = db
+            .conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM {0} WHERE {0} MATCH 'alpha'", table),
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(found, 1);
+    }
+
+    #[tokio::test]
+    async fn test_lost_collection_table_is_rebuilt() {
+        let (_temp, db) = indexed_db().await;
+        db.conn
+            .execute_batch(&format!("DROP TABLE {}", exact_fts_table("notes")))
+            .unwrap();
+
+        assert_eq!(db.search_index_drift().unwrap().missing, 2);
+        db.repair_search_index().unwrap();
+        assert!(db.search_index_drift().unwrap().is_consistent());
+    }
+
+    #[tokio::test]
+    async fn test_orphaned_chunks_are_removed() {
+        let (_temp, db) = indexed_db().await;
+        db.deactivate_document("notes", "b.md").unwrap();
+        assert!(db.count_orphaned_chunks().unwrap() > 0);
+        assert_eq!(db.cleanup_orphaned_chunks().unwrap(), 1);
+        assert_eq!(db.count_orphaned_chunks().unwrap(), 0);
+    }
+}
@@ -0,0 +1,297 @@
+//! Document version history
+//!
+//! Content is stored by hash, so every body a document has had stays in
+//! `content` as long as something refers to it. `document_versions` is that
+//! reference: triggers on `documents` append a row whenever a path is indexed
+//! with content it did not have just before, and `cleanup` keeps the content
+//! of every recorded version. Versions are numbered from 1, oldest first.
+
+use super::content::docid_from_hash;
+use super::Database;
+use crate::config::virtual_path::{is_virtual_path, parse_virtual_path};
+use crate::error::{AgentRootError, Result};
+use rusqlite::params;
+use serde::Serialize;
+
+/// One recorded version of a document
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentVersion {
+    /// 1 for the first version indexed
+    pub version: usize,
+    pub hash: String,
+    pub docid: String,
+    pub title: String,
+    pub recorded_at: String,
+    /// Whether this is the content indexed now
+    pub current: bool,
+}
+
+/// All recorded versions of one path
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentHistory {
+    pub collection: String,
+    pub path: String,
+    /// Oldest first
+    pub versions: Vec<DocumentVersion>,
+}
+
+impl DocumentHistory {
+    /// Version by number (`3`) or docid (`#a1b2c3`, or a longer hash prefix)
+    pub fn find(&self, spec: &str) -> Option<&DocumentVersion> {
+        let spec = spec.trim();
+        if let Ok(n) = spec.parse::<usize>() {
+            if let Some(version) = self.versions.iter().find(|v| v.version == n) {
+                return Some(version);
+            }
+        }
+        let prefix = spec.trim_start_matches('#').to_ascii_lowercase();
+        if prefix.len() < 6 {
+            return None;
+        }
+        // The latest version wins if a path went back to earlier content
+        self.versions
+            .iter()
+            .rev()
+            .find(|v| v.hash.starts_with(&prefix))
+    }
+
+    /// The version indexed now, or the last one for a removed document
+    pub fn latest(&self) -> Option<&DocumentVersion> {
+        self.versions.last()
+    }
+}
+
+impl Database {
+    /// Recorded versions of the document a docid, virtual path or
+    /// collection/path refers to
+    ///
+    /// Removed documents keep their history and can be named by
+    /// collection/path or by the docid of any of their versions.
+    pub fn document_history(&self, query: &str) -> Result<DocumentHistory> {
+        let (collection, path) = self
+            .locate_versioned_document(query)?
+            .ok_or_else(|| AgentRootError::DocumentNotFound(query.trim().to_string()))?;
+
+        let current: Option<String> = self
+            .find_active_document(&collection, &path)?
+            .map(|doc| doc.hash);
+        let mut stmt = self.conn.prepare(
+            "SELECT hash, title, recorded_at FROM document_versions
+             WHERE collection = ?1 AND path = ?2
+             ORDER BY id",
+        )?;
+        let rows = stmt
+            .query_map(params![collection, path], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let count = rows.len();
+        let versions = rows
+            .into_iter()
+            .enumerate()
+            .map(|(i, (hash, title, recorded_at))| DocumentVersion {
+                version: i + 1,
+                docid: docid_from_hash(&hash),
+                current: i + 1 == count && current.as_deref() == Some(hash.as_str()),
+                hash,
+                title,
+                recorded_at,
+            })
+            .collect();
+
+        Ok(DocumentHistory {
+            collection,
+            path,
+            versions,
+        })
+    }
+
+    /// Body of one version of a document; see [`DocumentHistory::find`]
+    pub fn get_document_version(
+        &self,
+        query: &str,
+        version: &str,
+    ) -> Result<(DocumentVersion, String)> {
+        let history = self.document_history(query)?;
+        let found = find_version(&history, version)?;
+        let body = self.version_body(found)?;
+        Ok((found.clone(), body))
+    }
+
+    /// Unified diff between two versions of a document
+    ///
+    /// `to` defaults to the latest version.
+    pub fn diff_document_versions(
+        &self,
+        query: &str,
+        from: &str,
+        to: Option<&str>,
+    ) -> Result<String> {
+        let history = self.document_history(query)?;
+        let old = find_version(&history, from)?;
+        let new = match to {
+            Some(spec) => find_version(&history, spec)?,
+            None => history
+                .latest()
+                .ok_or_else(|| AgentRootError::DocumentNotFound(query.trim().to_string()))?,
+        };
+
+        let old_body = self.version_body(old)?;
+        let new_body = self.version_body(new)?;
+        let label = |v: &DocumentVersion| {
+            format!(
+                "{}/{} (v{}, #{})",
+                history.collection, history.path, v.version, v.docid
+            )
+        };
+        Ok(similar::TextDiff::from_lines(&old_body, &new_body)
+            .unified_diff()
+            .context_radius(3)
+            .header(&label(old), &label(new))
+            .to_string())
+    }
+
+    fn version_body(&self, version: &DocumentVersion) -> Result<String> {
+        self.get_content(&version.hash)?.ok_or_else(|| {
+            AgentRootError::DocumentNotFound(format!(
+                "content of version {} (#{})",
+                version.version, version.docid
+            ))
+        })
+    }
+
+    /// Collection and path a query names, if the path has recorded versions
+    fn locate_versioned_document(&self, query: &str) -> Result<Option<(String, String)>> {
+        let query = query.trim();
+        let mut candidates = Vec::new();
+
+        if query.starts_with('#')
+            || (query.len() >= 6 && query.chars().all(|c| c.is_ascii_hexdigit()))
+        {
+            let prefix = query.trim_start_matches('#').to_ascii_lowercase();
+            // Current documents first, then any recorded version
+            let found = self
+                .conn
+                .query_row(
+                    "SELECT collection, path FROM documents
+                     WHERE hash LIKE ?1 || '%' AND active = 1
+                     UNION ALL
+                     SELECT collection, path FROM (
+                         SELECT collection, path FROM document_versions
+                         WHERE hash LIKE ?1 || '%' ORDER BY id DESC
+                     )
+                     LIMIT 1",
+                    params![prefix],
+                    |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+                )
+                .ok();
+            candidates.extend(found);
+        }
+        if is_virtual_path(query) {
+            if let Ok(location) = parse_virtual_path(query) {
+                candidates.push(location);
+            }
+        }
+        if let Some((collection, path)) = query.split_once('/') {
+            candidates.push((collection.to_string(), path.to_string()));
+        }
+
+        for (collection, path) in candidates {
+            let has_versions: bool = self.conn.query_row(
+                "SELECT COUNT(*) > 0 FROM document_versions WHERE collection = ?1 AND path = ?2",
+                params![collection, path],
+                |row| row.get(0),
+            )?;
+            if has_versions {
+                return Ok(Some((collection, path)));
+            }
+        }
+        Ok(None)
+    }
+}
+
+fn find_version<'a>(history: &'a DocumentHistory, spec: &str) -> Result<&'a DocumentVersion> {
+    history.find(spec).ok_or_else(|| {
+        AgentRootError::InvalidInput(format!(
+            "No version '{}' of {}/{} ({} recorded)",
+            spec.trim(),
+            history.collection,
+            history.path,
+            history.versions.len()
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::hash_content;
+
+    fn index(db: &Database, path: &str, body: &str) {
+        let hash = hash_content(body);
+        let now = chrono::Utc::now().to_rfc3339();
+        db.insert_content(&hash, body).unwrap();
+        match db.find_active_document("notes", path).unwrap() {
+            Some(doc) => db.update_document(doc.id, "Plan", &hash, &now).unwrap(),
+            None => {
+                db.insert_document("notes", path, "Plan", &hash, &now, &now, "file", None)
+                    .unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_history_records_each_content_change() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.add_collection("notes", "/tmp/notes", "**/*.md", "file", None)
+            .unwrap();
+
+        index(&db, "plan.md", "# Plan\nship on friday\n");
+        index(&db, "plan.md", "# Plan\nship on friday\n");
+        index(&db, "plan.md", "# Plan\nship on monday\n");
+
+        let history = db.document_history("notes/plan.md").unwrap();
+        assert_eq!(history.versions.len(), 2);
+        assert!(!history.versions[0].current);
+        assert!(history.versions[1].current);
+
+        // Old bodies survive cleanup and can be fetched by number or docid
+        db.cleanup_orphaned_content().unwrap();
+        let (v1, body) = db.get_document_version("notes/plan.md", "1").unwrap();
+        assert_eq!(body, "# Plan\nship on friday\n");
+        let by_docid = db
+            .get_document_version(&format!("#{}", v1.docid), &v1.docid)
+            .unwrap();
+        assert_eq!(by_docid.0.version, 1);
+        assert!(db.get_document_version("notes/plan.md", "3").is_err());
+
+        let diff = db
+            .diff_document_versions("notes/plan.md", "1", None)
+            .unwrap();
+        assert!(diff.contains("-ship on friday"));
+        assert!(diff.contains("+ship on monday"));
+        assert!(diff.contains("notes/plan.md (v2"));
+    }
+
+    #[test]
+    fn test_history_outlives_removed_document() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.add_collection("notes", "/tmp/notes", "**/*.md", "file", None)
+            .unwrap();
+
+        index(&db, "old.md", "gone soon\n");
+        db.deactivate_document("notes", "old.md").unwrap();
+
+        let history = db.document_history("notes/old.md").unwrap();
+        assert_eq!(history.versions.len(), 1);
+        assert!(!history.versions[0].current);
+        assert!(db.document_history("notes/never.md").is_err());
+    }
+}
@@ -0,0 +1,401 @@
+//! Persistent background job queue
+//!
+//! Heavy work (reindexing, embedding, metadata backfill, PageRank) is queued
+//! here and drained by a worker, so interactive tools return immediately.
+//! Jobs are claimed by priority, then age; failed attempts are retried with
+//! exponential backoff until `max_attempts` is reached.
+
+use super::Database;
+use crate::error::{AgentRootError, Result};
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+/// Attempts made before a job is marked failed
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry; doubled on each further attempt
+const RETRY_BASE_SECS: i64 = 30;
+
+/// Upper bound on the retry delay
+const RETRY_MAX_SECS: i64 = 3600;
+
+/// Finished jobs kept in the table; older ones are pruned on enqueue
+const MAX_FINISHED_JOBS: i64 = 500;
+
+/// Work a job performs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JobKind {
+    /// Reindex one collection, or all when None
+    Reindex { collection: Option<String> },
+    /// Embed documents missing vectors, or all documents when forced
+    Embed { force: bool },
+    /// Generate LLM metadata for one collection, or all when None
+    MetadataBackfill { collection: Option<String> },
+    /// Rebuild the link graph and recompute PageRank
+    Pagerank,
+}
+
+impl JobKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            JobKind::Reindex { .. } => "reindex",
+            JobKind::Embed { .. } => "embed",
+            JobKind::MetadataBackfill { .. } => "metadata_backfill",
+            JobKind::Pagerank => "pagerank",
+        }
+    }
+}
+
+/// Lifecycle state of a job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Succeeded => "succeeded",
+            JobStatus::Failed => "failed",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl std::str::FromStr for JobStatus {
+    type Err = AgentRootError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "queued" => Ok(JobStatus::Queued),
+            "running" => Ok(JobStatus::Running),
+            "succeeded" => Ok(JobStatus::Succeeded),
+            "failed" => Ok(JobStatus::Failed),
+            "cancelled" => Ok(JobStatus::Cancelled),
+            other => Err(AgentRootError::InvalidInput(format!(
+                "Unknown job status: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// A queued, running or finished job
+#[derive(Debug, Clone, Serialize)]
+pub struct Job {
+    pub id: i64,
+    #[serde(flatten)]
+    pub kind: JobKind,
+    pub status: JobStatus,
+    /// Higher runs first
+    pub priority: i32,
+    pub attempts: u32,
+    pub max_attempts: u32,
+    pub last_error: Option<String>,
+    /// Summary of what a successful run did
+    pub result: Option<String>,
+    /// Earliest time the job may be claimed
+    pub run_after: String,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+}
+
+const JOB_COLUMNS: &str = "id, payload, status, priority, attempts, max_attempts, last_error,
+     result, run_after, created_at, started_at, finished_at";
+
+fn job_from_row(row: &Row) -> rusqlite::Result<Job> {
+    let payload: String = row.get(1)?;
+    let status: String = row.get(2)?;
+    Ok(Job {
+        id: row.get(0)?,
+        kind: serde_json::from_str(&payload).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(1, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+        status: status.parse().map_err(|e: AgentRootError| {
+            rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+        priority: row.get(3)?,
+        attempts: row.get(4)?,
+        max_attempts: row.get(5)?,
+        last_error: row.get(6)?,
+        result: row.get(7)?,
+        run_after: row.get(8)?,
+        created_at: row.get(9)?,
+        started_at: row.get(10)?,
+        finished_at: row.get(11)?,
+    })
+}
+
+/// Backoff before retrying after `attempts` failed attempts
+fn retry_delay(attempts: u32) -> Duration {
+    let secs = RETRY_BASE_SECS.saturating_mul(1i64 << attempts.saturating_sub(1).min(16));
+    Duration::seconds(secs.min(RETRY_MAX_SECS))
+}
+
+impl Database {
+    /// Queue a job, returning its id
+    ///
+    /// An identical job that is still queued is reused (keeping the higher
+    /// priority) rather than queued twice.
+    pub fn enqueue_job(&self, kind: &JobKind, priority: i32) -> Result<i64> {
+        let payload = serde_json::to_string(kind)?;
+        let existing: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT id FROM jobs WHERE status = 'queued' AND payload = ?1
+                 ORDER BY id LIMIT 1",
+                params![payload],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(id) = existing {
+            self.conn.execute(
+                "UPDATE jobs SET priority = MAX(priority, ?1) WHERE id = ?2",
+                params![priority, id],
+            )?;
+            return Ok(id);
+        }
+
+        let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT INTO jobs (kind, payload, priority, max_attempts, run_after, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+            params![kind.name(), payload, priority, DEFAULT_MAX_ATTEMPTS, now],
+        )?;
+        let id = self.conn.last_insert_rowid();
+
+        self.conn.execute(
+            "DELETE FROM jobs WHERE id IN (
+                 SELECT id FROM jobs WHERE status IN ('succeeded', 'failed', 'cancelled')
+                 ORDER BY id DESC LIMIT -1 OFFSET ?1
+             )",
+            params![MAX_FINISHED_JOBS],
+        )?;
+        Ok(id)
+    }
+
+    /// Mark the next runnable job as running and return it
+    pub fn claim_next_job(&self) -> Result<Option<Job>> {
+        let now = Utc::now().to_rfc3339();
+        self.in_transaction(|| {
+            let id: Option<i64> = self
+                .conn
+                .query_row(
+                    "SELECT id FROM jobs WHERE status = 'queued' AND run_after <= ?1
+                     ORDER BY priority DESC, id LIMIT 1",
+                    params![now],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let Some(id) = id else {
+                return Ok(None);
+            };
+            self.conn.execute(
+                "UPDATE jobs SET status = 'running', attempts = attempts + 1, started_at = ?1
+                 WHERE id = ?2",
+                params![now, id],
+            )?;
+            self.get_job(id)
+        })
+    }
+
+    /// Record a successful run
+    pub fn complete_job(&self, id: i64, result: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE jobs SET status = 'succeeded', result = ?1, last_error = NULL, finished_at = ?2
+             WHERE id = ?3 AND status = 'running'",
+            params![result, Utc::now().to_rfc3339(), id],
+        )?;
+        Ok(())
+    }
+
+    /// Record a failed attempt, scheduling a retry while attempts remain
+    ///
+    /// Returns the job's new status.
+    pub fn fail_job(&self, id: i64, error: &str) -> Result<JobStatus> {
+        let job = self
+            .get_job(id)?
+            .ok_or_else(|| AgentRootError::InvalidInput(format!("Job not found: {}", id)))?;
+        if job.status != JobStatus::Running {
+            return Ok(job.status);
+        }
+
+        let now = Utc::now();
+        if job.attempts < job.max_attempts {
+            let run_after = now + retry_delay(job.attempts);
+            self.conn.execute(
+                "UPDATE jobs SET status = 'queued', last_error = ?1, run_after = ?2
+                 WHERE id = ?3",
+                params![error, run_after.to_rfc3339(), id],
+            )?;
+            Ok(JobStatus::Queued)
+        } else {
+            self.conn.execute(
+                "UPDATE jobs SET status = 'failed', last_error = ?1, finished_at = ?2
+                 WHERE id = ?3",
+                params![error, now.to_rfc3339(), id],
+            )?;
+            Ok(JobStatus::Failed)
+        }
+    }
+
+    /// Cancel a queued job; running and finished jobs are left alone
+    pub fn cancel_job(&self, id: i64) -> Result<bool> {
+        let changed = self.conn.execute(
+            "UPDATE jobs SET status = 'cancelled', finished_at = ?1
+             WHERE id = ?2 AND status = 'queued'",
+            params![Utc::now().to_rfc3339(), id],
+        )?;
+        Ok(changed > 0)
+    }
+
+    /// Return jobs left running by a worker that died to the queue
+    ///
+    /// Jobs started before `older_than` ago are requeued without consuming an
+    /// attempt. Returns how many were recovered.
+    pub fn recover_stale_jobs(&self, older_than: std::time::Duration) -> Result<usize> {
+        let cutoff: DateTime<Utc> =
+            Utc::now() - Duration::from_std(older_than).unwrap_or(Duration::zero());
+        Ok(self.conn.execute(
+            "UPDATE jobs SET status = 'queued', attempts = MAX(attempts - 1, 0), started_at = NULL
+             WHERE status = 'running' AND started_at < ?1",
+            params![cutoff.to_rfc3339()],
+        )?)
+    }
+
+    pub fn get_job(&self, id: i64) -> Result<Option<Job>> {
+        Ok(self
+            .conn
+            .query_row(
+                &format!("SELECT {} FROM jobs WHERE id = ?1", JOB_COLUMNS),
+                params![id],
+                job_from_row,
+            )
+            .optional()?)
+    }
+
+    /// Newest jobs first, optionally restricted to one status
+    pub fn list_jobs(&self, status: Option<JobStatus>, limit: usize) -> Result<Vec<Job>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM jobs WHERE ?1 IS NULL OR status = ?1 ORDER BY id DESC LIMIT ?2",
+            JOB_COLUMNS
+        ))?;
+        let jobs = stmt
+            .query_map(
+                params![status.map(|s| s.as_str()), limit as i64],
+                job_from_row,
+            )?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(jobs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Database {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db
+    }
+
+    #[test]
+    fn test_claim_order_and_dedup() {
+        let db = setup_db();
+        let embed = db.enqueue_job(&JobKind::Embed { force: false }, 0).unwrap();
+        let pagerank = db.enqueue_job(&JobKind::Pagerank, 5).unwrap();
+        let again = db
+            .enqueue_job(&JobKind::Embed { force: false }, 10)
+            .unwrap();
+        assert_eq!(embed, again);
+
+        let first = db.claim_next_job().unwrap().unwrap();
+        assert_eq!(first.id, embed);
+        assert_eq!(first.status, JobStatus::Running);
+        assert_eq!(first.attempts, 1);
+
+        let second = db.claim_next_job().unwrap().unwrap();
+        assert_eq!(second.id, pagerank);
+        assert!(db.claim_next_job().unwrap().is_none());
+
+        db.complete_job(first.id, "3 documents").unwrap();
+        let done = db.get_job(first.id).unwrap().unwrap();
+        assert_eq!(done.status, JobStatus::Succeeded);
+        assert_eq!(done.result.as_deref(), Some("3 documents"));
+    }
+
+    #[test]
+    fn test_retry_with_backoff_then_fail() {
+        let db = setup_db();
+        let id = db
+            .enqueue_job(
+                &JobKind::Reindex {
+                    collection: Some("notes".to_string()),
+                },
+                0,
+            )
+            .unwrap();
+
+        db.claim_next_job().unwrap().unwrap();
+        assert_eq!(db.fail_job(id, "boom").unwrap(), JobStatus::Queued);
+        // Backoff keeps the retry out of reach for now
+        assert!(db.claim_next_job().unwrap().is_none());
+
+        for _ in 1..DEFAULT_MAX_ATTEMPTS {
+            db.conn
+                .execute(
+                    "UPDATE jobs SET run_after = ?1 WHERE id = ?2",
+                    params![Utc::now().to_rfc3339(), id],
+                )
+                .unwrap();
+            db.claim_next_job().unwrap().unwrap();
+            db.fail_job(id, "boom").unwrap();
+        }
+
+        let job = db.get_job(id).unwrap().unwrap();
+        assert_eq!(job.status, JobStatus::Failed);
+        assert_eq!(job.attempts, DEFAULT_MAX_ATTEMPTS);
+        assert_eq!(job.last_error.as_deref(), Some("boom"));
+        assert_eq!(db.list_jobs(Some(JobStatus::Failed), 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_cancel_and_recover() {
+        let db = setup_db();
+        let queued = db.enqueue_job(&JobKind::Pagerank, 0).unwrap();
+        assert!(db.cancel_job(queued).unwrap());
+        assert!(!db.cancel_job(queued).unwrap());
+
+        let id = db
+            .enqueue_job(&JobKind::MetadataBackfill { collection: None }, 0)
+            .unwrap();
+        db.claim_next_job().unwrap().unwrap();
+        assert_eq!(
+            db.recover_stale_jobs(std::time::Duration::from_secs(3600))
+                .unwrap(),
+            0
+        );
+        assert_eq!(db.recover_stale_jobs(std::time::Duration::ZERO).unwrap(), 1);
+        let job = db.get_job(id).unwrap().unwrap();
+        assert_eq!(job.status, JobStatus::Queued);
+        assert_eq!(job.attempts, 0);
+    }
+
+    #[test]
+    fn test_retry_delay_is_capped() {
+        assert_eq!(retry_delay(1), Duration::seconds(30));
+        assert_eq!(retry_delay(2), Duration::seconds(60));
+        assert_eq!(retry_delay(40), Duration::seconds(RETRY_MAX_SECS));
+    }
+}
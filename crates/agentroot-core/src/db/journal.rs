@@ -0,0 +1,179 @@
+//! Daily-note dates
+//!
+//! Documents named after a day (or with a `date` frontmatter field) get a
+//! `note_date`, so journals can be searched by when the note is about rather
+//! than when the file was last touched.
+
+use super::content::docid_from_hash;
+use super::Database;
+use crate::db::UserMetadata;
+use crate::error::Result;
+use crate::index::note_date;
+use chrono::NaiveDate;
+use rusqlite::params;
+use std::collections::HashMap;
+
+/// A dated note
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct JournalEntry {
+    pub docid: String,
+    pub collection: String,
+    pub path: String,
+    pub title: String,
+    /// YYYY-MM-DD
+    pub date: String,
+}
+
+impl Database {
+    /// Recompute a document's note date from its path and frontmatter
+    pub(crate) fn refresh_note_date(&self, doc_id: i64) -> Result<()> {
+        let (path, metadata): (String, Option<String>) = self.conn.query_row(
+            "SELECT path, user_metadata FROM documents WHERE id = ?1",
+            params![doc_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let metadata = metadata.and_then(|json| UserMetadata::from_json(&json).ok());
+        let date = note_date(&path, metadata.as_ref()).map(|d| d.to_string());
+        self.conn.execute(
+            "UPDATE documents SET note_date = ?1 WHERE id = ?2",
+            params![date, doc_id],
+        )?;
+        Ok(())
+    }
+
+    /// Date every document indexed before note dates existed
+    pub(crate) fn backfill_note_dates(&self) -> Result<usize> {
+        let ids: Vec<i64> = self
+            .conn
+            .prepare("SELECT id FROM documents")?
+            .query_map([], |row| row.get(0))?
+            .collect::<std::result::Result<_, _>>()?;
+        for id in &ids {
+            self.refresh_note_date(*id)?;
+        }
+        Ok(ids.len())
+    }
+
+    /// Dated notes between two days (inclusive), newest first
+    pub fn journal_entries(
+        &self,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+        collection: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<JournalEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT hash, collection, path, title, note_date
+             FROM documents
+             WHERE active = 1 AND note_date IS NOT NULL
+               AND (?1 IS NULL OR note_date >= ?1)
+               AND (?2 IS NULL OR note_date <= ?2)
+               AND (?3 IS NULL OR collection = ?3)
+             ORDER BY note_date DESC, path
+             LIMIT ?4",
+        )?;
+        let entries = stmt
+            .query_map(
+                params![
+                    from.map(|d| d.to_string()),
+                    to.map(|d| d.to_string()),
+                    collection,
+                    limit as i64
+                ],
+                |row| {
+                    Ok(JournalEntry {
+                        docid: docid_from_hash(&row.get::<_, String>(0)?),
+                        collection: row.get(1)?,
+                        path: row.get(2)?,
+                        title: row.get(3)?,
+                        date: row.get(4)?,
+                    })
+                },
+            )?
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(entries)
+    }
+
+    /// The day each document is about: its note date, else its modification day
+    pub fn document_dates(&self, hashes: &[&str]) -> Result<HashMap<String, NaiveDate>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT COALESCE(note_date, substr(modified_at, 1, 10))
+             FROM documents WHERE hash = ?1 AND active = 1
+             ORDER BY note_date IS NULL
+             LIMIT 1",
+        )?;
+        let mut dates = HashMap::new();
+        for hash in hashes {
+            let date: Option<String> = stmt
+                .query_row(params![hash], |row| row.get(0))
+                .ok()
+                .flatten();
+            if let Some(date) = date.and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok()) {
+                dates.insert(hash.to_string(), date);
+            }
+        }
+        Ok(dates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::MetadataValue;
+
+    #[test]
+    fn test_journal_entries() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = "2024-06-01T12:00:00+00:00";
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+        let mut ids = Vec::new();
+        for (hash, path) in [
+            ("aaa111", "daily/2024-03-05.md"),
+            ("bbb222", "daily/2024-03-20.md"),
+            ("ccc333", "daily/2024-04-02.md"),
+            ("ddd444", "postgres.md"),
+        ] {
+            db.insert_content(hash, path).unwrap();
+            let id = db
+                .insert_document("notes", path, path, hash, now, now, "file", None)
+                .unwrap();
+            ids.push(id);
+        }
+        db.backfill_note_dates().unwrap();
+
+        let march = db
+            .journal_entries(
+                NaiveDate::from_ymd_opt(2024, 3, 1),
+                NaiveDate::from_ymd_opt(2024, 3, 31),
+                None,
+                10,
+            )
+            .unwrap();
+        let dates: Vec<_> = march.iter().map(|e| e.date.as_str()).collect();
+        assert_eq!(dates, vec!["2024-03-20", "2024-03-05"]);
+        assert_eq!(db.journal_entries(None, None, None, 10).unwrap().len(), 3);
+
+        // Frontmatter dates undated files
+        let mut metadata = UserMetadata::new();
+        metadata.add("date", MetadataValue::Text("2024-03-10".into()));
+        db.conn
+            .execute(
+                "UPDATE documents SET user_metadata = ?1 WHERE id = ?2",
+                params![metadata.to_json().unwrap(), ids[3]],
+            )
+            .unwrap();
+        db.refresh_note_date(ids[3]).unwrap();
+
+        let dates = db.document_dates(&["ddd444", "aaa111"]).unwrap();
+        assert_eq!(
+            dates.get("ddd444"),
+            NaiveDate::from_ymd_opt(2024, 3, 10).as_ref()
+        );
+        assert_eq!(
+            dates.get("aaa111"),
+            NaiveDate::from_ymd_opt(2024, 3, 5).as_ref()
+        );
+    }
+}
@@ -0,0 +1,443 @@
+//! Document link graph queries
+
+use super::content::docid_from_hash;
+use super::Database;
+use crate::config::virtual_path::{is_virtual_path, parse_virtual_path};
+use crate::error::{AgentRootError, Result};
+use crate::graph::extract_reference_links;
+use rusqlite::{params, OptionalExtension};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+
+/// Deepest neighborhood traversal allowed
+pub const MAX_NEIGHBORHOOD_DEPTH: usize = 3;
+/// Node budget for a neighborhood; traversal stops once reached
+pub const MAX_NEIGHBORHOOD_NODES: usize = 200;
+
+/// A document on the other end of a link
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LinkedDocument {
+    pub docid: String,
+    pub collection: String,
+    pub path: String,
+    pub title: String,
+    pub link_type: String,
+}
+
+/// A markdown link or wikilink whose target is not in the index
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DanglingLink {
+    pub docid: String,
+    pub collection: String,
+    pub path: String,
+    pub target: String,
+    pub link_type: String,
+    /// Whether the target exists on disk (file collections only) but is not indexed
+    pub exists_on_disk: bool,
+}
+
+/// A document node in a graph neighborhood
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct GraphNode {
+    pub id: i64,
+    pub docid: String,
+    pub collection: String,
+    pub path: String,
+    pub title: String,
+    pub importance: f64,
+    /// Hops from the root document
+    pub depth: usize,
+}
+
+/// A typed, directed link between two nodes (by node id)
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct GraphEdge {
+    pub source: i64,
+    pub target: i64,
+    pub link_type: String,
+}
+
+/// Bounded subgraph around a document
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct GraphNeighborhood {
+    pub root: i64,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    /// True when the node budget cut the traversal short
+    pub truncated: bool,
+}
+
+/// Shortest hash prefix accepted as a docid, the length `docid_from_hash` produces
+const MIN_DOCID_PREFIX: usize = 6;
+
+impl Database {
+    /// Resolve a docid (#abc123), virtual path, or collection/path to a document row id
+    pub(crate) fn resolve_document_id(&self, query: &str) -> Result<Option<i64>> {
+        let query = query.trim();
+
+        let docid = query.trim_start_matches('#');
+        // An empty or short prefix would LIKE-match an arbitrary document
+        if docid.len() >= MIN_DOCID_PREFIX && docid.chars().all(|c| c.is_ascii_hexdigit()) {
+            let id = self
+                .conn
+                .query_row(
+                    "SELECT id FROM documents WHERE hash LIKE ?1 || '%' AND active = 1 LIMIT 1",
+                    params![docid],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if id.is_some() {
+                return Ok(id);
+            }
+        }
+
+        if is_virtual_path(query) {
+            if let Ok((collection, path)) = parse_virtual_path(query) {
+                return Ok(self.find_active_document(&collection, &path)?.map(|d| d.id));
+            }
+        }
+
+        if let Some((collection, path)) = query.split_once('/') {
+            return Ok(self.find_active_document(collection, path)?.map(|d| d.id));
+        }
+
+        Ok(None)
+    }
+
+    /// Get documents that link to the given document ("what references this?")
+    pub fn get_backlinks(&self, docid: &str) -> Result<Vec<LinkedDocument>> {
+        self.linked_documents(
+            docid,
+            "SELECT d.hash, d.collection, d.path, d.title, l.link_type
+             FROM document_links l
+             JOIN documents d ON d.id = l.source_id
+             WHERE l.target_id = ?1 AND d.active = 1
+             ORDER BY d.collection, d.path",
+        )
+    }
+
+    /// Get documents the given document links to ("what does this reference?")
+    pub fn get_outlinks(&self, docid: &str) -> Result<Vec<LinkedDocument>> {
+        self.linked_documents(
+            docid,
+            "SELECT d.hash, d.collection, d.path, d.title, l.link_type
+             FROM document_links l
+             JOIN documents d ON d.id = l.target_id
+             WHERE l.source_id = ?1 AND d.active = 1
+             ORDER BY d.collection, d.path",
+        )
+    }
+
+    /// Run a link query keyed by the resolved document id
+    fn linked_documents(&self, docid: &str, sql: &str) -> Result<Vec<LinkedDocument>> {
+        let id = self
+            .resolve_document_id(docid)?
+            .ok_or_else(|| AgentRootError::DocumentNotFound(docid.to_string()))?;
+
+        let mut stmt = self.conn.prepare(sql)?;
+        let results = stmt
+            .query_map(params![id], |row| {
+                Ok(LinkedDocument {
+                    docid: docid_from_hash(&row.get::<_, String>(0)?),
+                    collection: row.get(1)?,
+                    path: row.get(2)?,
+                    title: row.get(3)?,
+                    link_type: row.get(4)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(results)
+    }
+
+    /// Find markdown links and wikilinks pointing at documents not in the index
+    pub fn find_dangling_links(&self, collection: Option<&str>) -> Result<Vec<DanglingLink>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT d.hash, d.collection, d.path, c.doc, coll.path, coll.provider_type
+             FROM documents d
+             JOIN content c ON c.hash = d.hash
+             JOIN collections coll ON coll.name = d.collection
+             WHERE d.active = 1 AND (?1 IS NULL OR d.collection = ?1)
+             ORDER BY d.collection, d.path",
+        )?;
+        let docs: Vec<(String, String, String, String, String, String)> = stmt
+            .query_map(params![collection], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                ))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let mut dangling = Vec::new();
+        for (hash, coll, path, content, coll_path, provider_type) in docs {
+            for link in extract_reference_links(&content, &path, &coll_path) {
+                if self.resolve_link_target(&coll, &link)?.is_some() {
+                    continue;
+                }
+                let exists_on_disk = matches!(provider_type.as_str(), "file" | "obsidian")
+                    && Path::new(&coll_path).join(&link.target_path).exists();
+                dangling.push(DanglingLink {
+                    docid: docid_from_hash(&hash),
+                    collection: coll.clone(),
+                    path: path.clone(),
+                    target: link.target_path,
+                    link_type: link.link_type.as_str().to_string(),
+                    exists_on_disk,
+                });
+            }
+        }
+
+        Ok(dangling)
+    }
+
+    /// Get the subgraph within `depth` hops of a document, following links in both directions
+    ///
+    /// `edge_types` restricts traversal to those link types (empty means all).
+    /// Depth is capped at `MAX_NEIGHBORHOOD_DEPTH` and size at `MAX_NEIGHBORHOOD_NODES`.
+    pub fn graph_neighborhood(
+        &self,
+        docid: &str,
+        depth: usize,
+        edge_types: &[String],
+    ) -> Result<GraphNeighborhood> {
+        let root = self
+            .resolve_document_id(docid)?
+            .ok_or_else(|| AgentRootError::DocumentNotFound(docid.to_string()))?;
+        let depth = depth.min(MAX_NEIGHBORHOOD_DEPTH);
+        let allowed =
+            |link_type: &str| edge_types.is_empty() || edge_types.iter().any(|t| t == link_type);
+
+        let mut stmt = self.conn.prepare(
+            "SELECT l.source_id, l.target_id, l.link_type
+             FROM document_links l
+             JOIN documents s ON s.id = l.source_id AND s.active = 1
+             JOIN documents t ON t.id = l.target_id AND t.active = 1
+             WHERE l.source_id = ?1 OR l.target_id = ?1",
+        )?;
+
+        let mut depths: HashMap<i64, usize> = HashMap::from([(root, 0)]);
+        let mut queue = VecDeque::from([root]);
+        let mut truncated = false;
+
+        while let Some(node) = queue.pop_front() {
+            let node_depth = depths[&node];
+            if node_depth >= depth {
+                continue;
+            }
+
+            let neighbors = stmt
+                .query_map(params![node], |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, i64>(1)?,
+                        row.get::<_, String>(2)?,
+                    ))
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+
+            for (source, target, link_type) in neighbors {
+                if !allowed(&link_type) {
+                    continue;
+                }
+                let other = if source == node { target } else { source };
+                if depths.contains_key(&other) {
+                    continue;
+                }
+                if depths.len() >= MAX_NEIGHBORHOOD_NODES {
+                    truncated = true;
+                    break;
+                }
+                depths.insert(other, node_depth + 1);
+                queue.push_back(other);
+            }
+        }
+
+        let mut nodes = Vec::with_capacity(depths.len());
+        let mut node_stmt = self.conn.prepare(
+            "SELECT hash, collection, path, title, COALESCE(importance_score, 1.0)
+             FROM documents WHERE id = ?1",
+        )?;
+        for (id, node_depth) in &depths {
+            let node = node_stmt.query_row(params![id], |row| {
+                Ok(GraphNode {
+                    id: *id,
+                    docid: docid_from_hash(&row.get::<_, String>(0)?),
+                    collection: row.get(1)?,
+                    path: row.get(2)?,
+                    title: row.get(3)?,
+                    importance: row.get(4)?,
+                    depth: *node_depth,
+                })
+            })?;
+            nodes.push(node);
+        }
+        nodes.sort_by(|a, b| a.depth.cmp(&b.depth).then_with(|| a.path.cmp(&b.path)));
+
+        let included: HashSet<i64> = depths.keys().copied().collect();
+        let mut edge_stmt = self.conn.prepare(
+            "SELECT source_id, target_id, link_type FROM document_links
+             WHERE source_id = ?1
+             ORDER BY target_id, link_type",
+        )?;
+        let mut edges = Vec::new();
+        for node in &nodes {
+            for edge in edge_stmt.query_map(params![node.id], |row| {
+                Ok(GraphEdge {
+                    source: row.get(0)?,
+                    target: row.get(1)?,
+                    link_type: row.get(2)?,
+                })
+            })? {
+                let edge = edge?;
+                if included.contains(&edge.target) && allowed(&edge.link_type) {
+                    edges.push(edge);
+                }
+            }
+        }
+
+        Ok(GraphNeighborhood {
+            root,
+            nodes,
+            edges,
+            truncated,
+        })
+    }
+
+    /// Count edges in the document link graph
+    pub fn count_document_links(&self) -> Result<usize> {
+        let count: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM document_links", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn setup_linked_docs() -> Database {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+        db.insert_content("aaa111", "See [b](b.md) and [c](c.md)")
+            .unwrap();
+        db.insert_content("bbb222", "Back to [c](c.md)").unwrap();
+        db.insert_content("ccc333", "No links here").unwrap();
+        db.insert_document("notes", "a.md", "A", "aaa111", &now, &now, "file", None)
+            .unwrap();
+        db.insert_document("notes", "b.md", "B", "bbb222", &now, &now, "file", None)
+            .unwrap();
+        db.insert_document("notes", "c.md", "C", "ccc333", &now, &now, "file", None)
+            .unwrap();
+
+        db.build_link_graph().unwrap();
+        db
+    }
+
+    #[test]
+    fn test_resolve_document_id_rejects_short_docids() {
+        let db = setup_linked_docs();
+
+        for query in ["", "   ", "#", "#a", "aaa"] {
+            assert_eq!(db.resolve_document_id(query).unwrap(), None, "{:?}", query);
+        }
+        assert!(db.resolve_document_id(" #aaa111 ").unwrap().is_some());
+        assert!(db.resolve_document_id("bbb222").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_get_backlinks() {
+        let db = setup_linked_docs();
+
+        let backlinks = db.get_backlinks("notes/c.md").unwrap();
+        let paths: Vec<&str> = backlinks.iter().map(|l| l.path.as_str()).collect();
+        assert_eq!(paths, vec!["a.md", "b.md"]);
+        assert_eq!(backlinks[0].link_type, "markdown_link");
+
+        let by_docid = db.get_backlinks("#bbb222").unwrap();
+        assert_eq!(by_docid.len(), 1);
+        assert_eq!(by_docid[0].path, "a.md");
+
+        assert!(db.get_backlinks("notes/a.md").unwrap().is_empty());
+        assert_eq!(db.count_document_links().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_get_outlinks() {
+        let db = setup_linked_docs();
+
+        let outlinks = db.get_outlinks("notes/a.md").unwrap();
+        let paths: Vec<&str> = outlinks.iter().map(|l| l.path.as_str()).collect();
+        assert_eq!(paths, vec!["b.md", "c.md"]);
+        assert_eq!(outlinks[0].link_type, "markdown_link");
+
+        assert!(db.get_outlinks("#ccc333").unwrap().is_empty());
+        assert!(matches!(
+            db.get_outlinks("notes/missing.md"),
+            Err(AgentRootError::DocumentNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_find_dangling_links() {
+        let db = setup_linked_docs();
+        let now = Utc::now().to_rfc3339();
+        db.insert_content("ddd444", "Broken [x](gone.md), [[c]] and [[Nowhere]]")
+            .unwrap();
+        db.insert_document("notes", "d.md", "D", "ddd444", &now, &now, "file", None)
+            .unwrap();
+
+        let dangling = db.find_dangling_links(None).unwrap();
+        let targets: Vec<(&str, &str)> = dangling
+            .iter()
+            .map(|l| (l.target.as_str(), l.link_type.as_str()))
+            .collect();
+        assert_eq!(
+            targets,
+            vec![("gone.md", "markdown_link"), ("Nowhere.md", "wikilink")]
+        );
+        assert_eq!(dangling[0].path, "d.md");
+        assert!(!dangling[0].exists_on_disk);
+
+        assert!(db.find_dangling_links(Some("other")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_graph_neighborhood() {
+        let db = setup_linked_docs();
+
+        // a -> b, a -> c, b -> c
+        let hood = db.graph_neighborhood("notes/b.md", 1, &[]).unwrap();
+        let paths: Vec<&str> = hood.nodes.iter().map(|n| n.path.as_str()).collect();
+        assert_eq!(paths, vec!["b.md", "a.md", "c.md"]);
+        assert_eq!(hood.nodes[0].depth, 0);
+        assert_eq!(hood.edges.len(), 3);
+        assert!(!hood.truncated);
+
+        let root_only = db.graph_neighborhood("notes/b.md", 0, &[]).unwrap();
+        assert_eq!(root_only.nodes.len(), 1);
+        assert!(root_only.edges.is_empty());
+
+        let filtered = db
+            .graph_neighborhood("notes/b.md", 2, &["imports".to_string()])
+            .unwrap();
+        assert_eq!(filtered.nodes.len(), 1);
+    }
+
+    #[test]
+    fn test_get_backlinks_unknown_document() {
+        let db = setup_linked_docs();
+        let err = db.get_backlinks("notes/missing.md").unwrap_err();
+        assert!(matches!(err, AgentRootError::DocumentNotFound(_)));
+    }
+}
@@ -0,0 +1,165 @@
+//! Index maintenance
+//!
+//! Every reindex appends FTS5 segments, and fragmented segments slow down
+//! queries on large indexes. Reindexes add to a pending-change counter; once it
+//! reaches `AGENTROOT_OPTIMIZE_AFTER` (default 1000 documents, `off` to
+//! disable) the FTS tables are optimized and query planner statistics
+//! refreshed automatically.
+
+use super::Database;
+use crate::error::Result;
+use chrono::Utc;
+use rusqlite::params;
+use std::time::Instant;
+
+/// Changed documents that trigger automatic maintenance when the variable is unset
+pub const DEFAULT_OPTIMIZE_AFTER: usize = 1000;
+
+/// What a maintenance run did
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MaintenanceReport {
+    /// FTS5 tables that were optimized
+    pub fts_tables: Vec<String>,
+    /// Document changes accumulated since the previous run
+    pub pending_changes: usize,
+    pub duration_ms: f64,
+}
+
+/// Maintenance bookkeeping
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MaintenanceStatus {
+    pub pending_changes: usize,
+    pub last_optimized_at: Option<String>,
+    pub last_duration_ms: Option<f64>,
+}
+
+/// Pending-change threshold for automatic maintenance, or None when disabled
+pub fn optimize_after() -> Option<usize> {
+    match std::env::var("AGENTROOT_OPTIMIZE_AFTER") {
+        Ok(value) if value.trim().eq_ignore_ascii_case("off") => None,
+        Ok(value) => Some(value.trim().parse().unwrap_or(DEFAULT_OPTIMIZE_AFTER)),
+        Err(_) => Some(DEFAULT_OPTIMIZE_AFTER),
+    }
+}
+
+impl Database {
+    /// Merge all FTS5 segments and refresh planner statistics
+    pub fn optimize_indexes(&self) -> Result<MaintenanceReport> {
+        let start = Instant::now();
+        let pending_changes = self.maintenance_status()?.pending_changes;
+
+        let fts_tables: Vec<String> = self
+            .conn
+            .prepare(
+                "SELECT name FROM sqlite_master
+                 WHERE type = 'table' AND sql LIKE 'CREATE VIRTUAL TABLE%USING fts5%'
+                 ORDER BY name",
+            )?
+            .query_map([], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        for table in &fts_tables {
+            self.conn.execute(
+                &format!("INSERT INTO \"{0}\"(\"{0}\") VALUES('optimize')", table),
+                [],
+            )?;
+        }
+        self.conn.execute_batch("ANALYZE; PRAGMA optimize;")?;
+
+        let duration_ms = start.elapsed().as_secs_f64() * 1000.0;
+        self.conn.execute(
+            "UPDATE index_maintenance
+             SET pending_changes = 0, last_optimized_at = ?1, last_duration_ms = ?2
+             WHERE id = 1",
+            params![Utc::now().to_rfc3339(), duration_ms],
+        )?;
+
+        Ok(MaintenanceReport {
+            fts_tables,
+            pending_changes,
+            duration_ms,
+        })
+    }
+
+    /// Record changed documents and run maintenance once enough have accumulated
+    ///
+    /// Returns the report when maintenance ran.
+    pub fn note_index_changes(&self, changes: usize) -> Result<Option<MaintenanceReport>> {
+        if changes == 0 {
+            return Ok(None);
+        }
+        self.conn.execute(
+            "UPDATE index_maintenance SET pending_changes = pending_changes + ?1 WHERE id = 1",
+            params![changes as i64],
+        )?;
+
+        let Some(threshold) = optimize_after() else {
+            return Ok(None);
+        };
+        if self.maintenance_status()?.pending_changes < threshold {
+            return Ok(None);
+        }
+
+        let report = self.optimize_indexes()?;
+        tracing::info!(
+            "Optimized {} FTS tables after {} changes in {:.0} ms",
+            report.fts_tables.len(),
+            report.pending_changes,
+            report.duration_ms
+        );
+        Ok(Some(report))
+    }
+
+    /// Pending changes and the last maintenance run
+    pub fn maintenance_status(&self) -> Result<MaintenanceStatus> {
+        Ok(self.conn.query_row(
+            "SELECT pending_changes, last_optimized_at, last_duration_ms
+             FROM index_maintenance WHERE id = 1",
+            [],
+            |row| {
+                Ok(MaintenanceStatus {
+                    pending_changes: row.get::<_, i64>(0)? as usize,
+                    last_optimized_at: row.get(1)?,
+                    last_duration_ms: row.get(2)?,
+                })
+            },
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fts_partitions::{exact_fts_table, fts_table};
+
+    #[test]
+    fn test_optimize_indexes() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.add_collection("notes", "/tmp", "**/*.md", "file", None)
+            .unwrap();
+        db.note_index_changes(3).unwrap();
+        assert_eq!(db.maintenance_status().unwrap().pending_changes, 3);
+
+        let report = db.optimize_indexes().unwrap();
+        assert!(report.fts_tables.contains(&fts_table("notes")));
+        assert!(report.fts_tables.contains(&exact_fts_table("notes")));
+        assert!(report.fts_tables.contains(&"chunks_fts".to_string()));
+        assert_eq!(report.pending_changes, 3);
+
+        let status = db.maintenance_status().unwrap();
+        assert_eq!(status.pending_changes, 0);
+        assert!(status.last_optimized_at.is_some());
+    }
+
+    #[test]
+    fn test_large_reindex_triggers_maintenance() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+
+        assert!(db.note_index_changes(10).unwrap().is_none());
+        let report = db.note_index_changes(DEFAULT_OPTIMIZE_AFTER).unwrap();
+        assert_eq!(report.unwrap().pending_changes, DEFAULT_OPTIMIZE_AFTER + 10);
+        assert_eq!(db.maintenance_status().unwrap().pending_changes, 0);
+    }
+}
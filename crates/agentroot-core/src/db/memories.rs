@@ -76,7 +76,7 @@ impl Database {
         category: Option<&str>,
         limit: usize,
     ) -> Result<Vec<MemoryInfo>> {
-        let sanitized = crate::search::sanitize_fts5_query(query);
+        let sanitized = crate::search::sanitize_fts5_query(query)?;
         if sanitized.is_empty() {
             return Ok(vec![]);
         }
@@ -5,39 +5,96 @@
 //! - sqlite-vec vector storage
 //! - Content-addressable storage
 
+pub mod archive;
 mod chunks;
 mod collections;
+mod compare;
 mod content;
 mod context;
 pub mod directories;
+mod discover;
 mod documents;
+pub mod encryption;
+mod feedback;
+mod fts_partitions;
 pub mod glossary;
+mod grep;
+mod health;
+pub mod history;
+mod jobs;
+mod journal;
+mod links;
+mod maintenance;
 pub mod memories;
 pub mod metadata;
+mod multi_get;
 mod pagerank;
+mod pins;
+mod pool;
+mod related;
 mod schema;
+mod session_stats;
 pub mod sessions;
+mod slow_queries;
 mod stats;
+mod symbols;
+mod topics;
 mod user_metadata;
+mod vector_scan;
 pub mod vectors;
+mod vocabulary;
+mod write_queue;
 
-pub use chunks::ChunkInfo;
-pub use collections::CollectionInfo;
+pub use archive::{ArchiveManifest, ArchiveModel, ImportOptions, ImportStats};
+pub use chunks::{ChunkInfo, ChunkTag, SymbolDefinition};
+pub use collections::{CollectionInfo, ReindexStats, INGESTED_SOURCE_TYPE};
+pub use compare::{ChunkOverlap, ComparedDocument, DocumentComparison};
 pub use content::{docid_from_hash, hash_content};
 pub use context::ContextInfo;
 pub use directories::DirectoryInfo;
-pub use documents::{Document, DocumentInsert};
-pub use glossary::{ConceptChunkInfo, ConceptInfo};
+pub use discover::{DiscoverOptions, DiscoveredDocument};
+pub use documents::{DeletedDocument, Document, DocumentInsert};
+pub use feedback::FeedbackEntry;
+pub use glossary::{ConceptChunkInfo, ConceptDocument, ConceptInfo, RelatedConcept};
+pub use grep::{RegexMatch, RegexSearchOptions};
+pub use health::{DimensionMismatch, SearchIndexDrift};
+pub use history::{DocumentHistory, DocumentVersion};
+pub use jobs::{Job, JobKind, JobStatus, DEFAULT_MAX_ATTEMPTS};
+pub use journal::JournalEntry;
+pub use links::{
+    DanglingLink, GraphEdge, GraphNeighborhood, GraphNode, LinkedDocument, MAX_NEIGHBORHOOD_DEPTH,
+    MAX_NEIGHBORHOOD_NODES,
+};
+pub use maintenance::{
+    optimize_after, MaintenanceReport, MaintenanceStatus, DEFAULT_OPTIMIZE_AFTER,
+};
 pub use memories::{MemoryInfo, MemoryStats};
 pub use metadata::{MetadataBuilder, MetadataFilter, MetadataValue, UserMetadata};
+pub use multi_get::{BudgetedDocument, MultiGetOptions, MultiGetResult, DEFAULT_MULTI_GET_TOKENS};
+pub use pins::PinnedDocument;
+pub use pool::DbPool;
+pub use related::RelatedDocument;
 pub use schema::Database;
-pub use sessions::{SessionInfo, SessionQuery};
+pub use session_stats::{RetrievedDocument, SessionActivity, SessionStats, ZeroResultQuery};
+pub use sessions::{SessionInfo, SessionQuery, DEFAULT_SESSION_TTL};
+pub use slow_queries::{slow_query_threshold_ms, SlowQuery, StageTiming, DEFAULT_SLOW_QUERY_MS};
 use std::path::PathBuf;
-pub use vectors::CacheLookupResult;
+pub use symbols::SymbolUsage;
+pub use topics::{TopicInfo, TopicMember};
+pub use vector_scan::{vector_memory_limit_bytes, VectorFilter, DEFAULT_VECTOR_MEMORY_MB};
+pub use vectors::{CacheLookupResult, ChunkEmbedding, EmbeddingModel, Quantization};
+pub use vocabulary::{Suggestion, SuggestionKind};
+pub use write_queue::{write_timeout, DEFAULT_WRITE_TIMEOUT_SECS};
 
 impl Database {
     /// Get the default database path
+    ///
+    /// A project's `.agentroot/index.sqlite` when run inside one, otherwise
+    /// the shared index in the user cache directory.
     pub fn default_path() -> PathBuf {
+        if let Some(dir) = crate::project::project_dir() {
+            return dir.join(crate::project::PROJECT_DB_NAME);
+        }
         dirs::cache_dir()
             .unwrap_or_else(|| PathBuf::from("."))
             .join(crate::CACHE_DIR_NAME)
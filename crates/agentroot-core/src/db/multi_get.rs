@@ -0,0 +1,364 @@
+//! Budgeted multi-document retrieval
+//!
+//! Fetches the documents a glob or comma-separated list names and fits them
+//! into one total byte/token budget. The budget is shared out in proportion
+//! to each document's importance score: documents smaller than their share
+//! are returned whole and the surplus goes to the rest. Documents that still
+//! do not fit are cut at a heading or line boundary and end with a marker
+//! naming what was left out, rather than being skipped.
+
+use super::content::docid_from_hash;
+use super::Database;
+use crate::error::Result;
+use crate::index::ast_chunker::oversized::estimate_tokens;
+use rusqlite::params;
+
+/// Token budget used when the caller gives neither tokens nor bytes
+pub const DEFAULT_MULTI_GET_TOKENS: usize = 8000;
+
+/// Documents whose share would fall below this are omitted instead of
+/// being cut to a sliver
+const MIN_DOCUMENT_BYTES: usize = 512;
+
+/// Room kept in a truncated document's share for its marker
+const MARKER_RESERVE: usize = 256;
+
+/// Omitted section headings named in a truncation marker
+const MARKER_SECTIONS: usize = 3;
+
+/// Budget and per-file limits for [`Database::multi_get`]
+#[derive(Debug, Clone, Default)]
+pub struct MultiGetOptions {
+    /// Total estimated tokens across all documents
+    pub max_tokens: Option<usize>,
+    /// Total bytes across all documents
+    pub max_bytes: Option<usize>,
+    /// Lines kept per document before the budget is applied
+    pub max_lines: Option<usize>,
+}
+
+impl MultiGetOptions {
+    /// Total byte budget: the tighter of the two limits, tokens counting
+    /// four bytes each
+    pub fn budget_bytes(&self) -> usize {
+        match (self.max_tokens, self.max_bytes) {
+            (None, None) => DEFAULT_MULTI_GET_TOKENS * 4,
+            (tokens, bytes) => tokens
+                .map(|t| t.saturating_mul(4))
+                .unwrap_or(usize::MAX)
+                .min(bytes.unwrap_or(usize::MAX)),
+        }
+    }
+}
+
+/// One document of a [`MultiGetResult`]
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BudgetedDocument {
+    /// Display path, e.g. "collection/docs/guide.md"
+    pub path: String,
+    pub docid: String,
+    /// Importance score the budget was shared by
+    pub importance: f64,
+    /// Bytes of the stored document
+    pub original_bytes: usize,
+    /// Whether the content was cut to fit the budget
+    pub truncated: bool,
+    /// Headings of the sections cut off, in file order
+    pub omitted_sections: Vec<String>,
+    pub content: String,
+}
+
+/// Outcome of [`Database::multi_get`]
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MultiGetResult {
+    pub budget_bytes: usize,
+    /// Bytes of all returned content
+    pub bytes: usize,
+    /// Estimated tokens of all returned content
+    pub tokens: usize,
+    /// Documents in the order the pattern produced them
+    pub documents: Vec<BudgetedDocument>,
+    /// Paths of matching documents left out for lack of budget
+    pub omitted: Vec<String>,
+}
+
+impl Database {
+    /// Get the documents `pattern` names within the budget of `options`
+    ///
+    /// `pattern` is a glob or comma-separated list of paths/docids, as for
+    /// [`Database::get_documents_by_pattern`]. When the least important
+    /// documents cannot get at least a few hundred bytes each they are
+    /// listed in `omitted` instead.
+    pub fn multi_get(&self, pattern: &str, options: &MultiGetOptions) -> Result<MultiGetResult> {
+        let budget = options.budget_bytes();
+        let mut docs = Vec::new();
+        for doc in self.get_documents_by_pattern(pattern)? {
+            let importance = self.importance_by_hash(&doc.hash)?;
+            let original_bytes = doc.content.len();
+            let content = match options.max_lines {
+                Some(max_lines) => first_lines(&doc.content, max_lines),
+                None => doc.content,
+            };
+            docs.push(BudgetedDocument {
+                path: doc.path,
+                docid: docid_from_hash(&doc.hash),
+                importance,
+                original_bytes,
+                truncated: false,
+                omitted_sections: Vec::new(),
+                content,
+            });
+        }
+
+        let sizes: Vec<usize> = docs.iter().map(|d| d.content.len()).collect();
+        let weights: Vec<f64> = docs.iter().map(|d| d.importance).collect();
+        let shares = allocate(&sizes, &weights, budget);
+
+        let mut documents = Vec::new();
+        let mut omitted = Vec::new();
+        for (mut doc, share) in docs.into_iter().zip(shares) {
+            match share {
+                None => omitted.push(doc.path),
+                Some(share) if share >= doc.content.len() => documents.push(doc),
+                Some(share) => {
+                    let (content, sections) = truncate_at_heading(&doc.content, share);
+                    doc.content = content;
+                    doc.omitted_sections = sections;
+                    doc.truncated = true;
+                    documents.push(doc);
+                }
+            }
+        }
+
+        let bytes = documents.iter().map(|d| d.content.len()).sum();
+        let chars = documents.iter().map(|d| d.content.chars().count()).sum();
+        Ok(MultiGetResult {
+            budget_bytes: budget,
+            bytes,
+            tokens: estimate_tokens(chars),
+            documents,
+            omitted,
+        })
+    }
+
+    /// Importance score of the active document with this content, 1.0 when
+    /// PageRank has not been computed
+    pub(crate) fn importance_by_hash(&self, hash: &str) -> Result<f64> {
+        let score: Option<f64> = self.conn.query_row(
+            "SELECT MAX(importance_score) FROM documents WHERE hash = ?1 AND active = 1",
+            params![hash],
+            |row| row.get(0),
+        )?;
+        Ok(score.unwrap_or(1.0))
+    }
+}
+
+/// Share `budget` bytes among documents of `sizes` in proportion to
+/// `weights`
+///
+/// Documents smaller than their share get their full size and the rest is
+/// shared again among the others. While some share would be under
+/// [`MIN_DOCUMENT_BYTES`] the least important such document is dropped
+/// (`None`) and the budget shared again.
+fn allocate(sizes: &[usize], weights: &[f64], budget: usize) -> Vec<Option<usize>> {
+    let weights: Vec<f64> = weights.iter().map(|w| w.max(f64::EPSILON)).collect();
+    let mut dropped = vec![false; sizes.len()];
+    loop {
+        let shares = water_fill(sizes, &weights, &dropped, budget);
+        let starved = (0..sizes.len())
+            .filter(|&i| !dropped[i] && shares[i] < sizes[i] && shares[i] < MIN_DOCUMENT_BYTES)
+            .min_by(|&a, &b| weights[a].total_cmp(&weights[b]).then(b.cmp(&a)));
+        match starved {
+            Some(i) => dropped[i] = true,
+            None => {
+                return shares
+                    .into_iter()
+                    .zip(dropped)
+                    .map(|(share, dropped)| (!dropped).then_some(share))
+                    .collect()
+            }
+        }
+    }
+}
+
+fn water_fill(sizes: &[usize], weights: &[f64], dropped: &[bool], budget: usize) -> Vec<usize> {
+    let mut shares = vec![0; sizes.len()];
+    let mut open: Vec<usize> = (0..sizes.len()).filter(|&i| !dropped[i]).collect();
+    let mut remaining = budget;
+    while !open.is_empty() {
+        let total: f64 = open.iter().map(|&i| weights[i]).sum();
+        let fair = |i: usize| (remaining as f64 * weights[i] / total) as usize;
+        let (fits, rest): (Vec<usize>, Vec<usize>) =
+            open.iter().partition(|&&i| sizes[i] <= fair(i));
+        if fits.is_empty() {
+            for &i in &rest {
+                shares[i] = fair(i);
+            }
+            break;
+        }
+        for i in fits {
+            shares[i] = sizes[i];
+            remaining -= sizes[i];
+        }
+        open = rest;
+    }
+    shares
+}
+
+/// Cut `content` to at most `max_bytes`, marker included
+///
+/// The cut falls just before a Markdown heading when one lies in the
+/// second half of the kept text, otherwise at the last line boundary.
+/// Returns the kept text with its marker and the headings cut off.
+fn truncate_at_heading(content: &str, max_bytes: usize) -> (String, Vec<String>) {
+    let limit = max_bytes.saturating_sub(MARKER_RESERVE);
+
+    let mut last_line_end = 0;
+    let mut last_heading_start = 0;
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        if offset > 0 && heading_text(line).is_some() {
+            last_heading_start = offset;
+        }
+        if offset + line.len() > limit {
+            break;
+        }
+        offset += line.len();
+        last_line_end = offset;
+    }
+    let cut = if last_heading_start * 2 >= limit && last_heading_start > 0 {
+        last_heading_start
+    } else if last_line_end > 0 {
+        last_line_end
+    } else {
+        let mut boundary = limit.min(content.len());
+        while !content.is_char_boundary(boundary) {
+            boundary -= 1;
+        }
+        boundary
+    };
+
+    let (kept, rest) = content.split_at(cut);
+    let sections: Vec<String> = rest.lines().filter_map(heading_text).collect();
+    let mut marker = format!(
+        "[... truncated {} of {} lines",
+        rest.lines().count(),
+        content.lines().count()
+    );
+    if !sections.is_empty() {
+        let named: Vec<String> = sections
+            .iter()
+            .take(MARKER_SECTIONS)
+            .map(|s| s.chars().take(40).collect())
+            .collect();
+        marker.push_str(&format!("; omitted sections: {}", named.join(", ")));
+        if sections.len() > MARKER_SECTIONS {
+            marker.push_str(&format!(", +{} more", sections.len() - MARKER_SECTIONS));
+        }
+    }
+    marker.push(']');
+
+    let mut text = kept.to_string();
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+    text.push_str(&marker);
+    (text, sections)
+}
+
+/// Text of an ATX Markdown heading line
+fn heading_text(line: &str) -> Option<String> {
+    let hashes = line.chars().take_while(|&c| c == '#').count();
+    let rest = &line[hashes..];
+    if (1..=6).contains(&hashes) && rest.starts_with([' ', '\t']) {
+        Some(rest.trim().to_string())
+    } else {
+        None
+    }
+}
+
+fn first_lines(content: &str, max_lines: usize) -> String {
+    content
+        .split_inclusive('\n')
+        .take(max_lines)
+        .collect::<String>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_allocate_shares_by_importance_and_drops_slivers() {
+        // Small documents are kept whole, the surplus goes to the large ones
+        let shares = allocate(&[100, 5000, 5000], &[1.0, 1.0, 3.0], 4100);
+        assert_eq!(shares[0], Some(100));
+        assert_eq!(shares[1], Some(1000));
+        assert_eq!(shares[2], Some(3000));
+
+        // A share below the minimum drops the least important document
+        let shares = allocate(&[5000, 5000, 5000], &[1.0, 0.1, 1.0], 1200);
+        assert_eq!(shares[1], None);
+        assert_eq!(shares[0], Some(600));
+        assert_eq!(shares[2], Some(600));
+    }
+
+    #[test]
+    fn test_truncate_at_heading_marks_omitted_sections() {
+        let section = "text line\n".repeat(40);
+        let content = format!(
+            "# Guide\n{s}## Install\n{s}## Usage\n{s}## Faq\n{s}",
+            s = section
+        );
+        let (text, sections) = truncate_at_heading(&content, 1300);
+        assert!(text.len() <= 1300);
+        assert!(text.contains("## Install"));
+        assert!(!text.contains("## Usage"));
+        assert_eq!(sections, vec!["Usage", "Faq"]);
+        assert!(text.ends_with("omitted sections: Usage, Faq]"));
+
+        // A single long line is cut at a char boundary
+        let (text, sections) = truncate_at_heading(&"é".repeat(1000), 600);
+        assert!(text.len() <= 600);
+        assert!(sections.is_empty());
+        assert!(text.ends_with("[... truncated 1 of 1 lines]"));
+    }
+
+    #[test]
+    fn test_multi_get_fits_budget_without_skipping() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+        let small = "# Small\nshort note\n".to_string();
+        let large = format!("# Large\n{}## Tail\nend\n", "body line\n".repeat(2000));
+        for (hash, path, content) in [("h1", "a.md", &small), ("h2", "b.md", &large)] {
+            db.insert_content(hash, content).unwrap();
+            db.insert_document("notes", path, path, hash, &now, &now, "file", None)
+                .unwrap();
+        }
+
+        let options = MultiGetOptions {
+            max_bytes: Some(4000),
+            ..Default::default()
+        };
+        let result = db.multi_get("notes/*.md", &options).unwrap();
+        assert!(result.omitted.is_empty());
+        assert_eq!(result.documents.len(), 2);
+        assert!(result.bytes <= 4000);
+        let a = &result.documents[0];
+        assert_eq!((a.path.as_str(), a.truncated), ("notes/a.md", false));
+        assert_eq!(a.content, small);
+        let b = &result.documents[1];
+        assert!(b.truncated);
+        assert_eq!(b.original_bytes, large.len());
+        assert_eq!(b.omitted_sections, vec!["Tail"]);
+
+        let everything = db
+            .multi_get("notes/*.md", &MultiGetOptions::default())
+            .unwrap();
+        assert!(everything.documents.iter().all(|d| !d.truncated));
+    }
+}
@@ -1,10 +1,13 @@
 //! PageRank-related database operations
 
-use crate::db::Database;
+use crate::db::{Database, MetadataValue, UserMetadata};
 use crate::error::Result;
-use crate::graph::{compute_pagerank, extract_links};
+use crate::graph::{
+    compute_pagerank, extract_links, personalized_pagerank, DocumentLink, LinkType,
+};
 use chrono::Utc;
 use rusqlite::{params, OptionalExtension};
+use std::collections::HashMap;
 
 impl Database {
     /// Build document link graph by extracting links from all documents
@@ -38,9 +41,7 @@ impl Database {
             let links = extract_links(&content, &path, &coll_path);
 
             for link in links {
-                if let Some(target_id) =
-                    self.find_document_by_path(&collection, &link.target_path)?
-                {
+                if let Some(target_id) = self.resolve_link_target(&collection, &link)? {
                     self.conn.execute(
                         "INSERT OR IGNORE INTO document_links (source_id, target_id, link_type, created_at)
                          VALUES (?1, ?2, ?3, ?4)",
@@ -94,6 +95,99 @@ impl Database {
         Ok((doc_count, top_docs))
     }
 
+    /// Personalized PageRank seeded from (document hash, weight) pairs, keyed by document hash
+    pub fn personalized_pagerank_by_hash(
+        &self,
+        seeds: &[(String, f64)],
+    ) -> Result<HashMap<String, f64>> {
+        let mut seed_ids: HashMap<i64, f64> = HashMap::new();
+        let mut ids = self
+            .conn
+            .prepare("SELECT id FROM documents WHERE hash = ?1 AND active = 1")?;
+        for (hash, weight) in seeds {
+            for id in ids.query_map([hash], |row| row.get(0))? {
+                seed_ids.insert(id?, *weight);
+            }
+        }
+
+        let mut hash_of = self
+            .conn
+            .prepare("SELECT hash FROM documents WHERE id = ?1")?;
+        let mut by_hash: HashMap<String, f64> = HashMap::new();
+        for (id, score) in personalized_pagerank(&self.conn, &seed_ids)? {
+            if let Some(hash) = hash_of
+                .query_row([id], |row| row.get::<_, String>(0))
+                .optional()?
+            {
+                *by_hash.entry(hash).or_insert(0.0) += score;
+            }
+        }
+
+        Ok(by_hash)
+    }
+
+    /// Resolve an extracted link to a document id within the collection
+    ///
+    /// Wikilinks name a note, so they also match a document whose path ends
+    /// with the note name (Obsidian-style shortest-path resolution), or one
+    /// listing the name among its frontmatter `aliases`.
+    pub(super) fn resolve_link_target(
+        &self,
+        collection: &str,
+        link: &DocumentLink,
+    ) -> Result<Option<i64>> {
+        if let Some(id) = self.find_document_by_path(collection, &link.target_path)? {
+            return Ok(Some(id));
+        }
+        if !matches!(link.link_type, LinkType::WikiLink) {
+            return Ok(None);
+        }
+
+        let id = self
+            .conn
+            .query_row(
+                "SELECT id FROM documents
+                 WHERE collection = ?1 AND active = 1
+                   AND substr(path, -length(?2) - 1) = '/' || ?2
+                 ORDER BY length(path), path
+                 LIMIT 1",
+                params![collection, link.target_path],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if id.is_some() {
+            return Ok(id);
+        }
+
+        self.find_document_by_alias(collection, &link.target_path)
+    }
+
+    /// Document whose `aliases` metadata names the wikilink target
+    fn find_document_by_alias(&self, collection: &str, target_path: &str) -> Result<Option<i64>> {
+        let name = target_path.strip_suffix(".md").unwrap_or(target_path);
+        let mut stmt = self.conn.prepare(
+            "SELECT id, user_metadata FROM documents
+             WHERE collection = ?1 AND active = 1 AND user_metadata LIKE '%\"aliases\"%'
+             ORDER BY length(path), path",
+        )?;
+        let rows = stmt
+            .query_map(params![collection], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(rows.into_iter().find_map(|(id, json)| {
+            let metadata = UserMetadata::from_json(&json).ok()?;
+            match metadata.get("aliases")? {
+                MetadataValue::Tags(aliases) => aliases
+                    .iter()
+                    .any(|alias| alias.eq_ignore_ascii_case(name))
+                    .then_some(id),
+                _ => None,
+            }
+        }))
+    }
+
     fn find_document_by_path(&self, collection: &str, path: &str) -> Result<Option<i64>> {
         let id = self
             .conn
@@ -203,4 +297,145 @@ mod tests {
 
         assert!(score > 0.0, "Score should be positive");
     }
+
+    #[test]
+    fn test_personalized_pagerank_by_hash() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+
+        db.add_collection("test", "/test", "**/*.md", "file", None)
+            .unwrap();
+        db.insert_content("hash1", "See [doc2](doc2.md)").unwrap();
+        db.insert_content("hash2", "Content of doc2").unwrap();
+        db.insert_content("hash3", "Content of doc3").unwrap();
+        for (path, hash) in [
+            ("doc1.md", "hash1"),
+            ("doc2.md", "hash2"),
+            ("doc3.md", "hash3"),
+        ] {
+            db.insert_document("test", path, path, hash, &now, &now, "file", None)
+                .unwrap();
+        }
+        db.build_link_graph().unwrap();
+
+        let ranks = db
+            .personalized_pagerank_by_hash(&[("hash1".to_string(), 1.0)])
+            .unwrap();
+        assert!(ranks["hash2"] > 0.0);
+        assert!(!ranks.contains_key("hash3"));
+    }
+
+    #[test]
+    fn test_build_link_graph_code_imports() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+
+        db.add_collection("code", "/code", "**/*.rs", "file", None)
+            .unwrap();
+        db.insert_content("hash1", "mod parser;\nuse crate::util::helper;")
+            .unwrap();
+        db.insert_content("hash2", "pub fn parse() {}").unwrap();
+        db.insert_content("hash3", "pub fn helper() {}").unwrap();
+        for (path, hash) in [
+            ("src/lib.rs", "hash1"),
+            ("src/parser.rs", "hash2"),
+            ("src/util.rs", "hash3"),
+        ] {
+            db.insert_document("code", path, path, hash, &now, &now, "file", None)
+                .unwrap();
+        }
+
+        assert_eq!(db.build_link_graph().unwrap(), 2);
+        let imports: i64 = db
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM document_links WHERE link_type = 'imports'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(imports, 2);
+    }
+
+    #[test]
+    fn test_build_link_graph_wikilinks() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+
+        db.add_collection("vault", "/vault", "**/*.md", "file", None)
+            .unwrap();
+        db.insert_content("hash1", "See [[Plan]] and [[Missing Note]]")
+            .unwrap();
+        db.insert_content("hash2", "The plan").unwrap();
+        db.insert_document(
+            "vault", "index.md", "Index", "hash1", &now, &now, "file", None,
+        )
+        .unwrap();
+        db.insert_document(
+            "vault",
+            "projects/Plan.md",
+            "Plan",
+            "hash2",
+            &now,
+            &now,
+            "file",
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(db.build_link_graph().unwrap(), 1);
+        let link_type: String = db
+            .conn
+            .query_row("SELECT link_type FROM document_links", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(link_type, "wikilink");
+    }
+
+    #[test]
+    fn test_build_link_graph_resolves_aliases() {
+        use crate::db::MetadataBuilder;
+
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+
+        db.add_collection("vault", "/vault", "**/*.md", "obsidian", None)
+            .unwrap();
+        db.insert_content("aaa111", "Met about the [[roadmap|plan]]")
+            .unwrap();
+        db.insert_content("bbb222", "The plan").unwrap();
+        db.insert_document(
+            "vault",
+            "Daily/2026-10-14.md",
+            "2026-10-14",
+            "aaa111",
+            &now,
+            &now,
+            "obsidian",
+            None,
+        )
+        .unwrap();
+        db.insert_document(
+            "vault", "Plan.md", "Plan", "bbb222", &now, &now, "obsidian", None,
+        )
+        .unwrap();
+        db.add_metadata(
+            "bbb222",
+            &MetadataBuilder::new().tags("aliases", ["Roadmap"]).build(),
+        )
+        .unwrap();
+
+        assert_eq!(db.build_link_graph().unwrap(), 1);
+        let target: i64 = db
+            .conn
+            .query_row("SELECT target_id FROM document_links", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(
+            Some(target),
+            db.find_document_by_path("vault", "Plan.md").unwrap()
+        );
+    }
 }
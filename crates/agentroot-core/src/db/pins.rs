@@ -0,0 +1,267 @@
+//! Document pins
+//!
+//! A pin lives in the document's user metadata, so it can be set with
+//! `agentroot pin`, `metadata add` or markdown frontmatter:
+//! - `pinned`: boolean, the pin itself
+//! - `pin_queries`: tags; the pin only applies to queries containing one of them
+//! - `pin_boost`: number; multiply the score instead of placing the document first
+
+use super::content::docid_from_hash;
+use super::{Database, MetadataValue, UserMetadata};
+use crate::error::{AgentRootError, Result};
+use rusqlite::params;
+
+const PINNED_FIELD: &str = "pinned";
+const PIN_QUERIES_FIELD: &str = "pin_queries";
+const PIN_BOOST_FIELD: &str = "pin_boost";
+
+/// A pinned document
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PinnedDocument {
+    pub docid: String,
+    #[serde(skip)]
+    pub hash: String,
+    pub collection: String,
+    pub path: String,
+    pub title: String,
+    /// Query phrases the pin is limited to; empty pins apply to every query
+    pub queries: Vec<String>,
+    /// Score multiplier; `None` places the document first
+    pub boost: Option<f64>,
+}
+
+impl PinnedDocument {
+    /// Whether the pin applies to a query
+    pub fn matches_query(&self, query: &str) -> bool {
+        if self.queries.is_empty() {
+            return true;
+        }
+        let query = format!(" {} ", normalize(query));
+        self.queries
+            .iter()
+            .map(|q| normalize(q))
+            .any(|q| !q.is_empty() && query.contains(&format!(" {} ", q)))
+    }
+}
+
+/// Lowercased words joined by single spaces
+fn normalize(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn pin_from_metadata(metadata: &UserMetadata) -> Option<(Vec<String>, Option<f64>)> {
+    if metadata.get(PINNED_FIELD) != Some(&MetadataValue::Boolean(true)) {
+        return None;
+    }
+    let queries = match metadata.get(PIN_QUERIES_FIELD) {
+        Some(MetadataValue::Tags(tags)) => tags.clone(),
+        Some(MetadataValue::Text(text)) => text
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect(),
+        _ => Vec::new(),
+    };
+    let boost = match metadata.get(PIN_BOOST_FIELD) {
+        Some(MetadataValue::Float(f)) => Some(*f),
+        Some(MetadataValue::Integer(i)) => Some(*i as f64),
+        _ => None,
+    }
+    .filter(|b| *b > 0.0);
+    Some((queries, boost))
+}
+
+impl Database {
+    /// Pin a document, replacing any earlier pin on it
+    ///
+    /// The document is given by docid, virtual path or collection/path.
+    pub fn pin_document(
+        &self,
+        docid: &str,
+        queries: &[String],
+        boost: Option<f64>,
+    ) -> Result<PinnedDocument> {
+        if boost.is_some_and(|b| !(b > 0.0 && b.is_finite())) {
+            return Err(AgentRootError::InvalidInput(
+                "Pin boost must be a positive number".to_string(),
+            ));
+        }
+        let id = self
+            .resolve_document_id(docid)?
+            .ok_or_else(|| AgentRootError::DocumentNotFound(docid.to_string()))?;
+
+        let mut metadata = self.document_user_metadata(id)?;
+        metadata.remove(PIN_QUERIES_FIELD);
+        metadata.remove(PIN_BOOST_FIELD);
+        metadata.add(PINNED_FIELD, MetadataValue::Boolean(true));
+        let queries: Vec<String> = queries
+            .iter()
+            .map(|q| q.trim().to_string())
+            .filter(|q| !q.is_empty())
+            .collect();
+        if !queries.is_empty() {
+            metadata.add(PIN_QUERIES_FIELD, MetadataValue::Tags(queries));
+        }
+        if let Some(boost) = boost {
+            metadata.add(PIN_BOOST_FIELD, MetadataValue::Float(boost));
+        }
+        self.conn.execute(
+            "UPDATE documents SET user_metadata = ?1 WHERE id = ?2",
+            params![metadata.to_json()?, id],
+        )?;
+
+        self.pinned_document(id)?
+            .ok_or_else(|| AgentRootError::DocumentNotFound(docid.to_string()))
+    }
+
+    /// Remove a document's pin; returns false if it was not pinned
+    pub fn unpin_document(&self, docid: &str) -> Result<bool> {
+        let id = self
+            .resolve_document_id(docid)?
+            .ok_or_else(|| AgentRootError::DocumentNotFound(docid.to_string()))?;
+
+        let mut metadata = self.document_user_metadata(id)?;
+        let was_pinned = pin_from_metadata(&metadata).is_some();
+        for field in [PINNED_FIELD, PIN_QUERIES_FIELD, PIN_BOOST_FIELD] {
+            metadata.remove(field);
+        }
+        self.conn.execute(
+            "UPDATE documents SET user_metadata = ?1 WHERE id = ?2",
+            params![metadata.to_json()?, id],
+        )?;
+        Ok(was_pinned)
+    }
+
+    /// Pinned active documents, optionally in one collection
+    pub fn list_pins(&self, collection: Option<&str>) -> Result<Vec<PinnedDocument>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT hash, collection, path, title, user_metadata
+             FROM documents
+             WHERE active = 1
+               AND user_metadata LIKE '%\"pinned\"%'
+               AND (?1 IS NULL OR collection = ?1)
+             ORDER BY collection, path",
+        )?;
+        let rows = stmt
+            .query_map(params![collection], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                ))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(hash, collection, path, title, json)| {
+                let metadata = UserMetadata::from_json(&json).ok()?;
+                let (queries, boost) = pin_from_metadata(&metadata)?;
+                Some(PinnedDocument {
+                    docid: docid_from_hash(&hash),
+                    hash,
+                    collection,
+                    path,
+                    title,
+                    queries,
+                    boost,
+                })
+            })
+            .collect())
+    }
+
+    fn pinned_document(&self, id: i64) -> Result<Option<PinnedDocument>> {
+        let (hash, collection, path, title): (String, String, String, String) =
+            self.conn.query_row(
+                "SELECT hash, collection, path, title FROM documents WHERE id = ?1",
+                params![id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )?;
+        let metadata = self.document_user_metadata(id)?;
+        Ok(
+            pin_from_metadata(&metadata).map(|(queries, boost)| PinnedDocument {
+                docid: docid_from_hash(&hash),
+                hash,
+                collection,
+                path,
+                title,
+                queries,
+                boost,
+            }),
+        )
+    }
+
+    fn document_user_metadata(&self, id: i64) -> Result<UserMetadata> {
+        let json: Option<String> = self.conn.query_row(
+            "SELECT user_metadata FROM documents WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )?;
+        Ok(json
+            .and_then(|json| UserMetadata::from_json(&json).ok())
+            .unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_pin_and_unpin() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+        db.insert_content("aaa111", "Deploy runbook").unwrap();
+        db.insert_document(
+            "notes",
+            "runbook.md",
+            "Runbook",
+            "aaa111",
+            &now,
+            &now,
+            "file",
+            None,
+        )
+        .unwrap();
+
+        // Other metadata survives pinning
+        let author = crate::db::MetadataBuilder::new()
+            .text("author", "ops")
+            .build();
+        db.add_metadata("#aaa111", &author).unwrap();
+
+        let pin = db
+            .pin_document("notes/runbook.md", &["Deploy ".to_string()], None)
+            .unwrap();
+        assert_eq!(pin.queries, vec!["Deploy"]);
+        assert_eq!(pin.boost, None);
+        assert!(pin.matches_query("how do I deploy?"));
+        assert!(!pin.matches_query("deployment"));
+
+        // Re-pinning replaces the query context
+        let pin = db.pin_document("#aaa111", &[], Some(2.0)).unwrap();
+        assert!(pin.queries.is_empty());
+        assert_eq!(pin.boost, Some(2.0));
+        assert!(pin.matches_query("anything"));
+        assert_eq!(db.list_pins(Some("notes")).unwrap().len(), 1);
+        assert!(db.list_pins(Some("other")).unwrap().is_empty());
+        assert!(db.pin_document("#aaa111", &[], Some(0.0)).is_err());
+
+        assert!(db.unpin_document("#aaa111").unwrap());
+        assert!(!db.unpin_document("#aaa111").unwrap());
+        assert!(db.list_pins(None).unwrap().is_empty());
+        let metadata = db.get_metadata("#aaa111").unwrap().unwrap();
+        assert!(metadata.contains("author"));
+        assert!(!metadata.contains(PINNED_FIELD));
+    }
+}
@@ -0,0 +1,105 @@
+//! Database connections for async request handlers
+//!
+//! `Database` wraps a SQLite connection that cannot be shared between
+//! threads, so each worker thread owns its own connection and a
+//! single-threaded runtime. Servers send closures to the pool and await the
+//! result; up to `workers` requests run at once.
+
+use super::Database;
+use crate::error::{AgentRootError, Result};
+use futures::future::LocalBoxFuture;
+use std::path::Path;
+use std::sync::{mpsc, Arc, Mutex};
+
+type Task = Box<dyn for<'a> FnOnce(&'a Database) -> LocalBoxFuture<'a, ()> + Send>;
+
+/// A fixed set of threads, each with its own connection to the index
+pub struct DbPool {
+    sender: mpsc::Sender<Task>,
+}
+
+impl DbPool {
+    /// Open `workers` connections to the database at `path`
+    pub fn open(path: &Path, workers: usize) -> Result<Self> {
+        Self::open_with(path, workers, false)
+    }
+
+    /// Open `workers` read-only connections; see [`Database::open_read_only`]
+    pub fn open_read_only(path: &Path, workers: usize) -> Result<Self> {
+        Self::open_with(path, workers, true)
+    }
+
+    fn open_with(path: &Path, workers: usize, read_only: bool) -> Result<Self> {
+        let connect = move |path: &Path| {
+            let db = if read_only {
+                Database::open_read_only(path)?
+            } else {
+                Database::open(path)?
+            };
+            db.initialize()?;
+            Ok::<_, AgentRootError>(db)
+        };
+        // Surface open and migration errors before any thread starts
+        connect(path)?;
+
+        let (sender, receiver) = mpsc::channel::<Task>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for i in 0..workers.max(1) {
+            let receiver = Arc::clone(&receiver);
+            let path = path.to_path_buf();
+            std::thread::Builder::new()
+                .name(format!("agentroot-db-{}", i))
+                .spawn(move || {
+                    let runtime = match tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()
+                    {
+                        Ok(runtime) => runtime,
+                        Err(e) => {
+                            tracing::warn!("Database worker disabled: {}", e);
+                            return;
+                        }
+                    };
+                    let db = match connect(&path) {
+                        Ok(db) => db,
+                        Err(e) => {
+                            tracing::warn!("Database worker disabled: {}", e);
+                            return;
+                        }
+                    };
+                    loop {
+                        let task = match receiver.lock() {
+                            Ok(receiver) => receiver.recv(),
+                            Err(_) => return,
+                        };
+                        match task {
+                            Ok(task) => runtime.block_on(task(&db)),
+                            Err(_) => return,
+                        }
+                    }
+                })?;
+        }
+
+        Ok(Self { sender })
+    }
+
+    /// Run `f` against a pooled connection and return its output
+    pub async fn run<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: for<'a> FnOnce(&'a Database) -> LocalBoxFuture<'a, T> + Send + 'static,
+    {
+        let (reply, result) = tokio::sync::oneshot::channel();
+        let task: Task = Box::new(move |db| {
+            Box::pin(async move {
+                let _ = reply.send(f(db).await);
+            })
+        });
+        self.sender
+            .send(task)
+            .map_err(|_| AgentRootError::Other(anyhow::anyhow!("database workers have stopped")))?;
+        result.await.map_err(|_| {
+            AgentRootError::Other(anyhow::anyhow!("database worker dropped the request"))
+        })
+    }
+}
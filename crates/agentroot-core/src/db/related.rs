@@ -0,0 +1,350 @@
+//! Related-document recommendations
+//!
+//! Blends three signals into a single score:
+//! - direct links in either direction (from `document_links`)
+//! - shared glossary concepts (Jaccard overlap)
+//! - cosine similarity between the document's chunk-embedding centroid and
+//!   other documents' chunks, found with the saved ANN index or the streaming
+//!   vector scan
+
+use super::content::docid_from_hash;
+use super::vectors::bytes_to_embedding;
+use super::{Database, VectorFilter};
+use crate::error::{AgentRootError, Result};
+use rusqlite::{params, OptionalExtension};
+use std::collections::{HashMap, HashSet};
+
+const LINK_WEIGHT: f64 = 0.4;
+const CONCEPT_WEIGHT: f64 = 0.3;
+const EMBEDDING_WEIGHT: f64 = 0.3;
+
+/// Nearest chunks fetched per requested result; a document's own chunks and
+/// several chunks of one neighbour can take more than one slot each
+const EMBEDDING_CANDIDATES_PER_RESULT: usize = 8;
+
+/// A document recommended as related to another
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RelatedDocument {
+    pub docid: String,
+    pub collection: String,
+    pub path: String,
+    pub title: String,
+    pub score: f64,
+    pub link_score: f64,
+    pub concept_score: f64,
+    pub embedding_score: f64,
+    pub shared_concepts: Vec<String>,
+}
+
+#[derive(Default)]
+struct Signals {
+    link: f64,
+    concept: f64,
+    embedding: f64,
+    shared_concepts: Vec<String>,
+}
+
+impl Database {
+    /// Find documents related to the given document, best first
+    ///
+    /// Only the target's own links, concepts and embeddings are read, plus
+    /// the candidates they lead to, so the cost does not grow with the corpus.
+    pub fn related(&self, docid: &str, limit: usize) -> Result<Vec<RelatedDocument>> {
+        let target_id = self
+            .resolve_document_id(docid)?
+            .ok_or_else(|| AgentRootError::DocumentNotFound(docid.to_string()))?;
+        let target_hash: String = self.conn.query_row(
+            "SELECT hash FROM documents WHERE id = ?1",
+            params![target_id],
+            |row| row.get(0),
+        )?;
+
+        let mut signals: HashMap<i64, Signals> = HashMap::new();
+
+        for other in self.linked_document_ids(target_id)? {
+            if other != target_id {
+                signals.entry(other).or_default().link = 1.0;
+            }
+        }
+
+        for (hash, (score, terms)) in self.concept_overlap(&target_hash)? {
+            for id in self.active_ids_with_hash(&hash)? {
+                if id != target_id {
+                    let s = signals.entry(id).or_default();
+                    s.concept = score;
+                    s.shared_concepts = terms.clone();
+                }
+            }
+        }
+
+        if let Some(centroid) = self.document_centroid(&target_hash)? {
+            let k = limit.saturating_add(1) * EMBEDDING_CANDIDATES_PER_RESULT;
+            for (hash, sim) in self.nearest_document_hashes(&centroid, k)? {
+                for id in self.active_ids_with_hash(&hash)? {
+                    if id != target_id {
+                        signals.entry(id).or_default().embedding = sim;
+                    }
+                }
+            }
+        }
+
+        let mut details = self.conn.prepare(
+            "SELECT hash, collection, path, title FROM documents WHERE id = ?1 AND active = 1",
+        )?;
+        let mut results = Vec::new();
+        for (id, s) in signals {
+            let score =
+                LINK_WEIGHT * s.link + CONCEPT_WEIGHT * s.concept + EMBEDDING_WEIGHT * s.embedding;
+            if score <= 0.0 {
+                continue;
+            }
+            let Some((hash, collection, path, title)) = details
+                .query_row(params![id], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                    ))
+                })
+                .optional()?
+            else {
+                continue;
+            };
+            results.push(RelatedDocument {
+                docid: docid_from_hash(&hash),
+                collection,
+                path,
+                title,
+                score,
+                link_score: s.link,
+                concept_score: s.concept,
+                embedding_score: s.embedding,
+                shared_concepts: s.shared_concepts,
+            });
+        }
+
+        results.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        results.truncate(limit);
+
+        Ok(results)
+    }
+
+    /// Ids of the active documents with the given content hash
+    fn active_ids_with_hash(&self, hash: &str) -> Result<Vec<i64>> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT id FROM documents WHERE hash = ?1 AND active = 1")?;
+        let ids = stmt
+            .query_map(params![hash], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(ids)
+    }
+
+    /// Best chunk similarity per document hash among the `k` chunks nearest
+    /// to `embedding`
+    fn nearest_document_hashes(&self, embedding: &[f32], k: usize) -> Result<HashMap<String, f64>> {
+        let candidates = match self.saved_ann_index()? {
+            Some(ann) if ann.is_built() => ann.search(embedding, k),
+            _ => self.top_k_embeddings(embedding, k, VectorFilter::default())?,
+        };
+
+        let mut best: HashMap<String, f64> = HashMap::new();
+        for (hash_seq, score) in candidates {
+            let Some((hash, _)) = hash_seq.rsplit_once('_') else {
+                continue;
+            };
+            let score = (score as f64).max(0.0);
+            if score > 0.0 {
+                let entry = best.entry(hash.to_string()).or_insert(0.0);
+                *entry = entry.max(score);
+            }
+        }
+        Ok(best)
+    }
+
+    /// Ids of documents linked to or from the given document
+    fn linked_document_ids(&self, doc_id: i64) -> Result<HashSet<i64>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT target_id FROM document_links WHERE source_id = ?1
+             UNION
+             SELECT source_id FROM document_links WHERE target_id = ?1",
+        )?;
+        let ids = stmt
+            .query_map(params![doc_id], |row| row.get(0))?
+            .collect::<std::result::Result<HashSet<_>, _>>()?;
+        Ok(ids)
+    }
+
+    /// Jaccard concept overlap with the given document, keyed by document hash
+    fn concept_overlap(&self, doc_hash: &str) -> Result<HashMap<String, (f64, Vec<String>)>> {
+        let target_count: i64 = self.conn.query_row(
+            "SELECT COUNT(DISTINCT concept_id) FROM concept_chunks WHERE document_hash = ?1",
+            params![doc_hash],
+            |row| row.get(0),
+        )?;
+        if target_count == 0 {
+            return Ok(HashMap::new());
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT cc.document_hash, c.term
+             FROM concept_chunks cc
+             JOIN concepts c ON c.id = cc.concept_id
+             WHERE cc.document_hash != ?1
+               AND cc.concept_id IN (
+                   SELECT concept_id FROM concept_chunks WHERE document_hash = ?1
+               )
+             ORDER BY c.term",
+        )?;
+        let mut shared: HashMap<String, Vec<String>> = HashMap::new();
+        for row in stmt.query_map(params![doc_hash], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })? {
+            let (hash, term) = row?;
+            shared.entry(hash).or_default().push(term);
+        }
+
+        let mut count_stmt = self.conn.prepare(
+            "SELECT COUNT(DISTINCT concept_id) FROM concept_chunks WHERE document_hash = ?1",
+        )?;
+        let mut overlap = HashMap::new();
+        for (hash, terms) in shared {
+            let other_count: i64 = count_stmt.query_row(params![hash], |row| row.get(0))?;
+            let union = (target_count + other_count) as f64 - terms.len() as f64;
+            let score = if union > 0.0 {
+                terms.len() as f64 / union
+            } else {
+                0.0
+            };
+            overlap.insert(hash, (score, terms));
+        }
+
+        Ok(overlap)
+    }
+
+    /// Mean chunk embedding per active document hash
+    pub(super) fn document_centroids(&self) -> Result<HashMap<String, Vec<f32>>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT cv.hash, e.embedding
+             FROM content_vectors cv
+             JOIN embeddings e ON e.hash_seq = cv.hash || '_' || cv.seq
+             WHERE cv.hash IN (SELECT hash FROM documents WHERE active = 1)",
+        )?;
+
+        let mut sums: HashMap<String, (Vec<f32>, usize)> = HashMap::new();
+        for row in stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+        })? {
+            let (hash, bytes) = row?;
+            let embedding = bytes_to_embedding(&bytes);
+            let (sum, count) = sums
+                .entry(hash)
+                .or_insert_with(|| (vec![0.0; embedding.len()], 0));
+            if sum.len() != embedding.len() {
+                continue;
+            }
+            for (acc, v) in sum.iter_mut().zip(&embedding) {
+                *acc += v;
+            }
+            *count += 1;
+        }
+
+        Ok(sums
+            .into_iter()
+            .filter(|(_, (_, count))| *count > 0)
+            .map(|(hash, (sum, count))| {
+                let centroid = sum.into_iter().map(|v| v / count as f32).collect();
+                (hash, centroid)
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn setup_db() -> Database {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+        for (hash, path, body) in [
+            ("aaa111", "a.md", "Links to [b](b.md)"),
+            ("bbb222", "b.md", "Plain note"),
+            ("ccc333", "c.md", "Another note"),
+            ("ddd444", "d.md", "Unrelated"),
+        ] {
+            db.insert_content(hash, body).unwrap();
+            db.insert_document("notes", path, path, hash, &now, &now, "file", None)
+                .unwrap();
+        }
+        db.build_link_graph().unwrap();
+        db
+    }
+
+    fn tag_concept(db: &Database, term: &str, doc_hash: &str) {
+        let concept_id = db.upsert_concept(term).unwrap();
+        db.link_concept_to_chunk(concept_id, &format!("{}-chunk", doc_hash), doc_hash, "")
+            .unwrap();
+    }
+
+    #[test]
+    fn test_related_blends_links_and_concepts() {
+        let db = setup_db();
+        tag_concept(&db, "oauth", "aaa111");
+        tag_concept(&db, "oauth", "ccc333");
+
+        let related = db.related("notes/a.md", 10).unwrap();
+        let paths: Vec<&str> = related.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(paths, vec!["b.md", "c.md"]);
+
+        assert_eq!(related[0].link_score, 1.0);
+        assert_eq!(related[1].concept_score, 1.0);
+        assert_eq!(related[1].shared_concepts, vec!["oauth".to_string()]);
+
+        // Incoming links count too
+        let from_b = db.related("notes/b.md", 10).unwrap();
+        assert_eq!(from_b.len(), 1);
+        assert_eq!(from_b[0].path, "a.md");
+    }
+
+    #[test]
+    fn test_related_uses_embedding_similarity() {
+        let db = setup_db();
+        db.ensure_vec_table(2).unwrap();
+        db.insert_embedding("ccc333", 0, 0, "m", &[1.0, 0.0])
+            .unwrap();
+        db.insert_embedding("ddd444", 0, 0, "m", &[1.0, 0.1])
+            .unwrap();
+        db.insert_embedding("ddd444", 1, 50, "m", &[1.0, -0.1])
+            .unwrap();
+
+        let related = db.related("notes/c.md", 10).unwrap();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].path, "d.md");
+        // Cosine of c's centroid with d's closest chunk
+        let expected = 1.0 / 1.01f64.sqrt();
+        assert!((related[0].embedding_score - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_related_respects_limit() {
+        let db = setup_db();
+        tag_concept(&db, "shared", "aaa111");
+        tag_concept(&db, "shared", "ccc333");
+        tag_concept(&db, "shared", "ddd444");
+
+        assert_eq!(db.related("notes/a.md", 1).unwrap().len(), 1);
+        assert!(db.related("notes/missing.md", 5).is_err());
+    }
+}
@@ -1,15 +1,22 @@
 //! Database schema and initialization
 
-use crate::error::Result;
-use rusqlite::{params, Connection};
+use crate::error::{AgentRootError, Result};
+use rusqlite::{params, Connection, OpenFlags};
 use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 /// Main database handle
 pub struct Database {
     pub(crate) conn: Connection,
+    /// Shared by connections to the same file; None for in-memory databases
+    pub(crate) write_queue: Option<Arc<Mutex<()>>>,
+    pub(crate) write_timeout: Duration,
+    /// Opened with `open_read_only`; every write is rejected
+    pub(crate) read_only: bool,
 }
 
-const SCHEMA_VERSION: i32 = 11;
+pub(super) const SCHEMA_VERSION: i32 = 31;
 
 const CREATE_TABLES: &str = r#"
 -- Content storage (content-addressable by SHA-256 hash)
@@ -45,21 +52,6 @@ CREATE TABLE IF NOT EXISTS documents (
     UNIQUE(collection, path)
 );
 
--- Full-text search index
-CREATE VIRTUAL TABLE IF NOT EXISTS documents_fts USING fts5(
-    filepath,
-    title,
-    body,
-    llm_summary,
-    llm_title,
-    llm_keywords,
-    llm_intent,
-    llm_concepts,
-    user_metadata,
-    modified_at,
-    tokenize='porter unicode61'
-);
-
 -- Vector embeddings metadata
 CREATE TABLE IF NOT EXISTS content_vectors (
     hash TEXT NOT NULL,
@@ -128,13 +120,14 @@ CREATE INDEX IF NOT EXISTS idx_content_vectors_chunk_hash ON content_vectors(chu
 CREATE INDEX IF NOT EXISTS idx_chunk_embeddings_hash ON chunk_embeddings(chunk_hash);
 "#;
 
+/// Triggers of the shared FTS table, replaced by per-collection tables in v31
 const CREATE_TRIGGERS: &str = r#"
 -- Sync FTS on insert (only for active documents)
 CREATE TRIGGER IF NOT EXISTS documents_ai
 AFTER INSERT ON documents
 WHEN new.active = 1
 BEGIN
-    INSERT INTO documents_fts(rowid, filepath, title, body, llm_summary, llm_title, llm_keywords, llm_intent, llm_concepts, user_metadata, modified_at)
+    INSERT INTO documents_fts(rowid, filepath, title, body, llm_summary, llm_title, llm_keywords, llm_intent, llm_concepts, user_metadata, modified_at, collection_key)
     SELECT
         new.id,
         new.collection || '/' || new.path,
@@ -146,7 +139,8 @@ BEGIN
         new.llm_intent,
         new.llm_concepts,
         new.user_metadata,
-        new.modified_at;
+        new.modified_at,
+        'c' || lower(hex(new.collection));
 END;
 
 -- Sync FTS on update: handle activation/deactivation/content change
@@ -154,7 +148,7 @@ CREATE TRIGGER IF NOT EXISTS documents_au
 AFTER UPDATE ON documents
 BEGIN
     DELETE FROM documents_fts WHERE rowid = old.id;
-    INSERT INTO documents_fts(rowid, filepath, title, body, llm_summary, llm_title, llm_keywords, llm_intent, llm_concepts, user_metadata, modified_at)
+    INSERT INTO documents_fts(rowid, filepath, title, body, llm_summary, llm_title, llm_keywords, llm_intent, llm_concepts, user_metadata, modified_at, collection_key)
     SELECT
         new.id,
         new.collection || '/' || new.path,
@@ -166,7 +160,8 @@ BEGIN
         new.llm_intent,
         new.llm_concepts,
         new.user_metadata,
-        new.modified_at
+        new.modified_at,
+        'c' || lower(hex(new.collection))
     WHERE new.active = 1;
 END;
 
@@ -178,26 +173,184 @@ BEGIN
 END;
 "#;
 
+/// Keep the shared unstemmed index in step with the text it covers, until v31
+const CREATE_EXACT_TRIGGERS: &str = r#"
+CREATE TRIGGER IF NOT EXISTS documents_exact_ai
+AFTER INSERT ON documents
+WHEN new.active = 1
+BEGIN
+    INSERT INTO documents_fts_exact(rowid, filepath, title, body, collection_key)
+    SELECT
+        new.id,
+        new.collection || '/' || new.path,
+        new.title,
+        (SELECT doc FROM content WHERE hash = new.hash),
+        'c' || lower(hex(new.collection));
+END;
+
+CREATE TRIGGER IF NOT EXISTS documents_exact_au
+AFTER UPDATE OF collection, path, title, hash, active ON documents
+BEGIN
+    DELETE FROM documents_fts_exact WHERE rowid = old.id;
+    INSERT INTO documents_fts_exact(rowid, filepath, title, body, collection_key)
+    SELECT
+        new.id,
+        new.collection || '/' || new.path,
+        new.title,
+        (SELECT doc FROM content WHERE hash = new.hash),
+        'c' || lower(hex(new.collection))
+    WHERE new.active = 1;
+END;
+
+CREATE TRIGGER IF NOT EXISTS documents_exact_ad
+AFTER DELETE ON documents
+BEGIN
+    DELETE FROM documents_fts_exact WHERE rowid = old.id;
+END;
+"#;
+
+/// Record a document version whenever a path gets content it did not have
+/// just before, whichever code path wrote the row
+const CREATE_VERSION_TRIGGERS: &str = r#"
+CREATE TRIGGER IF NOT EXISTS documents_version_ai
+AFTER INSERT ON documents
+WHEN new.active = 1 AND new.hash IS NOT (
+    SELECT hash FROM document_versions
+    WHERE collection = new.collection AND path = new.path
+    ORDER BY id DESC LIMIT 1
+)
+BEGIN
+    INSERT INTO document_versions (collection, path, hash, title, recorded_at)
+    VALUES (new.collection, new.path, new.hash, new.title, new.modified_at);
+END;
+
+CREATE TRIGGER IF NOT EXISTS documents_version_au
+AFTER UPDATE OF hash ON documents
+WHEN new.active = 1 AND new.hash IS NOT (
+    SELECT hash FROM document_versions
+    WHERE collection = new.collection AND path = new.path
+    ORDER BY id DESC LIMIT 1
+)
+BEGIN
+    INSERT INTO document_versions (collection, path, hash, title, recorded_at)
+    VALUES (new.collection, new.path, new.hash, new.title, new.modified_at);
+END;
+"#;
+
 impl Database {
     /// Open database at path, creating if necessary
+    ///
+    /// Encrypted indexes are unlocked with their key, see `encryption`.
     pub fn open(path: impl AsRef<Path>) -> Result<Self> {
         let path = path.as_ref();
+        let key = super::encryption::key_for(path)?;
+        Self::open_with_key(path, key.as_deref())
+    }
+
+    pub(crate) fn open_with_key(path: &Path, key: Option<&str>) -> Result<Self> {
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)?;
         }
 
         let conn = Connection::open(path)?;
-        Ok(Self { conn })
+        if let Some(key) = key {
+            super::encryption::unlock(&conn, key)?;
+        }
+        Ok(Self {
+            conn,
+            write_queue: Some(super::write_queue::queue_for(path)),
+            write_timeout: super::write_queue::write_timeout(),
+            read_only: false,
+        })
+    }
+
+    /// Open an existing database that rejects every write
+    ///
+    /// Nothing is created or migrated, so agents can be pointed at a shared
+    /// or production index without being able to change it.
+    pub fn open_read_only(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Err(AgentRootError::ReadOnly(format!(
+                "no index at {}",
+                path.display()
+            )));
+        }
+
+        let key = super::encryption::key_for(path)?;
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY
+                | OpenFlags::SQLITE_OPEN_NO_MUTEX
+                | OpenFlags::SQLITE_OPEN_URI,
+        )?;
+        if let Some(key) = key {
+            super::encryption::unlock(&conn, &key)?;
+        }
+        Ok(Self {
+            conn,
+            write_queue: None,
+            write_timeout: super::write_queue::write_timeout(),
+            read_only: true,
+        })
+    }
+
+    /// Whether this connection was opened with `open_read_only`
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
     }
 
     /// Open in-memory database (for testing)
     pub fn open_in_memory() -> Result<Self> {
         let conn = Connection::open_in_memory()?;
-        Ok(Self { conn })
+        Ok(Self {
+            conn,
+            write_queue: None,
+            write_timeout: super::write_queue::write_timeout(),
+            read_only: false,
+        })
+    }
+
+    /// Run `f` in a write transaction, committing only if it succeeds
+    ///
+    /// Other connections keep reading the previous state until the commit.
+    /// Waits for the write lock as described in `write_queue`. Called inside
+    /// another transaction, `f` runs in a savepoint that is undone on error
+    /// and committed with the outer transaction.
+    pub(crate) fn in_transaction<T>(&self, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        if !self.conn.is_autocommit() {
+            self.conn.execute_batch("SAVEPOINT nested")?;
+            let result = f();
+            if result.is_ok() {
+                self.conn.execute_batch("RELEASE nested")?;
+            } else {
+                let _ = self
+                    .conn
+                    .execute_batch("ROLLBACK TO nested; RELEASE nested");
+            }
+            return result;
+        }
+
+        let _queue = self.begin_write()?;
+        let result = f();
+        let committed = match result {
+            Ok(_) => self.conn.execute_batch("COMMIT"),
+            Err(_) => Ok(()),
+        };
+        // A failed COMMIT leaves the transaction open; end it either way
+        if result.is_err() || committed.is_err() {
+            let _ = self.conn.execute_batch("ROLLBACK");
+        }
+        committed?;
+        result
     }
 
     /// Initialize database schema
     pub fn initialize(&self) -> Result<()> {
+        if self.read_only {
+            return self.check_read_only_schema();
+        }
+
         // Set PRAGMAs for performance
         self.conn.execute_batch(
             "PRAGMA journal_mode = WAL;
@@ -210,9 +363,6 @@ impl Database {
         // Create tables
         self.conn.execute_batch(CREATE_TABLES)?;
 
-        // Create triggers
-        self.conn.execute_batch(CREATE_TRIGGERS)?;
-
         // Run migrations to upgrade existing databases (BEFORE setting version)
         self.migrate()?;
 
@@ -238,6 +388,21 @@ impl Database {
         Ok(version)
     }
 
+    /// A read-only index cannot be migrated, so it must already be current
+    fn check_read_only_schema(&self) -> Result<()> {
+        self.conn.busy_timeout(Duration::from_millis(5000))?;
+        match self.schema_version()? {
+            Some(version) if version >= SCHEMA_VERSION => Ok(()),
+            Some(version) => Err(AgentRootError::ReadOnly(format!(
+                "index schema is v{} but v{} is required; open it once without --read-only to migrate",
+                version, SCHEMA_VERSION
+            ))),
+            None => Err(AgentRootError::ReadOnly(
+                "index is not initialized; run agentroot without --read-only first".to_string(),
+            )),
+        }
+    }
+
     /// Run migrations to upgrade schema to current version
     pub fn migrate(&self) -> Result<()> {
         let current = self.schema_version()?.unwrap_or(0);
@@ -282,6 +447,86 @@ impl Database {
             self.migrate_to_v11()?;
         }
 
+        if current < 12 {
+            self.migrate_to_v12()?;
+        }
+
+        if current < 13 {
+            self.migrate_to_v13()?;
+        }
+
+        if current < 14 {
+            self.migrate_to_v14()?;
+        }
+
+        if current < 15 {
+            self.migrate_to_v15()?;
+        }
+
+        if current < 16 {
+            self.migrate_to_v16()?;
+        }
+
+        if current < 17 {
+            self.migrate_to_v17()?;
+        }
+
+        if current < 18 {
+            self.migrate_to_v18()?;
+        }
+
+        if current < 19 {
+            self.migrate_to_v19()?;
+        }
+
+        if current < 20 {
+            self.migrate_to_v20()?;
+        }
+
+        if current < 21 {
+            self.migrate_to_v21()?;
+        }
+
+        if current < 22 {
+            self.migrate_to_v22()?;
+        }
+
+        if current < 23 {
+            self.migrate_to_v23()?;
+        }
+
+        if current < 24 {
+            self.migrate_to_v24()?;
+        }
+
+        if current < 25 {
+            self.migrate_to_v25()?;
+        }
+
+        if current < 26 {
+            self.migrate_to_v26()?;
+        }
+
+        if current < 27 {
+            self.migrate_to_v27()?;
+        }
+
+        if current < 28 {
+            self.migrate_to_v28()?;
+        }
+
+        if current < 29 {
+            self.migrate_to_v29()?;
+        }
+
+        if current < 30 {
+            self.migrate_to_v30()?;
+        }
+
+        if current < 31 {
+            self.migrate_to_v31()?;
+        }
+
         Ok(())
     }
 
@@ -1181,136 +1426,892 @@ impl Database {
 
         Ok(())
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_open_in_memory() {
-        let db = Database::open_in_memory().unwrap();
-        db.initialize().unwrap();
-        assert_eq!(db.schema_version().unwrap(), Some(SCHEMA_VERSION));
-    }
 
-    #[test]
-    fn test_migration_v2_to_v3() {
-        let db = Database::open_in_memory().unwrap();
+    fn migrate_to_v12(&self) -> Result<()> {
+        // Topic clusters detected over the link/concept/embedding graph
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS topics (
+                id INTEGER PRIMARY KEY,
+                label TEXT NOT NULL,
+                keywords TEXT,
+                size INTEGER NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
 
-        db.conn
-            .execute_batch(
-                "CREATE TABLE collections (
-                name TEXT PRIMARY KEY,
-                path TEXT NOT NULL,
-                pattern TEXT NOT NULL DEFAULT '**/*.md',
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
-            );
-            CREATE TABLE documents (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                collection TEXT NOT NULL,
-                path TEXT NOT NULL,
-                title TEXT NOT NULL,
-                hash TEXT NOT NULL,
-                created_at TEXT NOT NULL,
-                modified_at TEXT NOT NULL,
-                active INTEGER NOT NULL DEFAULT 1,
-                UNIQUE(collection, path)
-            );
-            CREATE TABLE schema_version (version INTEGER PRIMARY KEY);
-            INSERT INTO schema_version VALUES (2);",
-            )
-            .unwrap();
+        let has_topic_id: bool = self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('documents') WHERE name = 'topic_id'",
+            [],
+            |row| row.get(0),
+        )?;
 
-        assert_eq!(db.schema_version().unwrap(), Some(2));
+        if !has_topic_id {
+            self.conn
+                .execute("ALTER TABLE documents ADD COLUMN topic_id INTEGER", [])?;
+        }
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_documents_topic ON documents(topic_id)",
+            [],
+        )?;
 
-        db.initialize().unwrap();
+        // Update schema version
+        self.conn.execute(
+            "INSERT OR REPLACE INTO schema_version (version) VALUES (?1)",
+            params![12],
+        )?;
 
-        assert_eq!(db.schema_version().unwrap(), Some(11));
+        Ok(())
+    }
 
-        let has_provider_type: bool = db.conn.query_row(
-            "SELECT COUNT(*) > 0 FROM pragma_table_info('collections') WHERE name = 'provider_type'",
+    fn migrate_to_v13(&self) -> Result<()> {
+        // Named sessions that can be listed and resumed across restarts
+        let has_name: bool = self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('sessions') WHERE name = 'name'",
             [],
             |row| row.get(0),
-        ).unwrap();
-        assert!(
-            has_provider_type,
-            "collections should have provider_type column"
-        );
+        )?;
 
-        let has_provider_config: bool = db.conn.query_row(
-            "SELECT COUNT(*) > 0 FROM pragma_table_info('collections') WHERE name = 'provider_config'",
+        if !has_name {
+            self.conn
+                .execute("ALTER TABLE sessions ADD COLUMN name TEXT", [])?;
+        }
+        self.conn.execute(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_sessions_name ON sessions(name)",
             [],
-            |row| row.get(0),
-        ).unwrap();
-        assert!(
-            has_provider_config,
-            "collections should have provider_config column"
-        );
+        )?;
 
-        let has_source_type: bool = db.conn.query_row(
-            "SELECT COUNT(*) > 0 FROM pragma_table_info('documents') WHERE name = 'source_type'",
-            [],
-            |row| row.get(0),
-        ).unwrap();
-        assert!(has_source_type, "documents should have source_type column");
+        // Update schema version
+        self.conn.execute(
+            "INSERT OR REPLACE INTO schema_version (version) VALUES (?1)",
+            params![13],
+        )?;
 
-        let has_source_uri: bool = db
-            .conn
-            .query_row(
-                "SELECT COUNT(*) > 0 FROM pragma_table_info('documents') WHERE name = 'source_uri'",
-                [],
-                |row| row.get(0),
-            )
-            .unwrap();
-        assert!(has_source_uri, "documents should have source_uri column");
+        Ok(())
     }
 
-    #[test]
-    fn test_migration_v3_to_v4() {
-        let db = Database::open_in_memory().unwrap();
-
-        db.conn
-            .execute_batch(
-                "CREATE TABLE collections (
-                name TEXT PRIMARY KEY,
-                path TEXT NOT NULL,
-                pattern TEXT NOT NULL DEFAULT '**/*.md',
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                provider_type TEXT NOT NULL DEFAULT 'file',
-                provider_config TEXT
-            );
-            CREATE TABLE documents (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
+    fn migrate_to_v14(&self) -> Result<()> {
+        // Relevance judgments used to adjust ranking; keyed by collection/path
+        // so they survive document edits
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS search_feedback (
+                id INTEGER PRIMARY KEY,
+                query TEXT NOT NULL,
                 collection TEXT NOT NULL,
                 path TEXT NOT NULL,
-                title TEXT NOT NULL,
-                hash TEXT NOT NULL,
-                created_at TEXT NOT NULL,
-                modified_at TEXT NOT NULL,
-                active INTEGER NOT NULL DEFAULT 1,
-                source_type TEXT NOT NULL DEFAULT 'file',
-                source_uri TEXT,
-                UNIQUE(collection, path)
-            );
-            CREATE TABLE schema_version (version INTEGER PRIMARY KEY);
-            INSERT INTO schema_version VALUES (3);",
-            )
-            .unwrap();
+                document_hash TEXT NOT NULL,
+                relevant INTEGER NOT NULL,
+                session_id TEXT,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_search_feedback_doc ON search_feedback(collection, path)",
+            [],
+        )?;
 
-        assert_eq!(db.schema_version().unwrap(), Some(3));
+        // Update schema version
+        self.conn.execute(
+            "INSERT OR REPLACE INTO schema_version (version) VALUES (?1)",
+            params![14],
+        )?;
 
-        db.initialize().unwrap();
+        Ok(())
+    }
 
-        assert_eq!(db.schema_version().unwrap(), Some(11));
+    fn migrate_to_v15(&self) -> Result<()> {
+        // Mean result score per session query, for session analytics
+        let has_avg_score: bool = self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('session_queries') WHERE name = 'avg_score'",
+            [],
+            |row| row.get(0),
+        )?;
 
-        let metadata_columns = vec![
-            "llm_summary",
-            "llm_title",
-            "llm_keywords",
-            "llm_category",
+        if !has_avg_score {
+            self.conn
+                .execute("ALTER TABLE session_queries ADD COLUMN avg_score REAL", [])?;
+        }
+
+        // Update schema version
+        self.conn.execute(
+            "INSERT OR REPLACE INTO schema_version (version) VALUES (?1)",
+            params![15],
+        )?;
+
+        Ok(())
+    }
+
+    fn migrate_to_v16(&self) -> Result<()> {
+        // Searches that exceeded the slow query threshold, with per-stage timings
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS slow_queries (
+                id INTEGER PRIMARY KEY,
+                mode TEXT NOT NULL,
+                query TEXT NOT NULL,
+                options TEXT NOT NULL,
+                timings TEXT NOT NULL,
+                total_ms REAL NOT NULL,
+                result_count INTEGER NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // Update schema version
+        self.conn.execute(
+            "INSERT OR REPLACE INTO schema_version (version) VALUES (?1)",
+            params![16],
+        )?;
+
+        Ok(())
+    }
+
+    fn migrate_to_v17(&self) -> Result<()> {
+        // Single-row bookkeeping for automatic FTS optimize/ANALYZE runs
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS index_maintenance (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                pending_changes INTEGER NOT NULL DEFAULT 0,
+                last_optimized_at TEXT,
+                last_duration_ms REAL
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "INSERT OR IGNORE INTO index_maintenance (id) VALUES (1)",
+            [],
+        )?;
+
+        // Update schema version
+        self.conn.execute(
+            "INSERT OR REPLACE INTO schema_version (version) VALUES (?1)",
+            params![17],
+        )?;
+
+        Ok(())
+    }
+
+    fn migrate_to_v18(&self) -> Result<()> {
+        // Resume point for forced re-embedding runs, per model
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS embed_checkpoints (
+                model TEXT PRIMARY KEY,
+                last_hash TEXT NOT NULL,
+                documents INTEGER NOT NULL DEFAULT 0,
+                updated_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // Update schema version
+        self.conn.execute(
+            "INSERT OR REPLACE INTO schema_version (version) VALUES (?1)",
+            params![18],
+        )?;
+
+        Ok(())
+    }
+
+    fn migrate_to_v19(&self) -> Result<()> {
+        // Persistent queue for heavy background work
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS jobs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'queued',
+                priority INTEGER NOT NULL DEFAULT 0,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                max_attempts INTEGER NOT NULL DEFAULT 3,
+                last_error TEXT,
+                result TEXT,
+                run_after TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                started_at TEXT,
+                finished_at TEXT
+            );
+            CREATE INDEX IF NOT EXISTS idx_jobs_queue ON jobs(status, priority DESC, run_after);",
+        )?;
+
+        // Update schema version
+        self.conn.execute(
+            "INSERT OR REPLACE INTO schema_version (version) VALUES (?1)",
+            params![19],
+        )?;
+
+        Ok(())
+    }
+
+    fn migrate_to_v20(&self) -> Result<()> {
+        // Tag every FTS row with a single token derived from its collection
+        // name, so collection-scoped searches intersect posting lists instead
+        // of filtering all matches. Rows of all collections share one table.
+        self.conn
+            .execute("DROP TABLE IF EXISTS documents_fts", [])?;
+
+        self.conn.execute(
+            "CREATE VIRTUAL TABLE documents_fts USING fts5(
+                filepath,
+                title,
+                body,
+                llm_summary,
+                llm_title,
+                llm_keywords,
+                llm_intent,
+                llm_concepts,
+                user_metadata,
+                modified_at,
+                collection_key,
+                tokenize='porter unicode61'
+            )",
+            [],
+        )?;
+
+        self.conn.execute(
+            "INSERT INTO documents_fts(rowid, filepath, title, body, llm_summary, llm_title, llm_keywords, llm_intent, llm_concepts, user_metadata, modified_at, collection_key)
+             SELECT
+                d.id,
+                d.collection || '/' || d.path,
+                d.title,
+                c.doc,
+                d.llm_summary,
+                d.llm_title,
+                d.llm_keywords,
+                d.llm_intent,
+                d.llm_concepts,
+                d.user_metadata,
+                d.modified_at,
+                'c' || lower(hex(d.collection))
+             FROM documents d
+             JOIN content c ON c.hash = d.hash
+             WHERE d.active = 1",
+            [],
+        )?;
+
+        self.conn
+            .execute("DROP TRIGGER IF EXISTS documents_ai", [])?;
+        self.conn
+            .execute("DROP TRIGGER IF EXISTS documents_au", [])?;
+        self.conn
+            .execute("DROP TRIGGER IF EXISTS documents_ad", [])?;
+        self.conn.execute_batch(CREATE_TRIGGERS)?;
+
+        // Collection-scoped vector scans start from documents(collection, active)
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_documents_collection_active ON documents(collection, active, hash)",
+            [],
+        )?;
+
+        // Update schema version
+        self.conn.execute(
+            "INSERT OR REPLACE INTO schema_version (version) VALUES (?1)",
+            params![20],
+        )?;
+
+        Ok(())
+    }
+
+    fn migrate_to_v21(&self) -> Result<()> {
+        // remove_diacritics 2 also folds letters carrying several marks
+        // (Vietnamese "ế"), which the default of 1 leaves untouched
+        self.conn
+            .execute("DROP TABLE IF EXISTS documents_fts", [])?;
+
+        self.conn.execute(
+            "CREATE VIRTUAL TABLE documents_fts USING fts5(
+                filepath,
+                title,
+                body,
+                llm_summary,
+                llm_title,
+                llm_keywords,
+                llm_intent,
+                llm_concepts,
+                user_metadata,
+                modified_at,
+                collection_key,
+                tokenize='porter unicode61 remove_diacritics 2'
+            )",
+            [],
+        )?;
+
+        self.conn.execute(
+            "INSERT INTO documents_fts(rowid, filepath, title, body, llm_summary, llm_title, llm_keywords, llm_intent, llm_concepts, user_metadata, modified_at, collection_key)
+             SELECT
+                d.id,
+                d.collection || '/' || d.path,
+                d.title,
+                c.doc,
+                d.llm_summary,
+                d.llm_title,
+                d.llm_keywords,
+                d.llm_intent,
+                d.llm_concepts,
+                d.user_metadata,
+                d.modified_at,
+                'c' || lower(hex(d.collection))
+             FROM documents d
+             JOIN content c ON c.hash = d.hash
+             WHERE d.active = 1",
+            [],
+        )?;
+
+        // chunks_fts reads from the chunks table, so it only needs a rebuild
+        self.conn.execute("DROP TABLE IF EXISTS chunks_fts", [])?;
+        self.conn.execute(
+            "CREATE VIRTUAL TABLE chunks_fts USING fts5(
+                content,
+                breadcrumb,
+                llm_summary,
+                llm_purpose,
+                content='chunks',
+                content_rowid='rowid',
+                tokenize='porter unicode61 remove_diacritics 2'
+            )",
+            [],
+        )?;
+        self.conn
+            .execute("INSERT INTO chunks_fts(chunks_fts) VALUES ('rebuild')", [])?;
+
+        // Update schema version
+        self.conn.execute(
+            "INSERT OR REPLACE INTO schema_version (version) VALUES (?1)",
+            params![21],
+        )?;
+
+        Ok(())
+    }
+
+    fn migrate_to_v22(&self) -> Result<()> {
+        // Source file mtime and size, so reindexing skips unchanged files
+        // without reading and hashing them
+        for column in ["source_mtime", "source_size"] {
+            let exists: bool = self.conn.query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('documents') WHERE name = ?1",
+                params![column],
+                |row| row.get(0),
+            )?;
+            if !exists {
+                self.conn.execute(
+                    &format!("ALTER TABLE documents ADD COLUMN {} INTEGER", column),
+                    [],
+                )?;
+            }
+        }
+
+        // Update schema version
+        self.conn.execute(
+            "INSERT OR REPLACE INTO schema_version (version) VALUES (?1)",
+            params![22],
+        )?;
+
+        Ok(())
+    }
+
+    fn migrate_to_v23(&self) -> Result<()> {
+        // Surface words per collection for prefix completion
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS vocabulary (
+                collection TEXT NOT NULL,
+                term TEXT NOT NULL,
+                doc_count INTEGER NOT NULL,
+                PRIMARY KEY (collection, term)
+            ) WITHOUT ROWID",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_vocabulary_term ON vocabulary(term)",
+            [],
+        )?;
+
+        for collection in self.list_collections()? {
+            self.rebuild_vocabulary(&collection.name)?;
+        }
+
+        // Update schema version
+        self.conn.execute(
+            "INSERT OR REPLACE INTO schema_version (version) VALUES (?1)",
+            params![23],
+        )?;
+
+        Ok(())
+    }
+
+    fn migrate_to_v24(&self) -> Result<()> {
+        // Symbol cross-reference index: what each code chunk defines and uses
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS symbols (
+                chunk_hash TEXT NOT NULL,
+                name TEXT NOT NULL,
+                qualified_name TEXT NOT NULL,
+                kind TEXT,
+                PRIMARY KEY (chunk_hash, qualified_name)
+            ) WITHOUT ROWID",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_symbols_name ON symbols(name)",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS symbol_refs (
+                chunk_hash TEXT NOT NULL,
+                name TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                line INTEGER NOT NULL,
+                PRIMARY KEY (chunk_hash, name, kind)
+            ) WITHOUT ROWID",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_symbol_refs_name ON symbol_refs(name, kind)",
+            [],
+        )?;
+
+        self.rebuild_symbol_index()?;
+
+        // Update schema version
+        self.conn.execute(
+            "INSERT OR REPLACE INTO schema_version (version) VALUES (?1)",
+            params![24],
+        )?;
+
+        Ok(())
+    }
+
+    fn migrate_to_v25(&self) -> Result<()> {
+        // Day a daily note is about, from its path or frontmatter
+        let has_column: bool = self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('documents') WHERE name = 'note_date'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_column {
+            self.conn
+                .execute("ALTER TABLE documents ADD COLUMN note_date TEXT", [])?;
+        }
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_documents_note_date ON documents(note_date)",
+            [],
+        )?;
+
+        self.backfill_note_dates()?;
+
+        // Update schema version
+        self.conn.execute(
+            "INSERT OR REPLACE INTO schema_version (version) VALUES (?1)",
+            params![25],
+        )?;
+
+        Ok(())
+    }
+
+    fn migrate_to_v26(&self) -> Result<()> {
+        // When a document last came back from a search, kept past session expiry
+        let has_column: bool = self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('documents') WHERE name = 'last_retrieved_at'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_column {
+            self.conn.execute(
+                "ALTER TABLE documents ADD COLUMN last_retrieved_at TEXT",
+                [],
+            )?;
+        }
+        self.conn.execute(
+            "UPDATE documents SET last_retrieved_at = (
+                 SELECT MAX(seen_at) FROM session_seen WHERE document_hash = documents.hash
+             )
+             WHERE last_retrieved_at IS NULL",
+            [],
+        )?;
+
+        // Update schema version
+        self.conn.execute(
+            "INSERT OR REPLACE INTO schema_version (version) VALUES (?1)",
+            params![26],
+        )?;
+
+        Ok(())
+    }
+
+    fn migrate_to_v27(&self) -> Result<()> {
+        // Normalized canonical URL of web documents, for cross-collection dedup
+        let has_column: bool = self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('documents') WHERE name = 'canonical_url'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_column {
+            self.conn
+                .execute("ALTER TABLE documents ADD COLUMN canonical_url TEXT", [])?;
+        }
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_documents_canonical_url
+             ON documents(canonical_url) WHERE canonical_url IS NOT NULL",
+            [],
+        )?;
+
+        // Until refetched, a URL document's best guess is its own URL
+        let urls: Vec<(i64, String)> = self
+            .conn
+            .prepare("SELECT id, path FROM documents WHERE source_type = 'url' AND canonical_url IS NULL")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<std::result::Result<_, _>>()?;
+        for (id, url) in urls {
+            if let Some(canonical) = crate::providers::url::normalize_url(&url) {
+                self.conn.execute(
+                    "UPDATE documents SET canonical_url = ?1 WHERE id = ?2",
+                    params![canonical, id],
+                )?;
+            }
+        }
+
+        // Update schema version
+        self.conn.execute(
+            "INSERT OR REPLACE INTO schema_version (version) VALUES (?1)",
+            params![27],
+        )?;
+
+        Ok(())
+    }
+
+    fn migrate_to_v28(&self) -> Result<()> {
+        // Unstemmed companion of documents_fts for exact-token queries. It is
+        // contentless, so the text is not stored a third time, and covers
+        // only the document's own text, not LLM or user metadata
+        self.conn.execute(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS documents_fts_exact USING fts5(
+                filepath,
+                title,
+                body,
+                collection_key,
+                content='',
+                contentless_delete=1,
+                tokenize='unicode61 remove_diacritics 2'
+            )",
+            [],
+        )?;
+        self.conn.execute("DELETE FROM documents_fts_exact", [])?;
+        self.conn.execute(
+            "INSERT INTO documents_fts_exact(rowid, filepath, title, body, collection_key)
+             SELECT
+                d.id,
+                d.collection || '/' || d.path,
+                d.title,
+                c.doc,
+                'c' || lower(hex(d.collection))
+             FROM documents d
+             JOIN content c ON c.hash = d.hash
+             WHERE d.active = 1",
+            [],
+        )?;
+        self.conn.execute_batch(CREATE_EXACT_TRIGGERS)?;
+
+        // Update schema version
+        self.conn.execute(
+            "INSERT OR REPLACE INTO schema_version (version) VALUES (?1)",
+            params![28],
+        )?;
+
+        Ok(())
+    }
+
+    fn migrate_to_v29(&self) -> Result<()> {
+        // Every content hash a path has had, so old bodies stay retrievable
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS document_versions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                collection TEXT NOT NULL,
+                path TEXT NOT NULL,
+                hash TEXT NOT NULL,
+                title TEXT NOT NULL,
+                recorded_at TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_document_versions_path
+                ON document_versions(collection, path, id);
+            CREATE INDEX IF NOT EXISTS idx_document_versions_hash
+                ON document_versions(hash);",
+        )?;
+        self.conn.execute_batch(CREATE_VERSION_TRIGGERS)?;
+
+        // Until something changes, a document's history is its current content
+        let has_versions: bool =
+            self.conn
+                .query_row("SELECT COUNT(*) > 0 FROM document_versions", [], |row| {
+                    row.get(0)
+                })?;
+        if !has_versions {
+            self.conn.execute(
+                "INSERT INTO document_versions (collection, path, hash, title, recorded_at)
+                 SELECT collection, path, hash, title, modified_at
+                 FROM documents WHERE active = 1 ORDER BY id",
+                [],
+            )?;
+        }
+
+        // Update schema version
+        self.conn.execute(
+            "INSERT OR REPLACE INTO schema_version (version) VALUES (?1)",
+            params![29],
+        )?;
+
+        Ok(())
+    }
+
+    fn migrate_to_v30(&self) -> Result<()> {
+        // Vocabulary words by trigram, for typo-tolerant search
+        self.conn.execute(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS vocabulary_trigrams USING fts5(
+                term,
+                tokenize = 'trigram'
+            )",
+            [],
+        )?;
+        self.sync_vocabulary_trigrams()?;
+
+        // Update schema version
+        self.conn.execute(
+            "INSERT OR REPLACE INTO schema_version (version) VALUES (?1)",
+            params![30],
+        )?;
+
+        Ok(())
+    }
+
+    fn migrate_to_v31(&self) -> Result<()> {
+        // One pair of full-text tables per collection replaces the shared
+        // ones, so removing a collection drops its tables (see fts_partitions)
+        self.conn.execute_batch(
+            "DROP TRIGGER IF EXISTS documents_ai;
+             DROP TRIGGER IF EXISTS documents_au;
+             DROP TRIGGER IF EXISTS documents_ad;
+             DROP TRIGGER IF EXISTS documents_exact_ai;
+             DROP TRIGGER IF EXISTS documents_exact_au;
+             DROP TRIGGER IF EXISTS documents_exact_ad;
+             DROP TABLE IF EXISTS documents_fts;
+             DROP TABLE IF EXISTS documents_fts_exact;",
+        )?;
+
+        let collections: Vec<String> = self
+            .conn
+            .prepare("SELECT name FROM collections")?
+            .query_map([], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        for name in &collections {
+            self.create_fts_partition(name)?;
+        }
+
+        // Update schema version
+        self.conn.execute(
+            "INSERT OR REPLACE INTO schema_version (version) VALUES (?1)",
+            params![31],
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_open_in_memory() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        assert_eq!(db.schema_version().unwrap(), Some(SCHEMA_VERSION));
+    }
+
+    #[test]
+    fn test_nested_transaction_uses_savepoint() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let contexts = |db: &Database| -> i64 {
+            db.conn
+                .query_row("SELECT COUNT(*) FROM contexts", [], |row| row.get(0))
+                .unwrap()
+        };
+
+        db.in_transaction(|| {
+            db.add_context("kept", "outer")?;
+            let inner: Result<()> = db.in_transaction(|| {
+                db.add_context("undone", "inner")?;
+                Err(AgentRootError::InvalidInput("inner failure".to_string()))
+            });
+            assert!(inner.is_err());
+            db.in_transaction(|| db.add_context("nested", "inner"))
+        })
+        .unwrap();
+        assert_eq!(contexts(&db), 2);
+        assert!(db.conn.is_autocommit());
+    }
+
+    #[test]
+    fn test_failed_commit_rolls_back() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+
+        // A deferred foreign key violation only fails at COMMIT
+        let result = db.in_transaction(|| {
+            db.conn.execute_batch("PRAGMA defer_foreign_keys = ON")?;
+            let now = Utc::now().to_rfc3339();
+            db.insert_document("notes", "a.md", "A", "missing", &now, &now, "file", None)?;
+            Ok(())
+        });
+        assert!(result.is_err());
+        assert!(db.conn.is_autocommit());
+        db.in_transaction(|| db.add_context("notes", "after"))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_read_only_rejects_writes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("index.sqlite");
+        assert!(matches!(
+            Database::open_read_only(&path),
+            Err(AgentRootError::ReadOnly(_))
+        ));
+
+        let db = Database::open(&path).unwrap();
+        db.initialize().unwrap();
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+        drop(db);
+
+        let db = Database::open_read_only(&path).unwrap();
+        db.initialize().unwrap();
+        assert!(db.is_read_only());
+        assert_eq!(db.list_collections().unwrap().len(), 1);
+
+        let err = db.remove_collection("notes").unwrap_err();
+        assert!(matches!(err, AgentRootError::ReadOnly(_)), "{:?}", err);
+        let err = db.in_transaction(|| Ok(())).unwrap_err();
+        assert!(err.to_string().starts_with("Read-only index:"));
+        assert_eq!(db.list_collections().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_migration_v2_to_v3() {
+        let db = Database::open_in_memory().unwrap();
+
+        db.conn
+            .execute_batch(
+                "CREATE TABLE collections (
+                name TEXT PRIMARY KEY,
+                path TEXT NOT NULL,
+                pattern TEXT NOT NULL DEFAULT '**/*.md',
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+            CREATE TABLE documents (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                collection TEXT NOT NULL,
+                path TEXT NOT NULL,
+                title TEXT NOT NULL,
+                hash TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                modified_at TEXT NOT NULL,
+                active INTEGER NOT NULL DEFAULT 1,
+                UNIQUE(collection, path)
+            );
+            CREATE TABLE schema_version (version INTEGER PRIMARY KEY);
+            INSERT INTO schema_version VALUES (2);",
+            )
+            .unwrap();
+
+        assert_eq!(db.schema_version().unwrap(), Some(2));
+
+        db.initialize().unwrap();
+
+        assert_eq!(db.schema_version().unwrap(), Some(SCHEMA_VERSION));
+
+        let has_provider_type: bool = db.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('collections') WHERE name = 'provider_type'",
+            [],
+            |row| row.get(0),
+        ).unwrap();
+        assert!(
+            has_provider_type,
+            "collections should have provider_type column"
+        );
+
+        let has_provider_config: bool = db.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('collections') WHERE name = 'provider_config'",
+            [],
+            |row| row.get(0),
+        ).unwrap();
+        assert!(
+            has_provider_config,
+            "collections should have provider_config column"
+        );
+
+        let has_source_type: bool = db.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('documents') WHERE name = 'source_type'",
+            [],
+            |row| row.get(0),
+        ).unwrap();
+        assert!(has_source_type, "documents should have source_type column");
+
+        let has_source_uri: bool = db
+            .conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('documents') WHERE name = 'source_uri'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(has_source_uri, "documents should have source_uri column");
+    }
+
+    #[test]
+    fn test_migration_v3_to_v4() {
+        let db = Database::open_in_memory().unwrap();
+
+        db.conn
+            .execute_batch(
+                "CREATE TABLE collections (
+                name TEXT PRIMARY KEY,
+                path TEXT NOT NULL,
+                pattern TEXT NOT NULL DEFAULT '**/*.md',
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                provider_type TEXT NOT NULL DEFAULT 'file',
+                provider_config TEXT
+            );
+            CREATE TABLE documents (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                collection TEXT NOT NULL,
+                path TEXT NOT NULL,
+                title TEXT NOT NULL,
+                hash TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                modified_at TEXT NOT NULL,
+                active INTEGER NOT NULL DEFAULT 1,
+                source_type TEXT NOT NULL DEFAULT 'file',
+                source_uri TEXT,
+                UNIQUE(collection, path)
+            );
+            CREATE TABLE schema_version (version INTEGER PRIMARY KEY);
+            INSERT INTO schema_version VALUES (3);",
+            )
+            .unwrap();
+
+        assert_eq!(db.schema_version().unwrap(), Some(3));
+
+        db.initialize().unwrap();
+
+        assert_eq!(db.schema_version().unwrap(), Some(SCHEMA_VERSION));
+
+        let metadata_columns = vec![
+            "llm_summary",
+            "llm_title",
+            "llm_keywords",
+            "llm_category",
             "llm_intent",
             "llm_concepts",
             "llm_difficulty",
@@ -1395,7 +2396,7 @@ mod tests {
 
         db.initialize().unwrap();
 
-        assert_eq!(db.schema_version().unwrap(), Some(11));
+        assert_eq!(db.schema_version().unwrap(), Some(SCHEMA_VERSION));
 
         let has_user_metadata: bool = db
             .conn
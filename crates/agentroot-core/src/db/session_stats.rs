@@ -0,0 +1,299 @@
+//! Session analytics
+//!
+//! Summarizes logged session queries to show where search is failing users:
+//! queries that returned nothing, documents that keep coming back, and how
+//! confident the ranking was. Covers sessions that have not yet expired.
+
+use super::content::docid_from_hash;
+use super::Database;
+use crate::error::Result;
+use rusqlite::params;
+use std::collections::HashMap;
+
+/// Aggregate report over logged session queries
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SessionStats {
+    pub session_count: usize,
+    pub query_count: usize,
+    pub zero_result_count: usize,
+    pub avg_queries_per_session: f64,
+    /// Mean of per-query average result scores (queries with results only)
+    pub avg_result_score: Option<f64>,
+    pub sessions: Vec<SessionActivity>,
+    pub zero_result_queries: Vec<ZeroResultQuery>,
+    pub top_documents: Vec<RetrievedDocument>,
+}
+
+/// Query counts for a single session
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SessionActivity {
+    pub id: String,
+    pub name: Option<String>,
+    pub query_count: usize,
+    pub zero_result_count: usize,
+    pub avg_result_score: Option<f64>,
+    pub last_active_at: String,
+}
+
+/// A query that returned no results, grouped by normalized text
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ZeroResultQuery {
+    pub query: String,
+    pub count: usize,
+    pub last_seen: String,
+}
+
+/// A document frequently returned among the top results
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RetrievedDocument {
+    pub docid: String,
+    pub collection: Option<String>,
+    pub path: Option<String>,
+    pub title: Option<String>,
+    pub count: usize,
+}
+
+impl Database {
+    /// Build an analytics report, optionally restricted to one session
+    ///
+    /// `limit` caps the zero-result query and top document lists.
+    pub fn session_stats(&self, session_id: Option<&str>, limit: usize) -> Result<SessionStats> {
+        self.cleanup_expired_sessions()?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT s.id, s.name, s.last_active_at, q.query, q.result_count, q.top_results,
+                    q.avg_score, q.created_at
+             FROM sessions s
+             LEFT JOIN session_queries q ON q.session_id = s.id
+             WHERE ?1 IS NULL OR s.id = ?1
+             ORDER BY s.last_active_at DESC, q.id",
+        )?;
+        let rows = stmt.query_map(params![session_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<i64>>(4)?,
+                row.get::<_, Option<String>>(5)?,
+                row.get::<_, Option<f64>>(6)?,
+                row.get::<_, Option<String>>(7)?,
+            ))
+        })?;
+
+        let mut sessions: Vec<SessionActivity> = Vec::new();
+        let mut session_scores: Vec<Vec<f64>> = Vec::new();
+        let mut all_scores: Vec<f64> = Vec::new();
+        let mut zero: HashMap<String, ZeroResultQuery> = HashMap::new();
+        let mut retrieved: HashMap<String, usize> = HashMap::new();
+        let mut query_count = 0;
+        let mut zero_result_count = 0;
+
+        for row in rows {
+            let (id, name, last_active_at, query, result_count, top_json, avg_score, created_at) =
+                row?;
+
+            if sessions.last().map(|s| &s.id) != Some(&id) {
+                sessions.push(SessionActivity {
+                    id,
+                    name,
+                    query_count: 0,
+                    zero_result_count: 0,
+                    avg_result_score: None,
+                    last_active_at,
+                });
+                session_scores.push(Vec::new());
+            }
+            let (Some(query), Some(result_count)) = (query, result_count) else {
+                continue;
+            };
+            let session = sessions.last_mut().unwrap();
+            session.query_count += 1;
+            query_count += 1;
+
+            if result_count == 0 {
+                session.zero_result_count += 1;
+                zero_result_count += 1;
+                let created_at = created_at.unwrap_or_default();
+                let key = query.trim().to_lowercase();
+                let entry = zero.entry(key).or_insert_with(|| ZeroResultQuery {
+                    query: query.trim().to_string(),
+                    count: 0,
+                    last_seen: created_at.clone(),
+                });
+                entry.count += 1;
+                if created_at > entry.last_seen {
+                    entry.last_seen = created_at;
+                }
+            }
+
+            if let Some(score) = avg_score {
+                session_scores.last_mut().unwrap().push(score);
+                all_scores.push(score);
+            }
+
+            let top: Vec<String> = top_json
+                .and_then(|j| serde_json::from_str(&j).ok())
+                .unwrap_or_default();
+            for hash in top {
+                *retrieved.entry(hash).or_insert(0) += 1;
+            }
+        }
+
+        for (session, scores) in sessions.iter_mut().zip(&session_scores) {
+            session.avg_result_score = mean(scores);
+        }
+
+        let mut zero_result_queries: Vec<ZeroResultQuery> = zero.into_values().collect();
+        zero_result_queries.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| b.last_seen.cmp(&a.last_seen))
+        });
+        zero_result_queries.truncate(limit);
+
+        let mut top: Vec<(String, usize)> = retrieved.into_iter().collect();
+        top.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top.truncate(limit);
+        let top_documents = top
+            .into_iter()
+            .map(|(hash, count)| self.retrieved_document(&hash, count))
+            .collect::<Result<Vec<_>>>()?;
+
+        let session_count = sessions.len();
+        Ok(SessionStats {
+            session_count,
+            query_count,
+            zero_result_count,
+            avg_queries_per_session: if session_count > 0 {
+                query_count as f64 / session_count as f64
+            } else {
+                0.0
+            },
+            avg_result_score: mean(&all_scores),
+            sessions,
+            zero_result_queries,
+            top_documents,
+        })
+    }
+
+    fn retrieved_document(&self, hash: &str, count: usize) -> Result<RetrievedDocument> {
+        let doc = self
+            .conn
+            .query_row(
+                "SELECT collection, path, title FROM documents
+                 WHERE hash = ?1 ORDER BY active DESC LIMIT 1",
+                params![hash],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .ok();
+        let (collection, path, title) = match doc {
+            Some((c, p, t)) => (Some(c), Some(p), Some(t)),
+            None => (None, None, None),
+        };
+        Ok(RetrievedDocument {
+            docid: docid_from_hash(hash),
+            collection,
+            path,
+            title,
+            count,
+        })
+    }
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::{SearchResult, SearchSource};
+
+    fn make_result(hash: &str, score: f64) -> SearchResult {
+        SearchResult {
+            filepath: format!("agentroot://notes/{}.md", hash),
+            display_path: format!("notes/{}.md", hash),
+            title: hash.to_string(),
+            hash: hash.to_string(),
+            collection_name: "notes".to_string(),
+            modified_at: "".to_string(),
+            body: None,
+            body_length: 0,
+            docid: docid_from_hash(hash),
+            context: None,
+            score,
+            source: SearchSource::Bm25,
+            chunk_pos: None,
+            llm_summary: None,
+            llm_title: None,
+            llm_keywords: None,
+            llm_category: None,
+            llm_difficulty: None,
+            user_metadata: None,
+            is_chunk: false,
+            chunk_hash: None,
+            chunk_type: None,
+            chunk_breadcrumb: None,
+            chunk_start_line: None,
+            chunk_end_line: None,
+            chunk_language: None,
+            chunk_summary: None,
+            chunk_purpose: None,
+            chunk_concepts: Vec::new(),
+            chunk_labels: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_session_stats_report() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+
+        let a = db.create_named_session(Some("a"), None).unwrap();
+        let b = db.create_session(None).unwrap();
+
+        db.log_session_query(
+            &a,
+            "oauth",
+            &[make_result("aaa111", 0.8), make_result("bbb222", 0.4)],
+        )
+        .unwrap();
+        db.log_session_query(&a, "Kerberos", &[]).unwrap();
+        db.log_session_query(&b, "kerberos ", &[]).unwrap();
+        db.log_session_query(&b, "tokens", &[make_result("aaa111", 0.2)])
+            .unwrap();
+
+        let stats = db.session_stats(None, 10).unwrap();
+        assert_eq!(stats.session_count, 2);
+        assert_eq!(stats.query_count, 4);
+        assert_eq!(stats.zero_result_count, 2);
+        assert_eq!(stats.avg_queries_per_session, 2.0);
+        assert!((stats.avg_result_score.unwrap() - 0.4).abs() < 1e-9);
+
+        assert_eq!(stats.zero_result_queries.len(), 1);
+        assert_eq!(stats.zero_result_queries[0].count, 2);
+
+        assert_eq!(stats.top_documents[0].docid, docid_from_hash("aaa111"));
+        assert_eq!(stats.top_documents[0].count, 2);
+
+        let only_a = db.session_stats(Some(&a), 10).unwrap();
+        assert_eq!(only_a.session_count, 1);
+        assert_eq!(only_a.sessions[0].name.as_deref(), Some("a"));
+        assert_eq!(only_a.sessions[0].query_count, 2);
+        assert!((only_a.sessions[0].avg_result_score.unwrap() - 0.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_session_stats_empty() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.create_session(None).unwrap();
+
+        let stats = db.session_stats(None, 10).unwrap();
+        assert_eq!(stats.session_count, 1);
+        assert_eq!(stats.query_count, 0);
+        assert!(stats.avg_result_score.is_none());
+        assert!(stats.top_documents.is_empty());
+    }
+}
@@ -1,15 +1,20 @@
 //! Session management for multi-turn agent interactions
 
 use super::Database;
-use crate::error::Result;
+use crate::error::{AgentRootError, Result};
 use crate::search::SearchResult;
 use chrono::Utc;
 use rusqlite::params;
 use std::collections::{HashMap, HashSet};
 
+/// Default session time-to-live in seconds
+pub const DEFAULT_SESSION_TTL: i64 = 3600;
+
+/// A search session; a TTL of zero or less keeps it until ended explicitly
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct SessionInfo {
     pub id: String,
+    pub name: Option<String>,
     pub created_at: String,
     pub last_active_at: String,
     pub ttl_seconds: i64,
@@ -26,40 +31,58 @@ pub struct SessionQuery {
 
 impl Database {
     pub fn create_session(&self, ttl_seconds: Option<i64>) -> Result<String> {
+        self.create_named_session(None, ttl_seconds)
+    }
+
+    /// Create a session, optionally with a unique name it can be resumed by
+    pub fn create_named_session(
+        &self,
+        name: Option<&str>,
+        ttl_seconds: Option<i64>,
+    ) -> Result<String> {
+        // Cleanup expired sessions first so their names can be reused
+        self.cleanup_expired_sessions()?;
+
+        let name = name.map(str::trim).filter(|n| !n.is_empty());
+        if let Some(name) = name {
+            self.ensure_session_name_free(name)?;
+        }
+
         let id = generate_uuid();
         let now = Utc::now().to_rfc3339();
-        let ttl = ttl_seconds.unwrap_or(3600);
+        let ttl = ttl_seconds.unwrap_or(DEFAULT_SESSION_TTL);
 
         self.conn.execute(
-            "INSERT INTO sessions (id, created_at, last_active_at, ttl_seconds, context)
-             VALUES (?1, ?2, ?2, ?3, '{}')",
-            params![id, now, ttl],
+            "INSERT INTO sessions (id, name, created_at, last_active_at, ttl_seconds, context)
+             VALUES (?1, ?2, ?3, ?3, ?4, '{}')",
+            params![id, name, now, ttl],
         )?;
 
-        // Cleanup expired sessions while we're at it
-        self.cleanup_expired_sessions()?;
-
         Ok(id)
     }
 
     pub fn get_session(&self, session_id: &str) -> Result<Option<SessionInfo>> {
         let result = self.conn.query_row(
-            "SELECT id, created_at, last_active_at, ttl_seconds, context
-             FROM sessions WHERE id = ?1",
+            &format!("{} WHERE id = ?1", SESSION_SELECT),
             params![session_id],
-            |row| {
-                let context_json: Option<String> = row.get(4)?;
-                let context = context_json
-                    .and_then(|j| serde_json::from_str::<HashMap<String, String>>(&j).ok())
-                    .unwrap_or_default();
-                Ok(SessionInfo {
-                    id: row.get(0)?,
-                    created_at: row.get(1)?,
-                    last_active_at: row.get(2)?,
-                    ttl_seconds: row.get(3)?,
-                    context,
-                })
-            },
+            row_to_session,
+        );
+        match result {
+            Ok(info) => Ok(Some(info)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Look up a session by id, falling back to its name
+    pub fn find_session(&self, id_or_name: &str) -> Result<Option<SessionInfo>> {
+        if let Some(session) = self.get_session(id_or_name)? {
+            return Ok(Some(session));
+        }
+        let result = self.conn.query_row(
+            &format!("{} WHERE name = ?1", SESSION_SELECT),
+            params![id_or_name],
+            row_to_session,
         );
         match result {
             Ok(info) => Ok(Some(info)),
@@ -68,6 +91,84 @@ impl Database {
         }
     }
 
+    /// List live sessions, most recently active first
+    pub fn list_sessions(&self) -> Result<Vec<SessionInfo>> {
+        self.cleanup_expired_sessions()?;
+
+        let mut stmt = self.conn.prepare(&format!(
+            "{} ORDER BY last_active_at DESC, id",
+            SESSION_SELECT
+        ))?;
+        let sessions = stmt
+            .query_map([], row_to_session)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(sessions)
+    }
+
+    /// Resume a session by id or name, refreshing its activity timestamp
+    pub fn resume_session(&self, id_or_name: &str) -> Result<SessionInfo> {
+        self.cleanup_expired_sessions()?;
+
+        let session = self.find_session(id_or_name)?.ok_or_else(|| {
+            AgentRootError::InvalidInput(format!("Session not found: {}", id_or_name))
+        })?;
+        self.touch_session(&session.id)?;
+
+        Ok(self.get_session(&session.id)?.unwrap_or(session))
+    }
+
+    /// Rename a session, or clear its name with `None`
+    pub fn set_session_name(&self, session_id: &str, name: Option<&str>) -> Result<()> {
+        let name = name.map(str::trim).filter(|n| !n.is_empty());
+        if let Some(name) = name {
+            if let Some(existing) = self.find_session(name)? {
+                if existing.id != session_id {
+                    return Err(AgentRootError::InvalidInput(format!(
+                        "Session name already in use: {}",
+                        name
+                    )));
+                }
+            }
+        }
+
+        let updated = self.conn.execute(
+            "UPDATE sessions SET name = ?2 WHERE id = ?1",
+            params![session_id, name],
+        )?;
+        if updated == 0 {
+            return Err(AgentRootError::InvalidInput(format!(
+                "Session not found: {}",
+                session_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Change a session's TTL; zero or less disables expiry
+    pub fn set_session_ttl(&self, session_id: &str, ttl_seconds: i64) -> Result<()> {
+        let updated = self.conn.execute(
+            "UPDATE sessions SET ttl_seconds = ?2 WHERE id = ?1",
+            params![session_id, ttl_seconds],
+        )?;
+        if updated == 0 {
+            return Err(AgentRootError::InvalidInput(format!(
+                "Session not found: {}",
+                session_id
+            )));
+        }
+        Ok(())
+    }
+
+    fn ensure_session_name_free(&self, name: &str) -> Result<()> {
+        if self.find_session(name)?.is_some() {
+            return Err(AgentRootError::InvalidInput(format!(
+                "Session name already in use: {}",
+                name
+            )));
+        }
+        Ok(())
+    }
+
     pub fn touch_session(&self, session_id: &str) -> Result<()> {
         let now = Utc::now().to_rfc3339();
         self.conn.execute(
@@ -79,7 +180,7 @@ impl Database {
 
     pub fn set_session_context(&self, session_id: &str, key: &str, value: &str) -> Result<()> {
         let session = self.get_session(session_id)?.ok_or_else(|| {
-            crate::error::AgentRootError::InvalidInput(format!("Session not found: {}", session_id))
+            AgentRootError::InvalidInput(format!("Session not found: {}", session_id))
         })?;
 
         let mut context = session.context;
@@ -109,11 +210,21 @@ impl Database {
         let now = Utc::now().to_rfc3339();
         let top_hashes: Vec<String> = results.iter().take(5).map(|r| r.hash.clone()).collect();
         let top_json = serde_json::to_string(&top_hashes)?;
+        let avg_score = (!results.is_empty())
+            .then(|| results.iter().map(|r| r.score).sum::<f64>() / results.len() as f64);
 
         self.conn.execute(
-            "INSERT INTO session_queries (session_id, query, result_count, top_results, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![session_id, query, results.len() as i64, top_json, now],
+            "INSERT INTO session_queries
+             (session_id, query, result_count, top_results, avg_score, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                session_id,
+                query,
+                results.len() as i64,
+                top_json,
+                avg_score,
+                now
+            ],
         )?;
         Ok(())
     }
@@ -158,6 +269,10 @@ impl Database {
              VALUES (?1, ?2, ?3, ?4, ?5)",
             params![session_id, doc_hash, chunk, detail_level, now],
         )?;
+        self.conn.execute(
+            "UPDATE documents SET last_retrieved_at = ?2 WHERE hash = ?1",
+            params![doc_hash, now],
+        )?;
         Ok(())
     }
 
@@ -185,10 +300,12 @@ impl Database {
     }
 
     pub fn cleanup_expired_sessions(&self) -> Result<usize> {
-        // Delete sessions where last_active_at + ttl_seconds < now
+        // Delete sessions where last_active_at + ttl_seconds < now; a
+        // non-positive TTL marks a persistent session
         let now = Utc::now().to_rfc3339();
         let deleted = self.conn.execute(
-            "DELETE FROM sessions WHERE datetime(last_active_at, '+' || ttl_seconds || ' seconds') < datetime(?1)",
+            "DELETE FROM sessions WHERE ttl_seconds > 0
+               AND datetime(last_active_at, '+' || ttl_seconds || ' seconds') < datetime(?1)",
             params![now],
         )?;
         Ok(deleted)
@@ -201,6 +318,24 @@ impl Database {
     }
 }
 
+const SESSION_SELECT: &str =
+    "SELECT id, name, created_at, last_active_at, ttl_seconds, context FROM sessions";
+
+fn row_to_session(row: &rusqlite::Row) -> rusqlite::Result<SessionInfo> {
+    let context_json: Option<String> = row.get(5)?;
+    let context = context_json
+        .and_then(|j| serde_json::from_str::<HashMap<String, String>>(&j).ok())
+        .unwrap_or_default();
+    Ok(SessionInfo {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        created_at: row.get(2)?,
+        last_active_at: row.get(3)?,
+        ttl_seconds: row.get(4)?,
+        context,
+    })
+}
+
 fn generate_uuid() -> String {
     use std::sync::atomic::{AtomicU64, Ordering};
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -292,6 +427,68 @@ mod tests {
         assert_eq!(queries[0].result_count, 0);
     }
 
+    #[test]
+    fn test_named_session_resume() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+
+        let id = db
+            .create_named_session(Some("oauth-research"), Some(0))
+            .unwrap();
+        db.set_session_context(&id, "topic", "oauth").unwrap();
+
+        let resumed = db.resume_session("oauth-research").unwrap();
+        assert_eq!(resumed.id, id);
+        assert_eq!(resumed.name.as_deref(), Some("oauth-research"));
+        assert_eq!(resumed.context.get("topic").unwrap(), "oauth");
+        assert_eq!(db.resume_session(&id).unwrap().id, id);
+
+        // Names are unique
+        assert!(db
+            .create_named_session(Some("oauth-research"), None)
+            .is_err());
+        assert!(db.resume_session("missing").is_err());
+    }
+
+    #[test]
+    fn test_persistent_sessions_survive_cleanup() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+
+        let persistent = db.create_named_session(Some("long"), Some(0)).unwrap();
+        let expiring = db.create_session(Some(60)).unwrap();
+        db.conn
+            .execute(
+                "UPDATE sessions SET last_active_at = '2000-01-01T00:00:00+00:00'",
+                [],
+            )
+            .unwrap();
+
+        assert_eq!(db.cleanup_expired_sessions().unwrap(), 1);
+        assert!(db.get_session(&persistent).unwrap().is_some());
+        assert!(db.get_session(&expiring).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_list_and_rename_sessions() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+
+        let a = db.create_named_session(Some("a"), None).unwrap();
+        let b = db.create_session(None).unwrap();
+        assert_eq!(db.list_sessions().unwrap().len(), 2);
+
+        db.set_session_name(&b, Some("b")).unwrap();
+        assert_eq!(db.find_session("b").unwrap().unwrap().id, b);
+        assert!(db.set_session_name(&b, Some("a")).is_err());
+
+        db.set_session_name(&a, None).unwrap();
+        assert!(db.find_session("a").unwrap().is_none());
+
+        db.set_session_ttl(&a, 0).unwrap();
+        assert_eq!(db.get_session(&a).unwrap().unwrap().ttl_seconds, 0);
+    }
+
     #[test]
     fn test_generate_uuid() {
         let uuid1 = generate_uuid();
@@ -0,0 +1,166 @@
+//! Slow query log
+//!
+//! Searches slower than `AGENTROOT_SLOW_QUERY_MS` (default 1000) are stored
+//! with their options, per-stage timings and result count. Set the variable
+//! to `off` to disable logging, or `0` to record every query.
+
+use super::Database;
+use crate::error::Result;
+use crate::search::SearchOptions;
+use chrono::Utc;
+use rusqlite::params;
+
+/// Threshold used when `AGENTROOT_SLOW_QUERY_MS` is unset
+pub const DEFAULT_SLOW_QUERY_MS: f64 = 1000.0;
+
+/// Rows kept in the log; older entries are pruned on insert
+const MAX_SLOW_QUERIES: i64 = 1000;
+
+/// A logged slow search
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SlowQuery {
+    pub id: i64,
+    /// Search mode, e.g. "bm25", "vector" or "hybrid"
+    pub mode: String,
+    pub query: String,
+    pub options: serde_json::Value,
+    /// Time per stage, in execution order
+    pub timings: Vec<StageTiming>,
+    pub total_ms: f64,
+    pub result_count: usize,
+    pub created_at: String,
+}
+
+/// Time spent in one search stage
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct StageTiming {
+    pub stage: String,
+    pub ms: f64,
+}
+
+/// Current slow query threshold in milliseconds, or None when disabled
+pub fn slow_query_threshold_ms() -> Option<f64> {
+    match std::env::var("AGENTROOT_SLOW_QUERY_MS") {
+        Ok(value) if value.trim().eq_ignore_ascii_case("off") => None,
+        Ok(value) => Some(
+            value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|ms| *ms >= 0.0)
+                .unwrap_or(DEFAULT_SLOW_QUERY_MS),
+        ),
+        Err(_) => Some(DEFAULT_SLOW_QUERY_MS),
+    }
+}
+
+impl Database {
+    /// Store a slow query, pruning the oldest entries beyond the retention cap
+    pub fn record_slow_query(
+        &self,
+        mode: &str,
+        query: &str,
+        options: &SearchOptions,
+        timings: &[(String, f64)],
+        total_ms: f64,
+        result_count: usize,
+    ) -> Result<()> {
+        let timings: Vec<StageTiming> = timings
+            .iter()
+            .map(|(stage, ms)| StageTiming {
+                stage: stage.clone(),
+                ms: *ms,
+            })
+            .collect();
+        let options = serde_json::json!({
+            "limit": options.limit,
+            "min_score": options.min_score,
+            "collection": options.collection,
+            "provider": options.provider,
+            "metadata_filters": options.metadata_filters,
+            "detail": format!("{:?}", options.detail),
+        });
+
+        self.conn.execute(
+            "INSERT INTO slow_queries
+             (mode, query, options, timings, total_ms, result_count, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                mode,
+                query,
+                options.to_string(),
+                serde_json::to_string(&timings)?,
+                total_ms,
+                result_count as i64,
+                Utc::now().to_rfc3339()
+            ],
+        )?;
+        self.conn.execute(
+            "DELETE FROM slow_queries WHERE id <= (SELECT MAX(id) FROM slow_queries) - ?1",
+            params![MAX_SLOW_QUERIES],
+        )?;
+        Ok(())
+    }
+
+    /// Slowest logged queries first
+    pub fn list_slow_queries(&self, limit: usize) -> Result<Vec<SlowQuery>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, mode, query, options, timings, total_ms, result_count, created_at
+             FROM slow_queries ORDER BY total_ms DESC, id DESC LIMIT ?1",
+        )?;
+        let entries = stmt
+            .query_map(params![limit as i64], |row| {
+                let options: String = row.get(3)?;
+                let timings: String = row.get(4)?;
+                Ok(SlowQuery {
+                    id: row.get(0)?,
+                    mode: row.get(1)?,
+                    query: row.get(2)?,
+                    options: serde_json::from_str(&options).unwrap_or_default(),
+                    timings: serde_json::from_str(&timings).unwrap_or_default(),
+                    total_ms: row.get(5)?,
+                    result_count: row.get::<_, i64>(6)? as usize,
+                    created_at: row.get(7)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+
+    /// Delete all logged slow queries, returning how many were removed
+    pub fn clear_slow_queries(&self) -> Result<usize> {
+        Ok(self.conn.execute("DELETE FROM slow_queries", [])?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_list_slow_queries() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+
+        let options = SearchOptions {
+            collection: Some("notes".to_string()),
+            ..Default::default()
+        };
+        let timings = vec![("bm25".to_string(), 12.0), ("rerank".to_string(), 900.0)];
+        db.record_slow_query("hybrid", "oauth", &options, &timings, 912.0, 3)
+            .unwrap();
+        db.record_slow_query("bm25", "\"a*\"", &options, &[], 1500.0, 0)
+            .unwrap();
+
+        let entries = db.list_slow_queries(10).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].mode, "bm25");
+        assert_eq!(entries[1].timings[1].stage, "rerank");
+        assert_eq!(entries[1].timings[1].ms, 900.0);
+        assert_eq!(entries[1].result_count, 3);
+        assert_eq!(entries[1].options["collection"], "notes");
+
+        assert_eq!(db.clear_slow_queries().unwrap(), 2);
+        assert!(db.list_slow_queries(10).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,289 @@
+//! Symbol cross-reference index
+//!
+//! `symbols` holds the name each code chunk defines (from its breadcrumb) and
+//! `symbol_refs` the calls and type names it uses, as found by the AST
+//! chunker. References are stored by bare name, so `Parser::new()` is a call
+//! to `new`; lookups match the last segment of a qualified name.
+
+use super::chunks::strip_stride;
+use super::content::docid_from_hash;
+use super::Database;
+use crate::error::Result;
+use crate::index::ast_chunker::{is_supported, ReferenceKind, SemanticChunk, SemanticChunker};
+use rusqlite::params;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// A chunk that uses a symbol
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SymbolUsage {
+    pub name: String,
+    pub kind: ReferenceKind,
+    /// Breadcrumb of the using chunk, e.g. "Parser::parse"
+    pub caller: Option<String>,
+    pub collection: String,
+    pub path: String,
+    pub docid: String,
+    pub chunk_hash: String,
+    pub language: Option<String>,
+    /// Line of the first use in the chunk
+    pub line: i32,
+    /// Source text of that line
+    pub line_text: String,
+}
+
+/// Last segment of a possibly qualified name
+fn bare_name(name: &str) -> &str {
+    name.rsplit("::")
+        .next()
+        .and_then(|n| n.rsplit('.').next())
+        .unwrap_or(name)
+        .trim()
+}
+
+impl Database {
+    /// Record what a freshly stored chunk defines and references
+    pub(crate) fn store_chunk_symbols(&self, chunk: &SemanticChunk) -> Result<()> {
+        let hash = &chunk.chunk_hash;
+        self.conn
+            .execute("DELETE FROM symbols WHERE chunk_hash = ?1", params![hash])?;
+        self.conn.execute(
+            "DELETE FROM symbol_refs WHERE chunk_hash = ?1",
+            params![hash],
+        )?;
+
+        if let Some(mut qualified) = chunk.metadata.breadcrumb.clone() {
+            if strip_stride(&mut qualified) {
+                self.conn.execute(
+                    "INSERT OR REPLACE INTO symbols (chunk_hash, name, qualified_name, kind)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![
+                        hash,
+                        bare_name(&qualified),
+                        qualified,
+                        format!("{:?}", chunk.chunk_type)
+                    ],
+                )?;
+            }
+        }
+
+        let mut insert = self.conn.prepare(
+            "INSERT OR IGNORE INTO symbol_refs (chunk_hash, name, kind, line)
+             VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        for reference in &chunk.metadata.references {
+            insert.execute(params![
+                hash,
+                reference.name,
+                reference.kind.as_str(),
+                reference.line as i64
+            ])?;
+        }
+        Ok(())
+    }
+
+    /// Drop the symbol rows of a document's chunks
+    pub(crate) fn delete_symbols_for_document(&self, document_hash: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM symbols WHERE chunk_hash IN
+             (SELECT hash FROM chunks WHERE document_hash = ?1)",
+            params![document_hash],
+        )?;
+        self.conn.execute(
+            "DELETE FROM symbol_refs WHERE chunk_hash IN
+             (SELECT hash FROM chunks WHERE document_hash = ?1)",
+            params![document_hash],
+        )?;
+        Ok(())
+    }
+
+    /// Re-derive symbols for every chunked code document
+    ///
+    /// Used when the index predates the symbol tables: documents are parsed
+    /// again and symbols are stored for the chunks that are still current.
+    pub(crate) fn rebuild_symbol_index(&self) -> Result<usize> {
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT d.hash, d.path, c.doc
+             FROM documents d
+             JOIN content c ON c.hash = d.hash
+             WHERE d.active = 1
+               AND EXISTS (SELECT 1 FROM chunks ch WHERE ch.document_hash = d.hash)",
+        )?;
+        let documents: Vec<(String, String, String)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<std::result::Result<_, _>>()?;
+
+        let chunker = SemanticChunker::new();
+        let mut indexed = 0;
+        for (doc_hash, path, body) in documents {
+            if !is_supported(Path::new(&path)) {
+                continue;
+            }
+            let stored: HashSet<String> = self
+                .conn
+                .prepare("SELECT hash FROM chunks WHERE document_hash = ?1")?
+                .query_map(params![doc_hash], |row| row.get(0))?
+                .collect::<std::result::Result<_, _>>()?;
+            for chunk in chunker.chunk(&body, Path::new(&path))? {
+                if stored.contains(&chunk.chunk_hash) {
+                    self.store_chunk_symbols(&chunk)?;
+                    indexed += 1;
+                }
+            }
+        }
+        Ok(indexed)
+    }
+
+    /// Chunks in active documents that call or name a symbol
+    pub fn find_references(
+        &self,
+        symbol: &str,
+        kind: Option<ReferenceKind>,
+        collection: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<SymbolUsage>> {
+        let name = bare_name(symbol);
+        if name.is_empty() || limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT r.name, r.kind, r.line, c.breadcrumb, d.collection, d.path, d.hash,
+                    c.hash, c.language, c.start_line, c.content
+             FROM symbol_refs r
+             JOIN chunks c ON c.hash = r.chunk_hash
+             JOIN documents d ON d.hash = c.document_hash AND d.active = 1
+             WHERE r.name = ?1
+               AND (?2 IS NULL OR r.kind = ?2)
+               AND (?3 IS NULL OR d.collection = ?3)
+             ORDER BY d.collection, d.path, r.line
+             LIMIT ?4",
+        )?;
+        let rows = stmt.query_map(
+            params![name, kind.map(|k| k.as_str()), collection, limit as i64],
+            |row| {
+                let kind: String = row.get(1)?;
+                let line: i32 = row.get(2)?;
+                let start_line: Option<i32> = row.get(9)?;
+                let content: String = row.get(10)?;
+                let offset = (line - start_line.unwrap_or(1)).max(0) as usize;
+                // Every stride of a split chunk belongs to the same caller
+                let caller = row.get::<_, Option<String>>(3)?.map(|mut name| {
+                    if let Some(open) = name.rfind('[').filter(|_| name.ends_with(']')) {
+                        name.truncate(open);
+                    }
+                    name
+                });
+                Ok(SymbolUsage {
+                    name: row.get(0)?,
+                    kind: ReferenceKind::parse(&kind).unwrap_or(ReferenceKind::Call),
+                    caller,
+                    collection: row.get(4)?,
+                    path: row.get(5)?,
+                    docid: docid_from_hash(&row.get::<_, String>(6)?),
+                    chunk_hash: row.get(7)?,
+                    language: row.get(8)?,
+                    line,
+                    line_text: content
+                        .lines()
+                        .nth(offset)
+                        .unwrap_or_default()
+                        .trim()
+                        .to_string(),
+                })
+            },
+        )?;
+
+        Ok(rows.collect::<std::result::Result<_, _>>()?)
+    }
+
+    /// How many other active chunks reference what each chunk defines
+    ///
+    /// Chunks that define nothing, or nothing referenced, are left out.
+    pub fn reference_counts(&self, chunk_hashes: &[&str]) -> Result<HashMap<String, usize>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT COUNT(DISTINCT r.chunk_hash)
+             FROM symbols s
+             JOIN symbol_refs r ON r.name = s.name AND r.chunk_hash != s.chunk_hash
+             JOIN chunks c ON c.hash = r.chunk_hash
+             JOIN documents d ON d.hash = c.document_hash AND d.active = 1
+             WHERE s.chunk_hash = ?1",
+        )?;
+
+        let mut counts = HashMap::new();
+        for hash in chunk_hashes {
+            let count: i64 = stmt.query_row(params![hash], |row| row.get(0))?;
+            if count > 0 {
+                counts.insert(hash.to_string(), count as usize);
+            }
+        }
+        Ok(counts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_find_references_and_counts() {
+        let src = tempfile::tempdir().unwrap();
+        std::fs::write(
+            src.path().join("lib.rs"),
+            "pub fn tokenize(s: &str) -> Vec<Token> {\n    Vec::new()\n}\n\npub struct Token;\n\npub fn parse(s: &str) -> Vec<Token> {\n    let tokens = tokenize(s);\n    tokens\n}\n\npub fn lex(s: &str) {\n    tokenize(s);\n}\n",
+        )
+        .unwrap();
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.add_collection(
+            "code",
+            src.path().to_str().unwrap(),
+            "**/*.rs",
+            "file",
+            None,
+        )
+        .unwrap();
+        db.reindex_collection_with_metadata("code", None)
+            .await
+            .unwrap();
+
+        let callers = db
+            .find_references("tokenize", Some(ReferenceKind::Call), None, 10)
+            .unwrap();
+        let names: Vec<_> = callers.iter().map(|u| u.caller.as_deref()).collect();
+        assert_eq!(names, vec![Some("parse"), Some("lex")]);
+        assert_eq!(callers[0].line, 8);
+        assert_eq!(callers[0].line_text, "let tokens = tokenize(s);");
+
+        // Qualified names match on their last segment
+        assert_eq!(
+            db.find_references("lexer::tokenize", None, None, 10)
+                .unwrap()
+                .len(),
+            2
+        );
+        let token_users = db
+            .find_references("Token", Some(ReferenceKind::Type), Some("code"), 10)
+            .unwrap();
+        assert_eq!(token_users.len(), 2);
+
+        let tokenize = db.find_definitions("tokenize", None, 1).unwrap();
+        let token = db.find_definitions("Token", None, 1).unwrap();
+        let counts = db
+            .reference_counts(&[&tokenize[0].chunk_hash, &token[0].chunk_hash])
+            .unwrap();
+        assert_eq!(counts.get(&tokenize[0].chunk_hash), Some(&2));
+        // tokenize and parse both return Vec<Token>
+        assert_eq!(counts.get(&token[0].chunk_hash), Some(&2));
+
+        // Rebuilding from stored documents gives the same index
+        db.conn.execute("DELETE FROM symbol_refs", []).unwrap();
+        assert!(db.rebuild_symbol_index().unwrap() >= 4);
+        assert_eq!(
+            db.find_references("tokenize", None, None, 10)
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+}
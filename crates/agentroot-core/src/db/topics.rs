@@ -0,0 +1,363 @@
+//! Topic clusters over the document graph
+
+use super::content::docid_from_hash;
+use super::vectors::cosine_similarity;
+use super::Database;
+use crate::error::{AgentRootError, Result};
+use crate::graph::detect_communities;
+use chrono::Utc;
+use rusqlite::params;
+use std::collections::HashMap;
+
+/// Edge weight for a direct link between documents
+const LINK_EDGE_WEIGHT: f64 = 1.0;
+/// Edge weight per shared concept (capped at `LINK_EDGE_WEIGHT`)
+const CONCEPT_EDGE_WEIGHT: f64 = 0.25;
+/// Concepts spread over more documents than this are too generic to cluster on
+const MAX_CONCEPT_DOCUMENTS: usize = 50;
+/// Embedding neighbors considered per document
+const EMBEDDING_NEIGHBORS: usize = 5;
+/// Minimum centroid similarity for an embedding edge
+const MIN_EMBEDDING_SIMILARITY: f32 = 0.8;
+/// Smallest community stored as a topic
+const MIN_TOPIC_SIZE: usize = 2;
+/// Keywords kept per topic
+const TOPIC_KEYWORDS: usize = 5;
+
+/// A detected topic cluster
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TopicInfo {
+    pub id: i64,
+    pub label: String,
+    pub keywords: Vec<String>,
+    pub size: usize,
+}
+
+/// A document belonging to a topic
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TopicMember {
+    pub docid: String,
+    pub collection: String,
+    pub path: String,
+    pub title: String,
+    pub importance: f64,
+}
+
+impl Database {
+    /// Detect topic clusters and store them, replacing previous topics
+    ///
+    /// Clusters come from label propagation over a graph combining document
+    /// links, shared glossary concepts, and chunk-embedding centroid similarity.
+    /// Each topic gets a keyword label; use `set_topic_label` to replace it.
+    pub fn build_topics(&self) -> Result<Vec<TopicInfo>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, hash FROM documents WHERE active = 1 ORDER BY id")?;
+        let docs: Vec<(i64, String)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let nodes: Vec<i64> = docs.iter().map(|(id, _)| *id).collect();
+
+        let mut edges = self.link_edges()?;
+        edges.extend(self.concept_edges()?);
+        if self.has_vector_index() {
+            edges.extend(self.embedding_edges(&docs)?);
+        }
+
+        let labels = detect_communities(&nodes, &edges);
+        let mut communities: HashMap<i64, Vec<i64>> = HashMap::new();
+        for (node, label) in labels {
+            communities.entry(label).or_default().push(node);
+        }
+        let mut communities: Vec<Vec<i64>> = communities
+            .into_values()
+            .filter(|members| members.len() >= MIN_TOPIC_SIZE)
+            .collect();
+        for members in &mut communities {
+            members.sort_unstable();
+        }
+        communities.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a[0].cmp(&b[0])));
+
+        let now = Utc::now().to_rfc3339();
+        self.in_transaction(|| {
+            self.conn
+                .execute("UPDATE documents SET topic_id = NULL", [])?;
+            self.conn.execute("DELETE FROM topics", [])?;
+
+            let mut topics = Vec::new();
+            for (i, members) in communities.iter().enumerate() {
+                let topic_id = i as i64 + 1;
+                for doc_id in members {
+                    self.conn.execute(
+                        "UPDATE documents SET topic_id = ?1 WHERE id = ?2",
+                        params![topic_id, doc_id],
+                    )?;
+                }
+
+                let keywords = self.topic_keywords(topic_id)?;
+                let label = if keywords.is_empty() {
+                    self.topic_fallback_label(topic_id)?
+                } else {
+                    keywords
+                        .iter()
+                        .take(3)
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+
+                self.conn.execute(
+                    "INSERT INTO topics (id, label, keywords, size, created_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        topic_id,
+                        label,
+                        serde_json::to_string(&keywords)?,
+                        members.len(),
+                        now
+                    ],
+                )?;
+
+                topics.push(TopicInfo {
+                    id: topic_id,
+                    label,
+                    keywords,
+                    size: members.len(),
+                });
+            }
+            Ok(topics)
+        })
+    }
+
+    /// List stored topics, largest first
+    pub fn list_topics(&self) -> Result<Vec<TopicInfo>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, label, keywords, size FROM topics ORDER BY size DESC, id")?;
+        let topics = stmt
+            .query_map([], |row| {
+                let keywords: Option<String> = row.get(2)?;
+                Ok(TopicInfo {
+                    id: row.get(0)?,
+                    label: row.get(1)?,
+                    keywords: keywords
+                        .and_then(|k| serde_json::from_str(&k).ok())
+                        .unwrap_or_default(),
+                    size: row.get::<_, i64>(3)? as usize,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(topics)
+    }
+
+    /// Get the documents in a topic, most important first
+    pub fn get_topic_documents(&self, topic_id: i64, limit: usize) -> Result<Vec<TopicMember>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT hash, collection, path, title, COALESCE(importance_score, 1.0)
+             FROM documents
+             WHERE topic_id = ?1 AND active = 1
+             ORDER BY importance_score DESC, path
+             LIMIT ?2",
+        )?;
+        let members = stmt
+            .query_map(params![topic_id, limit as i64], |row| {
+                Ok(TopicMember {
+                    docid: docid_from_hash(&row.get::<_, String>(0)?),
+                    collection: row.get(1)?,
+                    path: row.get(2)?,
+                    title: row.get(3)?,
+                    importance: row.get(4)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(members)
+    }
+
+    /// Replace a topic's label (e.g. with an LLM-generated one)
+    pub fn set_topic_label(&self, topic_id: i64, label: &str) -> Result<()> {
+        let updated = self.conn.execute(
+            "UPDATE topics SET label = ?1 WHERE id = ?2",
+            params![label, topic_id],
+        )?;
+        if updated == 0 {
+            return Err(AgentRootError::InvalidInput(format!(
+                "Topic not found: {}",
+                topic_id
+            )));
+        }
+        Ok(())
+    }
+
+    fn link_edges(&self) -> Result<Vec<(i64, i64, f64)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT source_id, target_id FROM document_links")?;
+        let edges = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, LINK_EDGE_WEIGHT)))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(edges)
+    }
+
+    fn concept_edges(&self) -> Result<Vec<(i64, i64, f64)>> {
+        let mut stmt = self.conn.prepare(
+            "WITH doc_concepts AS (
+                 SELECT DISTINCT d.id AS doc_id, cc.concept_id
+                 FROM concept_chunks cc
+                 JOIN documents d ON d.hash = cc.document_hash AND d.active = 1
+                 WHERE cc.concept_id IN (
+                     SELECT concept_id FROM concept_chunks
+                     GROUP BY concept_id
+                     HAVING COUNT(DISTINCT document_hash) <= ?1
+                 )
+             )
+             SELECT a.doc_id, b.doc_id, COUNT(*)
+             FROM doc_concepts a
+             JOIN doc_concepts b ON a.concept_id = b.concept_id AND a.doc_id < b.doc_id
+             GROUP BY a.doc_id, b.doc_id",
+        )?;
+        let edges = stmt
+            .query_map(params![MAX_CONCEPT_DOCUMENTS as i64], |row| {
+                let shared: i64 = row.get(2)?;
+                let weight = (shared as f64 * CONCEPT_EDGE_WEIGHT).min(LINK_EDGE_WEIGHT);
+                Ok((row.get(0)?, row.get(1)?, weight))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(edges)
+    }
+
+    fn embedding_edges(&self, docs: &[(i64, String)]) -> Result<Vec<(i64, i64, f64)>> {
+        let centroids = self.document_centroids()?;
+        let embedded: Vec<(i64, &Vec<f32>)> = docs
+            .iter()
+            .filter_map(|(id, hash)| centroids.get(hash).map(|c| (*id, c)))
+            .collect();
+
+        let mut edges = Vec::new();
+        for (i, (id, centroid)) in embedded.iter().enumerate() {
+            let mut neighbors: Vec<(i64, f32)> = embedded
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, (other, c))| (*other, cosine_similarity(centroid, c)))
+                .filter(|(_, sim)| *sim >= MIN_EMBEDDING_SIMILARITY)
+                .collect();
+            neighbors.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+            for (other, sim) in neighbors.into_iter().take(EMBEDDING_NEIGHBORS) {
+                edges.push((*id, other, sim as f64 * 0.5));
+            }
+        }
+        Ok(edges)
+    }
+
+    /// Most common concepts among a topic's documents
+    fn topic_keywords(&self, topic_id: i64) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT c.term
+             FROM concepts c
+             JOIN concept_chunks cc ON cc.concept_id = c.id
+             JOIN documents d ON d.hash = cc.document_hash AND d.active = 1
+             WHERE d.topic_id = ?1
+             GROUP BY c.id
+             ORDER BY COUNT(DISTINCT d.id) DESC, c.term
+             LIMIT ?2",
+        )?;
+        let keywords = stmt
+            .query_map(params![topic_id, TOPIC_KEYWORDS as i64], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(keywords)
+    }
+
+    /// Title of the most important document in a topic
+    fn topic_fallback_label(&self, topic_id: i64) -> Result<String> {
+        let title: String = self.conn.query_row(
+            "SELECT title FROM documents
+             WHERE topic_id = ?1 AND active = 1
+             ORDER BY importance_score DESC, path
+             LIMIT 1",
+            params![topic_id],
+            |row| row.get(0),
+        )?;
+        Ok(title)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Database {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+        for (hash, path, body) in [
+            ("aaa111", "auth/login.md", "See [jwt](jwt.md)"),
+            ("bbb222", "auth/jwt.md", "Tokens"),
+            ("ccc333", "db/schema.md", "See [queries](queries.md)"),
+            ("ddd444", "db/queries.md", "SQL"),
+            ("eee555", "misc.md", "Alone"),
+        ] {
+            db.insert_content(hash, body).unwrap();
+            db.insert_document("notes", path, path, hash, &now, &now, "file", None)
+                .unwrap();
+        }
+        db.build_link_graph().unwrap();
+
+        for (term, hash) in [("oauth", "aaa111"), ("oauth", "bbb222"), ("sql", "ccc333")] {
+            let id = db.upsert_concept(term).unwrap();
+            db.link_concept_to_chunk(id, &format!("{}-c", hash), hash, "")
+                .unwrap();
+        }
+        db
+    }
+
+    #[test]
+    fn test_build_topics() {
+        let db = setup_db();
+
+        let topics = db.build_topics().unwrap();
+        assert_eq!(topics.len(), 2);
+        assert!(topics.iter().all(|t| t.size == 2));
+
+        let auth = topics.iter().find(|t| t.label == "oauth").unwrap();
+        let members: Vec<String> = db
+            .get_topic_documents(auth.id, 10)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.path)
+            .collect();
+        assert_eq!(members, vec!["auth/jwt.md", "auth/login.md"]);
+
+        // Singletons are not stored as topics
+        let topic_id: Option<i64> = db
+            .conn
+            .query_row(
+                "SELECT topic_id FROM documents WHERE path = 'misc.md'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(topic_id.is_none());
+
+        assert_eq!(db.list_topics().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_rebuild_and_relabel_topics() {
+        let db = setup_db();
+        db.build_topics().unwrap();
+        let topics = db.build_topics().unwrap();
+        assert_eq!(topics.len(), 2);
+
+        db.set_topic_label(topics[0].id, "Authentication").unwrap();
+        assert!(db
+            .list_topics()
+            .unwrap()
+            .iter()
+            .any(|t| t.label == "Authentication"));
+        assert!(db.set_topic_label(999, "Nope").is_err());
+    }
+}
@@ -142,12 +142,12 @@ impl Database {
             "SELECT d.id, c.hash, d.user_metadata 
              FROM documents d 
              JOIN content c ON c.hash = d.hash 
-             WHERE d.active = 1 AND d.user_metadata IS NOT NULL 
-             LIMIT ?1",
+             WHERE d.active = 1 AND d.user_metadata IS NOT NULL",
         )?;
 
+        // The filter runs in Rust, so the limit applies to matches, not rows scanned
         let docids: Vec<String> = stmt
-            .query_map(params![limit], |row| {
+            .query_map([], |row| {
                 let hash: String = row.get(1)?;
                 let metadata_json: Option<String> = row.get(2)?;
 
@@ -161,6 +161,7 @@ impl Database {
                 Ok(None)
             })?
             .filter_map(|r| r.ok().flatten())
+            .take(limit)
             .collect();
 
         Ok(docids)
@@ -312,5 +313,9 @@ mod tests {
         let results = db.find_by_metadata(&filter, 10).unwrap();
 
         assert_eq!(results.len(), 2); // hash2_abcdef and hash3_abcdef
+
+        // The limit counts matches, not the documents scanned to find them
+        let filter = MetadataFilter::IntegerGt("score".to_string(), 2);
+        assert_eq!(db.find_by_metadata(&filter, 1).unwrap(), vec!["#hash3_"]);
     }
 }
@@ -0,0 +1,413 @@
+//! Streaming nearest-neighbour scan over stored embeddings
+//!
+//! Embeddings are decoded one row at a time into a reused buffer and scored
+//! against the query, keeping only the best `k` candidates, so a query needs
+//! O(k) memory however many vectors are stored. Collection and provider
+//! filters are applied in SQL before any distance is computed: filtered scans
+//! start from the matching documents and look their vectors up by key, so a
+//! collection-scoped search only reads that collection's embeddings.
+//!
+//! When quantized vectors are scanned, a wider pool of candidates is kept and
+//! rescored against their full-precision embeddings before the top `k` are
+//! taken.
+
+use super::vectors::decode_embedding_into;
+use super::Database;
+use crate::error::Result;
+use crate::search::SearchOptions;
+use rusqlite::types::ValueRef;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+/// Memory ceiling used when `AGENTROOT_VECTOR_MEMORY_MB` is unset
+pub const DEFAULT_VECTOR_MEMORY_MB: usize = 256;
+
+/// Candidates kept per result for rescoring quantized scans
+const RESCORE_FACTOR: usize = 4;
+
+/// Pre-filters applied before distance computation
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VectorFilter<'a> {
+    pub collection: Option<&'a str>,
+    /// Collection provider type, e.g. "file" or "github"
+    pub provider: Option<&'a str>,
+}
+
+impl<'a> VectorFilter<'a> {
+    pub fn from_options(options: &'a SearchOptions) -> Self {
+        Self {
+            collection: options.collection.as_deref(),
+            provider: options.provider.as_deref(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.collection.is_none() && self.provider.is_none()
+    }
+
+    /// Distinct content hashes of active documents passing the filter
+    fn document_hashes(&self, params: &mut Vec<String>) -> String {
+        let mut sql = String::from(
+            "SELECT DISTINCT d.hash FROM documents d
+             JOIN collections coll ON coll.name = d.collection
+             WHERE d.active = 1",
+        );
+        self.apply(&mut sql, params);
+        sql
+    }
+
+    /// Append WHERE clauses for `documents d` / `collections coll` aliases
+    fn apply(&self, sql: &mut String, params: &mut Vec<String>) {
+        if let Some(collection) = self.collection {
+            params.push(collection.to_string());
+            sql.push_str(&format!(" AND d.collection = ?{}", params.len()));
+        }
+        if let Some(provider) = self.provider {
+            params.push(provider.to_string());
+            sql.push_str(&format!(" AND coll.provider_type = ?{}", params.len()));
+        }
+    }
+}
+
+/// Bytes of embedding data vector search may hold in memory at once
+///
+/// Read from `AGENTROOT_VECTOR_MEMORY_MB`. Streaming scans stay far below it;
+/// in-memory structures such as the ANN index are not built when their
+/// estimated size exceeds it.
+pub fn vector_memory_limit_bytes() -> usize {
+    std::env::var("AGENTROOT_VECTOR_MEMORY_MB")
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .unwrap_or(DEFAULT_VECTOR_MEMORY_MB)
+        .saturating_mul(1024 * 1024)
+}
+
+impl Database {
+    /// Best `k` document embeddings by cosine similarity, as (hash_seq, score)
+    pub fn top_k_embeddings(
+        &self,
+        query: &[f32],
+        k: usize,
+        filter: VectorFilter<'_>,
+    ) -> Result<Vec<(String, f32)>> {
+        if !filter.is_empty() {
+            let mut params = Vec::new();
+            let sql = format!(
+                "SELECT e.hash_seq, e.embedding
+                 FROM ({}) h
+                 CROSS JOIN content_vectors cv ON cv.hash = h.hash
+                 CROSS JOIN embeddings e ON e.hash_seq = cv.hash || '_' || cv.seq",
+                filter.document_hashes(&mut params)
+            );
+            return self.scan_top_k(&sql, &params, query, k);
+        }
+
+        let mut sql = String::from(
+            "SELECT e.hash_seq, e.embedding
+             FROM embeddings e
+             JOIN content_vectors cv ON e.hash_seq = cv.hash || '_' || cv.seq
+             WHERE EXISTS (
+                 SELECT 1 FROM documents d
+                 JOIN collections coll ON coll.name = d.collection
+                 WHERE d.hash = cv.hash AND d.active = 1",
+        );
+        let mut params = Vec::new();
+        filter.apply(&mut sql, &mut params);
+        sql.push(')');
+
+        self.scan_top_k(&sql, &params, query, k)
+    }
+
+    /// Best `k` chunk embeddings for `model` by cosine similarity, as (chunk_hash, score)
+    pub fn top_k_chunk_embeddings(
+        &self,
+        query: &[f32],
+        k: usize,
+        model: &str,
+        filter: VectorFilter<'_>,
+    ) -> Result<Vec<(String, f32)>> {
+        if !filter.is_empty() {
+            let mut params = vec![model.to_string()];
+            let sql = format!(
+                "SELECT ce.chunk_hash, ce.embedding
+                 FROM ({}) h
+                 CROSS JOIN chunks ch ON ch.document_hash = h.hash
+                 CROSS JOIN chunk_embeddings ce ON ce.chunk_hash = ch.hash AND ce.model = ?1",
+                filter.document_hashes(&mut params)
+            );
+            return self.scan_top_k(&sql, &params, query, k);
+        }
+
+        let mut sql = String::from(
+            "SELECT ce.chunk_hash, ce.embedding
+             FROM chunk_embeddings ce
+             WHERE ce.model = ?1 AND EXISTS (
+                 SELECT 1 FROM chunks ch
+                 JOIN documents d ON d.hash = ch.document_hash
+                 JOIN collections coll ON coll.name = d.collection
+                 WHERE ch.hash = ce.chunk_hash AND d.active = 1",
+        );
+        let mut params = vec![model.to_string()];
+        filter.apply(&mut sql, &mut params);
+        sql.push(')');
+
+        self.scan_top_k(&sql, &params, query, k)
+    }
+
+    /// Number of document embeddings and their total size in bytes
+    pub fn embedding_footprint(&self, collection: Option<&str>) -> Result<(usize, usize)> {
+        let (count, bytes): (i64, i64) = self.conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(LENGTH(e.embedding)), 0)
+             FROM embeddings e
+             WHERE ?1 IS NULL OR EXISTS (
+                 SELECT 1 FROM content_vectors cv
+                 JOIN documents d ON d.hash = cv.hash AND d.active = 1
+                 WHERE e.hash_seq = cv.hash || '_' || cv.seq AND d.collection = ?1
+             )",
+            [collection],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        Ok((count as usize, bytes as usize))
+    }
+
+    fn scan_top_k(
+        &self,
+        sql: &str,
+        params: &[String],
+        query: &[f32],
+        k: usize,
+    ) -> Result<Vec<(String, f32)>> {
+        let query_norm = norm(query);
+        if k == 0 || query_norm == 0.0 {
+            return Ok(Vec::new());
+        }
+
+        let pool = k.saturating_mul(RESCORE_FACTOR);
+        let mut stmt = self.conn.prepare(sql)?;
+        let mut rows = stmt.query(rusqlite::params_from_iter(params))?;
+        let mut heap: BinaryHeap<Reverse<Candidate>> = BinaryHeap::with_capacity(k + 1);
+        let mut buffer: Vec<f32> = Vec::with_capacity(query.len());
+        let mut quantized = false;
+
+        while let Some(row) = rows.next()? {
+            let ValueRef::Blob(bytes) = row.get_ref(1)? else {
+                continue;
+            };
+            quantized |= decode_embedding_into(bytes, &mut buffer);
+            if buffer.len() != query.len() {
+                continue;
+            }
+
+            let score = cosine_with_query_norm(query, query_norm, &buffer);
+            let capacity = if quantized { pool } else { k };
+            if heap.len() >= capacity
+                && heap
+                    .peek()
+                    .is_some_and(|Reverse(worst)| score <= worst.score)
+            {
+                continue;
+            }
+            // Identical chunks shared by several documents are scanned once per document
+            let key: String = row.get(0)?;
+            if heap.iter().any(|Reverse(c)| c.key == key) {
+                continue;
+            }
+            if heap.len() >= capacity {
+                heap.pop();
+            }
+            heap.push(Reverse(Candidate { score, key }));
+        }
+
+        let mut top: Vec<(String, f32)> = heap
+            .into_iter()
+            .map(|Reverse(c)| (c.key, c.score))
+            .collect();
+        if quantized {
+            for (key, score) in top.iter_mut() {
+                if let Some(full) = self.full_precision_embedding(key)? {
+                    if full.len() == query.len() {
+                        *score = cosine_with_query_norm(query, query_norm, &full);
+                    }
+                }
+            }
+        }
+        top.sort_by(|a, b| b.1.total_cmp(&a.1));
+        top.truncate(k);
+        Ok(top)
+    }
+}
+
+struct Candidate {
+    score: f32,
+    key: String,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score.total_cmp(&other.score)
+    }
+}
+
+fn norm(v: &[f32]) -> f32 {
+    v.iter().map(|x| x * x).sum::<f32>().sqrt()
+}
+
+fn cosine_with_query_norm(query: &[f32], query_norm: f32, other: &[f32]) -> f32 {
+    let other_norm = norm(other);
+    if other_norm == 0.0 {
+        return 0.0;
+    }
+    let dot: f32 = query.iter().zip(other).map(|(a, b)| a * b).sum();
+    dot / (query_norm * other_norm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn setup_db() -> Database {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.ensure_vec_table(3).unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+        db.add_collection("repo", "owner/repo", "**/*.md", "github", None)
+            .unwrap();
+        for (coll, hash, embedding) in [
+            ("notes", "aaa111", [1.0, 0.0, 0.0]),
+            ("notes", "bbb222", [0.7, 0.7, 0.0]),
+            ("repo", "ccc333", [0.9, 0.1, 0.0]),
+            ("repo", "ddd444", [0.0, 0.0, 1.0]),
+        ] {
+            db.insert_content(hash, hash).unwrap();
+            db.insert_document(coll, hash, hash, hash, &now, &now, "file", None)
+                .unwrap();
+            db.insert_embedding(hash, 0, 0, "test", &embedding).unwrap();
+        }
+        db
+    }
+
+    #[test]
+    fn test_top_k_matches_brute_force() {
+        let db = setup_db();
+        let query = [1.0, 0.05, 0.0];
+
+        let top = db
+            .top_k_embeddings(&query, 2, VectorFilter::default())
+            .unwrap();
+        let keys: Vec<&str> = top.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["aaa111_0", "ccc333_0"]);
+
+        let mut brute: Vec<(String, f32)> = db
+            .get_all_embeddings()
+            .unwrap()
+            .into_iter()
+            .map(|(k, e)| (k, crate::db::vectors::cosine_similarity(&query, &e)))
+            .collect();
+        brute.sort_by(|a, b| b.1.total_cmp(&a.1));
+        assert!((top[0].1 - brute[0].1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_top_k_prefilters() {
+        let db = setup_db();
+        let query = [1.0, 0.0, 0.0];
+
+        let notes = db
+            .top_k_embeddings(
+                &query,
+                10,
+                VectorFilter {
+                    collection: Some("notes"),
+                    provider: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(notes.len(), 2);
+
+        let github = db
+            .top_k_embeddings(
+                &query,
+                10,
+                VectorFilter {
+                    collection: None,
+                    provider: Some("github"),
+                },
+            )
+            .unwrap();
+        assert_eq!(github[0].0, "ccc333_0");
+        assert_eq!(github.len(), 2);
+
+        let (count, bytes) = db.embedding_footprint(Some("repo")).unwrap();
+        assert_eq!((count, bytes), (2, 24));
+    }
+
+    #[test]
+    fn test_collection_scan_dedupes_shared_content() {
+        let db = setup_db();
+        let now = Utc::now().to_rfc3339();
+        // Same content indexed twice in one collection
+        db.insert_document(
+            "notes", "copy.md", "copy", "aaa111", &now, &now, "file", None,
+        )
+        .unwrap();
+
+        let top = db
+            .top_k_embeddings(
+                &[1.0, 0.0, 0.0],
+                10,
+                VectorFilter {
+                    collection: Some("notes"),
+                    provider: None,
+                },
+            )
+            .unwrap();
+        let keys: Vec<&str> = top.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["aaa111_0", "bbb222_0"]);
+    }
+
+    #[test]
+    fn test_quantized_scan_rescores_with_full_precision() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.ensure_vec_table(3).unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+        for (hash, embedding) in [("aaa111", [1.0, 0.0, 0.0]), ("bbb222", [0.6, 0.8, 0.0])] {
+            db.insert_content(hash, hash).unwrap();
+            db.insert_document("notes", hash, hash, hash, &now, &now, "file", None)
+                .unwrap();
+            db.insert_chunk_embedding(hash, 0, 0, &format!("chunk-{}", hash), "test", &embedding)
+                .unwrap();
+        }
+        db.quantize_stored_embeddings(crate::db::Quantization::Binary)
+            .unwrap();
+
+        // Sign bits alone favour bbb222; the exact rescore restores aaa111
+        let query = [1.0, 0.2, 0.0];
+        let top = db
+            .top_k_embeddings(&query, 1, VectorFilter::default())
+            .unwrap();
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].0, "aaa111_0");
+        let exact = crate::db::vectors::cosine_similarity(&query, &[1.0, 0.0, 0.0]);
+        assert!((top[0].1 - exact).abs() < 1e-6);
+    }
+}
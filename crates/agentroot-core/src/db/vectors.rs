@@ -1,11 +1,105 @@
 //! Vector storage operations
 //!
 //! Stores embeddings as BLOBs and computes cosine similarity in Rust.
+//!
+//! The vectors searched (`embeddings`) may be quantized to int8 (4x smaller)
+//! or one bit per dimension (32x smaller), chosen by
+//! `AGENTROOT_VECTOR_QUANTIZATION`. The chunk embedding cache keeps full
+//! precision, so the best candidates of a quantized scan are rescored
+//! exactly; that cache is a second copy on disk until
+//! [`Database::drop_full_precision_embeddings`] removes it.
 
 use super::Database;
-use crate::error::Result;
+use crate::error::{AgentRootError, Result};
 use chrono::Utc;
-use rusqlite::params;
+use rusqlite::{params, OptionalExtension};
+
+/// Leading NaN words marking quantized vectors; real embeddings have no NaN
+const INT8_TAG: [u8; 4] = 0x7FC0_0008u32.to_le_bytes();
+const BINARY_TAG: [u8; 4] = 0x7FC0_0001u32.to_le_bytes();
+
+/// Vectors re-encoded per transaction by [`Database::quantize_stored_embeddings`]
+const REQUANTIZE_BATCH: usize = 1000;
+
+/// A model's share of the stored embeddings
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct EmbeddingModel {
+    pub model: String,
+    pub dimensions: usize,
+    pub last_used_at: String,
+    /// Documents whose searched vectors come from this model
+    pub indexed_documents: usize,
+    /// Chunk vectors cached for this model, including staged ones
+    pub cached_chunks: usize,
+}
+
+/// Encoding of stored search vectors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Quantization {
+    /// Full-precision f32
+    #[default]
+    None,
+    /// One signed byte per dimension plus a scale
+    Int8,
+    /// One sign bit per dimension
+    Binary,
+}
+
+impl Quantization {
+    /// `AGENTROOT_VECTOR_QUANTIZATION` (`none`, `int8` or `binary`); full
+    /// precision when unset or invalid
+    pub fn from_env() -> Self {
+        match std::env::var("AGENTROOT_VECTOR_QUANTIZATION") {
+            Ok(value) => value.parse().unwrap_or_else(|e| {
+                tracing::warn!("{}; storing full-precision vectors", e);
+                Self::None
+            }),
+            Err(_) => Self::None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Int8 => "int8",
+            Self::Binary => "binary",
+        }
+    }
+
+    /// Encoding of a stored vector
+    pub fn of(bytes: &[u8]) -> Self {
+        match bytes.get(..4) {
+            Some(tag) if tag == INT8_TAG => Self::Int8,
+            Some(tag) if tag == BINARY_TAG => Self::Binary,
+            _ => Self::None,
+        }
+    }
+}
+
+impl std::str::FromStr for Quantization {
+    type Err = AgentRootError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "" | "none" | "f32" => Ok(Self::None),
+            "int8" => Ok(Self::Int8),
+            "binary" => Ok(Self::Binary),
+            other => Err(AgentRootError::InvalidInput(format!(
+                "Unknown vector quantization '{}' (expected none, int8 or binary)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Embedding of one document chunk, for [`Database::replace_document_embeddings`]
+#[derive(Debug, Clone)]
+pub struct ChunkEmbedding {
+    pub seq: u32,
+    pub pos: usize,
+    pub chunk_hash: String,
+    pub embedding: Vec<f32>,
+}
 
 /// Result of looking up a cached embedding
 #[derive(Debug, Clone)]
@@ -43,10 +137,9 @@ impl Database {
     ) -> Result<()> {
         let now = Utc::now().to_rfc3339();
         let hash_seq = format!("{}_{}", hash, seq);
-        let embedding_bytes = embedding_to_bytes(embedding);
+        let embedding_bytes = encode_embedding(embedding, Quantization::from_env());
 
-        self.conn.execute("BEGIN IMMEDIATE", [])?;
-        let result = (|| {
+        self.in_transaction(|| {
             self.conn.execute(
                 "INSERT OR REPLACE INTO content_vectors (hash, seq, pos, model, created_at)
                  VALUES (?1, ?2, ?3, ?4, ?5)",
@@ -57,14 +150,7 @@ impl Database {
                 params![hash_seq, embedding_bytes, model],
             )?;
             Ok(())
-        })();
-
-        if result.is_ok() {
-            self.conn.execute("COMMIT", [])?;
-        } else {
-            let _ = self.conn.execute("ROLLBACK", []);
-        }
-        result
+        })
     }
 
     /// Check if vector index exists and has data
@@ -120,6 +206,118 @@ impl Database {
         Ok(results)
     }
 
+    /// Stored embedding of a document, or of one of its chunks
+    ///
+    /// A document is represented by its first chunk. `None` when nothing
+    /// has been embedded for it.
+    pub fn get_stored_embedding(
+        &self,
+        hash: &str,
+        chunk_hash: Option<&str>,
+    ) -> Result<Option<Vec<f32>>> {
+        let embedding = self
+            .conn
+            .prepare_cached(
+                "SELECT e.embedding
+                 FROM content_vectors cv
+                 JOIN embeddings e ON e.hash_seq = cv.hash || '_' || cv.seq
+                 WHERE cv.hash = ?1 AND (?2 IS NULL OR cv.chunk_hash = ?2)
+                 ORDER BY cv.seq
+                 LIMIT 1",
+            )?
+            .query_row(params![hash, chunk_hash], |row| row.get::<_, Vec<u8>>(0))
+            .optional()?;
+        Ok(embedding.map(|bytes| bytes_to_embedding(&bytes)))
+    }
+
+    /// Full-precision embedding behind a stored vector, from the chunk cache
+    pub(crate) fn full_precision_embedding(&self, hash_seq: &str) -> Result<Option<Vec<f32>>> {
+        let Some((hash, seq)) = hash_seq.rsplit_once('_') else {
+            return Ok(None);
+        };
+        let embedding = self
+            .conn
+            .prepare_cached(
+                "SELECT ce.embedding
+                 FROM content_vectors cv
+                 JOIN chunk_embeddings ce ON ce.chunk_hash = cv.chunk_hash AND ce.model = cv.model
+                 WHERE cv.hash = ?1 AND cv.seq = ?2",
+            )?
+            .query_row(params![hash, seq], |row| row.get::<_, Vec<u8>>(0))
+            .optional()?;
+        Ok(embedding.map(|bytes| bytes_to_embedding(&bytes)))
+    }
+
+    /// Re-encode stored vectors not already in `quantization`
+    ///
+    /// Vectors are re-encoded from the full-precision chunk cache where it
+    /// has them, else from their current encoding. Returns how many changed.
+    pub fn quantize_stored_embeddings(&self, quantization: Quantization) -> Result<usize> {
+        if !self.has_vector_index() {
+            return Ok(0);
+        }
+        let mut stale = Vec::new();
+        {
+            let mut stmt = self
+                .conn
+                .prepare("SELECT hash_seq, substr(embedding, 1, 4) FROM embeddings")?;
+            let mut rows = stmt.query([])?;
+            while let Some(row) = rows.next()? {
+                let tag: Vec<u8> = row.get(1)?;
+                if Quantization::of(&tag) != quantization {
+                    stale.push(row.get::<_, String>(0)?);
+                }
+            }
+        }
+
+        for batch in stale.chunks(REQUANTIZE_BATCH) {
+            self.in_transaction(|| {
+                for hash_seq in batch {
+                    let embedding = match self.full_precision_embedding(hash_seq)? {
+                        Some(embedding) => embedding,
+                        None => {
+                            let bytes: Vec<u8> = self.conn.query_row(
+                                "SELECT embedding FROM embeddings WHERE hash_seq = ?1",
+                                params![hash_seq],
+                                |row| row.get(0),
+                            )?;
+                            bytes_to_embedding(&bytes)
+                        }
+                    };
+                    self.conn.execute(
+                        "UPDATE embeddings SET embedding = ?2 WHERE hash_seq = ?1",
+                        params![hash_seq, encode_embedding(&embedding, quantization)],
+                    )?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(stale.len())
+    }
+
+    /// Delete the full-precision cached embeddings behind quantized vectors
+    ///
+    /// Only the quantized copy stays on disk. Quantized scans then keep their
+    /// approximate scores instead of being rescored, and re-embedding those
+    /// chunks (`embed --force`, a model migration) computes them again.
+    /// Returns how many cached embeddings were deleted.
+    pub fn drop_full_precision_embeddings(&self) -> Result<usize> {
+        if !self.has_vector_index() {
+            return Ok(0);
+        }
+        let deleted = self.conn.execute(
+            "DELETE FROM chunk_embeddings
+             WHERE (chunk_hash, model) IN (
+                 SELECT cv.chunk_hash, cv.model
+                 FROM content_vectors cv
+                 JOIN embeddings e ON e.hash_seq = cv.hash || '_' || cv.seq
+                 WHERE substr(e.embedding, 1, 4) IN (?1, ?2)
+             )",
+            params![INT8_TAG.as_slice(), BINARY_TAG.as_slice()],
+        )?;
+        Ok(deleted)
+    }
+
     /// Get hashes that need embedding
     pub fn get_hashes_needing_embedding(&self) -> Result<Vec<(String, String)>> {
         let mut stmt = self.conn.prepare(
@@ -151,8 +349,7 @@ impl Database {
     pub fn delete_embeddings(&self, hash: &str) -> Result<usize> {
         let pattern = format!("{}_*", hash);
 
-        self.conn.execute("BEGIN IMMEDIATE", [])?;
-        let result = (|| {
+        self.in_transaction(|| {
             self.conn
                 .execute("DELETE FROM content_vectors WHERE hash = ?1", params![hash])?;
             // Use GLOB instead of LIKE to avoid issues with special characters.
@@ -162,14 +359,7 @@ impl Database {
                 params![pattern],
             )?;
             Ok(rows)
-        })();
-
-        if result.is_ok() {
-            self.conn.execute("COMMIT", [])?;
-        } else {
-            let _ = self.conn.execute("ROLLBACK", []);
-        }
-        result
+        })
     }
 
     /// Get all hashes for embedding (for force re-embedding)
@@ -239,35 +429,100 @@ impl Database {
         model: &str,
         embedding: &[f32],
     ) -> Result<()> {
-        let now = Utc::now().to_rfc3339();
-        let hash_seq = format!("{}_{}", doc_hash, seq);
-        let embedding_bytes = embedding_to_bytes(embedding);
+        self.in_transaction(|| {
+            self.write_chunk_embedding(doc_hash, seq, pos, chunk_hash, model, embedding)
+        })
+    }
 
-        self.conn.execute("BEGIN IMMEDIATE", [])?;
-        let result = (|| {
+    /// Replace all embeddings of a document in one transaction
+    ///
+    /// A document is either fully embedded or not at all, so an interrupted
+    /// run never leaves it looking complete.
+    pub fn replace_document_embeddings(
+        &self,
+        doc_hash: &str,
+        model: &str,
+        chunks: &[ChunkEmbedding],
+    ) -> Result<()> {
+        self.in_transaction(|| {
             self.conn.execute(
-                "INSERT OR REPLACE INTO content_vectors (hash, seq, pos, model, chunk_hash, created_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-                params![doc_hash, seq, pos, model, chunk_hash, now],
+                "DELETE FROM embeddings WHERE hash_seq IN
+                 (SELECT hash || '_' || seq FROM content_vectors WHERE hash = ?1)",
+                params![doc_hash],
             )?;
             self.conn.execute(
-                "INSERT OR REPLACE INTO embeddings (hash_seq, embedding, model) VALUES (?1, ?2, ?3)",
-                params![hash_seq, embedding_bytes, model],
+                "DELETE FROM content_vectors WHERE hash = ?1",
+                params![doc_hash],
             )?;
-            self.conn.execute(
+            for chunk in chunks {
+                self.write_chunk_embedding(
+                    doc_hash,
+                    chunk.seq,
+                    chunk.pos,
+                    &chunk.chunk_hash,
+                    model,
+                    &chunk.embedding,
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Store chunk embeddings for `model` in the cache only
+    ///
+    /// Used to stage another model's vectors while the current ones keep
+    /// serving search.
+    pub fn cache_chunk_embeddings(&self, model: &str, chunks: &[ChunkEmbedding]) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        self.in_transaction(|| {
+            let mut stmt = self.conn.prepare_cached(
                 "INSERT OR REPLACE INTO chunk_embeddings (chunk_hash, model, embedding, created_at)
                  VALUES (?1, ?2, ?3, ?4)",
-                params![chunk_hash, model, &embedding_bytes, now],
             )?;
+            for chunk in chunks {
+                stmt.execute(params![
+                    chunk.chunk_hash,
+                    model,
+                    embedding_to_bytes(&chunk.embedding),
+                    now
+                ])?;
+            }
             Ok(())
-        })();
+        })
+    }
 
-        if result.is_ok() {
-            self.conn.execute("COMMIT", [])?;
-        } else {
-            let _ = self.conn.execute("ROLLBACK", []);
-        }
-        result
+    fn write_chunk_embedding(
+        &self,
+        doc_hash: &str,
+        seq: u32,
+        pos: usize,
+        chunk_hash: &str,
+        model: &str,
+        embedding: &[f32],
+    ) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        let hash_seq = format!("{}_{}", doc_hash, seq);
+        let embedding_bytes = embedding_to_bytes(embedding);
+
+        self.conn.execute(
+            "INSERT OR REPLACE INTO content_vectors (hash, seq, pos, model, chunk_hash, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![doc_hash, seq, pos, model, chunk_hash, now],
+        )?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO embeddings (hash_seq, embedding, model) VALUES (?1, ?2, ?3)",
+            params![
+                hash_seq,
+                encode_embedding(embedding, Quantization::from_env()),
+                model
+            ],
+        )?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO chunk_embeddings (chunk_hash, model, embedding, created_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![chunk_hash, model, &embedding_bytes, now],
+        )?;
+        Ok(())
     }
 
     /// Get chunk hashes for a document
@@ -362,6 +617,30 @@ impl Database {
         )?;
         Ok(count as usize)
     }
+
+    /// Every model with stored embeddings, most recently used first
+    pub fn embedding_models(&self) -> Result<Vec<EmbeddingModel>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT m.model, m.dimensions, m.last_used_at,
+                    (SELECT COUNT(DISTINCT cv.hash) FROM content_vectors cv
+                     WHERE cv.model = m.model),
+                    (SELECT COUNT(*) FROM chunk_embeddings ce WHERE ce.model = m.model)
+             FROM model_metadata m
+             ORDER BY m.last_used_at DESC, m.model",
+        )?;
+        let models = stmt
+            .query_map([], |row| {
+                Ok(EmbeddingModel {
+                    model: row.get(0)?,
+                    dimensions: row.get::<_, i64>(1)? as usize,
+                    last_used_at: row.get(2)?,
+                    indexed_documents: row.get::<_, i64>(3)? as usize,
+                    cached_chunks: row.get::<_, i64>(4)? as usize,
+                })
+            })?
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(models)
+    }
 }
 
 /// Convert f32 embedding to bytes (little-endian)
@@ -369,12 +648,94 @@ pub fn embedding_to_bytes(embedding: &[f32]) -> Vec<u8> {
     embedding.iter().flat_map(|f| f.to_le_bytes()).collect()
 }
 
-/// Convert bytes to f32 embedding
+/// Encode an embedding for the `embeddings` table
+pub fn encode_embedding(embedding: &[f32], quantization: Quantization) -> Vec<u8> {
+    match quantization {
+        Quantization::None => embedding_to_bytes(embedding),
+        Quantization::Int8 => {
+            let max = embedding.iter().fold(0.0f32, |m, v| m.max(v.abs()));
+            let scale = if max > 0.0 { max / 127.0 } else { 0.0 };
+            let mut bytes = Vec::with_capacity(8 + embedding.len());
+            bytes.extend_from_slice(&INT8_TAG);
+            bytes.extend_from_slice(&scale.to_le_bytes());
+            bytes.extend(embedding.iter().map(|v| {
+                let code = if scale > 0.0 {
+                    (v / scale).round()
+                } else {
+                    0.0
+                };
+                code.clamp(-127.0, 127.0) as i8 as u8
+            }));
+            bytes
+        }
+        Quantization::Binary => {
+            let mut bytes = Vec::with_capacity(8 + embedding.len().div_ceil(8));
+            bytes.extend_from_slice(&BINARY_TAG);
+            bytes.extend_from_slice(&(embedding.len() as u32).to_le_bytes());
+            bytes.extend(embedding.chunks(8).map(|bits| {
+                bits.iter()
+                    .enumerate()
+                    .filter(|(_, v)| **v > 0.0)
+                    .fold(0u8, |byte, (i, _)| byte | (1 << i))
+            }));
+            bytes
+        }
+    }
+}
+
+/// Convert stored bytes to an f32 embedding, dequantizing if needed
+///
+/// A binary vector decodes to +1 and -1 per dimension, which preserves its
+/// direction for cosine similarity.
 pub fn bytes_to_embedding(bytes: &[u8]) -> Vec<f32> {
-    bytes
-        .chunks_exact(4)
-        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
-        .collect()
+    let mut embedding = Vec::new();
+    decode_embedding_into(bytes, &mut embedding);
+    embedding
+}
+
+/// Decode into a reused buffer; returns whether the vector was quantized
+pub(crate) fn decode_embedding_into(bytes: &[u8], out: &mut Vec<f32>) -> bool {
+    out.clear();
+    match Quantization::of(bytes) {
+        Quantization::None => {
+            out.extend(
+                bytes
+                    .chunks_exact(4)
+                    .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]])),
+            );
+            false
+        }
+        Quantization::Int8 => {
+            let Some(scale) = bytes.get(4..8) else {
+                return true;
+            };
+            let scale = f32::from_le_bytes([scale[0], scale[1], scale[2], scale[3]]);
+            out.extend(bytes[8..].iter().map(|b| *b as i8 as f32 * scale));
+            true
+        }
+        Quantization::Binary => {
+            let dimensions = stored_dimensions(bytes, bytes.len()).unwrap_or(0);
+            let bits = bytes.get(8..).unwrap_or_default();
+            out.extend((0..dimensions).map(|i| {
+                match bits.get(i / 8).is_some_and(|b| b & (1 << (i % 8)) != 0) {
+                    true => 1.0,
+                    false => -1.0,
+                }
+            }));
+            true
+        }
+    }
+}
+
+/// Dimensions of a stored vector from its first 8 bytes and total length
+pub(crate) fn stored_dimensions(head: &[u8], len: usize) -> Option<usize> {
+    match Quantization::of(head) {
+        Quantization::None => len.is_multiple_of(4).then_some(len / 4),
+        Quantization::Int8 => len.checked_sub(8),
+        Quantization::Binary => head
+            .get(4..8)
+            .map(|d| u32::from_le_bytes([d[0], d[1], d[2], d[3]]) as usize),
+    }
 }
 
 /// Compute cosine similarity between two embeddings
@@ -421,4 +782,84 @@ mod tests {
         let sim = cosine_similarity(&a, &b);
         assert!(sim.abs() < 0.0001);
     }
+
+    #[test]
+    fn test_quantized_roundtrip() {
+        let original = vec![0.5f32, -0.25, 0.0, 1.0, -1.0, 0.75, 0.1, -0.1, 0.3];
+
+        let int8 = encode_embedding(&original, Quantization::Int8);
+        assert_eq!(Quantization::of(&int8), Quantization::Int8);
+        assert_eq!(int8.len(), 8 + original.len());
+        assert_eq!(stored_dimensions(&int8[..8], int8.len()), Some(9));
+        let restored = bytes_to_embedding(&int8);
+        assert!(original
+            .iter()
+            .zip(&restored)
+            .all(|(a, b)| (a - b).abs() < 0.01));
+
+        let binary = encode_embedding(&original, Quantization::Binary);
+        assert_eq!(binary.len(), 8 + 2);
+        assert_eq!(stored_dimensions(&binary[..8], binary.len()), Some(9));
+        let signs = bytes_to_embedding(&binary);
+        assert_eq!(signs, vec![1.0, -1.0, -1.0, 1.0, -1.0, 1.0, 1.0, -1.0, 1.0]);
+        assert!(cosine_similarity(&original, &signs) > 0.7);
+
+        let full = encode_embedding(&original, Quantization::None);
+        assert_eq!(Quantization::of(&full), Quantization::None);
+        assert_eq!(bytes_to_embedding(&full), original);
+        assert!("int4".parse::<Quantization>().is_err());
+    }
+
+    #[test]
+    fn test_quantize_stored_embeddings_uses_full_precision_cache() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.ensure_vec_table(3).unwrap();
+        let embedding = [0.2f32, -0.9, 0.4];
+        db.insert_chunk_embedding("aaa111", 0, 0, "chunk1", "test", &embedding)
+            .unwrap();
+
+        assert_eq!(
+            db.quantize_stored_embeddings(Quantization::Binary).unwrap(),
+            1
+        );
+        let (_, stored) = db.get_all_embeddings().unwrap().remove(0);
+        assert_eq!(stored, vec![1.0, -1.0, 1.0]);
+        assert_eq!(
+            db.full_precision_embedding("aaa111_0").unwrap(),
+            Some(embedding.to_vec())
+        );
+
+        // Back to full precision from the cache, not from the sign bits
+        assert_eq!(
+            db.quantize_stored_embeddings(Quantization::None).unwrap(),
+            1
+        );
+        let (_, stored) = db.get_all_embeddings().unwrap().remove(0);
+        assert_eq!(stored, embedding.to_vec());
+        assert_eq!(
+            db.quantize_stored_embeddings(Quantization::None).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_drop_full_precision_embeddings() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.ensure_vec_table(3).unwrap();
+        let embedding = [0.2f32, -0.9, 0.4];
+        db.insert_chunk_embedding("aaa111", 0, 0, "chunk1", "test", &embedding)
+            .unwrap();
+
+        // Full-precision vectors keep their cache
+        assert_eq!(db.drop_full_precision_embeddings().unwrap(), 0);
+        assert!(db.full_precision_embedding("aaa111_0").unwrap().is_some());
+
+        db.quantize_stored_embeddings(Quantization::Int8).unwrap();
+        assert_eq!(db.drop_full_precision_embeddings().unwrap(), 1);
+        assert_eq!(db.full_precision_embedding("aaa111_0").unwrap(), None);
+        let (_, stored) = db.get_all_embeddings().unwrap().remove(0);
+        assert!(cosine_similarity(&stored, &embedding) > 0.99);
+    }
 }
@@ -0,0 +1,523 @@
+//! Prefix completions for search-as-you-type
+//!
+//! The FTS vocabulary holds porter stems ("configur"), which make poor
+//! completions, so the words of each collection are kept in a `vocabulary`
+//! table that is rebuilt whenever a reindex changes the collection.
+//! Suggestions merge glossary concepts, vocabulary words and document titles.
+//!
+//! The same words feed typo-tolerant search: `vocabulary_trigrams` indexes
+//! them by trigram, so a misspelled query word finds its likely corrections.
+
+use super::Database;
+use crate::error::Result;
+use rusqlite::params;
+use std::collections::{HashMap, HashSet};
+
+const MIN_WORD_LEN: usize = 3;
+const MAX_WORD_LEN: usize = 40;
+
+/// Shorter query words are too easily one edit away from something else
+const MIN_FUZZY_LEN: usize = 4;
+/// Query words up to this length are corrected by one edit, longer ones by two
+const ONE_EDIT_MAX_LEN: usize = 5;
+/// Trigram matches checked for edit distance per query word
+const FUZZY_CANDIDATES: usize = 200;
+
+/// Where a suggestion came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestionKind {
+    Concept,
+    Term,
+    Title,
+}
+
+/// A completion for a query prefix
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Suggestion {
+    /// The full query with the completion applied
+    pub text: String,
+    pub kind: SuggestionKind,
+    /// Chunks for concepts, documents for terms
+    pub weight: usize,
+}
+
+/// Lowercased words worth completing to
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|w| {
+            let len = w.chars().count();
+            (MIN_WORD_LEN..=MAX_WORD_LEN).contains(&len) && !w.chars().all(|c| c.is_ascii_digit())
+        })
+        .map(str::to_lowercase)
+}
+
+/// Escape LIKE wildcards so user input matches literally
+fn like_prefix(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 1);
+    for c in text.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped.push('%');
+    escaped
+}
+
+/// Edits (insertions, deletions, substitutions and swaps of neighbouring
+/// characters) between two words
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut rows = vec![(0..=b.len()).collect::<Vec<_>>()];
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (rows[i - 1][j] + 1)
+                .min(row[j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+        rows.push(row);
+    }
+    rows[a.len()][b.len()]
+}
+
+impl Database {
+    /// Recount the words of a collection's active documents
+    pub(crate) fn rebuild_vocabulary(&self, collection: &str) -> Result<usize> {
+        self.conn.execute(
+            "DELETE FROM vocabulary WHERE collection = ?1",
+            params![collection],
+        )?;
+
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        let mut stmt = self.conn.prepare(
+            "SELECT d.title, c.doc
+             FROM documents d
+             JOIN content c ON c.hash = d.hash
+             WHERE d.collection = ?1 AND d.active = 1",
+        )?;
+        let mut rows = stmt.query(params![collection])?;
+        while let Some(row) = rows.next()? {
+            let title: String = row.get(0)?;
+            let body: String = row.get(1)?;
+            let seen: HashSet<String> = words(&title).chain(words(&body)).collect();
+            for word in seen {
+                *counts.entry(word).or_default() += 1;
+            }
+        }
+
+        let mut insert = self
+            .conn
+            .prepare("INSERT INTO vocabulary (collection, term, doc_count) VALUES (?1, ?2, ?3)")?;
+        for (term, doc_count) in &counts {
+            insert.execute(params![collection, term, *doc_count as i64])?;
+        }
+        self.sync_vocabulary_trigrams()?;
+
+        Ok(counts.len())
+    }
+
+    /// Take one removed document's words out of its collection's counts
+    ///
+    /// Words no other document uses are dropped, and with them their
+    /// trigrams once no collection has them.
+    pub(crate) fn remove_document_vocabulary(
+        &self,
+        collection: &str,
+        title: &str,
+        body: &str,
+    ) -> Result<()> {
+        let seen: HashSet<String> = words(title).chain(words(body)).collect();
+        let mut decrement = self.conn.prepare_cached(
+            "UPDATE vocabulary SET doc_count = doc_count - 1
+             WHERE collection = ?1 AND term = ?2",
+        )?;
+        for term in &seen {
+            decrement.execute(params![collection, term])?;
+        }
+        let mut stmt = self.conn.prepare(
+            "DELETE FROM vocabulary WHERE collection = ?1 AND doc_count <= 0 RETURNING term",
+        )?;
+        let dropped = stmt
+            .query_map(params![collection], |row| row.get::<_, String>(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let has_trigrams: bool = self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE name = 'vocabulary_trigrams'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_trigrams {
+            return Ok(());
+        }
+        // Words are alphanumeric, so quoting makes each a single trigram phrase
+        let mut delete = self.conn.prepare(
+            "DELETE FROM vocabulary_trigrams WHERE rowid IN (
+                 SELECT rowid FROM vocabulary_trigrams
+                 WHERE vocabulary_trigrams MATCH ?1 AND term = ?2
+             ) AND NOT EXISTS (SELECT 1 FROM vocabulary WHERE term = ?2)",
+        )?;
+        for term in &dropped {
+            delete.execute(params![format!("\"{}\"", term), term])?;
+        }
+        Ok(())
+    }
+
+    /// Bring the trigram index in line with the words of every collection
+    pub(crate) fn sync_vocabulary_trigrams(&self) -> Result<()> {
+        // Indexes from before the trigram table get it in a later migration
+        let exists: bool = self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE name = 'vocabulary_trigrams'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            return Ok(());
+        }
+
+        self.conn.execute(
+            "DELETE FROM vocabulary_trigrams WHERE term NOT IN (SELECT term FROM vocabulary)",
+            [],
+        )?;
+        let mut stmt = self.conn.prepare("SELECT term FROM vocabulary_trigrams")?;
+        let indexed = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<std::result::Result<HashSet<_>, _>>()?;
+        let mut stmt = self.conn.prepare("SELECT DISTINCT term FROM vocabulary")?;
+        let terms = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let mut insert = self
+            .conn
+            .prepare("INSERT INTO vocabulary_trigrams (term) VALUES (?1)")?;
+        for term in terms.iter().filter(|t| !indexed.contains(*t)) {
+            insert.execute(params![term])?;
+        }
+        Ok(())
+    }
+
+    /// Vocabulary words a misspelled `word` probably meant, best first
+    ///
+    /// Words the vocabulary already has are left alone. Candidates share a
+    /// trigram with the word and are at most one edit away for short words,
+    /// two for longer ones; closer and more common words rank first.
+    pub(crate) fn typo_corrections(
+        &self,
+        word: &str,
+        collection: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<String>> {
+        let word = word.to_lowercase();
+        let len = word.chars().count();
+        if !(MIN_FUZZY_LEN..=MAX_WORD_LEN).contains(&len) || limit == 0 {
+            return Ok(Vec::new());
+        }
+        let known: bool = self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM vocabulary
+             WHERE term = ?1 AND (?2 IS NULL OR collection = ?2)",
+            params![word, collection],
+            |row| row.get(0),
+        )?;
+        if known {
+            return Ok(Vec::new());
+        }
+
+        let max_edits = if len <= ONE_EDIT_MAX_LEN { 1 } else { 2 };
+        let chars: Vec<char> = word.chars().collect();
+        let trigrams: HashSet<String> = chars
+            .windows(3)
+            .map(|w| format!("\"{}\"", w.iter().collect::<String>()))
+            .collect();
+        let match_expr = trigrams.into_iter().collect::<Vec<_>>().join(" OR ");
+
+        let mut stmt = self.conn.prepare(
+            "SELECT c.term, SUM(v.doc_count) FROM (
+                 SELECT term FROM vocabulary_trigrams
+                 WHERE vocabulary_trigrams MATCH ?1 AND length(term) BETWEEN ?2 AND ?3
+                 ORDER BY rank
+                 LIMIT ?4
+             ) c
+             JOIN vocabulary v ON v.term = c.term
+             WHERE ?5 IS NULL OR v.collection = ?5
+             GROUP BY c.term",
+        )?;
+        let rows = stmt.query_map(
+            params![
+                match_expr,
+                len.saturating_sub(max_edits) as i64,
+                (len + max_edits) as i64,
+                FUZZY_CANDIDATES as i64,
+                collection
+            ],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)),
+        )?;
+
+        let mut corrections = Vec::new();
+        for row in rows {
+            let (term, docs) = row?;
+            let distance = edit_distance(&chars, &term.chars().collect::<Vec<_>>());
+            if distance <= max_edits {
+                corrections.push((distance, docs, term));
+            }
+        }
+        corrections.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)).then(a.2.cmp(&b.2)));
+        Ok(corrections
+            .into_iter()
+            .take(limit)
+            .map(|(_, _, term)| term)
+            .collect())
+    }
+
+    /// Words of `text` that best set it apart from the rest of a collection
+    ///
+    /// Ranked by tf-idf against the collection vocabulary. Words found in no
+    /// other document are skipped, as they cannot match anything else.
+    pub(crate) fn distinctive_terms(
+        &self,
+        collection: &str,
+        text: &str,
+        limit: usize,
+    ) -> Result<Vec<String>> {
+        let total: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM documents WHERE collection = ?1 AND active = 1",
+            params![collection],
+            |row| row.get(0),
+        )?;
+
+        let mut tf: HashMap<String, usize> = HashMap::new();
+        for word in words(text) {
+            *tf.entry(word).or_default() += 1;
+        }
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT doc_count FROM vocabulary WHERE collection = ?1 AND term = ?2")?;
+        let mut scored = Vec::new();
+        for (term, count) in tf {
+            let doc_count: i64 = stmt
+                .query_row(params![collection, term], |row| row.get(0))
+                .unwrap_or(0);
+            if doc_count < 2 {
+                continue;
+            }
+            let idf = (total as f64 / doc_count as f64).ln();
+            if idf > 0.0 {
+                scored.push((count as f64 * idf, term));
+            }
+        }
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        Ok(scored.into_iter().take(limit).map(|(_, t)| t).collect())
+    }
+
+    /// Ranked completions for what has been typed so far
+    ///
+    /// Concepts and titles complete the whole input; vocabulary words complete
+    /// its last word.
+    pub fn suggest(&self, prefix: &str, limit: usize) -> Result<Vec<Suggestion>> {
+        let input = prefix.trim_start();
+        let last_word_start = input
+            .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .map(|i| i + input[i..].chars().next().map_or(1, char::len_utf8))
+            .unwrap_or(0);
+        let (head, word) = input.split_at(last_word_start);
+        if word.is_empty() || limit == 0 {
+            return Ok(Vec::new());
+        }
+        let word = word.to_lowercase();
+
+        let mut suggestions = Vec::new();
+
+        let mut stmt = self.conn.prepare(
+            "SELECT term, chunk_count FROM concepts
+             WHERE term LIKE ?1 ESCAPE '\\'
+             ORDER BY chunk_count DESC, term
+             LIMIT ?2",
+        )?;
+        let concepts = stmt.query_map(params![like_prefix(input), limit as i64], |row| {
+            Ok(Suggestion {
+                text: row.get(0)?,
+                kind: SuggestionKind::Concept,
+                weight: row.get(1)?,
+            })
+        })?;
+        for concept in concepts {
+            suggestions.push(concept?);
+        }
+
+        // Range scan on the term index; U+10FFFF sorts after any continuation
+        let mut stmt = self.conn.prepare(
+            "SELECT term, SUM(doc_count) AS docs FROM vocabulary
+             WHERE term >= ?1 AND term < ?2
+             GROUP BY term
+             ORDER BY docs DESC, term
+             LIMIT ?3",
+        )?;
+        let upper = format!("{}\u{10FFFF}", word);
+        let terms = stmt.query_map(params![word, upper, limit as i64 + 1], |row| {
+            Ok(Suggestion {
+                text: format!("{}{}", head, row.get::<_, String>(0)?),
+                kind: SuggestionKind::Term,
+                weight: row.get(1)?,
+            })
+        })?;
+        for term in terms {
+            suggestions.push(term?);
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT title FROM documents
+             WHERE active = 1 AND title LIKE ?1 ESCAPE '\\'
+             GROUP BY title
+             ORDER BY MAX(COALESCE(importance_score, 1.0)) DESC, title
+             LIMIT ?2",
+        )?;
+        let titles = stmt.query_map(params![like_prefix(input), limit as i64], |row| {
+            Ok(Suggestion {
+                text: row.get(0)?,
+                kind: SuggestionKind::Title,
+                weight: 1,
+            })
+        })?;
+        for title in titles {
+            suggestions.push(title?);
+        }
+
+        let typed = input.to_lowercase();
+        let mut seen = HashSet::new();
+        suggestions.retain(|s| {
+            let key = s.text.to_lowercase();
+            key != typed && seen.insert(key)
+        });
+        suggestions.truncate(limit);
+        Ok(suggestions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_suggest_completes_prefix() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+        for (hash, path, title, body) in [
+            (
+                "h1",
+                "a.md",
+                "Configuration Guide",
+                "Configure the database.",
+            ),
+            (
+                "h2",
+                "b.md",
+                "Databases",
+                "Database configuration and config files.",
+            ),
+            ("h3", "c.md", "Old", "Configurable"),
+        ] {
+            db.insert_content(hash, body).unwrap();
+            db.insert_document("notes", path, title, hash, &now, &now, "file", None)
+                .unwrap();
+        }
+        db.deactivate_document("notes", "c.md").unwrap();
+        db.rebuild_vocabulary("notes").unwrap();
+        db.upsert_concept("configuration management").unwrap();
+
+        let texts = |prefix: &str| -> Vec<(String, SuggestionKind)> {
+            db.suggest(prefix, 10)
+                .unwrap()
+                .into_iter()
+                .map(|s| (s.text, s.kind))
+                .collect()
+        };
+
+        assert_eq!(
+            texts("config"),
+            vec![
+                (
+                    "configuration management".to_string(),
+                    SuggestionKind::Concept
+                ),
+                ("configuration".to_string(), SuggestionKind::Term),
+                ("configure".to_string(), SuggestionKind::Term),
+                ("Configuration Guide".to_string(), SuggestionKind::Title),
+            ]
+        );
+        assert_eq!(
+            texts("the datab"),
+            vec![
+                ("the database".to_string(), SuggestionKind::Term),
+                ("the databases".to_string(), SuggestionKind::Term),
+            ]
+        );
+        assert!(texts("config ").is_empty());
+        assert!(texts("100%").is_empty());
+    }
+
+    #[test]
+    fn test_edit_distance_counts_swaps_once() {
+        let d = |a: &str, b: &str| {
+            edit_distance(
+                &a.chars().collect::<Vec<_>>(),
+                &b.chars().collect::<Vec<_>>(),
+            )
+        };
+        assert_eq!(d("database", "database"), 0);
+        assert_eq!(d("databse", "database"), 1);
+        assert_eq!(d("teh", "the"), 1);
+        assert_eq!(d("kubernets", "kubernetes"), 1);
+        assert_eq!(d("cache", "catch"), 2);
+    }
+
+    #[test]
+    fn test_typo_corrections_come_from_vocabulary() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+        for (hash, path, body) in [
+            ("h1", "a.md", "Database migrations run at startup."),
+            ("h2", "b.md", "The database keeps a write-ahead log."),
+            ("h3", "c.md", "Databases and databank archives."),
+        ] {
+            db.insert_content(hash, body).unwrap();
+            db.insert_document("notes", path, "Notes", hash, &now, &now, "file", None)
+                .unwrap();
+        }
+        db.rebuild_vocabulary("notes").unwrap();
+
+        assert_eq!(
+            db.typo_corrections("databse", None, 3).unwrap(),
+            vec!["database", "databases"]
+        );
+        assert_eq!(
+            db.typo_corrections("Migratons", Some("notes"), 3).unwrap(),
+            vec!["migrations"]
+        );
+        // Known and very short words are not corrected
+        assert!(db.typo_corrections("database", None, 3).unwrap().is_empty());
+        assert!(db.typo_corrections("lgo", None, 3).unwrap().is_empty());
+        assert!(db
+            .typo_corrections("databse", Some("other"), 3)
+            .unwrap()
+            .is_empty());
+
+        // Words of removed documents leave the trigram index on rebuild
+        db.deactivate_document("notes", "c.md").unwrap();
+        db.rebuild_vocabulary("notes").unwrap();
+        assert_eq!(
+            db.typo_corrections("databse", None, 3).unwrap(),
+            vec!["database"]
+        );
+    }
+}
@@ -0,0 +1,244 @@
+//! Serialized writes with retry on lock contention
+//!
+//! SQLite allows one writer at a time. Connections in the same process wait
+//! their turn on a per-file queue; writers in other processes, such as an
+//! `agentroot update` running next to the MCP server, are retried with
+//! backoff until the write timeout runs out. Callers then get
+//! `AgentRootError::Busy` instead of an opaque SQLITE_BUSY.
+//!
+//! Writes reached from async handlers wait on a runtime worker thread; on a
+//! multi-threaded runtime the worker hands its other tasks to another thread
+//! while it waits for the lock, so a long wait stalls only the caller.
+
+use super::Database;
+use crate::error::{AgentRootError, Result, READ_ONLY_MESSAGE};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+use std::time::{Duration, Instant};
+use tokio::runtime::{Handle, RuntimeFlavor};
+
+/// Seconds a write waits for the database lock when not configured
+pub const DEFAULT_WRITE_TIMEOUT_SECS: u64 = 60;
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(25);
+const MAX_BACKOFF: Duration = Duration::from_secs(1);
+
+lazy_static! {
+    static ref QUEUES: Mutex<HashMap<PathBuf, Arc<Mutex<()>>>> = Mutex::new(HashMap::new());
+}
+
+/// How long a write waits for the database lock (`AGENTROOT_WRITE_TIMEOUT_SECS`)
+pub fn write_timeout() -> Duration {
+    let secs = std::env::var("AGENTROOT_WRITE_TIMEOUT_SECS")
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(DEFAULT_WRITE_TIMEOUT_SECS);
+    Duration::from_secs(secs)
+}
+
+/// The queue shared by every connection to the database file at `path`
+pub(crate) fn queue_for(path: &Path) -> Arc<Mutex<()>> {
+    let key = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let mut queues = QUEUES.lock().unwrap_or_else(|e| e.into_inner());
+    Arc::clone(queues.entry(key).or_default())
+}
+
+fn busy(waited: Duration) -> AgentRootError {
+    AgentRootError::Busy(format!(
+        "another writer held the database lock for {:.1}s; retry once it finishes",
+        waited.as_secs_f64()
+    ))
+}
+
+/// Exponential backoff between lock attempts, capped at `MAX_BACKOFF`
+struct Backoff {
+    start: Instant,
+    timeout: Duration,
+    delay: Duration,
+}
+
+impl Backoff {
+    fn new(timeout: Duration) -> Self {
+        Self {
+            start: Instant::now(),
+            timeout,
+            delay: INITIAL_BACKOFF,
+        }
+    }
+
+    /// Sleep before the next attempt, or false once the timeout has passed
+    fn wait(&mut self) -> bool {
+        let elapsed = self.start.elapsed();
+        if elapsed >= self.timeout {
+            return false;
+        }
+        std::thread::sleep(self.delay.min(self.timeout - elapsed));
+        self.delay = (self.delay * 2).min(MAX_BACKOFF);
+        true
+    }
+}
+
+/// Run blocking `f` without holding up other tasks of a multi-threaded runtime
+fn off_runtime<T>(f: impl FnOnce() -> T) -> T {
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(f)
+        }
+        _ => f(),
+    }
+}
+
+fn is_busy(error: &rusqlite::Error) -> bool {
+    matches!(
+        error.sqlite_error_code(),
+        Some(rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked)
+    )
+}
+
+impl Database {
+    /// Change how long writes wait for the database lock
+    pub fn set_write_timeout(&mut self, timeout: Duration) -> Result<()> {
+        self.write_timeout = timeout;
+        // Each BEGIN attempt also waits inside SQLite; keep that within the timeout
+        self.conn
+            .busy_timeout(timeout.min(Duration::from_millis(5000)))?;
+        Ok(())
+    }
+
+    /// Fail fast before slow work whose results could not be stored
+    pub(crate) fn ensure_writable(&self) -> Result<()> {
+        if self.read_only {
+            return Err(AgentRootError::ReadOnly(READ_ONLY_MESSAGE.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Take this process's write slot, then the SQLite write lock
+    ///
+    /// The returned guard must be held until the transaction commits or
+    /// rolls back.
+    pub(crate) fn begin_write(&self) -> Result<Option<MutexGuard<'_, ()>>> {
+        self.ensure_writable()?;
+        // Both waits below can last the whole write timeout
+        off_runtime(|| self.lock_for_write())
+    }
+
+    fn lock_for_write(&self) -> Result<Option<MutexGuard<'_, ()>>> {
+        let mut backoff = Backoff::new(self.write_timeout);
+        let guard = match &self.write_queue {
+            Some(queue) => loop {
+                match queue.try_lock() {
+                    Ok(guard) => break Some(guard),
+                    Err(TryLockError::Poisoned(e)) => break Some(e.into_inner()),
+                    Err(TryLockError::WouldBlock) => {
+                        if !backoff.wait() {
+                            return Err(busy(backoff.start.elapsed()));
+                        }
+                    }
+                }
+            },
+            None => None,
+        };
+
+        loop {
+            match self.conn.execute_batch("BEGIN IMMEDIATE") {
+                Ok(()) => return Ok(guard),
+                Err(e) if is_busy(&e) => {
+                    if !backoff.wait() {
+                        return Err(busy(backoff.start.elapsed()));
+                    }
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn open(path: &Path, timeout: Duration) -> Database {
+        let mut db = Database::open(path).unwrap();
+        db.initialize().unwrap();
+        db.set_write_timeout(timeout).unwrap();
+        db
+    }
+
+    #[test]
+    fn test_write_reports_busy_after_timeout() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("index.sqlite");
+        let holder = rusqlite::Connection::open(&path).unwrap();
+        let db = open(&path, Duration::from_millis(200));
+        holder.execute_batch("BEGIN IMMEDIATE").unwrap();
+
+        let err = db.add_context("notes", "held").unwrap_err();
+        assert!(matches!(err, AgentRootError::Busy(_)), "{:?}", err);
+        assert!(err.to_string().starts_with("Index busy:"));
+
+        holder.execute_batch("COMMIT").unwrap();
+        db.in_transaction(|| db.add_context("notes", "free"))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_write_retries_until_lock_released() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("index.sqlite");
+        let holder = rusqlite::Connection::open(&path).unwrap();
+        let db = open(&path, Duration::from_secs(10));
+        holder.execute_batch("BEGIN IMMEDIATE").unwrap();
+
+        let release = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(300));
+            holder.execute_batch("COMMIT").unwrap();
+        });
+        db.in_transaction(|| db.add_context("notes", "after wait"))
+            .unwrap();
+        release.join().unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_waiting_write_does_not_stall_runtime() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("index.sqlite");
+        let holder = rusqlite::Connection::open(&path).unwrap();
+        let db = open(&path, Duration::from_millis(500));
+        holder.execute_batch("BEGIN IMMEDIATE").unwrap();
+
+        let ticks = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = Arc::clone(&ticks);
+        let ticker = tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            }
+        });
+        let writer =
+            tokio::spawn(async move { db.in_transaction(|| db.add_context("notes", "held")) });
+
+        let err = writer.await.unwrap().unwrap_err();
+        assert!(matches!(err, AgentRootError::Busy(_)));
+        // The only worker kept running other tasks while the write waited
+        assert!(ticks.load(std::sync::atomic::Ordering::Relaxed) >= 10);
+        ticker.abort();
+        holder.execute_batch("COMMIT").unwrap();
+    }
+
+    #[test]
+    fn test_connections_share_process_queue() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("index.sqlite");
+        let first = open(&path, Duration::from_secs(10));
+        let second = open(&path, Duration::from_millis(100));
+
+        let _guard = first.begin_write().unwrap();
+        let err = second.begin_write().unwrap_err();
+        assert!(matches!(err, AgentRootError::Busy(_)));
+        first.conn.execute_batch("COMMIT").unwrap();
+    }
+}
@@ -0,0 +1,348 @@
+//! Diagnostics behind `agentroot doctor`
+//!
+//! Each check produces a finding that says what is wrong and, when something
+//! is, the command that fixes it. Nothing is changed by running the checks.
+
+use crate::config::{Config, LLMServiceConfig};
+use crate::db::Database;
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+/// Collections not updated for this many days are reported as stale
+pub const STALE_AFTER_DAYS: i64 = 7;
+
+/// How long an LLM endpoint gets to answer
+const ENDPOINT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How serious a finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// Result of one check
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Finding {
+    /// Short name of the check, e.g. "integrity"
+    pub check: String,
+    pub severity: Severity,
+    pub message: String,
+    /// Command or step that fixes the problem
+    pub fix: Option<String>,
+}
+
+impl Finding {
+    fn ok(check: &str, message: impl Into<String>) -> Self {
+        Self {
+            check: check.to_string(),
+            severity: Severity::Ok,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn problem(
+        check: &str,
+        severity: Severity,
+        message: impl Into<String>,
+        fix: impl Into<String>,
+    ) -> Self {
+        Self {
+            check: check.to_string(),
+            severity,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Run every check; LLM endpoints are contacted only with `check_endpoints`
+pub async fn diagnose(db: &Database, check_endpoints: bool) -> Result<Vec<Finding>> {
+    let mut findings = vec![
+        check_integrity(db)?,
+        check_search_index(db)?,
+        check_orphans(db)?,
+    ];
+    findings.extend(check_dimensions(db)?);
+    findings.extend(check_collections(db)?);
+    if check_endpoints {
+        findings.extend(check_endpoints_reachable().await);
+    }
+    Ok(findings)
+}
+
+fn check_integrity(db: &Database) -> Result<Finding> {
+    let problems = db.integrity_problems()?;
+    if problems.is_empty() {
+        return Ok(Finding::ok("integrity", "Database file is intact"));
+    }
+    let shown: Vec<&str> = problems.iter().take(3).map(String::as_str).collect();
+    Ok(Finding::problem(
+        "integrity",
+        Severity::Error,
+        format!(
+            "SQLite reports {} problem(s): {}",
+            problems.len(),
+            shown.join("; ")
+        ),
+        "Restore the index from a backup, or remove it and run `agentroot update` to rebuild it",
+    ))
+}
+
+fn check_search_index(db: &Database) -> Result<Finding> {
+    let drift = db.search_index_drift()?;
+    if drift.is_consistent() {
+        return Ok(Finding::ok(
+            "search index",
+            "Full-text index matches the active documents",
+        ));
+    }
+    Ok(Finding::problem(
+        "search index",
+        Severity::Error,
+        format!(
+            "Full-text index is missing {} document(s) and has {} stale row(s)",
+            drift.missing, drift.extra
+        ),
+        "agentroot cleanup",
+    ))
+}
+
+fn check_orphans(db: &Database) -> Result<Finding> {
+    let chunks = db.count_orphaned_chunks()?;
+    let vectors = if db.has_vector_index() {
+        db.count_orphaned_vectors()?
+    } else {
+        0
+    };
+    if chunks == 0 && vectors == 0 {
+        return Ok(Finding::ok("orphans", "No orphaned chunks or vectors"));
+    }
+    let fix = if vectors > 0 {
+        "agentroot cleanup && agentroot embed --verify"
+    } else {
+        "agentroot cleanup"
+    };
+    Ok(Finding::problem(
+        "orphans",
+        Severity::Warning,
+        format!(
+            "{} chunk(s) and {} vector(s) belong to no active document",
+            chunks, vectors
+        ),
+        fix,
+    ))
+}
+
+fn check_dimensions(db: &Database) -> Result<Vec<Finding>> {
+    let mut findings: Vec<Finding> = db
+        .dimension_mismatches()?
+        .into_iter()
+        .map(|m| {
+            Finding::problem(
+                "embeddings",
+                Severity::Error,
+                format!(
+                    "{} vector(s) of model {} do not have its {} dimensions",
+                    m.vectors, m.model, m.expected
+                ),
+                "agentroot embed --verify",
+            )
+        })
+        .collect();
+
+    // A service configured for other dimensions than its vectors were stored with
+    let config = LLMServiceConfig::from_env_or_config();
+    if let (Some(configured), Some(stored)) = (
+        config.embedding_dimensions,
+        db.get_model_dimensions(&config.embedding_model)?,
+    ) {
+        if configured != stored {
+            findings.push(Finding::problem(
+                "embeddings",
+                Severity::Error,
+                format!(
+                    "{} is configured for {} dimensions but its vectors have {}",
+                    config.embedding_model, configured, stored
+                ),
+                "Fix AGENTROOT_EMBEDDING_DIMS, or run `agentroot embed --verify` to re-embed",
+            ));
+        }
+    }
+
+    if findings.is_empty() {
+        findings.push(Finding::ok("embeddings", "Vector sizes match their models"));
+    }
+    Ok(findings)
+}
+
+fn check_collections(db: &Database) -> Result<Vec<Finding>> {
+    let collections = db.list_collections()?;
+    if collections.is_empty() {
+        return Ok(vec![Finding::problem(
+            "collections",
+            Severity::Warning,
+            "No collections are indexed",
+            "agentroot collection add <path>",
+        )]);
+    }
+
+    let mut findings = Vec::new();
+    for coll in &collections {
+        if coll.provider_type == "file" && !std::path::Path::new(&coll.path).exists() {
+            findings.push(Finding::problem(
+                "collections",
+                Severity::Error,
+                format!(
+                    "'{}' points at {}, which no longer exists",
+                    coll.name, coll.path
+                ),
+                format!(
+                    "Restore the directory, or run `agentroot collection remove {}`",
+                    coll.name
+                ),
+            ));
+            continue;
+        }
+        if coll.document_count == 0 {
+            findings.push(Finding::problem(
+                "collections",
+                Severity::Warning,
+                format!("'{}' has no documents", coll.name),
+                format!(
+                    "Check its pattern ({}) and run `agentroot update`",
+                    coll.pattern
+                ),
+            ));
+            continue;
+        }
+        let age = DateTime::parse_from_rfc3339(&coll.updated_at)
+            .map(|updated| Utc::now().signed_duration_since(updated).num_days())
+            .unwrap_or(0);
+        if age >= STALE_AFTER_DAYS {
+            findings.push(Finding::problem(
+                "collections",
+                Severity::Warning,
+                format!("'{}' was last updated {} days ago", coll.name, age),
+                "agentroot update",
+            ));
+        }
+    }
+
+    if findings.is_empty() {
+        findings.push(Finding::ok(
+            "collections",
+            format!("{} collection(s) up to date", collections.len()),
+        ));
+    }
+    Ok(findings)
+}
+
+/// The endpoints this environment would call, by role
+fn configured_endpoints() -> Vec<(&'static str, String)> {
+    let config = LLMServiceConfig::from_env_or_config();
+    let mut endpoints = Vec::new();
+
+    // Without a configured service the defaults are not expected to answer
+    let llm_configured = std::env::var("AGENTROOT_LLM_URL").is_ok()
+        || std::env::var("AGENTROOT_LLM_PROVIDER").is_ok()
+        || Config::default_path().exists();
+    if llm_configured {
+        endpoints.push(("llm", config.url.clone()));
+    }
+    match &config.embedding_url {
+        Some(url) if !endpoints.iter().any(|(_, u)| u == url) => {
+            endpoints.push(("embeddings", url.clone()));
+        }
+        _ => {}
+    }
+    endpoints
+}
+
+async fn check_endpoints_reachable() -> Vec<Finding> {
+    let endpoints = configured_endpoints();
+    if endpoints.is_empty() {
+        return vec![Finding::ok(
+            "endpoints",
+            "No LLM service configured; embeddings run locally",
+        )];
+    }
+
+    let client = match reqwest::Client::builder().timeout(ENDPOINT_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            return vec![Finding::problem(
+                "endpoints",
+                Severity::Error,
+                format!("Could not create an HTTP client: {}", e),
+                "Check the TLS setup of this machine",
+            )]
+        }
+    };
+
+    let mut findings = Vec::new();
+    for (role, url) in endpoints {
+        // Any HTTP answer, even an error status, means the service is up
+        let finding = match client.get(&url).send().await {
+            Ok(response) => Finding::ok(
+                "endpoints",
+                format!(
+                    "{} service at {} answered ({})",
+                    role,
+                    url,
+                    response.status()
+                ),
+            ),
+            Err(e) => Finding::problem(
+                "endpoints",
+                Severity::Error,
+                format!("{} service at {} is unreachable: {}", role, url, e),
+                format!(
+                    "Start the service, or point {} at a running one",
+                    if role == "llm" {
+                        "AGENTROOT_LLM_URL"
+                    } else {
+                        "AGENTROOT_EMBEDDING_URL"
+                    }
+                ),
+            ),
+        };
+        findings.push(finding);
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_missing_collection_directory_is_an_error() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.add_collection(
+            "gone",
+            "/nonexistent/agentroot-doctor",
+            "**/*.md",
+            "file",
+            None,
+        )
+        .unwrap();
+
+        let findings = diagnose(&db, false).await.unwrap();
+        let integrity = findings.iter().find(|f| f.check == "integrity").unwrap();
+        assert_eq!(integrity.severity, Severity::Ok);
+        let gone = findings.iter().find(|f| f.check == "collections").unwrap();
+        assert_eq!(gone.severity, Severity::Error);
+        assert!(gone
+            .fix
+            .as_deref()
+            .unwrap()
+            .contains("collection remove gone"));
+        assert!(!findings.iter().any(|f| f.check == "endpoints"));
+    }
+}
@@ -14,13 +14,29 @@ pub mod exit_codes {
     pub const GENERAL_ERROR: i32 = 1;
     pub const NOT_FOUND: i32 = 2;
     pub const INVALID_INPUT: i32 = 3;
+    pub const BUSY: i32 = 4;
 }
 
+/// Reason given for writes rejected by a read-only index
+pub(crate) const READ_ONLY_MESSAGE: &str = "writes are disabled (--read-only)";
+
 /// Main error type for agentroot
 #[derive(Debug, Error)]
 pub enum AgentRootError {
     #[error("Database error: {0}")]
-    Database(#[from] rusqlite::Error),
+    Database(rusqlite::Error),
+
+    /// Another writer held the database lock past the write timeout
+    #[error("Index busy: {0}")]
+    Busy(String),
+
+    /// The caller cancelled the operation before it finished
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
+
+    /// A write was attempted on an index opened read-only
+    #[error("Read-only index: {0}")]
+    ReadOnly(String),
 
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
@@ -28,6 +44,9 @@ pub enum AgentRootError {
     #[error("Walk directory error: {0}")]
     WalkDir(#[from] walkdir::Error),
 
+    #[error("File watch error: {0}")]
+    Watch(#[from] notify::Error),
+
     #[error("Collection not found: {0}")]
     CollectionNotFound(String),
 
@@ -89,11 +108,27 @@ impl AgentRootError {
         match self {
             Self::CollectionNotFound(_) | Self::DocumentNotFound(_) => exit_codes::NOT_FOUND,
             Self::InvalidVirtualPath(_) | Self::Config(_) => exit_codes::INVALID_INPUT,
+            Self::Busy(_) => exit_codes::BUSY,
             _ => exit_codes::GENERAL_ERROR,
         }
     }
 }
 
+impl From<rusqlite::Error> for AgentRootError {
+    fn from(err: rusqlite::Error) -> Self {
+        match err.sqlite_error_code() {
+            Some(rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked) => {
+                Self::Busy(format!(
+                    "another process is writing to the index ({}); retry once it finishes",
+                    err
+                ))
+            }
+            Some(rusqlite::ErrorCode::ReadOnly) => Self::ReadOnly(READ_ONLY_MESSAGE.to_string()),
+            _ => Self::Database(err),
+        }
+    }
+}
+
 impl From<csv::Error> for AgentRootError {
     fn from(err: csv::Error) -> Self {
         Self::Csv(err.to_string())
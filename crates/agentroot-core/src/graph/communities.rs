@@ -0,0 +1,108 @@
+//! Community detection over weighted document graphs
+
+use std::collections::{BTreeMap, HashMap};
+
+/// Maximum label propagation rounds before giving up on convergence
+const MAX_ROUNDS: usize = 30;
+
+/// Detect communities with weighted label propagation
+///
+/// Every node starts in its own community and repeatedly adopts the label
+/// carrying the most edge weight among its neighbors. Nodes are visited in
+/// id order and ties go to the smallest label, so results are deterministic.
+/// Returns node id -> community label (the label is a member node id).
+pub fn detect_communities(nodes: &[i64], edges: &[(i64, i64, f64)]) -> HashMap<i64, i64> {
+    let mut adjacency: BTreeMap<i64, HashMap<i64, f64>> =
+        nodes.iter().map(|n| (*n, HashMap::new())).collect();
+
+    for (a, b, weight) in edges {
+        if a == b || *weight <= 0.0 {
+            continue;
+        }
+        if !adjacency.contains_key(a) || !adjacency.contains_key(b) {
+            continue;
+        }
+        *adjacency.get_mut(a).unwrap().entry(*b).or_insert(0.0) += weight;
+        *adjacency.get_mut(b).unwrap().entry(*a).or_insert(0.0) += weight;
+    }
+
+    let mut labels: HashMap<i64, i64> = adjacency.keys().map(|n| (*n, *n)).collect();
+
+    for _ in 0..MAX_ROUNDS {
+        let mut changed = false;
+
+        for (node, neighbors) in &adjacency {
+            if neighbors.is_empty() {
+                continue;
+            }
+
+            let mut votes: BTreeMap<i64, f64> = BTreeMap::new();
+            for (neighbor, weight) in neighbors {
+                *votes.entry(labels[neighbor]).or_insert(0.0) += weight;
+            }
+
+            let current = labels[node];
+            let best = votes
+                .iter()
+                .fold(None::<(i64, f64)>, |best, (label, weight)| match best {
+                    Some((_, w)) if w >= *weight => best,
+                    _ => Some((*label, *weight)),
+                })
+                .map(|(label, _)| label)
+                .unwrap_or(current);
+
+            // Keep the current label when it is tied with the winner
+            if best != current && votes.get(&current) != votes.get(&best) {
+                labels.insert(*node, best);
+                changed = true;
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_cliques_with_weak_bridge() {
+        let nodes = vec![1, 2, 3, 4, 5, 6];
+        let edges = vec![
+            (1, 2, 1.0),
+            (2, 3, 1.0),
+            (1, 3, 1.0),
+            (4, 5, 1.0),
+            (5, 6, 1.0),
+            (4, 6, 1.0),
+            (3, 4, 0.1),
+        ];
+
+        let labels = detect_communities(&nodes, &edges);
+
+        assert_eq!(labels[&1], labels[&2]);
+        assert_eq!(labels[&2], labels[&3]);
+        assert_eq!(labels[&4], labels[&5]);
+        assert_eq!(labels[&5], labels[&6]);
+        assert_ne!(labels[&1], labels[&4]);
+    }
+
+    #[test]
+    fn test_isolated_nodes_keep_own_label() {
+        let labels = detect_communities(&[1, 2, 3], &[(1, 2, 1.0)]);
+        assert_eq!(labels[&1], labels[&2]);
+        assert_eq!(labels[&3], 3);
+    }
+
+    #[test]
+    fn test_edges_to_unknown_nodes_ignored() {
+        let labels = detect_communities(&[1], &[(1, 99, 1.0)]);
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[&1], 1);
+    }
+}
@@ -13,14 +13,16 @@ pub struct DocumentLink {
 #[derive(Debug, Clone, Copy)]
 pub enum LinkType {
     MarkdownLink,
-    CodeImport,
+    WikiLink,
+    Imports,
 }
 
 impl LinkType {
     pub fn as_str(&self) -> &'static str {
         match self {
             LinkType::MarkdownLink => "markdown_link",
-            LinkType::CodeImport => "code_import",
+            LinkType::WikiLink => "wikilink",
+            LinkType::Imports => "imports",
         }
     }
 }
@@ -34,11 +36,23 @@ pub fn extract_links(content: &str, source_path: &str, collection_path: &str) ->
         source_path,
         collection_path,
     ));
+    links.extend(extract_wikilinks(content));
     links.extend(extract_code_imports(content, source_path, collection_path));
 
     links
 }
 
+/// Extract references written by hand (markdown links and wikilinks), skipping code imports
+pub fn extract_reference_links(
+    content: &str,
+    source_path: &str,
+    collection_path: &str,
+) -> Vec<DocumentLink> {
+    let mut links = extract_markdown_links(content, source_path, collection_path);
+    links.extend(extract_wikilinks(content));
+    links
+}
+
 /// Extract markdown-style links: [text](path)
 fn extract_markdown_links(
     content: &str,
@@ -51,16 +65,24 @@ fn extract_markdown_links(
 
     for cap in re.captures_iter(content) {
         if let Some(target) = cap.get(2) {
-            let target_str = target.as_str();
-
-            if target_str.starts_with("http://") || target_str.starts_with("https://") {
+            // Drop optional link title and angle brackets: [a](<b.md> "Title")
+            let raw = target.as_str().trim();
+            let target_str = match raw.strip_prefix('<') {
+                Some(rest) => rest.split('>').next().unwrap_or(""),
+                None => raw.split_whitespace().next().unwrap_or(""),
+            };
+
+            if target_str.is_empty() || target_str.starts_with('#') {
                 continue;
             }
 
-            if target_str.starts_with('#') {
+            // Skip external URLs and other schemes (mailto:, tel:, ...)
+            if target_str.contains("://") || is_uri_scheme(target_str) {
                 continue;
             }
 
+            let target_str = target_str.split(['#', '?']).next().unwrap_or(target_str);
+
             if let Some(normalized) = normalize_path(target_str, source_path, collection_path) {
                 links.push(DocumentLink {
                     link_type: LinkType::MarkdownLink,
@@ -73,85 +95,304 @@ fn extract_markdown_links(
     links
 }
 
-/// Extract code imports (Rust, Python, JavaScript)
+fn is_uri_scheme(target: &str) -> bool {
+    target.split_once(':').is_some_and(|(scheme, _)| {
+        scheme.len() > 1
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '+')
+    })
+}
+
+/// Extract wikilinks: [[Note]], [[Note|alias]], [[Note#Heading]], ![[embed.png]]
+///
+/// Wikilinks name a note rather than a path, so targets are left unresolved
+/// (with `.md` appended when no extension is given) for the caller to match
+/// against document paths.
+fn extract_wikilinks(content: &str) -> Vec<DocumentLink> {
+    let re = Regex::new(r"\[\[([^\[\]\n]+)\]\]").expect("Invalid regex");
+
+    re.captures_iter(content)
+        .filter_map(|cap| {
+            let target = cap[1].split(['|', '#', '^']).next()?.trim();
+            if target.is_empty() {
+                return None;
+            }
+            let target_path = if Path::new(target).extension().is_some() {
+                target.to_string()
+            } else {
+                format!("{}.md", target)
+            };
+            Some(DocumentLink {
+                link_type: LinkType::WikiLink,
+                target_path,
+            })
+        })
+        .collect()
+}
+
+/// Extract code dependencies (Rust, Python, JavaScript/TypeScript, C/C++)
+///
+/// Imports are resolved to candidate file paths; a module may live in several
+/// places (e.g. `foo.rs` or `foo/mod.rs`), so one import can yield several
+/// candidates. Only candidates that exist in the index become graph edges.
 fn extract_code_imports(
     content: &str,
     source_path: &str,
     collection_path: &str,
 ) -> Vec<DocumentLink> {
-    let mut links = Vec::new();
+    let extension = Path::new(source_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("");
+
+    let targets = match extension {
+        "rs" => extract_rust_imports(content, source_path),
+        "py" => extract_python_imports(content, source_path),
+        "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" => extract_js_imports(content, source_path),
+        "c" | "h" | "cc" | "cpp" | "cxx" | "hpp" | "hh" | "hxx" => {
+            extract_c_includes(content, source_path)
+        }
+        _ => Vec::new(),
+    };
+
+    let mut seen = std::collections::HashSet::new();
+    targets
+        .into_iter()
+        .filter_map(|t| normalize_root_path(&t, collection_path))
+        .filter(|t| t != source_path && seen.insert(t.clone()))
+        .map(|target_path| DocumentLink {
+            link_type: LinkType::Imports,
+            target_path,
+        })
+        .collect()
+}
 
-    if source_path.ends_with(".rs") {
-        links.extend(extract_rust_imports(content, source_path, collection_path));
-    } else if source_path.ends_with(".py") {
-        links.extend(extract_python_imports(
-            content,
-            source_path,
-            collection_path,
-        ));
-    } else if source_path.ends_with(".js") || source_path.ends_with(".ts") {
-        links.extend(extract_js_imports(content, source_path, collection_path));
+/// Directory holding a Rust module's child modules
+fn rust_module_dir(source_path: &str) -> PathBuf {
+    let source = Path::new(source_path);
+    let parent = source.parent().unwrap_or(Path::new(""));
+    match source.file_name().and_then(|f| f.to_str()) {
+        Some("mod.rs") | Some("lib.rs") | Some("main.rs") => parent.to_path_buf(),
+        _ => parent.join(source.file_stem().unwrap_or_default()),
     }
+}
 
-    links
+/// Crate `src` directory containing the source file
+fn rust_crate_root(source_path: &str) -> PathBuf {
+    let source = Path::new(source_path);
+    source
+        .ancestors()
+        .find(|a| a.file_name().is_some_and(|n| n == "src"))
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| source.parent().unwrap_or(Path::new("")).to_path_buf())
 }
 
-fn extract_rust_imports(
-    content: &str,
-    source_path: &str,
-    collection_path: &str,
-) -> Vec<DocumentLink> {
-    let mut links = Vec::new();
+/// Candidate files for a Rust module path relative to a base directory
+fn rust_module_candidates(base: &Path, segments: &[&str]) -> Vec<String> {
+    if segments.is_empty() {
+        return Vec::new();
+    }
+    let module = segments
+        .iter()
+        .fold(base.to_path_buf(), |path, seg| path.join(seg));
+    vec![
+        module.with_extension("rs").to_string_lossy().to_string(),
+        module.join("mod.rs").to_string_lossy().to_string(),
+    ]
+}
+
+fn extract_rust_imports(content: &str, source_path: &str) -> Vec<String> {
+    let mut targets = Vec::new();
+    let module_dir = rust_module_dir(source_path);
 
-    let mod_re = Regex::new(r"mod\s+([a-zA-Z_][a-zA-Z0-9_]*);").expect("Invalid regex");
+    let mod_re = Regex::new(r"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?mod\s+([a-zA-Z_][a-zA-Z0-9_]*)\s*;")
+        .expect("Invalid regex");
     for cap in mod_re.captures_iter(content) {
-        if let Some(module) = cap.get(1) {
-            let module_name = module.as_str();
-            let parent = Path::new(source_path).parent().unwrap_or(Path::new(""));
-            let target = parent.join(format!("{}.rs", module_name));
-
-            if let Some(normalized) =
-                normalize_path(target.to_str().unwrap_or(""), source_path, collection_path)
-            {
-                links.push(DocumentLink {
-                    link_type: LinkType::CodeImport,
-                    target_path: normalized,
-                });
+        targets.extend(rust_module_candidates(&module_dir, &[&cap[1]]));
+    }
+
+    let use_re = Regex::new(r"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?use\s+(crate|super|self)::([^;]+);")
+        .expect("Invalid regex");
+    for cap in use_re.captures_iter(content) {
+        let base = match &cap[1] {
+            "crate" => rust_crate_root(source_path),
+            "super" => module_dir.parent().unwrap_or(Path::new("")).to_path_buf(),
+            _ => module_dir.clone(),
+        };
+
+        for path in expand_use_tree(&cap[2]) {
+            let segments: Vec<&str> = path
+                .split("::")
+                .map(str::trim)
+                .take_while(|s| {
+                    s.chars()
+                        .next()
+                        .is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+                        && *s != "self"
+                })
+                .collect();
+            // The last segment may be an item (fn, const) rather than a module
+            for len in (1..=segments.len()).rev().take(2) {
+                targets.extend(rust_module_candidates(&base, &segments[..len]));
             }
         }
     }
 
-    links
+    targets
 }
 
-fn extract_python_imports(
-    content: &str,
-    _source_path: &str,
-    _collection_path: &str,
-) -> Vec<DocumentLink> {
-    let mut links = Vec::new();
+/// Expand one level of `{...}` grouping in a use path: `a::{b, c::d}` -> `a::b`, `a::c::d`
+fn expand_use_tree(tree: &str) -> Vec<String> {
+    let tree: String = tree.split_whitespace().collect();
+    match (tree.find('{'), tree.rfind('}')) {
+        (Some(open), Some(close)) if open < close => {
+            let prefix = &tree[..open];
+            split_top_level(&tree[open + 1..close])
+                .into_iter()
+                .filter(|item| !item.is_empty())
+                .map(|item| format!("{}{}", prefix, item))
+                .collect()
+        }
+        _ => vec![tree],
+    }
+}
+
+fn split_top_level(items: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in items.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&items[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&items[start..]);
+    parts
+}
+
+fn extract_python_imports(content: &str, source_path: &str) -> Vec<String> {
+    let mut targets = Vec::new();
+    let source_dir = Path::new(source_path).parent().unwrap_or(Path::new(""));
+
+    let module_candidates = |base: &Path, module: &str| -> Vec<String> {
+        let module_path = base.join(module.replace('.', "/"));
+        vec![
+            module_path
+                .with_extension("py")
+                .to_string_lossy()
+                .to_string(),
+            module_path
+                .join("__init__.py")
+                .to_string_lossy()
+                .to_string(),
+        ]
+    };
 
-    let import_re =
-        Regex::new(r"from\s+([a-zA-Z_][a-zA-Z0-9_.]*)\s+import").expect("Invalid regex");
+    let from_re =
+        Regex::new(r"(?m)^\s*from\s+(\.*)([a-zA-Z_][a-zA-Z0-9_.]*)?\s+import\s+([^#\n]+)")
+            .expect("Invalid regex");
+    for cap in from_re.captures_iter(content) {
+        let dots = cap.get(1).map(|m| m.as_str().len()).unwrap_or(0);
+        let module = cap.get(2).map(|m| m.as_str());
+
+        if dots == 0 {
+            if let Some(module) = module {
+                targets.extend(module_candidates(Path::new(""), module));
+            }
+            continue;
+        }
+
+        // Relative import: one dot is the current package, each extra dot goes up
+        let base = (1..dots).fold(source_dir.to_path_buf(), |dir, _| {
+            dir.parent().unwrap_or(Path::new("")).to_path_buf()
+        });
+        match module {
+            Some(module) => targets.extend(module_candidates(&base, module)),
+            None => {
+                for name in cap[3].trim_matches(|c| c == '(' || c == ')').split(',') {
+                    let name = name.split_whitespace().next().unwrap_or("");
+                    if !name.is_empty() && name != "*" {
+                        targets.extend(module_candidates(&base, name));
+                    }
+                }
+            }
+        }
+    }
+
+    let import_re = Regex::new(
+        r"(?m)^\s*import\s+([a-zA-Z_][a-zA-Z0-9_.]*(?:\s*,\s*[a-zA-Z_][a-zA-Z0-9_.]*)*)",
+    )
+    .expect("Invalid regex");
     for cap in import_re.captures_iter(content) {
-        if let Some(module) = cap.get(1) {
-            let module_path = module.as_str().replace('.', "/");
-            links.push(DocumentLink {
-                link_type: LinkType::CodeImport,
-                target_path: format!("{}.py", module_path),
-            });
+        for module in cap[1].split(',') {
+            targets.extend(module_candidates(Path::new(""), module.trim()));
         }
     }
 
-    links
+    targets
 }
 
-fn extract_js_imports(
-    _content: &str,
-    _source_path: &str,
-    _collection_path: &str,
-) -> Vec<DocumentLink> {
-    Vec::new()
+fn extract_js_imports(content: &str, source_path: &str) -> Vec<String> {
+    let mut targets = Vec::new();
+    let source_dir = Path::new(source_path).parent().unwrap_or(Path::new(""));
+
+    let import_re = Regex::new(
+        r#"(?:import|export)\s+(?:[^'"`;]*?\s+from\s+)?['"](\.{1,2}/[^'"]+)['"]|(?:require|import)\s*\(\s*['"](\.{1,2}/[^'"]+)['"]\s*\)"#,
+    )
+    .expect("Invalid regex");
+
+    for cap in import_re.captures_iter(content) {
+        let Some(spec) = cap.get(1).or_else(|| cap.get(2)) else {
+            continue;
+        };
+        let base = source_dir.join(spec.as_str());
+        let base_str = base.to_string_lossy().to_string();
+
+        if Path::new(spec.as_str()).extension().is_some() {
+            targets.push(base_str);
+            continue;
+        }
+        for ext in ["ts", "tsx", "js", "jsx", "mjs"] {
+            targets.push(format!("{}.{}", base_str, ext));
+        }
+        for ext in ["ts", "tsx", "js", "jsx"] {
+            targets.push(
+                base.join(format!("index.{}", ext))
+                    .to_string_lossy()
+                    .to_string(),
+            );
+        }
+    }
+
+    targets
+}
+
+fn extract_c_includes(content: &str, source_path: &str) -> Vec<String> {
+    let mut targets = Vec::new();
+    let source_dir = Path::new(source_path).parent().unwrap_or(Path::new(""));
+
+    // Only quoted includes refer to project files; <...> are system headers
+    let include_re = Regex::new(r#"(?m)^\s*#\s*include\s+"([^"]+)""#).expect("Invalid regex");
+    for cap in include_re.captures_iter(content) {
+        targets.push(source_dir.join(&cap[1]).to_string_lossy().to_string());
+        targets.push(cap[1].to_string());
+    }
+
+    targets
+}
+
+/// Normalize a collection-root-relative path (resolving `.` and `..`)
+fn normalize_root_path(target: &str, _collection_path: &str) -> Option<String> {
+    let normalized = normalize_pathbuf(Path::new(target));
+    let normalized = normalized.to_string_lossy().to_string();
+    (!normalized.is_empty()).then_some(normalized)
 }
 
 /// Normalize a relative path to collection-relative path
@@ -201,12 +442,105 @@ mod tests {
         assert_eq!(links[1].target_path, "path/to/docs/guide.md");
     }
 
+    #[test]
+    fn test_extract_markdown_links_strips_anchors_and_schemes() {
+        let content =
+            "[a](guide.md#setup) [b](<my notes.md> \"Title\") [c](mailto:x@y.z) [d](https://x.io)";
+        let links = extract_markdown_links(content, "doc.md", "/collection");
+
+        assert_eq!(targets(&links), vec!["guide.md", "my notes.md"]);
+    }
+
+    #[test]
+    fn test_extract_wikilinks() {
+        let content =
+            "See [[Project Plan]], [[notes/Daily|today]], [[Ideas#Later]] and ![[diagram.png]]";
+        let links = extract_wikilinks(content);
+
+        assert_eq!(
+            targets(&links),
+            vec![
+                "Project Plan.md",
+                "notes/Daily.md",
+                "Ideas.md",
+                "diagram.png"
+            ]
+        );
+        assert_eq!(links[0].link_type.as_str(), "wikilink");
+    }
+
+    fn targets(links: &[DocumentLink]) -> Vec<&str> {
+        links.iter().map(|l| l.target_path.as_str()).collect()
+    }
+
     #[test]
     fn test_extract_rust_mod() {
-        let content = "mod parser;\nmod scanner;";
-        let links = extract_rust_imports(content, "src/index/mod.rs", "/collection");
+        let content = "mod parser;\npub mod scanner;";
+        let links = extract_code_imports(content, "src/index/mod.rs", "/collection");
+
+        assert_eq!(
+            targets(&links),
+            vec![
+                "src/index/parser.rs",
+                "src/index/parser/mod.rs",
+                "src/index/scanner.rs",
+                "src/index/scanner/mod.rs"
+            ]
+        );
+        assert_eq!(links[0].link_type.as_str(), "imports");
+    }
 
-        assert_eq!(links.len(), 2);
-        assert!(links[0].target_path.ends_with("parser.rs"));
+    #[test]
+    fn test_extract_rust_mod_from_non_mod_file() {
+        let links = extract_code_imports("mod helpers;", "src/db.rs", "/collection");
+        assert_eq!(targets(&links)[0], "src/db/helpers.rs");
+    }
+
+    #[test]
+    fn test_extract_rust_use() {
+        let content = "use crate::db::Database;\nuse super::{content::docid_from_hash, Database};\nuse std::fs;";
+        let links = extract_code_imports(content, "src/db/links.rs", "/collection");
+        let found = targets(&links);
+
+        assert!(found.contains(&"src/db.rs"));
+        assert!(found.contains(&"src/db/mod.rs"));
+        assert!(found.contains(&"src/db/content.rs"));
+        assert!(!found.iter().any(|t| t.contains("std")));
+    }
+
+    #[test]
+    fn test_extract_python_imports() {
+        let content = "import os.path\nfrom pkg.util import helper\nfrom . import sibling\nfrom ..core import base";
+        let links = extract_code_imports(content, "app/sub/main.py", "/collection");
+        let found = targets(&links);
+
+        assert!(found.contains(&"os/path.py"));
+        assert!(found.contains(&"pkg/util.py"));
+        assert!(found.contains(&"pkg/util/__init__.py"));
+        assert!(found.contains(&"app/sub/sibling.py"));
+        assert!(found.contains(&"app/core.py"));
+    }
+
+    #[test]
+    fn test_extract_js_imports() {
+        let content = "import { a } from './util';\nconst b = require('../lib/b.js');\nimport React from 'react';";
+        let links = extract_code_imports(content, "src/app/index.ts", "/collection");
+        let found = targets(&links);
+
+        assert!(found.contains(&"src/app/util.ts"));
+        assert!(found.contains(&"src/app/util/index.js"));
+        assert!(found.contains(&"src/lib/b.js"));
+        assert!(!found.iter().any(|t| t.contains("react")));
+    }
+
+    #[test]
+    fn test_extract_c_includes() {
+        let content = "#include <stdio.h>\n#include \"util/strings.h\"";
+        let links = extract_code_imports(content, "src/main.c", "/collection");
+
+        assert_eq!(
+            targets(&links),
+            vec!["src/util/strings.h", "util/strings.h"]
+        );
     }
 }
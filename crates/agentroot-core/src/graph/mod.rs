@@ -1,7 +1,9 @@
 //! Document graph and PageRank computation
 
+mod communities;
 mod link_extractor;
 mod pagerank;
 
-pub use link_extractor::extract_links;
-pub use pagerank::compute_pagerank;
+pub use communities::detect_communities;
+pub use link_extractor::{extract_links, extract_reference_links, DocumentLink, LinkType};
+pub use pagerank::{compute_pagerank, personalized_pagerank};
@@ -4,6 +4,14 @@ use crate::error::Result;
 use rusqlite::Connection;
 use std::collections::HashMap;
 
+/// Probability of following a link rather than teleporting back to the seeds
+const DAMPING: f64 = 0.85;
+const PPR_MAX_ITERATIONS: usize = 50;
+const PPR_TOLERANCE: f64 = 1e-8;
+
+/// Link hops from the seeds loaded for personalized PageRank
+const PPR_MAX_HOPS: usize = 3;
+
 /// Document type classification for importance weighting
 #[derive(Debug, Clone, Copy)]
 enum DocType {
@@ -62,6 +70,66 @@ pub fn compute_pagerank(conn: &Connection) -> Result<HashMap<i64, f64>> {
     Ok(scores)
 }
 
+/// Personalized PageRank over the document link graph
+///
+/// Random walks restart at the `seeds` (document id -> weight) instead of a
+/// uniform distribution, so scores reflect centrality relative to the seeds.
+/// Only links within `PPR_MAX_HOPS` of the seeds are loaded, so the cost
+/// follows the size of that neighbourhood rather than the whole graph; walks
+/// reaching its edge restart at the seeds. Returns only documents with
+/// non-zero rank.
+pub fn personalized_pagerank(
+    conn: &Connection,
+    seeds: &HashMap<i64, f64>,
+) -> Result<HashMap<i64, f64>> {
+    let total: f64 = seeds.values().filter(|w| **w > 0.0).sum();
+    if total <= 0.0 {
+        return Ok(HashMap::new());
+    }
+    let teleport: HashMap<i64, f64> = seeds
+        .iter()
+        .filter(|(_, w)| **w > 0.0)
+        .map(|(id, w)| (*id, w / total))
+        .collect();
+
+    let outgoing_links = neighbourhood_links(conn, teleport.keys().copied(), PPR_MAX_HOPS)?;
+    let mut rank = teleport.clone();
+
+    for _ in 0..PPR_MAX_ITERATIONS {
+        let mut next: HashMap<i64, f64> = HashMap::new();
+        let mut dangling = 0.0;
+
+        for (node, score) in &rank {
+            match outgoing_links.get(node) {
+                Some(targets) if !targets.is_empty() => {
+                    let share = DAMPING * score / targets.len() as f64;
+                    for target in targets {
+                        *next.entry(*target).or_insert(0.0) += share;
+                    }
+                }
+                _ => dangling += score,
+            }
+        }
+
+        // Teleport plus mass from dangling nodes both return to the seeds
+        let restart = (1.0 - DAMPING) + DAMPING * dangling;
+        for (node, weight) in &teleport {
+            *next.entry(*node).or_insert(0.0) += restart * weight;
+        }
+
+        let delta: f64 = next
+            .iter()
+            .map(|(node, score)| (score - rank.get(node).unwrap_or(&0.0)).abs())
+            .sum();
+        rank = next;
+        if delta < PPR_TOLERANCE {
+            break;
+        }
+    }
+
+    Ok(rank)
+}
+
 fn get_document_data(conn: &Connection) -> Result<Vec<(i64, String)>> {
     let mut stmt = conn.prepare("SELECT id, path FROM documents WHERE active = 1")?;
     let docs: Vec<(i64, String)> = stmt
@@ -82,6 +150,41 @@ fn build_incoming_links(conn: &Connection) -> Result<HashMap<i64, Vec<i64>>> {
     Ok(links)
 }
 
+/// Outgoing links between active documents, followed breadth-first from
+/// `start` for up to `hops` links
+fn neighbourhood_links(
+    conn: &Connection,
+    start: impl Iterator<Item = i64>,
+    hops: usize,
+) -> Result<HashMap<i64, Vec<i64>>> {
+    let mut stmt = conn.prepare(
+        "SELECT l.target_id
+         FROM document_links l
+         JOIN documents s ON s.id = l.source_id AND s.active = 1
+         JOIN documents t ON t.id = l.target_id AND t.active = 1
+         WHERE l.source_id = ?1",
+    )?;
+    let mut links: HashMap<i64, Vec<i64>> = HashMap::new();
+    let mut frontier: Vec<i64> = start.collect();
+
+    for _ in 0..hops {
+        let mut next = Vec::new();
+        for source in frontier {
+            if links.contains_key(&source) {
+                continue;
+            }
+            let targets = stmt
+                .query_map([source], |row| row.get(0))?
+                .collect::<std::result::Result<Vec<i64>, _>>()?;
+            next.extend(targets.iter().copied());
+            links.insert(source, targets);
+        }
+        frontier = next;
+    }
+
+    Ok(links)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -119,4 +222,92 @@ mod tests {
         let score = scores.values().next().unwrap();
         assert!(*score > 0.0, "Score should be positive");
     }
+
+    #[test]
+    fn test_personalized_pagerank_follows_seed_links() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+
+        for (hash, path) in [("h1", "a.md"), ("h2", "b.md"), ("h3", "c.md")] {
+            db.conn
+                .execute(
+                    "INSERT INTO content (hash, doc, created_at) VALUES (?1, '', '2024-01-01')",
+                    [hash],
+                )
+                .unwrap();
+            db.conn
+                .execute(
+                    "INSERT INTO documents (collection, path, title, hash, created_at, modified_at)
+                 VALUES ('test', ?1, ?1, ?2, '2024-01-01', '2024-01-01')",
+                    [path, hash],
+                )
+                .unwrap();
+        }
+        // a -> b, c is isolated
+        db.conn
+            .execute(
+                "INSERT INTO document_links (source_id, target_id, link_type, created_at)
+             VALUES (1, 2, 'markdown_link', '2024-01-01')",
+                [],
+            )
+            .unwrap();
+
+        let seeds = HashMap::from([(1, 1.0)]);
+        let ranks = personalized_pagerank(&db.conn, &seeds).unwrap();
+
+        assert!(ranks[&1] > 0.0);
+        assert!(ranks[&2] > 0.0);
+        assert!(!ranks.contains_key(&3));
+        let total: f64 = ranks.values().sum();
+        assert!((total - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_personalized_pagerank_stays_near_the_seeds() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+
+        // A chain 1 -> 2 -> ... -> 6
+        for i in 1..=6 {
+            let hash = format!("h{}", i);
+            db.conn
+                .execute(
+                    "INSERT INTO content (hash, doc, created_at) VALUES (?1, '', '2024-01-01')",
+                    [&hash],
+                )
+                .unwrap();
+            db.conn
+                .execute(
+                    "INSERT INTO documents (collection, path, title, hash, created_at, modified_at)
+                 VALUES ('test', ?1, ?1, ?2, '2024-01-01', '2024-01-01')",
+                    [format!("{}.md", i), hash],
+                )
+                .unwrap();
+        }
+        for i in 1..6 {
+            db.conn
+                .execute(
+                    "INSERT INTO document_links (source_id, target_id, link_type, created_at)
+                 VALUES (?1, ?2, 'markdown_link', '2024-01-01')",
+                    [i, i + 1],
+                )
+                .unwrap();
+        }
+
+        let ranks = personalized_pagerank(&db.conn, &HashMap::from([(1, 1.0)])).unwrap();
+        let mut reached: Vec<i64> = ranks.keys().copied().collect();
+        reached.sort();
+        assert_eq!(reached, vec![1, 2, 3, 4]);
+        let total: f64 = ranks.values().sum();
+        assert!((total - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_personalized_pagerank_no_seeds() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+
+        let ranks = personalized_pagerank(&db.conn, &HashMap::new()).unwrap();
+        assert!(ranks.is_empty());
+    }
 }
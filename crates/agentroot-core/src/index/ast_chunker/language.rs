@@ -11,6 +11,10 @@ pub enum Language {
     TypeScript,
     TypeScriptTsx,
     Go,
+    Java,
+    C,
+    Cpp,
+    CSharp,
 }
 
 impl Language {
@@ -22,6 +26,10 @@ impl Language {
             Self::TypeScript => "typescript",
             Self::TypeScriptTsx => "tsx",
             Self::Go => "go",
+            Self::Java => "java",
+            Self::C => "c",
+            Self::Cpp => "cpp",
+            Self::CSharp => "csharp",
         }
     }
 
@@ -40,6 +48,11 @@ impl Language {
             "ts" | "mts" | "cts" => Some(Self::TypeScript),
             "tsx" => Some(Self::TypeScriptTsx),
             "go" => Some(Self::Go),
+            "java" => Some(Self::Java),
+            // Headers are parsed as C++, a superset close enough for C
+            "c" => Some(Self::C),
+            "h" | "hh" | "hpp" | "hxx" | "cc" | "cpp" | "cxx" | "c++" | "ipp" => Some(Self::Cpp),
+            "cs" => Some(Self::CSharp),
             _ => None,
         }
     }
@@ -111,6 +124,27 @@ mod tests {
         assert_eq!(Language::from_path(Path::new("foo.go")), Some(Language::Go));
     }
 
+    #[test]
+    fn test_jvm_and_c_family_detection() {
+        assert_eq!(
+            Language::from_path(Path::new("Main.java")),
+            Some(Language::Java)
+        );
+        assert_eq!(Language::from_path(Path::new("main.c")), Some(Language::C));
+        assert_eq!(
+            Language::from_path(Path::new("util.h")),
+            Some(Language::Cpp)
+        );
+        assert_eq!(
+            Language::from_path(Path::new("engine.cpp")),
+            Some(Language::Cpp)
+        );
+        assert_eq!(
+            Language::from_path(Path::new("Program.cs")),
+            Some(Language::CSharp)
+        );
+    }
+
     #[test]
     fn test_unsupported() {
         assert_eq!(Language::from_path(Path::new("foo.md")), None);
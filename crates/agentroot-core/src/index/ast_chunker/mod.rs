@@ -7,16 +7,19 @@
 pub mod language;
 pub mod oversized;
 pub mod parser;
+pub mod references;
 pub mod strategies;
 pub mod types;
 
 pub use language::{is_supported, Language};
 pub use oversized::{split_oversized_chunk, split_oversized_chunks};
 pub use strategies::{
-    ChunkingStrategy, GoStrategy, JavaScriptStrategy, LanguageStrategy, PythonStrategy,
-    RustStrategy,
+    CSharpStrategy, CStrategy, ChunkingStrategy, GoStrategy, JavaScriptStrategy, JavaStrategy,
+    LanguageStrategy, PythonStrategy, RustStrategy,
+};
+pub use types::{
+    compute_chunk_hash, ChunkMetadata, ChunkType, ReferenceKind, SemanticChunk, SymbolReference,
 };
-pub use types::{compute_chunk_hash, ChunkMetadata, ChunkType, SemanticChunk};
 
 use super::chunker::{chunk_by_chars, Chunk, CHUNK_OVERLAP_CHARS, CHUNK_SIZE_CHARS};
 use crate::error::Result;
@@ -79,7 +82,8 @@ impl SemanticChunker {
 
         let strategy = LanguageStrategy::for_language(language);
         let chunks = strategy.extract_chunks(content, tree.root_node())?;
-        let chunks = split_oversized_chunks(chunks, self.max_chunk_chars);
+        let mut chunks = split_oversized_chunks(chunks, self.max_chunk_chars);
+        references::attach_references(&mut chunks, content, tree.root_node());
 
         Ok(chunks)
     }
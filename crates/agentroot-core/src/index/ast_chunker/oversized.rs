@@ -78,6 +78,7 @@ pub fn split_oversized_chunk(chunk: SemanticChunk, max_chars: usize) -> Vec<Sema
                 language: chunk.metadata.language,
                 start_line,
                 end_line,
+                references: Vec::new(),
             },
         });
 
@@ -26,6 +26,10 @@ fn get_tree_sitter_language(language: Language) -> TsLanguage {
         Language::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
         Language::TypeScriptTsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
         Language::Go => tree_sitter_go::LANGUAGE.into(),
+        Language::Java => tree_sitter_java::LANGUAGE.into(),
+        Language::C => tree_sitter_c::LANGUAGE.into(),
+        Language::Cpp => tree_sitter_cpp::LANGUAGE.into(),
+        Language::CSharp => tree_sitter_c_sharp::LANGUAGE.into(),
     }
 }
 
@@ -67,4 +71,22 @@ mod tests {
         let tree = parse(source, Language::Go).unwrap();
         assert_eq!(tree.root_node().kind(), "source_file");
     }
+
+    #[test]
+    fn test_parse_java() {
+        let source = "class Main { void run() {} }";
+        let tree = parse(source, Language::Java).unwrap();
+        assert_eq!(tree.root_node().kind(), "program");
+    }
+
+    #[test]
+    fn test_parse_c_family() {
+        let source = "int main(void) { return 0; }";
+        for language in [Language::C, Language::Cpp] {
+            let tree = parse(source, language).unwrap();
+            assert_eq!(tree.root_node().kind(), "translation_unit");
+        }
+        let tree = parse("class Main { void Run() {} }", Language::CSharp).unwrap();
+        assert_eq!(tree.root_node().kind(), "compilation_unit");
+    }
 }
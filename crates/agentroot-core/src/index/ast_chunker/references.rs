@@ -0,0 +1,254 @@
+//! Symbol references for the cross-reference index
+//!
+//! Collects call targets and type names from the syntax tree and attaches
+//! each to the innermost chunk containing it, so an impl block does not also
+//! claim the calls made by its methods.
+
+use super::types::{ReferenceKind, SemanticChunk, SymbolReference};
+use std::collections::HashSet;
+use tree_sitter::{Node, TreeCursor};
+
+/// Call-like nodes and the field holding what they call
+const CALL_NODES: &[(&str, &str)] = &[
+    ("call_expression", "function"), // Rust, JavaScript, TypeScript, Go, C, C++
+    ("call", "function"),            // Python
+    ("new_expression", "constructor"), // JavaScript, TypeScript
+    ("new_expression", "type"),      // C++
+    ("macro_invocation", "macro"),   // Rust
+    ("method_invocation", "name"),   // Java
+    ("invocation_expression", "function"), // C#
+    ("object_creation_expression", "type"), // Java, C#
+];
+
+/// Fields leading to the last name of a path, member access or generic
+const NAME_FIELDS: &[&str] = &["name", "field", "property", "attribute", "function", "type"];
+
+/// Names shorter than this (generic parameters, loop variables) are skipped
+const MIN_NAME_LEN: usize = 2;
+
+/// Attach the references found in `root` to the chunks covering them
+pub fn attach_references(chunks: &mut [SemanticChunk], source: &str, root: Node) {
+    let mut found = Vec::new();
+    let mut cursor = root.walk();
+    collect(source, &mut cursor, &mut found);
+    found.retain(|(_, name, _)| name.chars().count() >= MIN_NAME_LEN);
+    if found.is_empty() {
+        return;
+    }
+
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let mut seen: Vec<HashSet<(String, ReferenceKind)>> = vec![HashSet::new(); chunks.len()];
+
+    for (offset, name, kind) in found {
+        let owner = chunks
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.position <= offset && offset < c.position + c.text.len())
+            .min_by_key(|(_, c)| c.text.len())
+            .map(|(i, _)| i);
+        let Some(owner) = owner else {
+            continue;
+        };
+        if seen[owner].insert((name.clone(), kind)) {
+            let line = line_starts.partition_point(|&start| start <= offset);
+            chunks[owner]
+                .metadata
+                .references
+                .push(SymbolReference { name, kind, line });
+        }
+    }
+}
+
+fn collect(source: &str, cursor: &mut TreeCursor, found: &mut Vec<(usize, String, ReferenceKind)>) {
+    loop {
+        let node = cursor.node();
+
+        let call_target = CALL_NODES
+            .iter()
+            .filter(|(kind, _)| *kind == node.kind())
+            .find_map(|(_, field)| node.child_by_field_name(field));
+        if let Some(target) = call_target {
+            if let Some(name) = target_name(source, target) {
+                found.push((node.start_byte(), name, ReferenceKind::Call));
+            }
+        } else if node.kind() == "type_identifier" && !is_definition_name(node) {
+            found.push((
+                node.start_byte(),
+                source[node.byte_range()].to_string(),
+                ReferenceKind::Type,
+            ));
+        }
+
+        if cursor.goto_first_child() {
+            collect(source, cursor, found);
+            cursor.goto_parent();
+        }
+        if !cursor.goto_next_sibling() {
+            break;
+        }
+    }
+}
+
+/// The rightmost identifier of a call target (`new` in `Parser::new`)
+fn target_name(source: &str, node: Node) -> Option<String> {
+    if node.kind().ends_with("identifier") && node.named_child_count() == 0 {
+        return Some(source[node.byte_range()].to_string());
+    }
+    NAME_FIELDS
+        .iter()
+        .find_map(|field| node.child_by_field_name(field))
+        .and_then(|child| target_name(source, child))
+}
+
+/// Whether a type identifier names the item being declared rather than a use
+fn is_definition_name(node: Node) -> bool {
+    node.parent()
+        .and_then(|parent| parent.child_by_field_name("name"))
+        .is_some_and(|name| name.id() == node.id())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{chunk_semantic, ChunkType};
+    use super::*;
+    use std::path::Path;
+
+    fn refs(chunk: &SemanticChunk) -> Vec<(&str, ReferenceKind)> {
+        chunk
+            .metadata
+            .references
+            .iter()
+            .map(|r| (r.name.as_str(), r.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_rust_references_go_to_innermost_chunk() {
+        let source = r#"struct Parser {
+    input: String,
+}
+
+impl Parser {
+    fn parse(&self) -> Token {
+        let t = Token::new(tokenize(&self.input));
+        self.input.trim();
+        println!("{:?}", t);
+        t
+    }
+}
+"#;
+        let chunks = chunk_semantic(source, Path::new("lib.rs")).unwrap();
+        let parse = chunks
+            .iter()
+            .find(|c| c.metadata.breadcrumb.as_deref() == Some("Parser::parse"))
+            .unwrap();
+        assert_eq!(
+            refs(parse),
+            vec![
+                ("Token", ReferenceKind::Type),
+                ("new", ReferenceKind::Call),
+                ("tokenize", ReferenceKind::Call),
+                ("trim", ReferenceKind::Call),
+                ("println", ReferenceKind::Call),
+            ]
+        );
+        let tokenize = &parse.metadata.references[2];
+        assert_eq!(tokenize.line, 7);
+
+        // The struct's own name is a definition, not a reference
+        let parser = chunks
+            .iter()
+            .find(|c| c.chunk_type == ChunkType::Struct)
+            .unwrap();
+        assert_eq!(refs(parser), vec![("String", ReferenceKind::Type)]);
+
+        // Uses inside the method belong to the method, not the impl block
+        let imp = chunks
+            .iter()
+            .find(|c| c.text.starts_with("impl Parser"))
+            .unwrap();
+        assert_eq!(refs(imp), vec![("Parser", ReferenceKind::Type)]);
+    }
+
+    #[test]
+    fn test_python_and_javascript_calls() {
+        let py = chunk_semantic(
+            "def load(path):\n    return json.loads(read_file(path))\n",
+            Path::new("a.py"),
+        )
+        .unwrap();
+        assert_eq!(
+            refs(&py[0]),
+            vec![
+                ("loads", ReferenceKind::Call),
+                ("read_file", ReferenceKind::Call)
+            ]
+        );
+
+        let js = chunk_semantic(
+            "function start() {\n  const s = new Server(config);\n  s.listen(8080);\n}\n",
+            Path::new("a.js"),
+        )
+        .unwrap();
+        assert_eq!(
+            refs(&js[0]),
+            vec![
+                ("Server", ReferenceKind::Call),
+                ("listen", ReferenceKind::Call)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_java_csharp_and_cpp_calls() {
+        let java = chunk_semantic(
+            "class App {\n  void start() {\n    server.listen(port(8080));\n  }\n}\n",
+            Path::new("App.java"),
+        )
+        .unwrap();
+        let start = java
+            .iter()
+            .find(|c| c.chunk_type == ChunkType::Method)
+            .unwrap();
+        assert_eq!(
+            refs(start),
+            vec![
+                ("listen", ReferenceKind::Call),
+                ("port", ReferenceKind::Call)
+            ]
+        );
+
+        let cs = chunk_semantic(
+            "class App {\n  void Start() {\n    var s = new Server();\n    s.Listen(8080);\n  }\n}\n",
+            Path::new("App.cs"),
+        )
+        .unwrap();
+        let start = cs
+            .iter()
+            .find(|c| c.chunk_type == ChunkType::Method)
+            .unwrap();
+        assert_eq!(
+            refs(start),
+            vec![
+                ("Server", ReferenceKind::Call),
+                ("Listen", ReferenceKind::Call)
+            ]
+        );
+
+        let cpp = chunk_semantic(
+            "void start() {\n  auto s = new Server(config);\n  s->listen(8080);\n}\n",
+            Path::new("app.cpp"),
+        )
+        .unwrap();
+        assert_eq!(
+            refs(&cpp[0]),
+            vec![
+                ("Server", ReferenceKind::Call),
+                ("Server", ReferenceKind::Type),
+                ("listen", ReferenceKind::Call)
+            ]
+        );
+    }
+}
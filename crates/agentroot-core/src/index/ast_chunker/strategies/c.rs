@@ -0,0 +1,309 @@
+//! C/C++-specific chunking strategy
+
+use super::{get_breadcrumb, line_numbers, ChunkingStrategy};
+use crate::error::Result;
+use crate::index::ast_chunker::types::{
+    compute_chunk_hash, ChunkMetadata, ChunkType, SemanticChunk,
+};
+use tree_sitter::Node;
+
+const C_SEMANTIC_NODES: &[&str] = &[
+    "function_definition",
+    "struct_specifier",
+    "union_specifier",
+    "enum_specifier",
+    "class_specifier",
+    "type_definition",
+    "template_declaration",
+];
+
+/// Type specifiers whose bodies hold member functions (C++)
+const C_TYPE_NODES: &[&str] = &["class_specifier", "struct_specifier", "union_specifier"];
+
+pub struct CStrategy {
+    pub is_cpp: bool,
+}
+
+impl CStrategy {
+    pub fn c() -> Self {
+        Self { is_cpp: false }
+    }
+
+    pub fn cpp() -> Self {
+        Self { is_cpp: true }
+    }
+}
+
+impl ChunkingStrategy for CStrategy {
+    fn semantic_node_types(&self) -> &[&str] {
+        C_SEMANTIC_NODES
+    }
+
+    fn extract_chunks(&self, source: &str, root: Node) -> Result<Vec<SemanticChunk>> {
+        let mut chunks = Vec::new();
+        let mut cursor = root.walk();
+        extract_c_chunks(source, &mut cursor, &mut chunks, self, None);
+
+        if chunks.is_empty() {
+            chunks.push(SemanticChunk::new(source.to_string(), ChunkType::Text, 0));
+        }
+
+        Ok(chunks)
+    }
+
+    fn chunk_type_for_node(&self, node: Node) -> ChunkType {
+        match node.kind() {
+            "function_definition" => ChunkType::Function,
+            "struct_specifier" | "union_specifier" => ChunkType::Struct,
+            "enum_specifier" => ChunkType::Enum,
+            "class_specifier" => ChunkType::Class,
+            "type_definition" => node
+                .child_by_field_name("type")
+                .map(|ty| self.chunk_type_for_node(ty))
+                .unwrap_or(ChunkType::Struct),
+            "template_declaration" => template_item(node)
+                .map(|item| self.chunk_type_for_node(item))
+                .unwrap_or(ChunkType::Function),
+            _ => ChunkType::Text,
+        }
+    }
+}
+
+fn extract_c_chunks(
+    source: &str,
+    cursor: &mut tree_sitter::TreeCursor,
+    chunks: &mut Vec<SemanticChunk>,
+    strategy: &CStrategy,
+    parent_type: Option<&str>,
+) {
+    loop {
+        let node = cursor.node();
+        let kind = node.kind();
+
+        if C_SEMANTIC_NODES.contains(&kind) {
+            // Forward declarations and plain typedefs are not worth a chunk
+            if is_definition(node) {
+                push_chunk(source, cursor, chunks, strategy, parent_type);
+            }
+        } else if cursor.goto_first_child() {
+            extract_c_chunks(source, cursor, chunks, strategy, parent_type);
+            cursor.goto_parent();
+        }
+
+        if !cursor.goto_next_sibling() {
+            break;
+        }
+    }
+}
+
+fn push_chunk(
+    source: &str,
+    cursor: &mut tree_sitter::TreeCursor,
+    chunks: &mut Vec<SemanticChunk>,
+    strategy: &CStrategy,
+    parent_type: Option<&str>,
+) {
+    let node = cursor.node();
+    let item = if node.kind() == "template_declaration" {
+        template_item(node).unwrap_or(node)
+    } else {
+        node
+    };
+
+    let leading = strategy.extract_leading_trivia(source, node);
+    let trailing = strategy.extract_trailing_trivia(source, node);
+    let text = source[node.start_byte()..node.end_byte()].to_string();
+    let (start_line, end_line) = line_numbers(source, node.start_byte(), node.end_byte());
+
+    let name = get_c_name(source, item);
+    let breadcrumb = match (parent_type, &name) {
+        (Some(parent), Some(n)) => Some(format!("{}::{}", parent, n)),
+        (None, Some(n)) => Some(n.clone()),
+        _ => get_breadcrumb(source, node),
+    };
+
+    let chunk_type = if parent_type.is_some() && item.kind() == "function_definition" {
+        ChunkType::Method
+    } else {
+        strategy.chunk_type_for_node(node)
+    };
+
+    let chunk_hash = compute_chunk_hash(&text, &leading, &trailing);
+    let lang: &'static str = if strategy.is_cpp { "cpp" } else { "c" };
+
+    chunks.push(SemanticChunk {
+        text,
+        chunk_type,
+        chunk_hash,
+        position: node.start_byte(),
+        token_count: None,
+        metadata: ChunkMetadata {
+            leading_trivia: leading,
+            trailing_trivia: trailing,
+            breadcrumb: breadcrumb.clone(),
+            language: Some(lang),
+            start_line,
+            end_line,
+            references: Vec::new(),
+        },
+    });
+
+    // Member functions defined inside a class body are chunked as well
+    if C_TYPE_NODES.contains(&item.kind()) {
+        if let Some(body) = item.child_by_field_name("body") {
+            let mut body_cursor = body.walk();
+            if body_cursor.goto_first_child() {
+                extract_c_chunks(
+                    source,
+                    &mut body_cursor,
+                    chunks,
+                    strategy,
+                    breadcrumb.as_deref(),
+                );
+            }
+        }
+    }
+}
+
+/// Whether a node defines something rather than only naming it
+fn is_definition(node: Node) -> bool {
+    match node.kind() {
+        "function_definition" => true,
+        "template_declaration" => template_item(node).is_some_and(is_definition),
+        "type_definition" => node
+            .child_by_field_name("type")
+            .is_some_and(|ty| ty.child_by_field_name("body").is_some()),
+        _ => node.child_by_field_name("body").is_some(),
+    }
+}
+
+/// The declaration a `template <...>` applies to
+fn template_item(node: Node) -> Option<Node> {
+    let mut cursor = node.walk();
+    let item = node.named_children(&mut cursor).find(|child| {
+        child.kind() != "template_parameter_list" && !child.kind().contains("comment")
+    });
+    item
+}
+
+fn get_c_name(source: &str, node: Node) -> Option<String> {
+    let name_node = match node.kind() {
+        "function_definition" => function_name(node)?,
+        "type_definition" => node.child_by_field_name("declarator")?,
+        _ => node.child_by_field_name("name")?,
+    };
+    Some(source[name_node.start_byte()..name_node.end_byte()].to_string())
+}
+
+/// Follow the declarator chain (`*`, `&`, parentheses) to the function name,
+/// which is qualified for out-of-class definitions: `Parser::parse`
+fn function_name(node: Node) -> Option<Node> {
+    let mut declarator = node.child_by_field_name("declarator")?;
+    loop {
+        let inner = declarator.child_by_field_name("declarator");
+        if declarator.kind() == "function_declarator" {
+            return inner;
+        }
+        declarator = inner?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::ast_chunker::language::Language;
+    use crate::index::ast_chunker::parser::parse;
+
+    fn chunk(source: &str, language: Language) -> Vec<(ChunkType, String)> {
+        let strategy = match language {
+            Language::Cpp => CStrategy::cpp(),
+            _ => CStrategy::c(),
+        };
+        let tree = parse(source, language).unwrap();
+        strategy
+            .extract_chunks(source, tree.root_node())
+            .unwrap()
+            .into_iter()
+            .map(|c| (c.chunk_type, c.metadata.breadcrumb.unwrap_or_default()))
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_c_definitions() {
+        let source = r#"
+#include <stdio.h>
+
+struct point;
+int *make_point(void);
+
+/* A point on the grid */
+struct point {
+    int x, y;
+};
+
+typedef struct {
+    int r, g, b;
+} color;
+
+enum mode { READ, WRITE };
+
+static int *make_point(void) {
+    return NULL;
+}
+"#;
+        assert_eq!(
+            chunk(source, Language::C),
+            vec![
+                (ChunkType::Struct, "point".to_string()),
+                (ChunkType::Struct, "color".to_string()),
+                (ChunkType::Enum, "mode".to_string()),
+                (ChunkType::Function, "make_point".to_string()),
+            ]
+        );
+
+        let tree = parse(source, Language::C).unwrap();
+        let chunks = CStrategy::c()
+            .extract_chunks(source, tree.root_node())
+            .unwrap();
+        assert_eq!(
+            chunks[0].metadata.leading_trivia,
+            "/* A point on the grid */"
+        );
+        assert_eq!(chunks[0].metadata.language, Some("c"));
+    }
+
+    #[test]
+    fn test_extract_cpp_classes_and_templates() {
+        let source = r#"
+namespace geo {
+
+/// A shape with an area
+class Shape : public Base {
+public:
+    Shape() {}
+    virtual double area() const { return 0; }
+    void scale(double factor);
+};
+
+void Shape::scale(double factor) {}
+
+template <typename T>
+T largest(T a, T b) { return a > b ? a : b; }
+
+enum class Kind { Circle, Square };
+
+}
+"#;
+        assert_eq!(
+            chunk(source, Language::Cpp),
+            vec![
+                (ChunkType::Class, "Shape".to_string()),
+                (ChunkType::Method, "Shape::Shape".to_string()),
+                (ChunkType::Method, "Shape::area".to_string()),
+                (ChunkType::Function, "Shape::scale".to_string()),
+                (ChunkType::Function, "largest".to_string()),
+                (ChunkType::Enum, "Kind".to_string()),
+            ]
+        );
+    }
+}
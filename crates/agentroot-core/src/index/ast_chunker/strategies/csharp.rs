@@ -0,0 +1,225 @@
+//! C#-specific chunking strategy
+
+use super::{get_breadcrumb, line_numbers, ChunkingStrategy};
+use crate::error::Result;
+use crate::index::ast_chunker::types::{
+    compute_chunk_hash, ChunkMetadata, ChunkType, SemanticChunk,
+};
+use tree_sitter::Node;
+
+const CSHARP_SEMANTIC_NODES: &[&str] = &[
+    "class_declaration",
+    "struct_declaration",
+    "interface_declaration",
+    "enum_declaration",
+    "record_declaration",
+    "delegate_declaration",
+    "method_declaration",
+    "constructor_declaration",
+    "destructor_declaration",
+    "operator_declaration",
+];
+
+/// Declarations whose bodies hold further members
+const CSHARP_TYPE_NODES: &[&str] = &[
+    "class_declaration",
+    "struct_declaration",
+    "interface_declaration",
+    "record_declaration",
+];
+
+pub struct CSharpStrategy;
+
+impl ChunkingStrategy for CSharpStrategy {
+    fn semantic_node_types(&self) -> &[&str] {
+        CSHARP_SEMANTIC_NODES
+    }
+
+    fn extract_chunks(&self, source: &str, root: Node) -> Result<Vec<SemanticChunk>> {
+        let mut chunks = Vec::new();
+        let mut cursor = root.walk();
+        extract_csharp_chunks(source, &mut cursor, &mut chunks, self, None);
+
+        if chunks.is_empty() {
+            chunks.push(SemanticChunk::new(source.to_string(), ChunkType::Text, 0));
+        }
+
+        Ok(chunks)
+    }
+
+    fn chunk_type_for_node(&self, node: Node) -> ChunkType {
+        match node.kind() {
+            "class_declaration" | "record_declaration" => ChunkType::Class,
+            "struct_declaration" => ChunkType::Struct,
+            "interface_declaration" => ChunkType::Interface,
+            "enum_declaration" => ChunkType::Enum,
+            "delegate_declaration" => ChunkType::Function,
+            "method_declaration"
+            | "constructor_declaration"
+            | "destructor_declaration"
+            | "operator_declaration" => ChunkType::Method,
+            _ => ChunkType::Text,
+        }
+    }
+}
+
+fn extract_csharp_chunks(
+    source: &str,
+    cursor: &mut tree_sitter::TreeCursor,
+    chunks: &mut Vec<SemanticChunk>,
+    strategy: &CSharpStrategy,
+    parent_type: Option<&str>,
+) {
+    loop {
+        let node = cursor.node();
+        let kind = node.kind();
+
+        if CSHARP_SEMANTIC_NODES.contains(&kind) {
+            let leading = strategy.extract_leading_trivia(source, node);
+            let trailing = strategy.extract_trailing_trivia(source, node);
+            let text = source[node.start_byte()..node.end_byte()].to_string();
+            let (start_line, end_line) = line_numbers(source, node.start_byte(), node.end_byte());
+
+            let name = get_csharp_name(source, node);
+            let breadcrumb = match (parent_type, &name) {
+                (Some(parent), Some(n)) => Some(format!("{}::{}", parent, n)),
+                (None, Some(n)) => Some(n.clone()),
+                _ => get_breadcrumb(source, node),
+            };
+
+            let chunk_hash = compute_chunk_hash(&text, &leading, &trailing);
+
+            let chunk = SemanticChunk {
+                text,
+                chunk_type: strategy.chunk_type_for_node(node),
+                chunk_hash,
+                position: node.start_byte(),
+                token_count: None,
+                metadata: ChunkMetadata {
+                    leading_trivia: leading,
+                    trailing_trivia: trailing,
+                    breadcrumb: breadcrumb.clone(),
+                    language: Some("csharp"),
+                    start_line,
+                    end_line,
+                    references: Vec::new(),
+                },
+            };
+            chunks.push(chunk);
+
+            // Members (and nested types) of a type are chunked as well
+            if CSHARP_TYPE_NODES.contains(&kind) && cursor.goto_first_child() {
+                extract_csharp_chunks(source, cursor, chunks, strategy, breadcrumb.as_deref());
+                cursor.goto_parent();
+            }
+        } else if cursor.goto_first_child() {
+            extract_csharp_chunks(source, cursor, chunks, strategy, parent_type);
+            cursor.goto_parent();
+        }
+
+        if !cursor.goto_next_sibling() {
+            break;
+        }
+    }
+}
+
+fn get_csharp_name(source: &str, node: Node) -> Option<String> {
+    if let Some(name_node) = node.child_by_field_name("name") {
+        let name = &source[name_node.start_byte()..name_node.end_byte()];
+        return Some(match node.kind() {
+            "destructor_declaration" => format!("~{}", name),
+            _ => name.to_string(),
+        });
+    }
+    // Operators are named by their symbol: `operator +`
+    node.child_by_field_name("operator")
+        .map(|op| format!("operator {}", &source[op.start_byte()..op.end_byte()]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::ast_chunker::language::Language;
+    use crate::index::ast_chunker::parser::parse;
+
+    #[test]
+    fn test_extract_class_and_members() {
+        let source = r#"
+using System;
+
+namespace App.Services
+{
+    /// <summary>Handles requests.</summary>
+    public class Handler : IHandler
+    {
+        public Handler() { }
+
+        /// <summary>Run one request.</summary>
+        [Obsolete]
+        public string Run(int id) => "ok";
+
+        public int Count { get; set; }
+    }
+}
+"#;
+        let tree = parse(source, Language::CSharp).unwrap();
+        let chunks = CSharpStrategy
+            .extract_chunks(source, tree.root_node())
+            .unwrap();
+
+        let class = chunks
+            .iter()
+            .find(|c| c.chunk_type == ChunkType::Class)
+            .unwrap();
+        assert_eq!(class.metadata.breadcrumb.as_deref(), Some("Handler"));
+        assert!(class.metadata.leading_trivia.contains("Handles requests."));
+
+        let methods: Vec<_> = chunks
+            .iter()
+            .filter(|c| c.chunk_type == ChunkType::Method)
+            .map(|c| c.metadata.breadcrumb.as_deref().unwrap())
+            .collect();
+        assert_eq!(methods, vec!["Handler::Handler", "Handler::Run"]);
+        let run = chunks
+            .iter()
+            .find(|c| c.metadata.breadcrumb.as_deref() == Some("Handler::Run"))
+            .unwrap();
+        assert!(run.metadata.leading_trivia.contains("Run one request."));
+        assert!(run.text.starts_with("[Obsolete]"));
+        assert_eq!(run.metadata.language, Some("csharp"));
+    }
+
+    #[test]
+    fn test_extract_types_in_file_scoped_namespace() {
+        let source = r#"
+namespace App;
+
+public interface IShape { double Area(); }
+
+public struct Point { public int X; }
+
+public enum Kind { Circle, Square }
+
+public record Person(string Name);
+"#;
+        let tree = parse(source, Language::CSharp).unwrap();
+        let chunks = CSharpStrategy
+            .extract_chunks(source, tree.root_node())
+            .unwrap();
+
+        let found: Vec<_> = chunks
+            .iter()
+            .map(|c| (c.chunk_type, c.metadata.breadcrumb.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (ChunkType::Interface, "IShape"),
+                (ChunkType::Method, "IShape::Area"),
+                (ChunkType::Struct, "Point"),
+                (ChunkType::Enum, "Kind"),
+                (ChunkType::Class, "Person"),
+            ]
+        );
+    }
+}
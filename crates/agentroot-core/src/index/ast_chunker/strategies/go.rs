@@ -92,6 +92,7 @@ fn extract_go_chunks(
                     language: Some("go"),
                     start_line,
                     end_line,
+                    references: Vec::new(),
                 },
             };
             chunks.push(chunk);
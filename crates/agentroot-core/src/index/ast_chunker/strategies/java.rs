@@ -0,0 +1,210 @@
+//! Java-specific chunking strategy
+
+use super::{get_breadcrumb, line_numbers, ChunkingStrategy};
+use crate::error::Result;
+use crate::index::ast_chunker::types::{
+    compute_chunk_hash, ChunkMetadata, ChunkType, SemanticChunk,
+};
+use tree_sitter::Node;
+
+const JAVA_SEMANTIC_NODES: &[&str] = &[
+    "class_declaration",
+    "interface_declaration",
+    "enum_declaration",
+    "record_declaration",
+    "annotation_type_declaration",
+    "method_declaration",
+    "constructor_declaration",
+];
+
+/// Declarations whose bodies hold further members
+const JAVA_TYPE_NODES: &[&str] = &[
+    "class_declaration",
+    "interface_declaration",
+    "enum_declaration",
+    "record_declaration",
+];
+
+pub struct JavaStrategy;
+
+impl ChunkingStrategy for JavaStrategy {
+    fn semantic_node_types(&self) -> &[&str] {
+        JAVA_SEMANTIC_NODES
+    }
+
+    fn extract_chunks(&self, source: &str, root: Node) -> Result<Vec<SemanticChunk>> {
+        let mut chunks = Vec::new();
+        let mut cursor = root.walk();
+        extract_java_chunks(source, &mut cursor, &mut chunks, self, None);
+
+        if chunks.is_empty() {
+            chunks.push(SemanticChunk::new(source.to_string(), ChunkType::Text, 0));
+        }
+
+        Ok(chunks)
+    }
+
+    fn chunk_type_for_node(&self, node: Node) -> ChunkType {
+        match node.kind() {
+            "class_declaration" => ChunkType::Class,
+            "interface_declaration" | "annotation_type_declaration" => ChunkType::Interface,
+            "enum_declaration" => ChunkType::Enum,
+            "record_declaration" => ChunkType::Struct,
+            "method_declaration" | "constructor_declaration" => ChunkType::Method,
+            _ => ChunkType::Text,
+        }
+    }
+}
+
+fn extract_java_chunks(
+    source: &str,
+    cursor: &mut tree_sitter::TreeCursor,
+    chunks: &mut Vec<SemanticChunk>,
+    strategy: &JavaStrategy,
+    parent_type: Option<&str>,
+) {
+    loop {
+        let node = cursor.node();
+        let kind = node.kind();
+
+        if JAVA_SEMANTIC_NODES.contains(&kind) {
+            let leading = strategy.extract_leading_trivia(source, node);
+            let trailing = strategy.extract_trailing_trivia(source, node);
+            let text = source[node.start_byte()..node.end_byte()].to_string();
+            let (start_line, end_line) = line_numbers(source, node.start_byte(), node.end_byte());
+
+            let name = node
+                .child_by_field_name("name")
+                .map(|n| source[n.start_byte()..n.end_byte()].to_string());
+            let breadcrumb = match (parent_type, &name) {
+                (Some(parent), Some(n)) => Some(format!("{}::{}", parent, n)),
+                (None, Some(n)) => Some(n.clone()),
+                _ => get_breadcrumb(source, node),
+            };
+
+            let chunk_hash = compute_chunk_hash(&text, &leading, &trailing);
+
+            let chunk = SemanticChunk {
+                text,
+                chunk_type: strategy.chunk_type_for_node(node),
+                chunk_hash,
+                position: node.start_byte(),
+                token_count: None,
+                metadata: ChunkMetadata {
+                    leading_trivia: leading,
+                    trailing_trivia: trailing,
+                    breadcrumb: breadcrumb.clone(),
+                    language: Some("java"),
+                    start_line,
+                    end_line,
+                    references: Vec::new(),
+                },
+            };
+            chunks.push(chunk);
+
+            // Members (and nested types) of a type are chunked as well
+            if JAVA_TYPE_NODES.contains(&kind) && cursor.goto_first_child() {
+                extract_java_chunks(source, cursor, chunks, strategy, breadcrumb.as_deref());
+                cursor.goto_parent();
+            }
+        } else if cursor.goto_first_child() {
+            extract_java_chunks(source, cursor, chunks, strategy, parent_type);
+            cursor.goto_parent();
+        }
+
+        if !cursor.goto_next_sibling() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::ast_chunker::language::Language;
+    use crate::index::ast_chunker::parser::parse;
+
+    #[test]
+    fn test_extract_class_and_methods() {
+        let source = r#"
+package app;
+
+/**
+ * Handles requests.
+ */
+@Service
+public class Handler extends Base {
+    private int count;
+
+    public Handler() {
+        super();
+    }
+
+    /** Run one request. */
+    @Override
+    public String run(int id) {
+        return "ok";
+    }
+}
+"#;
+        let tree = parse(source, Language::Java).unwrap();
+        let chunks = JavaStrategy
+            .extract_chunks(source, tree.root_node())
+            .unwrap();
+
+        let class = chunks
+            .iter()
+            .find(|c| c.chunk_type == ChunkType::Class)
+            .unwrap();
+        assert_eq!(class.metadata.breadcrumb.as_deref(), Some("Handler"));
+        assert!(class.metadata.leading_trivia.contains("Handles requests."));
+        assert!(class.text.starts_with("@Service"));
+
+        let methods: Vec<_> = chunks
+            .iter()
+            .filter(|c| c.chunk_type == ChunkType::Method)
+            .map(|c| c.metadata.breadcrumb.as_deref().unwrap())
+            .collect();
+        assert_eq!(methods, vec!["Handler::Handler", "Handler::run"]);
+        let run = chunks
+            .iter()
+            .find(|c| c.metadata.breadcrumb.as_deref() == Some("Handler::run"))
+            .unwrap();
+        assert_eq!(
+            run.metadata.leading_trivia.trim(),
+            "/** Run one request. */"
+        );
+        assert_eq!(run.metadata.language, Some("java"));
+    }
+
+    #[test]
+    fn test_extract_nested_types() {
+        let source = r#"
+interface Shape {
+    double area();
+
+    enum Kind { CIRCLE, SQUARE }
+
+    record Point(int x, int y) {}
+}
+"#;
+        let tree = parse(source, Language::Java).unwrap();
+        let chunks = JavaStrategy
+            .extract_chunks(source, tree.root_node())
+            .unwrap();
+
+        let found: Vec<_> = chunks
+            .iter()
+            .map(|c| (c.chunk_type, c.metadata.breadcrumb.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (ChunkType::Interface, "Shape"),
+                (ChunkType::Method, "Shape::area"),
+                (ChunkType::Enum, "Shape::Kind"),
+                (ChunkType::Struct, "Shape::Point"),
+            ]
+        );
+    }
+}
@@ -137,6 +137,7 @@ fn extract_js_chunks(
                     language: Some(lang),
                     start_line,
                     end_line,
+                    references: Vec::new(),
                 },
             };
             chunks.push(chunk);
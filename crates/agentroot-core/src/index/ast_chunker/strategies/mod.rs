@@ -1,11 +1,17 @@
 //! Language-specific chunking strategies
 
+mod c;
+mod csharp;
 mod go;
+mod java;
 mod javascript;
 mod python;
 mod rust;
 
+pub use c::CStrategy;
+pub use csharp::CSharpStrategy;
 pub use go::GoStrategy;
+pub use java::JavaStrategy;
 pub use javascript::JavaScriptStrategy;
 pub use python::PythonStrategy;
 pub use rust::RustStrategy;
@@ -43,6 +49,9 @@ pub enum LanguageStrategy {
     Python(PythonStrategy),
     JavaScript(JavaScriptStrategy),
     Go(GoStrategy),
+    Java(JavaStrategy),
+    C(CStrategy),
+    CSharp(CSharpStrategy),
 }
 
 impl LanguageStrategy {
@@ -55,6 +64,10 @@ impl LanguageStrategy {
                 Self::JavaScript(JavaScriptStrategy::typescript())
             }
             Language::Go => Self::Go(GoStrategy),
+            Language::Java => Self::Java(JavaStrategy),
+            Language::C => Self::C(CStrategy::c()),
+            Language::Cpp => Self::C(CStrategy::cpp()),
+            Language::CSharp => Self::CSharp(CSharpStrategy),
         }
     }
 
@@ -64,6 +77,9 @@ impl LanguageStrategy {
             Self::Python(s) => s.extract_chunks(source, root),
             Self::JavaScript(s) => s.extract_chunks(source, root),
             Self::Go(s) => s.extract_chunks(source, root),
+            Self::Java(s) => s.extract_chunks(source, root),
+            Self::C(s) => s.extract_chunks(source, root),
+            Self::CSharp(s) => s.extract_chunks(source, root),
         }
     }
 }
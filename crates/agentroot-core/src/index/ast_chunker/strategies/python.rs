@@ -120,6 +120,7 @@ fn extract_python_chunks(
                     language: Some("python"),
                     start_line,
                     end_line,
+                    references: Vec::new(),
                 },
             };
             chunks.push(chunk);
@@ -89,6 +89,7 @@ fn extract_rust_chunks(
                     language: Some("rust"),
                     start_line,
                     end_line,
+                    references: Vec::new(),
                 },
             };
             chunks.push(chunk);
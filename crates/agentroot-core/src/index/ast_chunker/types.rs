@@ -34,6 +34,43 @@ impl ChunkType {
     }
 }
 
+/// How a chunk uses an identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReferenceKind {
+    /// Called as a function, method, constructor or macro
+    Call,
+    /// Named as a type
+    Type,
+}
+
+impl ReferenceKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Call => "call",
+            Self::Type => "type",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "call" => Some(Self::Call),
+            "type" => Some(Self::Type),
+            _ => None,
+        }
+    }
+}
+
+/// An identifier a chunk refers to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolReference {
+    /// Bare name, without path or receiver ("new" for `Parser::new()`)
+    pub name: String,
+    pub kind: ReferenceKind,
+    /// Line of the first use (1-indexed)
+    pub line: usize,
+}
+
 /// Metadata associated with a chunk
 #[derive(Debug, Clone, Default)]
 pub struct ChunkMetadata {
@@ -49,6 +86,8 @@ pub struct ChunkMetadata {
     pub start_line: usize,
     /// Ending line number (1-indexed)
     pub end_line: usize,
+    /// Calls and type names used in the chunk, outside nested chunks
+    pub references: Vec<SymbolReference>,
 }
 
 /// A semantic chunk of source code or text
@@ -2,24 +2,19 @@
 
 use super::ast_chunker::{compute_chunk_hash, SemanticChunk, SemanticChunker};
 use super::chunker::{chunk_by_chars, CHUNK_OVERLAP_CHARS, CHUNK_SIZE_CHARS};
-use crate::db::{CacheLookupResult, Database};
+use crate::db::{CacheLookupResult, ChunkEmbedding, Database};
 use crate::error::Result;
 use crate::llm::Embedder;
+use crate::progress::{
+    check_cancelled, CancellationToken, NoProgress, ProgressSink, ProgressTracker,
+};
+use chrono::Utc;
+use rusqlite::params;
+use std::collections::BTreeMap;
 use std::path::Path;
 
 const BATCH_SIZE: usize = 32;
 
-/// Embedding progress
-#[derive(Debug, Clone)]
-pub struct EmbedProgress {
-    pub total_docs: usize,
-    pub processed_docs: usize,
-    pub total_chunks: usize,
-    pub processed_chunks: usize,
-    pub cached_chunks: usize,
-    pub computed_chunks: usize,
-}
-
 /// Embedding statistics
 #[derive(Debug, Clone, Default)]
 pub struct EmbedStats {
@@ -40,30 +35,313 @@ impl EmbedStats {
     }
 }
 
-/// Chunk ready for embedding with cache metadata
-struct ChunkToEmbed {
+/// Progress of an interrupted forced run or migration
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct EmbedCheckpoint {
+    /// Model of a forced run, or `migrate:<model>` for a migration
+    pub key: String,
+    /// Last document finished, in hash order
+    pub last_hash: String,
+    /// Documents finished so far
+    pub documents: usize,
+    pub updated_at: String,
+}
+
+impl EmbedCheckpoint {
+    /// Target model, if this is a migration
+    pub fn migration_target(&self) -> Option<&str> {
+        self.key.strip_prefix(MIGRATION_PREFIX)
+    }
+}
+
+/// Prefix of migration checkpoint keys
+pub(super) const MIGRATION_PREFIX: &str = "migrate:";
+
+/// Documents fetched from the database per page
+const PAGE_SIZE: usize = 256;
+
+/// Document waiting for its chunk embeddings
+struct PendingDoc {
+    hash: String,
+    path: Option<String>,
+    bytes: u64,
+    chunks: Vec<PendingChunk>,
+    missing: usize,
+}
+
+struct PendingChunk {
     seq: u32,
-    text: String,
     position: usize,
     chunk_hash: String,
-    cached_embedding: Option<Vec<f32>>,
+    embedding: Option<Vec<f32>>,
+}
+
+/// Chunk text queued for the next embedding request
+struct QueuedText {
+    chunk_hash: String,
+    /// (document, chunk) slots waiting for this embedding
+    targets: Vec<(usize, usize)>,
+    text: String,
+}
+
+/// Streams documents through chunk → batch → embed → write-back
+///
+/// Only the current page of document hashes, the open batch and the documents
+/// it touches are held in memory. A chunk already embedded or queued in this
+/// run is not sent again, so unchanged chunks of edited documents and chunks
+/// repeated across files cost one embedding. Documents are written atomically and in
+/// order, so the checkpoint always marks a prefix of finished documents.
+struct Pipeline<'a> {
+    db: &'a Database,
+    embedder: &'a dyn Embedder,
+    model: &'a str,
+    /// Key finished documents are checkpointed under, if any
+    checkpoint: Option<&'a str>,
+    /// Reuse chunk embeddings cached for this model
+    cache_enabled: bool,
+    /// Only fill the chunk cache, leaving the vectors search reads untouched
+    stage: bool,
+    /// Stops the run before the next batch when triggered
+    cancel: Option<&'a CancellationToken>,
+    chunker: SemanticChunker,
+    docs: BTreeMap<usize, PendingDoc>,
+    queue: Vec<QueuedText>,
+    next_id: usize,
+    stats: EmbedStats,
+}
+
+impl Pipeline<'_> {
+    /// Chunk a document, take what the cache has and queue the rest
+    fn prepare(&mut self, hash: String, path: Option<String>) -> Result<()> {
+        let Some(content) = self.db.get_content(&hash)? else {
+            return Ok(());
+        };
+        let title = self.db.get_document_title_by_hash(&hash)?;
+
+        let semantic_chunks = document_chunks(&self.chunker, &content, path.as_deref())?;
+        self.stats.total_chunks += semantic_chunks.len();
+
+        let id = self.next_id;
+        self.next_id += 1;
+        let dimensions = self.embedder.dimensions();
+        let mut chunks = Vec::with_capacity(semantic_chunks.len());
+        let mut missing = 0;
+        for (seq, chunk) in semantic_chunks.into_iter().enumerate() {
+            // Try to find cached embedding (using fast lookup since we checked compatibility upfront)
+            let cached = match self.in_flight(&chunk.chunk_hash) {
+                Some(emb) => Some(emb),
+                None if self.cache_enabled => match self
+                    .db
+                    .get_cached_embedding_fast(&chunk.chunk_hash, self.model)?
+                {
+                    CacheLookupResult::Hit(emb) if emb.len() == dimensions => Some(emb),
+                    _ => None,
+                },
+                None => None,
+            };
+
+            if cached.is_some() {
+                self.stats.cached_chunks += 1;
+            } else {
+                missing += 1;
+                self.enqueue(
+                    &chunk.chunk_hash,
+                    (id, seq),
+                    format_doc_for_embedding(&chunk.text, title.as_deref()),
+                );
+            }
+            chunks.push(PendingChunk {
+                seq: seq as u32,
+                position: chunk.position,
+                chunk_hash: chunk.chunk_hash,
+                embedding: cached,
+            });
+        }
+
+        self.docs.insert(
+            id,
+            PendingDoc {
+                hash,
+                path,
+                bytes: content.len() as u64,
+                chunks,
+                missing,
+            },
+        );
+        Ok(())
+    }
+
+    /// Embedding of a chunk computed this run but not yet written
+    fn in_flight(&self, chunk_hash: &str) -> Option<Vec<f32>> {
+        self.docs
+            .values()
+            .flat_map(|d| &d.chunks)
+            .find(|c| c.chunk_hash == chunk_hash && c.embedding.is_some())
+            .and_then(|c| c.embedding.clone())
+    }
+
+    /// Queue a chunk for embedding, sharing the request with an identical
+    /// chunk already queued
+    fn enqueue(&mut self, chunk_hash: &str, target: (usize, usize), text: String) {
+        match self.queue.iter_mut().find(|q| q.chunk_hash == chunk_hash) {
+            Some(queued) => queued.targets.push(target),
+            None => self.queue.push(QueuedText {
+                chunk_hash: chunk_hash.to_string(),
+                targets: vec![target],
+                text,
+            }),
+        }
+    }
+
+    /// Embed full batches and write back what is complete
+    async fn pump(&mut self, tracker: &mut ProgressTracker<'_>) -> Result<()> {
+        while self.queue.len() >= BATCH_SIZE {
+            self.flush(tracker).await?;
+        }
+        self.drain_complete(tracker)
+    }
+
+    /// Embed whatever is still queued
+    async fn finish(&mut self, tracker: &mut ProgressTracker<'_>) -> Result<()> {
+        while !self.queue.is_empty() {
+            self.flush(tracker).await?;
+        }
+        self.drain_complete(tracker)
+    }
+
+    /// Embed one batch from the front of the queue
+    async fn flush(&mut self, tracker: &mut ProgressTracker<'_>) -> Result<()> {
+        if let Some(cancel) = self.cancel {
+            check_cancelled(cancel, "embedding")?;
+        }
+        let take = self.queue.len().min(BATCH_SIZE);
+        let batch: Vec<QueuedText> = self.queue.drain(..take).collect();
+        let texts: Vec<String> = batch.iter().map(|q| q.text.clone()).collect();
+
+        let embeddings = match self.embedder.embed_batch(&texts).await {
+            Ok(embeddings) => embeddings,
+            Err(e) => {
+                let path = batch
+                    .first()
+                    .and_then(|q| self.docs.get(&q.targets[0].0))
+                    .and_then(|d| d.path.clone());
+                tracker.failed(path);
+                return Err(e);
+            }
+        };
+
+        for (queued, embedding) in batch.into_iter().zip(embeddings) {
+            self.stats.computed_chunks += 1;
+            // Slots after the first reuse the embedding like a cache hit
+            self.stats.cached_chunks += queued.targets.len() - 1;
+            for (doc, chunk) in queued.targets {
+                if let Some(doc) = self.docs.get_mut(&doc) {
+                    doc.chunks[chunk].embedding = Some(embedding.clone());
+                    doc.missing -= 1;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write finished documents from the front, preserving order
+    fn drain_complete(&mut self, tracker: &mut ProgressTracker<'_>) -> Result<()> {
+        while let Some(entry) = self.docs.first_entry() {
+            if entry.get().missing > 0 {
+                break;
+            }
+            let doc = entry.remove();
+            let chunks: Vec<ChunkEmbedding> = doc
+                .chunks
+                .into_iter()
+                .filter_map(|c| {
+                    c.embedding.map(|embedding| ChunkEmbedding {
+                        seq: c.seq,
+                        pos: c.position,
+                        chunk_hash: c.chunk_hash,
+                        embedding,
+                    })
+                })
+                .collect();
+
+            if self.stage {
+                self.db.cache_chunk_embeddings(self.model, &chunks)?;
+            } else {
+                self.db
+                    .replace_document_embeddings(&doc.hash, self.model, &chunks)?;
+            }
+            self.stats.embedded_chunks += chunks.len();
+            self.stats.embedded_documents += 1;
+            if let Some(key) = self.checkpoint {
+                self.db.save_embed_checkpoint(key, &doc.hash)?;
+            }
+
+            tracker.processed(
+                doc.bytes,
+                Some(format!(
+                    "{} chunks ({} cached, {} computed)",
+                    self.stats.embedded_chunks,
+                    self.stats.cached_chunks,
+                    self.stats.computed_chunks
+                )),
+            );
+        }
+        Ok(())
+    }
 }
 
 /// Generate embeddings for documents with smart caching
+///
+/// Documents are streamed from the database in pages and their chunks sent in
+/// fixed-size batches, so memory stays bounded on large corpora. Each document
+/// is written atomically once all its chunks are embedded: an interrupted run
+/// resumes where it stopped, and forced runs continue from their checkpoint.
+///
+/// Progress counts documents; each update's message carries running chunk totals.
 pub async fn embed_documents(
     db: &Database,
     embedder: &dyn Embedder,
     model: &str,
     force: bool,
-    progress: Option<Box<dyn Fn(EmbedProgress) + Send + Sync>>,
+    progress: &dyn ProgressSink,
+) -> Result<EmbedStats> {
+    embed_documents_cancellable(
+        db,
+        embedder,
+        model,
+        force,
+        progress,
+        &CancellationToken::new(),
+    )
+    .await
+}
+
+/// [`embed_documents`], stopping before the next batch once `cancel` is
+/// triggered
+///
+/// Documents finished by then keep their vectors, and a forced run keeps its
+/// checkpoint, so running again picks up where it stopped.
+pub async fn embed_documents_cancellable(
+    db: &Database,
+    embedder: &dyn Embedder,
+    model: &str,
+    force: bool,
+    progress: &dyn ProgressSink,
+    cancel: &CancellationToken,
 ) -> Result<EmbedStats> {
-    let docs = if force {
-        db.get_all_content_with_paths()?
+    db.ensure_writable()?;
+    // Forced runs skip documents already re-embedded before an interruption
+    let mut cursor = if force {
+        db.embed_checkpoint(model)?.unwrap_or_default()
     } else {
-        db.get_content_needing_embedding_with_paths()?
+        String::new()
     };
 
-    if docs.is_empty() {
+    let total_docs = db.count_documents_to_embed(&cursor, force)?;
+    if total_docs == 0 {
+        if force {
+            db.clear_embed_checkpoint(model)?;
+        }
         return Ok(EmbedStats::default());
     }
 
@@ -74,105 +352,168 @@ pub async fn embed_documents(
     let cache_enabled = !force && db.check_model_compatibility(model, dimensions)?;
     db.register_model(model, dimensions)?;
 
-    let total_docs = docs.len();
-    let mut stats = EmbedStats {
-        total_documents: total_docs,
-        ..Default::default()
+    let mut pipeline = Pipeline {
+        db,
+        embedder,
+        model,
+        checkpoint: force.then_some(model),
+        cache_enabled,
+        stage: false,
+        cancel: Some(cancel),
+        chunker: SemanticChunker::new(),
+        docs: BTreeMap::new(),
+        queue: Vec::new(),
+        next_id: 0,
+        stats: EmbedStats {
+            total_documents: total_docs,
+            ..Default::default()
+        },
     };
 
-    let chunker = SemanticChunker::new();
-
-    for (doc_idx, (hash, content, path)) in docs.iter().enumerate() {
-        let title = db.get_document_title_by_hash(hash)?;
+    let mut tracker = ProgressTracker::new(progress, "embed");
+    tracker.discovered(total_docs);
 
-        // Use semantic chunking if we have a file path
-        let semantic_chunks = if let Some(p) = path {
-            chunker.chunk(content, Path::new(p))?
-        } else {
-            fallback_to_semantic_chunks(content)
+    loop {
+        let page = db.documents_to_embed_page(&cursor, force, PAGE_SIZE)?;
+        let Some((last, _)) = page.last() else {
+            break;
         };
+        cursor = last.clone();
 
-        stats.total_chunks += semantic_chunks.len();
-
-        // Prepare chunks with cache lookups
-        let mut chunks_to_embed: Vec<ChunkToEmbed> = Vec::new();
-
-        for (seq, chunk) in semantic_chunks.iter().enumerate() {
-            let formatted_text = format_doc_for_embedding(&chunk.text, title.as_deref());
+        for (hash, path) in page {
+            pipeline.prepare(hash, path)?;
+            pipeline.pump(&mut tracker).await?;
+        }
+    }
+    pipeline.finish(&mut tracker).await?;
 
-            // Try to find cached embedding (using fast lookup since we checked compatibility upfront)
-            let cached = if cache_enabled {
-                match db.get_cached_embedding_fast(&chunk.chunk_hash, model)? {
-                    CacheLookupResult::Hit(emb) => Some(emb),
-                    CacheLookupResult::Miss | CacheLookupResult::ModelMismatch => None,
-                }
-            } else {
-                None
-            };
+    if force {
+        db.clear_embed_checkpoint(model)?;
+    }
+    tracker.finish();
+    Ok(pipeline.stats)
+}
 
-            chunks_to_embed.push(ChunkToEmbed {
-                seq: seq as u32,
-                text: formatted_text,
-                position: chunk.position,
-                chunk_hash: chunk.chunk_hash.clone(),
-                cached_embedding: cached,
-            });
-        }
+/// Embed a single document, e.g. one just added outside a reindex
+pub async fn embed_document(
+    db: &Database,
+    embedder: &dyn Embedder,
+    hash: &str,
+    path: &str,
+) -> Result<EmbedStats> {
+    let documents = vec![(hash.to_string(), Some(path.to_string()))];
+    embed_selected(db, embedder, embedder.model_name(), documents, &NoProgress).await
+}
 
-        // Separate cached from needing computation
-        let (cached, to_compute): (Vec<_>, Vec<_>) = chunks_to_embed
-            .into_iter()
-            .partition(|c| c.cached_embedding.is_some());
+/// Re-embed the given (hash, path) documents, replacing their vectors
+///
+/// Used for targeted repairs; cached chunk embeddings are reused when the
+/// stored model dimensions match.
+pub(super) async fn embed_selected(
+    db: &Database,
+    embedder: &dyn Embedder,
+    model: &str,
+    documents: Vec<(String, Option<String>)>,
+    progress: &dyn ProgressSink,
+) -> Result<EmbedStats> {
+    let dimensions = embedder.dimensions();
+    db.ensure_vec_table(dimensions)?;
+    let cache_enabled = db.check_model_compatibility(model, dimensions)?;
+    db.register_model(model, dimensions)?;
 
-        // Store cached embeddings
-        for chunk in cached {
-            let embedding = chunk.cached_embedding.unwrap();
-            db.insert_chunk_embedding(
-                hash,
-                chunk.seq,
-                chunk.position,
-                &chunk.chunk_hash,
-                model,
-                &embedding,
-            )?;
-            stats.embedded_chunks += 1;
-            stats.cached_chunks += 1;
-        }
+    let mut pipeline = Pipeline {
+        db,
+        embedder,
+        model,
+        checkpoint: None,
+        cache_enabled,
+        stage: false,
+        cancel: None,
+        chunker: SemanticChunker::new(),
+        docs: BTreeMap::new(),
+        queue: Vec::new(),
+        next_id: 0,
+        stats: EmbedStats {
+            total_documents: documents.len(),
+            ..Default::default()
+        },
+    };
 
-        // Batch embed new chunks
-        for batch in to_compute.chunks(BATCH_SIZE) {
-            let texts: Vec<String> = batch.iter().map(|c| c.text.clone()).collect();
-            let embeddings = embedder.embed_batch(&texts).await?;
+    let mut tracker = ProgressTracker::new(progress, "embed");
+    tracker.discovered(documents.len());
+    for (hash, path) in documents {
+        pipeline.prepare(hash, path)?;
+        pipeline.pump(&mut tracker).await?;
+    }
+    pipeline.finish(&mut tracker).await?;
+    tracker.finish();
+    Ok(pipeline.stats)
+}
 
-            for (chunk, embedding) in batch.iter().zip(embeddings.iter()) {
-                db.insert_chunk_embedding(
-                    hash,
-                    chunk.seq,
-                    chunk.position,
-                    &chunk.chunk_hash,
-                    model,
-                    embedding,
-                )?;
-                stats.embedded_chunks += 1;
-                stats.computed_chunks += 1;
-            }
-        }
+/// Embed every active document with `model` into the chunk cache only
+///
+/// The vectors search reads are left alone, so another model keeps serving
+/// queries meanwhile. Chunks already cached for `model` are reused, and
+/// finished documents are checkpointed under `checkpoint` so an interrupted
+/// run resumes where it stopped.
+pub(super) async fn stage_embeddings(
+    db: &Database,
+    embedder: &dyn Embedder,
+    model: &str,
+    checkpoint: &str,
+    progress: &dyn ProgressSink,
+) -> Result<EmbedStats> {
+    let mut cursor = db.embed_checkpoint(checkpoint)?.unwrap_or_default();
+    let total_docs = db.count_documents_to_embed(&cursor, true)?;
+    let cache_enabled = db.check_model_compatibility(model, embedder.dimensions())?;
+
+    let mut pipeline = Pipeline {
+        db,
+        embedder,
+        model,
+        checkpoint: Some(checkpoint),
+        cache_enabled,
+        stage: true,
+        cancel: None,
+        chunker: SemanticChunker::new(),
+        docs: BTreeMap::new(),
+        queue: Vec::new(),
+        next_id: 0,
+        stats: EmbedStats {
+            total_documents: total_docs,
+            ..Default::default()
+        },
+    };
 
-        stats.embedded_documents += 1;
+    let mut tracker = ProgressTracker::new(progress, "migrate");
+    tracker.discovered(total_docs);
+    loop {
+        let page = db.documents_to_embed_page(&cursor, true, PAGE_SIZE)?;
+        let Some((last, _)) = page.last() else {
+            break;
+        };
+        cursor = last.clone();
 
-        if let Some(ref cb) = progress {
-            cb(EmbedProgress {
-                total_docs,
-                processed_docs: doc_idx + 1,
-                total_chunks: stats.total_chunks,
-                processed_chunks: stats.embedded_chunks,
-                cached_chunks: stats.cached_chunks,
-                computed_chunks: stats.computed_chunks,
-            });
+        for (hash, path) in page {
+            pipeline.prepare(hash, path)?;
+            pipeline.pump(&mut tracker).await?;
         }
     }
+    pipeline.finish(&mut tracker).await?;
+    tracker.finish();
+    Ok(pipeline.stats)
+}
 
-    Ok(stats)
+/// Chunks a document is embedded as: semantic when its path is known
+pub(super) fn document_chunks(
+    chunker: &SemanticChunker,
+    content: &str,
+    path: Option<&str>,
+) -> Result<Vec<SemanticChunk>> {
+    match path {
+        Some(p) => chunker.chunk(content, Path::new(p)),
+        None => Ok(fallback_to_semantic_chunks(content)),
+    }
 }
 
 /// Fallback: convert character-based chunks to semantic chunks with hashes
@@ -254,4 +595,259 @@ impl Database {
             Err(e) => Err(e.into()),
         }
     }
+
+    /// Number of documents an embedding run would process after `cursor`
+    fn count_documents_to_embed(&self, cursor: &str, force: bool) -> Result<usize> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(DISTINCT c.hash) FROM content c
+             JOIN documents d ON d.hash = c.hash AND d.active = 1
+             WHERE c.hash > ?1
+               AND (?2 OR NOT EXISTS (SELECT 1 FROM content_vectors cv WHERE cv.hash = c.hash))",
+            params![cursor, force],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    /// Next page of (hash, path) to embed, in hash order after `cursor`
+    fn documents_to_embed_page(
+        &self,
+        cursor: &str,
+        force: bool,
+        limit: usize,
+    ) -> Result<Vec<(String, Option<String>)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT c.hash, MIN(d.path) FROM content c
+             JOIN documents d ON d.hash = c.hash AND d.active = 1
+             WHERE c.hash > ?1
+               AND (?2 OR NOT EXISTS (SELECT 1 FROM content_vectors cv WHERE cv.hash = c.hash))
+             GROUP BY c.hash
+             ORDER BY c.hash
+             LIMIT ?3",
+        )?;
+        let results = stmt
+            .query_map(params![cursor, force, limit as i64], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(results)
+    }
+
+    /// Last document hash finished by an interrupted forced run for `model`
+    pub fn embed_checkpoint(&self, model: &str) -> Result<Option<String>> {
+        let result = self.conn.query_row(
+            "SELECT last_hash FROM embed_checkpoints WHERE model = ?1",
+            params![model],
+            |row| row.get(0),
+        );
+        match result {
+            Ok(hash) => Ok(Some(hash)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save_embed_checkpoint(&self, model: &str, last_hash: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO embed_checkpoints (model, last_hash, documents, updated_at)
+             VALUES (?1, ?2, 1, ?3)
+             ON CONFLICT(model) DO UPDATE SET
+                last_hash = excluded.last_hash,
+                documents = documents + 1,
+                updated_at = excluded.updated_at",
+            params![model, last_hash, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Interrupted runs, most recent first
+    pub fn embed_checkpoints(&self) -> Result<Vec<EmbedCheckpoint>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT model, last_hash, documents, updated_at FROM embed_checkpoints
+             ORDER BY updated_at DESC",
+        )?;
+        let checkpoints = stmt
+            .query_map([], |row| {
+                Ok(EmbedCheckpoint {
+                    key: row.get(0)?,
+                    last_hash: row.get(1)?,
+                    documents: row.get::<_, i64>(2)? as usize,
+                    updated_at: row.get(3)?,
+                })
+            })?
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(checkpoints)
+    }
+
+    /// Forget the checkpoint so the next forced run starts over
+    pub fn clear_embed_checkpoint(&self, model: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM embed_checkpoints WHERE model = ?1",
+            params![model],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AgentRootError;
+    use crate::progress::NoProgress;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Returns constant vectors, failing the `fail_on`-th batch (1-based)
+    struct FlakyEmbedder {
+        calls: AtomicUsize,
+        fail_on: usize,
+    }
+
+    #[async_trait]
+    impl Embedder for FlakyEmbedder {
+        async fn embed(&self, _text: &str) -> Result<Vec<f32>> {
+            Ok(vec![1.0, 0.0])
+        }
+
+        async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            if call == self.fail_on {
+                return Err(AgentRootError::ExternalError("connection reset".into()));
+            }
+            Ok(texts.iter().map(|_| vec![1.0, 0.0]).collect())
+        }
+
+        fn dimensions(&self) -> usize {
+            2
+        }
+
+        fn model_name(&self) -> &str {
+            "test"
+        }
+    }
+
+    fn embedder(fail_on: usize) -> FlakyEmbedder {
+        FlakyEmbedder {
+            calls: AtomicUsize::new(0),
+            fail_on,
+        }
+    }
+
+    fn setup_db(docs: usize) -> Database {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("notes", "/notes", "**/*.txt", "file", None)
+            .unwrap();
+        for i in 0..docs {
+            let hash = format!("{:06}", i);
+            let path = format!("{}.txt", i);
+            db.insert_content(&hash, &format!("note number {}", i))
+                .unwrap();
+            db.insert_document("notes", &path, &path, &hash, &now, &now, "file", None)
+                .unwrap();
+        }
+        db
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_run_resumes() {
+        let db = setup_db(40);
+
+        // The second batch fails; the first 32 documents are already written
+        let err = embed_documents(&db, &embedder(2), "test", false, &NoProgress).await;
+        assert!(err.is_err());
+        assert_eq!(db.count_documents_to_embed("", false).unwrap(), 8);
+
+        let stats = embed_documents(&db, &embedder(0), "test", false, &NoProgress)
+            .await
+            .unwrap();
+        assert_eq!(stats.total_documents, 8);
+        assert_eq!(stats.embedded_documents, 8);
+        assert_eq!(db.count_documents_to_embed("", false).unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_run_keeps_finished_documents() {
+        let db = setup_db(40);
+        let cancel = CancellationToken::new();
+        let stop_after_first_batch = |u: &crate::progress::ProgressUpdate| {
+            if u.processed >= 32 {
+                cancel.cancel();
+            }
+        };
+
+        let err = embed_documents_cancellable(
+            &db,
+            &embedder(0),
+            "test",
+            false,
+            &stop_after_first_batch,
+            &cancel,
+        )
+        .await;
+        assert!(matches!(err, Err(AgentRootError::Cancelled(_))));
+        assert_eq!(db.count_documents_to_embed("", false).unwrap(), 8);
+    }
+
+    #[tokio::test]
+    async fn test_forced_run_continues_from_checkpoint() {
+        let db = setup_db(40);
+        embed_documents(&db, &embedder(0), "test", false, &NoProgress)
+            .await
+            .unwrap();
+
+        let err = embed_documents(&db, &embedder(2), "test", true, &NoProgress).await;
+        assert!(err.is_err());
+        assert_eq!(
+            db.embed_checkpoint("test").unwrap().as_deref(),
+            Some("000031")
+        );
+        let checkpoints = db.embed_checkpoints().unwrap();
+        assert_eq!(checkpoints.len(), 1);
+        assert_eq!(checkpoints[0].key, "test");
+        assert_eq!(checkpoints[0].documents, 32);
+        assert_eq!(checkpoints[0].migration_target(), None);
+
+        let stats = embed_documents(&db, &embedder(0), "test", true, &NoProgress)
+            .await
+            .unwrap();
+        assert_eq!(stats.embedded_documents, 8);
+        assert_eq!(stats.computed_chunks, 8);
+        assert!(db.embed_checkpoint("test").unwrap().is_none());
+        assert!(db.embed_checkpoints().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_only_changed_and_unique_chunks_are_computed() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("src", "/src", "**/*.rs", "file", None)
+            .unwrap();
+        let v1 = "fn a() {\n    1;\n}\n\nfn b() {\n    2;\n}\n\nfn c() {\n    3;\n}\n";
+        db.insert_content("h1", v1).unwrap();
+        let id = db
+            .insert_document("src", "x.rs", "x.rs", "h1", &now, &now, "file", None)
+            .unwrap();
+        // A copy of the file embedded in the same run
+        db.insert_content("h2", &format!("{}\n", v1)).unwrap();
+        db.insert_document("src", "y.rs", "y.rs", "h2", &now, &now, "file", None)
+            .unwrap();
+
+        let stats = embed_documents(&db, &embedder(0), "test", false, &NoProgress)
+            .await
+            .unwrap();
+        assert_eq!(stats.embedded_chunks, 6);
+        assert_eq!(stats.computed_chunks, 3);
+
+        // Editing one function re-embeds only that chunk
+        db.insert_content("h3", &v1.replace("2;", "22;")).unwrap();
+        db.update_document(id, "x.rs", "h3", &now).unwrap();
+        let stats = embed_documents(&db, &embedder(0), "test", false, &NoProgress)
+            .await
+            .unwrap();
+        assert_eq!(stats.computed_chunks, 1);
+        assert_eq!(stats.cached_chunks, 2);
+    }
 }
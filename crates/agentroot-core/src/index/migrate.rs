@@ -0,0 +1,227 @@
+//! Switching the index to another embedding model
+//!
+//! Vectors for the new model are first staged in the chunk cache, which holds
+//! any number of models side by side, while the current vectors keep serving
+//! search. Only once every document is staged are the searched vectors
+//! swapped over, document by document, from the cache. Both phases resume
+//! after an interruption, and the old model's cached vectors stay behind, so
+//! migrating back costs no embedding calls.
+
+use super::embedder::{embed_selected, stage_embeddings, EmbedStats, MIGRATION_PREFIX};
+use crate::db::Database;
+use crate::error::Result;
+use crate::llm::Embedder;
+use crate::progress::ProgressSink;
+use rusqlite::params;
+
+/// Outcome of [`migrate_embeddings`]
+#[derive(Debug, Clone, Default)]
+pub struct MigrationReport {
+    /// Models the index was built with before the switch
+    pub previous_models: Vec<String>,
+    /// Staging pass into the chunk cache
+    pub staged: EmbedStats,
+    /// Documents whose searched vectors were switched to the new model
+    pub switched: EmbedStats,
+}
+
+/// Re-embed the index with `model`, incrementally and without a gap in
+/// vector search
+///
+/// Staging progress reports as `migrate`, the switch as `embed`.
+pub async fn migrate_embeddings(
+    db: &Database,
+    embedder: &dyn Embedder,
+    model: &str,
+    progress: &dyn ProgressSink,
+) -> Result<MigrationReport> {
+    db.ensure_writable()?;
+    let dimensions = embedder.dimensions();
+    db.ensure_vec_table(dimensions)?;
+    db.register_model(model, dimensions)?;
+
+    let checkpoint = Database::migration_checkpoint_key(model);
+    let staged = stage_embeddings(db, embedder, model, &checkpoint, progress).await?;
+
+    let previous_models = db.indexed_models_other_than(model)?;
+    let pending = db.documents_not_indexed_with(model)?;
+    let switched = embed_selected(db, embedder, model, pending, progress).await?;
+    db.clear_embed_checkpoint(&checkpoint)?;
+
+    Ok(MigrationReport {
+        previous_models,
+        staged,
+        switched,
+    })
+}
+
+impl Database {
+    /// Checkpoint key of a migration to `model`, kept apart from forced runs
+    pub fn migration_checkpoint_key(model: &str) -> String {
+        format!("{}{}", MIGRATION_PREFIX, model)
+    }
+
+    /// Models other than `model` that active documents are indexed with
+    fn indexed_models_other_than(&self, model: &str) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT cv.model FROM content_vectors cv
+             JOIN documents d ON d.hash = cv.hash AND d.active = 1
+             WHERE cv.model != ?1
+             ORDER BY cv.model",
+        )?;
+        let models = stmt
+            .query_map(params![model], |row| row.get(0))?
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(models)
+    }
+
+    /// Active (hash, path) without vectors, or with any from another model
+    fn documents_not_indexed_with(&self, model: &str) -> Result<Vec<(String, Option<String>)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT c.hash, MIN(d.path) FROM content c
+             JOIN documents d ON d.hash = c.hash AND d.active = 1
+             WHERE NOT EXISTS (SELECT 1 FROM content_vectors cv WHERE cv.hash = c.hash)
+                OR EXISTS (SELECT 1 FROM content_vectors cv
+                           WHERE cv.hash = c.hash AND cv.model != ?1)
+             GROUP BY c.hash
+             ORDER BY c.hash",
+        )?;
+        let documents = stmt
+            .query_map(params![model], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(documents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AgentRootError;
+    use crate::index::embed_documents;
+    use crate::progress::NoProgress;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Vectors tagged by model, failing after `fail_after` texts if set
+    struct ModelEmbedder {
+        name: &'static str,
+        value: f32,
+        texts: AtomicUsize,
+        fail_after: Option<usize>,
+    }
+
+    impl ModelEmbedder {
+        fn new(name: &'static str, value: f32) -> Self {
+            Self {
+                name,
+                value,
+                texts: AtomicUsize::new(0),
+                fail_after: None,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Embedder for ModelEmbedder {
+        async fn embed(&self, _text: &str) -> Result<Vec<f32>> {
+            Ok(vec![self.value; 4])
+        }
+
+        async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            let before = self.texts.fetch_add(texts.len(), Ordering::SeqCst);
+            if self.fail_after.is_some_and(|n| before + texts.len() > n) {
+                return Err(AgentRootError::Llm("interrupted".to_string()));
+            }
+            Ok(texts.iter().map(|_| vec![self.value; 4]).collect())
+        }
+
+        fn dimensions(&self) -> usize {
+            4
+        }
+
+        fn model_name(&self) -> &str {
+            self.name
+        }
+    }
+
+    fn setup_db(docs: usize) -> Database {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = chrono::Utc::now().to_rfc3339();
+        db.add_collection("notes", "/notes", "**/*.txt", "file", None)
+            .unwrap();
+        for i in 0..docs {
+            let hash = format!("{:06}", i);
+            let path = format!("{}.txt", i);
+            db.insert_content(&hash, &format!("note number {}", i))
+                .unwrap();
+            db.insert_document("notes", &path, &path, &hash, &now, &now, "file", None)
+                .unwrap();
+        }
+        db
+    }
+
+    fn indexed_models(db: &Database) -> Vec<String> {
+        let mut stmt = db
+            .conn
+            .prepare("SELECT DISTINCT model FROM embeddings ORDER BY model")
+            .unwrap();
+        stmt.query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<std::result::Result<_, _>>()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_migration_keeps_old_vectors_until_switch() {
+        let db = setup_db(40);
+        let old = ModelEmbedder::new("old-model", 1.0);
+        embed_documents(&db, &old, "old-model", false, &NoProgress)
+            .await
+            .unwrap();
+
+        // Interrupted in the second batch: search still reads the old vectors
+        let mut new = ModelEmbedder::new("new-model", 2.0);
+        new.fail_after = Some(32);
+        assert!(migrate_embeddings(&db, &new, "new-model", &NoProgress)
+            .await
+            .is_err());
+        assert_eq!(indexed_models(&db), vec!["old-model"]);
+        let key = Database::migration_checkpoint_key("new-model");
+        assert_eq!(
+            db.embed_checkpoint(&key).unwrap().as_deref(),
+            Some("000031")
+        );
+        let checkpoints = db.embed_checkpoints().unwrap();
+        assert_eq!(checkpoints[0].migration_target(), Some("new-model"));
+
+        // Resuming embeds only what was not staged
+        let new = ModelEmbedder::new("new-model", 2.0);
+        let report = migrate_embeddings(&db, &new, "new-model", &NoProgress)
+            .await
+            .unwrap();
+        assert_eq!(report.previous_models, vec!["old-model"]);
+        assert_eq!(report.staged.embedded_documents, 8);
+        assert_eq!(report.switched.embedded_documents, 40);
+        assert_eq!(report.switched.computed_chunks, 0);
+        assert_eq!(new.texts.load(Ordering::SeqCst), 8);
+        assert_eq!(indexed_models(&db), vec!["new-model"]);
+        assert!(db.embed_checkpoint(&key).unwrap().is_none());
+
+        let models = db.embedding_models().unwrap();
+        let old_model = models.iter().find(|m| m.model == "old-model").unwrap();
+        assert_eq!(old_model.indexed_documents, 0);
+        assert_eq!(old_model.cached_chunks, 40);
+        let new_model = models.iter().find(|m| m.model == "new-model").unwrap();
+        assert_eq!(new_model.indexed_documents, 40);
+
+        // Going back reuses the old model's cached vectors
+        let old = ModelEmbedder::new("old-model", 1.0);
+        let report = migrate_embeddings(&db, &old, "old-model", &NoProgress)
+            .await
+            .unwrap();
+        assert_eq!(report.switched.embedded_documents, 40);
+        assert_eq!(old.texts.load(Ordering::SeqCst), 0);
+        assert_eq!(indexed_models(&db), vec!["old-model"]);
+    }
+}
@@ -5,11 +5,22 @@
 pub mod ast_chunker;
 mod chunker;
 mod embedder;
+mod migrate;
+mod normalize;
+mod parallel;
 mod parser;
 mod scanner;
+mod secrets;
+mod verify;
+pub mod watcher;
 
-pub use ast_chunker::{chunk_semantic, ChunkType, SemanticChunk, SemanticChunker};
+pub use ast_chunker::{chunk_semantic, ChunkType, ReferenceKind, SemanticChunk, SemanticChunker};
 pub use chunker::*;
 pub use embedder::*;
+pub use migrate::{migrate_embeddings, MigrationReport};
+pub use normalize::normalize_text;
+pub use parallel::{index_workers, map_parallel};
 pub use parser::*;
 pub use scanner::*;
+pub use secrets::{SecretMatch, SecretMode, SecretScanner};
+pub use verify::{verify_embeddings, EmbeddingIssue, VerifyReport};
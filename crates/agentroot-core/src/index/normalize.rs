@@ -0,0 +1,30 @@
+//! Unicode normalization of indexed text and queries
+//!
+//! Editors disagree on whether "café" ends in a precomposed `é` or an `e`
+//! followed by a combining accent. Content and queries are both converted to
+//! NFC so the two spellings hash, tokenize and embed the same way.
+
+use std::borrow::Cow;
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+
+/// Convert text to NFC, borrowing when it is already normalized
+pub fn normalize_text(text: &str) -> Cow<'_, str> {
+    match is_nfc_quick(text.chars()) {
+        IsNormalized::Yes => Cow::Borrowed(text),
+        _ => Cow::Owned(text.nfc().collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_text() {
+        let decomposed = "cafe\u{301} au lait";
+        let normalized = normalize_text(decomposed);
+        assert_eq!(normalized, "caf\u{e9} au lait");
+        assert!(matches!(normalize_text("plain ascii"), Cow::Borrowed(_)));
+        assert!(matches!(normalize_text(&normalized), Cow::Borrowed(_)));
+    }
+}
@@ -0,0 +1,92 @@
+//! Bounded parallelism for the indexing pipeline
+//!
+//! Reading, decoding, hashing and secret scanning are CPU- and IO-bound and
+//! independent per file, so they run on a small pool of scoped threads.
+//! Results keep the input order, which later steps rely on (the first of two
+//! pages with the same canonical URL wins, progress is reported in order).
+
+use std::sync::Mutex;
+
+/// Worker count used when `AGENTROOT_INDEX_WORKERS` is unset: one per core
+pub fn index_workers() -> usize {
+    std::env::var("AGENTROOT_INDEX_WORKERS")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        })
+}
+
+/// Apply `f` to every item on up to `workers` threads, keeping input order
+///
+/// Threads pull the next item when they finish one, so a few large files do
+/// not hold up the rest.
+pub fn map_parallel<T, R, F>(items: Vec<T>, workers: usize, f: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+{
+    let workers = workers.min(items.len());
+    if workers <= 1 {
+        return items.into_iter().map(f).collect();
+    }
+
+    let total = items.len();
+    let queue = Mutex::new(items.into_iter().enumerate());
+    let mut results: Vec<(usize, R)> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let next = queue.lock().unwrap_or_else(|e| e.into_inner()).next();
+                        let Some((i, item)) = next else { break };
+                        done.push((i, f(item)));
+                    }
+                    done
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+            .collect()
+    });
+
+    debug_assert_eq!(results.len(), total);
+    results.sort_unstable_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, r)| r).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_parallel_keeps_order() {
+        let items: Vec<u64> = (0..500).collect();
+        let squares = map_parallel(items, 8, |n| {
+            // Uneven work so threads finish out of order
+            if n % 7 == 0 {
+                std::thread::sleep(std::time::Duration::from_micros(200));
+            }
+            n * n
+        });
+        assert_eq!(squares, (0..500u64).map(|n| n * n).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_map_parallel_uses_several_threads() {
+        let ids = map_parallel((0..64).collect::<Vec<_>>(), 4, |_| {
+            std::thread::sleep(std::time::Duration::from_millis(2));
+            std::thread::current().id()
+        });
+        let distinct: std::collections::HashSet<_> = ids.into_iter().collect();
+        assert!(distinct.len() > 1);
+        assert!(map_parallel(Vec::<u8>::new(), 4, |n| n).is_empty());
+    }
+}
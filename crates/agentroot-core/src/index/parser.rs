@@ -1,12 +1,16 @@
 //! Document parsing utilities
 
+use crate::db::{MetadataValue, UserMetadata};
 use lazy_static::lazy_static;
 use regex::Regex;
+use serde_json::Value;
 use std::path::Path;
 
 lazy_static! {
     static ref HEADING_RE: Regex = Regex::new(r"^##?\s+(.+)$").unwrap();
     static ref SECOND_HEADING_RE: Regex = Regex::new(r"^##\s+(.+)$").unwrap();
+    static ref PATH_DATE_RE: Regex =
+        Regex::new(r"((?:19|20)[0-9]{2})([-_./]?)([0-9]{2})([-_./]?)([0-9]{2})").unwrap();
 }
 
 /// Generic headings to skip
@@ -44,6 +48,169 @@ pub fn extract_title(content: &str, filename: &str) -> String {
         .unwrap_or_else(|| filename.to_string())
 }
 
+/// The day a daily note is about
+///
+/// A `date` frontmatter field wins; otherwise the last date in the path is
+/// used, written as `2024-03-05`, `2024_03_05`, `20240305` or `2024/03/05`.
+pub fn note_date(path: &str, metadata: Option<&UserMetadata>) -> Option<chrono::NaiveDate> {
+    let from_metadata = metadata
+        .and_then(|m| m.get("date"))
+        .and_then(|value| match value {
+            MetadataValue::DateTime(s) | MetadataValue::Text(s) => s.get(..10),
+            _ => None,
+        })
+        .and_then(|s| chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").ok());
+    if from_metadata.is_some() {
+        return from_metadata;
+    }
+
+    // Matches may share separators ("2023-01-01/2024-03-05"), so scan from
+    // every position rather than after the previous match
+    let is_digit_at = |i: usize| path.as_bytes().get(i).is_some_and(u8::is_ascii_digit);
+    let mut found = None;
+    let mut start = 0;
+    while let Some(caps) = PATH_DATE_RE.captures_at(path, start) {
+        let whole = caps.get(0).unwrap();
+        start = whole.start() + 1;
+        if caps[2] != caps[4]
+            || (whole.start() > 0 && is_digit_at(whole.start() - 1))
+            || is_digit_at(whole.end())
+        {
+            continue;
+        }
+        let date = chrono::NaiveDate::from_ymd_opt(
+            caps[1].parse().unwrap_or(0),
+            caps[3].parse().unwrap_or(0),
+            caps[5].parse().unwrap_or(0),
+        );
+        if date.is_some() {
+            found = date;
+        }
+    }
+    found
+}
+
+/// YAML (`---`) or TOML (`+++`) block at the top of a markdown document
+#[derive(Debug, Clone)]
+pub struct Frontmatter<'a> {
+    pub metadata: UserMetadata,
+    /// The document with the frontmatter block removed
+    pub body: &'a str,
+}
+
+/// Split frontmatter off a markdown document and convert it to user metadata
+///
+/// Scalars map to text, integer, float, boolean or datetime values; lists of
+/// scalars become tags. `tags` and `aliases` given as a single string are
+/// split on commas. Returns None when there is no frontmatter or it does not
+/// parse.
+pub fn parse_frontmatter(content: &str) -> Option<Frontmatter<'_>> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut lines = content.split_inclusive('\n');
+    let first = lines.next()?;
+    let delimiter = match first.trim_end() {
+        "---" => "---",
+        "+++" => "+++",
+        _ => return None,
+    };
+
+    let mut offset = first.len();
+    let mut closing = None;
+    for line in lines {
+        let trimmed = line.trim_end();
+        if trimmed == delimiter || (delimiter == "---" && trimmed == "...") {
+            closing = Some(offset);
+            break;
+        }
+        offset += line.len();
+    }
+    let end = closing?;
+    let block = &content[first.len()..end];
+    let body = content[end..].split_once('\n').map_or("", |(_, body)| body);
+
+    let fields: serde_json::Map<String, Value> = if delimiter == "---" {
+        match serde_yaml::from_str::<Option<Value>>(block) {
+            Ok(Some(Value::Object(map))) => map,
+            Ok(None) => serde_json::Map::new(),
+            _ => return None,
+        }
+    } else {
+        let table: toml::Table = block.parse().ok()?;
+        match toml_to_json(toml::Value::Table(table)) {
+            Value::Object(map) => map,
+            _ => return None,
+        }
+    };
+
+    let mut metadata = UserMetadata::new();
+    for (key, value) in fields {
+        if let Some(value) = frontmatter_value(&key, value) {
+            metadata.add(key, value);
+        }
+    }
+    Some(Frontmatter { metadata, body })
+}
+
+fn frontmatter_value(key: &str, value: Value) -> Option<MetadataValue> {
+    let list_key = matches!(key, "tags" | "aliases");
+    match value {
+        Value::Null => None,
+        Value::Bool(b) => Some(MetadataValue::Boolean(b)),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Some(MetadataValue::Integer(i)),
+            None => n.as_f64().map(MetadataValue::Float),
+        },
+        Value::String(s) if list_key => Some(MetadataValue::tags(split_list(&s))),
+        Value::String(s) => Some(match parse_date(&s) {
+            Some(date) => MetadataValue::DateTime(date),
+            None => MetadataValue::Text(s),
+        }),
+        Value::Array(items) if items.iter().all(|v| !v.is_array() && !v.is_object()) => {
+            let tags = items.into_iter().filter_map(|v| match v {
+                Value::Null => None,
+                Value::String(s) => Some(s.trim_start_matches('#').to_string()),
+                other => Some(other.to_string()),
+            });
+            Some(MetadataValue::tags(tags))
+        }
+        other => Some(MetadataValue::Json(other)),
+    }
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|s| s.trim().trim_start_matches('#').to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// RFC 3339 timestamps, or plain `YYYY-MM-DD` dates taken as midnight UTC
+fn parse_date(value: &str) -> Option<String> {
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(dt.to_rfc3339());
+    }
+    let date = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+    Some(date.and_hms_opt(0, 0, 0)?.and_utc().to_rfc3339())
+}
+
+fn toml_to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => Value::from(f),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(dt) => Value::String(dt.to_string()),
+        toml::Value::Array(items) => Value::Array(items.into_iter().map(toml_to_json).collect()),
+        toml::Value::Table(table) => Value::Object(
+            table
+                .into_iter()
+                .map(|(k, v)| (k, toml_to_json(v)))
+                .collect(),
+        ),
+    }
+}
+
 /// Normalize path for storage (handelize)
 pub fn handelize(path: &str) -> String {
     path.to_lowercase()
@@ -66,6 +233,81 @@ pub fn handelize(path: &str) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_yaml_frontmatter() {
+        let content = "---\ntitle: Weekly Review\ntags: [rust, \"#async\"]\naliases: review, weekly\n\
+                       date: 2024-03-01\npriority: 2\ndraft: false\nauthor: ~\n---\n# Heading\nBody";
+        let fm = parse_frontmatter(content).unwrap();
+        assert_eq!(fm.body, "# Heading\nBody");
+        let m = &fm.metadata;
+        assert_eq!(
+            m.get("title"),
+            Some(&MetadataValue::Text("Weekly Review".into()))
+        );
+        assert_eq!(m.get("tags"), Some(&MetadataValue::tags(["rust", "async"])));
+        assert_eq!(
+            m.get("aliases"),
+            Some(&MetadataValue::tags(["review", "weekly"]))
+        );
+        assert_eq!(
+            m.get("date"),
+            Some(&MetadataValue::DateTime("2024-03-01T00:00:00+00:00".into()))
+        );
+        assert_eq!(m.get("priority"), Some(&MetadataValue::Integer(2)));
+        assert_eq!(m.get("draft"), Some(&MetadataValue::Boolean(false)));
+        assert!(!m.contains("author"));
+    }
+
+    #[test]
+    fn test_parse_toml_frontmatter() {
+        let content =
+            "+++\ntitle = \"Post\"\ntags = [\"a\", \"b\"]\ndate = 2024-03-01T10:00:00Z\n+++\nBody";
+        let fm = parse_frontmatter(content).unwrap();
+        assert_eq!(fm.body, "Body");
+        assert_eq!(
+            fm.metadata.get("tags"),
+            Some(&MetadataValue::tags(["a", "b"]))
+        );
+        assert_eq!(
+            fm.metadata.get("date"),
+            Some(&MetadataValue::DateTime("2024-03-01T10:00:00+00:00".into()))
+        );
+    }
+
+    #[test]
+    fn test_note_date() {
+        let day = |y, m, d| chrono::NaiveDate::from_ymd_opt(y, m, d);
+        assert_eq!(note_date("journal/2024-03-05.md", None), day(2024, 3, 5));
+        assert_eq!(
+            note_date("daily/2024_03_05 standup.md", None),
+            day(2024, 3, 5)
+        );
+        assert_eq!(note_date("2024/03/05.md", None), day(2024, 3, 5));
+        assert_eq!(note_date("log-20240305.md", None), day(2024, 3, 5));
+        assert_eq!(note_date("2023-01-01/2024-03-05.md", None), day(2024, 3, 5));
+        assert_eq!(note_date("notes/2024-13-05.md", None), None);
+        assert_eq!(note_date("notes/2024-0305.md", None), None);
+        assert_eq!(note_date("src/v120240305x.rs", None), None);
+        assert_eq!(note_date("notes/postgres.md", None), None);
+
+        let mut metadata = UserMetadata::new();
+        metadata.add(
+            "date",
+            MetadataValue::DateTime("2024-04-01T00:00:00+00:00".into()),
+        );
+        assert_eq!(
+            note_date("journal/2024-03-05.md", Some(&metadata)),
+            day(2024, 4, 1)
+        );
+    }
+
+    #[test]
+    fn test_parse_frontmatter_absent_or_invalid() {
+        assert!(parse_frontmatter("# Title\n---\nnot frontmatter").is_none());
+        assert!(parse_frontmatter("---\nunterminated: true\n").is_none());
+        assert!(parse_frontmatter("---\n: [bad yaml\n---\nBody").is_none());
+    }
+
     #[test]
     fn test_extract_title_heading() {
         let content = "# My Document\n\nSome content here.";
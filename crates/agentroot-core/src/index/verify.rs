@@ -0,0 +1,341 @@
+//! Embedding verification and repair
+//!
+//! Compares what is stored for every active document with what an embedding
+//! run for the current model would produce, and re-embeds only the documents
+//! that differ. Vectors of content no active document uses are removed.
+
+use super::ast_chunker::SemanticChunker;
+use super::embedder::{document_chunks, embed_selected};
+use crate::db::vectors::stored_dimensions;
+use crate::db::Database;
+use crate::error::Result;
+use crate::llm::Embedder;
+use crate::progress::{ProgressSink, ProgressTracker};
+use rusqlite::params;
+
+/// What is wrong with a document's stored embeddings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbeddingIssue {
+    /// No vectors, or vectors missing for some chunks
+    Missing,
+    /// Embedded with a different model
+    WrongModel,
+    /// Vector length does not match the model's dimensions
+    BadDimensions,
+    /// Stored chunks no longer match the document's chunking
+    Stale,
+}
+
+/// Outcome of [`verify_embeddings`]
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct VerifyReport {
+    pub checked_documents: usize,
+    pub missing: usize,
+    pub wrong_model: usize,
+    pub bad_dimensions: usize,
+    pub stale: usize,
+    /// Vectors of content no active document uses; removed unless dry run
+    pub orphaned_vectors: usize,
+    /// Documents re-embedded; zero for dry runs
+    pub repaired_documents: usize,
+    pub repaired_chunks: usize,
+    pub cached_chunks: usize,
+}
+
+impl VerifyReport {
+    /// Documents needing re-embedding
+    pub fn broken_documents(&self) -> usize {
+        self.missing + self.wrong_model + self.bad_dimensions + self.stale
+    }
+
+    fn count(&mut self, issue: EmbeddingIssue) {
+        match issue {
+            EmbeddingIssue::Missing => self.missing += 1,
+            EmbeddingIssue::WrongModel => self.wrong_model += 1,
+            EmbeddingIssue::BadDimensions => self.bad_dimensions += 1,
+            EmbeddingIssue::Stale => self.stale += 1,
+        }
+    }
+}
+
+/// One stored chunk vector of a document
+struct StoredVector {
+    seq: u32,
+    model: String,
+    chunk_hash: Option<String>,
+    /// Dimensions of the embedding (0 if unreadable), None when the vector
+    /// row is gone
+    dimensions: Option<usize>,
+}
+
+/// Find documents whose embeddings are missing, from another model, of the
+/// wrong size or stale, and re-embed just those unless `dry_run`
+///
+/// Progress covers the verification pass; the repair reports as `embed`.
+pub async fn verify_embeddings(
+    db: &Database,
+    embedder: &dyn Embedder,
+    model: &str,
+    dry_run: bool,
+    progress: &dyn ProgressSink,
+) -> Result<VerifyReport> {
+    if !dry_run {
+        db.ensure_writable()?;
+    }
+    let dimensions = embedder.dimensions();
+    db.ensure_vec_table(dimensions)?;
+
+    let documents = db.active_embedding_documents()?;
+    let mut report = VerifyReport {
+        checked_documents: documents.len(),
+        ..Default::default()
+    };
+
+    let chunker = SemanticChunker::new();
+    let mut tracker = ProgressTracker::new(progress, "verify");
+    tracker.discovered(documents.len());
+    let mut broken = Vec::new();
+    for (hash, path) in documents {
+        let Some(content) = db.get_content(&hash)? else {
+            continue;
+        };
+        let expected: Vec<String> = document_chunks(&chunker, &content, path.as_deref())?
+            .into_iter()
+            .map(|c| c.chunk_hash)
+            .collect();
+        let stored = db.stored_vectors(&hash)?;
+
+        if let Some(issue) = diagnose(&expected, &stored, model, dimensions) {
+            report.count(issue);
+            broken.push((hash, path));
+        }
+        tracker.processed(content.len() as u64, None);
+    }
+    tracker.finish();
+
+    if dry_run {
+        report.orphaned_vectors = db.count_orphaned_vectors()?;
+        return Ok(report);
+    }
+
+    report.orphaned_vectors = db.remove_orphaned_vectors()?;
+    if !broken.is_empty() {
+        // Vectors of the old size would make cached lookups for this model unusable
+        if report.bad_dimensions > 0 {
+            db.set_model_dimensions(model, dimensions)?;
+        }
+        let stats = embed_selected(db, embedder, model, broken, progress).await?;
+        report.repaired_documents = stats.embedded_documents;
+        report.repaired_chunks = stats.embedded_chunks;
+        report.cached_chunks = stats.cached_chunks;
+    }
+    Ok(report)
+}
+
+/// First problem found with a document's vectors, checked in order of
+/// severity; None when they match what embedding it now would store
+fn diagnose(
+    expected: &[String],
+    stored: &[StoredVector],
+    model: &str,
+    dimensions: usize,
+) -> Option<EmbeddingIssue> {
+    if stored.is_empty() {
+        // Empty documents produce no chunks and are never embedded
+        return (!expected.is_empty()).then_some(EmbeddingIssue::Missing);
+    }
+    if stored.iter().any(|v| v.dimensions.is_none()) {
+        return Some(EmbeddingIssue::Missing);
+    }
+    if stored.iter().any(|v| v.model != model) {
+        return Some(EmbeddingIssue::WrongModel);
+    }
+    if stored.iter().any(|v| v.dimensions != Some(dimensions)) {
+        return Some(EmbeddingIssue::BadDimensions);
+    }
+    let matches = stored.len() == expected.len()
+        && stored.iter().zip(0u32..).all(|(v, seq)| {
+            v.seq == seq && v.chunk_hash.as_deref() == Some(&expected[seq as usize])
+        });
+    (!matches).then_some(EmbeddingIssue::Stale)
+}
+
+impl Database {
+    /// (hash, path) of every active content hash, in hash order
+    fn active_embedding_documents(&self) -> Result<Vec<(String, Option<String>)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT c.hash, MIN(d.path) FROM content c
+             JOIN documents d ON d.hash = c.hash AND d.active = 1
+             GROUP BY c.hash
+             ORDER BY c.hash",
+        )?;
+        let results = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(results)
+    }
+
+    fn stored_vectors(&self, hash: &str) -> Result<Vec<StoredVector>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT cv.seq, cv.model, cv.chunk_hash, substr(e.embedding, 1, 8), LENGTH(e.embedding)
+             FROM content_vectors cv
+             LEFT JOIN embeddings e ON e.hash_seq = cv.hash || '_' || cv.seq
+             WHERE cv.hash = ?1
+             ORDER BY cv.seq",
+        )?;
+        let results = stmt
+            .query_map(params![hash], |row| {
+                Ok(StoredVector {
+                    seq: row.get(0)?,
+                    model: row.get(1)?,
+                    chunk_hash: row.get(2)?,
+                    dimensions: match (
+                        row.get::<_, Option<Vec<u8>>>(3)?,
+                        row.get::<_, Option<i64>>(4)?,
+                    ) {
+                        (Some(head), Some(len)) => {
+                            Some(stored_dimensions(&head, len as usize).unwrap_or(0))
+                        }
+                        _ => None,
+                    },
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(results)
+    }
+
+    /// Vector rows not backing any active document's chunk
+    pub(crate) fn count_orphaned_vectors(&self) -> Result<usize> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM embeddings
+             WHERE hash_seq NOT IN (
+                SELECT cv.hash || '_' || cv.seq FROM content_vectors cv
+                JOIN documents d ON d.hash = cv.hash AND d.active = 1
+             )",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    /// Drop vectors of inactive content; returns the vector rows removed
+    fn remove_orphaned_vectors(&self) -> Result<usize> {
+        self.in_transaction(|| {
+            self.conn.execute(
+                "DELETE FROM content_vectors
+                 WHERE hash NOT IN (SELECT hash FROM documents WHERE active = 1)",
+                [],
+            )?;
+            let removed = self.conn.execute(
+                "DELETE FROM embeddings
+                 WHERE hash_seq NOT IN (SELECT hash || '_' || seq FROM content_vectors)",
+                [],
+            )?;
+            Ok(removed)
+        })
+    }
+
+    fn set_model_dimensions(&self, model: &str, dimensions: usize) -> Result<()> {
+        self.conn.execute(
+            "UPDATE model_metadata SET dimensions = ?2 WHERE model = ?1",
+            params![model, dimensions as i64],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::embed_documents;
+    use crate::progress::NoProgress;
+    use async_trait::async_trait;
+    use chrono::Utc;
+
+    struct ConstEmbedder;
+
+    #[async_trait]
+    impl Embedder for ConstEmbedder {
+        async fn embed(&self, _text: &str) -> Result<Vec<f32>> {
+            Ok(vec![1.0, 0.0])
+        }
+
+        async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            Ok(texts.iter().map(|_| vec![1.0, 0.0]).collect())
+        }
+
+        fn dimensions(&self) -> usize {
+            2
+        }
+
+        fn model_name(&self) -> &str {
+            "test"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verify_finds_and_repairs_only_broken_documents() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("notes", "/notes", "**/*.txt", "file", None)
+            .unwrap();
+        for i in 0..5 {
+            let hash = format!("{:06}", i);
+            let path = format!("{}.txt", i);
+            db.insert_content(&hash, &format!("note number {}", i))
+                .unwrap();
+            db.insert_document("notes", &path, &path, &hash, &now, &now, "file", None)
+                .unwrap();
+        }
+        embed_documents(&db, &ConstEmbedder, "test", false, &NoProgress)
+            .await
+            .unwrap();
+
+        let clean = verify_embeddings(&db, &ConstEmbedder, "test", true, &NoProgress)
+            .await
+            .unwrap();
+        assert_eq!(clean.checked_documents, 5);
+        assert_eq!(clean.broken_documents(), 0);
+        assert_eq!(clean.orphaned_vectors, 0);
+
+        db.conn
+            .execute_batch(
+                "DELETE FROM embeddings WHERE hash_seq = '000000_0';
+                 UPDATE content_vectors SET model = 'old' WHERE hash = '000001';
+                 UPDATE embeddings SET embedding = zeroblob(12) WHERE hash_seq = '000002_0';
+                 UPDATE content_vectors SET chunk_hash = 'changed' WHERE hash = '000003';
+                 UPDATE documents SET active = 0 WHERE hash = '000004';",
+            )
+            .unwrap();
+
+        let found = verify_embeddings(&db, &ConstEmbedder, "test", true, &NoProgress)
+            .await
+            .unwrap();
+        assert_eq!(found.checked_documents, 4);
+        assert_eq!(
+            (
+                found.missing,
+                found.wrong_model,
+                found.bad_dimensions,
+                found.stale
+            ),
+            (1, 1, 1, 1)
+        );
+        assert_eq!(found.orphaned_vectors, 1);
+        assert_eq!(found.repaired_documents, 0);
+
+        let repaired = verify_embeddings(&db, &ConstEmbedder, "test", false, &NoProgress)
+            .await
+            .unwrap();
+        assert_eq!(repaired.broken_documents(), 4);
+        assert_eq!(repaired.repaired_documents, 4);
+        assert_eq!(repaired.orphaned_vectors, 1);
+
+        let after = verify_embeddings(&db, &ConstEmbedder, "test", true, &NoProgress)
+            .await
+            .unwrap();
+        assert_eq!(after.broken_documents(), 0);
+        assert_eq!(after.orphaned_vectors, 0);
+    }
+}
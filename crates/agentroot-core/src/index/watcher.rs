@@ -0,0 +1,375 @@
+//! Live reindexing of file collections
+//!
+//! Watches the directories of file-provider collections and reindexes a
+//! collection once edits under it settle. The reindex is the same
+//! stamp-based pass `update` runs, so only files whose modification time or
+//! size changed are read, and their FTS rows, chunks and (when an embedder
+//! is given) embeddings are refreshed; other collections are left alone.
+
+use super::embedder::{embed_documents, EmbedStats};
+use crate::db::{CollectionInfo, Database, ReindexStats};
+use crate::error::Result;
+use crate::llm::Embedder;
+use crate::progress::NoProgress;
+use crate::providers::file::is_excluded_path;
+use glob::Pattern;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// Quiet period after the last edit before a collection is reindexed
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// A file collection whose directory is watched
+#[derive(Debug, Clone)]
+pub struct WatchedCollection {
+    pub name: String,
+    pub root: PathBuf,
+    /// Root with symlinks resolved, as some platforms report event paths
+    canonical_root: PathBuf,
+    pattern: Pattern,
+    exclude_hidden: bool,
+}
+
+impl WatchedCollection {
+    /// Watch settings for a collection, or None unless it reads local files
+    pub fn from_info(info: &CollectionInfo) -> Result<Option<Self>> {
+        if !matches!(info.provider_type.as_str(), "file" | "obsidian") {
+            return Ok(None);
+        }
+        let options: HashMap<String, String> = info
+            .provider_config
+            .as_deref()
+            .and_then(|c| serde_json::from_str(c).ok())
+            .unwrap_or_default();
+        let exclude_hidden = options
+            .get("exclude_hidden")
+            .and_then(|v| v.parse::<bool>().ok())
+            .unwrap_or(true);
+
+        let root = PathBuf::from(&info.path);
+        Ok(Some(Self {
+            name: info.name.clone(),
+            canonical_root: std::fs::canonicalize(&root).unwrap_or_else(|_| root.clone()),
+            root,
+            pattern: Pattern::new(&info.pattern)?,
+            exclude_hidden,
+        }))
+    }
+
+    /// Whether a change at `path` can affect the collection
+    ///
+    /// Files must match the collection's pattern and lie outside the
+    /// directories a scan skips. Paths that are not files, such as removed
+    /// files or moved directories, count when they are under the root, as
+    /// the files they held may be indexed.
+    pub fn matches(&self, path: &Path) -> bool {
+        let Some(relative) = path
+            .strip_prefix(&self.root)
+            .or_else(|_| path.strip_prefix(&self.canonical_root))
+            .ok()
+        else {
+            return false;
+        };
+        if relative.as_os_str().is_empty() || is_excluded_path(relative, self.exclude_hidden) {
+            return false;
+        }
+        !path.is_file() || self.pattern.matches(&relative.to_string_lossy())
+    }
+}
+
+/// File collections of the index that can be watched
+pub fn watched_collections(db: &Database) -> Result<Vec<WatchedCollection>> {
+    let mut watched = Vec::new();
+    for info in db.list_collections()? {
+        watched.extend(WatchedCollection::from_info(&info)?);
+    }
+    Ok(watched)
+}
+
+/// Holds back collections until edits to them stop for a quiet period
+#[derive(Debug)]
+pub struct Debouncer {
+    quiet: Duration,
+    /// Collection -> time of its latest edit
+    pending: HashMap<String, Instant>,
+}
+
+impl Debouncer {
+    pub fn new(quiet: Duration) -> Self {
+        Self {
+            quiet,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Record an edit to a collection, restarting its quiet period
+    pub fn touch(&mut self, collection: &str, now: Instant) {
+        self.pending.insert(collection.to_string(), now);
+    }
+
+    /// When the next pending collection settles
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.pending.values().min().map(|last| *last + self.quiet)
+    }
+
+    /// Remove and return the collections that have been quiet long enough
+    pub fn take_settled(&mut self, now: Instant) -> Vec<String> {
+        let mut settled: Vec<String> = self
+            .pending
+            .iter()
+            .filter(|(_, last)| now.duration_since(**last) >= self.quiet)
+            .map(|(name, _)| name.clone())
+            .collect();
+        settled.sort();
+        for name in &settled {
+            self.pending.remove(name);
+        }
+        settled
+    }
+}
+
+/// Filesystem watcher over a set of file collections
+pub struct CollectionWatcher {
+    // Dropping the watcher stops the events
+    _watcher: RecommendedWatcher,
+    events: mpsc::UnboundedReceiver<notify::Result<Event>>,
+    collections: Vec<WatchedCollection>,
+    ignored: Vec<PathBuf>,
+    debouncer: Debouncer,
+}
+
+impl CollectionWatcher {
+    /// Start watching the roots of `collections`
+    pub fn new(collections: Vec<WatchedCollection>, debounce: Duration) -> Result<Self> {
+        let (tx, events) = mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = tx.send(event);
+        })?;
+        for coll in &collections {
+            watcher.watch(&coll.root, RecursiveMode::Recursive)?;
+        }
+        Ok(Self {
+            _watcher: watcher,
+            events,
+            collections,
+            ignored: Vec::new(),
+            debouncer: Debouncer::new(debounce),
+        })
+    }
+
+    /// Ignore changes to `path` and to files named after it, such as the
+    /// index's own `-wal` and `-shm` files
+    pub fn ignore(mut self, path: &Path) -> Self {
+        self.ignored.push(path.to_path_buf());
+        self.ignored
+            .push(std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()));
+        self
+    }
+
+    pub fn collections(&self) -> &[WatchedCollection] {
+        &self.collections
+    }
+
+    /// Wait until edits settle in at least one collection and return the
+    /// names of the settled collections
+    ///
+    /// Returns None once the watcher stops delivering events.
+    pub async fn next_batch(&mut self) -> Option<Vec<String>> {
+        loop {
+            let event = match self.debouncer.next_deadline() {
+                Some(deadline) => {
+                    match tokio::time::timeout_at(deadline.into(), self.events.recv()).await {
+                        Ok(event) => event,
+                        Err(_) => {
+                            let settled = self.debouncer.take_settled(Instant::now());
+                            if !settled.is_empty() {
+                                return Some(settled);
+                            }
+                            continue;
+                        }
+                    }
+                }
+                None => self.events.recv().await,
+            };
+            match event? {
+                Ok(event) => self.record(&event),
+                Err(e) => tracing::warn!("File watch error: {}", e),
+            }
+        }
+    }
+
+    fn record(&mut self, event: &Event) {
+        if matches!(event.kind, EventKind::Access(_)) {
+            return;
+        }
+        let now = Instant::now();
+        for path in &event.paths {
+            if self.is_ignored(path) {
+                continue;
+            }
+            for coll in &self.collections {
+                if coll.matches(path) {
+                    self.debouncer.touch(&coll.name, now);
+                }
+            }
+        }
+    }
+
+    fn is_ignored(&self, path: &Path) -> bool {
+        let path = path.to_string_lossy();
+        self.ignored
+            .iter()
+            .any(|ignored| path.starts_with(ignored.to_string_lossy().as_ref()))
+    }
+}
+
+/// What reindexing a changed collection did
+#[derive(Debug, Clone)]
+pub struct WatchUpdate {
+    pub collection: String,
+    pub stats: ReindexStats,
+    /// Embedding pass over the changed documents, when an embedder was given
+    pub embed: Option<EmbedStats>,
+    /// Why embedding failed; the reindex itself is kept
+    pub embed_error: Option<String>,
+}
+
+/// Bring a collection up to date after its files changed
+///
+/// Reads only files whose stamps changed, then embeds the documents that
+/// lost their embeddings, reusing cached vectors for unchanged chunks. An
+/// unreachable embedding service does not undo the reindex; the documents
+/// are embedded by a later pass or `embed` run.
+pub async fn reindex_changed(
+    db: &Database,
+    collection: &str,
+    embedder: Option<&dyn Embedder>,
+) -> Result<WatchUpdate> {
+    let stats = db
+        .reindex_collection_with_progress(collection, &NoProgress)
+        .await?;
+    let mut update = WatchUpdate {
+        collection: collection.to_string(),
+        stats,
+        embed: None,
+        embed_error: None,
+    };
+    if let Some(embedder) = embedder.filter(|_| stats.updated > 0) {
+        match embed_documents(db, embedder, embedder.model_name(), false, &NoProgress).await {
+            Ok(embed) => update.embed = Some(embed),
+            Err(e) => update.embed_error = Some(e.to_string()),
+        }
+    }
+    Ok(update)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn info(root: &Path, provider_type: &str, config: Option<&str>) -> CollectionInfo {
+        CollectionInfo {
+            name: "notes".to_string(),
+            path: root.to_string_lossy().to_string(),
+            pattern: "**/*.md".to_string(),
+            document_count: 0,
+            created_at: String::new(),
+            updated_at: String::new(),
+            provider_type: provider_type.to_string(),
+            provider_config: config.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_matches_respects_pattern_and_excluded_dirs() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::create_dir_all(root.join("node_modules/pkg")).unwrap();
+        fs::write(root.join("docs/guide.md"), "# Guide").unwrap();
+        fs::write(root.join("docs/build.log"), "ok").unwrap();
+        fs::write(root.join("node_modules/pkg/README.md"), "# Pkg").unwrap();
+        fs::write(root.join(".draft.md"), "# Draft").unwrap();
+
+        let coll = WatchedCollection::from_info(&info(root, "file", None))
+            .unwrap()
+            .unwrap();
+        assert!(coll.matches(&root.join("docs/guide.md")));
+        assert!(!coll.matches(&root.join("docs/build.log")));
+        assert!(!coll.matches(&root.join("node_modules/pkg/README.md")));
+        assert!(!coll.matches(&root.join(".draft.md")));
+        // A deleted file or a moved directory may drop indexed files
+        assert!(coll.matches(&root.join("docs/removed.md")));
+        assert!(coll.matches(&root.join("docs")));
+        assert!(!coll.matches(root));
+        assert!(!coll.matches(Path::new("/elsewhere/guide.md")));
+
+        let hidden = info(root, "file", Some(r#"{"exclude_hidden":"false"}"#));
+        let coll = WatchedCollection::from_info(&hidden).unwrap().unwrap();
+        assert!(coll.matches(&root.join(".draft.md")));
+
+        let web = info(root, "url", None);
+        assert!(WatchedCollection::from_info(&web).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_reindex_changed_reads_edited_files() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::write(root.join("a.md"), "# A\n\nFirst draft.").unwrap();
+        fs::write(root.join("b.md"), "# B\n\nUnchanged.").unwrap();
+
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.add_collection("notes", &root.to_string_lossy(), "**/*.md", "file", None)
+            .unwrap();
+        db.reindex_collection("notes").await.unwrap();
+
+        fs::write(root.join("a.md"), "# A\n\nSecond draft, longer.").unwrap();
+        fs::remove_file(root.join("b.md")).unwrap();
+        let update = reindex_changed(&db, "notes", None).await.unwrap();
+        assert_eq!(update.stats.updated, 1);
+        assert_eq!(update.stats.deleted, 1);
+        assert!(update.embed.is_none() && update.embed_error.is_none());
+
+        let hits = db
+            .search_fts("second draft", &crate::search::SearchOptions::default())
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+    }
+
+    #[test]
+    fn test_debouncer_waits_for_quiet_period() {
+        let start = Instant::now();
+        let quiet = Duration::from_millis(500);
+        let mut debouncer = Debouncer::new(quiet);
+        assert_eq!(debouncer.next_deadline(), None);
+
+        debouncer.touch("a", start);
+        debouncer.touch("b", start + Duration::from_millis(100));
+        // Another edit to a restarts its quiet period
+        debouncer.touch("a", start + Duration::from_millis(400));
+        assert_eq!(
+            debouncer.next_deadline(),
+            Some(start + Duration::from_millis(600))
+        );
+
+        assert!(debouncer
+            .take_settled(start + Duration::from_millis(550))
+            .is_empty());
+        assert_eq!(
+            debouncer.take_settled(start + Duration::from_millis(600)),
+            vec!["b".to_string()]
+        );
+        assert_eq!(
+            debouncer.take_settled(start + Duration::from_millis(900)),
+            vec!["a".to_string()]
+        );
+        assert_eq!(debouncer.next_deadline(), None);
+    }
+}
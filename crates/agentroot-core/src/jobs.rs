@@ -0,0 +1,191 @@
+//! Background job execution
+//!
+//! Jobs are stored by [`Database::enqueue_job`] and executed here, either on
+//! demand (`agentroot jobs run`) or by a worker thread that long-running
+//! processes such as the MCP server start alongside their request loop.
+
+use crate::db::{Database, Job, JobKind, JobStatus};
+use crate::error::{AgentRootError, Result};
+use crate::index::embed_documents;
+use crate::llm::{default_embedder, HttpMetadataGenerator, MetadataGenerator};
+use crate::progress::NoProgress;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// How often an idle worker checks for new jobs
+pub const WORKER_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Running jobs older than this are assumed orphaned by a dead worker
+pub const STALE_JOB_AFTER: Duration = Duration::from_secs(2 * 60 * 60);
+
+/// Execute a job, returning a short summary of what it did
+pub async fn run_job(db: &Database, kind: &JobKind) -> Result<String> {
+    match kind {
+        JobKind::Reindex { collection } => {
+            // Generate metadata along the way when a service is configured, like `update`
+            let generator = HttpMetadataGenerator::from_env().ok();
+            let generator = generator.as_ref().map(|g| g as &dyn MetadataGenerator);
+            let mut updated = 0;
+            for name in target_collections(db, collection.as_deref())? {
+                updated += db
+                    .reindex_collection_with_metadata(&name, generator)
+                    .await?;
+            }
+            Ok(format!("{} documents updated", updated))
+        }
+        JobKind::Embed { force } => {
+            let embedder = default_embedder().await?;
+            let stats = embed_documents(
+                db,
+                embedder.as_ref(),
+                embedder.model_name(),
+                *force,
+                &NoProgress,
+            )
+            .await?;
+            Ok(format!(
+                "{} documents, {} chunks embedded ({} cached)",
+                stats.embedded_documents, stats.embedded_chunks, stats.cached_chunks
+            ))
+        }
+        JobKind::MetadataBackfill { collection } => {
+            let generator = HttpMetadataGenerator::from_env().map_err(|_| {
+                AgentRootError::Config(
+                    "No metadata generation service configured (set AGENTROOT_LLM_URL)".to_string(),
+                )
+            })?;
+            let mut updated = 0;
+            for name in target_collections(db, collection.as_deref())? {
+                updated += db
+                    .reindex_collection_with_metadata(&name, Some(&generator))
+                    .await?;
+            }
+            Ok(format!("{} documents updated", updated))
+        }
+        JobKind::Pagerank => {
+            let links = db.build_link_graph()?;
+            db.compute_and_store_pagerank()?;
+            let (scored, _) = db.get_pagerank_stats()?;
+            Ok(format!("{} links, {} documents scored", links, scored))
+        }
+    }
+}
+
+fn target_collections(db: &Database, collection: Option<&str>) -> Result<Vec<String>> {
+    match collection {
+        Some(name) => Ok(vec![name.to_string()]),
+        None => Ok(db.list_collections()?.into_iter().map(|c| c.name).collect()),
+    }
+}
+
+/// Claim and run the next runnable job, returning it in its final state
+///
+/// Returns None when nothing is runnable.
+pub async fn run_next_job(db: &Database) -> Result<Option<Job>> {
+    let Some(job) = db.claim_next_job()? else {
+        return Ok(None);
+    };
+
+    let span = tracing::info_span!("job", id = job.id, kind = job.kind.name());
+    let outcome = tracing::Instrument::instrument(run_job(db, &job.kind), span).await;
+    match outcome {
+        Ok(summary) => db.complete_job(job.id, &summary)?,
+        Err(e) => {
+            let status = db.fail_job(job.id, &e.to_string())?;
+            if status == JobStatus::Failed {
+                tracing::warn!("Job {} ({}) failed: {}", job.id, job.kind.name(), e);
+            }
+        }
+    }
+    db.get_job(job.id)
+}
+
+/// Run runnable jobs until the queue is empty or `max` jobs have run
+pub async fn drain_jobs(db: &Database, max: usize) -> Result<Vec<Job>> {
+    let mut finished = Vec::new();
+    while finished.len() < max {
+        match run_next_job(db).await? {
+            Some(job) => finished.push(job),
+            None => break,
+        }
+    }
+    Ok(finished)
+}
+
+/// Start a worker thread that drains the queue for the life of the process
+///
+/// The worker opens its own connection to `db_path`, so the caller's
+/// connection stays free for interactive requests.
+pub fn spawn_worker(db_path: PathBuf) -> std::io::Result<std::thread::JoinHandle<()>> {
+    std::thread::Builder::new()
+        .name("agentroot-jobs".to_string())
+        .spawn(move || {
+            let runtime = match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    tracing::warn!("Job worker disabled: {}", e);
+                    return;
+                }
+            };
+            runtime.block_on(worker_loop(db_path));
+        })
+}
+
+async fn worker_loop(db_path: PathBuf) {
+    let db = match Database::open(&db_path).and_then(|db| db.initialize().map(|_| db)) {
+        Ok(db) => db,
+        Err(e) => {
+            tracing::warn!("Job worker disabled: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = db.recover_stale_jobs(STALE_JOB_AFTER) {
+        tracing::warn!("Failed to recover stale jobs: {}", e);
+    }
+
+    loop {
+        match run_next_job(&db).await {
+            Ok(Some(_)) => continue,
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Job worker error: {}", e),
+        }
+        tokio::time::sleep(WORKER_POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_drain_runs_and_retries() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+
+        let pagerank = db.enqueue_job(&JobKind::Pagerank, 0).unwrap();
+        let missing = db
+            .enqueue_job(
+                &JobKind::Reindex {
+                    collection: Some("missing".to_string()),
+                },
+                1,
+            )
+            .unwrap();
+
+        let finished = drain_jobs(&db, 10).await.unwrap();
+        assert_eq!(finished.len(), 2);
+        // Higher priority first; the failure is requeued with backoff
+        assert_eq!(finished[0].id, missing);
+        assert_eq!(finished[0].status, JobStatus::Queued);
+        assert!(finished[0]
+            .last_error
+            .as_deref()
+            .unwrap()
+            .contains("missing"));
+        assert_eq!(finished[1].id, pagerank);
+        assert_eq!(finished[1].status, JobStatus::Succeeded);
+    }
+}
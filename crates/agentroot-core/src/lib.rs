@@ -11,34 +11,44 @@
 
 pub mod config;
 pub mod db;
+pub mod doctor;
 pub mod error;
 pub mod graph;
 pub mod index;
+pub mod jobs;
 pub mod llm;
+pub mod progress;
+pub mod project;
 pub mod providers;
+pub mod schedule;
 pub mod search;
 
-pub use config::{CollectionConfig, Config, LLMServiceConfig};
+pub use config::{
+    CollectionConfig, Config, LLMProvider, LLMServiceConfig, PiiMaskConfig, RankingWeights,
+};
 pub use db::{
     Database, MemoryInfo, MemoryStats, MetadataBuilder, MetadataFilter, MetadataValue, UserMetadata,
 };
 pub use error::{AgentRootError, Error, Result};
 pub use graph::{compute_pagerank, extract_links};
-pub use index::{chunk_semantic, ChunkType, SemanticChunk, SemanticChunker};
+pub use index::{chunk_semantic, ChunkType, ReferenceKind, SemanticChunk, SemanticChunker};
 pub use llm::{
-    ChatMessage, DocumentMetadata, Embedder, ExtractedMemory, HttpEmbedder, HttpMetadataGenerator,
-    HttpQueryExpander, HttpQueryParser, HttpReranker, LLMClient, MemoryExtractor, MetadataContext,
-    MetadataFilterHint, MetadataGenerator, MetricsSnapshot, ParsedQuery, QueryExpander, Reranker,
-    SearchType, TemporalFilter, VLLMClient,
+    default_embedder, embedder_for_model, ChatMessage, DocumentMetadata, Embedder, ExtractedMemory,
+    HttpEmbedder, HttpMetadataGenerator, HttpQueryExpander, HttpQueryParser, HttpReranker,
+    LLMClient, MemoryExtractor, MetadataContext, MetadataFilterHint, MetadataGenerator,
+    MetricsSnapshot, ParsedQuery, QueryExpander, Reranker, SearchType, TemporalFilter, VLLMClient,
 };
+pub use progress::{CancellationToken, NoProgress, ProgressSink, ProgressTracker, ProgressUpdate};
 pub use providers::{
-    CSVProvider, FileProvider, GitHubProvider, JSONProvider, PDFProvider, ProviderConfig,
-    ProviderRegistry, SQLProvider, SourceItem, SourceProvider, URLProvider,
+    CSVProvider, EmailProvider, FileProvider, FileStamp, GitHubProvider, JSONProvider,
+    ObsidianProvider, PDFProvider, ProviderConfig, ProviderListing, ProviderRegistry, SQLProvider,
+    SkipReason, SkippedItem, SourceItem, SourceProvider, URLProvider,
 };
 pub use search::{
-    orchestrated_search, parse_metadata_filters, smart_search, unified_search, AnnIndex,
-    DetailLevel, SearchContext, SearchOptions, SearchResult, SearchSource, SearchStats,
-    SearchStatsSnapshot,
+    build_context, build_hybrid_context, journal_search, multi_query_search, orchestrated_search,
+    orchestrated_search_cancellable, parse_date_phrase, parse_metadata_filters, smart_search,
+    unified_search, AnnIndex, DateRange, DetailLevel, SearchContext, SearchOptions, SearchResult,
+    SearchSource, SearchStats, SearchStatsSnapshot,
 };
 
 /// Virtual path prefix for agentroot URIs
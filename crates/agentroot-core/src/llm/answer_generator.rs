@@ -0,0 +1,114 @@
+//! Grounded answers from context packs
+
+use crate::error::Result;
+use crate::llm::client::{chat_client_from_env, ChatMessage, LLMClient};
+use crate::search::{ContextChunk, ContextPack};
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Answer given when retrieval finds nothing to ground on
+pub const NO_CONTEXT_ANSWER: &str = "No relevant documents were found to answer this question.";
+
+const ANSWER_PROMPT: &str = r#"You answer questions using only the numbered context excerpts you are given.
+
+Rules:
+- Base every statement on the excerpts; do not use outside knowledge.
+- Cite the excerpts a statement relies on with their numbers in brackets, e.g. [1] or [2][3].
+- If the excerpts do not contain the answer, say so plainly instead of guessing.
+- Be concise. Answer in plain prose or short lists."#;
+
+/// An answer and the sources it was grounded on
+#[derive(Debug, Clone, Serialize)]
+pub struct Answer {
+    pub question: String,
+    pub answer: String,
+    /// Context chunks given to the LLM, numbered as cited
+    pub sources: Vec<ContextChunk>,
+    /// Citation numbers the answer refers to, ascending
+    pub cited: Vec<usize>,
+}
+
+pub struct AnswerGenerator {
+    client: Arc<dyn LLMClient>,
+}
+
+impl AnswerGenerator {
+    pub fn new(client: Arc<dyn LLMClient>) -> Self {
+        Self { client }
+    }
+
+    pub fn from_env() -> Result<Self> {
+        Ok(Self {
+            client: chat_client_from_env()?,
+        })
+    }
+
+    /// Answer `question` from the chunks of `pack`
+    ///
+    /// An empty pack is answered without calling the LLM.
+    pub async fn answer(&self, question: &str, pack: &ContextPack) -> Result<Answer> {
+        if pack.chunks.is_empty() {
+            return Ok(Answer {
+                question: question.to_string(),
+                answer: NO_CONTEXT_ANSWER.to_string(),
+                sources: Vec::new(),
+                cited: Vec::new(),
+            });
+        }
+
+        let prompt = format!("Context:\n\n{}\nQuestion: {}", pack.text, question);
+        let messages = vec![
+            ChatMessage::system(ANSWER_PROMPT),
+            ChatMessage::user(prompt),
+        ];
+        let response = self.client.chat_completion(messages).await?;
+        let answer = response.trim().to_string();
+
+        Ok(Answer {
+            question: question.to_string(),
+            cited: cited_numbers(&answer, pack.chunks.len()),
+            answer,
+            sources: pack.chunks.clone(),
+        })
+    }
+}
+
+/// Citation numbers in `[n]` or `[n, m]` form that name one of `count` sources
+fn cited_numbers(answer: &str, count: usize) -> Vec<usize> {
+    let mut cited = Vec::new();
+    for group in answer.split('[').skip(1) {
+        let Some((inside, _)) = group.split_once(']') else {
+            continue;
+        };
+        let numbers: Option<Vec<usize>> = inside
+            .split(',')
+            .map(|n| n.trim().parse::<usize>().ok())
+            .collect();
+        for n in numbers.unwrap_or_default() {
+            if (1..=count).contains(&n) && !cited.contains(&n) {
+                cited.push(n);
+            }
+        }
+    }
+    cited.sort_unstable();
+    cited
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cited_numbers() {
+        assert_eq!(
+            cited_numbers("Caches expire hourly [2]. Embeddings stay [1, 3][2].", 3),
+            vec![1, 2, 3]
+        );
+        // Out of range and non-numeric brackets are ignored
+        assert_eq!(
+            cited_numbers("See [4] and [a] and [1 ", 3),
+            Vec::<usize>::new()
+        );
+        assert_eq!(cited_numbers("[x][1]", 1), vec![1]);
+    }
+}
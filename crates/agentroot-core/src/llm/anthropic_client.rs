@@ -0,0 +1,232 @@
+//! HTTP client for the Anthropic Messages API
+
+use super::client::{ChatMessage, LLMClient};
+use super::resilience::{send_with_retry, RetryPolicy, ServiceHealth};
+use crate::config::LLMServiceConfig;
+use crate::error::{AgentRootError, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Version of the Messages API requests are written against
+const API_VERSION: &str = "2023-06-01";
+
+/// Upper bound on generated tokens, as for chat completions
+const MAX_TOKENS: u32 = 512;
+
+/// Chat client for Claude models
+///
+/// Serves query parsing, metadata generation and the other chat-based
+/// features. Anthropic offers no embeddings, so vectors still come from the
+/// embedding service or the local model.
+pub struct AnthropicClient {
+    http_client: reqwest::Client,
+    config: LLMServiceConfig,
+    api_key: String,
+    cache: Arc<super::cache::LLMCache>,
+    masker: Option<super::PiiMasker>,
+    health: Arc<ServiceHealth>,
+    retry: RetryPolicy,
+}
+
+#[derive(Debug, Serialize)]
+struct MessagesRequest {
+    model: String,
+    max_tokens: u32,
+    temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<String>,
+    messages: Vec<ChatMessage>,
+}
+
+#[derive(Deserialize)]
+struct MessagesResponse {
+    content: Vec<ContentBlock>,
+}
+
+#[derive(Deserialize)]
+struct ContentBlock {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    text: String,
+}
+
+impl AnthropicClient {
+    /// Create from configuration; the API key is `api_key`, else
+    /// `ANTHROPIC_API_KEY`
+    pub fn new(config: LLMServiceConfig) -> Result<Self> {
+        let api_key = config
+            .api_key
+            .clone()
+            .or_else(|| std::env::var("ANTHROPIC_API_KEY").ok())
+            .ok_or_else(|| {
+                AgentRootError::Config(
+                    "Anthropic provider needs AGENTROOT_LLM_API_KEY or ANTHROPIC_API_KEY"
+                        .to_string(),
+                )
+            })?;
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(AgentRootError::Http)?;
+        let masker = super::PiiMasker::from_config(&config.pii_mask)?;
+        let health = ServiceHealth::for_service(&config.url);
+
+        Ok(Self {
+            http_client,
+            config,
+            api_key,
+            cache: Arc::new(super::cache::LLMCache::new()),
+            masker,
+            health,
+            retry: RetryPolicy::from_env(),
+        })
+    }
+
+    /// Create from environment variables (with config file fallback)
+    pub fn from_env() -> Result<Self> {
+        Self::new(LLMServiceConfig::from_env_or_config())
+    }
+}
+
+/// Move system messages into the request's `system` field, which is where
+/// the Messages API expects them
+fn build_request(model: &str, messages: Vec<ChatMessage>) -> MessagesRequest {
+    let (system, messages): (Vec<_>, Vec<_>) =
+        messages.into_iter().partition(|m| m.role == "system");
+    let system: Vec<String> = system.into_iter().map(|m| m.content).collect();
+    MessagesRequest {
+        model: model.to_string(),
+        max_tokens: MAX_TOKENS,
+        temperature: 0.7,
+        system: (!system.is_empty()).then(|| system.join("\n\n")),
+        messages,
+    }
+}
+
+/// Text of the response's text blocks
+fn response_text(response: MessagesResponse) -> Result<String> {
+    let text: String = response
+        .content
+        .into_iter()
+        .filter(|block| block.kind == "text")
+        .map(|block| block.text)
+        .collect();
+    if text.is_empty() {
+        return Err(AgentRootError::Llm("No response from LLM".to_string()));
+    }
+    Ok(text)
+}
+
+#[async_trait]
+impl LLMClient for AnthropicClient {
+    #[tracing::instrument(
+        name = "llm_chat",
+        skip_all,
+        fields(model = %self.config.model, messages = messages.len())
+    )]
+    async fn chat_completion(&self, mut messages: Vec<ChatMessage>) -> Result<String> {
+        if let Some(masker) = &self.masker {
+            for message in &mut messages {
+                if let Cow::Owned(masked) = masker.mask(&message.content) {
+                    message.content = masked;
+                }
+            }
+        }
+
+        let messages_json = serde_json::to_string(&messages).unwrap_or_default();
+        let cache_key = super::cache::chat_cache_key(&self.config.model, &messages_json);
+        if let Some(cached) = self.cache.get(&cache_key) {
+            tracing::debug!("Cache hit for chat completion");
+            return Ok(cached);
+        }
+
+        let request = build_request(&self.config.model, messages);
+        let url = format!("{}/v1/messages", self.config.url.trim_end_matches('/'));
+        let req = self
+            .http_client
+            .post(&url)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", API_VERSION)
+            .json(&request);
+        let response = send_with_retry(&self.health, &self.retry, req).await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(AgentRootError::ExternalError(format!(
+                "Anthropic API error (HTTP {}): {}",
+                status, body
+            )));
+        }
+
+        let content = response_text(response.json().await?)?;
+        let _ = self.cache.set(cache_key, content.clone());
+        Ok(content)
+    }
+
+    async fn embed(&self, _text: &str) -> Result<Vec<f32>> {
+        Err(no_embeddings())
+    }
+
+    async fn embed_batch(&self, _texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Err(no_embeddings())
+    }
+
+    fn embedding_dimensions(&self) -> usize {
+        self.config.embedding_dimensions.unwrap_or(0)
+    }
+
+    fn model_name(&self) -> &str {
+        &self.config.model
+    }
+}
+
+fn no_embeddings() -> AgentRootError {
+    AgentRootError::Llm(
+        "The Anthropic API has no embeddings; use AGENTROOT_EMBEDDING_URL or the local model"
+            .to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_messages_move_to_system_field() {
+        let request = build_request(
+            "claude-haiku-4-5",
+            vec![
+                ChatMessage::system("Output JSON."),
+                ChatMessage::user("Parse: rust files from last week"),
+            ],
+        );
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["system"], "Output JSON.");
+        assert_eq!(json["max_tokens"], MAX_TOKENS);
+        assert_eq!(json["messages"].as_array().unwrap().len(), 1);
+        assert_eq!(json["messages"][0]["role"], "user");
+
+        let request = build_request("claude-haiku-4-5", vec![ChatMessage::user("hi")]);
+        assert!(serde_json::to_value(&request)
+            .unwrap()
+            .get("system")
+            .is_none());
+    }
+
+    #[test]
+    fn test_response_text_joins_text_blocks() {
+        let response: MessagesResponse = serde_json::from_str(
+            r#"{"content":[{"type":"text","text":"{\"a\":"},{"type":"tool_use","id":"x"},{"type":"text","text":"1}"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(response_text(response).unwrap(), "{\"a\":1}");
+
+        let empty: MessagesResponse = serde_json::from_str(r#"{"content":[]}"#).unwrap();
+        assert!(response_text(empty).is_err());
+    }
+}
@@ -1,11 +1,13 @@
 //! HTTP client for external LLM services (vLLM, OpenAI, etc.)
 
-use crate::config::LLMServiceConfig;
+use super::resilience::{send_with_retry, RetryPolicy, ServiceHealth};
+use crate::config::{LLMProvider, LLMServiceConfig};
 use crate::error::{AgentRootError, Result};
 use crate::llm::{DocumentMetadata, MetadataContext};
 use async_trait::async_trait;
 use futures::stream;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::sync::{atomic::AtomicU64, Arc};
 use std::time::{Duration, Instant};
 
@@ -68,6 +70,10 @@ pub struct VLLMClient {
     embedding_dimensions: usize,
     cache: Arc<super::cache::LLMCache>,
     metrics: Arc<APIMetrics>,
+    masker: Option<super::PiiMasker>,
+    chat_health: Arc<ServiceHealth>,
+    embed_health: Arc<ServiceHealth>,
+    retry: RetryPolicy,
 }
 
 impl VLLMClient {
@@ -87,12 +93,20 @@ impl VLLMClient {
         // Initialize metrics
         let metrics = Arc::new(APIMetrics::default());
 
+        let masker = super::PiiMasker::from_config(&config.pii_mask)?;
+        let chat_health = ServiceHealth::for_service(&config.url);
+        let embed_health = ServiceHealth::for_service(config.embeddings_url());
+
         Ok(Self {
             http_client,
             config,
             embedding_dimensions,
             cache,
             metrics,
+            masker,
+            chat_health,
+            embed_health,
+            retry: RetryPolicy::from_env(),
         })
     }
 
@@ -237,9 +251,22 @@ pub struct MetricsSnapshot {
 
 #[async_trait]
 impl LLMClient for VLLMClient {
-    async fn chat_completion(&self, messages: Vec<ChatMessage>) -> Result<String> {
+    #[tracing::instrument(
+        name = "llm_chat",
+        skip_all,
+        fields(model = %self.config.model, messages = messages.len())
+    )]
+    async fn chat_completion(&self, mut messages: Vec<ChatMessage>) -> Result<String> {
         use std::sync::atomic::Ordering;
 
+        if let Some(masker) = &self.masker {
+            for message in &mut messages {
+                if let Cow::Owned(masked) = masker.mask(&message.content) {
+                    message.content = masked;
+                }
+            }
+        }
+
         let start = Instant::now();
         self.metrics.total_requests.fetch_add(1, Ordering::Relaxed);
 
@@ -288,10 +315,11 @@ impl LLMClient for VLLMClient {
             req = req.header("Authorization", format!("Bearer {}", api_key));
         }
 
-        let response = req.send().await.map_err(|e| {
-            self.metrics.total_errors.fetch_add(1, Ordering::Relaxed);
-            AgentRootError::Http(e)
-        })?;
+        let response = send_with_retry(&self.chat_health, &self.retry, req)
+            .await
+            .inspect_err(|_| {
+                self.metrics.total_errors.fetch_add(1, Ordering::Relaxed);
+            })?;
 
         if !response.status().is_success() {
             self.metrics.total_errors.fetch_add(1, Ordering::Relaxed);
@@ -339,9 +367,23 @@ impl LLMClient for VLLMClient {
             .ok_or_else(|| AgentRootError::Llm("No embedding returned".to_string()))
     }
 
+    #[tracing::instrument(
+        name = "llm_embed",
+        skip_all,
+        fields(model = %self.config.embedding_model, texts = texts.len())
+    )]
     async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
         use std::sync::atomic::Ordering;
 
+        let masked: Vec<String>;
+        let texts = match &self.masker {
+            Some(masker) => {
+                masked = texts.iter().map(|t| masker.mask(t).into_owned()).collect();
+                &masked[..]
+            }
+            None => texts,
+        };
+
         let start = Instant::now();
         self.metrics.total_requests.fetch_add(1, Ordering::Relaxed);
 
@@ -407,10 +449,11 @@ impl LLMClient for VLLMClient {
             req = req.header("Authorization", format!("Bearer {}", api_key));
         }
 
-        let response = req.send().await.map_err(|e| {
-            self.metrics.total_errors.fetch_add(1, Ordering::Relaxed);
-            AgentRootError::Http(e)
-        })?;
+        let response = send_with_retry(&self.embed_health, &self.retry, req)
+            .await
+            .inspect_err(|_| {
+                self.metrics.total_errors.fetch_add(1, Ordering::Relaxed);
+            })?;
 
         if !response.status().is_success() {
             self.metrics.total_errors.fetch_add(1, Ordering::Relaxed);
@@ -458,6 +501,19 @@ impl LLMClient for VLLMClient {
     }
 }
 
+/// Chat client for the configured provider
+pub fn chat_client(config: LLMServiceConfig) -> Result<Arc<dyn LLMClient>> {
+    Ok(match config.provider {
+        LLMProvider::OpenAi => Arc::new(VLLMClient::new(config)?),
+        LLMProvider::Anthropic => Arc::new(super::AnthropicClient::new(config)?),
+    })
+}
+
+/// Chat client for the provider configured by environment or config file
+pub fn chat_client_from_env() -> Result<Arc<dyn LLMClient>> {
+    chat_client(LLMServiceConfig::from_env_or_config())
+}
+
 /// Helper to generate metadata using LLM client
 pub async fn generate_metadata_with_llm(
     client: &dyn LLMClient,
@@ -0,0 +1,35 @@
+//! Choice between the embedding service and the local model
+
+use super::{Embedder, HttpEmbedder};
+use crate::config::LLMServiceConfig;
+use crate::error::Result;
+
+/// Embedder for this environment
+///
+/// The embedding service when `AGENTROOT_EMBEDDING_URL` (or `embedding_url`
+/// in the config file) is set. Otherwise the model named by
+/// `AGENTROOT_EMBEDDING_MODEL` runs locally, so vector search works offline;
+/// builds without the `local-embeddings` feature use the LLM service URL.
+pub async fn default_embedder() -> Result<Box<dyn Embedder>> {
+    let config = LLMServiceConfig::from_env_or_config();
+    #[cfg(feature = "local-embeddings")]
+    if config.embedding_url.is_none() {
+        return Ok(Box::new(super::LocalEmbedder::from_env().await?));
+    }
+    Ok(Box::new(HttpEmbedder::from_config(config)?))
+}
+
+/// Embedder for `model` in this environment, whatever the configured model
+///
+/// Chooses between the embedding service and the local model the same way
+/// as [`default_embedder`]. A service reports `AGENTROOT_EMBEDDING_DIMS`,
+/// which should then describe `model`.
+pub async fn embedder_for_model(model: &str) -> Result<Box<dyn Embedder>> {
+    let mut config = LLMServiceConfig::from_env_or_config();
+    config.embedding_model = model.to_string();
+    #[cfg(feature = "local-embeddings")]
+    if config.embedding_url.is_none() {
+        return Ok(Box::new(super::LocalEmbedder::load(model).await?));
+    }
+    Ok(Box::new(HttpEmbedder::from_config(config)?))
+}
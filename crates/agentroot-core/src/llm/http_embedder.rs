@@ -1,46 +1,64 @@
 //! HTTP-based embedder using external LLM service
 
-use super::{Embedder, LLMClient};
+use super::{Embedder, LLMClient, RateLimiter};
 use crate::config::LLMServiceConfig;
 use crate::error::Result;
 use async_trait::async_trait;
 use std::sync::Arc;
 
 /// Embedder that uses external HTTP service (vLLM, OpenAI, etc.)
+///
+/// Batches are paced by the configured requests- and tokens-per-minute
+/// limits, if any.
 pub struct HttpEmbedder {
     client: Arc<dyn LLMClient>,
+    limiter: Option<RateLimiter>,
 }
 
 impl HttpEmbedder {
     /// Create from LLM client
     pub fn new(client: Arc<dyn LLMClient>) -> Self {
-        Self { client }
+        Self {
+            client,
+            limiter: None,
+        }
     }
 
     /// Create from configuration
     pub fn from_config(config: LLMServiceConfig) -> Result<Self> {
+        let limiter = RateLimiter::from_config(&config);
         let client = super::VLLMClient::new(config)?;
         Ok(Self {
             client: Arc::new(client),
+            limiter,
         })
     }
 
     /// Create from environment variables
     pub fn from_env() -> Result<Self> {
-        let client = super::VLLMClient::from_env()?;
-        Ok(Self {
-            client: Arc::new(client),
-        })
+        Self::from_config(LLMServiceConfig::from_env_or_config())
+    }
+
+    /// Pace requests with a rate limiter
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.limiter = Some(limiter);
+        self
     }
 }
 
 #[async_trait]
 impl Embedder for HttpEmbedder {
     async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        if let Some(limiter) = &self.limiter {
+            limiter.acquire(&[text.to_string()]).await;
+        }
         self.client.embed(text).await
     }
 
     async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        if let Some(limiter) = &self.limiter {
+            limiter.acquire(texts).await;
+        }
         self.client.embed_batch(texts).await
     }
 
@@ -19,17 +19,15 @@ impl HttpMetadataGenerator {
 
     /// Create from configuration
     pub fn from_config(config: LLMServiceConfig) -> Result<Self> {
-        let client = super::VLLMClient::new(config)?;
         Ok(Self {
-            client: Arc::new(client),
+            client: super::chat_client(config)?,
         })
     }
 
     /// Create from environment variables
     pub fn from_env() -> Result<Self> {
-        let client = super::VLLMClient::from_env()?;
         Ok(Self {
-            client: Arc::new(client),
+            client: super::chat_client_from_env()?,
         })
     }
 
@@ -229,6 +227,7 @@ JSON:"#,
 
 #[async_trait]
 impl MetadataGenerator for HttpMetadataGenerator {
+    #[tracing::instrument(name = "generate_metadata", skip_all, fields(collection = %context.collection_name))]
     async fn generate_metadata(
         &self,
         content: &str,
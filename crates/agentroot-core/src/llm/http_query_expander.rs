@@ -19,23 +19,22 @@ impl HttpQueryExpander {
 
     /// Create from configuration
     pub fn from_config(config: LLMServiceConfig) -> Result<Self> {
-        let client = super::VLLMClient::new(config)?;
         Ok(Self {
-            client: Arc::new(client),
+            client: super::chat_client(config)?,
         })
     }
 
     /// Create from environment variables
     pub fn from_env() -> Result<Self> {
-        let client = super::VLLMClient::from_env()?;
         Ok(Self {
-            client: Arc::new(client),
+            client: super::chat_client_from_env()?,
         })
     }
 }
 
 #[async_trait]
 impl QueryExpander for HttpQueryExpander {
+    #[tracing::instrument(name = "expand_query", skip_all)]
     async fn expand(&self, query: &str, context: Option<&str>) -> Result<ExpandedQuery> {
         let prompt = build_expansion_prompt(query, context);
 
@@ -19,21 +19,20 @@ impl HttpQueryParser {
 
     /// Create from configuration
     pub fn from_config(config: LLMServiceConfig) -> Result<Self> {
-        let client = super::VLLMClient::new(config)?;
         Ok(Self {
-            client: Arc::new(client),
+            client: super::chat_client(config)?,
         })
     }
 
     /// Create from environment variables
     pub fn from_env() -> Result<Self> {
-        let client = super::VLLMClient::from_env()?;
         Ok(Self {
-            client: Arc::new(client),
+            client: super::chat_client_from_env()?,
         })
     }
 
     /// Parse natural language query
+    #[tracing::instrument(name = "parse_query", skip_all)]
     pub async fn parse(&self, query: &str) -> Result<ParsedQuery> {
         let prompt = build_query_parsing_prompt(query);
 
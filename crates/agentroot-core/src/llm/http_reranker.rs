@@ -19,23 +19,22 @@ impl HttpReranker {
 
     /// Create from configuration
     pub fn from_config(config: LLMServiceConfig) -> Result<Self> {
-        let client = super::VLLMClient::new(config)?;
         Ok(Self {
-            client: Arc::new(client),
+            client: super::chat_client(config)?,
         })
     }
 
     /// Create from environment variables
     pub fn from_env() -> Result<Self> {
-        let client = super::VLLMClient::from_env()?;
         Ok(Self {
-            client: Arc::new(client),
+            client: super::chat_client_from_env()?,
         })
     }
 }
 
 #[async_trait]
 impl Reranker for HttpReranker {
+    #[tracing::instrument(name = "rerank", skip_all, fields(documents = documents.len()))]
     async fn rerank(&self, query: &str, documents: &[RerankDocument]) -> Result<Vec<RerankResult>> {
         // If no documents, return empty
         if documents.is_empty() {
@@ -0,0 +1,268 @@
+//! In-process embedder for offline use
+//!
+//! Runs a BERT sentence-transformer (by default
+//! `sentence-transformers/all-MiniLM-L6-v2`) on the CPU with candle. The
+//! model files are downloaded from the Hugging Face hub on first use and
+//! cached under the user cache directory, so later runs need no network.
+//! `AGENTROOT_EMBEDDING_MODEL` may also name a local directory holding
+//! `config.json`, `tokenizer.json` and `model.safetensors`.
+
+use super::Embedder;
+use crate::error::{AgentRootError, Result};
+use async_trait::async_trait;
+use candle_core::{DType, Device, Tensor};
+use candle_nn::VarBuilder;
+use candle_transformers::models::bert::{BertModel, Config, DTYPE};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokenizers::{PaddingParams, Tokenizer, TruncationParams};
+
+/// Files a model directory needs
+const MODEL_FILES: &[&str] = &["config.json", "tokenizer.json", "model.safetensors"];
+
+/// Tokens embedded per text; sentence-transformers truncate at 256 as well
+const MAX_TOKENS: usize = 256;
+
+/// Texts run through the model at once
+const BATCH_SIZE: usize = 32;
+
+const HUB_URL: &str = "https://huggingface.co";
+
+/// Embedder running a sentence-transformer in process
+#[derive(Clone)]
+pub struct LocalEmbedder {
+    inner: Arc<LocalModel>,
+}
+
+struct LocalModel {
+    model: BertModel,
+    tokenizer: Tokenizer,
+    model_name: String,
+    dimensions: usize,
+}
+
+impl LocalEmbedder {
+    /// Load a model by Hugging Face id or directory, downloading it if needed
+    pub async fn load(model: &str) -> Result<Self> {
+        let dir = if Path::new(model).is_dir() {
+            PathBuf::from(model)
+        } else {
+            download_model(model).await?
+        };
+        let model_name = model.trim_end_matches('/').to_string();
+        let inner = tokio::task::spawn_blocking(move || LocalModel::open(&dir, model_name))
+            .await
+            .map_err(|e| AgentRootError::Llm(format!("Loading local model failed: {}", e)))??;
+        Ok(Self {
+            inner: Arc::new(inner),
+        })
+    }
+
+    /// The model named by `AGENTROOT_EMBEDDING_MODEL`, loaded once per process
+    pub async fn from_env() -> Result<Self> {
+        static SHARED: tokio::sync::OnceCell<LocalEmbedder> = tokio::sync::OnceCell::const_new();
+
+        let model = crate::config::LLMServiceConfig::from_env_or_config().embedding_model;
+        let shared = SHARED.get_or_try_init(|| Self::load(&model)).await?;
+        if shared.model_name() == model.trim_end_matches('/') {
+            Ok(shared.clone())
+        } else {
+            Self::load(&model).await
+        }
+    }
+}
+
+impl LocalModel {
+    fn open(dir: &Path, model_name: String) -> Result<Self> {
+        let config: Config =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("config.json"))?)?;
+
+        let mut tokenizer = Tokenizer::from_file(dir.join("tokenizer.json")).map_err(model_err)?;
+        tokenizer.with_padding(Some(PaddingParams::default()));
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: MAX_TOKENS.min(config.max_position_embeddings),
+                ..Default::default()
+            }))
+            .map_err(model_err)?;
+
+        let device = Device::Cpu;
+        let weights = candle_core::safetensors::load(dir.join("model.safetensors"), &device)
+            .map_err(model_err)?;
+        let vb = VarBuilder::from_tensors(weights, DTYPE, &device);
+        let model = BertModel::load(vb, &config).map_err(model_err)?;
+
+        Ok(Self {
+            model,
+            tokenizer,
+            model_name,
+            dimensions: config.hidden_size,
+        })
+    }
+
+    fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let encodings = self
+            .tokenizer
+            .encode_batch(texts.to_vec(), true)
+            .map_err(model_err)?;
+        let batch = encodings.len();
+        let len = encodings.first().map_or(0, |e| e.get_ids().len());
+        let flatten = |get: fn(&tokenizers::Encoding) -> &[u32]| -> Vec<u32> {
+            encodings.iter().flat_map(|e| get(e).to_vec()).collect()
+        };
+
+        let device = &self.model.device;
+        let ids = Tensor::from_vec(flatten(|e| e.get_ids()), (batch, len), device);
+        let type_ids = Tensor::from_vec(flatten(|e| e.get_type_ids()), (batch, len), device);
+        let mask = Tensor::from_vec(flatten(|e| e.get_attention_mask()), (batch, len), device);
+        let (ids, type_ids, mask) = (
+            ids.map_err(model_err)?,
+            type_ids.map_err(model_err)?,
+            mask.map_err(model_err)?,
+        );
+
+        let hidden = self
+            .model
+            .forward(&ids, &type_ids, Some(&mask))
+            .map_err(model_err)?;
+        mean_pool(&hidden, &mask).map_err(model_err)
+    }
+}
+
+/// Average token vectors over the attention mask, then L2-normalize
+fn mean_pool(hidden: &Tensor, mask: &Tensor) -> candle_core::Result<Vec<Vec<f32>>> {
+    let mask = mask.to_dtype(DType::F32)?.unsqueeze(2)?;
+    let summed = hidden.broadcast_mul(&mask)?.sum(1)?;
+    let counts = mask.sum(1)?.clamp(1e-9f32, f32::MAX)?;
+    let mean = summed.broadcast_div(&counts)?;
+    let norms = mean
+        .sqr()?
+        .sum_keepdim(1)?
+        .sqrt()?
+        .clamp(1e-12f32, f32::MAX)?;
+    mean.broadcast_div(&norms)?.to_vec2::<f32>()
+}
+
+#[async_trait]
+impl Embedder for LocalEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        self.embed_batch(&[text.to_string()])
+            .await?
+            .pop()
+            .ok_or_else(|| AgentRootError::Llm("No embedding returned".to_string()))
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let inner = Arc::clone(&self.inner);
+        let texts = texts.to_vec();
+        // Inference is CPU-bound; keep it off the async workers
+        tokio::task::spawn_blocking(move || {
+            let mut embeddings = Vec::with_capacity(texts.len());
+            for batch in texts.chunks(BATCH_SIZE) {
+                embeddings.extend(inner.embed_batch(batch)?);
+            }
+            Ok(embeddings)
+        })
+        .await
+        .map_err(|e| AgentRootError::Llm(format!("Local embedding failed: {}", e)))?
+    }
+
+    fn dimensions(&self) -> usize {
+        self.inner.dimensions
+    }
+
+    fn model_name(&self) -> &str {
+        &self.inner.model_name
+    }
+}
+
+/// Cached model directory, fetching missing files from the hub
+async fn download_model(model: &str) -> Result<PathBuf> {
+    let dir = model_cache_dir(model);
+    let missing: Vec<&str> = MODEL_FILES
+        .iter()
+        .copied()
+        .filter(|file| !dir.join(file).exists())
+        .collect();
+    if missing.is_empty() {
+        return Ok(dir);
+    }
+
+    std::fs::create_dir_all(&dir)?;
+    tracing::info!("Downloading embedding model {} to {}", model, dir.display());
+    let client = reqwest::Client::new();
+    for file in missing {
+        let url = format!("{}/{}/resolve/main/{}", HUB_URL, model, file);
+        let response = client.get(&url).send().await?;
+        if !response.status().is_success() {
+            return Err(AgentRootError::ModelNotFound(format!(
+                "{} (HTTP {} fetching {})",
+                model,
+                response.status(),
+                file
+            )));
+        }
+        let bytes = response.bytes().await?;
+        // Write then rename so an interrupted download is not mistaken for a model
+        let partial = dir.join(format!("{}.partial", file));
+        std::fs::write(&partial, &bytes)?;
+        std::fs::rename(&partial, dir.join(file))?;
+    }
+    Ok(dir)
+}
+
+fn model_cache_dir(model: &str) -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(crate::CACHE_DIR_NAME)
+        .join("models")
+        .join(model.replace('/', "--"))
+}
+
+fn model_err(e: impl std::fmt::Display) -> AgentRootError {
+    AgentRootError::Llm(format!("Local embedding model: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mean_pool_ignores_padding_and_normalizes() {
+        let device = Device::Cpu;
+        // Two texts of two token slots each; the second has one padded token
+        let hidden = Tensor::new(
+            &[[[1.0f32, 0.0], [3.0, 0.0]], [[0.0, 2.0], [100.0, 100.0]]],
+            &device,
+        )
+        .unwrap();
+        let mask = Tensor::new(&[[1u32, 1], [1, 0]], &device).unwrap();
+
+        let pooled = mean_pool(&hidden, &mask).unwrap();
+        assert_eq!(pooled, vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+    }
+
+    #[test]
+    fn test_model_cache_dir() {
+        let dir = model_cache_dir("sentence-transformers/all-MiniLM-L6-v2");
+        assert!(dir.ends_with("models/sentence-transformers--all-MiniLM-L6-v2"));
+    }
+
+    #[tokio::test]
+    #[ignore = "downloads a model from the Hugging Face hub"]
+    async fn test_local_embeddings_rank_by_meaning() {
+        let embedder = LocalEmbedder::load("sentence-transformers/all-MiniLM-L6-v2")
+            .await
+            .unwrap();
+        assert_eq!(embedder.dimensions(), 384);
+
+        let texts = [
+            "How do I reset my password?".to_string(),
+            "Steps to recover a forgotten login credential".to_string(),
+            "The weather is sunny in Lisbon today".to_string(),
+        ];
+        let vectors = embedder.embed_batch(&texts).await.unwrap();
+        let dot = |a: &[f32], b: &[f32]| a.iter().zip(b).map(|(x, y)| x * y).sum::<f32>();
+        assert!(dot(&vectors[0], &vectors[1]) > dot(&vectors[0], &vectors[2]));
+    }
+}
@@ -2,7 +2,7 @@
 
 use crate::db::sessions::SessionQuery;
 use crate::error::Result;
-use crate::llm::client::{ChatMessage, LLMClient, VLLMClient};
+use crate::llm::client::{chat_client_from_env, ChatMessage, LLMClient};
 use std::collections::HashMap;
 use std::sync::Arc;
 
@@ -39,9 +39,8 @@ impl MemoryExtractor {
     }
 
     pub fn from_env() -> Result<Self> {
-        let client = VLLMClient::from_env()?;
         Ok(Self {
-            client: Arc::new(client),
+            client: chat_client_from_env()?,
         })
     }
 
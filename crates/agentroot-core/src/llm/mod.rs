@@ -6,41 +6,66 @@
 //! - Query parsing
 //! - Reranking
 //!
-//! All inference is performed via external HTTP services.
-//! No local models are downloaded or executed.
+//! Chat features speak the OpenAI-compatible API or, with the `anthropic`
+//! provider, the Anthropic Messages API.
+//!
+//! Inference is performed via external HTTP services. The one exception is
+//! embedding: with no embedding service configured, a small
+//! sentence-transformer runs in process (`local-embeddings` feature).
 
+mod answer_generator;
+mod anthropic_client;
 mod cache;
 mod chunk_metadata_generator;
 mod client;
+mod default_embedder;
 mod http_embedder;
 mod http_metadata_generator;
 mod http_query_expander;
 mod http_query_parser;
 mod http_reranker;
+#[cfg(feature = "local-embeddings")]
+mod local_embedder;
 pub mod memory_extractor;
 mod metadata_generator;
+mod pii;
 mod query_parser;
+mod rate_limit;
+mod resilience;
 mod strategy_analyzer;
+mod topic_labeler;
 mod traits;
 mod workflow_orchestrator;
 
+pub use answer_generator::{Answer, AnswerGenerator, NO_CONTEXT_ANSWER};
+pub use anthropic_client::AnthropicClient;
 pub use chunk_metadata_generator::{
     generate_batch_chunk_metadata, generate_chunk_metadata, ChunkContext, ChunkMetadata,
 };
-pub use client::{generate_metadata_with_llm, ChatMessage, LLMClient, MetricsSnapshot, VLLMClient};
+pub use client::{
+    chat_client, chat_client_from_env, generate_metadata_with_llm, ChatMessage, LLMClient,
+    MetricsSnapshot, VLLMClient,
+};
+pub use default_embedder::{default_embedder, embedder_for_model};
 pub use http_embedder::HttpEmbedder;
 pub use http_metadata_generator::HttpMetadataGenerator;
 pub use http_query_expander::HttpQueryExpander;
 pub use http_query_parser::HttpQueryParser;
 pub use http_reranker::HttpReranker;
+#[cfg(feature = "local-embeddings")]
+pub use local_embedder::LocalEmbedder;
 pub use memory_extractor::{ExtractedMemory, MemoryExtractor};
 pub use metadata_generator::{
     DocumentMetadata, ExtractedConcept, MetadataContext, MetadataGenerator,
 };
+pub use pii::PiiMasker;
 pub use query_parser::{MetadataFilterHint, ParsedQuery, SearchType, TemporalFilter};
+pub use rate_limit::RateLimiter;
+pub use resilience::{service_health, CircuitState, RetryPolicy, ServiceHealth};
 pub use strategy_analyzer::{
     heuristic_strategy, HttpStrategyAnalyzer, SearchGranularity, SearchStrategy, StrategyAnalysis,
 };
+pub use topic_labeler::TopicLabeler;
 pub use traits::*;
 pub use workflow_orchestrator::{
     fallback_workflow, MergeStrategy, Workflow, WorkflowContext, WorkflowOrchestrator, WorkflowStep,
@@ -0,0 +1,100 @@
+//! Masking of personal data in text sent to external LLM services
+//!
+//! Opt-in via `pii_mask` in the LLM service config. Only outgoing requests
+//! are masked: the index keeps the original content, and documents and
+//! queries are masked the same way so their embeddings still line up.
+
+use crate::config::PiiMaskConfig;
+use crate::error::{AgentRootError, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::borrow::Cow;
+
+lazy_static! {
+    static ref EMAIL_RE: Regex =
+        Regex::new(r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b").unwrap();
+    // Three digit groups at least, so dates and version numbers are left alone
+    static ref PHONE_RE: Regex = Regex::new(
+        r"(?:\+\d{1,3}[\s-]?)?(?:\(\d{1,4}\)[\s-]?|\b\d{2,4}[\s-])\d{3,4}[\s-]?\d{3,4}\b"
+    )
+    .unwrap();
+}
+
+/// Replaces configured kinds of personal data with placeholders
+#[derive(Debug, Clone)]
+pub struct PiiMasker {
+    rules: Vec<(&'static str, Regex)>,
+}
+
+impl PiiMasker {
+    /// Build a masker, or None when masking is not enabled
+    pub fn from_config(config: &PiiMaskConfig) -> Result<Option<Self>> {
+        let mut rules = Vec::new();
+        for kind in &config.kinds {
+            match kind.trim() {
+                "email" => rules.push(("[EMAIL]", EMAIL_RE.clone())),
+                "phone" => rules.push(("[PHONE]", PHONE_RE.clone())),
+                "" => {}
+                other => {
+                    return Err(AgentRootError::Config(format!(
+                        "Unknown PII kind '{}' (expected email or phone)",
+                        other
+                    )))
+                }
+            }
+        }
+        for pattern in &config.patterns {
+            let re = Regex::new(pattern).map_err(|e| {
+                AgentRootError::Config(format!("Invalid PII pattern '{}': {}", pattern, e))
+            })?;
+            rules.push(("[PII]", re));
+        }
+        Ok((!rules.is_empty()).then_some(Self { rules }))
+    }
+
+    /// Replace every match with its placeholder, borrowing when nothing matched
+    pub fn mask<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut text = Cow::Borrowed(text);
+        for (placeholder, re) in &self.rules {
+            if let Cow::Owned(masked) = re.replace_all(&text, *placeholder) {
+                text = Cow::Owned(masked);
+            }
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn masker(kinds: &[&str], patterns: &[&str]) -> Option<PiiMasker> {
+        PiiMasker::from_config(&PiiMaskConfig {
+            kinds: kinds.iter().map(|k| k.to_string()).collect(),
+            patterns: patterns.iter().map(|p| p.to_string()).collect(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_mask_pii() {
+        let masker = masker(&["email", "phone"], &[r"\bAlice Smith\b"]).unwrap();
+        let text = "Ask Alice Smith (alice.smith@example.com, +1 555-123-4567 \
+                    or (020) 7946 0958) before 2024-01-15 about v1.2.3.";
+        assert_eq!(
+            masker.mask(text),
+            "Ask [PII] ([EMAIL], [PHONE] or [PHONE]) before 2024-01-15 about v1.2.3."
+        );
+        assert!(matches!(masker.mask("nothing here"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_masking_is_opt_in() {
+        assert!(masker(&[], &[]).is_none());
+        assert!(PiiMasker::from_config(&PiiMaskConfig {
+            kinds: vec!["ssn".to_string()],
+            patterns: Vec::new(),
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,148 @@
+//! Token-bucket rate limiting for embedding requests
+//!
+//! Hosted embedding APIs cap requests and tokens per minute. Each cap is a
+//! bucket holding a minute's allowance that refills continuously; a batch
+//! waits until both buckets can pay for it, so long runs slow down to the
+//! allowed pace instead of being rejected part way.
+
+use crate::config::LLMServiceConfig;
+use crate::index::ast_chunker::oversized::estimate_tokens;
+use std::sync::Mutex;
+use tokio::time::{Duration, Instant};
+
+/// One per-minute allowance
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    per_second: f64,
+    state: Mutex<(f64, Instant)>,
+}
+
+impl TokenBucket {
+    fn per_minute(limit: u32) -> Self {
+        let capacity = f64::from(limit.max(1));
+        Self {
+            capacity,
+            per_second: capacity / 60.0,
+            state: Mutex::new((capacity, Instant::now())),
+        }
+    }
+
+    /// Take `amount`, or how long to wait before it can be taken
+    ///
+    /// More than the capacity is granted once the bucket is full, leaving it
+    /// in debt, so an oversized batch is delayed rather than stuck.
+    fn try_take(&self, amount: f64) -> Option<Duration> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let (available, last) = &mut *state;
+        let now = Instant::now();
+        *available = (*available + now.duration_since(*last).as_secs_f64() * self.per_second)
+            .min(self.capacity);
+        *last = now;
+
+        let needed = amount.min(self.capacity);
+        if *available >= needed {
+            *available -= amount;
+            None
+        } else {
+            Some(Duration::from_secs_f64(
+                (needed - *available) / self.per_second,
+            ))
+        }
+    }
+}
+
+/// Requests-per-minute and tokens-per-minute limits for one embedder
+#[derive(Debug)]
+pub struct RateLimiter {
+    requests: Option<TokenBucket>,
+    tokens: Option<TokenBucket>,
+}
+
+impl RateLimiter {
+    /// Limits from `embedding_rpm` and `embedding_tpm`; `None` when neither
+    /// is set
+    pub fn from_config(config: &LLMServiceConfig) -> Option<Self> {
+        Self::new(config.embedding_rpm, config.embedding_tpm)
+    }
+
+    pub fn new(rpm: Option<u32>, tpm: Option<u32>) -> Option<Self> {
+        let requests = rpm.filter(|&n| n > 0).map(TokenBucket::per_minute);
+        let tokens = tpm.filter(|&n| n > 0).map(TokenBucket::per_minute);
+        (requests.is_some() || tokens.is_some()).then_some(Self { requests, tokens })
+    }
+
+    /// Wait until a request embedding `texts` fits both limits
+    pub async fn acquire(&self, texts: &[String]) {
+        let tokens = texts
+            .iter()
+            .map(|t| estimate_tokens(t.chars().count()).max(1))
+            .sum::<usize>() as f64;
+
+        // Requests are taken first so a request waiting on tokens keeps its
+        // slot; the token bucket is retried until it pays
+        if let Some(bucket) = &self.requests {
+            while let Some(wait) = bucket.try_take(1.0) {
+                tracing::debug!("Embedding request limit reached; waiting {:?}", wait);
+                tokio::time::sleep(wait).await;
+            }
+        }
+        if let Some(bucket) = &self.tokens {
+            while let Some(wait) = bucket.try_take(tokens) {
+                tracing::debug!("Embedding token limit reached; waiting {:?}", wait);
+                tokio::time::sleep(wait).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(n: usize, chars: usize) -> Vec<String> {
+        vec!["x".repeat(chars); n]
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_requests_per_minute_spread_over_the_minute() {
+        let limiter = RateLimiter::new(Some(60), None).unwrap();
+        let start = Instant::now();
+        // A full bucket lets the first minute's worth through at once
+        for _ in 0..60 {
+            limiter.acquire(&texts(1, 4)).await;
+        }
+        assert!(start.elapsed() < Duration::from_millis(10));
+
+        // Then one request per second
+        limiter.acquire(&texts(1, 4)).await;
+        limiter.acquire(&texts(1, 4)).await;
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(1990), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(2100), "{:?}", elapsed);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_tokens_per_minute_and_oversized_batches() {
+        // 600 tokens a minute is 10 a second
+        let limiter = RateLimiter::new(None, Some(600)).unwrap();
+        let start = Instant::now();
+        limiter.acquire(&texts(3, 800)).await; // 600 tokens
+        limiter.acquire(&texts(1, 200)).await; // 50 more: 5s
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(4990), "{:?}", elapsed);
+
+        // Larger than the bucket: waits for a full bucket, then goes through
+        let limiter = RateLimiter::new(None, Some(600)).unwrap();
+        let start = Instant::now();
+        limiter.acquire(&texts(1, 4000)).await; // 1000 tokens
+        assert!(start.elapsed() < Duration::from_millis(10));
+        limiter.acquire(&texts(1, 40)).await; // 400 in debt + 10: 41s
+        assert!(start.elapsed() >= Duration::from_secs(40));
+    }
+
+    #[test]
+    fn test_no_limits_configured() {
+        assert!(RateLimiter::new(None, Some(0)).is_none());
+    }
+}
@@ -0,0 +1,366 @@
+//! Retries and circuit breaking for requests to inference services
+//!
+//! Transient failures (timeouts, dropped connections, 429 and 5xx responses)
+//! are retried with exponential backoff and jitter. A service that keeps
+//! failing trips its circuit breaker: further requests fail fast for a
+//! cooldown, then a single probe decides whether it is back. Health is kept
+//! per service URL and shared by every client in the process, so the
+//! embedder, reranker and chat features see the same state.
+
+use crate::error::{AgentRootError, Result};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Consecutive failed requests that open the circuit
+const FAILURE_THRESHOLD: u32 = 5;
+const DEFAULT_RETRIES: u32 = 3;
+const DEFAULT_COOLDOWN_SECS: u64 = 30;
+const BASE_DELAY: Duration = Duration::from_millis(500);
+const MAX_DELAY: Duration = Duration::from_secs(30);
+
+static SERVICES: OnceLock<Mutex<HashMap<String, Arc<ServiceHealth>>>> = OnceLock::new();
+
+/// How often and how patiently to retry a request
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_RETRIES,
+            base_delay: BASE_DELAY,
+            max_delay: MAX_DELAY,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Retries from `AGENTROOT_LLM_RETRIES` (default 3, 0 disables)
+    pub fn from_env() -> Self {
+        Self {
+            max_retries: std::env::var("AGENTROOT_LLM_RETRIES")
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(DEFAULT_RETRIES),
+            ..Self::default()
+        }
+    }
+
+    /// Wait before retry `attempt` (0-based): half the exponential delay
+    /// plus up to as much again at random, capped at `max_delay`
+    fn delay(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(1u32 << attempt.min(16))
+            .min(self.max_delay);
+        let half = exponential / 2;
+        half + half.mul_f64(fastrand::f64())
+    }
+}
+
+/// State of a service's circuit breaker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Requests flow normally
+    Closed,
+    /// Requests fail fast until the cooldown ends
+    Open,
+    /// Cooldown over; the next request probes the service
+    HalfOpen,
+}
+
+/// Failure tracking for one service
+#[derive(Debug)]
+pub struct ServiceHealth {
+    url: String,
+    cooldown: Duration,
+    state: Mutex<HealthState>,
+}
+
+#[derive(Debug, Default)]
+struct HealthState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    /// A half-open probe is in flight
+    probing: bool,
+}
+
+impl ServiceHealth {
+    pub fn new(url: impl Into<String>, cooldown: Duration) -> Self {
+        Self {
+            url: url.into(),
+            cooldown,
+            state: Mutex::new(HealthState::default()),
+        }
+    }
+
+    /// Shared health of the service at `url`; the cooldown comes from
+    /// `AGENTROOT_LLM_BREAKER_COOLDOWN_SECS` (default 30)
+    pub fn for_service(url: &str) -> Arc<Self> {
+        let services = SERVICES.get_or_init(Default::default);
+        let mut services = services.lock().unwrap_or_else(|e| e.into_inner());
+        services
+            .entry(url.to_string())
+            .or_insert_with(|| {
+                let cooldown = std::env::var("AGENTROOT_LLM_BREAKER_COOLDOWN_SECS")
+                    .ok()
+                    .and_then(|v| v.trim().parse().ok())
+                    .unwrap_or(DEFAULT_COOLDOWN_SECS);
+                Arc::new(Self::new(url, Duration::from_secs(cooldown)))
+            })
+            .clone()
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn state(&self) -> CircuitState {
+        let state = self.lock();
+        match state.open_until {
+            None => CircuitState::Closed,
+            Some(until) if Instant::now() < until => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.lock().consecutive_failures
+    }
+
+    /// Let a request through unless the circuit is open, or half-open with
+    /// a probe already in flight
+    fn admit(&self) -> Result<()> {
+        let mut state = self.lock();
+        let Some(until) = state.open_until else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        if now < until || state.probing {
+            let wait = until.saturating_duration_since(now).as_secs().max(1);
+            return Err(AgentRootError::ExternalError(format!(
+                "{} is unavailable after {} failed requests; retrying in {}s",
+                self.url, state.consecutive_failures, wait
+            )));
+        }
+        state.probing = true;
+        Ok(())
+    }
+
+    fn record_success(&self) {
+        *self.lock() = HealthState::default();
+    }
+
+    fn record_failure(&self) {
+        let mut state = self.lock();
+        state.consecutive_failures += 1;
+        state.probing = false;
+        if state.consecutive_failures >= FAILURE_THRESHOLD {
+            state.open_until = Some(Instant::now() + self.cooldown);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HealthState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Health of every service contacted by this process
+pub fn service_health() -> Vec<Arc<ServiceHealth>> {
+    let services = SERVICES.get_or_init(Default::default);
+    let services = services.lock().unwrap_or_else(|e| e.into_inner());
+    let mut all: Vec<_> = services.values().cloned().collect();
+    all.sort_by(|a, b| a.url.cmp(&b.url));
+    all
+}
+
+/// Worth retrying: rate limiting and server-side errors
+fn retryable_status(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Worth retrying: timeouts and dropped connections
+///
+/// A refused connection means nothing is listening, which a second later
+/// will not change; it still counts against the circuit.
+fn retryable_error(error: &reqwest::Error) -> bool {
+    error.is_timeout() || (error.is_request() && !error.is_connect())
+}
+
+/// `Retry-After` in seconds, if the service sent one
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// Send `request`, retrying transient failures under `policy` and keeping
+/// `health` up to date
+///
+/// Any response that is not retryable, or the last one, is returned for the
+/// caller to interpret. Only requests that fail after their retries count
+/// against the service; client errors such as 400 show it is up.
+pub(crate) async fn send_with_retry(
+    health: &ServiceHealth,
+    policy: &RetryPolicy,
+    request: reqwest::RequestBuilder,
+) -> Result<reqwest::Response> {
+    health.admit()?;
+
+    let mut attempt = 0;
+    loop {
+        let req = request
+            .try_clone()
+            .ok_or_else(|| AgentRootError::ExternalError("Failed to clone request".to_string()))?;
+        let retries_left = attempt < policy.max_retries;
+
+        let wait = match req.send().await {
+            Ok(response) if !retryable_status(response.status()) => {
+                health.record_success();
+                return Ok(response);
+            }
+            Ok(response) if retries_left => {
+                let wait = retry_after(&response)
+                    .map(|d| d.min(policy.max_delay))
+                    .unwrap_or_else(|| policy.delay(attempt));
+                tracing::warn!(
+                    "{} returned HTTP {}; retrying in {:?} (attempt {}/{})",
+                    health.url,
+                    response.status(),
+                    wait,
+                    attempt + 1,
+                    policy.max_retries
+                );
+                wait
+            }
+            Ok(response) => {
+                health.record_failure();
+                return Ok(response);
+            }
+            Err(e) if retries_left && retryable_error(&e) => {
+                let wait = policy.delay(attempt);
+                tracing::warn!(
+                    "Request to {} failed ({}); retrying in {:?} (attempt {}/{})",
+                    health.url,
+                    e,
+                    wait,
+                    attempt + 1,
+                    policy.max_retries
+                );
+                wait
+            }
+            Err(e) => {
+                health.record_failure();
+                return Err(e.into());
+            }
+        };
+        tokio::time::sleep(wait).await;
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve one canned status per connection, in order
+    async fn serve(statuses: Vec<u16>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for status in statuses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 4096];
+                let _ = socket.read(&mut buf).await;
+                let response = format!(
+                    "HTTP/1.1 {} X\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok",
+                    status
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    fn quick_policy(max_retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_retries,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transient_errors_are_retried() {
+        let url = serve(vec![503, 429, 200]).await;
+        let health = ServiceHealth::new(&url, Duration::from_secs(30));
+        let client = reqwest::Client::new();
+
+        let response = send_with_retry(&health, &quick_policy(3), client.get(&url))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(health.consecutive_failures(), 0);
+
+        // Client errors are returned at once and do not count as failures
+        let url = serve(vec![400]).await;
+        let health = ServiceHealth::new(&url, Duration::from_secs(30));
+        let response = send_with_retry(&health, &quick_policy(3), client.get(&url))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 400);
+        assert_eq!(health.state(), CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_opens_and_probes_after_cooldown() {
+        let url = serve(vec![500; FAILURE_THRESHOLD as usize + 1]).await;
+        let health = ServiceHealth::new(&url, Duration::from_millis(50));
+        let client = reqwest::Client::new();
+
+        for _ in 0..FAILURE_THRESHOLD {
+            let response = send_with_retry(&health, &quick_policy(0), client.get(&url))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), 500);
+        }
+        assert_eq!(health.state(), CircuitState::Open);
+        assert!(matches!(
+            send_with_retry(&health, &quick_policy(0), client.get(&url)).await,
+            Err(AgentRootError::ExternalError(_))
+        ));
+
+        // One probe after the cooldown; it fails, so the circuit reopens
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(health.state(), CircuitState::HalfOpen);
+        send_with_retry(&health, &quick_policy(0), client.get(&url))
+            .await
+            .unwrap();
+        assert_eq!(health.state(), CircuitState::Open);
+    }
+
+    #[test]
+    fn test_backoff_grows_with_jitter_and_cap() {
+        let policy = RetryPolicy::default();
+        for attempt in 0..4 {
+            let full = BASE_DELAY * (1 << attempt);
+            let delay = policy.delay(attempt);
+            assert!(delay >= full / 2 && delay <= full, "{:?}", delay);
+        }
+        assert!(policy.delay(20) <= MAX_DELAY);
+    }
+}
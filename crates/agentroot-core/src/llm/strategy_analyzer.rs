@@ -42,6 +42,9 @@ pub struct StrategyAnalysis {
     pub reasoning: String,
     /// Whether query is multilingual or non-English
     pub is_multilingual: bool,
+    /// Lexical share for hybrid fusion (1.0 = keywords only, 0.0 = semantic only)
+    #[serde(default)]
+    pub alpha: Option<f64>,
 }
 
 impl Default for StrategyAnalysis {
@@ -52,6 +55,7 @@ impl Default for StrategyAnalysis {
             confidence: 0.5,
             reasoning: "Fallback to hybrid document search".to_string(),
             is_multilingual: false,
+            alpha: None,
         }
     }
 }
@@ -69,9 +73,8 @@ impl HttpStrategyAnalyzer {
 
     /// Create from environment variables
     pub fn from_env() -> Result<Self> {
-        let client = super::VLLMClient::from_env()?;
         Ok(Self {
-            client: Arc::new(client),
+            client: super::chat_client_from_env()?,
         })
     }
 
@@ -156,13 +159,18 @@ Examples:
 - "what features does X have?" → document (broad feature list)
 - "MCP server implementation" → chunk (specific code)
 
+For "hybrid", also choose "alpha", the weight of keyword matching against semantic
+similarity: near 1.0 for exact symbols and identifiers, near 0.0 for conceptual
+questions, 0.5 when both matter. Use null for other strategies.
+
 Output ONLY this JSON (no markdown, no explanation):
 {{
   "strategy": "bm25" | "vector" | "hybrid",
   "granularity": "document" | "chunk" | "both",
   "confidence": 0.0-1.0,
   "reasoning": "brief explanation",
-  "is_multilingual": true | false
+  "is_multilingual": true | false,
+  "alpha": 0.0-1.0 | null
 }}"#,
         query, context_info
     )
@@ -204,6 +212,7 @@ pub fn heuristic_strategy(query: &str, has_embeddings: bool) -> StrategyAnalysis
             confidence: 1.0,
             reasoning: "No embeddings available".to_string(),
             is_multilingual: false,
+            alpha: None,
         };
     }
 
@@ -229,6 +238,7 @@ pub fn heuristic_strategy(query: &str, has_embeddings: bool) -> StrategyAnalysis
             confidence: 0.7,
             reasoning: "Natural language query detected (heuristic)".to_string(),
             is_multilingual: false,
+            alpha: None,
         }
     } else {
         StrategyAnalysis {
@@ -237,6 +247,12 @@ pub fn heuristic_strategy(query: &str, has_embeddings: bool) -> StrategyAnalysis
             confidence: 0.6,
             reasoning: "Mixed or technical query (heuristic)".to_string(),
             is_multilingual: false,
+            // Lean on keywords for code terms, on meaning for phrased questions
+            alpha: Some(match (has_tech, is_nl) {
+                (true, false) => 0.75,
+                (true, true) => 0.5,
+                _ => 0.4,
+            }),
         }
     }
 }
@@ -357,6 +373,21 @@ mod tests {
         assert_eq!(result.strategy, SearchStrategy::Vector);
         assert_eq!(result.granularity, SearchGranularity::Document);
         assert_eq!(result.confidence, 0.9);
+        assert_eq!(result.alpha, None);
+
+        let json = r#"{"strategy": "hybrid", "granularity": "chunk", "confidence": 0.8, "reasoning": "Symbol lookup", "is_multilingual": false, "alpha": 0.8}"#;
+        assert_eq!(parse_strategy_response(json).unwrap().alpha, Some(0.8));
+    }
+
+    #[test]
+    fn test_heuristic_alpha() {
+        let code = heuristic_strategy("HttpEmbedder::from_env", true);
+        assert_eq!(code.strategy, SearchStrategy::Hybrid);
+        assert_eq!(code.alpha, Some(0.75));
+
+        let prose = heuristic_strategy("how can I use agentroot?", true);
+        assert_eq!(prose.strategy, SearchStrategy::Vector);
+        assert_eq!(prose.alpha, None);
     }
 
     #[test]
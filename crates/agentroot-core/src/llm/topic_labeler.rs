@@ -0,0 +1,85 @@
+//! LLM-based labeling of topic clusters
+
+use crate::error::Result;
+use crate::llm::client::{chat_client_from_env, ChatMessage, LLMClient};
+use std::sync::Arc;
+
+/// Maximum label length accepted from the LLM
+const MAX_LABEL_CHARS: usize = 60;
+
+const LABEL_PROMPT: &str = r#"You name clusters of related documents from a knowledge base.
+
+Given the titles and key concepts of documents in one cluster, reply with a short
+topic label (2-5 words, Title Case) describing what they have in common.
+Only return the label, nothing else."#;
+
+pub struct TopicLabeler {
+    client: Arc<dyn LLMClient>,
+}
+
+impl TopicLabeler {
+    pub fn new(client: Arc<dyn LLMClient>) -> Self {
+        Self { client }
+    }
+
+    pub fn from_env() -> Result<Self> {
+        Ok(Self {
+            client: chat_client_from_env()?,
+        })
+    }
+
+    /// Generate a label for a cluster.
+    /// Returns None on LLM failure (graceful degradation).
+    pub async fn label_topic(&self, titles: &[String], keywords: &[String]) -> Option<String> {
+        let mut prompt = String::from("Document titles:\n");
+        for title in titles.iter().take(20) {
+            prompt.push_str(&format!("- {}\n", title));
+        }
+        if !keywords.is_empty() {
+            prompt.push_str(&format!("\nKey concepts: {}\n", keywords.join(", ")));
+        }
+
+        let messages = vec![ChatMessage::system(LABEL_PROMPT), ChatMessage::user(prompt)];
+
+        match self.client.chat_completion(messages).await {
+            Ok(response) => clean_label(&response),
+            Err(e) => {
+                tracing::debug!("Topic labeling LLM call failed: {}", e);
+                None
+            }
+        }
+    }
+}
+
+fn clean_label(response: &str) -> Option<String> {
+    let line = response.lines().map(str::trim).find(|l| !l.is_empty())?;
+    let label = line
+        .trim_start_matches("Label:")
+        .trim()
+        .trim_matches(|c| c == '"' || c == '\'' || c == '*' || c == '.')
+        .trim();
+
+    if label.is_empty() || label.chars().count() > MAX_LABEL_CHARS {
+        return None;
+    }
+    Some(label.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_label() {
+        assert_eq!(
+            clean_label("\n\"Authentication Flow\"\n").as_deref(),
+            Some("Authentication Flow")
+        );
+        assert_eq!(
+            clean_label("Label: Database Schema.").as_deref(),
+            Some("Database Schema")
+        );
+        assert!(clean_label("   ").is_none());
+        assert!(clean_label(&"x".repeat(100)).is_none());
+    }
+}
@@ -99,6 +99,28 @@ pub enum WorkflowStep {
     },
 }
 
+impl WorkflowStep {
+    /// Step name as it appears in serialized workflows
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Bm25Search { .. } => "bm25_search",
+            Self::VectorSearch { .. } => "vector_search",
+            Self::HybridSearch { .. } => "hybrid_search",
+            Self::FilterMetadata { .. } => "filter_metadata",
+            Self::FilterTemporal { .. } => "filter_temporal",
+            Self::FilterCollection { .. } => "filter_collection",
+            Self::ExpandQuery { .. } => "expand_query",
+            Self::GlossarySearch { .. } => "glossary_search",
+            Self::Rerank { .. } => "rerank",
+            Self::Deduplicate => "deduplicate",
+            Self::Merge { .. } => "merge",
+            Self::Limit { .. } => "limit",
+            Self::Bm25ChunkSearch { .. } => "bm25_chunk_search",
+            Self::VectorChunkSearch { .. } => "vector_chunk_search",
+        }
+    }
+}
+
 fn default_limit() -> usize {
     20
 }
@@ -173,9 +195,8 @@ impl WorkflowOrchestrator {
 
     /// Create from environment variables
     pub fn from_env() -> Result<Self> {
-        let client = super::VLLMClient::from_env()?;
         Ok(Self {
-            client: Arc::new(client),
+            client: super::chat_client_from_env()?,
         })
     }
 
@@ -0,0 +1,198 @@
+//! Progress reporting for long-running operations
+//!
+//! Indexing and embedding loops report through a [`ProgressSink`] so every
+//! frontend (CLI bars, TUI status line, MCP notifications) sees the same
+//! numbers instead of estimating its own. The same loops check a
+//! [`CancellationToken`] between items, so a caller can stop them cleanly.
+
+use crate::error::{AgentRootError, Result};
+use std::time::{Duration, Instant};
+
+pub use tokio_util::sync::CancellationToken;
+
+/// Fail with [`AgentRootError::Cancelled`] once `cancel` is triggered
+pub(crate) fn check_cancelled(cancel: &CancellationToken, operation: &str) -> Result<()> {
+    if cancel.is_cancelled() {
+        return Err(AgentRootError::Cancelled(format!("{} stopped", operation)));
+    }
+    Ok(())
+}
+
+/// Snapshot of a long-running operation
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ProgressUpdate {
+    /// Operation name, e.g. "index" or "embed"
+    pub operation: String,
+    /// Items found so far (the expected total once discovery is complete)
+    pub discovered: usize,
+    /// Items finished successfully
+    pub processed: usize,
+    /// Items that failed
+    pub failed: usize,
+    /// Bytes of content processed
+    pub bytes: u64,
+    /// Time since the operation started
+    pub elapsed: Duration,
+    /// Item currently being worked on, or a short status note
+    pub message: Option<String>,
+}
+
+impl ProgressUpdate {
+    /// Items finished, successfully or not
+    pub fn completed(&self) -> usize {
+        self.processed + self.failed
+    }
+
+    /// Completed fraction in [0, 1], if the total is known
+    pub fn fraction(&self) -> Option<f64> {
+        (self.discovered > 0).then(|| (self.completed() as f64 / self.discovered as f64).min(1.0))
+    }
+
+    /// Estimated time remaining, extrapolated from the rate so far
+    pub fn eta(&self) -> Option<Duration> {
+        let completed = self.completed();
+        if completed == 0 || self.discovered <= completed {
+            return None;
+        }
+        let per_item = self.elapsed.as_secs_f64() / completed as f64;
+        Some(Duration::from_secs_f64(
+            per_item * (self.discovered - completed) as f64,
+        ))
+    }
+}
+
+/// Receiver for progress updates
+pub trait ProgressSink: Send + Sync {
+    /// Called whenever the operation advances
+    fn on_progress(&self, update: &ProgressUpdate);
+
+    /// Called once when the operation completes
+    fn on_finish(&self, update: &ProgressUpdate) {
+        self.on_progress(update);
+    }
+}
+
+impl<F> ProgressSink for F
+where
+    F: Fn(&ProgressUpdate) + Send + Sync,
+{
+    fn on_progress(&self, update: &ProgressUpdate) {
+        self(update)
+    }
+}
+
+/// Sink that ignores all updates
+pub struct NoProgress;
+
+impl ProgressSink for NoProgress {
+    fn on_progress(&self, _update: &ProgressUpdate) {}
+}
+
+/// Accumulates counts for one operation and forwards them to a sink
+pub struct ProgressTracker<'a> {
+    sink: &'a dyn ProgressSink,
+    start: Instant,
+    state: ProgressUpdate,
+}
+
+impl<'a> ProgressTracker<'a> {
+    pub fn new(sink: &'a dyn ProgressSink, operation: &str) -> Self {
+        Self {
+            sink,
+            start: Instant::now(),
+            state: ProgressUpdate {
+                operation: operation.to_string(),
+                discovered: 0,
+                processed: 0,
+                failed: 0,
+                bytes: 0,
+                elapsed: Duration::ZERO,
+                message: None,
+            },
+        }
+    }
+
+    /// Record newly discovered items
+    pub fn discovered(&mut self, count: usize) {
+        self.state.discovered += count;
+        self.emit();
+    }
+
+    /// Record a successfully processed item
+    pub fn processed(&mut self, bytes: u64, message: Option<String>) {
+        self.state.processed += 1;
+        self.state.bytes += bytes;
+        self.state.message = message;
+        self.emit();
+    }
+
+    /// Record a failed item
+    pub fn failed(&mut self, message: Option<String>) {
+        self.state.failed += 1;
+        self.state.message = message;
+        self.emit();
+    }
+
+    /// Report completion and return the final snapshot
+    pub fn finish(mut self) -> ProgressUpdate {
+        self.state.elapsed = self.start.elapsed();
+        self.state.message = None;
+        self.sink.on_finish(&self.state);
+        self.state
+    }
+
+    fn emit(&mut self) {
+        self.state.elapsed = self.start.elapsed();
+        self.sink.on_progress(&self.state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_tracker_forwards_counts() {
+        let seen: Mutex<Vec<(usize, usize, usize, u64)>> = Mutex::new(Vec::new());
+        let sink = |u: &ProgressUpdate| {
+            seen.lock()
+                .unwrap()
+                .push((u.discovered, u.processed, u.failed, u.bytes));
+        };
+
+        let mut tracker = ProgressTracker::new(&sink, "index");
+        tracker.discovered(3);
+        tracker.processed(10, Some("a.md".to_string()));
+        tracker.failed(Some("b.md".to_string()));
+        let last = tracker.finish();
+
+        assert_eq!(last.completed(), 2);
+        assert_eq!(last.fraction(), Some(2.0 / 3.0));
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![(3, 0, 0, 0), (3, 1, 0, 10), (3, 1, 1, 10), (3, 1, 1, 10)]
+        );
+    }
+
+    #[test]
+    fn test_eta() {
+        let update = ProgressUpdate {
+            operation: "embed".to_string(),
+            discovered: 10,
+            processed: 4,
+            failed: 1,
+            bytes: 0,
+            elapsed: Duration::from_secs(5),
+            message: None,
+        };
+        assert_eq!(update.eta(), Some(Duration::from_secs(5)));
+
+        let done = ProgressUpdate {
+            processed: 10,
+            failed: 0,
+            ..update
+        };
+        assert_eq!(done.eta(), None);
+    }
+}
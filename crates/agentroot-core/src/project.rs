@@ -0,0 +1,83 @@
+//! Project-local indexes
+//!
+//! A `.agentroot/` directory in a project root holds that project's
+//! `config.yml` and `index.sqlite`. Like `.git`, it is found by walking up
+//! from the working directory, and when present it replaces the global
+//! config and cache locations. Set `AGENTROOT_PROJECT=off` to ignore it.
+
+use crate::error::Result;
+use std::path::{Path, PathBuf};
+
+/// Name of the project-local directory
+pub const PROJECT_DIR_NAME: &str = ".agentroot";
+
+/// Index database file inside a project directory
+pub const PROJECT_DB_NAME: &str = "index.sqlite";
+
+/// Config file inside a project directory
+pub const PROJECT_CONFIG_NAME: &str = "config.yml";
+
+/// Keeps the index out of version control while the config can be committed
+const PROJECT_GITIGNORE: &str = "index.sqlite*\n";
+
+/// Nearest `.agentroot/` directory at or above `start`
+pub fn find_project_dir(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_DIR_NAME))
+        .find(|dir| dir.is_dir())
+}
+
+/// Project directory for the current working directory, if any
+pub fn project_dir() -> Option<PathBuf> {
+    if project_detection_disabled() {
+        return None;
+    }
+    find_project_dir(&std::env::current_dir().ok()?)
+}
+
+fn project_detection_disabled() -> bool {
+    std::env::var("AGENTROOT_PROJECT")
+        .map(|v| matches!(v.to_ascii_lowercase().as_str(), "off" | "0" | "false"))
+        .unwrap_or(false)
+}
+
+/// Create `.agentroot/` in `root`, returning its path
+///
+/// Succeeds without changes when the directory already exists.
+pub fn init_project(root: &Path) -> Result<PathBuf> {
+    let dir = root.join(PROJECT_DIR_NAME);
+    std::fs::create_dir_all(&dir)?;
+    let gitignore = dir.join(".gitignore");
+    if !gitignore.exists() {
+        std::fs::write(gitignore, PROJECT_GITIGNORE)?;
+    }
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_project_dir_walks_up() {
+        let temp = TempDir::new().unwrap();
+        let nested = temp.path().join("src/deep/module");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert_eq!(find_project_dir(&nested), None);
+
+        let dir = init_project(temp.path()).unwrap();
+        assert_eq!(dir, temp.path().join(PROJECT_DIR_NAME));
+        assert!(dir.join(".gitignore").exists());
+        assert_eq!(find_project_dir(&nested), Some(dir.clone()));
+        assert_eq!(find_project_dir(temp.path()), Some(dir.clone()));
+
+        // The nearest project wins
+        let inner = init_project(&temp.path().join("src")).unwrap();
+        assert_eq!(find_project_dir(&nested), Some(inner));
+
+        // Re-initializing is a no-op
+        assert_eq!(init_project(temp.path()).unwrap(), dir);
+    }
+}
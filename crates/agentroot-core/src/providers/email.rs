@@ -0,0 +1,760 @@
+//! Email provider for mbox archives and IMAP folders
+//!
+//! Every message becomes one markdown document: the subject as title, a short
+//! header block, then the plain-text body (HTML-only mail is reduced to text).
+//! Sender, recipients, date and thread go into the document's user metadata,
+//! so `author:` filters, `journal` and `metadata query` work on mail too.
+
+use crate::db::{hash_content, MetadataValue, UserMetadata};
+use crate::error::{AgentRootError, Result};
+use crate::providers::{ProviderConfig, SourceItem, SourceProvider, FRONTMATTER_KEY};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use mailparse::{DispositionType, MailAddr, MailHeaderMap, ParsedMail};
+use regex::Regex;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::OnceLock;
+use walkdir::WalkDir;
+
+/// Messages read from each IMAP folder unless `max_messages` says otherwise
+const DEFAULT_MAX_MESSAGES: usize = 1000;
+
+/// Environment variable holding the IMAP password when the config has none
+pub const IMAP_PASSWORD_ENV: &str = "AGENTROOT_IMAP_PASSWORD";
+
+/// Provider for mail archives: an mbox file, a directory of mbox and `.eml`
+/// files, or IMAP folders (`imaps://user@host/Folder`)
+pub struct EmailProvider;
+
+impl Default for EmailProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EmailProvider {
+    /// Create a new EmailProvider
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Messages of the local mailbox files under `base_path`
+    fn read_local(&self, config: &ProviderConfig) -> Result<Vec<SourceItem>> {
+        let base_path = Path::new(&config.base_path);
+        if !base_path.exists() {
+            return Err(AgentRootError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Path not found: {:?}", base_path),
+            )));
+        }
+
+        let mut keys = HashSet::new();
+        if base_path.is_file() {
+            let mailbox = base_path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| "mbox".to_string());
+            let data = std::fs::read(base_path)?;
+            return Ok(messages_to_items(
+                &mailbox,
+                split_messages(&data),
+                &mut keys,
+            ));
+        }
+
+        let glob_pattern = glob::Pattern::new(&config.pattern)?;
+        let mut items = Vec::new();
+        for entry in WalkDir::new(base_path)
+            .follow_links(true)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'))
+        {
+            let entry = entry?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let Ok(relative) = entry.path().strip_prefix(base_path) else {
+                continue;
+            };
+            let mailbox = relative.to_string_lossy().to_string();
+            if !glob_pattern.matches(&mailbox) {
+                continue;
+            }
+            match std::fs::read(entry.path()) {
+                Ok(data) => items.extend(messages_to_items(
+                    &mailbox,
+                    split_messages(&data),
+                    &mut keys,
+                )),
+                Err(e) => tracing::warn!("Failed to read mailbox {:?}: {}", entry.path(), e),
+            }
+        }
+        Ok(items)
+    }
+}
+
+#[async_trait]
+impl SourceProvider for EmailProvider {
+    fn provider_type(&self) -> &'static str {
+        "email"
+    }
+
+    async fn list_items(&self, config: &ProviderConfig) -> Result<Vec<SourceItem>> {
+        if is_imap_url(&config.base_path) {
+            let target = ImapTarget::from_config(config)?;
+            let messages = tokio::task::spawn_blocking(move || fetch_imap(&target))
+                .await
+                .map_err(|e| AgentRootError::ExternalError(format!("IMAP task failed: {}", e)))??;
+            let mut keys = HashSet::new();
+            let mut items = Vec::new();
+            for (folder, raw) in messages {
+                items.extend(messages_to_items(&folder, vec![raw], &mut keys));
+            }
+            return Ok(items);
+        }
+        self.read_local(config)
+    }
+
+    async fn fetch_item(&self, uri: &str) -> Result<SourceItem> {
+        Err(AgentRootError::InvalidInput(format!(
+            "Email messages cannot be fetched one by one: {}. Reindex the collection instead",
+            uri
+        )))
+    }
+}
+
+/// Whether a collection path names an IMAP server rather than local files
+pub fn is_imap_url(path: &str) -> bool {
+    let lower = path.to_ascii_lowercase();
+    lower.starts_with("imaps://") || lower.starts_with("imap://")
+}
+
+/// Raw messages in a mailbox file: split when it is an mbox, the whole file
+/// when it is a single message, nothing otherwise
+fn split_messages(data: &[u8]) -> Vec<Vec<u8>> {
+    if data.starts_with(b"From ") {
+        return split_mbox(data);
+    }
+    match mailparse::parse_headers(data) {
+        Ok((headers, _))
+            if headers.get_first_header("From").is_some()
+                || headers.get_first_header("Message-ID").is_some() =>
+        {
+            vec![data.to_vec()]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Split an mbox on its `From ` separator lines
+///
+/// A separator starts the file or follows a blank line. Body lines that the
+/// writer escaped as `>From ` (mboxrd) lose one `>`.
+fn split_mbox(data: &[u8]) -> Vec<Vec<u8>> {
+    let mut messages = Vec::new();
+    let mut current: Option<Vec<u8>> = None;
+    let mut previous_blank = true;
+    for line in data.split_inclusive(|b| *b == b'\n') {
+        if previous_blank && line.starts_with(b"From ") {
+            messages.extend(current.take());
+            current = Some(Vec::new());
+            previous_blank = false;
+            continue;
+        }
+        previous_blank = line == b"\n" || line == b"\r\n";
+        if let Some(message) = current.as_mut() {
+            let quotes = line.iter().take_while(|b| **b == b'>').count();
+            if quotes > 0 && line[quotes..].starts_with(b"From ") {
+                message.extend_from_slice(&line[1..]);
+            } else {
+                message.extend_from_slice(line);
+            }
+        }
+    }
+    messages.extend(current);
+    messages
+}
+
+/// Sender or recipient of a message
+#[derive(Debug, Clone, PartialEq)]
+struct Address {
+    name: Option<String>,
+    addr: String,
+}
+
+impl std::fmt::Display for Address {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{} <{}>", name, self.addr),
+            None => write!(f, "{}", self.addr),
+        }
+    }
+}
+
+/// The parts of a message that end up in the index
+#[derive(Debug, Clone)]
+struct Message {
+    id: Option<String>,
+    subject: String,
+    from: Option<Address>,
+    to: Vec<Address>,
+    cc: Vec<Address>,
+    date: Option<DateTime<Utc>>,
+    /// Id of the first message in the conversation
+    thread: Option<String>,
+    body: String,
+    attachments: Vec<String>,
+}
+
+impl Message {
+    fn parse(raw: &[u8]) -> Result<Self> {
+        let mail = mailparse::parse_mail(raw)
+            .map_err(|e| AgentRootError::Parse(format!("Invalid email message: {}", e)))?;
+        let headers = mail.get_headers();
+        let header = |name: &str| {
+            headers
+                .get_first_value(name)
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let first_id = |name: &str| {
+            header(name)
+                .and_then(|v| mailparse::msgidparse(&v).ok())
+                .and_then(|ids| ids.first().cloned())
+        };
+
+        let id = first_id("Message-ID");
+        let thread = first_id("References")
+            .or_else(|| first_id("In-Reply-To"))
+            .or_else(|| id.clone());
+        let date = header("Date")
+            .and_then(|d| mailparse::dateparse(&d).ok())
+            .and_then(|secs| DateTime::from_timestamp(secs, 0));
+
+        let mut plain = None;
+        let mut html = None;
+        let mut attachments = Vec::new();
+        collect_parts(&mail, &mut plain, &mut html, &mut attachments);
+        let body = plain
+            .or_else(|| html.map(|h| html_to_text(&h)))
+            .unwrap_or_default();
+
+        Ok(Self {
+            id,
+            subject: header("Subject").unwrap_or_else(|| "(no subject)".to_string()),
+            from: header("From").and_then(|v| addresses(&v).into_iter().next()),
+            to: header("To").map(|v| addresses(&v)).unwrap_or_default(),
+            cc: header("Cc").map(|v| addresses(&v)).unwrap_or_default(),
+            date,
+            thread,
+            body: body.trim().to_string(),
+            attachments,
+        })
+    }
+
+    /// Markdown rendering: subject heading, header lines, body, attachments
+    fn to_markdown(&self) -> String {
+        let join = |list: &[Address]| {
+            list.iter()
+                .map(|a| a.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let mut out = format!("# {}\n\n", self.subject);
+        if let Some(from) = &self.from {
+            out.push_str(&format!("**From:** {}  \n", from));
+        }
+        if !self.to.is_empty() {
+            out.push_str(&format!("**To:** {}  \n", join(&self.to)));
+        }
+        if !self.cc.is_empty() {
+            out.push_str(&format!("**Cc:** {}  \n", join(&self.cc)));
+        }
+        if let Some(date) = self.date {
+            out.push_str(&format!(
+                "**Date:** {}  \n",
+                date.format("%Y-%m-%d %H:%M UTC")
+            ));
+        }
+        out.push('\n');
+        if !self.body.is_empty() {
+            out.push_str(&self.body);
+            out.push('\n');
+        }
+        if !self.attachments.is_empty() {
+            out.push_str(&format!(
+                "\n**Attachments:** {}\n",
+                self.attachments.join(", ")
+            ));
+        }
+        out
+    }
+
+    /// Sender, recipients, date and thread as user metadata
+    fn metadata(&self, mailbox: &str) -> UserMetadata {
+        let mut metadata = UserMetadata::new();
+        if let Some(from) = &self.from {
+            metadata.add("from", MetadataValue::Text(from.to_string()));
+            let author = from.name.clone().unwrap_or_else(|| from.addr.clone());
+            metadata.add("author", MetadataValue::Text(author));
+        }
+        if !self.to.is_empty() {
+            metadata.add(
+                "to",
+                MetadataValue::tags(self.to.iter().map(|a| a.addr.clone())),
+            );
+        }
+        if let Some(date) = self.date {
+            metadata.add("date", MetadataValue::datetime(date));
+        }
+        if let Some(thread) = &self.thread {
+            metadata.add("thread", MetadataValue::Text(thread.clone()));
+        }
+        if let Some(id) = &self.id {
+            metadata.add("message_id", MetadataValue::Text(id.clone()));
+        }
+        metadata.add("mailbox", MetadataValue::Text(mailbox.to_string()));
+        metadata
+    }
+}
+
+/// Addresses in a header, with groups flattened
+fn addresses(value: &str) -> Vec<Address> {
+    let Ok(list) = mailparse::addrparse(value) else {
+        return Vec::new();
+    };
+    let single = |info: &mailparse::SingleInfo| Address {
+        name: info
+            .display_name
+            .as_ref()
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty()),
+        addr: info.addr.clone(),
+    };
+    list.iter()
+        .flat_map(|addr| match addr {
+            MailAddr::Single(info) => vec![single(info)],
+            MailAddr::Group(group) => group.addrs.iter().map(single).collect(),
+        })
+        .collect()
+}
+
+/// First text/plain and text/html bodies, and the names of attachments
+fn collect_parts(
+    part: &ParsedMail,
+    plain: &mut Option<String>,
+    html: &mut Option<String>,
+    attachments: &mut Vec<String>,
+) {
+    let disposition = part.get_content_disposition();
+    let filename = disposition
+        .params
+        .get("filename")
+        .or_else(|| part.ctype.params.get("name"))
+        .cloned();
+    if disposition.disposition == DispositionType::Attachment {
+        attachments.push(filename.unwrap_or_else(|| "(unnamed)".to_string()));
+        return;
+    }
+    if !part.subparts.is_empty() {
+        for sub in &part.subparts {
+            collect_parts(sub, plain, html, attachments);
+        }
+        return;
+    }
+    match part.ctype.mimetype.to_ascii_lowercase().as_str() {
+        "text/plain" if plain.is_none() => *plain = part.get_body().ok(),
+        "text/html" if html.is_none() => *html = part.get_body().ok(),
+        _ => attachments.extend(filename),
+    }
+}
+
+/// Readable text from an HTML body: tags dropped, block ends as line breaks
+fn html_to_text(html: &str) -> String {
+    static HIDDEN: OnceLock<Regex> = OnceLock::new();
+    static BREAKS: OnceLock<Regex> = OnceLock::new();
+    static TAGS: OnceLock<Regex> = OnceLock::new();
+    static BLANK_LINES: OnceLock<Regex> = OnceLock::new();
+    let hidden = HIDDEN.get_or_init(|| {
+        Regex::new(r"(?is)<script\b.*?</script>|<style\b.*?</style>|<head\b.*?</head>").unwrap()
+    });
+    let breaks = BREAKS.get_or_init(|| {
+        Regex::new(r"(?i)<br\s*/?>|</(p|div|li|tr|h[1-6]|blockquote)\s*>").unwrap()
+    });
+    let tags = TAGS.get_or_init(|| Regex::new(r"(?s)<[^>]*>").unwrap());
+    let blank_lines = BLANK_LINES.get_or_init(|| Regex::new(r"\n{3,}").unwrap());
+
+    let text = hidden.replace_all(html, "");
+    let text = breaks.replace_all(&text, "\n");
+    let text = tags.replace_all(&text, "");
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    let text = text
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n");
+    blank_lines.replace_all(text.trim(), "\n\n").into_owned()
+}
+
+/// Index items for the raw messages of one mailbox
+///
+/// URIs are `mailbox/message-id`; messages without an id are keyed by a hash
+/// prefix, and a key seen before gets a numeric suffix.
+fn messages_to_items(
+    mailbox: &str,
+    messages: Vec<Vec<u8>>,
+    keys: &mut HashSet<String>,
+) -> Vec<SourceItem> {
+    let mut items = Vec::new();
+    for raw in messages {
+        let message = match Message::parse(&raw) {
+            Ok(message) => message,
+            Err(e) => {
+                tracing::warn!("Skipping message in {}: {}", mailbox, e);
+                continue;
+            }
+        };
+        let content = message.to_markdown();
+        let hash = hash_content(&content);
+        let key = match &message.id {
+            Some(id) => id
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || "@.-_+".contains(c) {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect(),
+            None => format!("msg-{}", &hash[..12]),
+        };
+        let base_uri = format!("{}/{}", mailbox.trim_end_matches('/'), key);
+        let mut uri = base_uri.clone();
+        let mut n = 2;
+        while !keys.insert(uri.clone()) {
+            uri = format!("{}-{}", base_uri, n);
+            n += 1;
+        }
+
+        let mut item = SourceItem::new(
+            uri,
+            message.subject.clone(),
+            content,
+            hash,
+            "email".to_string(),
+        )
+        .with_metadata("mailbox".to_string(), mailbox.to_string());
+        if let Some(id) = &message.id {
+            item = item.with_metadata("message_id".to_string(), id.clone());
+        }
+        if let Ok(json) = message.metadata(mailbox).to_json() {
+            item = item.with_metadata(FRONTMATTER_KEY.to_string(), json);
+        }
+        items.push(item);
+    }
+    items
+}
+
+/// Server, account and folders an `imap://` or `imaps://` collection reads
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "imap"), allow(dead_code))]
+struct ImapTarget {
+    /// Implicit TLS (`imaps`); `imap` upgrades with STARTTLS
+    tls: bool,
+    host: String,
+    port: u16,
+    user: String,
+    password: String,
+    folders: Vec<String>,
+    max_messages: usize,
+}
+
+impl ImapTarget {
+    /// Read `imaps://user@host[:port]/Folder` plus the `user`, `password`,
+    /// `folders` and `max_messages` options
+    fn from_config(config: &ProviderConfig) -> Result<Self> {
+        let url = reqwest::Url::parse(&config.base_path).map_err(|e| {
+            AgentRootError::InvalidInput(format!("Invalid IMAP URL {}: {}", config.base_path, e))
+        })?;
+        let tls = url.scheme().eq_ignore_ascii_case("imaps");
+        let host = url
+            .host_str()
+            .ok_or_else(|| {
+                AgentRootError::InvalidInput(format!("IMAP URL has no host: {}", config.base_path))
+            })?
+            .to_string();
+        let user = config
+            .get_option("user")
+            .cloned()
+            .unwrap_or_else(|| percent_decode(url.username()));
+        if user.is_empty() {
+            return Err(AgentRootError::Config(format!(
+                "No IMAP user for {}; use imaps://user@{}/INBOX or the `user` option",
+                config.base_path, host
+            )));
+        }
+        let password = config
+            .get_option("password")
+            .cloned()
+            .or_else(|| std::env::var(IMAP_PASSWORD_ENV).ok())
+            .ok_or_else(|| {
+                AgentRootError::Config(format!(
+                    "No IMAP password; set {} or the `password` option",
+                    IMAP_PASSWORD_ENV
+                ))
+            })?;
+
+        let folders: Vec<String> = match config.get_option("folders") {
+            Some(list) => list
+                .split(',')
+                .map(|f| f.trim().to_string())
+                .filter(|f| !f.is_empty())
+                .collect(),
+            None => {
+                let path = percent_decode(url.path().trim_matches('/'));
+                if path.is_empty() {
+                    vec!["INBOX".to_string()]
+                } else {
+                    vec![path]
+                }
+            }
+        };
+        let max_messages = config
+            .get_option("max_messages")
+            .and_then(|n| n.parse().ok())
+            .unwrap_or(DEFAULT_MAX_MESSAGES);
+
+        Ok(Self {
+            tls,
+            port: url.port().unwrap_or(if tls { 993 } else { 143 }),
+            host,
+            user,
+            password,
+            folders,
+            max_messages,
+        })
+    }
+}
+
+/// Decode `%XX` escapes in a URL user name or path
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Newest `max_messages` raw messages of each folder, read-only
+#[cfg(feature = "imap")]
+fn fetch_imap(target: &ImapTarget) -> Result<Vec<(String, Vec<u8>)>> {
+    let imap_error = |e: imap::Error| AgentRootError::ExternalError(format!("IMAP: {}", e));
+    let tls = native_tls::TlsConnector::builder()
+        .build()
+        .map_err(|e| AgentRootError::ExternalError(format!("TLS setup failed: {}", e)))?;
+    let address = (target.host.as_str(), target.port);
+    let client = if target.tls {
+        imap::connect(address, &target.host, &tls)
+    } else {
+        imap::connect_starttls(address, &target.host, &tls)
+    }
+    .map_err(imap_error)?;
+    let mut session = client
+        .login(&target.user, &target.password)
+        .map_err(|(e, _)| imap_error(e))?;
+
+    let mut messages = Vec::new();
+    for folder in &target.folders {
+        let mailbox = session.examine(folder).map_err(imap_error)?;
+        if mailbox.exists == 0 {
+            continue;
+        }
+        let limit = u32::try_from(target.max_messages).unwrap_or(u32::MAX);
+        let first = mailbox.exists.saturating_sub(limit) + 1;
+        let fetches = session
+            .fetch(format!("{}:{}", first, mailbox.exists), "BODY.PEEK[]")
+            .map_err(imap_error)?;
+        for fetch in fetches.iter() {
+            if let Some(body) = fetch.body() {
+                messages.push((folder.clone(), body.to_vec()));
+            }
+        }
+    }
+    if let Err(e) = session.logout() {
+        tracing::debug!("IMAP logout failed: {}", e);
+    }
+    Ok(messages)
+}
+
+#[cfg(not(feature = "imap"))]
+fn fetch_imap(_target: &ImapTarget) -> Result<Vec<(String, Vec<u8>)>> {
+    Err(AgentRootError::Config(
+        "this build cannot read IMAP folders; rebuild with `--features imap`".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MBOX: &str = "From alice@example.com Mon Mar  3 09:15:00 2025\n\
+Message-ID: <launch-1@example.com>\n\
+From: Alice Smith <alice@example.com>\n\
+To: Bob <bob@example.com>, carol@example.com\n\
+Subject: Launch plan\n\
+Date: Mon, 03 Mar 2025 09:15:00 +0000\n\
+MIME-Version: 1.0\n\
+Content-Type: multipart/mixed; boundary=\"b1\"\n\
+\n\
+--b1\n\
+Content-Type: text/html; charset=utf-8\n\
+\n\
+<html><head><style>p {}</style></head><body><p>We ship on <b>Friday</b>.</p><p>Tom &amp; Jerry</p></body></html>\n\
+--b1\n\
+Content-Type: application/pdf; name=\"plan.pdf\"\n\
+Content-Disposition: attachment; filename=\"plan.pdf\"\n\
+Content-Transfer-Encoding: base64\n\
+\n\
+JVBERi0xLjQK\n\
+--b1--\n\
+\n\
+From bob@example.com Tue Mar  4 10:00:00 2025\n\
+Message-ID: <reply-2@example.com>\n\
+In-Reply-To: <launch-1@example.com>\n\
+References: <launch-1@example.com>\n\
+From: bob@example.com\n\
+To: alice@example.com\n\
+Subject: Re: Launch plan\n\
+Date: Tue, 04 Mar 2025 10:00:00 +0000\n\
+\n\
+Friday works.\n\
+>From the release checklist: tag first.\n";
+
+    #[test]
+    fn test_provider_type() {
+        assert_eq!(EmailProvider::new().provider_type(), "email");
+    }
+
+    #[test]
+    fn test_split_mbox_unescapes_from_lines() {
+        let messages = split_messages(MBOX.as_bytes());
+        assert_eq!(messages.len(), 2);
+        let reply = String::from_utf8(messages[1].clone()).unwrap();
+        assert!(reply.contains("\nFrom the release checklist"));
+        assert!(split_messages(b"# just notes\n").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mbox_messages_become_documents() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("work.mbox");
+        std::fs::write(&path, MBOX).unwrap();
+
+        let provider = EmailProvider::new();
+        let config = ProviderConfig::new(path.to_string_lossy().to_string(), "**/*".to_string());
+        let items = provider.list_items(&config).await.unwrap();
+        assert_eq!(items.len(), 2);
+
+        let launch = &items[0];
+        assert_eq!(launch.uri, "work.mbox/launch-1@example.com");
+        assert_eq!(launch.title, "Launch plan");
+        assert!(launch.content.contains("We ship on Friday."));
+        assert!(launch.content.contains("Tom & Jerry"));
+        assert!(!launch.content.contains("<p>"));
+        assert!(launch
+            .content
+            .contains("**From:** Alice Smith <alice@example.com>"));
+        assert!(launch.content.contains("**Attachments:** plan.pdf"));
+
+        let metadata = UserMetadata::from_json(&launch.metadata[FRONTMATTER_KEY]).unwrap();
+        assert_eq!(
+            metadata.get("author"),
+            Some(&MetadataValue::Text("Alice Smith".to_string()))
+        );
+        assert_eq!(
+            metadata.get("to"),
+            Some(&MetadataValue::tags([
+                "bob@example.com",
+                "carol@example.com"
+            ]))
+        );
+        assert!(matches!(
+            metadata.get("date"),
+            Some(MetadataValue::DateTime(d)) if d.starts_with("2025-03-03T09:15:00")
+        ));
+
+        // A reply joins the thread of the message it answers
+        let reply = UserMetadata::from_json(&items[1].metadata[FRONTMATTER_KEY]).unwrap();
+        assert_eq!(
+            reply.get("thread"),
+            Some(&MetadataValue::Text("launch-1@example.com".to_string()))
+        );
+        assert_eq!(reply.get("thread"), metadata.get("thread"));
+        assert_eq!(
+            reply.get("author"),
+            Some(&MetadataValue::Text("bob@example.com".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_directory_reads_eml_files_and_skips_other_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("note.eml"),
+            "From: dana@example.com\nSubject: Hello\n\nHi there\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("readme.txt"), "not mail\n").unwrap();
+
+        let config =
+            ProviderConfig::new(dir.path().to_string_lossy().to_string(), "**/*".to_string());
+        let items = EmailProvider::new().list_items(&config).await.unwrap();
+        assert_eq!(items.len(), 1);
+        assert!(items[0].uri.starts_with("note.eml/msg-"));
+        assert!(items[0].content.contains("Hi there"));
+    }
+
+    #[test]
+    fn test_imap_target_from_config() {
+        let config = ProviderConfig::new(
+            "imaps://me%40example.com@mail.example.com/Archive%202024".to_string(),
+            "**/*".to_string(),
+        )
+        .with_option("password".to_string(), "secret".to_string());
+        let target = ImapTarget::from_config(&config).unwrap();
+        assert!(target.tls);
+        assert_eq!(target.port, 993);
+        assert_eq!(target.user, "me@example.com");
+        assert_eq!(target.folders, vec!["Archive 2024"]);
+        assert_eq!(target.max_messages, DEFAULT_MAX_MESSAGES);
+
+        let starttls =
+            ProviderConfig::new("imap://me@mail.example.com".to_string(), "**/*".to_string())
+                .with_option("password".to_string(), "secret".to_string())
+                .with_option("folders".to_string(), "INBOX, Sent".to_string());
+        let target = ImapTarget::from_config(&starttls).unwrap();
+        assert!(!target.tls);
+        assert_eq!(target.port, 143);
+        assert_eq!(target.folders, vec!["INBOX", "Sent"]);
+    }
+}
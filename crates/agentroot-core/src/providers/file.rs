@@ -2,12 +2,19 @@
 //!
 //! Provides content from local file system using glob patterns.
 
-use super::{ProviderConfig, SourceItem, SourceProvider};
+use super::{
+    FileStamp, ProviderConfig, ProviderListing, SkipReason, SkippedItem, SourceItem,
+    SourceProvider, MTIME_KEY, SIZE_KEY,
+};
 use crate::db::hash_content;
-use crate::error::Result;
+use crate::error::{AgentRootError, Result};
 use crate::index::extract_title;
+use chardetng::{EncodingDetector, Iso2022JpDetection, Utf8Detection};
+use encoding_rs::Encoding;
 use glob::Pattern;
-use std::path::Path;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use walkdir::{DirEntry, WalkDir};
 
 /// Directories to exclude from scanning
@@ -23,6 +30,20 @@ const EXCLUDE_DIRS: &[&str] = &[
     "target",
 ];
 
+/// Bytes inspected when deciding whether a file is binary
+const BINARY_SNIFF_BYTES: usize = 8192;
+
+/// Size cap used when a collection sets no `max_file_size_mb`
+pub const DEFAULT_MAX_FILE_SIZE_MB: f64 = 10.0;
+
+/// What to do with files over the size cap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Oversized {
+    Skip,
+    /// Index the first `max_file_size_mb` and append a marker
+    Truncate,
+}
+
 /// File system provider
 pub struct FileProvider;
 
@@ -46,6 +67,10 @@ impl SourceProvider for FileProvider {
     }
 
     async fn list_items(&self, config: &ProviderConfig) -> Result<Vec<SourceItem>> {
+        Ok(self.scan(config).await?.items)
+    }
+
+    async fn scan(&self, config: &ProviderConfig) -> Result<ProviderListing> {
         let root = Path::new(&config.base_path);
         let pattern = Pattern::new(&config.pattern)?;
 
@@ -59,17 +84,48 @@ impl SourceProvider for FileProvider {
             .and_then(|v| v.parse::<bool>().ok())
             .unwrap_or(true);
 
+        let max_file_size = (config
+            .get_option("max_file_size_mb")
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|mb| *mb > 0.0)
+            .unwrap_or(DEFAULT_MAX_FILE_SIZE_MB)
+            * 1024.0
+            * 1024.0) as u64;
+
+        let oversized = match config.get_option("oversized").map(String::as_str) {
+            None | Some("skip") => Oversized::Skip,
+            Some("truncate") => Oversized::Truncate,
+            Some(other) => {
+                return Err(AgentRootError::InvalidInput(format!(
+                    "Invalid oversized option '{}' (expected skip or truncate)",
+                    other
+                )))
+            }
+        };
+
         let exclude_dirs: Vec<String> = EXCLUDE_DIRS.iter().map(|s| s.to_string()).collect();
 
-        let mut items = Vec::new();
+        let real_root = std::fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+        let mut matches: Vec<(String, PathBuf)> = Vec::new();
+        // Canonical target -> index into matches, so each file is indexed once
+        let mut seen: HashMap<PathBuf, usize> = HashMap::new();
 
         let walker = WalkDir::new(root)
             .follow_links(follow_symlinks)
+            .sort_by_file_name()
             .into_iter()
             .filter_entry(|e| !should_skip(e, &exclude_dirs, exclude_hidden));
 
         for entry in walker {
-            let entry = entry?;
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) if is_link_error(&e) => {
+                    tracing::warn!("Skipping symlink: {}", e);
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            // Without follow_symlinks, links report as symlinks and are skipped here
             if !entry.file_type().is_file() {
                 continue;
             }
@@ -80,32 +136,216 @@ impl SourceProvider for FileProvider {
                 .map(|p| p.to_string_lossy().to_string())
                 .unwrap_or_else(|_| path.to_string_lossy().to_string());
 
-            if pattern.matches(&relative) {
-                let content = std::fs::read_to_string(path)?;
-                let title = extract_title(&content, &relative);
-                let hash = hash_content(&content);
+            if !pattern.matches(&relative) {
+                continue;
+            }
+
+            let canonical = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+            match seen.get(&canonical) {
+                // Keep the file's own location over a link pointing at it
+                Some(&i) => {
+                    if canonical == real_root.join(&relative) {
+                        matches[i] = (relative, path.to_path_buf());
+                    }
+                }
+                None => {
+                    seen.insert(canonical, matches.len());
+                    matches.push((relative, path.to_path_buf()));
+                }
+            }
+        }
 
-                items.push(
-                    SourceItem::new(relative, title, content, hash, "file".to_string())
-                        .with_metadata("absolute_path".to_string(), path.display().to_string()),
-                );
+        // Reading, decoding and hashing dominate a first index; spread them
+        // over the workers and collect in walk order
+        let read = FileRead {
+            max_file_size,
+            oversized,
+            known_stamps: &config.known_stamps,
+        };
+        let scanned =
+            crate::index::map_parallel(matches, crate::index::index_workers(), |m| read.read(m));
+
+        let mut listing = ProviderListing::default();
+        for scanned in scanned {
+            match scanned? {
+                Scanned::Item(item) => listing.items.push(item),
+                Scanned::Skipped(skipped) => listing.skipped.push(skipped),
+                Scanned::Unchanged(uri) => listing.unchanged.push(uri),
             }
         }
 
-        Ok(items)
+        Ok(listing)
     }
 
     async fn fetch_item(&self, uri: &str) -> Result<SourceItem> {
         let path = Path::new(uri);
-        let content = std::fs::read_to_string(path)?;
+        let bytes = std::fs::read(path)?;
+        let (content, encoding) = decode_text(&bytes).ok_or_else(|| {
+            AgentRootError::InvalidInput(format!("{} is a binary file", path.display()))
+        })?;
         let title = extract_title(&content, uri);
         let hash = hash_content(&content);
 
-        Ok(
-            SourceItem::new(uri.to_string(), title, content, hash, "file".to_string())
-                .with_metadata("absolute_path".to_string(), path.display().to_string()),
-        )
+        let mut item = SourceItem::new(uri.to_string(), title, content, hash, "file".to_string())
+            .with_metadata("absolute_path".to_string(), path.display().to_string());
+        if let Some(encoding) = encoding {
+            item = item.with_metadata("encoding".to_string(), encoding.name().to_string());
+        }
+        Ok(item)
+    }
+}
+
+/// What scanning found at one matched path
+enum Scanned {
+    Item(SourceItem),
+    Skipped(SkippedItem),
+    Unchanged(String),
+}
+
+/// Per-file part of a scan, shared by the worker threads
+struct FileRead<'a> {
+    max_file_size: u64,
+    oversized: Oversized,
+    known_stamps: &'a HashMap<String, FileStamp>,
+}
+
+impl FileRead<'_> {
+    fn read(&self, (relative, path): (String, PathBuf)) -> Result<Scanned> {
+        let metadata = std::fs::metadata(&path)?;
+        let size = metadata.len();
+        let truncated = size > self.max_file_size;
+        if truncated && self.oversized == Oversized::Skip {
+            tracing::warn!(
+                "Skipping {} ({} bytes, over max_file_size_mb of {} bytes)",
+                path.display(),
+                size,
+                self.max_file_size
+            );
+            return Ok(Scanned::Skipped(SkippedItem {
+                uri: relative,
+                reason: SkipReason::TooLarge,
+            }));
+        }
+
+        let stamp = FileStamp::from_metadata(&metadata);
+        if stamp.is_some() && self.known_stamps.get(&relative) == stamp.as_ref() {
+            return Ok(Scanned::Unchanged(relative));
+        }
+
+        // Never read more than the cap, however large the file
+        let mut bytes = Vec::with_capacity(size.min(self.max_file_size) as usize);
+        std::fs::File::open(&path)?
+            .take(self.max_file_size)
+            .read_to_end(&mut bytes)?;
+        let Some((mut content, encoding)) = decode_text(&bytes) else {
+            tracing::debug!("Skipping binary file: {}", path.display());
+            return Ok(Scanned::Skipped(SkippedItem {
+                uri: relative,
+                reason: SkipReason::Binary,
+            }));
+        };
+        if truncated {
+            // Back to the last full line
+            if let Some(newline) = content.rfind('\n') {
+                content.truncate(newline + 1);
+            }
+            let indexed = content.len();
+            if !content.ends_with('\n') {
+                content.push('\n');
+            }
+            content.push_str(&format!(
+                "\n[truncated: indexed {} of {} bytes]\n",
+                indexed, size
+            ));
+        }
+        let title = extract_title(&content, &relative);
+        let hash = hash_content(&content);
+
+        let mut item = SourceItem::new(relative, title, content, hash, "file".to_string())
+            .with_metadata("absolute_path".to_string(), path.display().to_string());
+        if truncated {
+            item = item
+                .with_metadata("truncated".to_string(), "true".to_string())
+                .with_metadata("original_size".to_string(), size.to_string());
+        }
+        if let Some(encoding) = encoding {
+            item = item.with_metadata("encoding".to_string(), encoding.name().to_string());
+        }
+        if let Some(stamp) = stamp {
+            item = item
+                .with_metadata(MTIME_KEY.to_string(), stamp.mtime.to_string())
+                .with_metadata(SIZE_KEY.to_string(), stamp.size.to_string());
+        }
+        Ok(Scanned::Item(item))
+    }
+}
+
+/// Decode file bytes to UTF-8 text, or None for binary content
+///
+/// A byte order mark wins (UTF-16 text is full of NUL bytes, so it must be
+/// recognized before the binary check). UTF-8 is taken as is; anything else
+/// goes through chardetng. The encoding is returned when it was not UTF-8.
+fn decode_text(bytes: &[u8]) -> Option<(String, Option<&'static Encoding>)> {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+        let encoding = (encoding != encoding_rs::UTF_8).then_some(encoding);
+        return Some((text.into_owned(), encoding));
+    }
+    if is_binary(bytes) {
+        return None;
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) => return Some((text.to_string(), None)),
+        // A capped read can end mid-character
+        Err(e) if e.error_len().is_none() => {
+            return Some((
+                String::from_utf8_lossy(&bytes[..e.valid_up_to()]).into_owned(),
+                None,
+            ))
+        }
+        Err(_) => {}
+    }
+
+    // Valid UTF-8 was handled above, so it is not a candidate here
+    let mut detector = EncodingDetector::new(Iso2022JpDetection::Deny);
+    detector.feed(bytes, true);
+    let encoding = detector.guess(None, Utf8Detection::Deny);
+    let (text, _) = encoding.decode_without_bom_handling(bytes);
+    Some((text.into_owned(), Some(encoding)))
+}
+
+/// NUL bytes, or more than 10% control characters, in the first few KB
+fn is_binary(bytes: &[u8]) -> bool {
+    let sample = &bytes[..bytes.len().min(BINARY_SNIFF_BYTES)];
+    if sample.contains(&0) {
+        return true;
+    }
+    let control = sample
+        .iter()
+        .filter(|&&b| b < 0x20 && !matches!(b, b'\n' | b'\r' | b'\t' | 0x0c | 0x1b))
+        .count();
+    control * 10 > sample.len()
+}
+
+/// Symlink cycles and dangling links are skipped rather than failing the scan
+fn is_link_error(error: &walkdir::Error) -> bool {
+    if error.loop_ancestor().is_some() {
+        return true;
     }
+    error
+        .path()
+        .and_then(|p| p.symlink_metadata().ok())
+        .is_some_and(|m| m.file_type().is_symlink())
+}
+
+/// Whether a path relative to a collection root lies where a scan never looks
+pub(crate) fn is_excluded_path(relative: &Path, exclude_hidden: bool) -> bool {
+    let names: Vec<_> = relative.components().map(|c| c.as_os_str()).collect();
+    names.iter().enumerate().any(|(i, name)| {
+        let name = name.to_string_lossy();
+        (exclude_hidden && name.starts_with('.'))
+            || (i + 1 < names.len() && EXCLUDE_DIRS.iter().any(|d| name == *d))
+    })
 }
 
 fn should_skip(entry: &DirEntry, exclude_dirs: &[String], exclude_hidden: bool) -> bool {
@@ -158,6 +398,122 @@ mod tests {
         assert!(items.iter().any(|i| i.uri == "test2.md"));
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_file_provider_symlinks() {
+        use std::os::unix::fs::symlink;
+
+        let temp = TempDir::new().unwrap();
+        let base = temp.path();
+        fs::create_dir(base.join("vault")).unwrap();
+        fs::write(base.join("vault/note.md"), "# Note").unwrap();
+        symlink(base.join("vault"), base.join("alias")).unwrap();
+        symlink(base.join("vault/note.md"), base.join("link.md")).unwrap();
+        symlink(base, base.join("vault/loop")).unwrap();
+        symlink(base.join("missing.md"), base.join("dangling.md")).unwrap();
+
+        let provider = FileProvider::new();
+        let config = |follow: &str| {
+            ProviderConfig::new(base.to_string_lossy().to_string(), "**/*.md".to_string())
+                .with_option("follow_symlinks".to_string(), follow.to_string())
+        };
+
+        // Followed: the loop and dangling link are skipped, and the note is
+        // indexed once under its real path
+        let items = provider.list_items(&config("true")).await.unwrap();
+        let uris: Vec<&str> = items.iter().map(|i| i.uri.as_str()).collect();
+        assert_eq!(uris, vec!["vault/note.md"]);
+
+        let items = provider.list_items(&config("false")).await.unwrap();
+        let uris: Vec<&str> = items.iter().map(|i| i.uri.as_str()).collect();
+        assert_eq!(uris, vec!["vault/note.md"]);
+    }
+
+    #[tokio::test]
+    async fn test_file_provider_size_cap() {
+        let temp = TempDir::new().unwrap();
+        let base = temp.path();
+        fs::write(base.join("small.md"), "# Small").unwrap();
+        let big: String = (0..40)
+            .map(|i| format!("line {:03} of text\n", i))
+            .collect();
+        fs::write(base.join("big.md"), &big).unwrap();
+
+        // About 105 bytes
+        let config = |policy: &str| {
+            ProviderConfig::new(base.to_string_lossy().to_string(), "**/*.md".to_string())
+                .with_option("max_file_size_mb".to_string(), "0.0001".to_string())
+                .with_option("oversized".to_string(), policy.to_string())
+        };
+        let provider = FileProvider::new();
+
+        // Both files fit the default cap
+        let default_cap =
+            ProviderConfig::new(base.to_string_lossy().to_string(), "**/*.md".to_string());
+        let listing = provider.scan(&default_cap).await.unwrap();
+        assert_eq!(listing.items.len(), 2);
+        assert!(listing.skipped.is_empty());
+
+        let listing = provider.scan(&config("skip")).await.unwrap();
+        assert_eq!(listing.items.len(), 1);
+        assert_eq!(
+            listing.skipped,
+            vec![SkippedItem {
+                uri: "big.md".to_string(),
+                reason: SkipReason::TooLarge
+            }]
+        );
+
+        let listing = provider.scan(&config("truncate")).await.unwrap();
+        assert!(listing.skipped.is_empty());
+        let item = listing.items.iter().find(|i| i.uri == "big.md").unwrap();
+        assert!(item.content.starts_with("line 000 of text\nline 001"));
+        assert!(item
+            .content
+            .contains("\nline 005 of text\n\n[truncated: indexed 102 of"));
+        assert_eq!(item.metadata["truncated"], "true");
+        assert_eq!(item.metadata["original_size"], big.len().to_string());
+
+        assert!(provider.scan(&config("drop")).await.is_err());
+    }
+
+    #[test]
+    fn test_decode_text() {
+        let (text, encoding) = decode_text("caf\u{e9}".as_bytes()).unwrap();
+        assert_eq!(text, "caf\u{e9}");
+        assert!(encoding.is_none());
+
+        // UTF-16LE with a BOM is text, despite its NUL bytes
+        let mut utf16 = vec![0xff, 0xfe];
+        utf16.extend(
+            "# Notes\nh\u{e9}llo"
+                .encode_utf16()
+                .flat_map(|u| u.to_le_bytes()),
+        );
+        let (text, encoding) = decode_text(&utf16).unwrap();
+        assert_eq!(text, "# Notes\nh\u{e9}llo");
+        assert_eq!(encoding, Some(encoding_rs::UTF_16LE));
+
+        let latin1 = b"Le caf\xe9 est tr\xe8s bon, la cr\xe8me br\xfbl\xe9e aussi.";
+        let (text, encoding) = decode_text(latin1).unwrap();
+        assert_eq!(
+            text,
+            "Le caf\u{e9} est tr\u{e8}s bon, la cr\u{e8}me br\u{fb}l\u{e9}e aussi."
+        );
+        assert_eq!(encoding, Some(encoding_rs::WINDOWS_1252));
+
+        assert!(decode_text(b"\x89PNG\r\n\x1a\n\x00\x00").is_none());
+    }
+
+    #[test]
+    fn test_is_binary() {
+        assert!(!is_binary(b"# Title\n\nplain text\twith tabs\r\n"));
+        assert!(!is_binary("unicode caf\u{e9}".as_bytes()));
+        assert!(!is_binary(b""));
+        assert!(is_binary(b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR"));
+        assert!(is_binary(b"\x01\x02\x03\x04abcdef"));
+    }
+
     #[tokio::test]
     async fn test_file_provider_fetch_item() {
         let temp = TempDir::new().unwrap();
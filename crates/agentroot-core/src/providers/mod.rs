@@ -2,34 +2,52 @@
 //!
 //! Provides a unified interface for indexing content from different sources:
 //! - File system (local files)
+//! - Obsidian vaults
 //! - GitHub (repositories, files, gists)
 //! - URLs (web pages, PDFs)
 //! - Databases (SQL, NoSQL)
+//! - Email (mbox archives, IMAP folders)
 //! - Calendar/Notes/Books
 //!
 //! Each provider implements the SourceProvider trait to enable seamless
 //! integration with agentroot's indexing and search capabilities.
 
 use crate::error::Result;
+use crate::index::{extract_title, normalize_text, parse_frontmatter, SecretMatch, SecretScanner};
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::sync::Arc;
 
 pub mod csv;
+pub mod email;
 pub mod file;
 pub mod github;
 pub mod json;
+pub mod obsidian;
 pub mod pdf;
 pub mod sql;
 pub mod url;
 
 pub use csv::CSVProvider;
+pub use email::EmailProvider;
 pub use file::FileProvider;
 pub use github::GitHubProvider;
 pub use json::JSONProvider;
+pub use obsidian::ObsidianProvider;
 pub use pdf::PDFProvider;
 pub use sql::SQLProvider;
 pub use url::URLProvider;
 
+/// `SourceItem::metadata` key holding extracted frontmatter as `UserMetadata` JSON
+pub const FRONTMATTER_KEY: &str = "frontmatter";
+
+/// `SourceItem::metadata` key holding a web page's normalized canonical URL
+pub const CANONICAL_URL_KEY: &str = "canonical_url";
+
+/// `SourceItem::metadata` keys holding the file's [`FileStamp`]
+pub const MTIME_KEY: &str = "mtime";
+pub const SIZE_KEY: &str = "size";
+
 /// Source provider trait - all content sources must implement this
 #[async_trait::async_trait]
 pub trait SourceProvider: Send + Sync {
@@ -39,6 +57,17 @@ pub trait SourceProvider: Send + Sync {
     /// List all items from source (for scanning/indexing)
     async fn list_items(&self, config: &ProviderConfig) -> Result<Vec<SourceItem>>;
 
+    /// List items along with the ones deliberately left out
+    ///
+    /// Providers that filter content (binary files, for example) override this
+    /// so reindexing can report what was skipped.
+    async fn scan(&self, config: &ProviderConfig) -> Result<ProviderListing> {
+        Ok(ProviderListing {
+            items: self.list_items(config).await?,
+            ..Default::default()
+        })
+    }
+
     /// Fetch single item by URI
     async fn fetch_item(&self, uri: &str) -> Result<SourceItem>;
 }
@@ -54,6 +83,35 @@ pub struct ProviderConfig {
 
     /// Provider-specific options (auth tokens, filters, etc.)
     pub options: HashMap<String, String>,
+
+    /// Modification time and size of already indexed items, by URI
+    ///
+    /// Providers that can stat an item cheaply report matches as unchanged
+    /// instead of reading them.
+    pub known_stamps: HashMap<String, FileStamp>,
+}
+
+/// Modification time (nanoseconds since the epoch) and size of a source file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileStamp {
+    pub mtime: i64,
+    pub size: u64,
+}
+
+impl FileStamp {
+    /// Stamp from filesystem metadata; None when the platform has no mtime
+    pub fn from_metadata(metadata: &std::fs::Metadata) -> Option<Self> {
+        let mtime = metadata
+            .modified()
+            .ok()?
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()?
+            .as_nanos();
+        Some(Self {
+            mtime: i64::try_from(mtime).ok()?,
+            size: metadata.len(),
+        })
+    }
 }
 
 impl ProviderConfig {
@@ -63,6 +121,7 @@ impl ProviderConfig {
             base_path,
             pattern,
             options: HashMap::new(),
+            known_stamps: HashMap::new(),
         }
     }
 
@@ -76,6 +135,12 @@ impl ProviderConfig {
     pub fn get_option(&self, key: &str) -> Option<&String> {
         self.options.get(key)
     }
+
+    /// Provide stamps of indexed items so unchanged ones are not re-read
+    pub fn with_known_stamps(mut self, stamps: HashMap<String, FileStamp>) -> Self {
+        self.known_stamps = stamps;
+        self
+    }
 }
 
 /// Item from a source provider
@@ -124,6 +189,93 @@ impl SourceItem {
         self.metadata.insert(key, value);
         self
     }
+
+    /// Convert title and content to NFC, rehashing if the content changed
+    pub fn normalized(mut self) -> Self {
+        if let Cow::Owned(title) = normalize_text(&self.title) {
+            self.title = title;
+        }
+        if let Cow::Owned(content) = normalize_text(&self.content) {
+            self.hash = crate::db::hash_content(&content);
+            self.content = content;
+        }
+        self
+    }
+
+    /// Redact credentials in the content, rehashing if any were replaced
+    ///
+    /// Returns the detected credentials, which are also reported in
+    /// report-only mode.
+    pub fn with_secrets_redacted(mut self, scanner: &SecretScanner) -> (Self, Vec<SecretMatch>) {
+        let (content, found) = scanner.redact(&self.content);
+        if let Cow::Owned(content) = content {
+            self.hash = crate::db::hash_content(&content);
+            self.content = content;
+        }
+        (self, found)
+    }
+
+    /// Move markdown frontmatter out of the content into [`FRONTMATTER_KEY`]
+    ///
+    /// The hash still covers the whole source, so editing only the
+    /// frontmatter reindexes the document.
+    pub fn with_frontmatter_extracted(mut self) -> Self {
+        let uri = self.uri.to_lowercase();
+        if ![".md", ".markdown", ".mdx"]
+            .iter()
+            .any(|ext| uri.ends_with(ext))
+        {
+            return self;
+        }
+        let Some(frontmatter) = parse_frontmatter(&self.content) else {
+            return self;
+        };
+        let Ok(json) = frontmatter.metadata.to_json() else {
+            return self;
+        };
+
+        self.title = match frontmatter.metadata.get("title") {
+            Some(crate::db::MetadataValue::Text(title)) => title.clone(),
+            _ => extract_title(frontmatter.body, &self.uri),
+        };
+        self.content = frontmatter.body.to_string();
+        self.metadata.insert(FRONTMATTER_KEY.to_string(), json);
+        self
+    }
+}
+
+/// Items found by a provider scan
+#[derive(Debug, Clone, Default)]
+pub struct ProviderListing {
+    pub items: Vec<SourceItem>,
+    pub skipped: Vec<SkippedItem>,
+    /// URIs whose stamp matched `ProviderConfig::known_stamps`; not read
+    pub unchanged: Vec<String>,
+}
+
+/// A source item that matched the collection but was not indexed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedItem {
+    pub uri: String,
+    pub reason: SkipReason,
+}
+
+/// Why a source item was not indexed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// Content looks like an image, archive or executable rather than text
+    Binary,
+    /// Larger than the collection's size cap
+    TooLarge,
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipReason::Binary => write!(f, "binary"),
+            SkipReason::TooLarge => write!(f, "too large"),
+        }
+    }
 }
 
 /// Registry for managing provider instances
@@ -143,9 +295,11 @@ impl ProviderRegistry {
     pub fn with_defaults() -> Self {
         let mut registry = Self::new();
         registry.register(Arc::new(CSVProvider::new()));
+        registry.register(Arc::new(EmailProvider::new()));
         registry.register(Arc::new(FileProvider::new()));
         registry.register(Arc::new(GitHubProvider::new()));
         registry.register(Arc::new(JSONProvider::new()));
+        registry.register(Arc::new(ObsidianProvider::new()));
         registry.register(Arc::new(PDFProvider::new()));
         registry.register(Arc::new(SQLProvider::new()));
         registry.register(Arc::new(URLProvider::new()));
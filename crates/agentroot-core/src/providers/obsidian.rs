@@ -0,0 +1,374 @@
+//! Obsidian vault provider
+//!
+//! Reads a vault like the file provider and adds what Obsidian keeps outside
+//! the frontmatter: inline `#tags` join the frontmatter `tags`, so notes can
+//! be filtered on either, and notes named with the vault's daily-note format
+//! are dated. `[[wiki-links]]`, including links to a note's `aliases`, become
+//! `document_links` edges when the link graph is built.
+
+use super::{
+    FileProvider, ProviderConfig, ProviderListing, SourceItem, SourceProvider, FRONTMATTER_KEY,
+};
+use crate::db::{MetadataValue, UserMetadata};
+use crate::error::Result;
+use chrono::NaiveDate;
+use std::path::{Path, PathBuf};
+
+/// Vault settings directory
+const SETTINGS_DIR: &str = ".obsidian";
+
+/// Daily notes core plugin settings, relative to the vault root
+const DAILY_NOTES_SETTINGS: &str = ".obsidian/daily-notes.json";
+
+/// Obsidian's default daily note name
+const DEFAULT_DAILY_FORMAT: &str = "YYYY-MM-DD";
+
+lazy_static::lazy_static! {
+    /// `#tag` after whitespace or at the start of a line; `#` followed by a
+    /// space is a heading
+    static ref INLINE_TAG_RE: regex::Regex =
+        regex::Regex::new(r"(?:^|\s)#([\p{L}\p{N}_/-]+)").expect("Invalid regex");
+}
+
+/// Obsidian vault provider
+pub struct ObsidianProvider {
+    files: FileProvider,
+}
+
+impl ObsidianProvider {
+    /// Create new Obsidian provider
+    pub fn new() -> Self {
+        Self {
+            files: FileProvider::new(),
+        }
+    }
+}
+
+impl Default for ObsidianProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl SourceProvider for ObsidianProvider {
+    fn provider_type(&self) -> &'static str {
+        "obsidian"
+    }
+
+    async fn list_items(&self, config: &ProviderConfig) -> Result<Vec<SourceItem>> {
+        Ok(self.scan(config).await?.items)
+    }
+
+    async fn scan(&self, config: &ProviderConfig) -> Result<ProviderListing> {
+        let mut listing = self.files.scan(config).await?;
+        let daily = DailyNotes::load(Path::new(&config.base_path));
+        listing.items = listing
+            .items
+            .into_iter()
+            .map(|item| vault_note(item, &daily))
+            .collect();
+        Ok(listing)
+    }
+
+    async fn fetch_item(&self, uri: &str) -> Result<SourceItem> {
+        let item = self.files.fetch_item(uri).await?;
+        let path = Path::new(uri);
+        let Some(root) = vault_root(path) else {
+            return Ok(vault_note(item, &DailyNotes::default()));
+        };
+        let daily = DailyNotes::load(&root);
+        let relative = path
+            .strip_prefix(&root)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|_| uri.to_string());
+        let item = SourceItem {
+            uri: relative,
+            ..item
+        };
+        let mut item = vault_note(item, &daily);
+        item.uri = uri.to_string();
+        Ok(item)
+    }
+}
+
+/// Closest ancestor of `path` holding a `.obsidian` settings directory
+fn vault_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .skip(1)
+        .find(|dir| dir.join(SETTINGS_DIR).is_dir())
+        .map(Path::to_path_buf)
+}
+
+/// Fold inline tags and the daily-note date into a note's frontmatter
+///
+/// Frontmatter is extracted here rather than at indexing time so the tags
+/// written in the body can be merged with it; a `date` in the frontmatter
+/// wins over the one taken from the file name.
+fn vault_note(item: SourceItem, daily: &DailyNotes) -> SourceItem {
+    let mut item = item.with_frontmatter_extracted();
+    item.source_type = "obsidian".to_string();
+    if !item.uri.to_lowercase().ends_with(".md") {
+        return item;
+    }
+
+    let mut metadata = item
+        .metadata
+        .get(FRONTMATTER_KEY)
+        .and_then(|json| UserMetadata::from_json(json).ok())
+        .unwrap_or_default();
+    let original_len = metadata.fields.len();
+
+    let mut tags = match metadata.get("tags") {
+        Some(MetadataValue::Tags(tags)) => tags.clone(),
+        Some(MetadataValue::Text(tag)) => vec![tag.clone()],
+        _ => Vec::new(),
+    };
+    let tag_count = tags.len();
+    for tag in inline_tags(&item.content) {
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+            tags.push(tag);
+        }
+    }
+    let tags_changed = tags.len() != tag_count;
+    if tags_changed {
+        metadata.add("tags", MetadataValue::tags(tags));
+    }
+
+    if !metadata.contains("date") {
+        if let Some(date) = daily.date_of(&item.uri) {
+            let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+            metadata.add("date", MetadataValue::datetime(midnight));
+        }
+    }
+
+    if tags_changed || metadata.fields.len() != original_len {
+        if let Ok(json) = metadata.to_json() {
+            item.metadata.insert(FRONTMATTER_KEY.to_string(), json);
+        }
+    }
+    item
+}
+
+/// Inline `#tags` outside code, with nested tags also counting for their
+/// parents (`#project/alpha` adds `project`)
+///
+/// Purely numeric `#123` is not a tag in Obsidian and is skipped.
+fn inline_tags(content: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    let mut fence: Option<&str> = None;
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if let Some(open) = fence {
+            if trimmed.starts_with(open) {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            fence = Some(marker);
+            continue;
+        }
+
+        let prose: String = line.split('`').step_by(2).collect::<Vec<_>>().join(" ");
+        for cap in INLINE_TAG_RE.captures_iter(&prose) {
+            let tag = cap[1].trim_matches('/');
+            if tag.is_empty() || tag.chars().all(|c| c.is_ascii_digit() || c == '/') {
+                continue;
+            }
+            let mut parts = Vec::new();
+            for part in tag.split('/').filter(|p| !p.is_empty()) {
+                parts.push(part);
+                let name = parts.join("/");
+                if !tags.iter().any(|t| t.eq_ignore_ascii_case(&name)) {
+                    tags.push(name);
+                }
+            }
+        }
+    }
+    tags
+}
+
+/// Where daily notes live and how they are named
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DailyNotes {
+    /// Folder relative to the vault root; empty for the root
+    folder: String,
+    /// chrono format equivalent to the vault's moment.js format, or None
+    /// when the format uses tokens that cannot be parsed back
+    format: Option<String>,
+}
+
+impl Default for DailyNotes {
+    fn default() -> Self {
+        Self {
+            folder: String::new(),
+            format: moment_to_chrono(DEFAULT_DAILY_FORMAT),
+        }
+    }
+}
+
+impl DailyNotes {
+    /// Read the daily notes plugin settings of a vault, falling back to
+    /// Obsidian's defaults
+    fn load(root: &Path) -> Self {
+        let settings: serde_json::Value = std::fs::read_to_string(root.join(DAILY_NOTES_SETTINGS))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        let text = |key: &str| {
+            settings
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+        };
+        Self {
+            folder: text("folder").unwrap_or("").trim_matches('/').to_string(),
+            format: moment_to_chrono(text("format").unwrap_or(DEFAULT_DAILY_FORMAT)),
+        }
+    }
+
+    /// Day a note is for, when its path in the daily folder fits the format
+    fn date_of(&self, uri: &str) -> Option<NaiveDate> {
+        let format = self.format.as_deref()?;
+        let relative = if self.folder.is_empty() {
+            uri
+        } else {
+            uri.strip_prefix(&self.folder)?.strip_prefix('/')?
+        };
+        let name = relative.strip_suffix(".md")?;
+        NaiveDate::parse_from_str(name, format).ok()
+    }
+}
+
+/// Translate a moment.js date format into a chrono one
+///
+/// Covers the year, month and day tokens daily note formats use, plus
+/// `[escaped]` text. Returns None for other tokens.
+fn moment_to_chrono(format: &str) -> Option<String> {
+    const TOKENS: &[(&str, &str)] = &[
+        ("YYYY", "%Y"),
+        ("YY", "%y"),
+        ("MMMM", "%B"),
+        ("MMM", "%b"),
+        ("MM", "%m"),
+        ("M", "%m"),
+        ("DD", "%d"),
+        ("D", "%d"),
+        ("dddd", "%A"),
+        ("ddd", "%a"),
+    ];
+
+    let mut out = String::new();
+    let mut rest = format;
+    while let Some(c) = rest.chars().next() {
+        if c == '[' {
+            let end = rest.find(']')?;
+            out.push_str(&rest[1..end].replace('%', "%%"));
+            rest = &rest[end + 1..];
+        } else if let Some((token, spec)) = TOKENS.iter().find(|(t, _)| rest.starts_with(t)) {
+            out.push_str(spec);
+            rest = &rest[token.len()..];
+        } else if c.is_ascii_alphabetic() {
+            return None;
+        } else {
+            if c == '%' {
+                out.push('%');
+            }
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn tags_of(item: &SourceItem) -> Vec<String> {
+        let metadata = UserMetadata::from_json(&item.metadata[FRONTMATTER_KEY]).unwrap();
+        match metadata.get("tags") {
+            Some(MetadataValue::Tags(tags)) => tags.clone(),
+            other => panic!("unexpected tags {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_inline_tags_skip_code_headings_and_numbers() {
+        let content = "# Heading\n\nWorking on #project/alpha and #Idea.\n\
+                       Issue #123 and a url http://x.org/#frag\n\
+                       `#not-inline` but #real\n```\n#in-fence\n```\n#idea again";
+        assert_eq!(
+            inline_tags(content),
+            vec!["project", "project/alpha", "Idea", "real"]
+        );
+    }
+
+    #[test]
+    fn test_moment_formats() {
+        assert_eq!(moment_to_chrono("YYYY-MM-DD").unwrap(), "%Y-%m-%d");
+        assert_eq!(
+            moment_to_chrono("YYYY/MM/[Week] DD dddd").unwrap(),
+            "%Y/%m/Week %d %A"
+        );
+        assert_eq!(moment_to_chrono("gggg-[W]ww"), None);
+
+        let daily = DailyNotes {
+            folder: "Journal".to_string(),
+            format: moment_to_chrono("DD.MM.YYYY"),
+        };
+        assert_eq!(
+            daily.date_of("Journal/05.03.2024.md"),
+            NaiveDate::from_ymd_opt(2024, 3, 5)
+        );
+        assert_eq!(daily.date_of("Other/05.03.2024.md"), None);
+        assert_eq!(daily.date_of("Journal/Meeting.md"), None);
+    }
+
+    #[tokio::test]
+    async fn test_scan_merges_tags_and_dates_daily_notes() {
+        let vault = TempDir::new().unwrap();
+        let root = vault.path();
+        fs::create_dir_all(root.join(".obsidian")).unwrap();
+        fs::create_dir_all(root.join("Daily")).unwrap();
+        fs::write(
+            root.join(".obsidian/daily-notes.json"),
+            r#"{"folder": "Daily", "format": "DD-MM-YYYY"}"#,
+        )
+        .unwrap();
+        fs::write(
+            root.join("Plan.md"),
+            "---\ntags: [work]\naliases: [Roadmap]\n---\n# Plan\n\nSee [[Ideas]] #Work #q3",
+        )
+        .unwrap();
+        fs::write(
+            root.join("Daily/14-10-2026.md"),
+            "Met about the [[Roadmap]]",
+        )
+        .unwrap();
+
+        let config = ProviderConfig::new(root.to_string_lossy().to_string(), "**/*.md".into());
+        let mut items = ObsidianProvider::new().scan(&config).await.unwrap().items;
+        items.sort_by(|a, b| a.uri.cmp(&b.uri));
+        assert_eq!(items.len(), 2);
+
+        let (daily, plan) = (&items[0], &items[1]);
+        assert_eq!(plan.source_type, "obsidian");
+        assert!(plan.content.starts_with("# Plan"));
+        assert_eq!(tags_of(plan), vec!["work", "q3"]);
+
+        let metadata = UserMetadata::from_json(&daily.metadata[FRONTMATTER_KEY]).unwrap();
+        match metadata.get("date") {
+            Some(MetadataValue::DateTime(date)) => assert!(date.starts_with("2026-10-14")),
+            other => panic!("unexpected date {:?}", other),
+        }
+
+        // Extracting again at indexing time leaves the merged metadata alone
+        let again = plan.clone().with_frontmatter_extracted();
+        assert_eq!(tags_of(&again), vec!["work", "q3"]);
+    }
+}
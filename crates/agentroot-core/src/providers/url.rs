@@ -2,11 +2,77 @@
 
 use crate::db::hash_content;
 use crate::error::{AgentRootError, Result};
-use crate::providers::{ProviderConfig, SourceItem, SourceProvider};
+use crate::providers::{ProviderConfig, SourceItem, SourceProvider, CANONICAL_URL_KEY};
 use async_trait::async_trait;
-use reqwest::{Client, StatusCode};
+use regex::Regex;
+use reqwest::{Client, StatusCode, Url};
+use std::sync::OnceLock;
 use std::time::Duration;
 
+/// Query parameters that only track where a visitor came from
+const TRACKING_PARAMS: &[&str] = &[
+    "fbclid", "gclid", "dclid", "gbraid", "wbraid", "msclkid", "yclid", "mc_cid", "mc_eid",
+    "igshid", "_ga", "_gl", "ref_src",
+];
+
+fn is_tracking_param(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.starts_with("utm_") || TRACKING_PARAMS.contains(&name.as_str())
+}
+
+/// Normalize a URL so equivalent addresses compare equal
+///
+/// Drops the fragment, tracking parameters and a trailing slash, and sorts
+/// the remaining query parameters. Scheme, host and default ports are
+/// normalized by parsing. Returns None for anything but http(s) URLs.
+pub fn normalize_url(url: &str) -> Option<String> {
+    let mut url = Url::parse(url.trim()).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    url.set_fragment(None);
+
+    let mut params: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(name, _)| !is_tracking_param(name))
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+    params.sort();
+    if params.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(params);
+    }
+
+    if url.path().len() > 1 && url.path().ends_with('/') {
+        let path = url.path().trim_end_matches('/').to_string();
+        url.set_path(&path);
+    }
+    Some(url.to_string())
+}
+
+/// The page's `<link rel="canonical">` target, resolved against its URL
+pub fn canonical_link(html: &str, page_url: &Url) -> Option<Url> {
+    static LINK: OnceLock<Regex> = OnceLock::new();
+    static REL: OnceLock<Regex> = OnceLock::new();
+    static HREF: OnceLock<Regex> = OnceLock::new();
+    let link = LINK.get_or_init(|| Regex::new(r"(?is)<link\b[^>]*>").unwrap());
+    let rel = REL.get_or_init(|| Regex::new(r#"(?i)\brel\s*=\s*["']?([^"'>]+)"#).unwrap());
+    let href = HREF.get_or_init(|| Regex::new(r#"(?i)\bhref\s*=\s*["']?([^"'\s>]+)"#).unwrap());
+
+    link.find_iter(html).find_map(|tag| {
+        let tag = tag.as_str();
+        let rels = rel.captures(tag)?;
+        if !rels[1]
+            .split_whitespace()
+            .any(|r| r.eq_ignore_ascii_case("canonical"))
+        {
+            return None;
+        }
+        page_url.join(href.captures(tag)?[1].trim()).ok()
+    })
+}
+
 /// Provider for fetching content from URLs
 pub struct URLProvider {
     client: Client,
@@ -36,7 +102,9 @@ impl URLProvider {
     }
 
     /// Fetch content from a URL with proper error handling
-    async fn fetch_url(&self, url: &str) -> Result<String> {
+    ///
+    /// Returns the body and the URL it was served from after redirects.
+    async fn fetch_url(&self, url: &str) -> Result<(String, Url)> {
         let response = self.client.get(url).send().await.map_err(|e| {
             if e.is_timeout() {
                 AgentRootError::ExternalError(format!(
@@ -88,12 +156,14 @@ impl URLProvider {
             return Err(AgentRootError::ExternalError(error_msg));
         }
 
-        response.text().await.map_err(|e| {
+        let final_url = response.url().clone();
+        let body = response.text().await.map_err(|e| {
             AgentRootError::ExternalError(format!(
                 "Failed to read response body from {}: {}",
                 url, e
             ))
-        })
+        })?;
+        Ok((body, final_url))
     }
 
     /// Extract title from content (looks for markdown # header or HTML title)
@@ -110,8 +180,7 @@ impl URLProvider {
         }
 
         url.split('/')
-            .filter(|s| !s.is_empty())
-            .next_back()
+            .rfind(|s| !s.is_empty())
             .map(|s| s.to_string())
             .unwrap_or_else(|| "Untitled".to_string())
     }
@@ -129,12 +198,17 @@ impl SourceProvider for URLProvider {
     }
 
     async fn fetch_item(&self, uri: &str) -> Result<SourceItem> {
-        let content = self.fetch_url(uri).await?;
+        let (content, final_url) = self.fetch_url(uri).await?;
         let title = self.extract_title(&content, uri);
         let hash = hash_content(&content);
+        let canonical = canonical_link(&content, &final_url).unwrap_or(final_url);
 
         let mut item = SourceItem::new(uri.to_string(), title, content, hash, "url".to_string());
         item.metadata.insert("url".to_string(), uri.to_string());
+        if let Some(canonical) = normalize_url(canonical.as_str()) {
+            item.metadata
+                .insert(CANONICAL_URL_KEY.to_string(), canonical);
+        }
 
         Ok(item)
     }
@@ -182,6 +256,35 @@ mod tests {
         assert_eq!(title, "example.com");
     }
 
+    #[test]
+    fn test_normalize_url() {
+        assert_eq!(
+            normalize_url("HTTPS://Example.com:443/Docs/?utm_source=x&b=2&a=1&fbclid=y#intro")
+                .unwrap(),
+            "https://example.com/Docs?a=1&b=2"
+        );
+        assert_eq!(
+            normalize_url("http://example.com/?utm_medium=email").unwrap(),
+            "http://example.com/"
+        );
+        assert!(normalize_url("file:///tmp/a.html").is_none());
+        assert!(normalize_url("not a url").is_none());
+    }
+
+    #[test]
+    fn test_canonical_link() {
+        let page = Url::parse("https://example.com/blog/post?ref=feed").unwrap();
+        let html = r#"<head>
+            <link rel="stylesheet" href="/style.css">
+            <LINK href="/blog/post/" REL="canonical">
+        </head>"#;
+        assert_eq!(
+            canonical_link(html, &page).unwrap().as_str(),
+            "https://example.com/blog/post/"
+        );
+        assert!(canonical_link("<link rel=icon href=/a.ico>", &page).is_none());
+    }
+
     #[tokio::test]
     async fn test_fetch_invalid_url() {
         let provider = URLProvider::new();
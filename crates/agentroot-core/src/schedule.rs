@@ -0,0 +1,356 @@
+//! Scheduled collection updates
+//!
+//! Collections with a `schedule` in the config file are reindexed on a
+//! cron-like timetable. The scheduler only queues `Reindex` jobs; the job
+//! worker runs them with its usual retries, and a job that finally fails
+//! triggers the collection's `on_failure` command.
+//!
+//! Expressions have five fields (minute, hour, day of month, month, day of
+//! week) evaluated in local time, supporting `*`, lists, ranges and steps,
+//! or one of `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly`.
+
+use crate::config::Config;
+use crate::db::{Database, JobKind, JobStatus};
+use crate::error::{AgentRootError, Result};
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, Timelike};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// How often the scheduler checks for due collections and finished jobs
+pub const SCHEDULER_TICK: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// Upper bound on the random delay added to each run
+const MAX_JITTER_SECS: i64 = 300;
+
+/// How far ahead to look for the next matching minute
+const MAX_LOOKAHEAD_DAYS: i64 = 5 * 366;
+
+/// A parsed cron expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Whether the day-of-month and day-of-week fields were `*`
+    any_day: bool,
+    any_weekday: bool,
+}
+
+fn parse_field(field: &str, min: u32, max: u32) -> Option<u64> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().ok().filter(|s| *s > 0)?),
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((a, b)) => (a.parse().ok()?, b.parse().ok()?),
+                None => {
+                    let value: u32 = range.parse().ok()?;
+                    // "5/10" means from 5 to the end in steps of 10
+                    (value, if part.contains('/') { max } else { value })
+                }
+            },
+        };
+        if start < min || end > max || start > end {
+            return None;
+        }
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Some(mask)
+}
+
+impl Schedule {
+    /// Parse a five-field cron expression or an `@` shorthand
+    pub fn parse(expr: &str) -> Result<Self> {
+        let expanded = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let invalid = || AgentRootError::Config(format!("Invalid schedule: {}", expr));
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(invalid());
+        };
+
+        let mut weekdays = parse_field(weekday, 0, 7).ok_or_else(invalid)?;
+        // 7 is Sunday too
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59).ok_or_else(invalid)?,
+            hours: parse_field(hour, 0, 23).ok_or_else(invalid)?,
+            days: parse_field(day, 1, 31).ok_or_else(invalid)?,
+            months: parse_field(month, 1, 12).ok_or_else(invalid)?,
+            weekdays,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        // Like cron: when both are restricted, either one matching is enough
+        match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+
+    /// The first matching minute strictly after `after`
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut t = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = after + Duration::days(MAX_LOOKAHEAD_DAYS);
+
+        while t <= limit {
+            if self.months & (1 << t.month()) == 0 {
+                let (year, month) = if t.month() == 12 {
+                    (t.year() + 1, 1)
+                } else {
+                    (t.year(), t.month() + 1)
+                };
+                t = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+            } else if !self.matches_day(t.date()) {
+                t = (t.date() + Duration::days(1)).and_hms_opt(0, 0, 0)?;
+            } else if self.hours & (1 << t.hour()) == 0 {
+                t = t.with_minute(0)? + Duration::hours(1);
+            } else if self.minutes & (1 << t.minute()) == 0 {
+                t += Duration::minutes(1);
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+}
+
+/// A collection with an update schedule
+#[derive(Debug, Clone)]
+pub struct ScheduledCollection {
+    pub collection: String,
+    pub schedule: Schedule,
+    /// Shell command run when a scheduled update fails for good
+    pub on_failure: Option<String>,
+}
+
+impl ScheduledCollection {
+    /// Next run after `now`, delayed by up to a tenth of the interval
+    ///
+    /// The jitter (capped at five minutes) keeps collections sharing a
+    /// schedule, or several machines sharing a config, from updating at
+    /// the same instant.
+    pub fn next_run(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let next = self.schedule.next_after(now)?;
+        let interval = self
+            .schedule
+            .next_after(next)
+            .map(|after| (after - next).num_seconds())
+            .unwrap_or(0);
+        let max_jitter = (interval / 10).min(MAX_JITTER_SECS);
+        let jitter = if max_jitter > 0 {
+            fastrand::i64(0..=max_jitter)
+        } else {
+            0
+        };
+        Some(next + Duration::seconds(jitter))
+    }
+}
+
+/// Collections in the config file that have a schedule
+pub fn scheduled_collections(config: &Config) -> Result<Vec<ScheduledCollection>> {
+    let mut scheduled: Vec<ScheduledCollection> = config
+        .collections
+        .iter()
+        .filter_map(|(name, c)| c.schedule.as_ref().map(|expr| (name, c, expr)))
+        .map(|(name, c, expr)| {
+            Ok(ScheduledCollection {
+                collection: name.clone(),
+                schedule: Schedule::parse(expr)?,
+                on_failure: c.on_failure.clone(),
+            })
+        })
+        .collect::<Result<_>>()?;
+    scheduled.sort_by(|a, b| a.collection.cmp(&b.collection));
+    Ok(scheduled)
+}
+
+/// Report a scheduled update that failed after its retries
+fn notify_failure(scheduled: &ScheduledCollection, error: &str) {
+    tracing::warn!(
+        "Scheduled update of {} failed: {}",
+        scheduled.collection,
+        error
+    );
+    let Some(command) = &scheduled.on_failure else {
+        return;
+    };
+    let mut child = std::process::Command::new("sh");
+    child
+        .arg("-c")
+        .arg(command)
+        .env("AGENTROOT_COLLECTION", &scheduled.collection)
+        .env("AGENTROOT_ERROR", error);
+    let collection = scheduled.collection.clone();
+    // Waited on off the scheduler thread, so a slow hook cannot stall it
+    std::thread::spawn(move || {
+        if let Err(e) = child.status() {
+            tracing::warn!("Failure notification for {} not sent: {}", collection, e);
+        }
+    });
+}
+
+/// Start a thread that queues scheduled updates for the life of the process
+///
+/// Jobs are only queued; a job worker has to be running to execute them.
+pub fn spawn_scheduler(
+    db_path: PathBuf,
+    scheduled: Vec<ScheduledCollection>,
+) -> std::io::Result<std::thread::JoinHandle<()>> {
+    std::thread::Builder::new()
+        .name("agentroot-scheduler".to_string())
+        .spawn(move || {
+            let runtime = match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    tracing::warn!("Update scheduler disabled: {}", e);
+                    return;
+                }
+            };
+            runtime.block_on(scheduler_loop(db_path, scheduled));
+        })
+}
+
+async fn scheduler_loop(db_path: PathBuf, scheduled: Vec<ScheduledCollection>) {
+    let db = match Database::open(&db_path).and_then(|db| db.initialize().map(|_| db)) {
+        Ok(db) => db,
+        Err(e) => {
+            tracing::warn!("Update scheduler disabled: {}", e);
+            return;
+        }
+    };
+
+    let now = Local::now().naive_local();
+    let mut next: Vec<Option<NaiveDateTime>> = scheduled.iter().map(|s| s.next_run(now)).collect();
+    // Job id -> index of the collection it updates
+    let mut pending: HashMap<i64, usize> = HashMap::new();
+
+    loop {
+        let now = Local::now().naive_local();
+        for (i, s) in scheduled.iter().enumerate() {
+            if next[i].is_none_or(|due| due > now) {
+                continue;
+            }
+            next[i] = s.next_run(now);
+            // A run still in progress covers this one
+            if pending.values().any(|&p| p == i) {
+                continue;
+            }
+            let kind = JobKind::Reindex {
+                collection: Some(s.collection.clone()),
+            };
+            match db.enqueue_job(&kind, 0) {
+                Ok(id) => {
+                    tracing::info!("Queued scheduled update of {} (job {})", s.collection, id);
+                    pending.insert(id, i);
+                }
+                Err(e) => notify_failure(s, &e.to_string()),
+            }
+        }
+
+        pending.retain(|&id, &mut i| match db.get_job(id) {
+            Ok(Some(job)) => match job.status {
+                JobStatus::Queued | JobStatus::Running => true,
+                JobStatus::Failed => {
+                    notify_failure(
+                        &scheduled[i],
+                        job.last_error.as_deref().unwrap_or("unknown error"),
+                    );
+                    false
+                }
+                JobStatus::Succeeded | JobStatus::Cancelled => false,
+            },
+            Ok(None) => false,
+            Err(e) => {
+                tracing::warn!("Update scheduler error: {}", e);
+                true
+            }
+        });
+
+        tokio::time::sleep(SCHEDULER_TICK).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn next(expr: &str, after: &str) -> String {
+        Schedule::parse(expr)
+            .unwrap()
+            .next_after(at(after))
+            .unwrap()
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    }
+
+    #[test]
+    fn test_next_after() {
+        assert_eq!(next("*/15 * * * *", "2024-06-12 10:07"), "2024-06-12 10:15");
+        assert_eq!(next("0 3 * * *", "2024-06-12 03:00"), "2024-06-13 03:00");
+        assert_eq!(next("@hourly", "2024-12-31 23:30"), "2025-01-01 00:00");
+        assert_eq!(next("30 9 * * 1-5", "2024-06-14 10:00"), "2024-06-17 09:30");
+        // 7 is Sunday
+        assert_eq!(next("0 0 * * 7", "2024-06-12 00:00"), "2024-06-16 00:00");
+        assert_eq!(next("0 0 29 2 *", "2024-03-01 00:00"), "2028-02-29 00:00");
+        // Day of month or day of week, like cron
+        assert_eq!(next("0 12 1 * 1", "2024-06-12 00:00"), "2024-06-17 12:00");
+        assert_eq!(next("0 6,18 * * *", "2024-06-12 07:00"), "2024-06-12 18:00");
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid() {
+        for expr in [
+            "",
+            "* * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+        ] {
+            assert!(Schedule::parse(expr).is_err(), "{:?}", expr);
+        }
+    }
+
+    #[test]
+    fn test_next_run_jitter() {
+        let scheduled = ScheduledCollection {
+            collection: "notes".to_string(),
+            schedule: Schedule::parse("@hourly").unwrap(),
+            on_failure: None,
+        };
+        for _ in 0..50 {
+            let run = scheduled.next_run(at("2024-06-12 10:07")).unwrap();
+            let delay = (run - at("2024-06-12 11:00")).num_seconds();
+            assert!((0..=300).contains(&delay), "{}", delay);
+        }
+    }
+}
@@ -1,22 +1,54 @@
 //! HNSW approximate nearest neighbor index for vector search
+//!
+//! `agentroot embed --optimize` builds the index over every embedding and
+//! saves it next to the database as `<db>.ann`. Unfiltered vector searches
+//! then load it instead of scanning all vectors, for as long as the
+//! embeddings it was built from are unchanged.
 
 use crate::db::vectors::cosine_similarity;
-use crate::db::Database;
-use crate::error::Result;
+use crate::db::{vector_memory_limit_bytes, Database};
+use crate::error::{AgentRootError, Result};
 use instant_distance::{Builder, HnswMap, Search};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::RwLock;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
 
 /// Minimum embedding count to justify building an ANN index.
 /// Below this threshold, brute-force is fast enough.
 const ANN_THRESHOLD: usize = 1000;
 
+/// Version of the saved index format
+const ANN_FILE_VERSION: u32 = 1;
+
+/// Saved indexes loaded by this process, by file
+static LOADED: OnceLock<Mutex<HashMap<PathBuf, Arc<AnnIndex>>>> = OnceLock::new();
+
 /// Wrapper for f32 vectors implementing instant_distance::Point
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 struct EmbeddingPoint {
     values: Vec<f32>,
 }
 
+/// Count and highest row id of the stored embeddings
+///
+/// Embedding writes replace rows, so any change moves one of the two.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct EmbeddingFingerprint {
+    count: i64,
+    max_rowid: i64,
+}
+
+/// Header of a saved index, read before the (large) graph
+#[derive(Serialize, Deserialize)]
+struct FileHeader {
+    version: u32,
+    fingerprint: EmbeddingFingerprint,
+    embedding_count: usize,
+}
+
 impl instant_distance::Point for EmbeddingPoint {
     fn distance(&self, other: &Self) -> f32 {
         // Cosine distance = 1.0 - cosine_similarity
@@ -28,6 +60,8 @@ impl instant_distance::Point for EmbeddingPoint {
 pub struct AnnIndex {
     index: RwLock<Option<HnswMap<EmbeddingPoint, String>>>,
     embedding_count: AtomicUsize,
+    /// Embeddings the index was built from, when built from the whole database
+    fingerprint: Option<EmbeddingFingerprint>,
 }
 
 impl AnnIndex {
@@ -35,19 +69,43 @@ impl AnnIndex {
         Self {
             index: RwLock::new(None),
             embedding_count: AtomicUsize::new(0),
+            fingerprint: None,
         }
     }
 
     /// Build index from database embeddings.
-    /// Skips building if fewer than ANN_THRESHOLD embeddings.
+    /// Skips building if fewer than ANN_THRESHOLD embeddings, or if the index
+    /// would not fit in the vector memory ceiling.
     pub fn build_from_db(db: &Database, collection: Option<&str>) -> Result<Self> {
+        // HNSW keeps every vector plus its graph, roughly twice the raw size
+        let (stored, bytes) = db.embedding_footprint(collection)?;
+        let limit = vector_memory_limit_bytes();
+        if bytes.saturating_mul(2) > limit {
+            tracing::info!(
+                "Skipping ANN index build: {} embeddings need ~{} MB, limit is {} MB",
+                stored,
+                bytes * 2 / (1024 * 1024),
+                limit / (1024 * 1024)
+            );
+            let ann = Self::new();
+            ann.embedding_count.store(stored, Ordering::Relaxed);
+            return Ok(ann);
+        }
+
+        let fingerprint = match collection {
+            Some(_) => None,
+            None => Some(db.embedding_fingerprint()?),
+        };
         let embeddings = match collection {
             Some(c) => db.get_embeddings_for_collection(c)?,
             None => db.get_all_embeddings()?,
         };
 
         let count = embeddings.len();
-        let ann = Self::new();
+        let ann = Self {
+            fingerprint,
+            ..Self::new()
+        };
         ann.embedding_count.store(count, Ordering::Relaxed);
 
         if count < ANN_THRESHOLD {
@@ -66,9 +124,10 @@ impl AnnIndex {
 
         let hnsw_map = Builder::default().build(points, keys);
 
-        *ann.index.write().map_err(|e| {
-            crate::error::AgentRootError::Search(format!("ANN lock poisoned: {}", e))
-        })? = Some(hnsw_map);
+        *ann.index
+            .write()
+            .map_err(|e| AgentRootError::Search(format!("ANN lock poisoned: {}", e)))? =
+            Some(hnsw_map);
 
         tracing::info!("Built ANN index with {} embeddings", count);
         Ok(ann)
@@ -115,6 +174,133 @@ impl AnnIndex {
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// Write a built index of the whole database to `path`
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let guard = self
+            .index
+            .read()
+            .map_err(|e| AgentRootError::Search(format!("ANN lock poisoned: {}", e)))?;
+        let (Some(map), Some(fingerprint)) = (guard.as_ref(), self.fingerprint) else {
+            return Err(AgentRootError::Search(
+                "Only an ANN index built over all embeddings can be saved".to_string(),
+            ));
+        };
+
+        // Written beside the target and renamed, so readers never see half a file
+        let tmp = path.with_extension("ann.tmp");
+        let mut writer = BufWriter::new(std::fs::File::create(&tmp)?);
+        let header = FileHeader {
+            version: ANN_FILE_VERSION,
+            fingerprint,
+            embedding_count: self.len(),
+        };
+        bincode::serialize_into(&mut writer, &header).map_err(encoding_error)?;
+        bincode::serialize_into(&mut writer, map).map_err(encoding_error)?;
+        writer
+            .into_inner()
+            .map_err(|e| e.into_error())?
+            .sync_all()?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Read an index saved by [`AnnIndex::save`]
+    ///
+    /// `None` when the file is of another format version or was built from
+    /// other embeddings than `current`.
+    fn load(path: &Path, current: EmbeddingFingerprint) -> Result<Option<Self>> {
+        let mut reader = BufReader::new(std::fs::File::open(path)?);
+        let header: FileHeader = bincode::deserialize_from(&mut reader).map_err(encoding_error)?;
+        if header.version != ANN_FILE_VERSION || header.fingerprint != current {
+            return Ok(None);
+        }
+        let map: HnswMap<EmbeddingPoint, String> =
+            bincode::deserialize_from(&mut reader).map_err(encoding_error)?;
+        Ok(Some(Self {
+            index: RwLock::new(Some(map)),
+            embedding_count: AtomicUsize::new(header.embedding_count),
+            fingerprint: Some(header.fingerprint),
+        }))
+    }
+}
+
+fn encoding_error(e: bincode::Error) -> AgentRootError {
+    AgentRootError::Search(format!("Invalid ANN index file: {}", e))
+}
+
+impl Database {
+    /// Where this database's ANN index is saved; `None` in memory
+    pub fn ann_index_path(&self) -> Option<PathBuf> {
+        self.conn
+            .path()
+            .filter(|p| !p.is_empty())
+            .map(|p| PathBuf::from(format!("{}.ann", p)))
+    }
+
+    /// Build the ANN index over all embeddings and save it
+    ///
+    /// Returns how many embeddings it covers, or `None` when no index was
+    /// built: too few embeddings, over the vector memory ceiling, or an
+    /// in-memory database. A saved index is then removed.
+    pub fn optimize_vector_index(&self) -> Result<Option<usize>> {
+        let Some(path) = self.ann_index_path() else {
+            return Ok(None);
+        };
+        let ann = AnnIndex::build_from_db(self, None)?;
+        if !ann.is_built() {
+            if path.exists() {
+                std::fs::remove_file(&path)?;
+            }
+            return Ok(None);
+        }
+        ann.save(&path)?;
+        Ok(Some(ann.len()))
+    }
+
+    /// The saved ANN index, while it matches the stored embeddings
+    pub(crate) fn saved_ann_index(&self) -> Result<Option<Arc<AnnIndex>>> {
+        let Some(path) = self.ann_index_path().filter(|p| p.exists()) else {
+            return Ok(None);
+        };
+        let current = self.embedding_fingerprint()?;
+
+        let mut loaded = LOADED
+            .get_or_init(Default::default)
+            .lock()
+            .map_err(|e| AgentRootError::Search(format!("ANN lock poisoned: {}", e)))?;
+        if let Some(ann) = loaded.get(&path).filter(|a| a.fingerprint == Some(current)) {
+            return Ok(Some(ann.clone()));
+        }
+        loaded.remove(&path);
+
+        // A stale or unreadable index only costs the speed-up
+        match AnnIndex::load(&path, current) {
+            Ok(Some(ann)) => {
+                let ann = Arc::new(ann);
+                loaded.insert(path, ann.clone());
+                Ok(Some(ann))
+            }
+            Ok(None) => Ok(None),
+            Err(e) => {
+                tracing::warn!("Ignoring ANN index {}: {}", path.display(), e);
+                Ok(None)
+            }
+        }
+    }
+
+    fn embedding_fingerprint(&self) -> Result<EmbeddingFingerprint> {
+        Ok(self.conn.query_row(
+            "SELECT COUNT(*), COALESCE(MAX(rowid), 0) FROM embeddings",
+            [],
+            |row| {
+                Ok(EmbeddingFingerprint {
+                    count: row.get(0)?,
+                    max_rowid: row.get(1)?,
+                })
+            },
+        )?)
+    }
 }
 
 impl Default for AnnIndex {
@@ -209,4 +395,33 @@ mod tests {
         assert!(!ann.is_built());
         assert!(ann.is_empty());
     }
+
+    #[test]
+    fn test_optimize_saves_index_until_embeddings_change() {
+        let (db, _temp) = setup_db_with_embeddings(ANN_THRESHOLD + 10);
+        assert_eq!(
+            db.optimize_vector_index().unwrap(),
+            Some(ANN_THRESHOLD + 10)
+        );
+        assert!(db.ann_index_path().unwrap().exists());
+
+        let saved = db.saved_ann_index().unwrap().unwrap();
+        assert!(saved.is_built());
+        assert_eq!(saved.search(&[1.0, 0.0, 0.5, 0.5], 3).len(), 3);
+
+        db.insert_embedding("newhash", 0, 0, "test-model", &[0.1, 0.2, 0.3, 0.4])
+            .unwrap();
+        assert!(db.saved_ann_index().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_optimize_below_threshold_removes_saved_index() {
+        let (db, _temp) = setup_db_with_embeddings(10);
+        let path = db.ann_index_path().unwrap();
+        std::fs::write(&path, b"stale").unwrap();
+
+        assert_eq!(db.optimize_vector_index().unwrap(), None);
+        assert!(!path.exists());
+        assert!(db.saved_ann_index().unwrap().is_none());
+    }
 }
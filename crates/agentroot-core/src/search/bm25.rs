@@ -1,22 +1,111 @@
 //! BM25 full-text search via FTS5
 
-use super::{extract_snippet, parse_metadata_filters, SearchOptions, SearchResult, SearchSource};
+use super::slow_log::{log_if_slow, QueryTimer};
+use super::{
+    author_filter_sql, clean_query_text, extract_snippet, importance_boost_sql,
+    split_metadata_filters, tag_filter_sql, take_page, FtsQuery, SearchOptions, SearchResult,
+    SearchSource,
+};
 use crate::db::{docid_from_hash, Database};
 use crate::error::Result;
+use crate::index::normalize_text;
+use std::collections::HashMap;
+
+/// Corrections a misspelled query word may also match
+const MAX_TYPO_CORRECTIONS: usize = 3;
 
 impl Database {
+    /// Parse a query, widening misspelled words in a fuzzy search
+    fn fts_query(&self, query: &str, options: &SearchOptions) -> Result<FtsQuery> {
+        let parsed = FtsQuery::parse(query)?;
+        if !options.fuzzy {
+            return Ok(parsed);
+        }
+        let mut corrections = HashMap::new();
+        for word in parsed.words() {
+            let found =
+                self.typo_corrections(&word, options.collection.as_deref(), MAX_TYPO_CORRECTIONS)?;
+            if !found.is_empty() {
+                corrections.insert(word, found);
+            }
+        }
+        Ok(parsed.with_alternatives(&corrections))
+    }
+
     /// Perform BM25 full-text search
     pub fn search_fts(&self, query: &str, options: &SearchOptions) -> Result<Vec<SearchResult>> {
-        // Parse metadata filters from query or use provided filters
-        let (clean_query, mut filters) = parse_metadata_filters(query);
+        let mut timer = QueryTimer::new();
+        let results = self.fts_search(query, &options.page_window(), &mut timer)?;
+        let results = take_page(results, options);
+        log_if_slow(self, "bm25", query, options, &timer, results.len());
+        Ok(results)
+    }
 
-        // Preprocess query for FTS5 compatibility (handle :: and other special chars)
-        let clean_query = preprocess_fts_query(&clean_query);
+    /// BM25 search that records stage timings on a caller-owned timer
+    #[tracing::instrument(name = "bm25_search", skip_all, fields(query = %query))]
+    pub(crate) fn fts_search(
+        &self,
+        query: &str,
+        options: &SearchOptions,
+        timer: &mut QueryTimer,
+    ) -> Result<Vec<SearchResult>> {
+        // Parse metadata filters from query or use provided filters
+        let (raw_query, mut filters) = split_metadata_filters(&normalize_text(query));
+        let feedback_query = clean_query_text(&raw_query);
+
+        // Translate the boolean query language to FTS5 syntax
+        let fts_query = self.fts_query(&raw_query, options)?;
+        let match_expr = fts_query.to_match();
+        if match_expr.is_empty() {
+            return Ok(Vec::new());
+        }
+        let snippet_terms = fts_query.keywords();
 
         // Merge with filters from options (options take precedence)
         filters.extend(options.metadata_filters.clone());
 
-        let mut sql = String::from(
+        // Exact queries match unstemmed tokens of the document's own text
+        let weights = if options.exact {
+            "1.0,   -- filepath
+                    10.0,  -- title
+                    5.0    -- body"
+        } else {
+            "1.0,   -- filepath
+                    10.0,  -- title
+                    5.0,   -- body
+                    8.0,   -- llm_summary (high weight)
+                    10.0,  -- llm_title (high weight)
+                    15.0,  -- llm_keywords (very high weight)
+                    7.0,   -- llm_intent (high weight)
+                    12.0,  -- llm_concepts (very high weight)
+                    20.0,  -- user_metadata (highest weight)
+                    0.1    -- modified_at (very low)"
+        };
+
+        // Each collection has its own tables; scoped searches read only theirs
+        let tables = self.fts_partitions(
+            options.exact,
+            options.collection.as_deref(),
+            options.provider.as_deref(),
+        )?;
+        if tables.is_empty() {
+            return Ok(Vec::new());
+        }
+        let matches = tables
+            .iter()
+            .map(|table| {
+                format!(
+                    "SELECT rowid, bm25({table},
+                    {weights}
+                ) AS rank FROM {table} WHERE {table} MATCH ?1"
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" UNION ALL ");
+
+        // Scores scale with PageRank importance, its weight bound as ?2
+        let importance = importance_boost_sql(2);
+        let mut sql = format!(
             r#"
             SELECT
                 'agentroot://' || d.collection || '/' || d.path as filepath,
@@ -27,45 +116,24 @@ impl Database {
                 d.modified_at,
                 c.doc,
                 LENGTH(c.doc),
-                (1.0 / (1.0 + (-1.0 * bm25(documents_fts, 
-                    1.0,   -- filepath
-                    10.0,  -- title
-                    5.0,   -- body
-                    8.0,   -- llm_summary (high weight)
-                    10.0,  -- llm_title (high weight)
-                    15.0,  -- llm_keywords (very high weight)
-                    7.0,   -- llm_intent (high weight)
-                    12.0,  -- llm_concepts (very high weight)
-                    20.0,  -- user_metadata (highest weight)
-                    0.1    -- modified_at (very low)
-                )))) * COALESCE(d.importance_score, 1.0) as score,
+                (1.0 / (1.0 + (-1.0 * fts.rank))) * {importance} as score,
                 d.llm_summary,
                 d.llm_title,
                 d.llm_keywords,
                 d.llm_category,
                 d.llm_difficulty,
                 d.user_metadata
-            FROM documents_fts fts
+            FROM ({matches}) fts
             JOIN documents d ON d.id = fts.rowid
             JOIN content c ON c.hash = d.hash
-            JOIN collections coll ON coll.name = d.collection
-            WHERE documents_fts MATCH ?1 AND d.active = 1
+            WHERE d.active = 1
         "#,
         );
 
-        let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(clean_query.clone())];
-
-        if let Some(ref coll) = options.collection {
-            sql.push_str(" AND d.collection = ?");
-            sql.push_str(&(params_vec.len() + 1).to_string());
-            params_vec.push(Box::new(coll.clone()));
-        }
-
-        if let Some(ref provider) = options.provider {
-            sql.push_str(" AND coll.provider_type = ?");
-            sql.push_str(&(params_vec.len() + 1).to_string());
-            params_vec.push(Box::new(provider.clone()));
-        }
+        let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = vec![
+            Box::new(match_expr),
+            Box::new(options.ranking_weights().importance),
+        ];
 
         // Apply metadata filters
         for (field, value) in filters {
@@ -82,24 +150,41 @@ impl Database {
                     params_vec.push(Box::new(value));
                 }
                 "tag" | "keyword" => {
+                    sql.push_str(&tag_filter_sql(params_vec.len() + 1));
+                    params_vec.push(Box::new(value));
+                }
+                "author" => {
+                    sql.push_str(&author_filter_sql(params_vec.len() + 1));
+                    params_vec.push(Box::new(value));
+                }
+                // Day bounds: the note date, else the modification day
+                "after" => {
                     sql.push_str(&format!(
-                        " AND d.llm_keywords LIKE ?{}",
+                        " AND COALESCE(d.note_date, substr(d.modified_at, 1, 10)) >= ?{}",
                         params_vec.len() + 1
                     ));
-                    params_vec.push(Box::new(format!("%{}%", value)));
+                    params_vec.push(Box::new(value));
                 }
+                "before" => {
+                    sql.push_str(&format!(
+                        " AND COALESCE(d.note_date, substr(d.modified_at, 1, 10)) <= ?{}",
+                        params_vec.len() + 1
+                    ));
+                    params_vec.push(Box::new(value));
+                }
+                "dated" => sql.push_str(" AND d.note_date IS NOT NULL"),
                 _ => {} // Ignore unknown filters
             }
         }
 
-        sql.push_str(" ORDER BY score DESC");
+        sql.push_str(" ORDER BY score DESC, d.hash");
 
         if options.limit > 0 {
             sql.push_str(&format!(" LIMIT {}", options.limit));
         }
 
         let mut stmt = self.conn.prepare(&sql)?;
-        let results = stmt
+        let mut results = stmt
             .query_map(
                 rusqlite::params_from_iter(params_vec.iter().map(|p| p.as_ref())),
                 |row| {
@@ -114,7 +199,7 @@ impl Database {
 
                     // Extract snippet from document body
                     let body: String = row.get(6)?;
-                    let snippet = extract_snippet(&body, &clean_query, Some(150), None);
+                    let snippet = extract_snippet(&body, &snippet_terms, Some(150), None);
 
                     Ok(SearchResult {
                         filepath: row.get(0)?,
@@ -156,6 +241,10 @@ impl Database {
                 },
             )?
             .collect::<std::result::Result<Vec<_>, _>>()?;
+        timer.stage("fts");
+
+        super::feedback::apply_feedback(self, &feedback_query, &mut results)?;
+        timer.stage("feedback");
 
         let filtered: Vec<SearchResult> = results
             .into_iter()
@@ -171,13 +260,17 @@ impl Database {
         query: &str,
         options: &SearchOptions,
     ) -> Result<Vec<SearchResult>> {
-        let (clean_query, filters) = parse_metadata_filters(query);
+        let (raw_query, filters) = split_metadata_filters(&normalize_text(query));
+        let fts_query = self.fts_query(&raw_query, options)?;
+        let snippet_terms = fts_query.keywords();
 
-        let use_fts = !clean_query.is_empty();
+        // Chunks have no title or path columns, so field scopes are dropped
+        let match_expr = fts_query.to_match_unscoped();
+        let use_fts = !match_expr.is_empty();
         let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
 
         let mut sql = if use_fts {
-            params_vec.push(Box::new(clean_query.clone()));
+            params_vec.push(Box::new(match_expr));
             String::from(
                 r#"
                 SELECT
@@ -255,6 +348,26 @@ impl Database {
             params_vec.push(Box::new(provider.clone()));
         }
 
+        // Exact queries keep chunks of documents holding the literal tokens
+        if use_fts && options.exact {
+            let tables = self.fts_partitions(
+                true,
+                options.collection.as_deref(),
+                options.provider.as_deref(),
+            )?;
+            if tables.is_empty() {
+                return Ok(Vec::new());
+            }
+            let param = params_vec.len() + 1;
+            let matches = tables
+                .iter()
+                .map(|table| format!("SELECT rowid FROM {0} WHERE {0} MATCH ?{1}", table, param))
+                .collect::<Vec<_>>()
+                .join(" UNION ALL ");
+            sql.push_str(&format!(" AND d.id IN ({})", matches));
+            params_vec.push(Box::new(fts_query.to_match()));
+        }
+
         // Apply chunk-level label filters
         for (field, value) in filters {
             if field != "label" {
@@ -304,7 +417,7 @@ impl Database {
 
                     // Extract snippet from chunk body
                     let body: String = row.get(6)?;
-                    let snippet = extract_snippet(&body, &clean_query, Some(150), None);
+                    let snippet = extract_snippet(&body, &snippet_terms, Some(150), None);
 
                     Ok(SearchResult {
                         filepath: row.get(0)?,
@@ -348,26 +461,430 @@ impl Database {
             .collect::<std::result::Result<Vec<_>, _>>()?;
 
         // Filter by min_score
-        let filtered: Vec<SearchResult> = results
+        let mut filtered: Vec<SearchResult> = results
             .into_iter()
             .filter(|r| r.score >= options.min_score)
             .collect();
 
+        let hashes: Vec<&str> = filtered
+            .iter()
+            .filter_map(|r| r.chunk_hash.as_deref())
+            .collect();
+        let counts = self.reference_counts(&hashes)?;
+        apply_reference_boost(&mut filtered, &counts);
+
         Ok(filtered)
     }
 }
 
-/// Preprocess query for FTS5 compatibility
-/// Handles special characters that FTS5 can't tokenize properly
-fn preprocess_fts_query(query: &str) -> String {
-    query
-        // Replace :: with space (Rust path separator)
-        .replace("::", " ")
-        // Replace -> with space (function return type)
-        .replace("->", " ")
-        // Replace < and > with spaces (generics)
-        .replace('<', " ")
-        .replace('>', " ")
-        // Preserve other characters that FTS5 handles well
-        .to_string()
+/// Weight of the reference-count boost for chunks defining a widely used symbol
+const REFERENCE_BOOST_WEIGHT: f64 = 0.2;
+
+/// Boost chunk results by how often what they define is referenced
+///
+/// Counts are log-scaled against the most referenced chunk in the results, so
+/// a handful of extra callers matters more than the hundredth.
+fn apply_reference_boost(results: &mut [SearchResult], counts: &HashMap<String, usize>) {
+    let max = counts.values().copied().max().unwrap_or(0);
+    if max == 0 {
+        return;
+    }
+    let scale = (1.0 + max as f64).ln();
+
+    for result in results.iter_mut() {
+        if let Some(&count) = result.chunk_hash.as_ref().and_then(|h| counts.get(h)) {
+            result.score *= 1.0 + REFERENCE_BOOST_WEIGHT * (1.0 + count as f64).ln() / scale;
+        }
+    }
+
+    results.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_searches_cover_the_collection_tables() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        for (coll, provider, hash) in [
+            ("notes", "file", "aaa111"),
+            ("my-notes", "file", "bbb222"),
+            ("docs", "url", "ccc333"),
+        ] {
+            db.add_collection(coll, "/tmp", "**/*.md", provider, None)
+                .unwrap();
+            let body = format!("# Tokio\n\ntokio runtime notes for {}", coll);
+            db.insert_content(hash, &body).unwrap();
+            db.insert_document(coll, "tokio.md", "Tokio", hash, &now, &now, provider, None)
+                .unwrap();
+        }
+
+        let options = SearchOptions {
+            collection: Some("my-notes".to_string()),
+            ..Default::default()
+        };
+        let results = db.search_fts("tokio", &options).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].collection_name, "my-notes");
+
+        let options = SearchOptions {
+            provider: Some("url".to_string()),
+            exact: true,
+            ..Default::default()
+        };
+        let results = db.search_fts("tokio", &options).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].collection_name, "docs");
+
+        let all = db.search_fts("tokio", &SearchOptions::default()).unwrap();
+        assert_eq!(all.len(), 3);
+
+        db.remove_collection("notes").unwrap();
+        let all = db.search_fts("tokio", &SearchOptions::default()).unwrap();
+        assert_eq!(all.len(), 2);
+        let options = SearchOptions {
+            collection: Some("notes".to_string()),
+            ..Default::default()
+        };
+        assert!(db.search_fts("tokio", &options).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_importance_weight_orders_bm25_results() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.add_collection("notes", "/tmp", "**/*.md", "file", None)
+            .unwrap();
+        let now = Utc::now().to_rfc3339();
+        for (path, hash) in [("a.md", "aaa111"), ("b.md", "bbb222")] {
+            db.insert_content(hash, "# Tokio\n\ntokio runtime notes")
+                .unwrap();
+            db.insert_document("notes", path, "Tokio", hash, &now, &now, "file", None)
+                .unwrap();
+        }
+        db.conn
+            .execute(
+                "UPDATE documents SET importance_score = 3.0 WHERE hash = 'bbb222'",
+                [],
+            )
+            .unwrap();
+
+        let options = SearchOptions {
+            weights: Some(Default::default()),
+            ..Default::default()
+        };
+        let results = db.search_fts("tokio", &options).unwrap();
+        assert_eq!(results[0].hash, "bbb222");
+        assert!((results[0].score / results[1].score - 3.0).abs() < 1e-6);
+
+        let options = SearchOptions {
+            weights: Some(crate::config::RankingWeights::default().without_pagerank()),
+            ..Default::default()
+        };
+        let results = db.search_fts("tokio", &options).unwrap();
+        assert!((results[0].score - results[1].score).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_frontmatter_tag_and_author_filters() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("notes", "/tmp", "**/*.md", "file", None)
+            .unwrap();
+        for (hash, path, author, tags) in [
+            ("aaa111", "a.md", "Ann Lee", vec!["Rust", "async"]),
+            ("bbb222", "b.md", "Bo Chen", vec!["python"]),
+        ] {
+            db.insert_content(hash, "# Runtime\n\nruntime notes")
+                .unwrap();
+            db.insert_document("notes", path, path, hash, &now, &now, "file", None)
+                .unwrap();
+            let metadata = crate::db::MetadataBuilder::new()
+                .text("author", author)
+                .tags("tags", tags)
+                .build();
+            db.add_metadata(&format!("#{}", hash), &metadata).unwrap();
+        }
+
+        let search = |query: &str| -> Vec<String> {
+            db.search_fts(query, &SearchOptions::default())
+                .unwrap()
+                .into_iter()
+                .map(|r| r.display_path)
+                .collect()
+        };
+        assert_eq!(search("runtime tag:rust"), vec!["notes/a.md"]);
+        assert_eq!(search("runtime tag:python"), vec!["notes/b.md"]);
+        assert_eq!(search("runtime author:chen"), vec!["notes/b.md"]);
+        assert!(search("runtime tag:go").is_empty());
+    }
+
+    #[test]
+    fn test_exact_search_skips_stemming() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("notes", "/tmp", "**/*.md", "file", None)
+            .unwrap();
+        for (hash, path, body) in [
+            (
+                "aaa111",
+                "plural.md",
+                "# Suite\n\nrunning the tests nightly",
+            ),
+            ("bbb222", "single.md", "# Check\n\nrun one test"),
+        ] {
+            db.insert_content(hash, body).unwrap();
+            db.insert_document("notes", path, path, hash, &now, &now, "file", None)
+                .unwrap();
+        }
+
+        let stemmed = db.search_fts("tests", &SearchOptions::default()).unwrap();
+        assert_eq!(stemmed.len(), 2);
+
+        let exact = SearchOptions {
+            exact: true,
+            ..Default::default()
+        };
+        let results = db.search_fts("tests", &exact).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].display_path, "notes/plural.md");
+        let results = db.search_fts("run", &exact).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].display_path, "notes/single.md");
+
+        // The exact index follows deactivation
+        db.deactivate_document("notes", "plural.md").unwrap();
+        assert!(db.search_fts("tests", &exact).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_fuzzy_search_tolerates_typos() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("notes", "/tmp", "**/*.md", "file", None)
+            .unwrap();
+        for (hash, path, body) in [
+            (
+                "aaa111",
+                "db.md",
+                "# Storage\n\nthe database keeps a journal",
+            ),
+            ("bbb222", "net.md", "# Network\n\nretry with backoff"),
+        ] {
+            db.insert_content(hash, body).unwrap();
+            db.insert_document("notes", path, path, hash, &now, &now, "file", None)
+                .unwrap();
+        }
+        db.rebuild_vocabulary("notes").unwrap();
+
+        assert!(db
+            .search_fts("databse journal", &SearchOptions::default())
+            .unwrap()
+            .is_empty());
+
+        let fuzzy = SearchOptions {
+            fuzzy: true,
+            ..Default::default()
+        };
+        let results = db.search_fts("databse journal", &fuzzy).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].display_path, "notes/db.md");
+        let results = db.search_fts("bakcoff", &fuzzy).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].display_path, "notes/net.md");
+        assert!(db.search_fts("zebra", &fuzzy).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_matches_across_unicode_forms() {
+        let temp = tempfile::tempdir().unwrap();
+        // Decomposed: "e" followed by a combining acute accent
+        std::fs::write(
+            temp.path().join("cafe.md"),
+            "# Cafe\n\nthe cafe\u{301} menu",
+        )
+        .unwrap();
+        std::fs::write(
+            temp.path().join("viet.md"),
+            "# Notes\n\nti\u{1ebf}ng Vi\u{1ec7}t",
+        )
+        .unwrap();
+
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.add_collection(
+            "notes",
+            temp.path().to_str().unwrap(),
+            "**/*.md",
+            "file",
+            None,
+        )
+        .unwrap();
+        db.reindex_collection("notes").await.unwrap();
+
+        let options = SearchOptions::default();
+        for query in ["caf\u{e9}", "cafe\u{301}", "cafe"] {
+            let results = db.search_fts(query, &options).unwrap();
+            assert_eq!(results.len(), 1, "query {:?}", query);
+            assert_eq!(results[0].display_path, "notes/cafe.md");
+        }
+        // Stored content is NFC
+        let doc = db
+            .find_active_document("notes", "cafe.md")
+            .unwrap()
+            .unwrap();
+        let body = db.get_content(&doc.hash).unwrap().unwrap();
+        assert!(body.contains("caf\u{e9} menu"));
+
+        let results = db.search_fts("tieng viet", &options).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].display_path, "notes/viet.md");
+    }
+
+    #[tokio::test]
+    async fn test_chunk_search_boosts_referenced_symbols() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp.path().join("lib.rs"),
+            "fn route_a() {\n    // dispatch\n}\n\nfn route_b() {\n    // dispatch\n}\n\nfn main() {\n    route_b();\n}\n\nfn serve() {\n    route_b();\n}\n",
+        )
+        .unwrap();
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.add_collection(
+            "code",
+            temp.path().to_str().unwrap(),
+            "**/*.rs",
+            "file",
+            None,
+        )
+        .unwrap();
+        db.reindex_collection_with_metadata("code", None)
+            .await
+            .unwrap();
+
+        let results = db
+            .search_chunks_bm25("dispatch", &SearchOptions::default())
+            .unwrap();
+        let names: Vec<_> = results
+            .iter()
+            .map(|r| r.chunk_breadcrumb.as_deref().unwrap())
+            .collect();
+        assert_eq!(names, vec!["route_b", "route_a"]);
+        assert!(results[0].score > results[1].score);
+    }
+
+    #[test]
+    fn test_paged_search_walks_the_ranking() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("notes", "/tmp", "**/*.md", "file", None)
+            .unwrap();
+        // Identical bodies tie on score, so order comes from the tie-break
+        for i in 0..7 {
+            let hash = format!("h{:05}", i);
+            db.insert_content(&hash, "# Cache\n\ncache eviction notes")
+                .unwrap();
+            db.insert_document(
+                "notes",
+                &format!("{}.md", i),
+                "Cache",
+                &hash,
+                &now,
+                &now,
+                "file",
+                None,
+            )
+            .unwrap();
+        }
+
+        let full = db.search_fts("cache", &SearchOptions::default()).unwrap();
+        assert_eq!(full.len(), 7);
+
+        let mut paged = Vec::new();
+        for offset in (0..7).step_by(3) {
+            let options = SearchOptions {
+                limit: 3,
+                offset: Some(offset),
+                ..Default::default()
+            };
+            paged.extend(db.search_fts("cache", &options).unwrap());
+        }
+        let hashes = |results: &[SearchResult]| -> Vec<String> {
+            results.iter().map(|r| r.hash.clone()).collect()
+        };
+        assert_eq!(hashes(&paged), hashes(&full));
+    }
+
+    #[test]
+    fn test_boolean_query_language() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("notes", "/tmp", "**/*.md", "file", None)
+            .unwrap();
+        for (hash, path, title, body) in [
+            (
+                "aaa111",
+                "src/cache.md",
+                "Cache",
+                "lru cache eviction policy",
+            ),
+            (
+                "bbb222",
+                "docs/tokio.md",
+                "Runtime",
+                "tokio runtime and the cache",
+            ),
+            (
+                "ccc333",
+                "docs/errors.md",
+                "Errors",
+                "error handling: retry-after",
+            ),
+        ] {
+            db.insert_content(hash, body).unwrap();
+            db.insert_document("notes", path, title, hash, &now, &now, "file", None)
+                .unwrap();
+        }
+
+        let paths = |query: &str| -> Vec<String> {
+            let mut paths: Vec<String> = db
+                .search_fts(query, &SearchOptions::default())
+                .unwrap()
+                .into_iter()
+                .map(|r| r.display_path)
+                .collect();
+            paths.sort();
+            paths
+        };
+
+        assert_eq!(paths("cache NOT tokio"), vec!["notes/src/cache.md"]);
+        assert_eq!(
+            paths("lru OR (error handling)"),
+            vec!["notes/docs/errors.md", "notes/src/cache.md"]
+        );
+        assert_eq!(paths(r#""eviction policy""#), vec!["notes/src/cache.md"]);
+        assert!(paths(r#""policy eviction""#).is_empty());
+        assert_eq!(paths("tok*"), vec!["notes/docs/tokio.md"]);
+        assert_eq!(paths("title:runtime"), vec!["notes/docs/tokio.md"]);
+        assert_eq!(paths("path:docs/ cache"), vec!["notes/docs/tokio.md"]);
+        // Operator characters no longer raise FTS5 syntax errors
+        assert_eq!(paths("retry-after (NOT"), vec!["notes/docs/errors.md"]);
+        assert!(db
+            .search_fts("NOT cache", &SearchOptions::default())
+            .is_err());
+    }
 }
@@ -0,0 +1,435 @@
+//! Context packs for agents
+//!
+//! Retrieves chunks for a query and assembles the best of them into one
+//! block of text that fits a token budget. Each chunk is headed by a
+//! numbered citation (`[n] collection/path:start-end`) so answers built
+//! from the pack can point back at their sources.
+
+use super::{parse_metadata_filters, DetailLevel, SearchOptions, SearchResult};
+use crate::config::RankingWeights;
+use crate::db::Database;
+use crate::error::Result;
+use crate::index::ast_chunker::oversized::estimate_tokens;
+use crate::llm::Embedder;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Budget used when the caller gives none
+pub const DEFAULT_TOKEN_BUDGET: usize = 4000;
+
+/// Chunks retrieved before deduplication and trimming
+const CANDIDATE_LIMIT: usize = 50;
+
+/// One cited chunk of a context pack
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextChunk {
+    /// Citation number, as printed in the pack text
+    pub citation: usize,
+    pub docid: String,
+    /// Display path, e.g. "collection/src/lib.rs"
+    pub path: String,
+    pub chunk_hash: String,
+    pub breadcrumb: Option<String>,
+    pub start_line: i32,
+    pub end_line: i32,
+    pub score: f64,
+    /// Estimated tokens of the chunk's entry, citation header included
+    pub tokens: usize,
+    /// Whether the content was cut to fit the budget
+    pub truncated: bool,
+    pub content: String,
+}
+
+/// Outcome of [`build_context`]
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextPack {
+    pub query: String,
+    pub token_budget: usize,
+    /// Estimated tokens of `text`
+    pub tokens: usize,
+    pub chunks: Vec<ContextChunk>,
+    /// Relevant chunks left out for lack of budget
+    pub omitted: usize,
+    /// Ready-to-paste context block
+    pub text: String,
+}
+
+/// Build a context pack of chunks relevant to `query` within `token_budget`
+///
+/// Candidates come from chunk search with `options` (collection, provider,
+/// exact, min score). Duplicate content and chunks overlapping a better one
+/// of the same document are dropped, then chunks are taken best first while
+/// they fit. The pack lists documents by their best chunk and each
+/// document's chunks in file order. Only when not even the best chunk fits
+/// is it cut down to the budget.
+pub fn build_context(
+    db: &Database,
+    query: &str,
+    token_budget: usize,
+    options: &SearchOptions,
+) -> Result<ContextPack> {
+    let candidates = retrieve(db, query, options)?;
+    Ok(pack(query, token_budget, dedupe(candidates)))
+}
+
+/// Like [`build_context`], with candidates ranked by reciprocal rank fusion
+/// of BM25 and vector chunk search
+pub async fn build_hybrid_context(
+    db: &Database,
+    query: &str,
+    token_budget: usize,
+    options: &SearchOptions,
+    embedder: &dyn Embedder,
+) -> Result<ContextPack> {
+    let bm25 = retrieve(db, query, options)?;
+    let mut vector = db
+        .search_chunks_vec(query, embedder, &candidate_options(options))
+        .await?;
+    vector.retain(|r| r.body.is_some());
+
+    let candidates = fuse_chunks(&bm25, &vector, options);
+    Ok(pack(query, token_budget, dedupe(candidates)))
+}
+
+/// Take candidates best first while they fit and number them
+fn pack(query: &str, token_budget: usize, candidates: Vec<SearchResult>) -> ContextPack {
+    let mut selected: Vec<ContextChunk> = Vec::new();
+    let mut used = 0;
+    let mut omitted = 0;
+    for result in &candidates {
+        let chunk = to_chunk(result);
+        if used + chunk.tokens <= token_budget {
+            used += chunk.tokens;
+            selected.push(chunk);
+        } else {
+            omitted += 1;
+        }
+    }
+
+    if selected.is_empty() {
+        if let Some(best) = candidates.first() {
+            let chunk = truncate_to_fit(to_chunk(best), token_budget);
+            if chunk.tokens <= token_budget && !chunk.content.is_empty() {
+                omitted -= 1;
+                selected.push(chunk);
+            }
+        }
+    }
+
+    order_by_document(&mut selected);
+    for (i, chunk) in selected.iter_mut().enumerate() {
+        chunk.citation = i + 1;
+    }
+
+    let text = selected
+        .iter()
+        .map(render_chunk)
+        .collect::<Vec<_>>()
+        .join("\n");
+    ContextPack {
+        query: query.to_string(),
+        token_budget,
+        tokens: estimate_tokens(text.chars().count()),
+        chunks: selected,
+        omitted,
+        text,
+    }
+}
+
+fn candidate_options(options: &SearchOptions) -> SearchOptions {
+    SearchOptions {
+        limit: CANDIDATE_LIMIT.max(options.limit),
+        detail: DetailLevel::L2,
+        ..options.clone()
+    }
+}
+
+/// Chunk hits ordered best first, retried with any-term matching when
+/// requiring every term finds nothing
+fn retrieve(db: &Database, query: &str, options: &SearchOptions) -> Result<Vec<SearchResult>> {
+    let search_options = candidate_options(options);
+    let mut results = db.search_chunks_bm25(query, &search_options)?;
+
+    let (clean_query, filters) = parse_metadata_filters(query);
+    let terms: Vec<&str> = clean_query.split_whitespace().collect();
+    if results.is_empty() && terms.len() > 1 {
+        let mut any_term = terms.join(" OR ");
+        for (field, value) in filters {
+            any_term.push_str(&format!(" {}:{}", field, value));
+        }
+        results = db.search_chunks_bm25(&any_term, &search_options)?;
+    }
+
+    results.retain(|r| r.score >= options.min_score && r.body.is_some());
+    Ok(results)
+}
+
+/// Reciprocal rank fusion of two chunk rankings; chunks are keyed by
+/// their own hash since several can come from one document
+fn fuse_chunks(
+    bm25: &[SearchResult],
+    vector: &[SearchResult],
+    options: &SearchOptions,
+) -> Vec<SearchResult> {
+    let weights = options.ranking_weights();
+    let rrf_k = if weights.rrf_k > 0.0 {
+        weights.rrf_k
+    } else {
+        RankingWeights::default().rrf_k
+    };
+    let mut fused: Vec<SearchResult> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for (results, weight) in [(bm25, weights.bm25), (vector, weights.vector)] {
+        if weight <= 0.0 {
+            continue;
+        }
+        for (rank, result) in results.iter().enumerate() {
+            let score = weight / (rrf_k + (rank + 1) as f64);
+            let key = result.chunk_hash.clone().unwrap_or_default();
+            match index.get(&key) {
+                Some(&i) => fused[i].score += score,
+                None => {
+                    index.insert(key, fused.len());
+                    let mut result = result.clone();
+                    result.score = score;
+                    fused.push(result);
+                }
+            }
+        }
+    }
+    fused.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.chunk_hash.cmp(&b.chunk_hash))
+    });
+    fused
+}
+
+/// Drop repeated content and chunks overlapping a higher scored chunk of
+/// the same document; expects results best first
+fn dedupe(results: Vec<SearchResult>) -> Vec<SearchResult> {
+    let mut seen_content = HashSet::new();
+    let mut kept: Vec<SearchResult> = Vec::new();
+    for result in results {
+        let content = result
+            .body
+            .as_deref()
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+        if !seen_content.insert(content) {
+            continue;
+        }
+        let (start, end) = line_range(&result);
+        let overlaps = kept.iter().any(|k| {
+            let (k_start, k_end) = line_range(k);
+            k.hash == result.hash && start <= k_end && k_start <= end
+        });
+        if !overlaps {
+            kept.push(result);
+        }
+    }
+    kept
+}
+
+fn line_range(result: &SearchResult) -> (i32, i32) {
+    (
+        result.chunk_start_line.unwrap_or(0),
+        result.chunk_end_line.unwrap_or(0),
+    )
+}
+
+fn to_chunk(result: &SearchResult) -> ContextChunk {
+    let (start_line, end_line) = line_range(result);
+    let mut chunk = ContextChunk {
+        citation: 0,
+        docid: result.docid.clone(),
+        path: result.display_path.clone(),
+        chunk_hash: result.chunk_hash.clone().unwrap_or_default(),
+        breadcrumb: result.chunk_breadcrumb.clone(),
+        start_line,
+        end_line,
+        score: result.score,
+        tokens: 0,
+        truncated: false,
+        content: result.body.clone().unwrap_or_default(),
+    };
+    chunk.tokens = chunk_tokens(&chunk);
+    chunk
+}
+
+/// Tokens of a chunk's rendered entry, allowing for the widest citation
+fn chunk_tokens(chunk: &ContextChunk) -> usize {
+    estimate_tokens(render_chunk(chunk).chars().count() + 4)
+}
+
+/// Cut a chunk's content, at a line boundary where there is one, so that
+/// its entry fits `budget`
+fn truncate_to_fit(mut chunk: ContextChunk, budget: usize) -> ContextChunk {
+    chunk.truncated = true;
+    let content = std::mem::take(&mut chunk.content);
+    let overhead = chunk_tokens(&chunk);
+    let max_chars = budget.saturating_sub(overhead + 1) * 4;
+    let mut kept: String = content.chars().take(max_chars).collect();
+    if kept.len() < content.len() {
+        if let Some(newline) = kept.rfind('\n') {
+            kept.truncate(newline);
+        }
+    }
+    chunk.end_line = chunk.start_line + kept.lines().count().saturating_sub(1) as i32;
+    chunk.content = kept;
+    chunk.tokens = chunk_tokens(&chunk);
+    chunk
+}
+
+/// Group chunks by document, documents by their best chunk and chunks in
+/// file order; expects chunks best first
+fn order_by_document(chunks: &mut [ContextChunk]) {
+    let mut rank: HashMap<String, usize> = HashMap::new();
+    for chunk in chunks.iter() {
+        let next = rank.len();
+        rank.entry(chunk.docid.clone()).or_insert(next);
+    }
+    chunks.sort_by_key(|c| (rank[&c.docid], c.start_line));
+}
+
+fn render_chunk(chunk: &ContextChunk) -> String {
+    let mut header = format!(
+        "[{}] {}:{}-{}",
+        chunk.citation, chunk.path, chunk.start_line, chunk.end_line
+    );
+    if let Some(breadcrumb) = chunk.breadcrumb.as_deref().filter(|b| !b.is_empty()) {
+        header.push_str(&format!(" ({})", breadcrumb));
+    }
+    if chunk.truncated {
+        header.push_str(" [truncated]");
+    }
+    let fence = fence_for(&chunk.content);
+    format!(
+        "{}\n{}\n{}\n{}\n",
+        header,
+        fence,
+        chunk.content.trim_end(),
+        fence
+    )
+}
+
+/// A backtick fence longer than any run inside the content
+fn fence_for(content: &str) -> String {
+    let longest = content.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn add_chunk(db: &Database, hash: &str, doc: &str, lines: (i32, i32), content: &str) {
+        let now = Utc::now().to_rfc3339();
+        db.insert_chunk(
+            hash,
+            doc,
+            lines.0,
+            lines.0,
+            content,
+            None,
+            None,
+            lines.0,
+            lines.1,
+            None,
+            None,
+            None,
+            &[],
+            &Default::default(),
+            &[],
+            None,
+            None,
+            &now,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_context_pack_dedupes_orders_and_fits_budget() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+        for (hash, path) in [("h1", "a.md"), ("h2", "b.md")] {
+            db.insert_content(hash, "placeholder").unwrap();
+            db.insert_document("notes", path, path, hash, &now, &now, "file", None)
+                .unwrap();
+        }
+        add_chunk(&db, "c1", "h1", (20, 30), "cache eviction runs hourly");
+        add_chunk(&db, "c2", "h1", (1, 10), "the cache holds embeddings");
+        // Overlaps c1 and repeats c2, so both are dropped
+        add_chunk(&db, "c3", "h1", (25, 35), "cache cache cache eviction");
+        add_chunk(&db, "c4", "h2", (1, 5), "the cache holds embeddings");
+        add_chunk(&db, "c5", "h2", (8, 9), &"cache ".repeat(400));
+
+        let pack = build_context(&db, "cache", 200, &SearchOptions::default()).unwrap();
+        assert!(!pack
+            .chunks
+            .iter()
+            .any(|c| c.path == "notes/a.md" && c.start_line == 25));
+        assert_eq!(
+            pack.chunks
+                .iter()
+                .filter(|c| c.content.contains("holds"))
+                .count(),
+            1
+        );
+        assert!(pack.tokens <= 200);
+        assert!(pack.omitted >= 1, "the oversized chunk does not fit");
+
+        // Citations follow document then line order
+        for (i, chunk) in pack.chunks.iter().enumerate() {
+            assert_eq!(chunk.citation, i + 1);
+            assert!(pack.text.contains(&format!(
+                "[{}] {}:{}-{}",
+                chunk.citation, chunk.path, chunk.start_line, chunk.end_line
+            )));
+        }
+        for pair in pack.chunks.windows(2) {
+            if pair[0].docid == pair[1].docid {
+                assert!(pair[0].start_line < pair[1].start_line);
+            }
+        }
+
+        // A budget too small for any whole chunk still yields the best one, cut down
+        let tiny = build_context(&db, "hourly", 13, &SearchOptions::default()).unwrap();
+        assert_eq!(tiny.chunks.len(), 1);
+        assert!(tiny.chunks[0].truncated);
+        assert!(tiny.text.contains("[truncated]"));
+        assert!(tiny.tokens <= 13);
+    }
+
+    #[test]
+    fn test_fuse_chunks_keys_by_chunk() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+        db.insert_content("h1", "placeholder").unwrap();
+        db.insert_document("notes", "a.md", "a.md", "h1", &now, &now, "file", None)
+            .unwrap();
+        add_chunk(&db, "c1", "h1", (1, 5), "cache cache cache eviction");
+        add_chunk(&db, "c2", "h1", (10, 15), "cache warmup");
+        add_chunk(&db, "c3", "h1", (20, 25), "cache sizing for embeddings");
+
+        let options = SearchOptions::default();
+        let bm25 = retrieve(&db, "cache", &options).unwrap();
+        assert_eq!(bm25.len(), 3);
+        // The vector ranking only finds the last BM25 hit
+        let vector = vec![bm25[2].clone()];
+
+        let fused = fuse_chunks(&bm25, &vector, &options);
+        assert_eq!(fused.len(), 3, "chunks of one document stay apart");
+        assert_eq!(fused[0].chunk_hash, bm25[2].chunk_hash);
+        assert!(fused[0].score > fused[1].score);
+    }
+}
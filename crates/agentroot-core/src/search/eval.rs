@@ -0,0 +1,376 @@
+//! Relevance evaluation against golden queries
+//!
+//! An eval set is a YAML file of queries with the documents that should
+//! come back for them:
+//!
+//! ```yaml
+//! k: 10
+//! queries:
+//!   - query: oauth token refresh
+//!     expected: ["#a1b2c3", docs/auth/refresh.md]
+//!   - query: schema migrations
+//!     collection: code
+//!     expected: [code/src/db/schema.rs]
+//! ```
+//!
+//! Each search mode is scored with recall@k, MRR and NDCG@k (binary
+//! relevance), so ranking changes can be measured before they ship.
+
+use super::{hybrid_search, smart_search, DetailLevel, SearchOptions};
+use crate::db::Database;
+use crate::error::{AgentRootError, Result};
+use crate::llm::Embedder;
+use rusqlite::params;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Metric cutoff when the eval set does not set `k`
+pub const DEFAULT_EVAL_K: usize = 10;
+
+fn default_k() -> usize {
+    DEFAULT_EVAL_K
+}
+
+/// Golden queries loaded from YAML
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct EvalSet {
+    #[serde(default = "default_k")]
+    pub k: usize,
+    pub queries: Vec<GoldenQuery>,
+}
+
+/// A query and the documents relevant to it
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct GoldenQuery {
+    pub query: String,
+    /// Docids (#abc123) or collection/path
+    pub expected: Vec<String>,
+    #[serde(default)]
+    pub collection: Option<String>,
+}
+
+impl EvalSet {
+    pub fn from_yaml(text: &str) -> Result<Self> {
+        let set: Self = serde_yaml::from_str(text)?;
+        if set.queries.is_empty() {
+            return Err(AgentRootError::InvalidInput(
+                "Eval set has no queries".to_string(),
+            ));
+        }
+        if let Some(q) = set.queries.iter().find(|q| q.expected.is_empty()) {
+            return Err(AgentRootError::InvalidInput(format!(
+                "Eval query '{}' has no expected documents",
+                q.query
+            )));
+        }
+        Ok(set)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::from_yaml(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// Search mode under evaluation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EvalMode {
+    Bm25,
+    Vector,
+    Hybrid,
+    Smart,
+}
+
+impl EvalMode {
+    pub const ALL: [EvalMode; 4] = [
+        EvalMode::Bm25,
+        EvalMode::Vector,
+        EvalMode::Hybrid,
+        EvalMode::Smart,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EvalMode::Bm25 => "bm25",
+            EvalMode::Vector => "vector",
+            EvalMode::Hybrid => "hybrid",
+            EvalMode::Smart => "smart",
+        }
+    }
+
+    fn needs_embeddings(&self) -> bool {
+        matches!(self, EvalMode::Vector | EvalMode::Hybrid)
+    }
+}
+
+/// Scores for one query in one mode
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct QueryEval {
+    pub query: String,
+    pub recall: f64,
+    pub reciprocal_rank: f64,
+    pub ndcg: f64,
+    /// Expected documents missing from the top k
+    pub missing: Vec<String>,
+}
+
+/// Averaged scores for one mode
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ModeEval {
+    pub mode: EvalMode,
+    pub k: usize,
+    pub recall: f64,
+    pub mrr: f64,
+    pub ndcg: f64,
+    pub queries: Vec<QueryEval>,
+    /// Why the mode could not be evaluated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
+}
+
+impl ModeEval {
+    fn skipped(mode: EvalMode, k: usize, reason: String) -> Self {
+        Self {
+            mode,
+            k,
+            recall: 0.0,
+            mrr: 0.0,
+            ndcg: 0.0,
+            queries: Vec::new(),
+            skipped: Some(reason),
+        }
+    }
+}
+
+/// Recall@k, reciprocal rank and NDCG@k of a ranking of document hashes
+pub fn score_ranking(ranked: &[String], relevant: &HashSet<String>, k: usize) -> (f64, f64, f64) {
+    if relevant.is_empty() || k == 0 {
+        return (0.0, 0.0, 0.0);
+    }
+    let top = &ranked[..ranked.len().min(k)];
+    let hits: Vec<usize> = top
+        .iter()
+        .enumerate()
+        .filter(|(_, hash)| relevant.contains(*hash))
+        .map(|(i, _)| i)
+        .collect();
+
+    let recall = hits.len() as f64 / relevant.len() as f64;
+    let reciprocal_rank = hits.first().map_or(0.0, |&i| 1.0 / (i + 1) as f64);
+    let gain = |i: usize| 1.0 / ((i + 2) as f64).log2();
+    let dcg: f64 = hits.iter().map(|&i| gain(i)).sum();
+    let ideal: f64 = (0..relevant.len().min(k)).map(gain).sum();
+    (recall, reciprocal_rank, dcg / ideal)
+}
+
+/// A golden query with its expected documents resolved to hashes
+struct ResolvedQuery<'a> {
+    golden: &'a GoldenQuery,
+    relevant: Vec<(String, String)>,
+}
+
+/// Run every query of `set` in each mode and score the rankings
+///
+/// Vector and hybrid modes need `embedder` and an embedded index; they are
+/// reported as skipped otherwise. Hybrid runs without query expansion or
+/// reranking so repeated runs are comparable.
+pub async fn evaluate(
+    db: &Database,
+    set: &EvalSet,
+    modes: &[EvalMode],
+    k: usize,
+    embedder: Option<&dyn Embedder>,
+) -> Result<Vec<ModeEval>> {
+    let resolved = set
+        .queries
+        .iter()
+        .map(|golden| {
+            let relevant = golden
+                .expected
+                .iter()
+                .map(|id| {
+                    resolve_hash(db, id)?
+                        .map(|hash| (id.clone(), hash))
+                        .ok_or_else(|| {
+                            AgentRootError::InvalidInput(format!(
+                                "Expected document '{}' for query '{}' is not in the index",
+                                id, golden.query
+                            ))
+                        })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(ResolvedQuery { golden, relevant })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut reports = Vec::new();
+    for &mode in modes {
+        if mode.needs_embeddings() {
+            if embedder.is_none() {
+                reports.push(ModeEval::skipped(
+                    mode,
+                    k,
+                    "no embedding service configured".to_string(),
+                ));
+                continue;
+            }
+            if !db.has_vector_index() {
+                reports.push(ModeEval::skipped(
+                    mode,
+                    k,
+                    "no embeddings; run 'agentroot embed'".to_string(),
+                ));
+                continue;
+            }
+        }
+
+        match evaluate_mode(db, &resolved, mode, k, embedder).await {
+            Ok(report) => reports.push(report),
+            Err(e) => reports.push(ModeEval::skipped(mode, k, e.to_string())),
+        }
+    }
+    Ok(reports)
+}
+
+async fn evaluate_mode(
+    db: &Database,
+    queries: &[ResolvedQuery<'_>],
+    mode: EvalMode,
+    k: usize,
+    embedder: Option<&dyn Embedder>,
+) -> Result<ModeEval> {
+    let mut scores = Vec::new();
+    for q in queries {
+        let options = SearchOptions {
+            limit: k,
+            collection: q.golden.collection.clone(),
+            detail: DetailLevel::L0,
+            ..Default::default()
+        };
+        let results = match (mode, embedder) {
+            (EvalMode::Bm25, _) => db.search_fts(&q.golden.query, &options)?,
+            (EvalMode::Vector, Some(embedder)) => {
+                db.search_vec(&q.golden.query, embedder, &options).await?
+            }
+            (EvalMode::Hybrid, Some(embedder)) => {
+                hybrid_search(db, &q.golden.query, &options, embedder, None, None).await?
+            }
+            (EvalMode::Smart, _) => smart_search(db, &q.golden.query, &options).await?,
+            (_, None) => unreachable!("embedding modes are skipped without an embedder"),
+        };
+
+        // Chunk results repeat documents; rank each document once
+        let mut seen = HashSet::new();
+        let ranked: Vec<String> = results
+            .into_iter()
+            .map(|r| r.hash)
+            .filter(|hash| seen.insert(hash.clone()))
+            .collect();
+        let relevant: HashSet<String> = q.relevant.iter().map(|(_, h)| h.clone()).collect();
+        let (recall, reciprocal_rank, ndcg) = score_ranking(&ranked, &relevant, k);
+        let top: HashSet<&String> = ranked.iter().take(k).collect();
+        scores.push(QueryEval {
+            query: q.golden.query.clone(),
+            recall,
+            reciprocal_rank,
+            ndcg,
+            missing: q
+                .relevant
+                .iter()
+                .filter(|(_, hash)| !top.contains(hash))
+                .map(|(id, _)| id.clone())
+                .collect(),
+        });
+    }
+
+    let mean = |f: fn(&QueryEval) -> f64| scores.iter().map(f).sum::<f64>() / scores.len() as f64;
+    Ok(ModeEval {
+        mode,
+        k,
+        recall: mean(|q| q.recall),
+        mrr: mean(|q| q.reciprocal_rank),
+        ndcg: mean(|q| q.ndcg),
+        queries: scores,
+        skipped: None,
+    })
+}
+
+fn resolve_hash(db: &Database, id: &str) -> Result<Option<String>> {
+    let Some(doc_id) = db.resolve_document_id(id)? else {
+        return Ok(None);
+    };
+    Ok(Some(db.conn.query_row(
+        "SELECT hash FROM documents WHERE id = ?1",
+        params![doc_id],
+        |row| row.get(0),
+    )?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(hashes: &[&str]) -> HashSet<String> {
+        hashes.iter().map(|h| h.to_string()).collect()
+    }
+
+    fn ranking(hashes: &[&str]) -> Vec<String> {
+        hashes.iter().map(|h| h.to_string()).collect()
+    }
+
+    #[test]
+    fn test_score_ranking() {
+        let (recall, rr, ndcg) = score_ranking(&ranking(&["a", "b", "c"]), &set(&["a", "b"]), 10);
+        assert_eq!((recall, rr), (1.0, 1.0));
+        assert!((ndcg - 1.0).abs() < 1e-9);
+
+        let (recall, rr, ndcg) = score_ranking(&ranking(&["x", "a", "y"]), &set(&["a", "b"]), 10);
+        assert_eq!((recall, rr), (0.5, 0.5));
+        let expected = (1.0 / 3f64.log2()) / (1.0 + 1.0 / 3f64.log2());
+        assert!((ndcg - expected).abs() < 1e-9);
+
+        // Hits past the cutoff do not count
+        assert_eq!(
+            score_ranking(&ranking(&["x", "y", "a"]), &set(&["a"]), 2),
+            (0.0, 0.0, 0.0)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_bm25() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = chrono::Utc::now().to_rfc3339();
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+        for (hash, path, body) in [
+            ("aaa111", "oauth.md", "OAuth token refresh flow"),
+            ("bbb222", "sql.md", "SQL schema migrations"),
+        ] {
+            db.insert_content(hash, body).unwrap();
+            db.insert_document("notes", path, path, hash, &now, &now, "file", None)
+                .unwrap();
+        }
+
+        let set = EvalSet::from_yaml(
+            "queries:\n  - query: token refresh\n    expected: ['#aaa111']\n  - query: migrations\n    expected: [notes/oauth.md]\n",
+        )
+        .unwrap();
+        assert_eq!(set.k, DEFAULT_EVAL_K);
+
+        let modes = [EvalMode::Bm25, EvalMode::Vector, EvalMode::Hybrid];
+        let reports = evaluate(&db, &set, &modes, set.k, None).await.unwrap();
+        let bm25 = &reports[0];
+        assert_eq!(bm25.mode, EvalMode::Bm25);
+        assert_eq!(bm25.recall, 0.5);
+        assert_eq!(bm25.queries[1].missing, vec!["notes/oauth.md"]);
+        assert!(reports[1].skipped.is_some());
+        assert!(reports[2].skipped.is_some());
+
+        let stale =
+            EvalSet::from_yaml("queries:\n  - query: x\n    expected: [notes/gone.md]\n").unwrap();
+        assert!(evaluate(&db, &stale, &[EvalMode::Bm25], 10, None)
+            .await
+            .is_err());
+    }
+}
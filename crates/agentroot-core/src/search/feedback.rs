@@ -0,0 +1,75 @@
+//! Relevance-feedback ranking adjustments
+
+use super::SearchResult;
+use crate::db::Database;
+use crate::error::Result;
+
+/// Scale scores by stored relevance judgments and re-sort.
+/// No-op when no feedback has been recorded.
+pub fn apply_feedback(db: &Database, query: &str, results: &mut [SearchResult]) -> Result<()> {
+    if results.is_empty() {
+        return Ok(());
+    }
+    let adjustments = db.feedback_adjustments(query)?;
+    if adjustments.is_empty() {
+        return Ok(());
+    }
+
+    for result in results.iter_mut() {
+        if let Some(multiplier) = adjustments.get(&result.display_path) {
+            result.score *= multiplier;
+        }
+    }
+
+    results.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::Database;
+    use crate::search::SearchOptions;
+    use chrono::Utc;
+
+    #[test]
+    fn test_feedback_reorders_bm25_results() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+        for (hash, path, body) in [
+            ("aaa111", "a.md", "oauth oauth oauth token refresh"),
+            (
+                "bbb222",
+                "b.md",
+                "notes that mention oauth once among many other words",
+            ),
+        ] {
+            db.insert_content(hash, body).unwrap();
+            db.insert_document("notes", path, path, hash, &now, &now, "file", None)
+                .unwrap();
+        }
+
+        let options = SearchOptions {
+            limit: 10,
+            ..Default::default()
+        };
+        let before = db.search_fts("oauth", &options).unwrap();
+        assert_eq!(before.len(), 2);
+        let (top, runner_up) = (&before[0], &before[1]);
+
+        db.record_feedback("oauth", &top.display_path, false, None)
+            .unwrap();
+        db.record_feedback("oauth", &runner_up.display_path, true, None)
+            .unwrap();
+
+        let after = db.search_fts("oauth", &options).unwrap();
+        assert_eq!(after[0].display_path, runner_up.display_path);
+    }
+}
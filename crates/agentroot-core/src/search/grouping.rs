@@ -0,0 +1,182 @@
+//! Grouping of chunk hits by document
+//!
+//! Chunk search returns each matching function or section on its own, so a
+//! file with ten matching chunks fills the result list. Grouping nests the
+//! hits under their document, scores the document from all of its hits and
+//! picks one chunk's snippet to represent it.
+
+use super::{extract_snippet, SearchResult};
+use std::collections::HashMap;
+
+/// Length of a group's representative snippet
+const SNIPPET_LENGTH: usize = 300;
+
+/// Weight of each further hit relative to the one before it when
+/// aggregating scores
+const HIT_DECAY: f64 = 0.5;
+
+/// Chunk hits of one document
+#[derive(Debug, Clone)]
+pub struct DocumentGroup {
+    pub docid: String,
+    pub hash: String,
+    pub display_path: String,
+    pub title: String,
+    pub collection_name: String,
+    /// Aggregate score of the document's hits
+    pub score: f64,
+    /// Index in `chunks` of the hit the snippet comes from
+    pub best: usize,
+    pub snippet: String,
+    /// Hits in file order
+    pub chunks: Vec<SearchResult>,
+}
+
+/// Nest chunk hits under their documents, best documents first
+///
+/// A document scores as a noisy-or of its hits, each hit after the best
+/// weighted by [`HIT_DECAY`] more than the one before, so several good hits
+/// beat a single one without long files winning on volume. The snippet
+/// comes from the best hit, ties going to the hit covering more query terms.
+pub fn group_by_document(results: Vec<SearchResult>, query: &str) -> Vec<DocumentGroup> {
+    let mut order: Vec<String> = Vec::new();
+    let mut by_hash: HashMap<String, Vec<SearchResult>> = HashMap::new();
+    for result in results {
+        if !by_hash.contains_key(&result.hash) {
+            order.push(result.hash.clone());
+        }
+        by_hash.entry(result.hash.clone()).or_default().push(result);
+    }
+
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .filter(|t| !t.contains(':'))
+        .map(str::to_lowercase)
+        .collect();
+
+    let mut groups: Vec<DocumentGroup> = order
+        .into_iter()
+        .filter_map(|hash| by_hash.remove(&hash))
+        .map(|chunks| into_group(chunks, query, &terms))
+        .collect();
+    groups.sort_by(|a, b| b.score.total_cmp(&a.score));
+    groups
+}
+
+fn into_group(mut chunks: Vec<SearchResult>, query: &str, terms: &[String]) -> DocumentGroup {
+    let mut scores: Vec<f64> = chunks.iter().map(|c| c.score.clamp(0.0, 1.0)).collect();
+    scores.sort_by(|a, b| b.total_cmp(a));
+    let miss: f64 = scores
+        .iter()
+        .enumerate()
+        .map(|(i, s)| 1.0 - s * HIT_DECAY.powi(i as i32))
+        .product();
+
+    chunks.sort_by_key(|c| c.chunk_start_line.unwrap_or(0));
+    let best = (0..chunks.len())
+        .max_by(|&a, &b| {
+            chunks[a]
+                .score
+                .total_cmp(&chunks[b].score)
+                .then_with(|| term_hits(&chunks[a], terms).cmp(&term_hits(&chunks[b], terms)))
+                .then(b.cmp(&a))
+        })
+        .unwrap_or(0);
+    let text = chunk_text(&chunks[best]);
+    let snippet = extract_snippet(text, query, Some(SNIPPET_LENGTH), None).snippet;
+
+    let first = &chunks[0];
+    DocumentGroup {
+        docid: first.docid.clone(),
+        hash: first.hash.clone(),
+        display_path: first.display_path.clone(),
+        title: first.title.clone(),
+        collection_name: first.collection_name.clone(),
+        score: 1.0 - miss,
+        best,
+        snippet,
+        chunks,
+    }
+}
+
+fn chunk_text(chunk: &SearchResult) -> &str {
+    chunk
+        .body
+        .as_deref()
+        .or(chunk.chunk_summary.as_deref())
+        .unwrap_or_default()
+}
+
+fn term_hits(chunk: &SearchResult, terms: &[String]) -> usize {
+    let text = chunk_text(chunk).to_lowercase();
+    terms.iter().filter(|t| text.contains(t.as_str())).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::SearchSource;
+
+    fn make_chunk(hash: &str, start: i32, score: f64, body: &str) -> SearchResult {
+        SearchResult {
+            filepath: format!("agentroot://test/{}.rs", hash),
+            display_path: format!("test/{}.rs", hash),
+            title: hash.to_string(),
+            hash: hash.to_string(),
+            collection_name: "test".to_string(),
+            modified_at: "".to_string(),
+            body: Some(body.to_string()),
+            body_length: body.len(),
+            docid: hash.to_string(),
+            context: None,
+            score,
+            source: SearchSource::Bm25,
+            chunk_pos: None,
+            llm_summary: None,
+            llm_title: None,
+            llm_keywords: None,
+            llm_category: None,
+            llm_difficulty: None,
+            user_metadata: None,
+            is_chunk: true,
+            chunk_hash: Some(format!("{}-{}", hash, start)),
+            chunk_type: None,
+            chunk_breadcrumb: None,
+            chunk_start_line: Some(start),
+            chunk_end_line: Some(start + 5),
+            chunk_language: None,
+            chunk_summary: None,
+            chunk_purpose: None,
+            chunk_concepts: vec![],
+            chunk_labels: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_group_by_document_nests_and_aggregates() {
+        let results = vec![
+            make_chunk("a", 40, 0.75, "fn evict() { cache.clear() }"),
+            make_chunk("b", 1, 0.7, "fn load() { cache.get() }"),
+            make_chunk("b", 20, 0.7, "fn store() { cache.put() } // cache store"),
+            make_chunk("a", 10, 0.2, "fn size() {}"),
+        ];
+        let groups = group_by_document(results, "cache store");
+
+        assert_eq!(groups.len(), 2);
+        // Two good hits outrank one better hit
+        assert_eq!(groups[0].hash, "b");
+        assert!((groups[0].score - (1.0 - 0.3 * 0.65)).abs() < 1e-9);
+        assert!((groups[1].score - (1.0 - 0.25 * 0.9)).abs() < 1e-9);
+
+        // Chunks in file order; equal scores pick the hit with more terms
+        let lines: Vec<i32> = groups[1]
+            .chunks
+            .iter()
+            .map(|c| c.chunk_start_line.unwrap())
+            .collect();
+        assert_eq!(lines, vec![10, 40]);
+        assert_eq!(groups[1].best, 1);
+        assert_eq!(groups[0].best, 1);
+        assert!(groups[0].snippet.contains("store"));
+    }
+}
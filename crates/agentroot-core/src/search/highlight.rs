@@ -0,0 +1,138 @@
+//! Match ranges of a query in document text
+//!
+//! Locates the query's terms in a document so `get` can point readers of a
+//! long file at the passages a search matched.
+
+use super::parse_metadata_filters;
+use serde::Serialize;
+
+/// Occurrence of query terms on one line
+///
+/// Lines and columns are 1-based and count characters; `end_col` is
+/// exclusive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MatchRange {
+    pub line: usize,
+    pub start_col: usize,
+    pub end_col: usize,
+}
+
+/// Case-insensitive occurrences of the terms of `query` in `content`
+///
+/// Metadata filters and stop words are ignored, as in search, and terms
+/// match inside longer words so "cache" also marks "caches". Overlapping
+/// or adjacent ranges on a line are merged.
+pub fn find_match_ranges(content: &str, query: &str) -> Vec<MatchRange> {
+    let terms = highlight_terms(query);
+    if terms.is_empty() {
+        return Vec::new();
+    }
+
+    let mut ranges = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let chars: Vec<char> = line.chars().map(fold).collect();
+        let mut spans: Vec<(usize, usize)> = Vec::new();
+        for term in &terms {
+            if term.len() > chars.len() {
+                continue;
+            }
+            for start in 0..=chars.len() - term.len() {
+                if chars[start..start + term.len()] == term[..] {
+                    spans.push((start, start + term.len()));
+                }
+            }
+        }
+        spans.sort_unstable();
+
+        let mut merged: Vec<(usize, usize)> = Vec::new();
+        for (start, end) in spans {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        ranges.extend(merged.into_iter().map(|(start, end)| MatchRange {
+            line: i + 1,
+            start_col: start + 1,
+            end_col: end + 1,
+        }));
+    }
+    ranges
+}
+
+/// Lower-cased terms of a query, longest first
+fn highlight_terms(query: &str) -> Vec<Vec<char>> {
+    let (clean_query, _) = parse_metadata_filters(query);
+    let mut terms: Vec<Vec<char>> = Vec::new();
+    for word in clean_query.split(|c: char| !c.is_alphanumeric() && c != '_') {
+        let term: Vec<char> = word.chars().map(fold).collect();
+        if term.len() >= 2 && !terms.contains(&term) {
+            terms.push(term);
+        }
+    }
+    terms.sort_by_key(|t| std::cmp::Reverse(t.len()));
+    terms
+}
+
+/// Lower-case a character without changing the character count
+fn fold(c: char) -> char {
+    let mut lower = c.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some(l), None) => l,
+        _ => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_match_ranges_merges_and_counts_chars() {
+        let content = "# Caches\n\nThe cache_key is hashed.\nÜber cache cache\nnothing here";
+        let ranges = find_match_ranges(content, "the cache key category:guide");
+
+        // "the" is a stop word and the filter is not a term
+        assert_eq!(
+            ranges,
+            vec![
+                MatchRange {
+                    line: 1,
+                    start_col: 3,
+                    end_col: 8
+                },
+                MatchRange {
+                    line: 3,
+                    start_col: 5,
+                    end_col: 10
+                },
+                MatchRange {
+                    line: 3,
+                    start_col: 11,
+                    end_col: 14
+                },
+                MatchRange {
+                    line: 4,
+                    start_col: 6,
+                    end_col: 11
+                },
+                MatchRange {
+                    line: 4,
+                    start_col: 12,
+                    end_col: 17
+                },
+            ]
+        );
+        assert!(find_match_ranges(content, "a").is_empty());
+
+        // Adjacent occurrences merge into one range
+        assert_eq!(
+            find_match_ranges("cachecache", "cache"),
+            vec![MatchRange {
+                line: 1,
+                start_col: 1,
+                end_col: 11
+            }]
+        );
+    }
+}
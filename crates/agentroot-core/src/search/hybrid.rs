@@ -1,17 +1,24 @@
 //! Hybrid search with Reciprocal Rank Fusion
 
-use super::{SearchOptions, SearchResult, SearchSource};
+use super::slow_log::{log_if_slow, QueryTimer};
+use super::{importance_boost, SearchOptions, SearchResult, SearchSource};
+use crate::config::RankingWeights;
+use crate::db::vectors::cosine_similarity;
 use crate::db::Database;
 use crate::error::Result;
 use crate::llm::{Embedder, QueryExpander, RerankDocument, Reranker};
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 
-/// RRF constant (standard value)
-const RRF_K: f64 = 60.0;
-
 /// Maximum documents to send to reranker (reduced for LLM token limits)
 const MAX_RERANK_DOCS: usize = 10;
 
+/// Number of top BM25 hits used to seed personalized PageRank
+const PPR_SEED_COUNT: usize = 5;
+
+/// Age at which the recency boost has halved
+const RECENCY_HALF_LIFE_DAYS: f64 = 30.0;
+
 /// Strong signal threshold
 const STRONG_SIGNAL_SCORE: f64 = 0.85;
 const STRONG_SIGNAL_GAP: f64 = 0.15;
@@ -38,7 +45,15 @@ pub fn cap_for_reranking(results: Vec<SearchResult>) -> Vec<SearchResult> {
 }
 
 /// Position-aware score blending
-pub fn blend_scores(rrf_rank: usize, rrf_score: f64, rerank_score: f64) -> f64 {
+///
+/// `reranker_weight` scales the reranker's share; 1.0 is the default blend
+/// and 0.0 keeps the fused score.
+pub fn blend_scores(
+    rrf_rank: usize,
+    rrf_score: f64,
+    rerank_score: f64,
+    reranker_weight: f64,
+) -> f64 {
     let rrf_weight = if rrf_rank <= 3 {
         0.75 // Trust retrieval for top results
     } else if rrf_rank <= 10 {
@@ -46,55 +61,61 @@ pub fn blend_scores(rrf_rank: usize, rrf_score: f64, rerank_score: f64) -> f64 {
     } else {
         0.40 // Trust reranker for lower-ranked
     };
+    let rerank_weight = (1.0 - rrf_weight) * reranker_weight.max(0.0);
 
-    rrf_weight * rrf_score + (1.0 - rrf_weight) * rerank_score
+    (rrf_weight * rrf_score + rerank_weight * rerank_score) / (rrf_weight + rerank_weight)
 }
 
-/// Reciprocal Rank Fusion
+/// Reciprocal Rank Fusion with the default weights
 pub fn rrf_fusion(
     bm25_results: &[SearchResult],
     vec_results: &[SearchResult],
 ) -> Vec<SearchResult> {
-    let mut scores: HashMap<String, (f64, SearchResult)> = HashMap::new();
-
-    // Process BM25 results (weight 2x)
-    for (rank, result) in bm25_results.iter().enumerate() {
-        let rrf_score = 2.0 / (RRF_K + (rank + 1) as f64);
-        // Bonus for appearing in top 3
-        let bonus = if rank < 3 {
-            0.05
-        } else if rank < 10 {
-            0.02
-        } else {
-            0.0
-        };
+    rrf_fusion_weighted(bm25_results, vec_results, &RankingWeights::default())
+}
 
-        let entry = scores
-            .entry(result.hash.clone())
-            .or_insert((0.0, result.clone()));
-        entry.0 += rrf_score + bonus;
-    }
-
-    // Process vector results
-    for (rank, result) in vec_results.iter().enumerate() {
-        let rrf_score = 1.0 / (RRF_K + (rank + 1) as f64);
-        let bonus = if rank < 3 {
-            0.05
-        } else if rank < 10 {
-            0.02
-        } else {
-            0.0
-        };
+/// Reciprocal Rank Fusion of BM25 and vector rankings
+pub fn rrf_fusion_weighted(
+    bm25_results: &[SearchResult],
+    vec_results: &[SearchResult],
+    weights: &RankingWeights,
+) -> Vec<SearchResult> {
+    let mut scores: HashMap<String, (f64, SearchResult)> = HashMap::new();
+    let rrf_k = if weights.rrf_k > 0.0 {
+        weights.rrf_k
+    } else {
+        RankingWeights::default().rrf_k
+    };
 
-        let entry = scores
-            .entry(result.hash.clone())
-            .or_insert((0.0, result.clone()));
-        entry.0 += rrf_score + bonus;
+    for (results, weight) in [(bm25_results, weights.bm25), (vec_results, weights.vector)] {
+        if weight <= 0.0 {
+            continue;
+        }
+        for (rank, result) in results.iter().enumerate() {
+            let rrf_score = weight / (rrf_k + (rank + 1) as f64);
+            // Bonus for appearing near the top
+            let bonus = if rank < 3 {
+                0.05
+            } else if rank < 10 {
+                0.02
+            } else {
+                0.0
+            };
+
+            let entry = scores
+                .entry(result.hash.clone())
+                .or_insert((0.0, result.clone()));
+            entry.0 += rrf_score + bonus;
+        }
     }
 
     // Sort by score
     let mut results: Vec<(f64, SearchResult)> = scores.into_values().collect();
-    results.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+    results.sort_by(|a, b| {
+        b.0.partial_cmp(&a.0)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.1.hash.cmp(&b.1.hash))
+    });
 
     results
         .into_iter()
@@ -106,7 +127,204 @@ pub fn rrf_fusion(
         .collect()
 }
 
+/// Boost results by their (max-normalized) graph rank and re-sort
+pub fn apply_graph_boost(results: &mut [SearchResult], ranks: &HashMap<String, f64>, weight: f64) {
+    let max_rank = ranks.values().cloned().fold(0.0, f64::max);
+    if max_rank <= 0.0 || weight <= 0.0 {
+        return;
+    }
+
+    for result in results.iter_mut() {
+        if let Some(rank) = ranks.get(&result.hash) {
+            result.score *= 1.0 + weight * (rank / max_rank);
+        }
+    }
+
+    results.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+}
+
+/// Scale results by their documents' PageRank importance and re-sort
+///
+/// Documents missing from `importance` are left alone.
+pub fn apply_importance_boost(
+    results: &mut [SearchResult],
+    importance: &HashMap<String, f64>,
+    weight: f64,
+) {
+    if weight <= 0.0 {
+        return;
+    }
+
+    for result in results.iter_mut() {
+        if let Some(&score) = importance.get(&result.hash) {
+            result.score *= importance_boost(score, weight);
+        }
+    }
+
+    results.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+}
+
+/// Boost recently modified results and re-sort
+///
+/// The boost halves every `RECENCY_HALF_LIFE_DAYS`; results with an
+/// unparseable modification time are left alone.
+pub fn apply_recency_boost(results: &mut [SearchResult], weight: f64, now: DateTime<Utc>) {
+    if weight <= 0.0 {
+        return;
+    }
+
+    for result in results.iter_mut() {
+        if let Ok(modified) = DateTime::parse_from_rfc3339(&result.modified_at) {
+            let age_days =
+                (now - modified.with_timezone(&Utc)).num_seconds().max(0) as f64 / 86_400.0;
+            result.score *= 1.0 + weight * 0.5f64.powf(age_days / RECENCY_HALF_LIFE_DAYS);
+        }
+    }
+
+    results.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+}
+
+/// Re-rank by Maximal Marginal Relevance
+///
+/// Repeatedly picks the result maximizing
+/// `lambda * relevance - (1 - lambda) * similarity`, where relevance is its
+/// score over the best score and similarity its highest similarity to a
+/// result already picked. A lambda of 1 keeps the order. A result moved below a less relevant one takes that
+/// one's score, so the list stays sorted by score.
+pub fn apply_mmr<F>(results: &mut Vec<SearchResult>, lambda: f64, similarity: F)
+where
+    F: Fn(&SearchResult, &SearchResult) -> f64,
+{
+    let top = results.iter().map(|r| r.score).fold(0.0, f64::max);
+    if lambda >= 1.0 || results.len() < 2 || top <= 0.0 {
+        return;
+    }
+    let lambda = lambda.max(0.0);
+
+    let mut pool = std::mem::take(results);
+    let mut redundancy = vec![0.0; pool.len()];
+    while !pool.is_empty() {
+        let mmr = |i: usize| lambda * pool[i].score / top - (1.0 - lambda) * redundancy[i];
+        // Ties go to the more relevant, earlier result
+        let best = (0..pool.len())
+            .max_by(|&a, &b| {
+                mmr(a)
+                    .partial_cmp(&mmr(b))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then(b.cmp(&a))
+            })
+            .unwrap_or(0);
+        let picked = pool.remove(best);
+        redundancy.remove(best);
+        for (candidate, seen) in pool.iter().zip(redundancy.iter_mut()) {
+            *seen = f64::max(*seen, similarity(candidate, &picked));
+        }
+        results.push(picked);
+    }
+
+    for i in 1..results.len() {
+        results[i].score = results[i].score.min(results[i - 1].score);
+    }
+}
+
+/// Similarity of two results for MMR, 0.0 - 1.0
+///
+/// Results from the same document count as identical. Otherwise their stored
+/// embeddings are compared, falling back to the overlap of their words.
+fn result_similarity(
+    a: &SearchResult,
+    b: &SearchResult,
+    embeddings: &HashMap<String, Vec<f32>>,
+) -> f64 {
+    if a.hash == b.hash {
+        return 1.0;
+    }
+    match (
+        embeddings.get(embedding_key(a)),
+        embeddings.get(embedding_key(b)),
+    ) {
+        (Some(x), Some(y)) => (cosine_similarity(x, y) as f64).max(0.0),
+        _ => word_overlap(a, b),
+    }
+}
+
+fn embedding_key(result: &SearchResult) -> &str {
+    result.chunk_hash.as_deref().unwrap_or(&result.hash)
+}
+
+/// Stored embeddings of results, keyed by chunk hash or document hash
+fn result_embeddings(db: &Database, results: &[SearchResult]) -> Result<HashMap<String, Vec<f32>>> {
+    let mut embeddings = HashMap::new();
+    if !db.has_vector_index() {
+        return Ok(embeddings);
+    }
+    for result in results {
+        if let Some(embedding) =
+            db.get_stored_embedding(&result.hash, result.chunk_hash.as_deref())?
+        {
+            embeddings.insert(embedding_key(result).to_string(), embedding);
+        }
+    }
+    Ok(embeddings)
+}
+
+/// Jaccard similarity of the words of two results' text
+fn word_overlap(a: &SearchResult, b: &SearchResult) -> f64 {
+    let words = |r: &SearchResult| -> std::collections::HashSet<String> {
+        r.body
+            .as_deref()
+            .or(r.chunk_summary.as_deref())
+            .or(r.llm_summary.as_deref())
+            .unwrap_or(&r.title)
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    };
+    let (a, b) = (words(a), words(b));
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+/// Promote results that are structurally central to the query's top BM25 hits
+fn apply_personalized_pagerank(
+    db: &Database,
+    bm25_results: &[SearchResult],
+    fused: &mut [SearchResult],
+    weight: f64,
+) -> Result<()> {
+    if bm25_results.is_empty() || weight <= 0.0 || db.count_document_links()? == 0 {
+        return Ok(());
+    }
+
+    let seeds: Vec<(String, f64)> = bm25_results
+        .iter()
+        .take(PPR_SEED_COUNT)
+        .map(|r| (r.hash.clone(), r.score.max(f64::EPSILON)))
+        .collect();
+    let ranks = db.personalized_pagerank_by_hash(&seeds)?;
+    apply_graph_boost(fused, &ranks, weight);
+
+    Ok(())
+}
+
 /// Full hybrid search pipeline
+#[tracing::instrument(name = "hybrid_search", skip_all, fields(query = %query))]
 pub async fn hybrid_search(
     db: &Database,
     query: &str,
@@ -115,8 +333,31 @@ pub async fn hybrid_search(
     expander: Option<&dyn QueryExpander>,
     reranker: Option<&dyn Reranker>,
 ) -> Result<Vec<SearchResult>> {
+    let mut timer = QueryTimer::new();
+    let window = options.page_window();
+    let mut results =
+        run_hybrid(db, query, &window, embedder, expander, reranker, &mut timer).await?;
+    super::pinning::apply_pins(db, query, &window, &mut results)?;
+    results.truncate(window.limit);
+    let results = super::take_page(results, options);
+    timer.stage("pins");
+    log_if_slow(db, "hybrid", query, options, &timer, results.len());
+    Ok(results)
+}
+
+async fn run_hybrid(
+    db: &Database,
+    query: &str,
+    options: &SearchOptions,
+    embedder: &dyn Embedder,
+    expander: Option<&dyn QueryExpander>,
+    reranker: Option<&dyn Reranker>,
+    timer: &mut QueryTimer,
+) -> Result<Vec<SearchResult>> {
+    let weights = options.ranking_weights();
+
     // 1. Initial BM25 search
-    let bm25_results = db.search_fts(query, options)?;
+    let bm25_results = db.fts_search(query, options, timer)?;
 
     // 2. Check for strong signal
     if has_strong_signal(&bm25_results) {
@@ -124,7 +365,9 @@ pub async fn hybrid_search(
     }
 
     // 3. Vector search
-    let vec_results = db.search_vec(query, embedder, options).await?;
+    let vec_results = db
+        .vector_search(query, embedder, options, None, timer)
+        .await?;
 
     // 4. Query expansion (if available and not skipped)
     let mut all_bm25 = bm25_results.clone();
@@ -132,28 +375,50 @@ pub async fn hybrid_search(
 
     if let Some(exp) = expander {
         let expanded = exp.expand(query, None).await?;
+        timer.stage("expand");
 
         // Run lexical variations
         for lex_query in &expanded.lexical {
-            let results = db.search_fts(lex_query, options)?;
+            let results = db.fts_search(lex_query, options, timer)?;
             all_bm25.extend(results);
         }
 
         // Run semantic variations
         for vec_query in &expanded.semantic {
-            let results = db.search_vec(vec_query, embedder, options).await?;
+            let results = db
+                .vector_search(vec_query, embedder, options, None, timer)
+                .await?;
             all_vec.extend(results);
         }
 
         // Run HyDE if present
         if let Some(ref hyde) = expanded.hyde {
-            let results = db.search_vec(hyde, embedder, options).await?;
+            let results = db
+                .vector_search(hyde, embedder, options, None, timer)
+                .await?;
             all_vec.extend(results);
         }
     }
 
     // 5. RRF fusion
-    let mut fused = rrf_fusion(&all_bm25, &all_vec);
+    let mut fused = tracing::info_span!("rrf_fusion")
+        .in_scope(|| rrf_fusion_weighted(&all_bm25, &all_vec, &weights));
+    timer.stage("fusion");
+
+    // Favour heavily linked documents, then those central to this query's
+    // neighborhood of the link graph
+    if weights.importance > 0.0 {
+        let mut importance = HashMap::new();
+        for result in &fused {
+            importance.insert(result.hash.clone(), db.importance_by_hash(&result.hash)?);
+        }
+        apply_importance_boost(&mut fused, &importance, weights.importance);
+    }
+    tracing::info_span!("graph_boost").in_scope(|| {
+        apply_personalized_pagerank(db, &bm25_results, &mut fused, weights.pagerank)
+    })?;
+    apply_recency_boost(&mut fused, weights.recency, Utc::now());
+    timer.stage("graph_boost");
 
     // 6. Cap for reranking
     fused = cap_for_reranking(fused);
@@ -206,7 +471,8 @@ pub async fn hybrid_search(
         for (rrf_rank, result) in fused.iter_mut().enumerate() {
             if let Some(&rerank_score) = rerank_scores.get(&result.hash) {
                 let rrf_score = result.score;
-                result.score = blend_scores(rrf_rank + 1, rrf_score, rerank_score);
+                result.score =
+                    blend_scores(rrf_rank + 1, rrf_score, rerank_score, weights.reranker);
             }
         }
 
@@ -216,9 +482,19 @@ pub async fn hybrid_search(
                 .partial_cmp(&a.score)
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
+        timer.stage("rerank");
+    }
+
+    // 8. Spread the top results across distinct documents
+    if weights.mmr_lambda < 1.0 && fused.len() > 1 {
+        let embeddings = result_embeddings(db, &fused)?;
+        apply_mmr(&mut fused, weights.mmr_lambda, |a, b| {
+            result_similarity(a, b, &embeddings)
+        });
+        timer.stage("mmr");
     }
 
-    // 8. Apply final limit and min_score
+    // 9. Apply final limit and min_score
     let final_results: Vec<SearchResult> = fused
         .into_iter()
         .filter(|r| r.score >= options.min_score)
@@ -252,3 +528,194 @@ impl Database {
         self.search_fts(query, options)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_result(hash: &str, score: f64) -> SearchResult {
+        SearchResult {
+            filepath: format!("agentroot://test/{}.md", hash),
+            display_path: format!("test/{}.md", hash),
+            title: hash.to_string(),
+            hash: hash.to_string(),
+            collection_name: "test".to_string(),
+            modified_at: "".to_string(),
+            body: None,
+            body_length: 0,
+            docid: hash.to_string(),
+            context: None,
+            score,
+            source: SearchSource::Hybrid,
+            chunk_pos: None,
+            llm_summary: None,
+            llm_title: None,
+            llm_keywords: None,
+            llm_category: None,
+            llm_difficulty: None,
+            user_metadata: None,
+            is_chunk: false,
+            chunk_hash: None,
+            chunk_type: None,
+            chunk_breadcrumb: None,
+            chunk_start_line: None,
+            chunk_end_line: None,
+            chunk_language: None,
+            chunk_summary: None,
+            chunk_purpose: None,
+            chunk_concepts: vec![],
+            chunk_labels: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_apply_graph_boost_promotes_central_documents() {
+        let mut results = vec![make_result("a", 0.10), make_result("b", 0.09)];
+        let ranks = HashMap::from([("b".to_string(), 0.5)]);
+
+        apply_graph_boost(&mut results, &ranks, 0.3);
+
+        assert_eq!(results[0].hash, "b");
+        assert!((results[0].score - 0.09 * 1.3).abs() < 1e-9);
+        assert_eq!(results[1].score, 0.10);
+    }
+
+    #[test]
+    fn test_apply_graph_boost_empty_ranks() {
+        let mut results = vec![make_result("a", 0.10), make_result("b", 0.09)];
+        apply_graph_boost(&mut results, &HashMap::new(), 0.3);
+        assert_eq!(results[0].hash, "a");
+        assert_eq!(results[0].score, 0.10);
+    }
+
+    #[test]
+    fn test_rrf_fusion_weighted() {
+        let bm25 = vec![make_result("a", 1.0), make_result("b", 0.5)];
+        let vec = vec![make_result("b", 0.9), make_result("c", 0.8)];
+
+        // Default weights favour BM25
+        let fused = rrf_fusion(&bm25, &vec);
+        assert_eq!(fused[0].hash, "b");
+        assert_eq!(fused[1].hash, "a");
+
+        // Vector-only ignores the BM25 ranking
+        let vector_only = RankingWeights {
+            bm25: 0.0,
+            ..Default::default()
+        };
+        let fused = rrf_fusion_weighted(&bm25, &vec, &vector_only);
+        let hashes: Vec<&str> = fused.iter().map(|r| r.hash.as_str()).collect();
+        assert_eq!(hashes, vec!["b", "c"]);
+    }
+
+    #[test]
+    fn test_alpha_splits_fusion_weight() {
+        let weights = RankingWeights::default().with_alpha(0.25);
+        assert!((weights.bm25 - 0.75).abs() < 1e-9);
+        assert!((weights.vector - 2.25).abs() < 1e-9);
+        assert_eq!(weights.pagerank, RankingWeights::default().pagerank);
+
+        let bm25 = vec![make_result("a", 1.0), make_result("b", 0.5)];
+        let vec = vec![make_result("c", 0.9), make_result("a", 0.8)];
+        let options = SearchOptions {
+            weights: Some(RankingWeights::default()),
+            alpha: Some(1.0),
+            ..Default::default()
+        };
+        let fused = rrf_fusion_weighted(&bm25, &vec, &options.ranking_weights());
+        let hashes: Vec<&str> = fused.iter().map(|r| r.hash.as_str()).collect();
+        assert_eq!(hashes, vec!["a", "b"]);
+
+        let options = SearchOptions {
+            alpha: Some(0.0),
+            ..options
+        };
+        let fused = rrf_fusion_weighted(&bm25, &vec, &options.ranking_weights());
+        let hashes: Vec<&str> = fused.iter().map(|r| r.hash.as_str()).collect();
+        assert_eq!(hashes, vec!["c", "a"]);
+    }
+
+    #[test]
+    fn test_blend_scores_reranker_weight() {
+        assert!((blend_scores(1, 0.8, 0.4, 1.0) - (0.75 * 0.8 + 0.25 * 0.4)).abs() < 1e-9);
+        assert!((blend_scores(1, 0.8, 0.4, 0.0) - 0.8).abs() < 1e-9);
+        assert!(blend_scores(12, 0.8, 0.4, 2.0) < blend_scores(12, 0.8, 0.4, 1.0));
+    }
+
+    #[test]
+    fn test_apply_recency_boost() {
+        let now = Utc::now();
+        let mut old = make_result("old", 0.10);
+        old.modified_at = (now - chrono::Duration::days(365)).to_rfc3339();
+        let mut new = make_result("new", 0.09);
+        new.modified_at = now.to_rfc3339();
+        let mut results = vec![old, new];
+
+        apply_recency_boost(&mut results, 0.0, now);
+        assert_eq!(results[0].hash, "old");
+
+        apply_recency_boost(&mut results, 0.5, now);
+        assert_eq!(results[0].hash, "new");
+        assert!((results[0].score - 0.09 * 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_apply_mmr_demotes_near_duplicates() {
+        let mut results = vec![
+            make_result("a", 1.0),
+            make_result("a2", 0.95),
+            make_result("b", 0.8),
+        ];
+        let similar = |x: &SearchResult, y: &SearchResult| {
+            if x.hash[..1] == y.hash[..1] {
+                0.9
+            } else {
+                0.1
+            }
+        };
+
+        apply_mmr(&mut results, 1.0, similar);
+        let hashes: Vec<&str> = results.iter().map(|r| r.hash.as_str()).collect();
+        assert_eq!(hashes, vec!["a", "a2", "b"]);
+
+        apply_mmr(&mut results, 0.5, similar);
+        let hashes: Vec<&str> = results.iter().map(|r| r.hash.as_str()).collect();
+        assert_eq!(hashes, vec!["a", "b", "a2"]);
+        // Scores stay sorted: the demoted duplicate drops to b's score
+        assert_eq!(results[1].score, 0.8);
+        assert_eq!(results[2].score, 0.8);
+    }
+
+    #[test]
+    fn test_result_similarity() {
+        let mut a = make_result("a", 1.0);
+        a.body = Some("connection pool timeout settings".to_string());
+        let mut b = make_result("b", 0.9);
+        b.body = Some("Connection pool timeout".to_string());
+        let c = make_result("c", 0.8);
+
+        let none = HashMap::new();
+        assert_eq!(result_similarity(&a, &a.clone(), &none), 1.0);
+        assert!((result_similarity(&a, &b, &none) - 0.75).abs() < 1e-9);
+
+        let embeddings = HashMap::from([
+            ("a".to_string(), vec![1.0, 0.0]),
+            ("c".to_string(), vec![0.0, 1.0]),
+        ]);
+        assert_eq!(result_similarity(&a, &c, &embeddings), 0.0);
+    }
+
+    #[test]
+    fn test_apply_importance_boost() {
+        let mut results = vec![make_result("leaf", 0.10), make_result("hub", 0.08)];
+        let importance = HashMap::from([("leaf".to_string(), 1.0), ("hub".to_string(), 2.0)]);
+
+        apply_importance_boost(&mut results, &importance, 0.0);
+        assert_eq!(results[0].hash, "leaf");
+
+        apply_importance_boost(&mut results, &importance, 0.5);
+        assert_eq!(results[0].hash, "hub");
+        assert!((results[0].score - 0.08 * 1.5).abs() < 1e-9);
+        assert!((results[1].score - 0.10).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,331 @@
+//! Daily-notes retrieval
+//!
+//! Resolves date phrases such as "in March", "last week" or "on 2024-03-05"
+//! without an LLM, and searches notes by the day they are about.
+
+use super::{SearchOptions, SearchResult};
+use crate::db::Database;
+use crate::error::Result;
+use chrono::{Datelike, Duration, NaiveDate};
+
+/// An inclusive range of days
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl DateRange {
+    pub fn day(day: NaiveDate) -> Self {
+        Self { from: day, to: day }
+    }
+
+    pub fn month(year: i32, month: u32) -> Option<Self> {
+        let from = NaiveDate::from_ymd_opt(year, month, 1)?;
+        let next = if month == 12 {
+            NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+        } else {
+            NaiveDate::from_ymd_opt(year, month + 1, 1)?
+        };
+        Some(Self {
+            from,
+            to: next - Duration::days(1),
+        })
+    }
+
+    pub fn year(year: i32) -> Option<Self> {
+        Some(Self {
+            from: NaiveDate::from_ymd_opt(year, 1, 1)?,
+            to: NaiveDate::from_ymd_opt(year, 12, 31)?,
+        })
+    }
+
+    pub fn contains(&self, day: NaiveDate) -> bool {
+        self.from <= day && day <= self.to
+    }
+
+    /// BM25 metadata filters restricting results to the range
+    pub fn filters(&self) -> Vec<(String, String)> {
+        vec![
+            ("after".to_string(), self.from.to_string()),
+            ("before".to_string(), self.to.to_string()),
+        ]
+    }
+}
+
+const MONTHS: &[&str] = &[
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+/// Words that may introduce a date phrase and are dropped with it
+const DATE_PREPOSITIONS: &[&str] = &["in", "during", "on", "from", "of"];
+
+fn month_number(word: &str) -> Option<u32> {
+    if word.len() < 3 {
+        return None;
+    }
+    MONTHS
+        .iter()
+        .position(|m| *m == word || (word.len() <= 4 && m.starts_with(word) && word != "ma"))
+        .map(|i| i as u32 + 1)
+}
+
+fn year_number(word: &str) -> Option<i32> {
+    (word.len() == 4)
+        .then(|| word.parse::<i32>().ok())
+        .flatten()
+        .filter(|y| (1900..=2100).contains(y))
+}
+
+/// The most recent occurrence of a month, not after today's month
+fn latest_month(month: u32, today: NaiveDate) -> Option<DateRange> {
+    let year = if month > today.month() {
+        today.year() - 1
+    } else {
+        today.year()
+    };
+    DateRange::month(year, month)
+}
+
+/// Match a date phrase starting at `words[i]`; returns the range and its length
+fn phrase_at(words: &[String], i: usize, today: NaiveDate) -> Option<(DateRange, usize)> {
+    let word = words[i].as_str();
+    let next = words.get(i + 1).map(String::as_str);
+    let after_preposition = i > 0 && DATE_PREPOSITIONS.contains(&words[i - 1].as_str());
+
+    match (word, next) {
+        ("today", _) => return Some((DateRange::day(today), 1)),
+        ("yesterday", _) => return Some((DateRange::day(today - Duration::days(1)), 1)),
+        ("this" | "last" | "past", Some(unit @ ("week" | "month" | "year"))) => {
+            let previous = word != "this";
+            let range = match unit {
+                "week" => {
+                    let monday =
+                        today - Duration::days(today.weekday().num_days_from_monday() as i64);
+                    if previous {
+                        DateRange {
+                            from: monday - Duration::days(7),
+                            to: monday - Duration::days(1),
+                        }
+                    } else {
+                        DateRange {
+                            from: monday,
+                            to: today,
+                        }
+                    }
+                }
+                "month" if previous => {
+                    let last = today.with_day(1)? - Duration::days(1);
+                    DateRange::month(last.year(), last.month())?
+                }
+                "month" => DateRange {
+                    from: today.with_day(1)?,
+                    to: today,
+                },
+                _ if previous => DateRange::year(today.year() - 1)?,
+                _ => DateRange {
+                    from: NaiveDate::from_ymd_opt(today.year(), 1, 1)?,
+                    to: today,
+                },
+            };
+            return Some((range, 2));
+        }
+        _ => {}
+    }
+
+    if let Some(month) = month_number(word) {
+        if let Some(year) = next.and_then(year_number) {
+            return Some((DateRange::month(year, month)?, 2));
+        }
+        // A bare month needs a preposition, so "may" stays a verb
+        if after_preposition {
+            return Some((latest_month(month, today)?, 1));
+        }
+        return None;
+    }
+
+    if after_preposition {
+        if let Some(year) = year_number(word) {
+            return Some((DateRange::year(year)?, 1));
+        }
+    }
+
+    if let Ok(day) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+        return Some((DateRange::day(day), 1));
+    }
+    if let Ok(first) = NaiveDate::parse_from_str(&format!("{}-01", word), "%Y-%m-%d") {
+        if word.len() == 7 {
+            return Some((DateRange::month(first.year(), first.month())?, 1));
+        }
+    }
+    None
+}
+
+/// Split the first date phrase off a query
+///
+/// Returns the query without the phrase (and a preposition introducing it)
+/// and the range it names. The query is returned unchanged when it has no
+/// date phrase.
+pub fn parse_date_phrase(query: &str, today: NaiveDate) -> (String, Option<DateRange>) {
+    let original: Vec<&str> = query.split_whitespace().collect();
+    let words: Vec<String> = original
+        .iter()
+        .map(|w| {
+            w.trim_matches(|c: char| !c.is_alphanumeric() && c != '-')
+                .to_lowercase()
+        })
+        .collect();
+
+    for i in 0..words.len() {
+        if let Some((range, len)) = phrase_at(&words, i, today) {
+            let start = if i > 0 && DATE_PREPOSITIONS.contains(&words[i - 1].as_str()) {
+                i - 1
+            } else {
+                i
+            };
+            let rest: Vec<&str> = original[..start]
+                .iter()
+                .chain(&original[i + len..])
+                .copied()
+                .collect();
+            return (rest.join(" "), Some(range));
+        }
+    }
+    (query.to_string(), None)
+}
+
+/// Drop results whose day falls outside the range
+///
+/// A document's day is its note date, else the day it was last modified.
+pub fn retain_in_range(
+    db: &Database,
+    results: &mut Vec<SearchResult>,
+    range: DateRange,
+) -> Result<()> {
+    let hashes: Vec<&str> = results.iter().map(|r| r.hash.as_str()).collect();
+    let dates = db.document_dates(&hashes)?;
+    results.retain(|r| dates.get(&r.hash).is_some_and(|d| range.contains(*d)));
+    Ok(())
+}
+
+/// BM25 search over dated notes, optionally within a range
+pub fn journal_search(
+    db: &Database,
+    query: &str,
+    range: Option<DateRange>,
+    options: &SearchOptions,
+) -> Result<Vec<SearchResult>> {
+    let mut options = options.clone();
+    options
+        .metadata_filters
+        .push(("dated".to_string(), "true".to_string()));
+    if let Some(range) = range {
+        options.metadata_filters.extend(range.filters());
+    }
+    db.search_fts(query, &options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_parse_date_phrase() {
+        // A Wednesday
+        let today = day(2024, 6, 12);
+        let parse = |q: &str| parse_date_phrase(q, today);
+
+        assert_eq!(
+            parse("what did I note about postgres in March?"),
+            (
+                "what did I note about postgres".to_string(),
+                DateRange::month(2024, 3)
+            )
+        );
+        // Months after the current one refer to last year
+        assert_eq!(
+            parse("deploys during october").1,
+            DateRange::month(2023, 10)
+        );
+        assert_eq!(parse("sept 2022 retro").1, DateRange::month(2022, 9));
+        assert_eq!(
+            parse("standup last week"),
+            (
+                "standup".to_string(),
+                Some(DateRange {
+                    from: day(2024, 6, 3),
+                    to: day(2024, 6, 9)
+                })
+            )
+        );
+        assert_eq!(parse("this month").1.unwrap().from, day(2024, 6, 1));
+        assert_eq!(parse("last month").1, DateRange::month(2024, 5));
+        assert_eq!(parse("notes in 2023").1, DateRange::year(2023));
+        assert_eq!(
+            parse("on 2024-03-05 meeting"),
+            ("meeting".to_string(), Some(DateRange::day(day(2024, 3, 5))))
+        );
+        assert_eq!(parse("2024-02 budget").1, DateRange::month(2024, 2));
+        assert_eq!(parse("yesterday").1, Some(DateRange::day(day(2024, 6, 11))));
+
+        // Not date phrases
+        assert_eq!(parse("you may retry").1, None);
+        assert_eq!(parse("march of progress").1, None);
+        assert_eq!(parse("port 2024").1, None);
+    }
+
+    #[test]
+    fn test_journal_search() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = chrono::Utc::now().to_rfc3339();
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+        for (hash, path, body) in [
+            ("aaa111", "daily/2024-03-05.md", "Tuned postgres vacuum"),
+            ("bbb222", "daily/2024-04-02.md", "Postgres upgrade"),
+            ("ccc333", "postgres.md", "Postgres reference"),
+        ] {
+            db.insert_content(hash, body).unwrap();
+            let id = db
+                .insert_document("notes", path, path, hash, &now, &now, "file", None)
+                .unwrap();
+            db.refresh_note_date(id).unwrap();
+        }
+
+        let options = SearchOptions::default();
+        let hashes = |results: Vec<SearchResult>| -> Vec<String> {
+            let mut hashes: Vec<_> = results.into_iter().map(|r| r.hash).collect();
+            hashes.sort();
+            hashes
+        };
+        assert_eq!(
+            hashes(journal_search(&db, "postgres", DateRange::month(2024, 3), &options).unwrap()),
+            vec!["aaa111"]
+        );
+        assert_eq!(
+            hashes(journal_search(&db, "postgres", None, &options).unwrap()),
+            vec!["aaa111", "bbb222"]
+        );
+
+        // Undated documents fall back to their modification day
+        let mut all = db.search_fts("postgres", &options).unwrap();
+        retain_in_range(&db, &mut all, DateRange::month(2024, 3).unwrap()).unwrap();
+        assert_eq!(hashes(all), vec!["aaa111"]);
+    }
+}
@@ -7,10 +7,22 @@
 
 pub mod ann_index;
 mod bm25;
+pub mod context_pack;
 pub mod directory_boost;
+pub mod eval;
+pub mod feedback;
+pub mod grouping;
+pub mod highlight;
 mod hybrid;
+pub mod journal;
+mod multi_query;
 mod orchestrated;
+pub mod paging;
+pub mod pinning;
+pub mod query_parser;
 pub mod session_aware;
+mod similar;
+pub mod slow_log;
 mod smart;
 mod snippet;
 pub mod stats;
@@ -21,14 +33,21 @@ mod vector;
 mod workflow_executor;
 
 pub use ann_index::AnnIndex;
+pub use context_pack::{build_context, build_hybrid_context, ContextChunk, ContextPack};
+pub use grouping::{group_by_document, DocumentGroup};
+pub use highlight::{find_match_ranges, MatchRange};
 pub use hybrid::*;
-pub use orchestrated::orchestrated_search;
+pub use journal::{journal_search, parse_date_phrase, DateRange};
+pub use multi_query::multi_query_search;
+pub use orchestrated::{orchestrated_search, orchestrated_search_cancellable};
+pub use paging::{take_page, SearchCursor, PAGE_BLOCK};
+pub use query_parser::FtsQuery;
 pub use smart::smart_search;
 pub use snippet::*;
 pub use stats::{SearchStats, SearchStatsSnapshot};
 pub use tiered::DetailLevel;
 pub use unified::unified_search;
-pub use workflow_executor::execute_workflow;
+pub use workflow_executor::{execute_workflow, execute_workflow_cancellable};
 
 /// Shared context for search operations (ANN index + stats)
 pub struct SearchContext {
@@ -56,6 +75,8 @@ impl Default for SearchContext {
 pub struct SearchOptions {
     /// Maximum number of results
     pub limit: usize,
+    /// Results to skip in a paged search; None for a plain top-`limit` search
+    pub offset: Option<usize>,
     /// Minimum score threshold (0.0 - 1.0)
     pub min_score: f64,
     /// Filter by collection name
@@ -68,23 +89,53 @@ pub struct SearchOptions {
     pub detail: DetailLevel,
     /// Optional session ID for multi-turn context tracking
     pub session_id: Option<String>,
+    /// Ranking weights; `None` reads them from the config file
+    pub weights: Option<RankingWeights>,
+    /// Match literal tokens without stemming ("tests" does not match "test")
+    pub exact: bool,
+    /// Let misspelled words also match their likely corrections
+    /// ("databse" matches "database")
+    pub fuzzy: bool,
+    /// Lexical share of the BM25 and vector fusion weight, 0.0 - 1.0
+    pub alpha: Option<f64>,
+}
+
+impl SearchOptions {
+    /// Weights to rank with: explicit ones, else the configured ones, split
+    /// by `alpha` when set
+    pub fn ranking_weights(&self) -> RankingWeights {
+        let weights = self
+            .weights
+            .unwrap_or_else(|| RankingWeights::from_config(self.collection.as_deref()));
+        match self.alpha {
+            Some(alpha) => weights.with_alpha(alpha),
+            None => weights,
+        }
+    }
 }
 
 impl Default for SearchOptions {
     fn default() -> Self {
         Self {
             limit: 20,
+            offset: None,
             min_score: 0.0,
             collection: None,
             provider: None,
             metadata_filters: Vec::new(),
             detail: DetailLevel::default(),
             session_id: None,
+            weights: None,
+            exact: false,
+            fuzzy: false,
+            alpha: None,
         }
     }
 }
 
+use crate::config::RankingWeights;
 use crate::db::UserMetadata;
+use crate::error::Result;
 
 /// Search result (can represent document or chunk)
 #[derive(Debug, Clone)]
@@ -141,24 +192,26 @@ const STOP_WORDS: &[&str] = &[
     "this", "these", "those", "there", "here",
 ];
 
-/// Sanitize query for FTS5 to prevent syntax errors
-/// Removes stop words and problematic FTS5 operator characters
-pub fn sanitize_fts5_query(query: &str) -> String {
+/// Translate a query to a safe FTS5 MATCH expression
+///
+/// Boolean operators, phrases and prefixes are kept (see [`FtsQuery`]); field
+/// scopes are dropped, so the result suits any FTS5 table. Empty when only
+/// stop words or punctuation remain; an error when the query nests too deep
+/// or negates nothing.
+pub fn sanitize_fts5_query(query: &str) -> Result<String> {
+    Ok(FtsQuery::parse(query)?.to_match_unscoped())
+}
+
+/// Plain keywords of a natural-language query
+/// Removes stop words and FTS5 operator characters
+pub(crate) fn clean_query_text(query: &str) -> String {
     if query.trim().is_empty() {
         return query.to_string();
     }
 
     // First, remove FTS5 special operator characters
-    let cleaned = query
-        .replace('?', "") // Remove question marks
-        .replace('!', "") // Remove exclamation
-        .replace('^', "") // Remove caret
-        .replace('(', "") // Remove unbalanced parens
-        .replace(')', "")
-        .replace('[', "") // Remove brackets
-        .replace(']', "")
-        .replace('{', "") // Remove braces
-        .replace('}', "");
+    // (question marks, exclamation, caret, unbalanced parens, brackets, braces)
+    let cleaned = query.replace(['?', '!', '^', '(', ')', '[', ']', '{', '}'], "");
 
     // Split into words and filter out stop words
     let words: Vec<&str> = cleaned
@@ -170,11 +223,6 @@ pub fn sanitize_fts5_query(query: &str) -> String {
         })
         .collect();
 
-    if words.is_empty() {
-        return String::new();
-    }
-
-    // Keep AND logic (default) for better precision
     // Natural language queries like "does agentroot have mcp?" become "agentroot mcp"
     words.join(" ")
 }
@@ -183,6 +231,48 @@ pub fn sanitize_fts5_query(query: &str) -> String {
 /// Supports syntax: "category:tutorial difficulty:beginner search terms"
 /// Returns: (clean_query, filters)
 pub fn parse_metadata_filters(query: &str) -> (String, Vec<(String, String)>) {
+    let (remaining, filters) = split_metadata_filters(query);
+    (clean_query_text(&remaining), filters)
+}
+
+/// SQL condition for a `tag:` filter, bound to parameter `param`
+///
+/// Matches LLM keywords as well as the `tags` of a document's frontmatter.
+pub(crate) fn tag_filter_sql(param: usize) -> String {
+    format!(
+        " AND (d.llm_keywords LIKE '%' || ?{0} || '%'
+              OR EXISTS (SELECT 1 FROM json_each(d.user_metadata, '$.tags.value')
+                         WHERE value = ?{0} COLLATE NOCASE))",
+        param
+    )
+}
+
+/// Score multiplier for a document's PageRank importance
+///
+/// Goes linearly from 1 at weight 0 to the importance itself at weight 1.
+pub(crate) fn importance_boost(importance: f64, weight: f64) -> f64 {
+    (1.0 + weight * (importance - 1.0)).max(0.0)
+}
+
+/// SQL for [`importance_boost`] of `d.importance_score`, with the weight
+/// bound to parameter `param`
+pub(crate) fn importance_boost_sql(param: usize) -> String {
+    format!(
+        "MAX(0.0, 1.0 + ?{} * (COALESCE(d.importance_score, 1.0) - 1.0))",
+        param
+    )
+}
+
+/// SQL condition for an `author:` filter on the frontmatter `author` field
+pub(crate) fn author_filter_sql(param: usize) -> String {
+    format!(
+        " AND json_extract(d.user_metadata, '$.author.value') LIKE '%' || ?{} || '%'",
+        param
+    )
+}
+
+/// Split metadata filters off a query, leaving the rest as typed
+pub(crate) fn split_metadata_filters(query: &str) -> (String, Vec<(String, String)>) {
     let mut filters = Vec::new();
     let mut remaining_terms = Vec::new();
 
@@ -194,7 +284,7 @@ pub fn parse_metadata_filters(query: &str) -> (String, Vec<(String, String)>) {
             // Only parse known metadata fields as filters
             if matches!(
                 field.as_str(),
-                "category" | "difficulty" | "tag" | "keyword"
+                "category" | "difficulty" | "tag" | "keyword" | "author" | "after" | "before"
             ) {
                 filters.push((field, value));
                 continue;
@@ -203,7 +293,5 @@ pub fn parse_metadata_filters(query: &str) -> (String, Vec<(String, String)>) {
         remaining_terms.push(term);
     }
 
-    let clean_query = remaining_terms.join(" ");
-    let sanitized_query = sanitize_fts5_query(&clean_query);
-    (sanitized_query, filters)
+    (remaining_terms.join(" "), filters)
 }
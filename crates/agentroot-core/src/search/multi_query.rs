@@ -0,0 +1,118 @@
+//! Multi-query fusion
+//!
+//! Agents often phrase the same need several ways. Each rephrasing is searched
+//! on its own (BM25, plus vectors when available) and the ranked lists are
+//! fused with the same RRF merge the workflow executor uses.
+
+use super::workflow_executor::merge_ranked_lists_rrf;
+use super::{rrf_fusion_weighted, SearchOptions, SearchResult};
+use crate::db::Database;
+use crate::error::{AgentRootError, Result};
+use crate::llm::Embedder;
+use futures::future::join_all;
+
+/// Search every query and fuse the rankings
+///
+/// Vector search runs for each query when an embedder is given and the index
+/// has embeddings. Queries are searched concurrently, so their embedding
+/// requests overlap.
+pub async fn multi_query_search(
+    db: &Database,
+    queries: &[String],
+    embedder: Option<&dyn Embedder>,
+    options: &SearchOptions,
+) -> Result<Vec<SearchResult>> {
+    let queries: Vec<&str> = queries
+        .iter()
+        .map(|q| q.trim())
+        .filter(|q| !q.is_empty())
+        .collect();
+    if queries.is_empty() {
+        return Err(AgentRootError::InvalidInput(
+            "At least one query is required".to_string(),
+        ));
+    }
+
+    let embedder = embedder.filter(|_| db.has_vector_index());
+    let searches = queries
+        .iter()
+        .map(|query| search_one(db, query, embedder, options));
+    let rankings = join_all(searches)
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+
+    let lists: Vec<&[SearchResult]> = rankings.iter().map(Vec::as_slice).collect();
+    let mut results = merge_ranked_lists_rrf(&lists);
+    results.retain(|r| r.score >= options.min_score);
+    results.truncate(options.limit);
+    Ok(results)
+}
+
+async fn search_one(
+    db: &Database,
+    query: &str,
+    embedder: Option<&dyn Embedder>,
+    options: &SearchOptions,
+) -> Result<Vec<SearchResult>> {
+    let mut unfiltered = options.clone();
+    unfiltered.min_score = 0.0;
+
+    let bm25 = db.search_fts(query, &unfiltered)?;
+    match embedder {
+        Some(embedder) => {
+            let vec = db.search_vec(query, embedder, &unfiltered).await?;
+            Ok(rrf_fusion_weighted(&bm25, &vec, &options.ranking_weights()))
+        }
+        None => Ok(bm25),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_multi_query_search_fuses_rephrasings() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = chrono::Utc::now().to_rfc3339();
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+        for (hash, path, body) in [
+            (
+                "aaa111",
+                "auth.md",
+                "Login sessions expire after the token refresh",
+            ),
+            (
+                "bbb222",
+                "sso.md",
+                "Single sign-on login through the identity provider",
+            ),
+            ("ccc333", "sql.md", "SQL schema migrations"),
+        ] {
+            db.insert_content(hash, body).unwrap();
+            db.insert_document("notes", path, path, hash, &now, &now, "file", None)
+                .unwrap();
+        }
+
+        let options = SearchOptions {
+            limit: 10,
+            ..Default::default()
+        };
+        let queries = vec!["token refresh".to_string(), "login".to_string()];
+        let results = multi_query_search(&db, &queries, None, &options)
+            .await
+            .unwrap();
+        let hashes: Vec<&str> = results.iter().map(|r| r.hash.as_str()).collect();
+        // Matched by both rephrasings, so it outranks the single match
+        assert_eq!(hashes, vec!["aaa111", "bbb222"]);
+        assert!(results[0].score > results[1].score);
+
+        let blank = vec![" ".to_string()];
+        assert!(multi_query_search(&db, &blank, None, &options)
+            .await
+            .is_err());
+    }
+}
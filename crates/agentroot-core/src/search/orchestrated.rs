@@ -6,10 +6,11 @@
 //! 3. Observes intermediate results
 //! 4. Adapts as needed
 
-use super::{execute_workflow, parse_metadata_filters, SearchOptions, SearchResult};
+use super::{execute_workflow_cancellable, parse_metadata_filters, SearchOptions, SearchResult};
 use crate::db::Database;
 use crate::error::Result;
 use crate::llm::{fallback_workflow, WorkflowOrchestrator};
+use crate::progress::CancellationToken;
 
 /// Orchestrated search with dynamic workflow planning
 ///
@@ -25,6 +26,17 @@ pub async fn orchestrated_search(
     db: &Database,
     query: &str,
     options: &SearchOptions,
+) -> Result<Vec<SearchResult>> {
+    orchestrated_search_cancellable(db, query, options, &CancellationToken::new()).await
+}
+
+/// [`orchestrated_search`], stopping between workflow steps once `cancel`
+/// is triggered
+pub async fn orchestrated_search_cancellable(
+    db: &Database,
+    query: &str,
+    options: &SearchOptions,
+    cancel: &CancellationToken,
 ) -> Result<Vec<SearchResult>> {
     // Parse metadata filters from query (category:X, difficulty:Y, etc.)
     let (clean_query, metadata_filters) = parse_metadata_filters(query);
@@ -35,21 +47,35 @@ pub async fn orchestrated_search(
 
     tracing::debug!("Parsed filters: {:?}", enhanced_options.metadata_filters);
 
+    let mut results = plan_and_execute(db, &clean_query, &enhanced_options, cancel).await?;
+    super::pinning::apply_pins(db, &clean_query, &enhanced_options, &mut results)?;
+    results.truncate(enhanced_options.limit);
+    Ok(results)
+}
+
+/// Plan a workflow for the query (LLM or fallback) and run it
+async fn plan_and_execute(
+    db: &Database,
+    clean_query: &str,
+    enhanced_options: &SearchOptions,
+    cancel: &CancellationToken,
+) -> Result<Vec<SearchResult>> {
     // Check if embeddings are available
     let has_embeddings = db.has_vector_index();
 
     if !has_embeddings {
         // No embeddings → Simple BM25 workflow
         tracing::info!("No embeddings available, using BM25 workflow");
-        let workflow = fallback_workflow(&clean_query, has_embeddings);
-        return execute_workflow(db, &workflow, &clean_query, &enhanced_options).await;
+        let workflow = fallback_workflow(clean_query, has_embeddings);
+        return execute_workflow_cancellable(db, &workflow, clean_query, enhanced_options, cancel)
+            .await;
     }
 
     // Try LLM-based workflow planning
     match WorkflowOrchestrator::from_env() {
         Ok(orchestrator) => {
             match orchestrator
-                .plan_workflow(&clean_query, has_embeddings)
+                .plan_workflow(clean_query, has_embeddings)
                 .await
             {
                 Ok(workflow) => {
@@ -60,20 +86,34 @@ pub async fn orchestrated_search(
                         workflow.reasoning
                     );
 
-                    execute_workflow(db, &workflow, &clean_query, &enhanced_options).await
+                    execute_workflow_cancellable(
+                        db,
+                        &workflow,
+                        clean_query,
+                        enhanced_options,
+                        cancel,
+                    )
+                    .await
                 }
                 Err(e) => {
                     tracing::warn!("Workflow planning failed: {}, using fallback", e);
-                    let workflow = fallback_workflow(&clean_query, has_embeddings);
-                    execute_workflow(db, &workflow, &clean_query, &enhanced_options).await
+                    let workflow = fallback_workflow(clean_query, has_embeddings);
+                    execute_workflow_cancellable(
+                        db,
+                        &workflow,
+                        clean_query,
+                        enhanced_options,
+                        cancel,
+                    )
+                    .await
                 }
             }
         }
         Err(e) => {
             // LLM not configured, use fallback workflow
             tracing::debug!("LLM not configured, using fallback workflow: {}", e);
-            let workflow = fallback_workflow(&clean_query, has_embeddings);
-            execute_workflow(db, &workflow, &clean_query, &enhanced_options).await
+            let workflow = fallback_workflow(clean_query, has_embeddings);
+            execute_workflow_cancellable(db, &workflow, clean_query, enhanced_options, cancel).await
         }
     }
 }
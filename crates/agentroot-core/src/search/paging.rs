@@ -0,0 +1,215 @@
+//! Paging through search rankings
+//!
+//! A paged search sets [`SearchOptions::offset`] and gets back the `limit`
+//! results after it. Feedback boosts, vector candidate pools and fusion all
+//! depend on how many results are asked for, so a ranking is always computed
+//! [`PAGE_BLOCK`] results at a time and pages are cut from it: every page
+//! inside the same block comes from the same ranking, with no result
+//! skipped or repeated. Ties are broken by document hash so the same index
+//! ranks the same way on every call.
+//!
+//! [`SearchCursor`] wraps the offset in an opaque token tied to the query,
+//! so a cursor is not replayed against a different search.
+
+use super::{SearchOptions, SearchResult};
+use crate::error::{AgentRootError, Result};
+use base64::Engine;
+
+/// Results ranked at a time for paged searches
+pub const PAGE_BLOCK: usize = 100;
+
+/// Cursor format version, bumped if the encoding changes
+const CURSOR_VERSION: &str = "v1";
+
+impl SearchOptions {
+    /// Options ranking from the top down to the end of this page's block
+    ///
+    /// Unpaged options are returned unchanged.
+    pub fn page_window(&self) -> SearchOptions {
+        let mut window = self.clone();
+        if let Some(offset) = self.offset {
+            window.offset = None;
+            if self.limit > 0 {
+                window.limit = (offset + self.limit).div_ceil(PAGE_BLOCK) * PAGE_BLOCK;
+            }
+        }
+        window
+    }
+}
+
+/// Cut the page `options` asks for out of results ranked with
+/// [`SearchOptions::page_window`]
+pub fn take_page(results: Vec<SearchResult>, options: &SearchOptions) -> Vec<SearchResult> {
+    let Some(offset) = options.offset else {
+        return results;
+    };
+    let limit = if options.limit > 0 {
+        options.limit
+    } else {
+        usize::MAX
+    };
+    results.into_iter().skip(offset).take(limit).collect()
+}
+
+/// Opaque position in a paged search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchCursor {
+    pub offset: usize,
+    fingerprint: String,
+}
+
+impl SearchCursor {
+    /// Cursor for the results after `offset` of this search
+    pub fn new(query: &str, options: &SearchOptions, offset: usize) -> Self {
+        Self {
+            offset,
+            fingerprint: fingerprint(query, options),
+        }
+    }
+
+    /// Token to hand to clients
+    pub fn encode(&self) -> String {
+        let raw = format!("{}:{}:{}", CURSOR_VERSION, self.offset, self.fingerprint);
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    /// Read a token back, checking it was issued for the same search
+    ///
+    /// The page size may change between pages; the query and filters may not.
+    pub fn decode(token: &str, query: &str, options: &SearchOptions) -> Result<Self> {
+        let invalid = || AgentRootError::InvalidInput(format!("Invalid cursor: {}", token));
+        let raw = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(token.trim())
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or_else(invalid)?;
+        let mut parts = raw.splitn(3, ':');
+        if parts.next() != Some(CURSOR_VERSION) {
+            return Err(invalid());
+        }
+        let offset = parts
+            .next()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(invalid)?;
+        let cursor = Self {
+            offset,
+            fingerprint: parts.next().ok_or_else(invalid)?.to_string(),
+        };
+        if cursor.fingerprint != fingerprint(query, options) {
+            return Err(AgentRootError::InvalidInput(
+                "Cursor belongs to a different query or filters".to_string(),
+            ));
+        }
+        Ok(cursor)
+    }
+}
+
+/// Hash of everything that decides a ranking besides its depth
+fn fingerprint(query: &str, options: &SearchOptions) -> String {
+    let key = serde_json::json!([
+        query,
+        options.collection,
+        options.provider,
+        options.metadata_filters,
+        options.min_score,
+        options.exact,
+        options.alpha,
+    ]);
+    blake3::hash(key.to_string().as_bytes()).to_hex()[..16].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::SearchSource;
+    use std::collections::HashMap;
+
+    fn result(hash: &str) -> SearchResult {
+        SearchResult {
+            filepath: format!("agentroot://test/{}.md", hash),
+            display_path: format!("test/{}.md", hash),
+            title: hash.to_string(),
+            hash: hash.to_string(),
+            collection_name: "test".to_string(),
+            modified_at: String::new(),
+            body: None,
+            body_length: 0,
+            docid: hash.to_string(),
+            context: None,
+            score: 1.0,
+            source: SearchSource::Bm25,
+            chunk_pos: None,
+            llm_summary: None,
+            llm_title: None,
+            llm_keywords: None,
+            llm_category: None,
+            llm_difficulty: None,
+            user_metadata: None,
+            is_chunk: false,
+            chunk_hash: None,
+            chunk_type: None,
+            chunk_breadcrumb: None,
+            chunk_start_line: None,
+            chunk_end_line: None,
+            chunk_language: None,
+            chunk_summary: None,
+            chunk_purpose: None,
+            chunk_concepts: vec![],
+            chunk_labels: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_page_window_and_take_page() {
+        let plain = SearchOptions {
+            limit: 10,
+            ..Default::default()
+        };
+        assert_eq!(plain.page_window().limit, 10);
+
+        let paged = SearchOptions {
+            limit: 10,
+            offset: Some(95),
+            ..Default::default()
+        };
+        let window = paged.page_window();
+        assert_eq!((window.limit, window.offset), (200, None));
+        let first = SearchOptions {
+            offset: Some(0),
+            ..paged.clone()
+        };
+        assert_eq!(first.page_window().limit, PAGE_BLOCK);
+
+        let ranked: Vec<SearchResult> = (0..100).map(|i| result(&format!("{:03}", i))).collect();
+        let page = take_page(ranked.clone(), &paged);
+        let hashes: Vec<&str> = page.iter().map(|r| r.hash.as_str()).collect();
+        assert_eq!(hashes, vec!["095", "096", "097", "098", "099"]);
+        assert_eq!(take_page(ranked, &plain).len(), 100);
+    }
+
+    #[test]
+    fn test_cursor_round_trip_and_mismatch() {
+        let options = SearchOptions {
+            limit: 10,
+            collection: Some("docs".to_string()),
+            ..Default::default()
+        };
+        let token = SearchCursor::new("cache eviction", &options, 30).encode();
+
+        // The page size may change between pages
+        let bigger = SearchOptions {
+            limit: 50,
+            ..options.clone()
+        };
+        let cursor = SearchCursor::decode(&token, "cache eviction", &bigger).unwrap();
+        assert_eq!(cursor.offset, 30);
+
+        assert!(SearchCursor::decode(&token, "cache", &options).is_err());
+        let other = SearchOptions {
+            collection: Some("notes".to_string()),
+            ..options.clone()
+        };
+        assert!(SearchCursor::decode(&token, "cache eviction", &other).is_err());
+        assert!(SearchCursor::decode("not-a-cursor", "cache eviction", &options).is_err());
+    }
+}
@@ -0,0 +1,222 @@
+//! Pinned-document ranking
+//!
+//! Pins without a boost put their document first; pins with a boost scale
+//! its score. A pin limited to query phrases adds its document to matching
+//! queries' results even when the search itself missed it.
+
+use super::{SearchOptions, SearchResult, SearchSource};
+use crate::db::{docid_from_hash, Database, PinnedDocument};
+use crate::error::Result;
+use rusqlite::{params, OptionalExtension};
+use std::collections::HashMap;
+
+/// Apply the pins that match a query and re-sort
+///
+/// Pinned-first documents come before everything else in pin order and
+/// share the best unpinned score. No-op when nothing is pinned.
+pub fn apply_pins(
+    db: &Database,
+    query: &str,
+    options: &SearchOptions,
+    results: &mut Vec<SearchResult>,
+) -> Result<()> {
+    let pins: Vec<PinnedDocument> = db
+        .list_pins(options.collection.as_deref())?
+        .into_iter()
+        .filter(|p| p.matches_query(query))
+        .collect();
+    if pins.is_empty() {
+        return Ok(());
+    }
+
+    let mut first: HashMap<String, usize> = HashMap::new();
+    for pin in &pins {
+        match pin.boost {
+            Some(boost) => {
+                for result in results.iter_mut().filter(|r| r.hash == pin.hash) {
+                    result.score *= boost;
+                }
+            }
+            None => {
+                let rank = first.len();
+                first.entry(pin.hash.clone()).or_insert(rank);
+                let present = results.iter().any(|r| r.hash == pin.hash);
+                if !present && !pin.queries.is_empty() {
+                    if let Some(result) = db.pinned_search_result(&pin.hash, options)? {
+                        results.push(result);
+                    }
+                }
+            }
+        }
+    }
+
+    results.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    let top = results
+        .iter()
+        .find(|r| !first.contains_key(&r.hash))
+        .map(|r| r.score)
+        .unwrap_or(1.0)
+        .max(options.min_score);
+    for result in results.iter_mut() {
+        if first.contains_key(&result.hash) {
+            result.score = top;
+        }
+    }
+    // Stable, so unpinned results keep their order
+    results.sort_by_key(|r| first.get(&r.hash).copied().unwrap_or(usize::MAX));
+
+    Ok(())
+}
+
+impl Database {
+    /// Document-level result for a pinned document the search did not return
+    fn pinned_search_result(
+        &self,
+        hash: &str,
+        options: &SearchOptions,
+    ) -> Result<Option<SearchResult>> {
+        let row = self
+            .conn
+            .query_row(
+                "SELECT d.collection, d.path, d.title, d.modified_at, c.doc
+                 FROM documents d JOIN content c ON c.hash = d.hash
+                 WHERE d.hash = ?1 AND d.active = 1
+                 LIMIT 1",
+                params![hash],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, String>(3)?,
+                        row.get::<_, String>(4)?,
+                    ))
+                },
+            )
+            .optional()?;
+        let Some((collection, path, title, modified_at, body)) = row else {
+            return Ok(None);
+        };
+
+        Ok(Some(SearchResult {
+            filepath: format!("agentroot://{}/{}", collection, path),
+            display_path: format!("{}/{}", collection, path),
+            title,
+            hash: hash.to_string(),
+            collection_name: collection,
+            modified_at,
+            body_length: body.len(),
+            body: options.detail.is_full_content().then_some(body),
+            docid: docid_from_hash(hash),
+            context: None,
+            score: 0.0,
+            source: SearchSource::Hybrid,
+            chunk_pos: None,
+            llm_summary: None,
+            llm_title: None,
+            llm_keywords: None,
+            llm_category: None,
+            llm_difficulty: None,
+            user_metadata: None,
+            is_chunk: false,
+            chunk_hash: None,
+            chunk_type: None,
+            chunk_breadcrumb: None,
+            chunk_start_line: None,
+            chunk_end_line: None,
+            chunk_language: None,
+            chunk_summary: None,
+            chunk_purpose: None,
+            chunk_concepts: Vec::new(),
+            chunk_labels: HashMap::new(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn setup_db() -> Database {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+        for (hash, path, body) in [
+            ("aaa111", "a.md", "deploy deploy deploy with the pipeline"),
+            ("bbb222", "b.md", "deploy notes among many other words here"),
+            ("ccc333", "runbook.md", "Step by step release checklist"),
+        ] {
+            db.insert_content(hash, body).unwrap();
+            db.insert_document("notes", path, path, hash, &now, &now, "file", None)
+                .unwrap();
+        }
+        db
+    }
+
+    fn paths(results: &[SearchResult]) -> Vec<&str> {
+        results.iter().map(|r| r.display_path.as_str()).collect()
+    }
+
+    #[test]
+    fn test_pins_reorder_and_inject() {
+        let db = setup_db();
+        let options = SearchOptions::default();
+        let before = db.search_fts("deploy", &options).unwrap();
+        assert_eq!(before.len(), 2);
+        let (top, runner_up) = (
+            before[0].display_path.clone(),
+            before[1].display_path.clone(),
+        );
+
+        // Pinned first
+        db.pin_document(&runner_up, &[], None).unwrap();
+        let mut results = before.clone();
+        apply_pins(&db, "deploy", &options, &mut results).unwrap();
+        assert_eq!(paths(&results), vec![runner_up.as_str(), top.as_str()]);
+        assert_eq!(results[0].score, results[1].score);
+
+        // A query-scoped pin brings in a document the search missed
+        db.pin_document("notes/runbook.md", &["deploy".to_string()], None)
+            .unwrap();
+        let mut results = before.clone();
+        apply_pins(&db, "how to deploy", &options, &mut results).unwrap();
+        assert_eq!(
+            paths(&results),
+            vec![runner_up.as_str(), "notes/runbook.md", top.as_str()]
+        );
+        let mut results = db.search_fts("pipeline", &options).unwrap();
+        apply_pins(&db, "pipeline", &options, &mut results).unwrap();
+        assert_eq!(paths(&results), vec!["notes/a.md"]);
+
+        // Pins are per collection
+        let scoped = SearchOptions {
+            collection: Some("other".to_string()),
+            ..Default::default()
+        };
+        let mut results = before.clone();
+        apply_pins(&db, "deploy", &scoped, &mut results).unwrap();
+        assert_eq!(paths(&results), paths(&before));
+    }
+
+    #[test]
+    fn test_pin_boost_scales_score() {
+        let db = setup_db();
+        let options = SearchOptions::default();
+        let mut results = db.search_fts("deploy", &options).unwrap();
+        let before = results.clone();
+
+        db.pin_document(&before[1].display_path, &[], Some(100.0))
+            .unwrap();
+        apply_pins(&db, "deploy", &options, &mut results).unwrap();
+        assert_eq!(results[0].display_path, before[1].display_path);
+        assert!((results[0].score - before[1].score * 100.0).abs() < 1e-9);
+        assert_eq!(results[1].score, before[0].score);
+    }
+}
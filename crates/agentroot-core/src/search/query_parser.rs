@@ -0,0 +1,587 @@
+//! Boolean query language for BM25 search
+//!
+//! Queries are parsed into a small expression tree and translated to FTS5
+//! syntax, so user input can never produce an FTS5 syntax error:
+//!
+//! - words are ANDed: `cache eviction`
+//! - `OR`, `AND` and `NOT` (upper case) combine terms, `(...)` groups them
+//! - `"exact phrase"` matches consecutive words
+//! - `tok*` matches words starting with `tok`
+//! - `title:foo`, `path:src/search` and `body:foo` search one field
+//!
+//! Every term is emitted as a quoted FTS5 string, which the tokenizer splits
+//! the same way it split the document, so punctuation such as `std::io` or
+//! `foo-bar` becomes a phrase instead of an operator. Bare lower-case stop
+//! words are dropped as before; quoted phrases keep them.
+//!
+//! Queries FTS5 cannot express are rejected rather than rewritten: `NOT`
+//! needs a term to exclude from in the same group (`cache NOT redis`, not
+//! `NOT redis` or `cache OR NOT redis`), and groups nest at most
+//! `MAX_DEPTH` levels.
+
+use super::STOP_WORDS;
+use crate::error::{AgentRootError, Result};
+use std::collections::HashMap;
+
+/// Deepest nesting of parentheses and `NOT`s a query may use
+const MAX_DEPTH: usize = 32;
+
+/// A field a term can be scoped to, and its FTS5 column
+const FIELDS: &[(&str, &str)] = &[("title", "title"), ("path", "filepath"), ("body", "body")];
+
+/// Parsed BM25 query
+#[derive(Debug, Clone, PartialEq)]
+pub struct FtsQuery {
+    root: Option<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Term(Term),
+    And(Vec<Node>),
+    Or(Vec<Node>),
+    Not(Box<Node>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Term {
+    text: String,
+    column: Option<&'static str>,
+    prefix: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Phrase(String),
+    Field(&'static str, String, bool),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl FtsQuery {
+    /// Parse a query; malformed input degrades to the terms it contains
+    ///
+    /// Fails on nesting deeper than `MAX_DEPTH` and on a `NOT` with nothing
+    /// to exclude from.
+    pub fn parse(query: &str) -> Result<Self> {
+        let tokens = tokenize(query);
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            depth: 0,
+        };
+        let mut root = parser.or_expr()?;
+        // Unbalanced closing parentheses end an expression early; keep going
+        while parser.pos < parser.tokens.len() {
+            parser.pos += 1;
+            if let Some(rest) = parser.or_expr()? {
+                root = Some(match root {
+                    Some(node) => Node::And(vec![node, rest]),
+                    None => rest,
+                });
+            }
+        }
+        if let Some(root) = &root {
+            check_negation(root, false)?;
+        }
+        Ok(Self { root })
+    }
+
+    /// FTS5 MATCH expression for the document tables, honouring field scopes
+    ///
+    /// Empty when nothing searchable is left, e.g. only stop words.
+    pub fn to_match(&self) -> String {
+        self.emit(true)
+    }
+
+    /// FTS5 MATCH expression that searches field-scoped terms in all columns,
+    /// for tables without title and path columns
+    pub fn to_match_unscoped(&self) -> String {
+        self.emit(false)
+    }
+
+    /// Words the query looks for, for snippets
+    pub fn keywords(&self) -> String {
+        let mut words = Vec::new();
+        if let Some(root) = &self.root {
+            collect_keywords(root, &mut words);
+        }
+        words.join(" ")
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.to_match().is_empty()
+    }
+
+    /// Single words the query searches for, lowercased; not prefixes or
+    /// phrases
+    pub fn words(&self) -> Vec<String> {
+        let mut words = Vec::new();
+        if let Some(root) = &self.root {
+            collect_words(root, &mut words);
+        }
+        let mut seen = std::collections::HashSet::new();
+        words.retain(|w| seen.insert(w.clone()));
+        words
+    }
+
+    /// Let each word that is a key of `alternatives` also match its values
+    ///
+    /// The alternatives keep the word's field scope.
+    pub fn with_alternatives(self, alternatives: &HashMap<String, Vec<String>>) -> Self {
+        if alternatives.is_empty() {
+            return self;
+        }
+        Self {
+            root: self.root.map(|root| expand(root, alternatives)),
+        }
+    }
+
+    fn emit(&self, scoped: bool) -> String {
+        self.root
+            .as_ref()
+            .and_then(|root| emit(root, scoped))
+            .unwrap_or_default()
+    }
+}
+
+fn emit(node: &Node, scoped: bool) -> Option<String> {
+    match node {
+        Node::Term(term) => {
+            let mut s = String::new();
+            if let (true, Some(column)) = (scoped, term.column) {
+                s.push_str(column);
+                s.push_str(" : ");
+            }
+            s.push('"');
+            s.push_str(&term.text.replace('"', "\"\""));
+            s.push('"');
+            if term.prefix {
+                s.push('*');
+            }
+            Some(s)
+        }
+        Node::Or(children) => {
+            let parts: Vec<String> = children.iter().filter_map(|c| emit(c, scoped)).collect();
+            match parts.len() {
+                0 => None,
+                1 => parts.into_iter().next(),
+                _ => Some(format!("({})", parts.join(" OR "))),
+            }
+        }
+        Node::And(children) => {
+            let mut positive = Vec::new();
+            let mut negative = Vec::new();
+            for child in children {
+                match child {
+                    Node::Not(inner) => negative.extend(emit(inner, scoped)),
+                    _ => positive.extend(emit(child, scoped)),
+                }
+            }
+            // FTS5 has no unary NOT: a purely negative query matches nothing
+            if positive.is_empty() {
+                return None;
+            }
+            let included = if positive.len() == 1 {
+                positive.remove(0)
+            } else {
+                format!("({})", positive.join(" AND "))
+            };
+            if negative.is_empty() {
+                Some(included)
+            } else {
+                Some(format!("({} NOT ({}))", included, negative.join(" OR ")))
+            }
+        }
+        Node::Not(_) => None,
+    }
+}
+
+/// Reject a `NOT` that is not excluding from a positive sibling in an AND
+/// group, which FTS5 has no way to express
+fn check_negation(node: &Node, in_and: bool) -> Result<()> {
+    match node {
+        Node::Term(_) => Ok(()),
+        Node::Not(inner) => {
+            if !in_and {
+                return Err(AgentRootError::InvalidInput(
+                    "NOT needs a term to exclude from in the same group, as in 'cache NOT redis'"
+                        .to_string(),
+                ));
+            }
+            check_negation(inner, false)
+        }
+        Node::Or(children) => children.iter().try_for_each(|c| check_negation(c, false)),
+        Node::And(children) => {
+            if children.iter().all(|c| matches!(c, Node::Not(_))) {
+                return check_negation(&children[0], false);
+            }
+            children.iter().try_for_each(|c| check_negation(c, true))
+        }
+    }
+}
+
+fn collect_keywords(node: &Node, words: &mut Vec<String>) {
+    match node {
+        Node::Term(term) => words.push(term.text.clone()),
+        Node::And(children) | Node::Or(children) => {
+            for child in children {
+                collect_keywords(child, words);
+            }
+        }
+        Node::Not(_) => {}
+    }
+}
+
+fn collect_words(node: &Node, words: &mut Vec<String>) {
+    match node {
+        Node::Term(term) => {
+            if !term.prefix && term.text.chars().all(char::is_alphanumeric) {
+                words.push(term.text.to_lowercase());
+            }
+        }
+        Node::And(children) | Node::Or(children) => {
+            for child in children {
+                collect_words(child, words);
+            }
+        }
+        Node::Not(inner) => collect_words(inner, words),
+    }
+}
+
+fn expand(node: Node, alternatives: &HashMap<String, Vec<String>>) -> Node {
+    match node {
+        Node::Term(term) if !term.prefix => match alternatives.get(&term.text.to_lowercase()) {
+            Some(words) => {
+                let mut choices = vec![Node::Term(term.clone())];
+                choices.extend(words.iter().map(|word| {
+                    Node::Term(Term {
+                        text: word.clone(),
+                        ..term.clone()
+                    })
+                }));
+                Node::Or(choices)
+            }
+            None => Node::Term(term),
+        },
+        Node::Term(term) => Node::Term(term),
+        Node::And(children) => Node::And(
+            children
+                .into_iter()
+                .map(|c| expand(c, alternatives))
+                .collect(),
+        ),
+        Node::Or(children) => Node::Or(
+            children
+                .into_iter()
+                .map(|c| expand(c, alternatives))
+                .collect(),
+        ),
+        Node::Not(inner) => Node::Not(Box::new(expand(*inner, alternatives))),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Open parentheses and NOTs around the current position
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    /// Go one level deeper, failing past `MAX_DEPTH`
+    fn descend(&mut self) -> Result<()> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(AgentRootError::InvalidInput(format!(
+                "query nests parentheses or NOT more than {} levels deep",
+                MAX_DEPTH
+            )));
+        }
+        Ok(())
+    }
+
+    /// `and_expr (OR and_expr)*`
+    fn or_expr(&mut self) -> Result<Option<Node>> {
+        let mut children: Vec<Node> = self.and_expr()?.into_iter().collect();
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            children.extend(self.and_expr()?);
+        }
+        Ok(match children.len() {
+            0 => None,
+            1 => children.pop(),
+            _ => Some(Node::Or(children)),
+        })
+    }
+
+    /// `unary ([AND] unary)*`
+    fn and_expr(&mut self) -> Result<Option<Node>> {
+        let mut children = Vec::new();
+        loop {
+            match self.peek() {
+                None | Some(Token::Or) | Some(Token::Close) => break,
+                Some(Token::And) => self.pos += 1,
+                _ => children.extend(self.unary()?),
+            }
+        }
+        Ok(match children.len() {
+            0 => None,
+            1 => children.pop(),
+            _ => Some(Node::And(children)),
+        })
+    }
+
+    /// `NOT unary | primary`
+    fn unary(&mut self) -> Result<Option<Node>> {
+        if self.peek() == Some(&Token::Not) {
+            self.pos += 1;
+            return match self.peek() {
+                None | Some(Token::Or) | Some(Token::Close) | Some(Token::And) => Ok(None),
+                _ => {
+                    self.descend()?;
+                    let node = self.unary()?.map(|node| Node::Not(Box::new(node)));
+                    self.depth -= 1;
+                    Ok(node)
+                }
+            };
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Option<Node>> {
+        let Some(token) = self.tokens.get(self.pos).cloned() else {
+            return Ok(None);
+        };
+        self.pos += 1;
+        Ok(match token {
+            Token::Open => {
+                self.descend()?;
+                let node = self.or_expr()?;
+                self.depth -= 1;
+                if self.peek() == Some(&Token::Close) {
+                    self.pos += 1;
+                }
+                node
+            }
+            Token::Word(word) => {
+                let (text, prefix) = match word.strip_suffix('*') {
+                    Some(stem) => (stem.to_string(), true),
+                    None => (word, false),
+                };
+                if !prefix && STOP_WORDS.contains(&text.to_lowercase().as_str()) {
+                    return Ok(None);
+                }
+                term(text, None, prefix)
+            }
+            Token::Phrase(text) => term(text, None, false),
+            Token::Field(column, text, prefix) => term(text, Some(column), prefix),
+            Token::And | Token::Or | Token::Not | Token::Close => None,
+        })
+    }
+}
+
+/// A term, unless it has nothing the tokenizer would index
+fn term(text: String, column: Option<&'static str>, prefix: bool) -> Option<Node> {
+    text.chars()
+        .any(char::is_alphanumeric)
+        .then_some(Node::Term(Term {
+            text,
+            column,
+            prefix,
+        }))
+}
+
+fn tokenize(query: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = query.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '"' => {
+                chars.next();
+                tokens.push(Token::Phrase(read_phrase(&mut chars)));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '(' | ')' | '"') {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                let field = word
+                    .strip_suffix(':')
+                    .and_then(field_column)
+                    .filter(|_| chars.peek() == Some(&'"'));
+                if let Some(column) = field {
+                    chars.next();
+                    tokens.push(Token::Field(column, read_phrase(&mut chars), false));
+                    continue;
+                }
+                tokens.push(match word.as_str() {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "NOT" => Token::Not,
+                    _ => match word.split_once(':') {
+                        Some((name, value)) if !value.is_empty() => match field_column(name) {
+                            Some(column) => {
+                                let (value, prefix) = match value.strip_suffix('*') {
+                                    Some(stem) => (stem, true),
+                                    None => (value, false),
+                                };
+                                Token::Field(column, value.to_string(), prefix)
+                            }
+                            None => Token::Word(word),
+                        },
+                        _ => Token::Word(word),
+                    },
+                });
+            }
+        }
+    }
+    tokens
+}
+
+/// Read up to the closing quote, which is consumed; an unclosed phrase runs
+/// to the end of the query
+fn read_phrase(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> String {
+    let mut phrase = String::new();
+    for c in chars.by_ref() {
+        if c == '"' {
+            break;
+        }
+        phrase.push(c);
+    }
+    phrase
+}
+
+fn field_column(name: &str) -> Option<&'static str> {
+    let name = name.to_lowercase();
+    FIELDS
+        .iter()
+        .find(|(field, _)| *field == name)
+        .map(|(_, column)| *column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fts(query: &str) -> String {
+        FtsQuery::parse(query).unwrap().to_match()
+    }
+
+    #[test]
+    fn test_translates_operators_and_terms() {
+        assert_eq!(fts("cache eviction"), r#"("cache" AND "eviction")"#);
+        assert_eq!(fts("tokio OR async-std"), r#"("tokio" OR "async-std")"#);
+        assert_eq!(
+            fts("(rust OR go) AND NOT python"),
+            r#"(("rust" OR "go") NOT ("python"))"#
+        );
+        assert_eq!(
+            fts(r#""the quick fox" jump*"#),
+            r#"("the quick fox" AND "jump"*)"#
+        );
+        assert_eq!(
+            fts(r#"title:setup path:src/search body:"error handling" title:conf*"#),
+            r#"(title : "setup" AND filepath : "src/search" AND body : "error handling" AND title : "conf"*)"#
+        );
+        assert_eq!(
+            FtsQuery::parse("title:setup guide")
+                .unwrap()
+                .to_match_unscoped(),
+            r#"("setup" AND "guide")"#
+        );
+    }
+
+    #[test]
+    fn test_malformed_input_stays_valid() {
+        assert_eq!(
+            fts("does agentroot have mcp?"),
+            r#"("agentroot" AND "mcp?")"#
+        );
+        assert_eq!(fts("std::io::Read"), r#""std::io::Read""#);
+        assert_eq!(fts(r#"say "hi"#), r#"("say" AND "hi")"#);
+        assert_eq!(fts("((foo bar"), r#"("foo" AND "bar")"#);
+        assert_eq!(fts("foo) bar"), r#"("foo" AND "bar")"#);
+        assert_eq!(fts("OR foo AND"), r#""foo""#);
+        assert_eq!(fts("the * ^ -"), "");
+        assert!(FtsQuery::parse("what is it").unwrap().is_empty());
+        assert_eq!(fts("http://example.com"), r#""http://example.com""#);
+        assert_eq!(fts("NOT foo bar"), r#"("bar" NOT ("foo"))"#);
+        assert_eq!(
+            FtsQuery::parse("cache NOT lru").unwrap().keywords(),
+            "cache".to_string()
+        );
+    }
+
+    #[test]
+    fn test_rejects_unexpressible_negation() {
+        for query in [
+            "NOT foo",
+            "cache OR NOT lru",
+            "NOT foo OR bar",
+            "(NOT foo)",
+            "foo NOT NOT bar",
+            "the NOT foo",
+        ] {
+            assert!(
+                matches!(FtsQuery::parse(query), Err(AgentRootError::InvalidInput(_))),
+                "{}",
+                query
+            );
+        }
+        assert!(FtsQuery::parse("cache OR (lru NOT redis)").is_ok());
+    }
+
+    #[test]
+    fn test_rejects_deep_nesting() {
+        let nested = format!("{}foo{}", "(".repeat(MAX_DEPTH), ")".repeat(MAX_DEPTH));
+        assert_eq!(fts(&nested), r#""foo""#);
+
+        let too_deep = "(".repeat(MAX_DEPTH + 1) + "foo";
+        assert!(FtsQuery::parse(&too_deep).is_err());
+        assert!(FtsQuery::parse(&"(".repeat(100_000)).is_err());
+        let nots = format!("bar {}foo", "NOT ".repeat(MAX_DEPTH + 1));
+        assert!(FtsQuery::parse(&nots).is_err());
+    }
+
+    #[test]
+    fn test_alternatives_widen_words() {
+        let query = FtsQuery::parse("databse title:Indx conf* NOT cahce").unwrap();
+        assert_eq!(query.words(), vec!["databse", "indx", "cahce"]);
+
+        let alternatives = HashMap::from([
+            ("databse".to_string(), vec!["database".to_string()]),
+            (
+                "indx".to_string(),
+                vec!["index".to_string(), "inbox".to_string()],
+            ),
+        ]);
+        assert_eq!(
+            query.with_alternatives(&alternatives).to_match(),
+            r#"((("databse" OR "database") AND (title : "Indx" OR title : "index" OR title : "inbox") AND "conf"*) NOT ("cahce"))"#
+        );
+    }
+}
@@ -8,7 +8,7 @@ use crate::error::Result;
 /// Seen results get score *= 0.3 (demoted, not removed).
 pub fn apply_session_awareness(
     db: &Database,
-    results: &mut Vec<SearchResult>,
+    results: &mut [SearchResult],
     session_id: &str,
 ) -> Result<()> {
     let seen = db.get_seen_hashes(session_id)?;
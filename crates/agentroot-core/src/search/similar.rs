@@ -0,0 +1,221 @@
+//! More-like-this search
+//!
+//! Finds documents similar to a given one without a query. The document's
+//! chunk-embedding centroid drives a vector scan, and its LLM keywords plus
+//! its most distinctive words seed BM25 searches; the rankings are fused
+//! with RRF.
+
+use super::workflow_executor::merge_ranked_lists_rrf;
+use super::{sanitize_fts5_query, SearchOptions, SearchResult};
+use crate::db::{Database, VectorFilter};
+use crate::error::{AgentRootError, Result};
+use rusqlite::params;
+use std::collections::HashSet;
+
+/// Distinctive words used as BM25 seeds next to the LLM keywords
+const SEED_TERMS: usize = 6;
+
+impl Database {
+    /// Documents similar to the given document, best first
+    ///
+    /// The document is given by docid, virtual path or collection/path.
+    /// Copies with identical content are left out along with the document.
+    /// Scores are relative to the best match, which scores 1.0.
+    pub fn similar_to(&self, docid: &str, limit: usize) -> Result<Vec<SearchResult>> {
+        let id = self
+            .resolve_document_id(docid)?
+            .ok_or_else(|| AgentRootError::DocumentNotFound(docid.to_string()))?;
+        let (hash, collection, title, body, keywords): (
+            String,
+            String,
+            String,
+            String,
+            Option<String>,
+        ) = self.conn.query_row(
+            "SELECT d.hash, d.collection, d.title, c.doc, d.llm_keywords
+             FROM documents d JOIN content c ON c.hash = d.hash
+             WHERE d.id = ?1",
+            params![id],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                ))
+            },
+        )?;
+
+        let options = SearchOptions {
+            // Room for the document itself and its copies
+            limit: limit * 2 + 5,
+            ..Default::default()
+        };
+
+        let mut seeds: Vec<String> = keywords
+            .and_then(|json| serde_json::from_str::<Vec<String>>(&json).ok())
+            .unwrap_or_default();
+        seeds.extend(self.distinctive_terms(
+            &collection,
+            &format!("{}\n{}", title, body),
+            SEED_TERMS,
+        )?);
+        let mut seen = HashSet::new();
+        // Keywords that parse to nothing searchable, or not at all, seed nothing
+        seeds.retain(|s| {
+            sanitize_fts5_query(s).is_ok_and(|q| !q.trim().is_empty())
+                && seen.insert(s.to_lowercase())
+        });
+
+        let mut seed_rankings = Vec::new();
+        for seed in &seeds {
+            let results = self.search_fts(seed, &options)?;
+            if !results.is_empty() {
+                seed_rankings.push(results);
+            }
+        }
+        let seed_lists: Vec<&[SearchResult]> = seed_rankings.iter().map(Vec::as_slice).collect();
+        let keyword_ranking = merge_ranked_lists_rrf(&seed_lists);
+
+        let vector_ranking = match self.document_centroid(&hash)? {
+            Some(centroid) => self.similar_by_embedding(&centroid, &options)?,
+            None => Vec::new(),
+        };
+
+        let mut results = merge_ranked_lists_rrf(&[&vector_ranking, &keyword_ranking]);
+        results.retain(|r| r.hash != hash);
+        results.truncate(limit);
+        if let Some(top) = results.first().map(|r| r.score).filter(|s| *s > 0.0) {
+            for result in &mut results {
+                result.score /= top;
+            }
+        }
+        Ok(results)
+    }
+
+    /// Best document per content hash for an embedding, in similarity order
+    fn similar_by_embedding(
+        &self,
+        embedding: &[f32],
+        options: &SearchOptions,
+    ) -> Result<Vec<SearchResult>> {
+        let candidates =
+            self.top_k_embeddings(embedding, options.limit * 3, VectorFilter::default())?;
+
+        let importance_weight = options.ranking_weights().importance;
+        let mut seen = HashSet::new();
+        let mut results = Vec::new();
+        for (hash_seq, score) in candidates {
+            let Some((doc_hash, _)) = hash_seq.rsplit_once('_') else {
+                continue;
+            };
+            if !seen.insert(doc_hash.to_string()) {
+                continue;
+            }
+            if let Some(result) = self.get_search_result_for_hash_seq(
+                &hash_seq,
+                score,
+                "",
+                options,
+                importance_weight,
+            )? {
+                results.push(result);
+            }
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn setup_db() -> Database {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+        for (hash, path, body) in [
+            (
+                "aaa111",
+                "raft.md",
+                "Raft elects a leader; the leader replicates the log to followers.",
+            ),
+            (
+                "bbb222",
+                "paxos.md",
+                "Paxos and raft both reach consensus; a leader replicates the log.",
+            ),
+            (
+                "ccc333",
+                "quorum.md",
+                "Followers acknowledge the log once a quorum is reached.",
+            ),
+            (
+                "ddd444",
+                "css.md",
+                "Flexbox aligns items along the main axis.",
+            ),
+            (
+                "eee555",
+                "grid.md",
+                "Grid places cells in rows and columns.",
+            ),
+        ] {
+            db.insert_content(hash, body).unwrap();
+            db.insert_document("notes", path, path, hash, &now, &now, "file", None)
+                .unwrap();
+        }
+        db.rebuild_vocabulary("notes").unwrap();
+        db
+    }
+
+    #[test]
+    fn test_similar_to_uses_distinctive_terms() {
+        let db = setup_db();
+
+        let similar = db.similar_to("notes/raft.md", 10).unwrap();
+        let paths: Vec<&str> = similar.iter().map(|r| r.display_path.as_str()).collect();
+        assert_eq!(paths[0], "notes/paxos.md");
+        assert!(paths.contains(&"notes/quorum.md"));
+        assert!(!paths.contains(&"notes/raft.md"));
+        assert!(!paths.contains(&"notes/css.md"));
+
+        assert_eq!(db.similar_to("notes/raft.md", 1).unwrap().len(), 1);
+        assert!(db.similar_to("notes/missing.md", 5).is_err());
+    }
+
+    #[test]
+    fn test_similar_to_uses_embeddings_and_keywords() {
+        let db = setup_db();
+        db.ensure_vec_table(2).unwrap();
+        db.insert_embedding("ddd444", 0, 0, "m", &[1.0, 0.0])
+            .unwrap();
+        db.insert_embedding("eee555", 0, 0, "m", &[0.9, 0.1])
+            .unwrap();
+        db.insert_embedding("aaa111", 0, 0, "m", &[0.0, 1.0])
+            .unwrap();
+
+        // No shared words, but close embeddings
+        let similar = db.similar_to("notes/css.md", 10).unwrap();
+        assert_eq!(similar[0].display_path, "notes/grid.md");
+
+        // LLM keywords seed BM25 too
+        db.conn
+            .execute(
+                "UPDATE documents SET llm_keywords = ?1 WHERE hash = 'ddd444'",
+                params![r#"["quorum"]"#],
+            )
+            .unwrap();
+        let paths: Vec<String> = db
+            .similar_to("notes/css.md", 10)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.display_path)
+            .collect();
+        assert!(paths.contains(&"notes/quorum.md".to_string()));
+    }
+}
@@ -0,0 +1,97 @@
+//! Per-stage timing for the slow query log
+
+use super::SearchOptions;
+use crate::db::Database;
+use std::time::Instant;
+
+/// Accumulates wall-clock time per search stage
+///
+/// Stages recorded more than once (e.g. one embed per expanded query) are summed.
+pub struct QueryTimer {
+    start: Instant,
+    mark: Instant,
+    stages: Vec<(String, f64)>,
+}
+
+impl QueryTimer {
+    pub fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            start: now,
+            mark: now,
+            stages: Vec::new(),
+        }
+    }
+
+    /// Attribute the time since the previous stage to `name`
+    pub fn stage(&mut self, name: &str) {
+        let now = Instant::now();
+        let ms = now.duration_since(self.mark).as_secs_f64() * 1000.0;
+        self.mark = now;
+        match self.stages.iter_mut().find(|(stage, _)| stage == name) {
+            Some((_, total)) => *total += ms,
+            None => self.stages.push((name.to_string(), ms)),
+        }
+    }
+
+    /// Stage timings in milliseconds, in first-seen order
+    pub fn stages(&self) -> &[(String, f64)] {
+        &self.stages
+    }
+
+    /// Milliseconds since the timer started
+    pub fn total_ms(&self) -> f64 {
+        self.start.elapsed().as_secs_f64() * 1000.0
+    }
+}
+
+impl Default for QueryTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Record the query if it exceeded the slow query threshold.
+/// Logging failures never fail the search.
+pub(crate) fn log_if_slow(
+    db: &Database,
+    mode: &str,
+    query: &str,
+    options: &SearchOptions,
+    timer: &QueryTimer,
+    result_count: usize,
+) {
+    if db.is_read_only() {
+        return;
+    }
+    let Some(threshold) = crate::db::slow_query_threshold_ms() else {
+        return;
+    };
+    let total_ms = timer.total_ms();
+    if total_ms < threshold {
+        return;
+    }
+    if let Err(e) =
+        db.record_slow_query(mode, query, options, timer.stages(), total_ms, result_count)
+    {
+        tracing::warn!("Failed to record slow query: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeated_stages_accumulate() {
+        let mut timer = QueryTimer::new();
+        timer.stage("embed");
+        timer.stage("scan");
+        timer.stage("embed");
+
+        let names: Vec<&str> = timer.stages().iter().map(|(s, _)| s.as_str()).collect();
+        assert_eq!(names, vec!["embed", "scan"]);
+        let staged: f64 = timer.stages().iter().map(|(_, ms)| ms).sum();
+        assert!(staged <= timer.total_ms());
+    }
+}
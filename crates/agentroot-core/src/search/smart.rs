@@ -2,7 +2,8 @@
 
 use crate::db::Database;
 use crate::error::Result;
-use crate::llm::{HttpEmbedder, HttpQueryParser};
+use crate::llm::{default_embedder, HttpQueryParser};
+use crate::search::journal::{parse_date_phrase, retain_in_range};
 use crate::search::{hybrid_search, SearchOptions, SearchResult};
 
 /// Smart search that understands natural language queries
@@ -12,11 +13,39 @@ use crate::search::{hybrid_search, SearchOptions, SearchResult};
 /// - "rust tutorials by Alice" → applies metadata filter
 /// - "recent python code" → semantic search with recency
 ///
+/// Date phrases such as "in March" or "last week" are resolved locally and
+/// restrict results to notes about those days (see [`super::journal`]).
+///
 /// Falls back to BM25 search if query parser model is not available.
+#[tracing::instrument(name = "smart_search", skip_all, fields(query = %query))]
 pub async fn smart_search(
     db: &Database,
     query: &str,
     options: &SearchOptions,
+) -> Result<Vec<SearchResult>> {
+    let (terms, range) = parse_date_phrase(query, chrono::Local::now().date_naive());
+    let Some(range) = range.filter(|_| !terms.trim().is_empty()) else {
+        return parse_and_search(db, query, options).await;
+    };
+
+    tracing::info!(
+        "Date phrase in '{}' → {} to {}",
+        query,
+        range.from,
+        range.to
+    );
+    let mut dated = options.clone();
+    dated.metadata_filters.extend(range.filters());
+    let mut results = parse_and_search(db, &terms, &dated).await?;
+    // Vector results are not filtered in SQL
+    retain_in_range(db, &mut results, range)?;
+    Ok(results)
+}
+
+async fn parse_and_search(
+    db: &Database,
+    query: &str,
+    options: &SearchOptions,
 ) -> Result<Vec<SearchResult>> {
     // Try to parse the natural language query using HTTP service
     let parser_result = HttpQueryParser::from_env();
@@ -37,12 +66,12 @@ pub async fn smart_search(
         let mut results = match parsed.search_type {
             crate::llm::SearchType::Bm25 => db.search_fts(&parsed.search_terms, options)?,
             crate::llm::SearchType::Vector => {
-                // Vector search requires HTTP embedder
-                match HttpEmbedder::from_env() {
+                // Vector search requires an embedder
+                match default_embedder().await {
                     Ok(embedder) => {
                         // Try vector search, fall back to BM25 if it fails (e.g., no embeddings yet)
                         match db
-                            .search_vec(&parsed.search_terms, &embedder, options)
+                            .search_vec(&parsed.search_terms, embedder.as_ref(), options)
                             .await
                         {
                             Ok(results) => results,
@@ -56,21 +85,21 @@ pub async fn smart_search(
                         }
                     }
                     Err(_) => {
-                        tracing::warn!("HTTP embedder not configured, falling back to BM25");
+                        tracing::warn!("Embedder unavailable, falling back to BM25");
                         db.search_fts(&parsed.search_terms, options)?
                     }
                 }
             }
             crate::llm::SearchType::Hybrid => {
-                // Hybrid search requires HTTP embedder
-                match HttpEmbedder::from_env() {
+                // Hybrid search requires an embedder
+                match default_embedder().await {
                     Ok(embedder) => {
                         // Try hybrid search, fall back to BM25 if it fails (e.g., no embeddings yet)
                         match hybrid_search(
                             db,
                             &parsed.search_terms,
                             options,
-                            &embedder,
+                            embedder.as_ref(),
                             None,
                             None,
                         )
@@ -87,7 +116,7 @@ pub async fn smart_search(
                         }
                     }
                     Err(_) => {
-                        tracing::warn!("HTTP embedder not configured, falling back to BM25");
+                        tracing::warn!("Embedder unavailable, falling back to BM25");
                         db.search_fts(&parsed.search_terms, options)?
                     }
                 }
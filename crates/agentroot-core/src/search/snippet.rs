@@ -70,7 +70,7 @@ fn find_query_position(content: &str, query: &str) -> usize {
     let query_lower = query.to_lowercase();
 
     // Try to find exact match first (case-insensitive)
-    if let Some(pos) = content.to_lowercase().find(&query_lower) {
+    if content.to_lowercase().contains(&query_lower) {
         // Find actual position in original content using case-insensitive character-by-character search
         if let Some(actual_pos) = find_case_insensitive(content, &query_lower) {
             return actual_pos;
@@ -81,11 +81,7 @@ impl SearchStats {
         let hits = self.cache_hits.load(Ordering::Relaxed);
         let misses = self.cache_misses.load(Ordering::Relaxed);
 
-        let avg_latency_us = if total_queries > 0 {
-            total_latency / total_queries
-        } else {
-            0
-        };
+        let avg_latency_us = total_latency.checked_div(total_queries).unwrap_or(0);
 
         let total_cache = hits + misses;
         let cache_hit_rate = if total_cache > 0 {
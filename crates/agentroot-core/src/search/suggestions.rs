@@ -9,6 +9,7 @@ use std::collections::HashSet;
 pub struct SearchSuggestions {
     pub related_directories: Vec<String>,
     pub related_concepts: Vec<String>,
+    pub related_documents: Vec<String>,
     pub refinement_queries: Vec<String>,
     pub unseen_related: usize,
 }
@@ -37,6 +38,8 @@ pub fn compute_suggestions(
         .take(10)
         .collect();
 
+    let related_documents = related_to_top_result(db, results);
+
     let refinement_queries = generate_refinements(query, &related_concepts);
 
     let unseen_related = match session_id {
@@ -47,11 +50,29 @@ pub fn compute_suggestions(
     Ok(SearchSuggestions {
         related_directories,
         related_concepts,
+        related_documents,
         refinement_queries,
         unseen_related,
     })
 }
 
+/// Documents related to the top hit that are not already in the results
+fn related_to_top_result(db: &Database, results: &[SearchResult]) -> Vec<String> {
+    let Some(top) = results.first() else {
+        return Vec::new();
+    };
+    let in_results: HashSet<&str> = results.iter().map(|r| r.filepath.as_str()).collect();
+
+    // Best-effort: recommendations are optional, a failure must not fail the search
+    db.related(&top.hash, 10)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|r| format!("agentroot://{}/{}", r.collection, r.path))
+        .filter(|p| !in_results.contains(p.as_str()))
+        .take(5)
+        .collect()
+}
+
 fn generate_refinements(query: &str, concepts: &[String]) -> Vec<String> {
     concepts
         .iter()
@@ -170,6 +191,36 @@ mod tests {
         assert!(concepts.contains("index"));
     }
 
+    #[test]
+    fn test_compute_suggestions_related_documents() {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        let now = chrono::Utc::now().to_rfc3339();
+        db.add_collection("test", "/test", "**/*.md", "file", None)
+            .unwrap();
+        db.insert_content("aaa111", "See [b](b.md) and [c](c.md)")
+            .unwrap();
+        db.insert_content("bbb222", "B").unwrap();
+        db.insert_content("ccc333", "C").unwrap();
+        for (path, hash) in [("a.md", "aaa111"), ("b.md", "bbb222"), ("c.md", "ccc333")] {
+            db.insert_document("test", path, path, hash, &now, &now, "file", None)
+                .unwrap();
+        }
+        db.build_link_graph().unwrap();
+
+        let mut top = make_result("agentroot://test/a.md", None);
+        top.hash = "aaa111".to_string();
+        let results = vec![top, make_result("agentroot://test/b.md", None)];
+
+        let suggestions = compute_suggestions(&db, &results, "a", None).unwrap();
+
+        // b.md is already in the results, so only c.md is suggested
+        assert_eq!(
+            suggestions.related_documents,
+            vec!["agentroot://test/c.md".to_string()]
+        );
+    }
+
     #[test]
     fn test_generate_refinements() {
         let concepts = vec!["auth".to_string(), "jwt".to_string(), "session".to_string()];
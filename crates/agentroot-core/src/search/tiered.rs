@@ -6,19 +6,14 @@
 
 use super::SearchResult;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum DetailLevel {
     L0,
+    #[default]
     L1,
     L2,
 }
 
-impl Default for DetailLevel {
-    fn default() -> Self {
-        DetailLevel::L1
-    }
-}
-
 impl DetailLevel {
     pub fn from_str_opt(s: Option<&str>) -> Self {
         match s {
@@ -114,7 +109,7 @@ mod tests {
     #[test]
     fn test_first_sentence_multibyte_utf8() {
         // 200+ bytes of multibyte characters: each CJK char is 3 bytes
-        let cjk: String = std::iter::repeat('\u{4e00}').take(80).collect(); // 240 bytes
+        let cjk: String = "\u{4e00}".repeat(80); // 240 bytes
         let result = first_sentence(&cjk);
         assert!(result.ends_with("..."));
         // Must not panic and must be valid UTF-8
@@ -124,7 +119,7 @@ mod tests {
     #[test]
     fn test_first_sentence_emoji() {
         // Each emoji is 4 bytes; 51 emojis = 204 bytes
-        let emojis: String = std::iter::repeat('\u{1F600}').take(51).collect();
+        let emojis: String = "\u{1F600}".repeat(51);
         let result = first_sentence(&emojis);
         assert!(result.ends_with("..."));
     }
@@ -7,7 +7,7 @@ use super::{hybrid_search, parse_metadata_filters, SearchOptions, SearchResult};
 use crate::db::Database;
 use crate::error::Result;
 use crate::llm::{
-    heuristic_strategy, HttpEmbedder, HttpQueryExpander, HttpQueryParser, HttpReranker,
+    default_embedder, heuristic_strategy, HttpQueryExpander, HttpQueryParser, HttpReranker,
     HttpStrategyAnalyzer,
 };
 
@@ -19,6 +19,7 @@ use crate::llm::{
 /// 5. Uses reranking when available
 ///
 /// This is the ONE search function users should use.
+#[tracing::instrument(name = "unified_search", skip_all, fields(query = %query))]
 pub async fn unified_search(
     db: &Database,
     query: &str,
@@ -168,24 +169,29 @@ async fn execute_intelligent_search(
         }
 
         SearchStrategy::Vector => {
-            let embedder = HttpEmbedder::from_env()?;
+            let embedder = default_embedder().await?;
             // For now, vector search at document level
             // TODO: Add chunk-level vector search
-            db.search_vec(query, &embedder, options).await?
+            db.search_vec(query, embedder.as_ref(), options).await?
         }
 
         SearchStrategy::Hybrid => {
-            let embedder = HttpEmbedder::from_env()?;
+            let embedder = default_embedder().await?;
             let expander = HttpQueryExpander::from_env().ok();
             let reranker = HttpReranker::from_env().ok();
 
+            // An explicit alpha wins over the analyzer's suggestion
+            let mut options = options.clone();
+            options.alpha = options.alpha.or(analysis.alpha);
+            let options = &options;
+
             // For now, hybrid at document level
             // TODO: Add chunk-level hybrid search
             hybrid_search(
                 db,
                 query,
                 options,
-                &embedder,
+                embedder.as_ref(),
                 expander
                     .as_ref()
                     .map(|e| e as &dyn crate::llm::QueryExpander),
@@ -354,6 +360,7 @@ fn capitalize_extension(ext: &str) -> String {
         "java" => "Java",
         "cpp" | "cc" | "cxx" => "C++",
         "c" => "C",
+        "cs" => "C#",
         "rb" => "Ruby",
         "php" => "PHP",
         "swift" => "Swift",
@@ -3,9 +3,12 @@
 //! Computes cosine similarity between query embedding and stored embeddings.
 
 use super::ann_index::AnnIndex;
-use super::{extract_snippet, SearchOptions, SearchResult, SearchSource};
-use crate::db::vectors::cosine_similarity;
-use crate::db::{docid_from_hash, Database};
+use super::slow_log::{log_if_slow, QueryTimer};
+use super::{
+    author_filter_sql, extract_snippet, importance_boost, tag_filter_sql, take_page, SearchOptions,
+    SearchResult, SearchSource,
+};
+use crate::db::{docid_from_hash, Database, VectorFilter};
 use crate::error::Result;
 use crate::llm::Embedder;
 use std::collections::HashMap;
@@ -29,43 +32,73 @@ impl Database {
         embedder: &dyn Embedder,
         options: &SearchOptions,
         ann_index: Option<&AnnIndex>,
+    ) -> Result<Vec<SearchResult>> {
+        let mut timer = QueryTimer::new();
+        let results = self
+            .vector_search(
+                query,
+                embedder,
+                &options.page_window(),
+                ann_index,
+                &mut timer,
+            )
+            .await?;
+        let results = take_page(results, options);
+        log_if_slow(self, "vector", query, options, &timer, results.len());
+        Ok(results)
+    }
+
+    /// Vector search that records stage timings on a caller-owned timer
+    #[tracing::instrument(
+        name = "vector_search",
+        skip_all,
+        fields(query = %query, ann = ann_index.is_some())
+    )]
+    pub(crate) async fn vector_search(
+        &self,
+        query: &str,
+        embedder: &dyn Embedder,
+        options: &SearchOptions,
+        ann_index: Option<&AnnIndex>,
+        timer: &mut QueryTimer,
     ) -> Result<Vec<SearchResult>> {
         // Get query embedding
         let query_embedding = embedder.embed(&format_query_for_embedding(query)).await?;
+        timer.stage("embed");
+
+        // Without an explicit index, unfiltered searches use the one saved by
+        // `embed --optimize`
+        let filter = VectorFilter::from_options(options);
+        let saved = match ann_index {
+            None if filter.is_empty() => self.saved_ann_index()?,
+            _ => None,
+        };
+        let ann_index = ann_index.or(saved.as_deref());
 
         // Use ANN index if available and built, otherwise brute-force
         let fetch_limit = options.limit * 3;
         let similarities = if let Some(ann) = ann_index.filter(|a| a.is_built()) {
             ann.search(&query_embedding, fetch_limit)
         } else {
-            // Brute-force: load all embeddings and compute cosine similarity
-            let stored_embeddings = if let Some(ref coll) = options.collection {
-                self.get_embeddings_for_collection(coll)?
-            } else {
-                self.get_all_embeddings()?
-            };
-
-            let mut sims: Vec<(String, f32)> = stored_embeddings
-                .iter()
-                .map(|(hash_seq, embedding)| {
-                    let sim = cosine_similarity(&query_embedding, embedding);
-                    (hash_seq.clone(), sim)
-                })
-                .collect();
-
-            sims.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-            sims
+            // Exact scan, streamed so only the top candidates stay in memory
+            self.top_k_embeddings(&query_embedding, fetch_limit, filter)?
         };
+        timer.stage("scan");
 
         // Take top candidates
         let top_candidates: Vec<_> = similarities.into_iter().take(fetch_limit).collect();
 
         // Fetch document details for top candidates
+        let importance_weight = options.ranking_weights().importance;
         let mut results = Vec::new();
         for (hash_seq, score) in top_candidates {
-            if let Some(result) =
-                self.get_search_result_for_hash_seq(&hash_seq, score, query, options)?
-            {
+            if let Some(result) = self.get_search_result_for_hash_seq(
+                &hash_seq,
+                score,
+                query,
+                options,
+                importance_weight,
+            )? {
                 results.push(result);
             }
         }
@@ -84,7 +117,11 @@ impl Database {
             b.score
                 .partial_cmp(&a.score)
                 .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.hash.cmp(&b.hash))
         });
+        timer.stage("fetch");
+        super::feedback::apply_feedback(self, query, &mut final_results)?;
+        timer.stage("feedback");
 
         // Filter by min_score and limit
         let mut filtered: Vec<SearchResult> = final_results
@@ -107,12 +144,13 @@ impl Database {
     }
 
     /// Get search result for a hash_seq
-    fn get_search_result_for_hash_seq(
+    pub(super) fn get_search_result_for_hash_seq(
         &self,
         hash_seq: &str,
         score: f32,
         query: &str,
         options: &SearchOptions,
+        importance_weight: f64,
     ) -> Result<Option<SearchResult>> {
         // Parse hash_seq (format: "hash_seq")
         let parts: Vec<&str> = hash_seq.rsplitn(2, '_').collect();
@@ -164,11 +202,12 @@ impl Database {
                     params_vec.push(Box::new(value.clone()));
                 }
                 "tag" | "keyword" => {
-                    sql.push_str(&format!(
-                        " AND d.llm_keywords LIKE ?{}",
-                        params_vec.len() + 1
-                    ));
-                    params_vec.push(Box::new(format!("%{}%", value)));
+                    sql.push_str(&tag_filter_sql(params_vec.len() + 1));
+                    params_vec.push(Box::new(value.clone()));
+                }
+                "author" => {
+                    sql.push_str(&author_filter_sql(params_vec.len() + 1));
+                    params_vec.push(Box::new(value.clone()));
                 }
                 _ => {}
             }
@@ -194,7 +233,8 @@ impl Database {
                 let collection_name: String = row.get(4)?;
 
                 // Apply importance boost (like BM25 does)
-                let mut boosted_score = score as f64 * importance_score;
+                let mut boosted_score =
+                    score as f64 * importance_boost(importance_score, importance_weight);
 
                 // Collection boost: prefer documentation collections over source code
                 // agentroot (docs) > agentroot-src (source code with tests)
@@ -292,6 +332,7 @@ impl Database {
     }
 
     /// Perform vector similarity search on chunks
+    #[tracing::instrument(name = "chunk_vector_search", skip_all, fields(query = %query))]
     pub async fn search_chunks_vec(
         &self,
         query: &str,
@@ -301,31 +342,13 @@ impl Database {
         // Get query embedding
         let query_embedding = embedder.embed(&format_query_for_embedding(query)).await?;
 
-        // Get all chunk embeddings (optionally filtered by collection)
-        let chunk_embeddings = if let Some(ref coll) = options.collection {
-            self.get_chunk_embeddings_for_collection(coll, embedder.model_name())?
-        } else {
-            self.get_all_chunk_embeddings(embedder.model_name())?
-        };
-
-        if chunk_embeddings.is_empty() {
-            return Ok(Vec::new());
-        }
-
-        // Compute similarities
-        let mut similarities: Vec<(String, f32)> = chunk_embeddings
-            .iter()
-            .map(|(chunk_hash, embedding)| {
-                let sim = cosine_similarity(&query_embedding, embedding);
-                (chunk_hash.clone(), sim)
-            })
-            .collect();
-
-        // Sort by similarity (descending)
-        similarities.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-
-        // Take top candidates
-        let top_candidates: Vec<_> = similarities.into_iter().take(options.limit).collect();
+        // Stream chunk embeddings, pre-filtered by collection and provider
+        let top_candidates = self.top_k_chunk_embeddings(
+            &query_embedding,
+            options.limit,
+            embedder.model_name(),
+            VectorFilter::from_options(options),
+        )?;
 
         // Fetch chunk details for top candidates
         let mut results = Vec::new();
@@ -457,57 +480,9 @@ impl Database {
             Err(e) => Err(e.into()),
         }
     }
-
-    /// Get chunk embeddings for a collection
-    fn get_chunk_embeddings_for_collection(
-        &self,
-        collection: &str,
-        model: &str,
-    ) -> Result<Vec<(String, Vec<f32>)>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT ce.chunk_hash, ce.embedding
-             FROM chunk_embeddings ce
-             JOIN chunks ch ON ch.hash = ce.chunk_hash
-             JOIN documents d ON d.hash = ch.document_hash
-             WHERE d.collection = ?1 AND d.active = 1 AND ce.model = ?2",
-        )?;
-
-        let results = stmt
-            .query_map(rusqlite::params![collection, model], |row| {
-                let chunk_hash: String = row.get(0)?;
-                let embedding_blob: Vec<u8> = row.get(1)?;
-                let embedding = crate::db::vectors::bytes_to_embedding(&embedding_blob);
-                Ok((chunk_hash, embedding))
-            })?
-            .collect::<std::result::Result<Vec<_>, _>>()?;
-
-        Ok(results)
-    }
-
-    /// Get all chunk embeddings
-    fn get_all_chunk_embeddings(&self, model: &str) -> Result<Vec<(String, Vec<f32>)>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT ce.chunk_hash, ce.embedding
-             FROM chunk_embeddings ce
-             JOIN chunks ch ON ch.hash = ce.chunk_hash
-             JOIN documents d ON d.hash = ch.document_hash
-             WHERE d.active = 1 AND ce.model = ?1",
-        )?;
-
-        let results = stmt
-            .query_map(rusqlite::params![model], |row| {
-                let chunk_hash: String = row.get(0)?;
-                let embedding_blob: Vec<u8> = row.get(1)?;
-                let embedding = crate::db::vectors::bytes_to_embedding(&embedding_blob);
-                Ok((chunk_hash, embedding))
-            })?
-            .collect::<std::result::Result<Vec<_>, _>>()?;
-
-        Ok(results)
-    }
 }
 
 /// Format query for embedding (matches document format)
 fn format_query_for_embedding(query: &str) -> String {
-    format!("search_query: {}", query)
+    format!("search_query: {}", crate::index::normalize_text(query))
 }
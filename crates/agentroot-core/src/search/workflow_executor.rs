@@ -4,19 +4,38 @@ use super::{hybrid_search, SearchOptions, SearchResult, SearchSource};
 use crate::db::Database;
 use crate::error::Result;
 use crate::llm::{
-    HttpEmbedder, HttpQueryExpander, HttpReranker, MergeStrategy, QueryExpander, RerankDocument,
-    Reranker, Workflow, WorkflowContext, WorkflowStep,
+    default_embedder, HttpQueryExpander, HttpReranker, MergeStrategy, QueryExpander,
+    RerankDocument, Reranker, Workflow, WorkflowContext, WorkflowStep,
 };
+use crate::progress::{check_cancelled, CancellationToken};
 use chrono::{DateTime, Duration, Utc};
 use rusqlite::params;
 use std::collections::HashMap;
+use tracing::Instrument;
 
 /// Execute a planned workflow
+#[tracing::instrument(
+    name = "workflow",
+    skip_all,
+    fields(query = %query, steps = workflow.steps.len())
+)]
 pub async fn execute_workflow(
     db: &Database,
     workflow: &Workflow,
     query: &str,
     options: &SearchOptions,
+) -> Result<Vec<SearchResult>> {
+    execute_workflow_cancellable(db, workflow, query, options, &CancellationToken::new()).await
+}
+
+/// Execute a planned workflow, stopping between steps once `cancel` is
+/// triggered
+pub async fn execute_workflow_cancellable(
+    db: &Database,
+    workflow: &Workflow,
+    query: &str,
+    options: &SearchOptions,
+    cancel: &CancellationToken,
 ) -> Result<Vec<SearchResult>> {
     let mut context = WorkflowContext::new(query.to_string());
 
@@ -27,9 +46,16 @@ pub async fn execute_workflow(
     );
 
     for (idx, step) in workflow.steps.iter().enumerate() {
+        check_cancelled(cancel, "workflow")?;
         tracing::debug!("Step {}/{}: {:?}", idx + 1, workflow.steps.len(), step);
 
-        context = execute_step(db, step, context, options).await?;
+        context = execute_step(db, step, context, options)
+            .instrument(tracing::info_span!(
+                "workflow_step",
+                step = step.name(),
+                index = idx + 1
+            ))
+            .await?;
 
         tracing::debug!("After step {}: {} results", idx + 1, context.results.len());
     }
@@ -73,11 +99,11 @@ async fn execute_step(
         }
 
         WorkflowStep::VectorSearch { query, limit } => {
-            let embedder = HttpEmbedder::from_env()?;
+            let embedder = default_embedder().await?;
             let mut opts = base_options.clone();
             opts.limit = *limit;
 
-            let mut new_results = db.search_vec(query, &embedder, &opts).await?;
+            let mut new_results = db.search_vec(query, embedder.as_ref(), &opts).await?;
             let count = new_results.len();
             context.results.append(&mut new_results);
             context
@@ -91,7 +117,7 @@ async fn execute_step(
             use_expansion,
             use_reranking,
         } => {
-            let embedder = HttpEmbedder::from_env()?;
+            let embedder = default_embedder().await?;
             let mut opts = base_options.clone();
             opts.limit = *limit;
 
@@ -111,7 +137,7 @@ async fn execute_step(
                 db,
                 query,
                 &opts,
-                &embedder,
+                embedder.as_ref(),
                 expander
                     .as_ref()
                     .map(|e| e as &dyn crate::llm::QueryExpander),
@@ -427,11 +453,11 @@ async fn execute_step(
                         }
 
                         // Search using semantic variations (vector search)
-                        if let Ok(embedder) = HttpEmbedder::from_env() {
+                        if let Ok(embedder) = default_embedder().await {
                             for variant in &expanded.semantic {
                                 if variant != original_query {
                                     let opts = base_options.clone();
-                                    match db.search_vec(variant, &embedder, &opts).await {
+                                    match db.search_vec(variant, embedder.as_ref(), &opts).await {
                                         Ok(mut results) => {
                                             all_results.append(&mut results);
                                         }
@@ -624,11 +650,13 @@ async fn execute_step(
         }
 
         WorkflowStep::VectorChunkSearch { query, limit } => {
-            let embedder = HttpEmbedder::from_env()?;
+            let embedder = default_embedder().await?;
             let mut opts = base_options.clone();
             opts.limit = *limit;
 
-            let mut new_results = db.search_chunks_vec(query, &embedder, &opts).await?;
+            let mut new_results = db
+                .search_chunks_vec(query, embedder.as_ref(), &opts)
+                .await?;
             let count = new_results.len();
             context.results.append(&mut new_results);
             context
@@ -767,12 +795,17 @@ fn extract_number(s: &str) -> Option<i64> {
 
 /// Merge results using Reciprocal Rank Fusion (RRF)
 fn merge_results_rrf(results: &[SearchResult]) -> Vec<SearchResult> {
+    merge_ranked_lists_rrf(&[results])
+}
+
+/// Fuse several ranked lists with RRF, ranking each list independently
+pub(crate) fn merge_ranked_lists_rrf(lists: &[&[SearchResult]]) -> Vec<SearchResult> {
     const RRF_K: f64 = 60.0;
 
     // Group results by hash
     let mut score_map: HashMap<String, (f64, SearchResult)> = HashMap::new();
 
-    for (rank, result) in results.iter().enumerate() {
+    for (rank, result) in lists.iter().flat_map(|list| list.iter().enumerate()) {
         let rrf_score = 1.0 / (rank as f64 + RRF_K);
 
         score_map
@@ -300,11 +300,10 @@ async fn test_end_to_end_metadata_generation() {
 
     let mut metadata_count = 0;
     for result in &all_results {
-        if result.llm_summary.is_some() {
+        if let Some(summary) = result.llm_summary.as_ref() {
             metadata_count += 1;
 
             // Verify metadata quality
-            let summary = result.llm_summary.as_ref().unwrap();
             assert!(
                 !summary.is_empty(),
                 "Summary should not be empty for {}",
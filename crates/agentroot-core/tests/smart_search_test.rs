@@ -91,7 +91,7 @@ async fn test_smart_search_with_metadata() {
     let alice_meta = MetadataBuilder::new().text("author", "Alice").build();
     let doc1_path = file1.to_str().unwrap();
     if let Ok(docs) = db.get_documents_by_pattern("doc1.md") {
-        if let Some(_) = docs.first() {
+        if !docs.is_empty() {
             db.add_metadata(doc1_path, &alice_meta).unwrap();
         }
     }
@@ -99,7 +99,7 @@ async fn test_smart_search_with_metadata() {
     let bob_meta = MetadataBuilder::new().text("author", "Bob").build();
     let doc2_path = file2.to_str().unwrap();
     if let Ok(docs) = db.get_documents_by_pattern("doc2.md") {
-        if let Some(_) = docs.first() {
+        if !docs.is_empty() {
             db.add_metadata(doc2_path, &bob_meta).unwrap();
         }
     }
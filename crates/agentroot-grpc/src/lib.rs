@@ -0,0 +1,51 @@
+//! gRPC API for agentroot
+//!
+//! Serves the `agentroot.v1.AgentRoot` service defined in
+//! `proto/agentroot.proto`: search (unary and streaming), document retrieval,
+//! background indexing and status. Clients in any language can generate typed
+//! stubs from the proto file instead of spawning the CLI per request.
+
+mod service;
+
+pub mod proto {
+    tonic::include_proto!("agentroot.v1");
+}
+
+pub use service::AgentRootService;
+
+use agentroot_core::db::DbPool;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+/// Default listen address
+pub const DEFAULT_ADDR: &str = "127.0.0.1:50051";
+
+/// Connections (and threads) used to serve requests concurrently
+pub const DEFAULT_WORKERS: usize = 4;
+
+/// Serve the gRPC API on `addr` until the process is interrupted
+///
+/// With `read_only`, indexing requests fail with `PERMISSION_DENIED`.
+pub async fn serve(
+    db_path: PathBuf,
+    addr: SocketAddr,
+    workers: usize,
+    read_only: bool,
+) -> anyhow::Result<()> {
+    let pool = if read_only {
+        DbPool::open_read_only(&db_path, workers)?
+    } else {
+        DbPool::open(&db_path, workers)?
+    };
+    tracing::info!("gRPC server listening on {}", addr);
+
+    tonic::transport::Server::builder()
+        .add_service(proto::agent_root_server::AgentRootServer::new(
+            AgentRootService::new(pool),
+        ))
+        .serve_with_shutdown(addr, async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,315 @@
+//! AgentRoot gRPC service implementation
+
+use crate::proto::agent_root_server::AgentRoot;
+use crate::proto::{
+    ChunkLocation, Document, GetJobRequest, GetRequest, IndexRequest, IndexResponse, Job,
+    SearchHit, SearchMode, SearchRequest, SearchResponse, StatusRequest, StatusResponse,
+};
+use agentroot_core::db::{DbPool, JobKind, JobStatus};
+use agentroot_core::error::AgentRootError;
+use agentroot_core::{
+    Database, DetailLevel, Embedder, HttpQueryExpander, HttpReranker, QueryExpander, Reranker,
+    SearchOptions, SearchResult, SearchSource,
+};
+use futures::Stream;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::pin::Pin;
+use tonic::{Request, Response, Status};
+
+/// Results returned when a request does not set a limit
+const DEFAULT_LIMIT: usize = 20;
+
+pub struct AgentRootService {
+    pool: DbPool,
+}
+
+impl AgentRootService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    async fn run<T, F>(&self, f: F) -> Result<T, Status>
+    where
+        T: Send + 'static,
+        F: for<'a> FnOnce(&'a Database) -> futures::future::LocalBoxFuture<'a, Result<T, Status>>
+            + Send
+            + 'static,
+    {
+        self.pool
+            .run(f)
+            .await
+            .map_err(|e| Status::unavailable(e.to_string()))?
+    }
+
+    async fn search_hits(&self, request: SearchRequest) -> Result<Vec<SearchHit>, Status> {
+        if request.query.trim().is_empty() {
+            return Err(Status::invalid_argument("query must not be empty"));
+        }
+        self.run(move |db| Box::pin(async move { search(db, &request).await }))
+            .await
+    }
+}
+
+type SearchHitStream = Pin<Box<dyn Stream<Item = Result<SearchHit, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl AgentRoot for AgentRootService {
+    async fn search(
+        &self,
+        request: Request<SearchRequest>,
+    ) -> Result<Response<SearchResponse>, Status> {
+        let results = self.search_hits(request.into_inner()).await?;
+        Ok(Response::new(SearchResponse { results }))
+    }
+
+    type SearchStreamStream = SearchHitStream;
+
+    async fn search_stream(
+        &self,
+        request: Request<SearchRequest>,
+    ) -> Result<Response<Self::SearchStreamStream>, Status> {
+        let results = self.search_hits(request.into_inner()).await?;
+        Ok(Response::new(Box::pin(futures::stream::iter(
+            results.into_iter().map(Ok),
+        ))))
+    }
+
+    async fn get(&self, request: Request<GetRequest>) -> Result<Response<Document>, Status> {
+        let id = request.into_inner().id;
+        let document = self
+            .run(move |db| {
+                Box::pin(async move {
+                    let collections: HashMap<String, PathBuf> = db
+                        .list_collections()
+                        .map_err(core_status)?
+                        .into_iter()
+                        .map(|c| (c.name, PathBuf::from(c.path)))
+                        .collect();
+                    let doc = db
+                        .lookup_document(&id, &collections)
+                        .map_err(core_status)?
+                        .ok_or_else(|| Status::not_found(format!("Document not found: {}", id)))?;
+                    Ok(Document {
+                        docid: doc.docid,
+                        uri: doc.filepath,
+                        path: doc.display_path,
+                        title: doc.title,
+                        collection: doc.collection_name,
+                        modified_at: doc.modified_at,
+                        body: doc.body.unwrap_or_default(),
+                    })
+                })
+            })
+            .await?;
+        Ok(Response::new(document))
+    }
+
+    async fn index(
+        &self,
+        request: Request<IndexRequest>,
+    ) -> Result<Response<IndexResponse>, Status> {
+        let request = request.into_inner();
+        let jobs = self
+            .run(move |db| {
+                Box::pin(async move {
+                    if let Some(name) = &request.collection {
+                        if db.get_collection(name).map_err(core_status)?.is_none() {
+                            return Err(Status::not_found(format!(
+                                "Collection not found: {}",
+                                name
+                            )));
+                        }
+                    }
+                    let mut kinds = vec![JobKind::Reindex {
+                        collection: request.collection.clone(),
+                    }];
+                    if request.embed {
+                        kinds.push(JobKind::Embed { force: false });
+                    }
+
+                    let mut jobs = Vec::new();
+                    for kind in &kinds {
+                        let id = db
+                            .enqueue_job(kind, request.priority)
+                            .map_err(core_status)?;
+                        if let Some(job) = db.get_job(id).map_err(core_status)? {
+                            jobs.push(job_message(job));
+                        }
+                    }
+                    Ok(jobs)
+                })
+            })
+            .await?;
+        Ok(Response::new(IndexResponse { jobs }))
+    }
+
+    async fn get_job(&self, request: Request<GetJobRequest>) -> Result<Response<Job>, Status> {
+        let id = request.into_inner().id;
+        let job = self
+            .run(move |db| {
+                Box::pin(async move {
+                    db.get_job(id)
+                        .map_err(core_status)?
+                        .map(job_message)
+                        .ok_or_else(|| Status::not_found(format!("Job not found: {}", id)))
+                })
+            })
+            .await?;
+        Ok(Response::new(job))
+    }
+
+    async fn status(
+        &self,
+        _request: Request<StatusRequest>,
+    ) -> Result<Response<StatusResponse>, Status> {
+        let status = self
+            .run(|db| {
+                Box::pin(async move {
+                    let collections = db.list_collections().map_err(core_status)?;
+                    let queued = db
+                        .list_jobs(Some(JobStatus::Queued), 10_000)
+                        .map_err(core_status)?;
+                    Ok(StatusResponse {
+                        total_documents: collections.iter().map(|c| c.document_count as u64).sum(),
+                        collections: collections
+                            .into_iter()
+                            .map(|c| crate::proto::CollectionStatus {
+                                name: c.name,
+                                path: c.path,
+                                provider: c.provider_type,
+                                documents: c.document_count as u64,
+                                updated_at: c.updated_at,
+                            })
+                            .collect(),
+                        needs_embedding: db.count_hashes_needing_embedding().map_err(core_status)?
+                            as u64,
+                        has_vector_index: db.has_vector_index(),
+                        queued_jobs: queued.len() as u64,
+                    })
+                })
+            })
+            .await?;
+        Ok(Response::new(status))
+    }
+}
+
+async fn search(db: &Database, request: &SearchRequest) -> Result<Vec<SearchHit>, Status> {
+    let options = SearchOptions {
+        limit: match request.limit {
+            0 => DEFAULT_LIMIT,
+            n => n as usize,
+        },
+        min_score: request.min_score,
+        collection: request.collection.clone(),
+        provider: request.provider.clone(),
+        detail: if request.full_content {
+            DetailLevel::L2
+        } else {
+            DetailLevel::L1
+        },
+        ..Default::default()
+    };
+    let query = request.query.as_str();
+
+    let results = match request.mode() {
+        SearchMode::Auto => agentroot_core::unified_search(db, query, &options).await,
+        SearchMode::Bm25 => db.search_fts(query, &options),
+        SearchMode::Vector => {
+            let embedder = embedder().await?;
+            db.search_vec(query, embedder.as_ref(), &options).await
+        }
+        SearchMode::Hybrid => match embedder().await {
+            Ok(embedder) => {
+                let expander = HttpQueryExpander::from_env().ok();
+                let reranker = HttpReranker::from_env().ok();
+                agentroot_core::search::hybrid_search(
+                    db,
+                    query,
+                    &options,
+                    embedder.as_ref(),
+                    expander.as_ref().map(|e| e as &dyn QueryExpander),
+                    reranker.as_ref().map(|r| r as &dyn Reranker),
+                )
+                .await
+            }
+            // Without embeddings hybrid search degrades to BM25, as over MCP
+            Err(_) => db.search_fts(query, &options),
+        },
+    }
+    .map_err(core_status)?;
+
+    Ok(results
+        .into_iter()
+        .map(|r| search_hit(r, request.full_content))
+        .collect())
+}
+
+async fn embedder() -> Result<Box<dyn Embedder>, Status> {
+    agentroot_core::default_embedder().await.map_err(|e| {
+        Status::failed_precondition(format!(
+            "Could not load an embedding model: {}. Set AGENTROOT_EMBEDDING_URL, \
+             AGENTROOT_EMBEDDING_MODEL, and AGENTROOT_EMBEDDING_DIMS to use an embedding service.",
+            e
+        ))
+    })
+}
+
+fn search_hit(result: SearchResult, full_content: bool) -> SearchHit {
+    let source = match result.source {
+        SearchSource::Bm25 => "bm25",
+        SearchSource::Vector => "vector",
+        SearchSource::Hybrid => "hybrid",
+        SearchSource::Glossary => "glossary",
+    };
+    let chunk = result
+        .chunk_hash
+        .filter(|_| result.is_chunk)
+        .map(|chunk_hash| ChunkLocation {
+            chunk_hash,
+            breadcrumb: result.chunk_breadcrumb,
+            start_line: result.chunk_start_line,
+            end_line: result.chunk_end_line,
+        });
+    SearchHit {
+        docid: result.docid,
+        uri: result.filepath,
+        path: result.display_path,
+        title: result.title,
+        collection: result.collection_name,
+        score: result.score,
+        source: source.to_string(),
+        modified_at: result.modified_at,
+        summary: result.llm_summary,
+        body: result.body.filter(|_| full_content),
+        chunk,
+    }
+}
+
+fn job_message(job: agentroot_core::db::Job) -> Job {
+    Job {
+        id: job.id,
+        kind: job.kind.name().to_string(),
+        status: job.status.as_str().to_string(),
+        attempts: job.attempts,
+        max_attempts: job.max_attempts,
+        last_error: job.last_error,
+        result: job.result,
+        created_at: job.created_at,
+        finished_at: job.finished_at,
+    }
+}
+
+fn core_status(error: AgentRootError) -> Status {
+    match error {
+        AgentRootError::CollectionNotFound(_) | AgentRootError::DocumentNotFound(_) => {
+            Status::not_found(error.to_string())
+        }
+        AgentRootError::InvalidInput(_) | AgentRootError::Parse(_) => {
+            Status::invalid_argument(error.to_string())
+        }
+        AgentRootError::Busy(_) => Status::unavailable(error.to_string()),
+        AgentRootError::ReadOnly(_) => Status::permission_denied(error.to_string()),
+        _ => Status::internal(error.to_string()),
+    }
+}
@@ -0,0 +1,129 @@
+//! End-to-end test of the gRPC service over a real socket
+
+use agentroot_core::Database;
+use agentroot_grpc::proto::agent_root_client::AgentRootClient;
+use agentroot_grpc::proto::{
+    GetJobRequest, GetRequest, IndexRequest, SearchMode, SearchRequest, StatusRequest,
+};
+use futures::StreamExt;
+use std::time::Duration;
+use tempfile::TempDir;
+
+async fn start_server(db_path: std::path::PathBuf) -> AgentRootClient<tonic::transport::Channel> {
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    tokio::spawn(agentroot_grpc::serve(db_path, addr, 2, false));
+
+    let endpoint = format!("http://{}", addr);
+    for _ in 0..50 {
+        if let Ok(client) = AgentRootClient::connect(endpoint.clone()).await {
+            return client;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("gRPC server did not start");
+}
+
+#[tokio::test]
+async fn test_search_get_index_status() {
+    let docs = TempDir::new().unwrap();
+    std::fs::write(
+        docs.path().join("tokio.md"),
+        "# Tokio Runtime\n\nThe tokio runtime schedules async tasks.",
+    )
+    .unwrap();
+    std::fs::write(
+        docs.path().join("serde.md"),
+        "# Serde\n\nSerialization framework for Rust.",
+    )
+    .unwrap();
+
+    let db_dir = TempDir::new().unwrap();
+    let db_path = db_dir.path().join("index.sqlite");
+    let db = Database::open(&db_path).unwrap();
+    db.initialize().unwrap();
+    db.add_collection(
+        "notes",
+        docs.path().to_str().unwrap(),
+        "**/*.md",
+        "file",
+        None,
+    )
+    .unwrap();
+    db.reindex_collection("notes").await.unwrap();
+    drop(db);
+
+    let mut client = start_server(db_path).await;
+
+    let status = client.status(StatusRequest {}).await.unwrap().into_inner();
+    assert_eq!(status.total_documents, 2);
+    assert_eq!(status.collections[0].name, "notes");
+
+    let request = SearchRequest {
+        query: "tokio".to_string(),
+        mode: SearchMode::Bm25 as i32,
+        ..Default::default()
+    };
+    let results = client
+        .search(request.clone())
+        .await
+        .unwrap()
+        .into_inner()
+        .results;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].path, "notes/tokio.md");
+    assert!(results[0].body.is_none());
+
+    let streamed: Vec<_> = client
+        .search_stream(request)
+        .await
+        .unwrap()
+        .into_inner()
+        .collect()
+        .await;
+    assert_eq!(streamed.len(), 1);
+    assert_eq!(streamed[0].as_ref().unwrap().docid, results[0].docid);
+
+    let doc = client
+        .get(GetRequest {
+            id: results[0].docid.clone(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(doc.body.contains("schedules async tasks"));
+
+    let missing = client
+        .get(GetRequest {
+            id: "#ffffff".to_string(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(missing.code(), tonic::Code::NotFound);
+
+    let empty = client.search(SearchRequest::default()).await.unwrap_err();
+    assert_eq!(empty.code(), tonic::Code::InvalidArgument);
+
+    let jobs = client
+        .index(IndexRequest {
+            collection: Some("notes".to_string()),
+            embed: true,
+            priority: 0,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .jobs;
+    assert_eq!(jobs.len(), 2);
+    assert_eq!(jobs[0].kind, "reindex");
+    assert_eq!(jobs[1].kind, "embed");
+
+    let job = client
+        .get_job(GetJobRequest { id: jobs[0].id })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(job.status, "queued");
+}
@@ -0,0 +1,51 @@
+//! JSON error responses
+
+use agentroot_core::error::AgentRootError;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+
+/// An error returned to the client as `{"error": "..."}`
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, message)
+    }
+}
+
+impl From<AgentRootError> for ApiError {
+    fn from(error: AgentRootError) -> Self {
+        let status = match error {
+            AgentRootError::CollectionNotFound(_) | AgentRootError::DocumentNotFound(_) => {
+                StatusCode::NOT_FOUND
+            }
+            AgentRootError::InvalidInput(_) | AgentRootError::Parse(_) => StatusCode::BAD_REQUEST,
+            AgentRootError::Busy(_) => StatusCode::SERVICE_UNAVAILABLE,
+            AgentRootError::ReadOnly(_) => StatusCode::FORBIDDEN,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self::new(status, error.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (
+            self.status,
+            Json(serde_json::json!({ "error": self.message })),
+        )
+            .into_response()
+    }
+}
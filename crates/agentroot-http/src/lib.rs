@@ -0,0 +1,113 @@
+//! HTTP API for agentroot
+//!
+//! Exposes the index over plain JSON so agent frameworks can use it as a
+//! retriever without glue code. `POST /retrieve` takes the common
+//! `{query, top_k, filters}` shape and `POST /query` follows the ChatGPT
+//! retrieval plugin API, which LangChain and LlamaIndex can call directly.
+//! `/v1/vector_stores` mirrors the OpenAI vector store search API for tools
+//! hardcoded against it.
+
+mod error;
+mod openai;
+mod retriever;
+
+pub use error::ApiError;
+pub use openai::{
+    VectorStore, VectorStoreSearchRequest, VectorStoreSearchResponse, VectorStoreSearchResult,
+};
+pub use retriever::{
+    QueryRequest, QueryResponse, QueryResult, RetrieveRequest, RetrieveResponse, RetrievedDocument,
+};
+
+use agentroot_core::db::DbPool;
+use axum::extract::{Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::Response;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Default listen address
+pub const DEFAULT_ADDR: &str = "127.0.0.1:7070";
+
+/// Connections (and threads) used to serve requests concurrently
+pub const DEFAULT_WORKERS: usize = 4;
+
+/// Build the HTTP routes
+///
+/// When `token` is set, every route except `/health` requires an
+/// `Authorization: Bearer <token>` header.
+pub fn router(pool: DbPool, token: Option<String>) -> Router {
+    let mut api = Router::new()
+        .route("/retrieve", post(retriever::retrieve))
+        .route("/query", post(retriever::query))
+        .route("/v1/vector_stores", get(openai::list_vector_stores))
+        .route("/v1/vector_stores/{id}", get(openai::get_vector_store))
+        .route(
+            "/v1/vector_stores/{id}/search",
+            post(openai::search_vector_store),
+        )
+        .with_state(Arc::new(pool));
+    if let Some(token) = token {
+        api = api.route_layer(middleware::from_fn_with_state(
+            Arc::<str>::from(token),
+            require_token,
+        ));
+    }
+    Router::new().route("/health", get(health)).merge(api)
+}
+
+/// Serve the HTTP API on `addr` until the process is interrupted
+///
+/// Requests must carry the bearer token in `AGENTROOT_HTTP_TOKEN` when it is set.
+pub async fn serve(
+    db_path: PathBuf,
+    addr: SocketAddr,
+    workers: usize,
+    read_only: bool,
+) -> anyhow::Result<()> {
+    let pool = if read_only {
+        DbPool::open_read_only(&db_path, workers)?
+    } else {
+        DbPool::open(&db_path, workers)?
+    };
+    let token = std::env::var("AGENTROOT_HTTP_TOKEN")
+        .ok()
+        .filter(|t| !t.is_empty());
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    tracing::info!("HTTP server listening on {}", listener.local_addr()?);
+    axum::serve(listener, router(pool, token))
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
+
+async fn health() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "status": "ok" }))
+}
+
+async fn require_token(
+    State(token): State<Arc<str>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let authorized = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|presented| presented == &*token);
+    if !authorized {
+        return Err(ApiError::new(
+            StatusCode::UNAUTHORIZED,
+            "Missing or invalid bearer token",
+        ));
+    }
+    Ok(next.run(request).await)
+}
@@ -0,0 +1,332 @@
+//! OpenAI-compatible vector store endpoints
+//!
+//! Collections appear as vector stores, so tools written against the OpenAI
+//! file-search API can point their base URL at agentroot. Only the read side
+//! is implemented: listing stores and searching them.
+
+use crate::error::ApiError;
+use agentroot_core::db::{CollectionInfo, DbPool};
+use agentroot_core::{
+    Database, DetailLevel, HttpQueryExpander, HttpReranker, QueryExpander, Reranker, SearchOptions,
+    SearchResult,
+};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+
+/// Results returned when `max_num_results` is not set, as in the OpenAI API
+const DEFAULT_MAX_RESULTS: usize = 10;
+
+/// The OpenAI API rejects larger `max_num_results`
+const MAX_RESULTS: usize = 50;
+
+/// `{"error": {"message", "type", ...}}`, the shape OpenAI clients parse
+pub struct OpenAiError(ApiError);
+
+impl<E: Into<ApiError>> From<E> for OpenAiError {
+    fn from(error: E) -> Self {
+        Self(error.into())
+    }
+}
+
+impl IntoResponse for OpenAiError {
+    fn into_response(self) -> Response {
+        let kind = match self.0.status {
+            StatusCode::NOT_FOUND | StatusCode::BAD_REQUEST => "invalid_request_error",
+            _ => "server_error",
+        };
+        let body = json!({
+            "error": { "message": self.0.message, "type": kind, "param": null, "code": null }
+        });
+        (self.0.status, Json(body)).into_response()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VectorStore {
+    /// Collection name
+    pub id: String,
+    pub object: String,
+    pub name: String,
+    pub created_at: i64,
+    pub status: String,
+    pub file_counts: FileCounts,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileCounts {
+    pub in_progress: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub cancelled: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum SearchQuery {
+    Text(String),
+    Many(Vec<String>),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VectorStoreSearchRequest {
+    pub query: SearchQuery,
+    #[serde(default)]
+    pub max_num_results: Option<usize>,
+    #[serde(default)]
+    pub filters: Option<Value>,
+    #[serde(default)]
+    pub ranking_options: Option<RankingOptions>,
+    /// Expand the query with the configured LLM before searching
+    #[serde(default)]
+    pub rewrite_query: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RankingOptions {
+    /// `none` disables reranking; anything else uses the configured reranker
+    #[serde(default)]
+    pub ranker: Option<String>,
+    #[serde(default)]
+    pub score_threshold: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VectorStoreSearchResponse {
+    pub object: String,
+    pub search_query: String,
+    pub data: Vec<VectorStoreSearchResult>,
+    pub has_more: bool,
+    pub next_page: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VectorStoreSearchResult {
+    /// Docid of the matching document
+    pub file_id: String,
+    pub filename: String,
+    pub score: f64,
+    pub attributes: Map<String, Value>,
+    pub content: Vec<ContentPart>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentPart {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub text: String,
+}
+
+pub(crate) async fn list_vector_stores(
+    State(pool): State<Arc<DbPool>>,
+) -> Result<Json<Value>, OpenAiError> {
+    let stores: Vec<VectorStore> = pool
+        .run(|db| Box::pin(async move { db.list_collections() }))
+        .await??
+        .into_iter()
+        .map(vector_store)
+        .collect();
+    Ok(Json(json!({
+        "object": "list",
+        "first_id": stores.first().map(|s| s.id.clone()),
+        "last_id": stores.last().map(|s| s.id.clone()),
+        "has_more": false,
+        "data": stores,
+    })))
+}
+
+pub(crate) async fn get_vector_store(
+    State(pool): State<Arc<DbPool>>,
+    Path(id): Path<String>,
+) -> Result<Json<VectorStore>, OpenAiError> {
+    let collection = pool
+        .run(move |db| Box::pin(async move { find_collection(db, &id) }))
+        .await??;
+    Ok(Json(vector_store(collection)))
+}
+
+pub(crate) async fn search_vector_store(
+    State(pool): State<Arc<DbPool>>,
+    Path(id): Path<String>,
+    Json(request): Json<VectorStoreSearchRequest>,
+) -> Result<Json<VectorStoreSearchResponse>, OpenAiError> {
+    let query = match &request.query {
+        SearchQuery::Text(text) => text.clone(),
+        SearchQuery::Many(texts) => texts.join(" "),
+    };
+    if query.trim().is_empty() {
+        return Err(ApiError::bad_request("query must not be empty").into());
+    }
+    let ranking = request.ranking_options.clone().unwrap_or_default();
+    let mut options = SearchOptions {
+        limit: request
+            .max_num_results
+            .unwrap_or(DEFAULT_MAX_RESULTS)
+            .clamp(1, MAX_RESULTS),
+        min_score: ranking.score_threshold.unwrap_or(0.0),
+        collection: Some(id.clone()),
+        detail: DetailLevel::L2,
+        ..Default::default()
+    };
+    if let Some(filters) = &request.filters {
+        apply_filter(filters, &mut options)?;
+    }
+    let rerank = ranking.ranker.as_deref() != Some("none");
+    let expand = request.rewrite_query;
+
+    let search_query = query.clone();
+    let results = pool
+        .run(move |db| {
+            Box::pin(async move {
+                find_collection(db, &id)?;
+                Ok::<_, ApiError>(hybrid(db, &query, &options, expand, rerank).await?)
+            })
+        })
+        .await??;
+
+    Ok(Json(VectorStoreSearchResponse {
+        object: "vector_store.search_results.page".to_string(),
+        search_query,
+        data: results.into_iter().map(search_result).collect(),
+        has_more: false,
+        next_page: None,
+    }))
+}
+
+fn find_collection(db: &Database, id: &str) -> Result<CollectionInfo, ApiError> {
+    db.list_collections()?
+        .into_iter()
+        .find(|c| c.name == id)
+        .ok_or_else(|| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("No vector store found with id '{}'", id),
+            )
+        })
+}
+
+/// BM25 and vector fused, degrading to BM25 without an embedding service
+async fn hybrid(
+    db: &Database,
+    query: &str,
+    options: &SearchOptions,
+    expand: bool,
+    rerank: bool,
+) -> agentroot_core::Result<Vec<SearchResult>> {
+    let Ok(embedder) = agentroot_core::default_embedder().await else {
+        return db.search_fts(query, options);
+    };
+    let expander = HttpQueryExpander::from_env().ok().filter(|_| expand);
+    let reranker = HttpReranker::from_env().ok().filter(|_| rerank);
+    agentroot_core::search::hybrid_search(
+        db,
+        query,
+        options,
+        embedder.as_ref(),
+        expander.as_ref().map(|e| e as &dyn QueryExpander),
+        reranker.as_ref().map(|r| r as &dyn Reranker),
+    )
+    .await
+}
+
+/// Map an OpenAI attribute filter onto search options
+///
+/// Supports `eq` comparisons on provider, category, difficulty, tag and
+/// keyword, combined with `and`. The collection is the vector store itself.
+fn apply_filter(filter: &Value, options: &mut SearchOptions) -> Result<(), ApiError> {
+    let kind = filter.get("type").and_then(Value::as_str).unwrap_or("");
+    match kind {
+        "and" => {
+            let filters = filter
+                .get("filters")
+                .and_then(Value::as_array)
+                .ok_or_else(|| ApiError::bad_request("'and' filter needs a 'filters' array"))?;
+            for f in filters {
+                apply_filter(f, options)?;
+            }
+            Ok(())
+        }
+        "eq" => {
+            let key = filter.get("key").and_then(Value::as_str).unwrap_or("");
+            let value = match filter.get("value") {
+                Some(Value::String(s)) => s.clone(),
+                Some(Value::Number(n)) => n.to_string(),
+                Some(Value::Bool(b)) => b.to_string(),
+                _ => return Err(ApiError::bad_request("filter value must be a scalar")),
+            };
+            match key {
+                "provider" => options.provider = Some(value),
+                "category" | "difficulty" | "tag" | "keyword" | "author" => {
+                    options.metadata_filters.push((key.to_string(), value))
+                }
+                _ => {
+                    return Err(ApiError::bad_request(format!(
+                        "Unsupported filter key '{}' (expected provider, category, difficulty, \
+                         tag, keyword or author)",
+                        key
+                    )))
+                }
+            }
+            Ok(())
+        }
+        other => Err(ApiError::bad_request(format!(
+            "Unsupported filter type '{}' (only 'eq' and 'and' are supported)",
+            other
+        ))),
+    }
+}
+
+fn vector_store(collection: CollectionInfo) -> VectorStore {
+    let created_at = chrono::DateTime::parse_from_rfc3339(&collection.created_at)
+        .map(|t| t.timestamp())
+        .unwrap_or(0);
+    VectorStore {
+        id: collection.name.clone(),
+        object: "vector_store".to_string(),
+        name: collection.name,
+        created_at,
+        status: "completed".to_string(),
+        file_counts: FileCounts {
+            in_progress: 0,
+            completed: collection.document_count,
+            failed: 0,
+            cancelled: 0,
+            total: collection.document_count,
+        },
+    }
+}
+
+fn search_result(result: SearchResult) -> VectorStoreSearchResult {
+    let mut attributes = Map::new();
+    attributes.insert("collection".into(), result.collection_name.into());
+    attributes.insert("path".into(), result.display_path.clone().into());
+    attributes.insert("title".into(), result.title.into());
+    attributes.insert("modified_at".into(), result.modified_at.into());
+    if let Some(category) = result.llm_category {
+        attributes.insert("category".into(), category.into());
+    }
+    if result.is_chunk {
+        if let Some(start) = result.chunk_start_line {
+            attributes.insert("start_line".into(), start.into());
+        }
+        if let Some(end) = result.chunk_end_line {
+            attributes.insert("end_line".into(), end.into());
+        }
+    }
+
+    VectorStoreSearchResult {
+        file_id: result.docid,
+        filename: result.display_path,
+        score: result.score,
+        attributes,
+        content: vec![ContentPart {
+            kind: "text".to_string(),
+            text: result.body.or(result.context).unwrap_or_default(),
+        }],
+    }
+}
@@ -0,0 +1,190 @@
+//! Retriever endpoints
+//!
+//! Results are flattened into `{id, text, metadata, score}` documents, the
+//! shape retriever integrations map onto their own document types.
+
+use crate::error::ApiError;
+use agentroot_core::db::DbPool;
+use agentroot_core::{DetailLevel, SearchOptions, SearchResult, SearchSource};
+use axum::extract::State;
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::sync::Arc;
+
+/// Documents returned when a request does not set `top_k`
+const DEFAULT_TOP_K: usize = 5;
+
+/// Upper bound on `top_k`, since every document carries its full text
+const MAX_TOP_K: usize = 100;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RetrieveRequest {
+    pub query: String,
+    #[serde(default, alias = "k")]
+    pub top_k: Option<usize>,
+    /// collection, provider, category, difficulty, tag or keyword
+    #[serde(default, alias = "filter")]
+    pub filters: Option<Map<String, Value>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetrievedDocument {
+    /// Docid (#abc123) of the source document
+    pub id: String,
+    pub text: String,
+    pub metadata: Map<String, Value>,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetrieveResponse {
+    pub documents: Vec<RetrievedDocument>,
+}
+
+/// ChatGPT retrieval plugin request: several queries in one call
+#[derive(Debug, Clone, Deserialize)]
+pub struct QueryRequest {
+    pub queries: Vec<RetrieveRequest>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryResult {
+    pub query: String,
+    pub results: Vec<RetrievedDocument>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryResponse {
+    pub results: Vec<QueryResult>,
+}
+
+pub(crate) async fn retrieve(
+    State(pool): State<Arc<DbPool>>,
+    Json(request): Json<RetrieveRequest>,
+) -> Result<Json<RetrieveResponse>, ApiError> {
+    let options = search_options(&request)?;
+    let documents = pool
+        .run(move |db| {
+            Box::pin(
+                async move { agentroot_core::unified_search(db, &request.query, &options).await },
+            )
+        })
+        .await??
+        .into_iter()
+        .map(retrieved_document)
+        .collect();
+    Ok(Json(RetrieveResponse { documents }))
+}
+
+pub(crate) async fn query(
+    State(pool): State<Arc<DbPool>>,
+    Json(request): Json<QueryRequest>,
+) -> Result<Json<QueryResponse>, ApiError> {
+    let queries = request
+        .queries
+        .into_iter()
+        .map(|q| search_options(&q).map(|options| (q.query, options)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let results = pool
+        .run(move |db| {
+            Box::pin(async move {
+                let mut results = Vec::with_capacity(queries.len());
+                for (query, options) in queries {
+                    let documents = agentroot_core::unified_search(db, &query, &options).await?;
+                    results.push(QueryResult {
+                        query,
+                        results: documents.into_iter().map(retrieved_document).collect(),
+                    });
+                }
+                Ok::<_, agentroot_core::error::AgentRootError>(results)
+            })
+        })
+        .await??;
+    Ok(Json(QueryResponse { results }))
+}
+
+fn search_options(request: &RetrieveRequest) -> Result<SearchOptions, ApiError> {
+    if request.query.trim().is_empty() {
+        return Err(ApiError::bad_request("query must not be empty"));
+    }
+    let mut options = SearchOptions {
+        limit: request.top_k.unwrap_or(DEFAULT_TOP_K).clamp(1, MAX_TOP_K),
+        detail: DetailLevel::L2,
+        ..Default::default()
+    };
+
+    for (field, value) in request.filters.iter().flatten() {
+        let value = match value {
+            Value::Null => continue,
+            Value::String(s) => s.clone(),
+            Value::Number(n) => n.to_string(),
+            Value::Bool(b) => b.to_string(),
+            _ => {
+                return Err(ApiError::bad_request(format!(
+                    "Filter '{}' must be a string",
+                    field
+                )))
+            }
+        };
+        match field.as_str() {
+            "collection" => options.collection = Some(value),
+            "provider" => options.provider = Some(value),
+            "category" | "difficulty" | "tag" | "keyword" | "author" => {
+                options.metadata_filters.push((field.clone(), value))
+            }
+            _ => {
+                return Err(ApiError::bad_request(format!(
+                    "Unsupported filter '{}' (expected collection, provider, category, \
+                     difficulty, tag, keyword or author)",
+                    field
+                )))
+            }
+        }
+    }
+    Ok(options)
+}
+
+fn retrieved_document(result: SearchResult) -> RetrievedDocument {
+    let source = match result.source {
+        SearchSource::Bm25 => "bm25",
+        SearchSource::Vector => "vector",
+        SearchSource::Hybrid => "hybrid",
+        SearchSource::Glossary => "glossary",
+    };
+    let mut metadata = Map::new();
+    metadata.insert("docid".into(), result.docid.clone().into());
+    metadata.insert("uri".into(), result.filepath.into());
+    metadata.insert("path".into(), result.display_path.into());
+    metadata.insert("title".into(), result.title.into());
+    metadata.insert("collection".into(), result.collection_name.into());
+    metadata.insert("modified_at".into(), result.modified_at.into());
+    metadata.insert("source".into(), source.into());
+    if let Some(summary) = result.llm_summary {
+        metadata.insert("summary".into(), summary.into());
+    }
+    if let Some(category) = result.llm_category {
+        metadata.insert("category".into(), category.into());
+    }
+    if let Some(keywords) = result.llm_keywords {
+        metadata.insert("keywords".into(), keywords.into());
+    }
+    if result.is_chunk {
+        if let Some(breadcrumb) = result.chunk_breadcrumb {
+            metadata.insert("breadcrumb".into(), breadcrumb.into());
+        }
+        if let Some(start) = result.chunk_start_line {
+            metadata.insert("start_line".into(), start.into());
+        }
+        if let Some(end) = result.chunk_end_line {
+            metadata.insert("end_line".into(), end.into());
+        }
+    }
+
+    RetrievedDocument {
+        id: result.docid,
+        text: result.body.or(result.context).unwrap_or_default(),
+        metadata,
+        score: result.score,
+    }
+}
@@ -0,0 +1,191 @@
+//! End-to-end test of the HTTP retriever endpoints over a real socket
+
+use agentroot_core::db::DbPool;
+use agentroot_core::Database;
+use agentroot_http::{QueryResponse, RetrieveResponse, VectorStoreSearchResponse};
+use serde_json::json;
+use tempfile::TempDir;
+
+async fn start_server(db_path: &std::path::Path, token: Option<&str>) -> String {
+    let pool = DbPool::open(db_path, 2).unwrap();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = agentroot_http::router(pool, token.map(str::to_string));
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    format!("http://{}", addr)
+}
+
+async fn indexed_db(docs: &TempDir, db_dir: &TempDir) -> std::path::PathBuf {
+    std::fs::write(
+        docs.path().join("tokio.md"),
+        "# Tokio Runtime\n\nThe tokio runtime schedules async tasks.",
+    )
+    .unwrap();
+    std::fs::write(
+        docs.path().join("serde.md"),
+        "# Serde\n\nSerialization framework for Rust.",
+    )
+    .unwrap();
+
+    let db_path = db_dir.path().join("index.sqlite");
+    let db = Database::open(&db_path).unwrap();
+    db.initialize().unwrap();
+    db.add_collection(
+        "notes",
+        docs.path().to_str().unwrap(),
+        "**/*.md",
+        "file",
+        None,
+    )
+    .unwrap();
+    db.reindex_collection("notes").await.unwrap();
+    db_path
+}
+
+#[tokio::test]
+async fn test_retrieve_and_query() {
+    let docs = TempDir::new().unwrap();
+    let db_dir = TempDir::new().unwrap();
+    let db_path = indexed_db(&docs, &db_dir).await;
+    let base = start_server(&db_path, None).await;
+    let client = reqwest::Client::new();
+
+    let health = client.get(format!("{}/health", base)).send().await.unwrap();
+    assert!(health.status().is_success());
+
+    let response: RetrieveResponse = client
+        .post(format!("{}/retrieve", base))
+        .json(&json!({"query": "tokio", "top_k": 3, "filters": {"collection": "notes"}}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(response.documents.len(), 1);
+    let doc = &response.documents[0];
+    assert!(doc.text.contains("schedules async tasks"));
+    assert_eq!(doc.metadata["path"], "notes/tokio.md");
+    assert_eq!(doc.metadata["collection"], "notes");
+    assert!(doc.score > 0.0);
+
+    let other = client
+        .post(format!("{}/retrieve", base))
+        .json(&json!({"query": "tokio", "filters": {"collection": "other"}}))
+        .send()
+        .await
+        .unwrap()
+        .json::<RetrieveResponse>()
+        .await
+        .unwrap();
+    assert!(other.documents.is_empty());
+
+    let unsupported = client
+        .post(format!("{}/retrieve", base))
+        .json(&json!({"query": "tokio", "filters": {"owner": "alice"}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(unsupported.status(), reqwest::StatusCode::BAD_REQUEST);
+
+    let response: QueryResponse = client
+        .post(format!("{}/query", base))
+        .json(&json!({"queries": [
+            {"query": "tokio", "top_k": 2},
+            {"query": "serialization", "filter": {"collection": "notes"}}
+        ]}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(response.results.len(), 2);
+    assert_eq!(response.results[0].query, "tokio");
+    assert_eq!(response.results[1].results.len(), 1);
+    assert_eq!(response.results[1].results[0].metadata["title"], "Serde");
+}
+
+#[tokio::test]
+async fn test_openai_vector_store_search() {
+    let docs = TempDir::new().unwrap();
+    let db_dir = TempDir::new().unwrap();
+    let db_path = indexed_db(&docs, &db_dir).await;
+    let base = start_server(&db_path, None).await;
+    let client = reqwest::Client::new();
+
+    let stores: serde_json::Value = client
+        .get(format!("{}/v1/vector_stores", base))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(stores["object"], "list");
+    assert_eq!(stores["data"][0]["id"], "notes");
+    assert_eq!(stores["data"][0]["file_counts"]["completed"], 2);
+
+    let response: VectorStoreSearchResponse = client
+        .post(format!("{}/v1/vector_stores/notes/search", base))
+        .json(&json!({"query": "serialization", "max_num_results": 5}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(response.object, "vector_store.search_results.page");
+    assert_eq!(response.data.len(), 1);
+    assert_eq!(response.data[0].filename, "notes/serde.md");
+    assert_eq!(response.data[0].content[0].kind, "text");
+    assert!(response.data[0].content[0].text.contains("Serialization"));
+
+    let missing = client
+        .post(format!("{}/v1/vector_stores/vs_missing/search", base))
+        .json(&json!({"query": "tokio"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(missing.status(), reqwest::StatusCode::NOT_FOUND);
+    let error: serde_json::Value = missing.json().await.unwrap();
+    assert_eq!(error["error"]["type"], "invalid_request_error");
+
+    let unsupported = client
+        .post(format!("{}/v1/vector_stores/notes/search", base))
+        .json(&json!({"query": "tokio", "filters": {"type": "gt", "key": "year", "value": 2020}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(unsupported.status(), reqwest::StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_bearer_token() {
+    let docs = TempDir::new().unwrap();
+    let db_dir = TempDir::new().unwrap();
+    let db_path = indexed_db(&docs, &db_dir).await;
+    let base = start_server(&db_path, Some("secret")).await;
+    let client = reqwest::Client::new();
+    let body = json!({"query": "tokio"});
+
+    let denied = client
+        .post(format!("{}/retrieve", base))
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(denied.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+    let allowed = client
+        .post(format!("{}/retrieve", base))
+        .bearer_auth("secret")
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    assert!(allowed.status().is_success());
+
+    let health = client.get(format!("{}/health", base)).send().await.unwrap();
+    assert!(health.status().is_success());
+}
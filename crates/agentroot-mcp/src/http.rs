@@ -0,0 +1,562 @@
+//! Streamable HTTP transport
+//!
+//! `POST /mcp` takes one JSON-RPC message and answers it with JSON,
+//! `GET /mcp` opens a server-sent event stream for notifications and
+//! `DELETE /mcp` ends the session. `initialize` opens a session whose id the
+//! client sends back in the `Mcp-Session-Id` header, so several clients can
+//! share one server without seeing each other's notifications. Requests run
+//! on a pool of database connections, as for the HTTP API. A client cancels
+//! one of its running requests with `notifications/cancelled`, or by
+//! hanging up.
+
+use crate::progress::progress_token;
+use crate::protocol::{JsonRpcNotification, JsonRpcRequest, JsonRpcResponse};
+use crate::server::{cancelled_request, McpServer, LIST_CHANGED_POLL};
+use agentroot_core::db::DbPool;
+use agentroot_core::CancellationToken;
+use axum::body::Bytes;
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde_json::Value;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc};
+
+/// Default listen address
+pub const DEFAULT_ADDR: &str = "127.0.0.1:7071";
+
+/// Connections (and threads) used to serve requests concurrently
+pub const DEFAULT_WORKERS: usize = 4;
+
+/// Header naming the session of a request
+pub const SESSION_HEADER: &str = "mcp-session-id";
+
+/// Sessions open at once; further `initialize` requests are refused
+pub const DEFAULT_MAX_SESSIONS: usize = 64;
+
+/// Sessions unused for this long are dropped
+const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(3600);
+
+/// Notifications buffered for a slow event stream before it skips ahead
+const EVENT_BUFFER: usize = 16;
+
+/// One client's session
+struct Session {
+    events: broadcast::Sender<String>,
+    last_seen: Mutex<Instant>,
+    /// Requests still running, by JSON-encoded id, so they can be cancelled
+    in_flight: Mutex<HashMap<String, CancellationToken>>,
+}
+
+impl Session {
+    fn in_flight(&self) -> std::sync::MutexGuard<'_, HashMap<String, CancellationToken>> {
+        self.in_flight.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn is_idle(&self) -> bool {
+        let last_seen = *self.last_seen.lock().unwrap_or_else(|e| e.into_inner());
+        last_seen.elapsed() >= SESSION_IDLE_TIMEOUT
+    }
+
+    fn cancel(&self, id: &Value) {
+        if let Some(token) = self.in_flight().get(&id.to_string()) {
+            token.cancel();
+        }
+    }
+}
+
+/// A running request; dropping it, which also happens when the client
+/// disconnects, cancels whatever work is left
+struct InFlight {
+    session: Option<Arc<Session>>,
+    key: String,
+    token: CancellationToken,
+}
+
+impl InFlight {
+    /// Track a request, or None when the session already runs one with this id
+    fn start(session: Option<Arc<Session>>, id: &Value) -> Option<Self> {
+        let key = id.to_string();
+        let token = CancellationToken::new();
+        if let Some(session) = &session {
+            match session.in_flight().entry(key.clone()) {
+                Entry::Occupied(_) => return None,
+                Entry::Vacant(entry) => {
+                    entry.insert(token.clone());
+                }
+            }
+        }
+        Some(Self {
+            session,
+            key,
+            token,
+        })
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.token.cancel();
+        if let Some(session) = &self.session {
+            session.in_flight().remove(&self.key);
+        }
+    }
+}
+
+struct McpState {
+    pool: DbPool,
+    sessions: Mutex<HashMap<String, Arc<Session>>>,
+    max_sessions: usize,
+}
+
+impl McpState {
+    fn sessions(&self) -> std::sync::MutexGuard<'_, HashMap<String, Arc<Session>>> {
+        self.sessions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Start a session, or None when `max_sessions` are open
+    ///
+    /// At the limit, sessions idle past the timeout are dropped first; live
+    /// sessions are never dropped to make room.
+    fn open_session(&self) -> anyhow::Result<Option<String>> {
+        let mut bytes = [0u8; 16];
+        getrandom::fill(&mut bytes)
+            .map_err(|e| anyhow::anyhow!("Failed to generate a session id: {}", e))?;
+        let id: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        let session = Session {
+            events: broadcast::channel(EVENT_BUFFER).0,
+            last_seen: Mutex::new(Instant::now()),
+            in_flight: Mutex::new(HashMap::new()),
+        };
+
+        let mut sessions = self.sessions();
+        if sessions.len() >= self.max_sessions {
+            sessions.retain(|_, session| !session.is_idle());
+            if sessions.len() >= self.max_sessions {
+                return Ok(None);
+            }
+        }
+        sessions.insert(id.clone(), Arc::new(session));
+        Ok(Some(id))
+    }
+
+    /// The session named by the request
+    fn session(&self, headers: &HeaderMap) -> Result<Arc<Session>, SessionError> {
+        let id = headers
+            .get(SESSION_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .ok_or(SessionError::Missing)?;
+        let session = self
+            .sessions()
+            .get(id)
+            .cloned()
+            .ok_or(SessionError::Unknown)?;
+        *session.last_seen.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+        Ok(session)
+    }
+
+    fn expire_idle_sessions(&self) {
+        self.sessions().retain(|_, session| !session.is_idle());
+    }
+
+    fn notify_all(&self, message: &str) {
+        for session in self.sessions().values() {
+            // No receiver just means the client has no event stream open
+            let _ = session.events.send(message.to_string());
+        }
+    }
+}
+
+/// Why a request has no usable session
+enum SessionError {
+    Missing,
+    Unknown,
+    /// `max_sessions` are already open
+    Full,
+}
+
+impl IntoResponse for SessionError {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Missing => rpc_error(
+                StatusCode::BAD_REQUEST,
+                -32600,
+                "Missing Mcp-Session-Id header; send initialize first",
+            ),
+            Self::Unknown => rpc_error(
+                StatusCode::NOT_FOUND,
+                -32001,
+                "Unknown or expired session; initialize again",
+            ),
+            Self::Full => rpc_error(
+                StatusCode::SERVICE_UNAVAILABLE,
+                -32000,
+                "Too many active sessions; try again later",
+            ),
+        }
+    }
+}
+
+/// Build the `/mcp` route
+///
+/// When `token` is set, requests need an `Authorization: Bearer <token>`
+/// header. Requests from a browser page must come from a loopback origin or
+/// one listed in `AGENTROOT_MCP_ALLOWED_ORIGINS` (comma-separated). At most
+/// `max_sessions` sessions are kept. Must be called within a Tokio runtime,
+/// which runs the check for changed documents.
+pub fn router(pool: DbPool, token: Option<String>, max_sessions: usize) -> Router {
+    let state = Arc::new(McpState {
+        pool,
+        sessions: Mutex::new(HashMap::new()),
+        max_sessions: max_sessions.max(1),
+    });
+    tokio::spawn(watch_documents(Arc::downgrade(&state)));
+
+    let allowed_origins: Vec<String> = std::env::var("AGENTROOT_MCP_ALLOWED_ORIGINS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|o| o.trim().trim_end_matches('/').to_string())
+        .filter(|o| !o.is_empty())
+        .collect();
+    let access = Arc::new(Access {
+        token,
+        allowed_origins,
+    });
+
+    Router::new()
+        .route(
+            "/mcp",
+            get(open_event_stream)
+                .post(post_message)
+                .delete(close_session),
+        )
+        .with_state(state)
+        .route_layer(middleware::from_fn_with_state(access, check_access))
+}
+
+/// Serve MCP over HTTP on `addr` until the process is interrupted
+///
+/// Requests must carry the bearer token in `AGENTROOT_MCP_TOKEN` when it is set.
+pub async fn serve(
+    db_path: PathBuf,
+    addr: SocketAddr,
+    workers: usize,
+    max_sessions: usize,
+    read_only: bool,
+) -> anyhow::Result<()> {
+    let pool = if read_only {
+        DbPool::open_read_only(&db_path, workers)?
+    } else {
+        DbPool::open(&db_path, workers)?
+    };
+    let token = std::env::var("AGENTROOT_MCP_TOKEN")
+        .ok()
+        .filter(|t| !t.is_empty());
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    tracing::info!("MCP server listening on {}", listener.local_addr()?);
+    axum::serve(listener, router(pool, token, max_sessions))
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
+
+fn rpc_error(status: StatusCode, code: i32, message: &str) -> Response {
+    (status, Json(JsonRpcResponse::error(None, code, message))).into_response()
+}
+
+async fn post_message(
+    State(state): State<Arc<McpState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let request: JsonRpcRequest = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(e) => {
+            return rpc_error(
+                StatusCode::BAD_REQUEST,
+                -32700,
+                &format!("Parse error: {}", e),
+            )
+        }
+    };
+
+    let (new_session, session) = if request.method == "initialize" {
+        match state.open_session() {
+            Ok(Some(id)) => (Some(id), None),
+            Ok(None) => return SessionError::Full.into_response(),
+            Err(e) => return rpc_error(StatusCode::INTERNAL_SERVER_ERROR, -32603, &e.to_string()),
+        }
+    } else {
+        match state.session(&headers) {
+            Ok(session) => (None, Some(session)),
+            Err(e) => return e.into_response(),
+        }
+    };
+
+    // Notifications and responses from the client need no answer
+    let Some(id) = request.id.clone() else {
+        if let (Some(session), Some(target)) = (&session, cancelled_request(&request)) {
+            session.cancel(target);
+        }
+        return StatusCode::ACCEPTED.into_response();
+    };
+
+    let Some(in_flight) = InFlight::start(session, &id) else {
+        let message = format!("Request id {} is already in flight", id);
+        return Json(JsonRpcResponse::error(Some(id), -32600, &message)).into_response();
+    };
+    let mut response = if progress_token(&request).is_some() && accepts_event_stream(&headers) {
+        respond_with_progress(state, request, id, in_flight)
+    } else {
+        let cancel = in_flight.token.clone();
+        let response = state
+            .pool
+            .run(move |db| {
+                Box::pin(async move { McpServer::new(db).handle_request(&request, &cancel).await })
+            })
+            .await
+            .unwrap_or_else(|e| JsonRpcResponse::error(Some(id), -32603, &e.to_string()));
+        Json(response).into_response()
+    };
+    if let Some(session_id) = new_session.and_then(|id| HeaderValue::from_str(&id).ok()) {
+        response.headers_mut().insert(SESSION_HEADER, session_id);
+    }
+    response
+}
+
+fn accepts_event_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/event-stream"))
+}
+
+/// Answer on an event stream that carries the request's progress
+/// notifications ahead of its response; closing the stream cancels the request
+fn respond_with_progress(
+    state: Arc<McpState>,
+    request: JsonRpcRequest,
+    id: Value,
+    in_flight: InFlight,
+) -> Response {
+    let (events, receiver) = mpsc::unbounded_channel::<String>();
+    tokio::spawn(async move {
+        let (sender, mut notifications) = mpsc::unbounded_channel();
+        let cancel = in_flight.token.clone();
+        let handling = state.pool.run(move |db| {
+            Box::pin(async move {
+                McpServer::new(db)
+                    .with_notifications(sender)
+                    .handle_request(&request, &cancel)
+                    .await
+            })
+        });
+        tokio::pin!(handling);
+        let response = loop {
+            tokio::select! {
+                response = &mut handling => break response,
+                Some(notification) = notifications.recv() => send_event(&events, &notification),
+                _ = events.closed(), if !in_flight.token.is_cancelled() => in_flight.token.cancel(),
+            }
+        };
+        while let Ok(notification) = notifications.try_recv() {
+            send_event(&events, &notification);
+        }
+        let response =
+            response.unwrap_or_else(|e| JsonRpcResponse::error(Some(id), -32603, &e.to_string()));
+        send_event(&events, &response);
+    });
+
+    let events = futures::stream::unfold(receiver, |mut receiver| async move {
+        let data = receiver.recv().await?;
+        Some((Ok::<_, Infallible>(Event::default().data(data)), receiver))
+    });
+    Sse::new(events).into_response()
+}
+
+fn send_event(events: &mpsc::UnboundedSender<String>, message: &impl serde::Serialize) {
+    if let Ok(data) = serde_json::to_string(message) {
+        // A client that hung up misses the rest
+        let _ = events.send(data);
+    }
+}
+
+async fn open_event_stream(
+    State(state): State<Arc<McpState>>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, SessionError> {
+    let session = state.session(&headers)?;
+    let events = futures::stream::unfold(session.events.subscribe(), |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(data) => {
+                    return Some((Ok::<_, Infallible>(Event::default().data(data)), receiver))
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+async fn close_session(
+    State(state): State<Arc<McpState>>,
+    headers: HeaderMap,
+) -> Result<StatusCode, SessionError> {
+    let id = headers
+        .get(SESSION_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .ok_or(SessionError::Missing)?;
+    state.sessions().remove(id).ok_or(SessionError::Unknown)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Tell every session when documents are added or removed, and drop idle
+/// sessions; stops once the router is gone
+async fn watch_documents(state: Weak<McpState>) {
+    let mut interval = tokio::time::interval(LIST_CHANGED_POLL);
+    let mut documents = None;
+    loop {
+        interval.tick().await;
+        let Some(state) = state.upgrade() else {
+            return;
+        };
+        state.expire_idle_sessions();
+
+        let current = state
+            .pool
+            .run(|db| Box::pin(async move { db.document_list_fingerprint().ok() }))
+            .await
+            .ok()
+            .flatten();
+        if documents
+            .as_ref()
+            .is_some_and(|previous| *previous != current)
+        {
+            let notification = JsonRpcNotification::new("notifications/resources/list_changed");
+            if let Ok(message) = serde_json::to_string(&notification) {
+                state.notify_all(&message);
+            }
+        }
+        documents = Some(current);
+    }
+}
+
+struct Access {
+    token: Option<String>,
+    allowed_origins: Vec<String>,
+}
+
+/// A page on another site must not reach a server bound to localhost
+fn origin_allowed(origin: &str, allowed: &[String]) -> bool {
+    let origin = origin.trim_end_matches('/');
+    if allowed.iter().any(|o| o == origin) {
+        return true;
+    }
+    let host = origin.split_once("://").map_or(origin, |(_, rest)| rest);
+    let host = match host.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "::1")
+}
+
+async fn check_access(State(access): State<Arc<Access>>, request: Request, next: Next) -> Response {
+    let origin = request
+        .headers()
+        .get(header::ORIGIN)
+        .and_then(|value| value.to_str().ok());
+    if origin.is_some_and(|origin| !origin_allowed(origin, &access.allowed_origins)) {
+        return rpc_error(StatusCode::FORBIDDEN, -32600, "Origin not allowed");
+    }
+
+    if let Some(token) = &access.token {
+        let authorized = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|presented| presented == token);
+        if !authorized {
+            return rpc_error(
+                StatusCode::UNAUTHORIZED,
+                -32600,
+                "Missing or invalid bearer token",
+            );
+        }
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_origin_allowed() {
+        let allowed = vec!["https://app.example.com".to_string()];
+        assert!(origin_allowed("http://localhost:3000", &allowed));
+        assert!(origin_allowed("http://127.0.0.1", &allowed));
+        assert!(origin_allowed("http://[::1]:8080", &allowed));
+        assert!(origin_allowed("https://app.example.com/", &allowed));
+        assert!(!origin_allowed("https://evil.example.com", &allowed));
+        assert!(!origin_allowed("http://localhost.evil.com", &allowed));
+    }
+
+    fn test_state(max_sessions: usize) -> (tempfile::TempDir, McpState) {
+        let dir = tempfile::TempDir::new().unwrap();
+        let state = McpState {
+            pool: DbPool::open(&dir.path().join("index.sqlite"), 1).unwrap(),
+            sessions: Mutex::new(HashMap::new()),
+            max_sessions,
+        };
+        (dir, state)
+    }
+
+    #[test]
+    fn test_only_idle_sessions_make_room() {
+        let (_dir, state) = test_state(1);
+        let first = state.open_session().unwrap().unwrap();
+        assert_eq!(state.open_session().unwrap(), None);
+
+        let session = state.sessions()[&first].clone();
+        // The monotonic clock may have started too recently to backdate to
+        let Some(expired) = Instant::now().checked_sub(SESSION_IDLE_TIMEOUT) else {
+            return;
+        };
+        *session.last_seen.lock().unwrap() = expired;
+        let second = state.open_session().unwrap().unwrap();
+        let sessions = state.sessions();
+        assert!(sessions.contains_key(&second) && !sessions.contains_key(&first));
+    }
+
+    #[test]
+    fn test_duplicate_request_ids_are_refused() {
+        let (_dir, state) = test_state(1);
+        let id = state.open_session().unwrap().unwrap();
+        let session = state.sessions()[&id].clone();
+
+        let running = InFlight::start(Some(session.clone()), &Value::from(1)).unwrap();
+        assert!(InFlight::start(Some(session.clone()), &Value::from(1)).is_none());
+        // The first request stays cancellable
+        session.cancel(&Value::from(1));
+        assert!(running.token.is_cancelled());
+
+        drop(running);
+        assert!(InFlight::start(Some(session), &Value::from(1)).is_some());
+    }
+}
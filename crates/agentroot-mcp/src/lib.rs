@@ -2,8 +2,11 @@
 //!
 //! Model Context Protocol server for integration with AI assistants.
 
+pub mod http;
+mod progress;
+mod prompts;
 mod protocol;
-mod resources;
+pub mod resources;
 mod server;
 pub mod tools;
 
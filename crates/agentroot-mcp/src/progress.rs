@@ -0,0 +1,165 @@
+//! MCP progress notifications
+//!
+//! A request that sets `params._meta.progressToken` receives
+//! `notifications/progress` while its tool runs, fed from the same progress
+//! updates that drive the CLI progress bars.
+
+use crate::protocol::{JsonRpcNotification, JsonRpcRequest};
+use agentroot_core::{ProgressSink, ProgressUpdate};
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Least time between two notifications, so a fast reindex does not flood
+/// the client with one message per file
+const MIN_INTERVAL: Duration = Duration::from_millis(200);
+
+/// The progress token a request asked to be kept informed under
+pub(crate) fn progress_token(request: &JsonRpcRequest) -> Option<&Value> {
+    request
+        .params
+        .get("_meta")
+        .and_then(|meta| meta.get("progressToken"))
+        .filter(|token| token.is_string() || token.is_number())
+}
+
+/// Turns progress updates into notifications for one request
+pub(crate) struct ProgressNotifier {
+    token: Value,
+    sender: UnboundedSender<JsonRpcNotification>,
+    /// Progress last sent and when
+    last: Mutex<Option<(usize, Instant)>>,
+}
+
+impl ProgressNotifier {
+    /// `None` when the request did not ask for progress
+    pub(crate) fn for_request(
+        request: &JsonRpcRequest,
+        sender: &UnboundedSender<JsonRpcNotification>,
+    ) -> Option<Self> {
+        Some(Self {
+            token: progress_token(request)?.clone(),
+            sender: sender.clone(),
+            last: Mutex::new(None),
+        })
+    }
+
+    /// Send `update` unless it is too soon after the last one; progress
+    /// must increase from one notification to the next
+    fn notify(&self, update: &ProgressUpdate, finished: bool) {
+        let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        let completed = update.completed();
+        let due = match *last {
+            None => true,
+            Some((sent, at)) => {
+                completed > sent
+                    && (finished || completed >= update.discovered || at.elapsed() >= MIN_INTERVAL)
+            }
+        };
+        if !due {
+            return;
+        }
+        *last = Some((completed, Instant::now()));
+
+        let mut params = json!({
+            "progressToken": self.token,
+            "progress": completed,
+            "message": describe(update),
+        });
+        if update.discovered > 0 {
+            params["total"] = json!(update.discovered);
+        }
+        // The request may have been answered already; nothing to tell then
+        let _ = self.sender.send(JsonRpcNotification::with_params(
+            "notifications/progress",
+            params,
+        ));
+    }
+}
+
+impl ProgressSink for ProgressNotifier {
+    fn on_progress(&self, update: &ProgressUpdate) {
+        self.notify(update, false);
+    }
+
+    fn on_finish(&self, update: &ProgressUpdate) {
+        self.notify(update, true);
+    }
+}
+
+/// "indexed 450/2000 files"
+fn describe(update: &ProgressUpdate) -> String {
+    let (verb, unit) = match update.operation.as_str() {
+        "index" => ("indexed", "files"),
+        "embed" => ("embedded", "documents"),
+        "migrate" => ("staged", "documents"),
+        other => (other, "items"),
+    };
+    let mut message = if update.discovered > 0 {
+        format!(
+            "{} {}/{} {}",
+            verb,
+            update.completed(),
+            update.discovered,
+            unit
+        )
+    } else {
+        format!("{} {} {}", verb, update.completed(), unit)
+    };
+    if update.failed > 0 {
+        message.push_str(&format!(" ({} failed)", update.failed));
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agentroot_core::ProgressTracker;
+    use tokio::sync::mpsc;
+
+    fn request(params: Value) -> JsonRpcRequest {
+        serde_json::from_value(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/call",
+            "params": params
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_notifications_follow_the_tracker() {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let with_token = request(json!({"name": "x", "_meta": {"progressToken": "t1"}}));
+        let notifier = ProgressNotifier::for_request(&with_token, &sender).unwrap();
+        assert!(ProgressNotifier::for_request(&request(json!({"name": "x"})), &sender).is_none());
+
+        let mut tracker = ProgressTracker::new(&notifier, "index");
+        tracker.discovered(3);
+        tracker.processed(10, None);
+        tracker.failed(None);
+        tracker.processed(10, None);
+        tracker.finish();
+
+        let mut sent = Vec::new();
+        while let Ok(notification) = receiver.try_recv() {
+            assert_eq!(notification.method, "notifications/progress");
+            sent.push(notification.params.unwrap());
+        }
+        // The first update, then whatever the interval lets through, and
+        // always the last
+        assert_eq!(sent[0]["progress"], 0);
+        assert_eq!(sent[0]["total"], 3);
+        assert_eq!(sent[0]["progressToken"], "t1");
+        let last = sent.last().unwrap();
+        assert_eq!(last["progress"], 3);
+        assert_eq!(last["message"], "indexed 3/3 files (1 failed)");
+        let progress: Vec<u64> = sent
+            .iter()
+            .map(|p| p["progress"].as_u64().unwrap())
+            .collect();
+        assert!(progress.windows(2).all(|w| w[0] < w[1]), "{:?}", progress);
+    }
+}
@@ -0,0 +1,222 @@
+//! MCP prompt templates
+//!
+//! Each prompt expands to a user message that walks the assistant through the
+//! tools suited to the task, so clients can offer search workflows without
+//! knowing the tool set.
+
+use crate::protocol::{Content, PromptArgument, PromptDefinition, PromptMessage};
+use agentroot_core::Database;
+use anyhow::Result;
+use serde_json::{json, Value};
+
+fn argument(name: &str, description: &str, required: bool) -> PromptArgument {
+    PromptArgument {
+        name: name.to_string(),
+        description: description.to_string(),
+        required,
+    }
+}
+
+/// Prompts offered by `prompts/list`
+pub fn prompt_definitions() -> Vec<PromptDefinition> {
+    vec![
+        PromptDefinition {
+            name: "query".to_string(),
+            title: "Agentroot Query Guide".to_string(),
+            description: "How to effectively search your knowledge base".to_string(),
+            arguments: vec![],
+        },
+        PromptDefinition {
+            name: "summarize_collection".to_string(),
+            title: "Summarize Collection".to_string(),
+            description: "Overview of what a collection holds, from a sample of its documents"
+                .to_string(),
+            arguments: vec![
+                argument("collection", "Collection name", true),
+                argument("focus", "Topic to concentrate the summary on", false),
+            ],
+        },
+        PromptDefinition {
+            name: "find_related".to_string(),
+            title: "Find Related Documents".to_string(),
+            description: "Documents related to a given one, and how they relate".to_string(),
+            arguments: vec![argument(
+                "document",
+                "Document ID (#abc123) or collection/path",
+                true,
+            )],
+        },
+        PromptDefinition {
+            name: "answer_from_kb".to_string(),
+            title: "Answer from Knowledge Base".to_string(),
+            description: "Answer a question using only indexed documents, citing them".to_string(),
+            arguments: vec![
+                argument("question", "Question to answer", true),
+                argument("collection", "Limit the search to this collection", false),
+            ],
+        },
+    ]
+}
+
+fn required<'a>(arguments: &'a Value, name: &str) -> Result<&'a str> {
+    optional(arguments, name).ok_or_else(|| anyhow::anyhow!("Missing argument: {}", name))
+}
+
+fn optional<'a>(arguments: &'a Value, name: &str) -> Option<&'a str> {
+    arguments
+        .get(name)
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+}
+
+/// Fill in prompt `name` with `arguments`, as a `prompts/get` result
+pub fn get_prompt(db: &Database, name: &str, arguments: &Value) -> Result<Value> {
+    let (description, text) = match name {
+        "query" => (
+            "How to effectively search your knowledge base".to_string(),
+            "Search the agentroot knowledge base with the tools that fit the question:\n\
+             - `search` for exact terms, names and identifiers (BM25)\n\
+             - `vsearch` for concepts phrased differently from the documents\n\
+             - `query` for the best results, combining both and reranking\n\
+             - `get` or `multi_get` to read what the results point to\n\
+             Start narrow with a collection filter when the topic has a home, and \
+             widen only if nothing relevant turns up."
+                .to_string(),
+        ),
+        "summarize_collection" => {
+            let collection = required(arguments, "collection")?;
+            let info = db
+                .list_collections()?
+                .into_iter()
+                .find(|c| c.name == collection)
+                .ok_or_else(|| anyhow::anyhow!("Collection not found: {}", collection))?;
+            let focus = optional(arguments, "focus")
+                .map(|f| format!(" Concentrate on {}.", f))
+                .unwrap_or_default();
+            (
+                format!("Summarize the {} collection", collection),
+                format!(
+                    "Summarize the agentroot collection \"{name}\" ({count} documents).{focus}\n\n\
+                     1. Call `discover` with collection \"{name}\" and limit 20 for a \
+                     representative sample.\n\
+                     2. Call `multi_get` on the most telling of them to read their content \
+                     within the token budget.\n\
+                     3. Use `search` with collection \"{name}\" to check any theme you are \
+                     unsure of.\n\n\
+                     Describe the main topics, the kinds of documents, and anything notable, \
+                     naming the documents each point comes from.",
+                    name = info.name,
+                    count = info.document_count,
+                    focus = focus,
+                ),
+            )
+        }
+        "find_related" => {
+            let document = required(arguments, "document")?;
+            (
+                format!("Find documents related to {}", document),
+                format!(
+                    "Find documents related to {doc} in the agentroot knowledge base.\n\n\
+                     1. Call `get` with file \"{doc}\" to read it.\n\
+                     2. Call `similar` with docid \"{doc}\" for documents close to it.\n\
+                     3. Call `search` with its key terms to find documents that cite or \
+                     build on it without resembling it.\n\n\
+                     List the related documents with their paths and, for each, one line on \
+                     how it relates.",
+                    doc = document,
+                ),
+            )
+        }
+        "answer_from_kb" => {
+            let question = required(arguments, "question")?;
+            let scope = optional(arguments, "collection")
+                .map(|c| format!(" with collection \"{}\"", c))
+                .unwrap_or_default();
+            (
+                "Answer a question from the knowledge base".to_string(),
+                format!(
+                    "Answer this question using only the agentroot knowledge base:\n\n\
+                     {question}\n\n\
+                     1. Call `build_context` with the question as query{scope} to gather \
+                     the relevant passages.\n\
+                     2. If they fall short, call `query`{scope} with other phrasings and \
+                     `get` the promising results.\n\n\
+                     Cite the path of every document you draw on. If the documents do not \
+                     answer the question, say so instead of answering from general knowledge.",
+                    question = question,
+                    scope = scope,
+                ),
+            )
+        }
+        _ => anyhow::bail!("Unknown prompt: {}", name),
+    };
+
+    let messages = vec![PromptMessage {
+        role: "user".to_string(),
+        content: Content::Text { text },
+    }];
+    Ok(json!({ "description": description, "messages": messages }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db_with_collection() -> Database {
+        let db = Database::open_in_memory().unwrap();
+        db.initialize().unwrap();
+        db.add_collection("notes", "/notes", "**/*.md", "file", None)
+            .unwrap();
+        db
+    }
+
+    #[test]
+    fn test_prompts_reference_existing_tools() {
+        use crate::tools::*;
+        let tools: Vec<String> = [
+            search_tool_definition(),
+            vsearch_tool_definition(),
+            query_tool_definition(),
+            get_tool_definition(),
+            multi_get_tool_definition(),
+            similar_tool_definition(),
+            discover_tool_definition(),
+            build_context_tool_definition(),
+        ]
+        .into_iter()
+        .map(|t| t.name)
+        .collect();
+        let db = db_with_collection();
+        let arguments = json!({
+            "collection": "notes",
+            "document": "notes/a.md",
+            "question": "How are releases cut?"
+        });
+        for prompt in prompt_definitions() {
+            let result = get_prompt(&db, &prompt.name, &arguments).unwrap();
+            let text = result["messages"][0]["content"]["text"].as_str().unwrap();
+            for tool in text.split('`').skip(1).step_by(2) {
+                assert!(tools.iter().any(|t| t == tool), "{}: {}", prompt.name, tool);
+            }
+        }
+    }
+
+    #[test]
+    fn test_arguments_are_checked() {
+        let db = db_with_collection();
+        let result = get_prompt(
+            &db,
+            "answer_from_kb",
+            &json!({"question": "What changed?", "collection": "notes"}),
+        )
+        .unwrap();
+        let text = result["messages"][0]["content"]["text"].as_str().unwrap();
+        assert!(text.contains("What changed?"));
+        assert!(text.contains("with collection \"notes\""));
+
+        assert!(get_prompt(&db, "answer_from_kb", &json!({})).is_err());
+        assert!(get_prompt(&db, "summarize_collection", &json!({"collection": "other"})).is_err());
+        assert!(get_prompt(&db, "nonexistent", &json!({})).is_err());
+    }
+}
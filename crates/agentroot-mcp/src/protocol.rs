@@ -57,6 +57,32 @@ impl JsonRpcResponse {
     }
 }
 
+/// JSON-RPC 2.0 Notification, sent without an id and never answered
+#[derive(Debug, Serialize)]
+pub struct JsonRpcNotification {
+    pub jsonrpc: String,
+    pub method: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+}
+
+impl JsonRpcNotification {
+    pub fn new(method: &str) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params: None,
+        }
+    }
+
+    pub fn with_params(method: &str, params: Value) -> Self {
+        Self {
+            params: Some(params),
+            ..Self::new(method)
+        }
+    }
+}
+
 /// MCP Tool Definition
 #[derive(Debug, Serialize)]
 pub struct ToolDefinition {
@@ -66,6 +92,30 @@ pub struct ToolDefinition {
     pub input_schema: Value,
 }
 
+/// MCP Prompt Definition
+#[derive(Debug, Serialize)]
+pub struct PromptDefinition {
+    pub name: String,
+    pub title: String,
+    pub description: String,
+    pub arguments: Vec<PromptArgument>,
+}
+
+/// Argument a prompt is filled in with
+#[derive(Debug, Serialize)]
+pub struct PromptArgument {
+    pub name: String,
+    pub description: String,
+    pub required: bool,
+}
+
+/// Message of a filled-in prompt
+#[derive(Debug, Serialize)]
+pub struct PromptMessage {
+    pub role: String,
+    pub content: Content,
+}
+
 /// MCP Content Types
 #[derive(Debug, Serialize)]
 #[serde(tag = "type")]
@@ -1,26 +1,68 @@
 //! MCP resource handlers
+//!
+//! Every active document is a resource at `agentroot://<collection>/<path>`,
+//! listed in pages and read as text.
 
 use crate::protocol::ResourceContent;
 use agentroot_core::Database;
 use anyhow::Result;
+use serde_json::{json, Value};
 
-/// Read a resource by URI
-#[allow(dead_code)]
-pub async fn read_resource(db: &Database, uri: &str) -> Result<ResourceContent> {
-    // Parse agentroot:// URI
-    if !uri.starts_with("agentroot://") {
-        anyhow::bail!("Invalid URI: {}", uri);
-    }
+const URI_SCHEME: &str = "agentroot://";
 
-    let rest = &uri["agentroot://".len()..];
-    let parts: Vec<&str> = rest.splitn(2, '/').collect();
+/// Resources returned per `resources/list` page
+pub const PAGE_SIZE: usize = 100;
 
-    if parts.len() < 2 {
-        anyhow::bail!("Invalid URI format: {}", uri);
+/// One page of document resources, and the cursor of the next page if any
+pub fn list_resources(db: &Database, cursor: Option<&str>) -> Result<Value> {
+    let documents = db.list_documents_after(cursor, PAGE_SIZE + 1)?;
+    let more = documents.len() > PAGE_SIZE;
+
+    let resources: Vec<Value> = documents
+        .iter()
+        .take(PAGE_SIZE)
+        .map(|doc| {
+            json!({
+                "uri": document_uri(&doc.path),
+                "name": doc.path,
+                "title": doc.title,
+                "description": format!("Document #{}", doc.docid),
+                "mimeType": mime_type(&doc.path),
+            })
+        })
+        .collect();
+
+    let mut result = json!({ "resources": resources });
+    if more {
+        result["nextCursor"] = json!(documents[PAGE_SIZE - 1].path);
     }
+    Ok(result)
+}
+
+/// URI templates clients can fill in to read a document directly
+pub fn resource_templates() -> Value {
+    json!({
+        "resourceTemplates": [{
+            "uriTemplate": "agentroot://{collection}/{path}",
+            "name": "document",
+            "title": "Indexed document",
+            "description": "A document by collection and path within it",
+        }]
+    })
+}
 
-    let collection = parts[0];
-    let path = parts[1];
+/// Read a resource by URI
+pub async fn read_resource(db: &Database, uri: &str) -> Result<ResourceContent> {
+    let Some(rest) = uri.strip_prefix(URI_SCHEME) else {
+        anyhow::bail!("Invalid URI: {}", uri);
+    };
+    let rest = percent_decode(rest);
+    let Some((collection, path)) = rest
+        .split_once('/')
+        .filter(|(c, p)| !c.is_empty() && !p.is_empty())
+    else {
+        anyhow::bail!("Invalid URI format: {}", uri);
+    };
 
     let doc = db
         .find_active_document(collection, path)?
@@ -34,7 +76,55 @@ pub async fn read_resource(db: &Database, uri: &str) -> Result<ResourceContent>
         uri: uri.to_string(),
         name: format!("{}/{}", collection, path),
         title: Some(doc.title),
-        mime_type: "text/markdown".to_string(),
+        mime_type: mime_type(path).to_string(),
         text: content,
     })
 }
+
+/// `agentroot://collection/path`, escaping what a URI cannot hold
+pub fn document_uri(collection_path: &str) -> String {
+    let mut uri = String::from(URI_SCHEME);
+    for byte in collection_path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn mime_type(path: &str) -> &'static str {
+    let extension = path.rsplit_once('.').map(|(_, e)| e.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("md" | "markdown") => "text/markdown",
+        Some("json") => "application/json",
+        Some("html" | "htm") => "text/html",
+        Some("csv") => "text/csv",
+        _ => "text/plain",
+    }
+}
@@ -1,36 +1,117 @@
 //! MCP server implementation
 
+use crate::progress::ProgressNotifier;
 use crate::protocol::*;
-use crate::tools;
-use agentroot_core::Database;
+use crate::{prompts, resources, tools};
+use agentroot_core::{AgentRootError, CancellationToken, Database, NoProgress, ProgressSink};
 use anyhow::Result;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter};
+use tokio::sync::mpsc;
+
+/// How often to check for added or removed documents
+pub(crate) const LIST_CHANGED_POLL: Duration = Duration::from_secs(5);
+
+/// Error code for a request the client cancelled
+pub(crate) const REQUEST_CANCELLED: i32 = -32800;
+
+/// The id of the request that `request` asks to cancel, for both the MCP
+/// `notifications/cancelled` and the LSP-style `$/cancelRequest`
+pub(crate) fn cancelled_request(request: &JsonRpcRequest) -> Option<&Value> {
+    let key = match request.method.as_str() {
+        "notifications/cancelled" => "requestId",
+        "$/cancelRequest" => "id",
+        _ => return None,
+    };
+    request.params.get(key).filter(|id| !id.is_null())
+}
+
+/// Whether `line` cancels the request with id `id`
+fn cancels(line: &str, id: Option<&Value>) -> bool {
+    let Ok(request) = serde_json::from_str::<JsonRpcRequest>(line.trim()) else {
+        return false;
+    };
+    id.is_some() && cancelled_request(&request) == id
+}
 
 pub struct McpServer<'a> {
     db: &'a Database,
+    /// Where notifications about a request in progress go, if the transport
+    /// can deliver them
+    notifications: Option<mpsc::UnboundedSender<JsonRpcNotification>>,
 }
 
 impl<'a> McpServer<'a> {
     pub fn new(db: &'a Database) -> Self {
-        Self { db }
+        Self {
+            db,
+            notifications: None,
+        }
+    }
+
+    /// Send progress of requests that ask for it to `sender`
+    pub(crate) fn with_notifications(
+        mut self,
+        sender: mpsc::UnboundedSender<JsonRpcNotification>,
+    ) -> Self {
+        self.notifications = Some(sender);
+        self
     }
 
     pub async fn run(&self) -> Result<()> {
-        let stdin = tokio::io::stdin();
         let stdout = tokio::io::stdout();
-
-        let mut reader = BufReader::new(stdin);
         let mut writer = BufWriter::new(stdout);
-        let mut line = String::new();
+
+        let (sender, mut notifications) = mpsc::unbounded_channel();
+        let handler = McpServer::new(self.db).with_notifications(sender);
+
+        // Lines are read on their own task so waiting for input never blocks
+        // the check for changed documents
+        let (tx, mut lines) = mpsc::channel::<std::io::Result<String>>(16);
+        tokio::spawn(async move {
+            let mut reader = BufReader::new(tokio::io::stdin());
+            loop {
+                let mut line = String::new();
+                let read = reader.read_line(&mut line).await.map(|n| (n, line));
+                let done = !matches!(read, Ok((n, _)) if n > 0);
+                if tx.send(read.map(|(_, line)| line)).await.is_err() || done {
+                    break;
+                }
+            }
+        });
+
+        let mut watch = tokio::time::interval(LIST_CHANGED_POLL);
+        let mut documents = self.db.document_list_fingerprint().ok();
+        // Lines that arrived while a request was running
+        let mut pending: VecDeque<String> = VecDeque::new();
+        let mut input_open = true;
 
         loop {
-            line.clear();
-            let bytes_read = reader.read_line(&mut line).await?;
+            let line = match pending.pop_front() {
+                Some(line) => line,
+                None => tokio::select! {
+                    line = lines.recv() => match line {
+                        Some(line) => line?,
+                        None => break,
+                    },
+                    _ = watch.tick() => {
+                        let current = self.db.document_list_fingerprint().ok();
+                        if current != documents {
+                            documents = current;
+                            let notification =
+                                JsonRpcNotification::new("notifications/resources/list_changed");
+                            self.write_message(&mut writer, &notification).await?;
+                        }
+                        continue;
+                    }
+                },
+            };
 
-            if bytes_read == 0 {
+            if line.is_empty() {
                 break;
             }
-
             let trimmed = line.trim();
             if trimmed.is_empty() {
                 continue;
@@ -45,8 +126,46 @@ impl<'a> McpServer<'a> {
                     continue;
                 }
             };
+            // The request it names has already been answered
+            if cancelled_request(&request).is_some() {
+                continue;
+            }
 
-            let response = self.handle_request(&request).await;
+            // Progress is written out while the request is still running, and
+            // input is still read so a cancellation can reach it
+            let cancel = CancellationToken::new();
+            let handling = handler.handle_request(&request, &cancel);
+            tokio::pin!(handling);
+            let response = loop {
+                tokio::select! {
+                    response = &mut handling => break response,
+                    Some(notification) = notifications.recv() => {
+                        self.write_message(&mut writer, &notification).await?;
+                    }
+                    line = lines.recv(), if input_open => match line {
+                        Some(line) => {
+                            let line = line?;
+                            if cancels(&line, request.id.as_ref()) {
+                                cancel.cancel();
+                            } else {
+                                input_open = !line.is_empty();
+                                pending.push_back(line);
+                            }
+                        }
+                        None => {
+                            input_open = false;
+                            pending.push_back(String::new());
+                        }
+                    },
+                }
+            };
+            while let Ok(notification) = notifications.try_recv() {
+                self.write_message(&mut writer, &notification).await?;
+            }
+            // Neither a cancelled request nor a notification gets a reply
+            if cancel.is_cancelled() || request.id.is_none() {
+                continue;
+            }
             self.write_response(&mut writer, &response).await?;
         }
 
@@ -58,20 +177,55 @@ impl<'a> McpServer<'a> {
         writer: &mut W,
         response: &JsonRpcResponse,
     ) -> Result<()> {
-        let json = serde_json::to_string(response)?;
+        self.write_message(writer, response).await
+    }
+
+    async fn write_message<W: AsyncWriteExt + Unpin>(
+        &self,
+        writer: &mut W,
+        message: &impl serde::Serialize,
+    ) -> Result<()> {
+        let json = serde_json::to_string(message)?;
         writer.write_all(json.as_bytes()).await?;
         writer.write_all(b"\n").await?;
         writer.flush().await?;
         Ok(())
     }
 
-    async fn handle_request(&self, request: &JsonRpcRequest) -> JsonRpcResponse {
+    /// Answer `request`, or give up with a "Request cancelled" error as soon
+    /// as `cancel` is triggered
+    pub(crate) async fn handle_request(
+        &self,
+        request: &JsonRpcRequest,
+        cancel: &CancellationToken,
+    ) -> JsonRpcResponse {
+        tokio::select! {
+            biased;
+            _ = cancel.cancelled() => JsonRpcResponse::error(
+                request.id.clone(),
+                REQUEST_CANCELLED,
+                "Request cancelled",
+            ),
+            response = self.dispatch(request, cancel) => response,
+        }
+    }
+
+    async fn dispatch(
+        &self,
+        request: &JsonRpcRequest,
+        cancel: &CancellationToken,
+    ) -> JsonRpcResponse {
         match request.method.as_str() {
             "initialize" => self.handle_initialize(request),
             "tools/list" => self.handle_tools_list(request),
-            "tools/call" => self.handle_tools_call(request).await,
+            "tools/call" => self.handle_tools_call(request, cancel).await,
             "resources/list" => self.handle_resources_list(request),
+            "resources/read" => self.handle_resources_read(request).await,
+            "resources/templates/list" => {
+                JsonRpcResponse::success(request.id.clone(), resources::resource_templates())
+            }
             "prompts/list" => self.handle_prompts_list(request),
+            "prompts/get" => self.handle_prompts_get(request),
             _ => JsonRpcResponse::error(
                 request.id.clone(),
                 -32601,
@@ -85,7 +239,7 @@ impl<'a> McpServer<'a> {
             "protocolVersion": "2024-11-05",
             "capabilities": {
                 "tools": {},
-                "resources": { "subscribe": false },
+                "resources": { "subscribe": false, "listChanged": true },
                 "prompts": {}
             },
             "serverInfo": {
@@ -102,12 +256,17 @@ impl<'a> McpServer<'a> {
             tools::vsearch_tool_definition(),
             tools::query_tool_definition(),
             tools::smart_search_tool_definition(),
+            tools::suggest_tool_definition(),
+            tools::similar_tool_definition(),
+            tools::discover_tool_definition(),
             tools::get_tool_definition(),
             tools::multi_get_tool_definition(),
             tools::status_tool_definition(),
             tools::collection_add_tool_definition(),
             tools::collection_remove_tool_definition(),
             tools::collection_update_tool_definition(),
+            tools::index_file_tool_definition(),
+            tools::delete_document_tool_definition(),
             tools::metadata_add_tool_definition(),
             tools::metadata_get_tool_definition(),
             tools::metadata_query_tool_definition(),
@@ -115,11 +274,18 @@ impl<'a> McpServer<'a> {
             tools::search_chunks_tool_definition(),
             tools::get_chunk_tool_definition(),
             tools::navigate_chunks_tool_definition(),
+            tools::find_definition_tool_definition(),
+            tools::find_references_tool_definition(),
+            tools::regex_search_tool_definition(),
+            tools::build_context_tool_definition(),
+            tools::answer_tool_definition(),
             // Session tools
             tools::session_start_tool_definition(),
             tools::session_get_tool_definition(),
             tools::session_set_tool_definition(),
             tools::session_end_tool_definition(),
+            tools::session_list_tool_definition(),
+            tools::session_resume_tool_definition(),
             // Directory browsing tools
             tools::browse_directory_tool_definition(),
             tools::search_directories_tool_definition(),
@@ -132,12 +298,41 @@ impl<'a> McpServer<'a> {
             tools::memory_list_tool_definition(),
             tools::memory_extract_tool_definition(),
             tools::memory_delete_tool_definition(),
+            // Graph tools
+            tools::backlinks_tool_definition(),
+            tools::related_tool_definition(),
+            tools::topics_tool_definition(),
+            tools::dead_links_tool_definition(),
+            tools::graph_neighborhood_tool_definition(),
+            tools::compare_documents_tool_definition(),
+            // Glossary tools
+            tools::glossary_list_tool_definition(),
+            tools::browse_concepts_tool_definition(),
+            tools::glossary_add_tool_definition(),
+            tools::glossary_merge_tool_definition(),
+            tools::glossary_delete_tool_definition(),
+            // Feedback tools
+            tools::feedback_tool_definition(),
+            // Background job tools
+            tools::job_enqueue_tool_definition(),
+            tools::job_list_tool_definition(),
         ];
 
+        let tools: Vec<_> = tools
+            .into_iter()
+            .filter(|t| {
+                !self.db.is_read_only() || !tools::MUTATING_TOOLS.contains(&t.name.as_str())
+            })
+            .collect();
+
         JsonRpcResponse::success(request.id.clone(), serde_json::json!({ "tools": tools }))
     }
 
-    async fn handle_tools_call(&self, request: &JsonRpcRequest) -> JsonRpcResponse {
+    async fn handle_tools_call(
+        &self,
+        request: &JsonRpcRequest,
+        cancel: &CancellationToken,
+    ) -> JsonRpcResponse {
         let name = request
             .params
             .get("name")
@@ -150,17 +345,37 @@ impl<'a> McpServer<'a> {
             .cloned()
             .unwrap_or(serde_json::json!({}));
 
+        let notifier = self
+            .notifications
+            .as_ref()
+            .and_then(|sender| ProgressNotifier::for_request(request, sender));
+        let progress: &dyn ProgressSink = match &notifier {
+            Some(notifier) => notifier,
+            None => &NoProgress,
+        };
+
         let result = match name {
+            _ if self.db.is_read_only() && tools::MUTATING_TOOLS.contains(&name) => Err(
+                anyhow::anyhow!("Tool '{}' is disabled: the index is read-only", name),
+            ),
             "search" => tools::handle_search(self.db, arguments).await,
             "vsearch" => tools::handle_vsearch(self.db, arguments).await,
             "query" => tools::handle_query(self.db, arguments).await,
             "smart_search" => tools::handle_smart_search(self.db, arguments).await,
+            "suggest" => tools::handle_suggest(self.db, arguments).await,
+            "similar" => tools::handle_similar(self.db, arguments).await,
+            "discover" => tools::handle_discover(self.db, arguments).await,
             "get" => tools::handle_get(self.db, arguments).await,
             "multi_get" => tools::handle_multi_get(self.db, arguments).await,
             "status" => tools::handle_status(self.db).await,
             "collection_add" => tools::handle_collection_add(self.db, arguments).await,
             "collection_remove" => tools::handle_collection_remove(self.db, arguments).await,
-            "collection_update" => tools::handle_collection_update(self.db, arguments).await,
+            "collection_update" => {
+                tools::handle_collection_update_cancellable(self.db, arguments, progress, cancel)
+                    .await
+            }
+            "index_file" => tools::handle_index_file(self.db, arguments).await,
+            "delete_document" => tools::handle_delete_document(self.db, arguments).await,
             "metadata_add" => tools::handle_metadata_add(self.db, arguments).await,
             "metadata_get" => tools::handle_metadata_get(self.db, arguments).await,
             "metadata_query" => tools::handle_metadata_query(self.db, arguments).await,
@@ -168,11 +383,18 @@ impl<'a> McpServer<'a> {
             "search_chunks" => tools::handle_search_chunks(self.db, arguments).await,
             "get_chunk" => tools::handle_get_chunk(self.db, arguments).await,
             "navigate_chunks" => tools::handle_navigate_chunks(self.db, arguments).await,
+            "find_definition" => tools::handle_find_definition(self.db, arguments).await,
+            "find_references" => tools::handle_find_references(self.db, arguments).await,
+            "regex_search" => tools::handle_regex_search(self.db, arguments).await,
+            "build_context" => tools::handle_build_context(self.db, arguments).await,
+            "answer" => tools::handle_answer(self.db, arguments).await,
             // Session tools
             "session_start" => tools::handle_session_start(self.db, arguments).await,
             "session_get" => tools::handle_session_get(self.db, arguments).await,
             "session_set" => tools::handle_session_set(self.db, arguments).await,
             "session_end" => tools::handle_session_end(self.db, arguments).await,
+            "session_list" => tools::handle_session_list(self.db, arguments).await,
+            "session_resume" => tools::handle_session_resume(self.db, arguments).await,
             // Directory browsing tools
             "browse_directory" => tools::handle_browse_directory(self.db, arguments).await,
             "search_directories" => tools::handle_search_directories(self.db, arguments).await,
@@ -185,6 +407,24 @@ impl<'a> McpServer<'a> {
             "memory_list" => tools::handle_memory_list(self.db, arguments).await,
             "memory_extract" => tools::handle_memory_extract(self.db, arguments).await,
             "memory_delete" => tools::handle_memory_delete(self.db, arguments).await,
+            // Graph tools
+            "backlinks" => tools::handle_backlinks(self.db, arguments).await,
+            "related" => tools::handle_related(self.db, arguments).await,
+            "topics" => tools::handle_topics(self.db, arguments).await,
+            "dead_links" => tools::handle_dead_links(self.db, arguments).await,
+            "graph_neighborhood" => tools::handle_graph_neighborhood(self.db, arguments).await,
+            "compare_documents" => tools::handle_compare_documents(self.db, arguments).await,
+            // Glossary tools
+            "glossary_list" => tools::handle_glossary_list(self.db, arguments).await,
+            "browse_concepts" => tools::handle_browse_concepts(self.db, arguments).await,
+            "glossary_add" => tools::handle_glossary_add(self.db, arguments).await,
+            "glossary_merge" => tools::handle_glossary_merge(self.db, arguments).await,
+            "glossary_delete" => tools::handle_glossary_delete(self.db, arguments).await,
+            // Feedback tools
+            "feedback" => tools::handle_feedback(self.db, arguments).await,
+            // Background job tools
+            "job_enqueue" => tools::handle_job_enqueue(self.db, arguments).await,
+            "job_list" => tools::handle_job_list(self.db, arguments).await,
             _ => Err(anyhow::anyhow!("Unknown tool: {}", name)),
         };
 
@@ -194,11 +434,17 @@ impl<'a> McpServer<'a> {
                 serde_json::to_value(tool_result).unwrap(),
             ),
             Err(e) => {
+                // Lock contention is transient, so tell the agent it can retry
+                let busy = matches!(
+                    e.downcast_ref::<AgentRootError>(),
+                    Some(AgentRootError::Busy(_))
+                );
                 let error_result = ToolResult {
                     content: vec![Content::Text {
                         text: format!("Error: {}", e),
                     }],
-                    structured_content: None,
+                    structured_content: busy
+                        .then(|| serde_json::json!({ "error": "index_busy", "retryable": true })),
                     is_error: Some(true),
                 };
                 JsonRpcResponse::success(
@@ -210,23 +456,104 @@ impl<'a> McpServer<'a> {
     }
 
     fn handle_resources_list(&self, request: &JsonRpcRequest) -> JsonRpcResponse {
-        JsonRpcResponse::success(request.id.clone(), serde_json::json!({ "resources": [] }))
+        let cursor = request.params.get("cursor").and_then(|v| v.as_str());
+        match resources::list_resources(self.db, cursor) {
+            Ok(result) => JsonRpcResponse::success(request.id.clone(), result),
+            Err(e) => JsonRpcResponse::error(request.id.clone(), -32603, &e.to_string()),
+        }
+    }
+
+    async fn handle_resources_read(&self, request: &JsonRpcRequest) -> JsonRpcResponse {
+        let Some(uri) = request.params.get("uri").and_then(|v| v.as_str()) else {
+            return JsonRpcResponse::error(request.id.clone(), -32602, "Missing uri");
+        };
+        match resources::read_resource(self.db, uri).await {
+            Ok(content) => JsonRpcResponse::success(
+                request.id.clone(),
+                serde_json::json!({ "contents": [content] }),
+            ),
+            // Unknown resources are reported as the spec's "resource not found"
+            Err(e) => JsonRpcResponse::error(request.id.clone(), -32002, &e.to_string()),
+        }
     }
 
     fn handle_prompts_list(&self, request: &JsonRpcRequest) -> JsonRpcResponse {
-        let prompts = vec![serde_json::json!({
-            "name": "query",
-            "title": "Agentroot Query Guide",
-            "description": "How to effectively search your knowledge base"
-        })];
         JsonRpcResponse::success(
             request.id.clone(),
-            serde_json::json!({ "prompts": prompts }),
+            serde_json::json!({ "prompts": prompts::prompt_definitions() }),
         )
     }
+
+    fn handle_prompts_get(&self, request: &JsonRpcRequest) -> JsonRpcResponse {
+        let Some(name) = request.params.get("name").and_then(|v| v.as_str()) else {
+            return JsonRpcResponse::error(request.id.clone(), -32602, "Missing prompt name");
+        };
+        let arguments = request
+            .params
+            .get("arguments")
+            .cloned()
+            .unwrap_or(serde_json::json!({}));
+        match prompts::get_prompt(self.db, name, &arguments) {
+            Ok(result) => JsonRpcResponse::success(request.id.clone(), result),
+            Err(e) => JsonRpcResponse::error(request.id.clone(), -32602, &e.to_string()),
+        }
+    }
 }
 
 pub async fn start_server(db: &Database) -> Result<()> {
     let server = McpServer::new(db);
     server.run().await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_cancellation_names_the_request() {
+        let cancelled =
+            r#"{"jsonrpc":"2.0","method":"notifications/cancelled","params":{"requestId":4}}"#;
+        let lsp = r#"{"jsonrpc":"2.0","method":"$/cancelRequest","params":{"id":"a"}}"#;
+        assert!(cancels(cancelled, Some(&json!(4))));
+        assert!(!cancels(cancelled, Some(&json!("4"))));
+        assert!(!cancels(cancelled, None));
+        assert!(cancels(lsp, Some(&json!("a"))));
+        assert!(!cancels(
+            r#"{"jsonrpc":"2.0","id":4,"method":"ping"}"#,
+            Some(&json!(4))
+        ));
+        assert!(!cancels("not json", Some(&json!(4))));
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_request_gets_an_error() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = Database::open(dir.path().join("index.sqlite")).unwrap();
+        db.initialize().unwrap();
+        db.add_collection(
+            "notes",
+            dir.path().to_str().unwrap(),
+            "**/*.md",
+            "file",
+            None,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("a.md"), "# A\n\nNot indexed yet.").unwrap();
+
+        let request: JsonRpcRequest = serde_json::from_value(json!({
+            "jsonrpc": "2.0",
+            "id": 9,
+            "method": "tools/call",
+            "params": {"name": "collection_update", "arguments": {"name": "notes"}}
+        }))
+        .unwrap();
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let response = McpServer::new(&db).handle_request(&request, &cancel).await;
+        let response = serde_json::to_value(response).unwrap();
+        assert_eq!(response["id"], 9);
+        assert_eq!(response["error"]["code"], REQUEST_CANCELLED);
+        assert_eq!(db.list_collections().unwrap()[0].document_count, 0);
+    }
+}
@@ -1,12 +1,38 @@
 //! MCP tool definitions and handlers
 
 use crate::protocol::*;
+use agentroot_core::db::{JobKind, JobStatus};
 use agentroot_core::llm::MemoryExtractor;
-use agentroot_core::{Database, DetailLevel, SearchOptions};
+use agentroot_core::search::SearchCursor;
+use agentroot_core::{
+    CancellationToken, Database, DetailLevel, NoProgress, ProgressSink, SearchOptions,
+};
 use anyhow::Result;
 use serde_json::Value;
 use tracing::warn;
 
+/// Tools that write to the index, hidden when it is opened read-only
+pub const MUTATING_TOOLS: &[&str] = &[
+    "collection_add",
+    "collection_remove",
+    "collection_update",
+    "index_file",
+    "delete_document",
+    "metadata_add",
+    "session_start",
+    "session_set",
+    "session_end",
+    "session_resume",
+    "memory_store",
+    "memory_extract",
+    "memory_delete",
+    "glossary_add",
+    "glossary_merge",
+    "glossary_delete",
+    "feedback",
+    "job_enqueue",
+];
+
 // Common detail and session parameters for search tool schemas
 fn detail_param() -> Value {
     serde_json::json!({
@@ -20,23 +46,105 @@ fn detail_param() -> Value {
 fn session_id_param() -> Value {
     serde_json::json!({
         "type": "string",
-        "description": "Optional session ID for multi-turn context tracking (from session_start)"
+        "description": "Optional session ID or name for multi-turn context tracking (from session_start, or shared with the CLI/TUI)"
+    })
+}
+
+fn exact_param() -> Value {
+    serde_json::json!({
+        "type": "boolean",
+        "default": false,
+        "description": "Match literal tokens without stemming, e.g. 'tests' but not 'test' (full-text search only)"
+    })
+}
+
+fn parse_exact(args: &Value) -> bool {
+    args.get("exact").and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+fn offset_param() -> Value {
+    serde_json::json!({
+        "type": "integer",
+        "default": 0,
+        "description": "Results to skip, for paging (ignored when cursor is given)"
+    })
+}
+
+fn cursor_param() -> Value {
+    serde_json::json!({
+        "type": "string",
+        "description": "nextCursor from the previous page of the same search"
     })
 }
 
+/// Make `options` a paged search from the cursor or offset, returning the page size
+///
+/// One result beyond the page is requested, to tell whether another page
+/// follows; [`finish_page`] drops it again.
+fn start_page(args: &Value, query: &str, options: &mut SearchOptions) -> Result<usize> {
+    let offset = match args.get("cursor").and_then(|v| v.as_str()) {
+        Some(token) => SearchCursor::decode(token, query, options)?.offset,
+        None => args.get("offset").and_then(|v| v.as_u64()).unwrap_or(0) as usize,
+    };
+    let page_size = options.limit;
+    options.offset = Some(offset);
+    if page_size > 0 {
+        options.limit = page_size + 1;
+    }
+    Ok(page_size)
+}
+
+/// Trim the lookahead result, returning the cursor of the next page if any
+fn finish_page(
+    results: &mut Vec<agentroot_core::SearchResult>,
+    query: &str,
+    options: &SearchOptions,
+    page_size: usize,
+) -> Option<String> {
+    if page_size == 0 || results.len() <= page_size {
+        return None;
+    }
+    results.truncate(page_size);
+    let offset = options.offset.unwrap_or(0) + page_size;
+    Some(SearchCursor::new(query, options, offset).encode())
+}
+
+/// Search results with the next page's cursor, in text and structured form
+fn paged_results(
+    summary: String,
+    results: &[agentroot_core::SearchResult],
+    next_cursor: Option<String>,
+) -> ToolResult {
+    let structured: Vec<Value> = results.iter().map(result_to_json).collect();
+    let mut body = serde_json::json!({ "results": structured });
+    let mut text = summary;
+    if let Some(cursor) = next_cursor {
+        text.push_str(&format!("\nMore results: pass cursor \"{}\"", cursor));
+        body["nextCursor"] = Value::String(cursor);
+    }
+    ToolResult {
+        content: vec![Content::Text { text }],
+        structured_content: Some(body),
+        is_error: None,
+    }
+}
+
 fn parse_detail(args: &Value) -> DetailLevel {
     DetailLevel::from_str_opt(args.get("detail").and_then(|v| v.as_str()))
 }
 
-fn parse_session_id(args: &Value) -> Option<String> {
-    args.get("session_id")
-        .and_then(|v| v.as_str())
-        .map(String::from)
+/// Read the session parameter, resolving session names (e.g. from the CLI) to ids
+fn parse_session_id(db: &Database, args: &Value) -> Option<String> {
+    let session = args.get("session_id").and_then(|v| v.as_str())?;
+    match db.find_session(session) {
+        Ok(Some(info)) => Some(info.id),
+        _ => Some(session.to_string()),
+    }
 }
 
 fn apply_session_and_project(
     db: &Database,
-    results: &mut Vec<agentroot_core::SearchResult>,
+    results: &mut [agentroot_core::SearchResult],
     detail: DetailLevel,
     session_id: Option<&str>,
     query: &str,
@@ -108,7 +216,7 @@ pub fn search_tool_definition() -> ToolDefinition {
             "properties": {
                 "query": {
                     "type": "string",
-                    "description": "Search query (keywords or phrases)"
+                    "description": "Search query: keywords, \"phrases\", OR, NOT, (groups), prefix*, title:/path:/body: field scopes"
                 },
                 "limit": {
                     "type": "integer",
@@ -140,8 +248,11 @@ pub fn search_tool_definition() -> ToolDefinition {
                     "type": "string",
                     "description": "Filter by concept/topic"
                 },
+                "exact": exact_param(),
                 "detail": detail_param(),
-                "session_id": session_id_param()
+                "session_id": session_id_param(),
+                "offset": offset_param(),
+                "cursor": cursor_param()
             },
             "required": ["query"]
         }),
@@ -190,7 +301,9 @@ pub fn vsearch_tool_definition() -> ToolDefinition {
                     "description": "Filter by concept/topic"
                 },
                 "detail": detail_param(),
-                "session_id": session_id_param()
+                "session_id": session_id_param(),
+                "offset": offset_param(),
+                "cursor": cursor_param()
             },
             "required": ["query"]
         }),
@@ -233,8 +346,22 @@ pub fn query_tool_definition() -> ToolDefinition {
                     "type": "string",
                     "description": "Filter by concept/topic"
                 },
+                "fuse": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Rephrasings of the query to search as well; all rankings are fused with RRF"
+                },
+                "alpha": {
+                    "type": "number",
+                    "minimum": 0,
+                    "maximum": 1,
+                    "description": "Fusion weight of keyword vs semantic ranking: 1 = BM25 only, 0 = vectors only (default: configured weights)"
+                },
+                "exact": exact_param(),
                 "detail": detail_param(),
-                "session_id": session_id_param()
+                "session_id": session_id_param(),
+                "offset": offset_param(),
+                "cursor": cursor_param()
             },
             "required": ["query"]
         }),
@@ -274,10 +401,153 @@ pub fn smart_search_tool_definition() -> ToolDefinition {
     }
 }
 
+pub fn suggest_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "suggest".to_string(),
+        description: "Complete a partial query from indexed words, glossary concepts and document titles (fast, for search-as-you-type)"
+            .to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "prefix": {
+                    "type": "string",
+                    "description": "Query typed so far; its last word is completed"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum suggestions (default: 10)",
+                    "default": 10
+                }
+            },
+            "required": ["prefix"]
+        }),
+    }
+}
+
+pub fn similar_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "similar".to_string(),
+        description: "Find documents similar to a document (more like this), without writing a query. Uses its embeddings and its keywords".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "docid": {
+                    "type": "string",
+                    "description": "Document ID (#abc123) or collection/path"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum results (default: 10)",
+                    "default": 10
+                },
+                "detail": detail_param(),
+                "session_id": session_id_param()
+            },
+            "required": ["docid"]
+        }),
+    }
+}
+
+pub async fn handle_similar(db: &Database, args: Value) -> Result<ToolResult> {
+    let docid = args
+        .get("docid")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing docid"))?;
+    let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(10) as usize;
+    let detail = parse_detail(&args);
+    let session_id = parse_session_id(db, &args);
+
+    let mut results = db.similar_to(docid, limit)?;
+    apply_session_and_project(db, &mut results, detail, session_id.as_deref(), docid);
+
+    let summary = format!("Found {} documents similar to {}", results.len(), docid);
+    let structured: Vec<Value> = results.iter().map(result_to_json).collect();
+
+    Ok(ToolResult {
+        content: vec![Content::Text { text: summary }],
+        structured_content: Some(serde_json::json!({ "results": structured })),
+        is_error: None,
+    })
+}
+
+pub fn discover_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "discover".to_string(),
+        description: "Resurface a weighted random sample of documents, favouring important and recent ones. Use it to rediscover forgotten notes rather than to answer a question".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "collection": {
+                    "type": "string",
+                    "description": "Filter by collection name"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Number of documents (default: 10)",
+                    "default": 10
+                },
+                "never_retrieved": {
+                    "type": "boolean",
+                    "description": "Only documents no search has returned yet (default: false)",
+                    "default": false
+                },
+                "seed": {
+                    "type": "integer",
+                    "description": "Seed for a reproducible sample"
+                }
+            }
+        }),
+    }
+}
+
+pub async fn handle_discover(db: &Database, args: Value) -> Result<ToolResult> {
+    let options = agentroot_core::db::DiscoverOptions {
+        collection: args
+            .get("collection")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        limit: args.get("limit").and_then(|v| v.as_u64()).unwrap_or(10) as usize,
+        never_retrieved: args
+            .get("never_retrieved")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+        seed: args.get("seed").and_then(|v| v.as_u64()),
+    };
+    let documents = db.discover(&options)?;
+
+    let mut summary = format!("Discovered {} documents", documents.len());
+    for d in &documents {
+        summary.push_str(&format!(
+            "\n  #{}  {}/{}  {}",
+            d.docid, d.collection, d.path, d.title
+        ));
+    }
+
+    let structured: Vec<Value> = documents
+        .iter()
+        .map(|d| {
+            serde_json::json!({
+                "docid": format!("#{}", d.docid),
+                "file": format!("{}/{}", d.collection, d.path),
+                "title": d.title,
+                "modified_at": d.modified_at,
+                "last_retrieved_at": d.last_retrieved_at,
+                "weight": (d.weight * 100.0).round() / 100.0
+            })
+        })
+        .collect();
+
+    Ok(ToolResult {
+        content: vec![Content::Text { text: summary }],
+        structured_content: Some(serde_json::json!({ "documents": structured })),
+        is_error: None,
+    })
+}
+
 pub fn get_tool_definition() -> ToolDefinition {
     ToolDefinition {
         name: "get".to_string(),
-        description: "Get a document by path, docid, or virtual path".to_string(),
+        description: "Get a document by path, docid, or virtual path, an earlier version of it, or a diff between versions".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
@@ -297,7 +567,25 @@ pub fn get_tool_definition() -> ToolDefinition {
                     "type": "boolean",
                     "description": "Include line numbers",
                     "default": false
-                }
+                },
+                "highlight": {
+                    "type": "string",
+                    "description": "Query whose term matches to report as line/column ranges (default: the session's latest query)"
+                },
+                "version": {
+                    "type": "string",
+                    "description": "Earlier version to return: its number (1 is the first indexed) or docid"
+                },
+                "diff": {
+                    "type": "string",
+                    "description": "Return a unified diff from this version to `version` (default: the latest)"
+                },
+                "history": {
+                    "type": "boolean",
+                    "description": "List the recorded versions instead of returning content",
+                    "default": false
+                },
+                "session_id": session_id_param()
             },
             "required": ["file"]
         }),
@@ -307,7 +595,7 @@ pub fn get_tool_definition() -> ToolDefinition {
 pub fn multi_get_tool_definition() -> ToolDefinition {
     ToolDefinition {
         name: "multi_get".to_string(),
-        description: "Get multiple documents by glob pattern or comma-separated list".to_string(),
+        description: "Get multiple documents by glob pattern or comma-separated list within a total token/byte budget. The budget is shared by document importance; documents that do not fit whole are cut at a heading and end with a marker naming the omitted sections".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
@@ -319,10 +607,13 @@ pub fn multi_get_tool_definition() -> ToolDefinition {
                     "type": "integer",
                     "description": "Maximum lines per file"
                 },
+                "maxTokens": {
+                    "type": "integer",
+                    "description": "Total estimated tokens across all documents (default: 8000 unless maxBytes is given)"
+                },
                 "maxBytes": {
                     "type": "integer",
-                    "description": "Skip files larger than this (default: 10240)",
-                    "default": 10240
+                    "description": "Total bytes across all documents"
                 },
                 "lineNumbers": {
                     "type": "boolean",
@@ -408,6 +699,11 @@ pub fn collection_update_tool_definition() -> ToolDefinition {
                 "name": {
                     "type": "string",
                     "description": "Collection name to update"
+                },
+                "background": {
+                    "type": "boolean",
+                    "description": "Queue the reindex as a background job and return immediately",
+                    "default": false
                 }
             },
             "required": ["name"]
@@ -422,9 +718,9 @@ pub async fn handle_search(db: &Database, args: Value) -> Result<ToolResult> {
         .ok_or_else(|| anyhow::anyhow!("Missing query"))?;
 
     let detail = parse_detail(&args);
-    let session_id = parse_session_id(&args);
+    let session_id = parse_session_id(db, &args);
 
-    let options = SearchOptions {
+    let mut options = SearchOptions {
         limit: args.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize,
         min_score: args.get("minScore").and_then(|v| v.as_f64()).unwrap_or(0.0),
         collection: args
@@ -437,12 +733,14 @@ pub async fn handle_search(db: &Database, args: Value) -> Result<ToolResult> {
             .map(String::from),
 
         detail,
+        exact: parse_exact(&args),
         session_id: session_id.clone(),
-        metadata_filters: Vec::new(),
         ..Default::default()
     };
+    let page_size = start_page(&args, query, &mut options)?;
 
     let mut results = db.search_fts(query, &options)?;
+    let next_cursor = finish_page(&mut results, query, &options, page_size);
 
     // Apply metadata filters
     let category_filter = args.get("category").and_then(|v| v.as_str());
@@ -451,18 +749,18 @@ pub async fn handle_search(db: &Database, args: Value) -> Result<ToolResult> {
 
     if category_filter.is_some() || difficulty_filter.is_some() || concept_filter.is_some() {
         results.retain(|r| {
-            let matches_category = category_filter.map_or(true, |cat| {
+            let matches_category = category_filter.is_none_or(|cat| {
                 r.llm_category
                     .as_ref()
-                    .map_or(false, |c| c.to_lowercase().contains(&cat.to_lowercase()))
+                    .is_some_and(|c| c.to_lowercase().contains(&cat.to_lowercase()))
             });
-            let matches_difficulty = difficulty_filter.map_or(true, |diff| {
+            let matches_difficulty = difficulty_filter.is_none_or(|diff| {
                 r.llm_difficulty
                     .as_ref()
-                    .map_or(false, |d| d.to_lowercase() == diff.to_lowercase())
+                    .is_some_and(|d| d.to_lowercase() == diff.to_lowercase())
             });
-            let matches_concept = concept_filter.map_or(true, |concept| {
-                r.llm_keywords.as_ref().map_or(false, |kws| {
+            let matches_concept = concept_filter.is_none_or(|concept| {
+                r.llm_keywords.as_ref().is_some_and(|kws| {
                     kws.iter()
                         .any(|kw| kw.to_lowercase().contains(&concept.to_lowercase()))
                 })
@@ -474,13 +772,7 @@ pub async fn handle_search(db: &Database, args: Value) -> Result<ToolResult> {
     apply_session_and_project(db, &mut results, detail, session_id.as_deref(), query);
 
     let summary = format!("Found {} results for \"{}\"", results.len(), query);
-    let structured: Vec<Value> = results.iter().map(result_to_json).collect();
-
-    Ok(ToolResult {
-        content: vec![Content::Text { text: summary }],
-        structured_content: Some(serde_json::json!({ "results": structured })),
-        is_error: None,
-    })
+    Ok(paged_results(summary, &results, next_cursor))
 }
 
 pub async fn handle_vsearch(db: &Database, args: Value) -> Result<ToolResult> {
@@ -500,9 +792,9 @@ pub async fn handle_vsearch(db: &Database, args: Value) -> Result<ToolResult> {
         .ok_or_else(|| anyhow::anyhow!("Missing query"))?;
 
     let detail = parse_detail(&args);
-    let session_id = parse_session_id(&args);
+    let session_id = parse_session_id(db, &args);
 
-    let options = SearchOptions {
+    let mut options = SearchOptions {
         limit: args.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize,
         min_score: args.get("minScore").and_then(|v| v.as_f64()).unwrap_or(0.3),
         collection: args
@@ -516,29 +808,31 @@ pub async fn handle_vsearch(db: &Database, args: Value) -> Result<ToolResult> {
 
         detail,
         session_id: session_id.clone(),
-        metadata_filters: Vec::new(),
         ..Default::default()
     };
+    let page_size = start_page(&args, query, &mut options)?;
 
-    // Try HTTP embedder first, fallback to local
-    let embedder: Box<dyn agentroot_core::Embedder> = match agentroot_core::HttpEmbedder::from_env()
-    {
-        Ok(http) => Box::new(http),
-        Err(_) => {
+    // The embedding service when configured, otherwise the local model
+    let embedder = match agentroot_core::default_embedder().await {
+        Ok(embedder) => embedder,
+        Err(e) => {
             return Ok(ToolResult {
-                    content: vec![Content::Text {
-                        text: "No embedding service configured. Set AGENTROOT_EMBEDDING_URL, \
-                              AGENTROOT_EMBEDDING_MODEL, and AGENTROOT_EMBEDDING_DIMS environment variables. \
-                              See VLLM_SETUP.md for details."
-                            .to_string(),
-                    }],
-                    structured_content: None,
-                    is_error: Some(true),
-                });
+                content: vec![Content::Text {
+                    text: format!(
+                        "Could not load an embedding model: {}. Set AGENTROOT_EMBEDDING_URL, \
+                         AGENTROOT_EMBEDDING_MODEL, and AGENTROOT_EMBEDDING_DIMS to use an \
+                         embedding service. See VLLM_SETUP.md for details.",
+                        e
+                    ),
+                }],
+                structured_content: None,
+                is_error: Some(true),
+            });
         }
     };
 
     let mut results = db.search_vec(query, embedder.as_ref(), &options).await?;
+    let next_cursor = finish_page(&mut results, query, &options, page_size);
 
     // Apply metadata filters
     let category_filter = args.get("category").and_then(|v| v.as_str());
@@ -547,18 +841,18 @@ pub async fn handle_vsearch(db: &Database, args: Value) -> Result<ToolResult> {
 
     if category_filter.is_some() || difficulty_filter.is_some() || concept_filter.is_some() {
         results.retain(|r| {
-            let matches_category = category_filter.map_or(true, |cat| {
+            let matches_category = category_filter.is_none_or(|cat| {
                 r.llm_category
                     .as_ref()
-                    .map_or(false, |c| c.to_lowercase().contains(&cat.to_lowercase()))
+                    .is_some_and(|c| c.to_lowercase().contains(&cat.to_lowercase()))
             });
-            let matches_difficulty = difficulty_filter.map_or(true, |diff| {
+            let matches_difficulty = difficulty_filter.is_none_or(|diff| {
                 r.llm_difficulty
                     .as_ref()
-                    .map_or(false, |d| d.to_lowercase() == diff.to_lowercase())
+                    .is_some_and(|d| d.to_lowercase() == diff.to_lowercase())
             });
-            let matches_concept = concept_filter.map_or(true, |concept| {
-                r.llm_keywords.as_ref().map_or(false, |kws| {
+            let matches_concept = concept_filter.is_none_or(|concept| {
+                r.llm_keywords.as_ref().is_some_and(|kws| {
                     kws.iter()
                         .any(|kw| kw.to_lowercase().contains(&concept.to_lowercase()))
                 })
@@ -570,17 +864,23 @@ pub async fn handle_vsearch(db: &Database, args: Value) -> Result<ToolResult> {
     apply_session_and_project(db, &mut results, detail, session_id.as_deref(), query);
 
     let summary = format!("Found {} results for \"{}\"", results.len(), query);
-    let structured: Vec<Value> = results.iter().map(result_to_json).collect();
-
-    Ok(ToolResult {
-        content: vec![Content::Text { text: summary }],
-        structured_content: Some(serde_json::json!({ "results": structured })),
-        is_error: None,
-    })
+    Ok(paged_results(summary, &results, next_cursor))
 }
 
 pub async fn handle_query(db: &Database, args: Value) -> Result<ToolResult> {
-    if !db.has_vector_index() {
+    let fuse: Vec<String> = args
+        .get("fuse")
+        .and_then(|v| v.as_array())
+        .map(|variants| {
+            variants
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if fuse.is_empty() && !db.has_vector_index() {
         return handle_search(db, args).await;
     }
 
@@ -589,10 +889,15 @@ pub async fn handle_query(db: &Database, args: Value) -> Result<ToolResult> {
         .and_then(|v| v.as_str())
         .ok_or_else(|| anyhow::anyhow!("Missing query"))?;
 
+    let alpha = args.get("alpha").and_then(|v| v.as_f64());
+    if alpha.is_some_and(|a| !(0.0..=1.0).contains(&a)) {
+        anyhow::bail!("alpha must be between 0 and 1");
+    }
+
     let detail = parse_detail(&args);
-    let session_id = parse_session_id(&args);
+    let session_id = parse_session_id(db, &args);
 
-    let options = SearchOptions {
+    let mut options = SearchOptions {
         limit: args.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize,
         min_score: 0.0,
         collection: args
@@ -605,31 +910,54 @@ pub async fn handle_query(db: &Database, args: Value) -> Result<ToolResult> {
             .map(String::from),
 
         detail,
+        exact: parse_exact(&args),
+        alpha,
         session_id: session_id.clone(),
-        metadata_filters: Vec::new(),
         ..Default::default()
     };
-
-    // Try HTTP embedder, fallback to BM25-only if not configured
-    let embedder: Box<dyn agentroot_core::Embedder> = match agentroot_core::HttpEmbedder::from_env()
-    {
-        Ok(http) => Box::new(http),
-        Err(_) => {
-            // No HTTP embedder configured, fall back to BM25-only search
+    let page_size = start_page(&args, query, &mut options)?;
+    // Fusion ranks from the top; the page is cut from the fused ranking
+    let window = options.page_window();
+
+    // BM25-only indexes never load (or download) the embedding model
+    let has_vectors = db.has_vector_index();
+    let final_results = if fuse.is_empty() {
+        // Without vectors or a usable embedder, fall back to BM25-only search
+        if !has_vectors {
             return handle_search(db, args).await;
         }
-    };
-
-    let bm25_results = db.search_fts(query, &options)?;
-    let vec_results = db.search_vec(query, embedder.as_ref(), &options).await?;
-
-    let fused_results = agentroot_core::search::rrf_fusion(&bm25_results, &vec_results);
+        let embedder = match agentroot_core::default_embedder().await {
+            Ok(embedder) => embedder,
+            Err(_) => return handle_search(db, args).await,
+        };
 
-    let mut final_results: Vec<_> = fused_results
-        .into_iter()
-        .filter(|r| r.score >= options.min_score)
-        .take(options.limit)
-        .collect();
+        let bm25_results = db.search_fts(query, &window)?;
+        let vec_results = db.search_vec(query, embedder.as_ref(), &window).await?;
+
+        let mut fused_results = agentroot_core::search::rrf_fusion_weighted(
+            &bm25_results,
+            &vec_results,
+            &window.ranking_weights(),
+        );
+        agentroot_core::search::pinning::apply_pins(db, query, &window, &mut fused_results)?;
+
+        fused_results
+            .into_iter()
+            .filter(|r| r.score >= window.min_score)
+            .take(window.limit)
+            .collect()
+    } else {
+        let mut queries = vec![query.to_string()];
+        queries.extend(fuse.iter().cloned());
+        let embedder = if has_vectors {
+            agentroot_core::default_embedder().await.ok()
+        } else {
+            None
+        };
+        agentroot_core::multi_query_search(db, &queries, embedder.as_deref(), &window).await?
+    };
+    let mut final_results = agentroot_core::search::take_page(final_results, &options);
+    let next_cursor = finish_page(&mut final_results, query, &options, page_size);
 
     // Apply metadata filters
     let category_filter = args.get("category").and_then(|v| v.as_str());
@@ -638,18 +966,18 @@ pub async fn handle_query(db: &Database, args: Value) -> Result<ToolResult> {
 
     if category_filter.is_some() || difficulty_filter.is_some() || concept_filter.is_some() {
         final_results.retain(|r| {
-            let matches_category = category_filter.map_or(true, |cat| {
+            let matches_category = category_filter.is_none_or(|cat| {
                 r.llm_category
                     .as_ref()
-                    .map_or(false, |c| c.to_lowercase().contains(&cat.to_lowercase()))
+                    .is_some_and(|c| c.to_lowercase().contains(&cat.to_lowercase()))
             });
-            let matches_difficulty = difficulty_filter.map_or(true, |diff| {
+            let matches_difficulty = difficulty_filter.is_none_or(|diff| {
                 r.llm_difficulty
                     .as_ref()
-                    .map_or(false, |d| d.to_lowercase() == diff.to_lowercase())
+                    .is_some_and(|d| d.to_lowercase() == diff.to_lowercase())
             });
-            let matches_concept = concept_filter.map_or(true, |concept| {
-                r.llm_keywords.as_ref().map_or(false, |kws| {
+            let matches_concept = concept_filter.is_none_or(|concept| {
+                r.llm_keywords.as_ref().is_some_and(|kws| {
                     kws.iter()
                         .any(|kw| kw.to_lowercase().contains(&concept.to_lowercase()))
                 })
@@ -660,18 +988,21 @@ pub async fn handle_query(db: &Database, args: Value) -> Result<ToolResult> {
 
     apply_session_and_project(db, &mut final_results, detail, session_id.as_deref(), query);
 
-    let summary = format!(
-        "Found {} results for \"{}\" (hybrid search)",
-        final_results.len(),
-        query
-    );
-    let structured: Vec<Value> = final_results.iter().map(result_to_json).collect();
-
-    Ok(ToolResult {
-        content: vec![Content::Text { text: summary }],
-        structured_content: Some(serde_json::json!({ "results": structured })),
-        is_error: None,
-    })
+    let summary = if fuse.is_empty() {
+        format!(
+            "Found {} results for \"{}\" (hybrid search)",
+            final_results.len(),
+            query
+        )
+    } else {
+        format!(
+            "Found {} results for \"{}\" (fused with {} rephrasings)",
+            final_results.len(),
+            query,
+            fuse.len()
+        )
+    };
+    Ok(paged_results(summary, &final_results, next_cursor))
 }
 
 pub async fn handle_smart_search(db: &Database, args: Value) -> Result<ToolResult> {
@@ -681,7 +1012,7 @@ pub async fn handle_smart_search(db: &Database, args: Value) -> Result<ToolResul
         .ok_or_else(|| anyhow::anyhow!("Missing query"))?;
 
     let detail = parse_detail(&args);
-    let session_id = parse_session_id(&args);
+    let session_id = parse_session_id(db, &args);
 
     let options = SearchOptions {
         limit: args.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize,
@@ -694,7 +1025,6 @@ pub async fn handle_smart_search(db: &Database, args: Value) -> Result<ToolResul
 
         detail,
         session_id: session_id.clone(),
-        metadata_filters: Vec::new(),
         ..Default::default()
     };
 
@@ -717,18 +1047,115 @@ pub async fn handle_smart_search(db: &Database, args: Value) -> Result<ToolResul
     })
 }
 
+pub async fn handle_suggest(db: &Database, args: Value) -> Result<ToolResult> {
+    let prefix = args
+        .get("prefix")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing prefix"))?;
+    let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(10) as usize;
+
+    let suggestions = db.suggest(prefix, limit)?;
+    let text = if suggestions.is_empty() {
+        format!("No suggestions for \"{}\"", prefix)
+    } else {
+        suggestions
+            .iter()
+            .map(|s| s.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    Ok(ToolResult {
+        content: vec![Content::Text { text }],
+        structured_content: Some(serde_json::json!({ "suggestions": suggestions })),
+        is_error: None,
+    })
+}
+
 pub async fn handle_get(db: &Database, args: Value) -> Result<ToolResult> {
     let file = args
         .get("file")
         .and_then(|v| v.as_str())
         .ok_or_else(|| anyhow::anyhow!("Missing file"))?;
 
+    let version = args.get("version").and_then(|v| v.as_str());
+    if args.get("history").and_then(|v| v.as_bool()) == Some(true) {
+        let history = db.document_history(file)?;
+        let lines: Vec<String> = history
+            .versions
+            .iter()
+            .rev()
+            .map(|v| {
+                format!(
+                    "v{} #{} {} {}{}",
+                    v.version,
+                    v.docid,
+                    v.recorded_at,
+                    v.title,
+                    if v.current { " (current)" } else { "" }
+                )
+            })
+            .collect();
+        return Ok(ToolResult {
+            content: vec![Content::Text {
+                text: format!(
+                    "{}/{}\n{}",
+                    history.collection,
+                    history.path,
+                    lines.join("\n")
+                ),
+            }],
+            structured_content: Some(serde_json::to_value(&history)?),
+            is_error: None,
+        });
+    }
+    if let Some(from) = args.get("diff").and_then(|v| v.as_str()) {
+        let diff = db.diff_document_versions(file, from, version)?;
+        return Ok(ToolResult {
+            content: vec![Content::Text { text: diff }],
+            structured_content: None,
+            is_error: None,
+        });
+    }
+    if let Some(version) = version {
+        let history = db.document_history(file)?;
+        let (found, body) = db.get_document_version(file, version)?;
+        return Ok(ToolResult {
+            content: vec![Content::Resource {
+                resource: ResourceContent {
+                    uri: format!("agentroot://{}/{}", history.collection, history.path),
+                    name: format!(
+                        "{}/{} (v{})",
+                        history.collection, history.path, found.version
+                    ),
+                    title: Some(found.title.clone()),
+                    mime_type: "text/markdown".to_string(),
+                    text: body,
+                },
+            }],
+            structured_content: Some(serde_json::to_value(&found)?),
+            is_error: None,
+        });
+    }
+
     let doc = db
         .find_by_docid(file)?
         .ok_or_else(|| anyhow::anyhow!("Document not found: {}", file))?;
 
     let body = doc.body.unwrap_or_default();
 
+    let highlight = match args.get("highlight").and_then(|v| v.as_str()) {
+        Some(query) => Some(query.to_string()),
+        None => match parse_session_id(db, &args) {
+            Some(sid) => db.get_session_queries(&sid)?.pop().map(|q| q.query),
+            None => None,
+        },
+    };
+    let structured_content = highlight.map(|query| {
+        let matches = agentroot_core::search::find_match_ranges(&body, &query);
+        serde_json::json!({ "highlight": query, "matches": matches })
+    });
+
     Ok(ToolResult {
         content: vec![Content::Resource {
             resource: ResourceContent {
@@ -739,35 +1166,98 @@ pub async fn handle_get(db: &Database, args: Value) -> Result<ToolResult> {
                 text: body,
             },
         }],
-        structured_content: None,
+        structured_content,
         is_error: None,
     })
 }
 
 pub async fn handle_multi_get(db: &Database, args: Value) -> Result<ToolResult> {
+    use agentroot_core::db::MultiGetOptions;
+
     let pattern = args
         .get("pattern")
         .and_then(|v| v.as_str())
         .ok_or_else(|| anyhow::anyhow!("Missing pattern"))?;
+    let get_usize = |key: &str| args.get(key).and_then(|v| v.as_u64()).map(|v| v as usize);
+    let options = MultiGetOptions {
+        max_tokens: get_usize("maxTokens"),
+        max_bytes: get_usize("maxBytes"),
+        max_lines: get_usize("maxLines"),
+    };
+    let line_numbers = args
+        .get("lineNumbers")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let mut result = db.multi_get(pattern, &options)?;
+    if result.documents.is_empty() && result.omitted.is_empty() {
+        // Not a glob or path list: fall back to a fuzzy path/title match
+        let matches: Vec<String> = db
+            .fuzzy_find_documents(pattern, 10)?
+            .into_iter()
+            .map(|doc| doc.display_path)
+            .collect();
+        if !matches.is_empty() {
+            result = db.multi_get(&matches.join(","), &options)?;
+        }
+    }
 
-    let docs = db.fuzzy_find_documents(pattern, 10)?;
+    let mut contents: Vec<Content> = result
+        .documents
+        .iter()
+        .map(|doc| {
+            let text = if line_numbers {
+                doc.content
+                    .lines()
+                    .enumerate()
+                    .map(|(i, line)| format!("{:>4} {}", i + 1, line))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            } else {
+                doc.content.clone()
+            };
+            Content::Resource {
+                resource: ResourceContent {
+                    uri: format!("agentroot://{}", doc.path),
+                    name: doc.path.clone(),
+                    title: None,
+                    mime_type: "text/markdown".to_string(),
+                    text,
+                },
+            }
+        })
+        .collect();
+    if !result.omitted.is_empty() {
+        contents.push(Content::Text {
+            text: format!("Omitted for lack of budget: {}", result.omitted.join(", ")),
+        });
+    }
 
-    let contents: Vec<Content> = docs
-        .into_iter()
-        .map(|doc| Content::Resource {
-            resource: ResourceContent {
-                uri: doc.filepath,
-                name: doc.display_path,
-                title: Some(doc.title),
-                mime_type: "text/markdown".to_string(),
-                text: doc.body.unwrap_or_default(),
-            },
+    let documents: Vec<Value> = result
+        .documents
+        .iter()
+        .map(|d| {
+            serde_json::json!({
+                "file": d.path,
+                "docid": format!("#{}", d.docid),
+                "importance": d.importance,
+                "original_bytes": d.original_bytes,
+                "bytes": d.content.len(),
+                "truncated": d.truncated,
+                "omitted_sections": d.omitted_sections
+            })
         })
         .collect();
 
     Ok(ToolResult {
         content: contents,
-        structured_content: None,
+        structured_content: Some(serde_json::json!({
+            "budget_bytes": result.budget_bytes,
+            "bytes": result.bytes,
+            "tokens": result.tokens,
+            "documents": documents,
+            "omitted": result.omitted
+        })),
         is_error: None,
     })
 }
@@ -925,12 +1415,40 @@ pub async fn handle_collection_remove(db: &Database, args: Value) -> Result<Tool
 }
 
 pub async fn handle_collection_update(db: &Database, args: Value) -> Result<ToolResult> {
+    handle_collection_update_cancellable(db, args, &NoProgress, &CancellationToken::new()).await
+}
+
+/// `collection_update`, reporting reindex progress to `progress` and
+/// rolling back if `cancel` is triggered before the reindex commits
+pub async fn handle_collection_update_cancellable(
+    db: &Database,
+    args: Value,
+    progress: &dyn ProgressSink,
+    cancel: &CancellationToken,
+) -> Result<ToolResult> {
     let name = args
         .get("name")
         .and_then(|v| v.as_str())
         .ok_or_else(|| anyhow::anyhow!("Missing collection name"))?;
 
-    let updated = db.reindex_collection(name).await?;
+    if args
+        .get("background")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+    {
+        if db.get_collection(name)?.is_none() {
+            anyhow::bail!("Collection not found: {}", name);
+        }
+        let kind = JobKind::Reindex {
+            collection: Some(name.to_string()),
+        };
+        return job_queued_result(db, &kind, 0);
+    }
+
+    let updated = db
+        .reindex_collection_cancellable(name, progress, cancel)
+        .await?
+        .updated;
 
     let summary = format!("Updated collection '{}': {} files changed", name, updated);
 
@@ -944,6 +1462,198 @@ pub async fn handle_collection_update(db: &Database, args: Value) -> Result<Tool
     })
 }
 
+pub fn index_file_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "index_file".to_string(),
+        description:
+            "Add a file or a piece of text to a collection and make it searchable right away"
+                .to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "collection": {
+                    "type": "string",
+                    "description": "Collection to add the document to"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "File to read; give this or content"
+                },
+                "content": {
+                    "type": "string",
+                    "description": "Raw text to index; needs a title"
+                },
+                "title": {
+                    "type": "string",
+                    "description": "Document title (default: taken from the content)"
+                },
+                "name": {
+                    "type": "string",
+                    "description": "Path of the document within the collection; indexing the same name again replaces it"
+                },
+                "embed": {
+                    "type": "boolean",
+                    "description": "Also generate embeddings so vector search finds it",
+                    "default": false
+                }
+            },
+            "required": ["collection"]
+        }),
+    }
+}
+
+pub async fn handle_index_file(db: &Database, args: Value) -> Result<ToolResult> {
+    let collection = args
+        .get("collection")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing collection"))?;
+    let coll = db
+        .get_collection(collection)?
+        .ok_or_else(|| anyhow::anyhow!("Collection not found: {}", collection))?;
+    let title = args.get("title").and_then(|v| v.as_str());
+    let name = args.get("name").and_then(|v| v.as_str());
+
+    let (uri, content) = match (
+        args.get("path").and_then(|v| v.as_str()),
+        args.get("content").and_then(|v| v.as_str()),
+    ) {
+        (Some(path), None) => {
+            let path = std::path::Path::new(path);
+            let content = std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("Cannot read {}: {}", path.display(), e))?;
+            // Files under the collection root keep their relative path
+            let uri = match name {
+                Some(name) => name.to_string(),
+                None => path
+                    .strip_prefix(&coll.path)
+                    .unwrap_or(path)
+                    .to_string_lossy()
+                    .to_string(),
+            };
+            (uri, content)
+        }
+        (None, Some(content)) => {
+            let title =
+                title.ok_or_else(|| anyhow::anyhow!("Indexing raw content needs a title"))?;
+            let uri = name
+                .map(String::from)
+                .unwrap_or_else(|| format!("{}.md", document_name(title)));
+            (uri, content.to_string())
+        }
+        _ => anyhow::bail!("Give either path or content"),
+    };
+
+    let title = title
+        .map(String::from)
+        .unwrap_or_else(|| agentroot_core::index::extract_title(&content, &uri));
+    let hash = agentroot_core::db::hash_content(&content);
+    let item = agentroot_core::providers::SourceItem::new(
+        uri.clone(),
+        title.clone(),
+        content,
+        hash,
+        agentroot_core::db::INGESTED_SOURCE_TYPE.to_string(),
+    );
+    let changed = db.ingest_item(collection, item).await?;
+    let doc = db
+        .find_active_document(collection, &uri)?
+        .ok_or_else(|| anyhow::anyhow!("Document not found after indexing: {}", uri))?;
+    let docid = agentroot_core::db::docid_from_hash(&doc.hash);
+
+    let mut summary = if changed {
+        format!("Indexed {}/{} as #{}", collection, uri, docid)
+    } else {
+        format!("{}/{} is unchanged (#{})", collection, uri, docid)
+    };
+    let mut embedded = false;
+    if args.get("embed").and_then(|v| v.as_bool()).unwrap_or(false) {
+        // The document stays indexed even if embedding fails
+        let result = match agentroot_core::default_embedder().await {
+            Ok(embedder) => {
+                agentroot_core::index::embed_document(db, embedder.as_ref(), &doc.hash, &uri).await
+            }
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(_) => embedded = true,
+            Err(e) => summary.push_str(&format!("; not embedded: {}", e)),
+        }
+    }
+
+    Ok(ToolResult {
+        content: vec![Content::Text { text: summary }],
+        structured_content: Some(serde_json::json!({
+            "collection": collection,
+            "path": uri,
+            "docid": format!("#{}", docid),
+            "title": title,
+            "changed": changed,
+            "embedded": embedded
+        })),
+        is_error: None,
+    })
+}
+
+pub fn delete_document_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "delete_document".to_string(),
+        description: "Remove a single document, with its chunks and embeddings, from the index"
+            .to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "docid": {
+                    "type": "string",
+                    "description": "Document ID (#abc123) or collection/path"
+                }
+            },
+            "required": ["docid"]
+        }),
+    }
+}
+
+pub async fn handle_delete_document(db: &Database, args: Value) -> Result<ToolResult> {
+    let docid = args
+        .get("docid")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing docid"))?;
+
+    let deleted = db.delete_document_by_docid(docid)?;
+
+    Ok(ToolResult {
+        content: vec![Content::Text {
+            text: format!(
+                "Removed #{} {}/{}",
+                deleted.docid, deleted.collection, deleted.path
+            ),
+        }],
+        structured_content: Some(serde_json::json!({
+            "docid": format!("#{}", deleted.docid),
+            "collection": deleted.collection,
+            "path": deleted.path,
+            "title": deleted.title,
+            "removed": true
+        })),
+        is_error: None,
+    })
+}
+
+/// File name for a document indexed from raw text: "Meeting Notes!" becomes
+/// "meeting-notes"
+fn document_name(title: &str) -> String {
+    let name = title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if name.is_empty() {
+        "note".to_string()
+    } else {
+        name
+    }
+}
+
 pub fn metadata_add_tool_definition() -> ToolDefinition {
     ToolDefinition {
         name: "metadata_add".to_string(),
@@ -1130,8 +1840,14 @@ pub fn search_chunks_tool_definition() -> ToolDefinition {
                     "type": "string",
                     "description": "Filter by chunk label (format: key:value, e.g., 'layer:service')"
                 },
+                "exact": exact_param(),
                 "detail": detail_param(),
-                "session_id": session_id_param()
+                "session_id": session_id_param(),
+                "group_by_document": {
+                    "type": "boolean",
+                    "description": "Nest chunk hits under their document with an aggregate score and best snippet; limit then counts documents (default: false)",
+                    "default": false
+                }
             },
             "required": ["query"]
         }),
@@ -1189,7 +1905,7 @@ pub async fn handle_search_chunks(db: &Database, args: Value) -> Result<ToolResu
         .ok_or_else(|| anyhow::anyhow!("Missing query"))?;
 
     let detail = parse_detail(&args);
-    let session_id = parse_session_id(&args);
+    let session_id = parse_session_id(db, &args);
 
     let mut options = SearchOptions {
         limit: args.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize,
@@ -1201,8 +1917,8 @@ pub async fn handle_search_chunks(db: &Database, args: Value) -> Result<ToolResu
         provider: None,
 
         detail,
+        exact: parse_exact(&args),
         session_id: session_id.clone(),
-        metadata_filters: Vec::new(),
         ..Default::default()
     };
 
@@ -1213,46 +1929,52 @@ pub async fn handle_search_chunks(db: &Database, args: Value) -> Result<ToolResu
             .push(("label".to_string(), label.to_string()));
     }
 
+    let group = args
+        .get("group_by_document")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let limit = options.limit;
+    if group {
+        // Leave room for several hits per document
+        options.limit = limit * GROUPED_CHUNKS_PER_DOCUMENT;
+    }
+
     let mut results = db.search_chunks_bm25(query, &options)?;
 
     apply_session_and_project(db, &mut results, detail, session_id.as_deref(), query);
 
-    let summary = format!("Found {} chunk(s) for \"{}\"", results.len(), query);
-    let structured: Vec<Value> = results
-        .iter()
-        .map(|r| {
-            let mut result_json = serde_json::json!({
-                "chunk_hash": r.chunk_hash.as_ref().unwrap_or(&"".to_string()),
-                "file": r.display_path,
-                "breadcrumb": r.chunk_breadcrumb.as_ref().unwrap_or(&"".to_string()),
-                "type": r.chunk_type.as_ref().unwrap_or(&"".to_string()),
-                "lines": format!("{}-{}",
-                    r.chunk_start_line.unwrap_or(0),
-                    r.chunk_end_line.unwrap_or(0)
-                ),
-                "score": (r.score * 100.0).round() / 100.0
-            });
-
-            // Include chunk metadata
-            if let Some(summary) = &r.chunk_summary {
-                result_json["summary"] = Value::String(summary.clone());
-            }
-            if let Some(purpose) = &r.chunk_purpose {
-                result_json["purpose"] = Value::String(purpose.clone());
-            }
-            if !r.chunk_concepts.is_empty() {
-                result_json["concepts"] = serde_json::to_value(&r.chunk_concepts).unwrap();
-            }
-            if !r.chunk_labels.is_empty() {
-                result_json["labels"] = serde_json::to_value(&r.chunk_labels).unwrap();
-            }
-            if let Some(content) = &r.body {
-                result_json["content"] = Value::String(content.clone());
-            }
+    if group {
+        let mut groups = agentroot_core::search::group_by_document(results, query);
+        groups.truncate(limit);
+        let summary = format!(
+            "Found {} document(s) with matching chunks for \"{}\"",
+            groups.len(),
+            query
+        );
+        let structured: Vec<Value> = groups
+            .iter()
+            .map(|g| {
+                serde_json::json!({
+                    "docid": format!("#{}", g.docid),
+                    "file": g.display_path,
+                    "title": g.title,
+                    "score": (g.score * 100.0).round() / 100.0,
+                    "hits": g.chunks.len(),
+                    "best_chunk": g.chunks[g.best].chunk_hash,
+                    "snippet": g.snippet,
+                    "chunks": g.chunks.iter().map(chunk_result_json).collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        return Ok(ToolResult {
+            content: vec![Content::Text { text: summary }],
+            structured_content: Some(serde_json::json!({ "groups": structured })),
+            is_error: None,
+        });
+    }
 
-            result_json
-        })
-        .collect();
+    let summary = format!("Found {} chunk(s) for \"{}\"", results.len(), query);
+    let structured: Vec<Value> = results.iter().map(chunk_result_json).collect();
 
     Ok(ToolResult {
         content: vec![Content::Text { text: summary }],
@@ -1261,6 +1983,42 @@ pub async fn handle_search_chunks(db: &Database, args: Value) -> Result<ToolResu
     })
 }
 
+/// Chunks fetched per requested document when grouping chunk hits
+const GROUPED_CHUNKS_PER_DOCUMENT: usize = 5;
+
+fn chunk_result_json(r: &agentroot_core::SearchResult) -> Value {
+    let mut result_json = serde_json::json!({
+        "chunk_hash": r.chunk_hash.as_ref().unwrap_or(&"".to_string()),
+        "file": r.display_path,
+        "breadcrumb": r.chunk_breadcrumb.as_ref().unwrap_or(&"".to_string()),
+        "type": r.chunk_type.as_ref().unwrap_or(&"".to_string()),
+        "lines": format!("{}-{}",
+            r.chunk_start_line.unwrap_or(0),
+            r.chunk_end_line.unwrap_or(0)
+        ),
+        "score": (r.score * 100.0).round() / 100.0
+    });
+
+    // Include chunk metadata
+    if let Some(summary) = &r.chunk_summary {
+        result_json["summary"] = Value::String(summary.clone());
+    }
+    if let Some(purpose) = &r.chunk_purpose {
+        result_json["purpose"] = Value::String(purpose.clone());
+    }
+    if !r.chunk_concepts.is_empty() {
+        result_json["concepts"] = serde_json::to_value(&r.chunk_concepts).unwrap();
+    }
+    if !r.chunk_labels.is_empty() {
+        result_json["labels"] = serde_json::to_value(&r.chunk_labels).unwrap();
+    }
+    if let Some(content) = &r.body {
+        result_json["content"] = Value::String(content.clone());
+    }
+
+    result_json
+}
+
 pub async fn handle_get_chunk(db: &Database, args: Value) -> Result<ToolResult> {
     let chunk_hash = args
         .get("chunk_hash")
@@ -1400,923 +2158,2447 @@ pub async fn handle_navigate_chunks(db: &Database, args: Value) -> Result<ToolRe
     })
 }
 
-pub async fn handle_metadata_query(db: &Database, args: Value) -> Result<ToolResult> {
-    use agentroot_core::MetadataFilter;
-
-    let field = args
-        .get("field")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Missing field"))?
-        .to_string();
+pub fn find_definition_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "find_definition".to_string(),
+        description: "Find where a function, method, struct or class is defined, by symbol name (more precise than text search for code navigation)".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Symbol name, bare (\"parse\") or qualified (\"Parser::parse\")"
+                },
+                "collection": {
+                    "type": "string",
+                    "description": "Filter by collection name"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum definitions (default: 10)",
+                    "default": 10
+                }
+            },
+            "required": ["name"]
+        }),
+    }
+}
 
-    let operator = args
-        .get("operator")
+pub async fn handle_find_definition(db: &Database, args: Value) -> Result<ToolResult> {
+    let name = args
+        .get("name")
         .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Missing operator"))?;
-
-    let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
-
-    let filter = match operator {
-        "exists" => MetadataFilter::Exists(field),
-        _ => {
-            let value = args
-                .get("value")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| anyhow::anyhow!("Missing value for operator"))?;
+        .ok_or_else(|| anyhow::anyhow!("Missing name"))?;
+    let collection = args.get("collection").and_then(|v| v.as_str());
+    let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(10) as usize;
 
-            match operator {
-                "eq" => MetadataFilter::TextEq(field, value.to_string()),
-                "contains" => MetadataFilter::TextContains(field, value.to_string()),
-                "gt" => {
-                    if let Ok(num) = value.parse::<i64>() {
-                        MetadataFilter::IntegerGt(field, num)
-                    } else if let Ok(num) = value.parse::<f64>() {
-                        MetadataFilter::FloatGt(field, num)
-                    } else {
-                        return Err(anyhow::anyhow!("Invalid numeric value for gt"));
-                    }
-                }
-                "lt" => {
-                    if let Ok(num) = value.parse::<i64>() {
-                        MetadataFilter::IntegerLt(field, num)
-                    } else if let Ok(num) = value.parse::<f64>() {
-                        MetadataFilter::FloatLt(field, num)
-                    } else {
-                        return Err(anyhow::anyhow!("Invalid numeric value for lt"));
-                    }
-                }
-                "has" => MetadataFilter::TagsContain(field, value.to_string()),
-                _ => return Err(anyhow::anyhow!("Invalid operator")),
-            }
-        }
-    };
+    let definitions = db.find_definitions(name, collection, limit)?;
 
-    let docids = db.find_by_metadata(&filter, limit)?;
+    let mut summary = format!("Found {} definitions of '{}'", definitions.len(), name);
+    for d in &definitions {
+        summary.push_str(&format!(
+            "\n  {}  {}/{}:{}-{}",
+            d.name, d.collection, d.path, d.start_line, d.end_line
+        ));
+    }
 
-    let summary = if docids.is_empty() {
-        "No documents found matching filter".to_string()
-    } else {
-        format!("Found {} document(s) matching filter", docids.len())
-    };
+    let structured: Vec<Value> = definitions
+        .iter()
+        .map(|d| {
+            serde_json::json!({
+                "name": d.name,
+                "docid": format!("#{}", d.docid),
+                "file": format!("{}/{}", d.collection, d.path),
+                "chunk_hash": d.chunk_hash,
+                "type": d.chunk_type,
+                "language": d.language,
+                "lines": format!("{}-{}", d.start_line, d.end_line),
+                "content": d.content
+            })
+        })
+        .collect();
 
     Ok(ToolResult {
-        content: vec![Content::Text {
-            text: format!("{}\n{}", summary, docids.join("\n")),
-        }],
+        content: vec![Content::Text { text: summary }],
         structured_content: Some(serde_json::json!({
-            "count": docids.len(),
-            "documents": docids
+            "name": name,
+            "count": structured.len(),
+            "definitions": structured
         })),
         is_error: None,
     })
 }
 
-// ============================================================================
-// Session Tools
-// ============================================================================
-
-pub fn session_start_tool_definition() -> ToolDefinition {
+pub fn find_references_tool_definition() -> ToolDefinition {
     ToolDefinition {
-        name: "session_start".to_string(),
-        description: "Start a new search session for multi-turn context tracking. Returns a session_id to pass to subsequent search calls.".to_string(),
+        name: "find_references".to_string(),
+        description: "Find code that calls a function or method or names a type (who calls X), from the symbol cross-reference index".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "ttl_seconds": {
-                    "type": "integer",
-                    "description": "Session time-to-live in seconds (default: 3600)",
-                    "default": 3600
-                }
-            }
-        }),
-    }
-}
-
-pub fn session_get_tool_definition() -> ToolDefinition {
-    ToolDefinition {
-        name: "session_get".to_string(),
-        description: "Get session context, query history, and seen document count.".to_string(),
-        input_schema: serde_json::json!({
-            "type": "object",
-            "properties": {
-                "session_id": {
-                    "type": "string",
-                    "description": "Session ID from session_start"
-                }
-            },
-            "required": ["session_id"]
-        }),
-    }
-}
-
-pub fn session_set_tool_definition() -> ToolDefinition {
-    ToolDefinition {
-        name: "session_set".to_string(),
-        description: "Set a key-value pair on the session context.".to_string(),
-        input_schema: serde_json::json!({
-            "type": "object",
-            "properties": {
-                "session_id": {
+                "name": {
                     "type": "string",
-                    "description": "Session ID from session_start"
+                    "description": "Symbol name; qualified names (\"Parser::parse\") match on their last segment"
                 },
-                "key": {
+                "kind": {
                     "type": "string",
-                    "description": "Context key to set"
+                    "enum": ["call", "type"],
+                    "description": "Only calls or only type uses (default: both)"
                 },
-                "value": {
-                    "type": "string",
-                    "description": "Context value to set"
-                }
-            },
-            "required": ["session_id", "key", "value"]
-        }),
-    }
-}
-
-pub fn session_end_tool_definition() -> ToolDefinition {
-    ToolDefinition {
-        name: "session_end".to_string(),
-        description: "End a search session and clean up resources.".to_string(),
-        input_schema: serde_json::json!({
-            "type": "object",
-            "properties": {
-                "session_id": {
+                "collection": {
                     "type": "string",
-                    "description": "Session ID to end"
+                    "description": "Filter by collection name"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum references (default: 50)",
+                    "default": 50
                 }
             },
-            "required": ["session_id"]
+            "required": ["name"]
         }),
     }
 }
 
-pub async fn handle_session_start(db: &Database, args: Value) -> Result<ToolResult> {
-    let ttl = args
-        .get("ttl_seconds")
-        .and_then(|v| v.as_i64())
-        .or(Some(3600));
-
-    let session_id = db.create_session(ttl)?;
-
-    Ok(ToolResult {
-        content: vec![Content::Text {
-            text: format!("Session started: {}", session_id),
-        }],
-        structured_content: Some(serde_json::json!({
-            "session_id": session_id,
-            "ttl_seconds": ttl
-        })),
-        is_error: None,
-    })
-}
-
-pub async fn handle_session_get(db: &Database, args: Value) -> Result<ToolResult> {
-    let session_id = args
-        .get("session_id")
+pub async fn handle_find_references(db: &Database, args: Value) -> Result<ToolResult> {
+    let name = args
+        .get("name")
         .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Missing session_id"))?;
-
-    let session = db
-        .get_session(session_id)?
-        .ok_or_else(|| anyhow::anyhow!("Session not found: {}", session_id))?;
-    let queries = db.get_session_queries(session_id)?;
-    let seen = db.get_seen_hashes(session_id)?;
+        .ok_or_else(|| anyhow::anyhow!("Missing name"))?;
+    let kind = match args.get("kind").and_then(|v| v.as_str()) {
+        Some(kind) => Some(
+            agentroot_core::ReferenceKind::parse(kind)
+                .ok_or_else(|| anyhow::anyhow!("Invalid kind '{}': use call or type", kind))?,
+        ),
+        None => None,
+    };
+    let collection = args.get("collection").and_then(|v| v.as_str());
+    let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(50) as usize;
+
+    let usages = db.find_references(name, kind, collection, limit)?;
+
+    let mut summary = format!("Found {} references to '{}'", usages.len(), name);
+    for u in &usages {
+        summary.push_str(&format!(
+            "\n  {}/{}:{}  {}  {}",
+            u.collection,
+            u.path,
+            u.line,
+            u.caller.as_deref().unwrap_or("-"),
+            u.line_text
+        ));
+    }
 
-    let queries_json: Vec<Value> = queries
+    let structured: Vec<Value> = usages
         .iter()
-        .map(|q| {
+        .map(|u| {
             serde_json::json!({
-                "query": q.query,
-                "result_count": q.result_count,
-                "created_at": q.created_at
+                "name": u.name,
+                "kind": u.kind.as_str(),
+                "caller": u.caller,
+                "docid": format!("#{}", u.docid),
+                "file": format!("{}/{}", u.collection, u.path),
+                "line": u.line,
+                "line_text": u.line_text,
+                "chunk_hash": u.chunk_hash,
+                "language": u.language
             })
         })
         .collect();
 
     Ok(ToolResult {
-        content: vec![Content::Text {
-            text: format!(
-                "Session {}: {} queries, {} seen documents",
-                session_id,
-                queries.len(),
-                seen.len()
-            ),
-        }],
-        structured_content: Some(serde_json::json!({
-            "session_id": session_id,
-            "created_at": session.created_at,
-            "last_active_at": session.last_active_at,
-            "ttl_seconds": session.ttl_seconds,
-            "context": session.context,
-            "queries": queries_json,
-            "seen_count": seen.len()
-        })),
-        is_error: None,
-    })
-}
-
-pub async fn handle_session_set(db: &Database, args: Value) -> Result<ToolResult> {
-    let session_id = args
-        .get("session_id")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Missing session_id"))?;
-    let key = args
-        .get("key")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Missing key"))?;
-    let value = args
-        .get("value")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Missing value"))?;
-
-    db.set_session_context(session_id, key, value)?;
-
-    Ok(ToolResult {
-        content: vec![Content::Text {
-            text: format!("Set {}={} on session {}", key, value, session_id),
-        }],
-        structured_content: Some(serde_json::json!({
-            "session_id": session_id,
-            "key": key,
-            "value": value
-        })),
-        is_error: None,
-    })
-}
-
-pub async fn handle_session_end(db: &Database, args: Value) -> Result<ToolResult> {
-    let session_id = args
-        .get("session_id")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Missing session_id"))?;
-
-    db.delete_session(session_id)?;
-
-    Ok(ToolResult {
-        content: vec![Content::Text {
-            text: format!("Session ended: {}", session_id),
-        }],
+        content: vec![Content::Text { text: summary }],
         structured_content: Some(serde_json::json!({
-            "session_id": session_id,
-            "ended": true
+            "name": name,
+            "count": structured.len(),
+            "references": structured
         })),
         is_error: None,
     })
 }
 
-// ============================================================================
-// Directory Browsing Tools
-// ============================================================================
-
-pub fn browse_directory_tool_definition() -> ToolDefinition {
+pub fn regex_search_tool_definition() -> ToolDefinition {
     ToolDefinition {
-        name: "browse_directory".to_string(),
-        description: "Browse the directory structure of indexed collections. Shows files, subdirectories, and metadata for a given path.".to_string(),
+        name: "regex_search".to_string(),
+        description: "Grep document bodies with a regular expression and get matching lines with line numbers, like ripgrep over the index. Use for exact patterns (identifiers, error codes, TODOs) that ranked search may miss".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
+                "pattern": {
+                    "type": "string",
+                    "description": "Regular expression (Rust regex syntax)"
+                },
                 "collection": {
                     "type": "string",
-                    "description": "Collection name to browse"
+                    "description": "Filter by collection name"
                 },
-                "path": {
+                "glob": {
                     "type": "string",
-                    "description": "Directory path within the collection (empty for root)"
+                    "description": "Only documents whose path matches this glob (\"src/**/*.rs\")"
                 },
-                "max_depth": {
+                "ignore_case": {
+                    "type": "boolean",
+                    "description": "Match case-insensitively",
+                    "default": false
+                },
+                "context": {
                     "type": "integer",
-                    "description": "Maximum depth of subdirectories to return (default: 2)",
-                    "default": 2
+                    "description": "Lines of context around each match (default: 0)",
+                    "default": 0
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum matching lines (default: 100)",
+                    "default": 100
                 }
             },
-            "required": ["collection"]
+            "required": ["pattern"]
         }),
     }
 }
 
-pub fn search_directories_tool_definition() -> ToolDefinition {
+pub async fn handle_regex_search(db: &Database, args: Value) -> Result<ToolResult> {
+    let pattern = args
+        .get("pattern")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing pattern"))?;
+    let options = agentroot_core::db::RegexSearchOptions {
+        collection: args
+            .get("collection")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        path_glob: args.get("glob").and_then(|v| v.as_str()).map(String::from),
+        ignore_case: args
+            .get("ignore_case")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+        context: args.get("context").and_then(|v| v.as_u64()).unwrap_or(0) as usize,
+        limit: args.get("limit").and_then(|v| v.as_u64()).unwrap_or(100) as usize,
+    };
+
+    let found = db.regex_search(pattern, &options)?;
+
+    let mut summary = format!("Found {} lines matching /{}/", found.len(), pattern);
+    for m in &found {
+        summary.push_str(&format!(
+            "\n  {}/{}:{}:{}  {}",
+            m.collection, m.path, m.line, m.column, m.text
+        ));
+    }
+
+    let structured: Vec<Value> = found
+        .iter()
+        .map(|m| {
+            serde_json::json!({
+                "docid": format!("#{}", m.docid),
+                "file": format!("{}/{}", m.collection, m.path),
+                "line": m.line,
+                "column": m.column,
+                "text": m.text,
+                "matches": m.matches,
+                "before": m.before,
+                "after": m.after,
+                "chunk_hash": m.chunk_hash,
+                "breadcrumb": m.breadcrumb
+            })
+        })
+        .collect();
+
+    Ok(ToolResult {
+        content: vec![Content::Text { text: summary }],
+        structured_content: Some(serde_json::json!({
+            "pattern": pattern,
+            "count": structured.len(),
+            "matches": structured
+        })),
+        is_error: None,
+    })
+}
+
+pub fn build_context_tool_definition() -> ToolDefinition {
     ToolDefinition {
-        name: "search_directories".to_string(),
-        description: "Search directories by name, concepts, or content using full-text search."
-            .to_string(),
+        name: "build_context".to_string(),
+        description: "Build a ready-to-paste context block for a question: retrieves relevant chunks, drops duplicates and overlaps, orders them by document and line, and trims to a token budget. Each chunk carries a [n] path:start-end citation.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
                 "query": {
                     "type": "string",
-                    "description": "Search query for directories"
+                    "description": "What the context is for (keywords or a question)"
+                },
+                "token_budget": {
+                    "type": "integer",
+                    "description": "Maximum estimated tokens of the context block (default: 4000)",
+                    "default": 4000
                 },
                 "collection": {
                     "type": "string",
                     "description": "Filter by collection name"
                 },
-                "limit": {
-                    "type": "integer",
-                    "description": "Maximum results (default: 10)",
-                    "default": 10
-                }
+                "minScore": {
+                    "type": "number",
+                    "description": "Minimum relevance score 0-1 (default: 0)",
+                    "default": 0
+                },
+                "exact": exact_param()
             },
             "required": ["query"]
         }),
     }
 }
 
-pub async fn handle_browse_directory(db: &Database, args: Value) -> Result<ToolResult> {
-    let collection = args
-        .get("collection")
+pub async fn handle_build_context(db: &Database, args: Value) -> Result<ToolResult> {
+    use agentroot_core::search::context_pack::DEFAULT_TOKEN_BUDGET;
+
+    let query = args
+        .get("query")
         .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Missing collection"))?;
+        .ok_or_else(|| anyhow::anyhow!("Missing query"))?;
+    let token_budget = args
+        .get("token_budget")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as usize)
+        .unwrap_or(DEFAULT_TOKEN_BUDGET);
 
-    let path = args.get("path").and_then(|v| v.as_str());
-    let max_depth = args.get("max_depth").and_then(|v| v.as_u64()).unwrap_or(2) as usize;
+    let options = SearchOptions {
+        min_score: args.get("minScore").and_then(|v| v.as_f64()).unwrap_or(0.0),
+        collection: args
+            .get("collection")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        exact: parse_exact(&args),
+        ..Default::default()
+    };
 
-    let dirs = db.list_directories(collection, path, Some(max_depth))?;
+    let pack = agentroot_core::build_context(db, query, token_budget, &options)?;
 
-    let summary = format!(
-        "Found {} directories in {}/{}",
-        dirs.len(),
-        collection,
-        path.unwrap_or("")
-    );
-
-    let structured: Vec<Value> = dirs
+    let text = if pack.chunks.is_empty() {
+        format!("No context found for \"{}\"", query)
+    } else {
+        pack.text.clone()
+    };
+    let citations: Vec<Value> = pack
+        .chunks
         .iter()
-        .map(|d| {
-            let mut dir_json = serde_json::json!({
-                "path": d.path,
-                "depth": d.depth,
-                "file_count": d.file_count,
-                "child_dir_count": d.child_dir_count
-            });
-            if let Some(lang) = &d.dominant_language {
-                dir_json["language"] = Value::String(lang.clone());
-            }
-            if let Some(cat) = &d.dominant_category {
-                dir_json["category"] = Value::String(cat.clone());
-            }
-            if let Some(summary) = &d.summary {
-                dir_json["summary"] = Value::String(summary.clone());
-            }
-            if !d.concepts.is_empty() {
-                dir_json["concepts"] = serde_json::to_value(&d.concepts).unwrap();
-            }
-            dir_json
+        .map(|c| {
+            serde_json::json!({
+                "citation": c.citation,
+                "docid": format!("#{}", c.docid),
+                "file": c.path,
+                "lines": format!("{}-{}", c.start_line, c.end_line),
+                "breadcrumb": c.breadcrumb,
+                "chunk_hash": c.chunk_hash,
+                "score": (c.score * 100.0).round() / 100.0,
+                "tokens": c.tokens,
+                "truncated": c.truncated
+            })
         })
         .collect();
 
     Ok(ToolResult {
-        content: vec![Content::Text { text: summary }],
-        structured_content: Some(serde_json::json!({ "directories": structured })),
+        content: vec![Content::Text { text }],
+        structured_content: Some(serde_json::json!({
+            "query": query,
+            "token_budget": pack.token_budget,
+            "tokens": pack.tokens,
+            "omitted": pack.omitted,
+            "citations": citations
+        })),
         is_error: None,
     })
 }
 
-pub async fn handle_search_directories(db: &Database, args: Value) -> Result<ToolResult> {
+pub fn answer_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "answer".to_string(),
+        description: "Answer a question from the indexed documents: retrieves chunks with hybrid search (BM25 only when no embeddings are available), packs the best into a token budget and asks the configured LLM for a grounded answer citing them as [n]. Returns the answer and its sources.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "Question to answer"
+                },
+                "token_budget": {
+                    "type": "integer",
+                    "description": "Maximum estimated tokens of context given to the LLM (default: 4000)",
+                    "default": 4000
+                },
+                "collection": {
+                    "type": "string",
+                    "description": "Filter by collection name"
+                },
+                "minScore": {
+                    "type": "number",
+                    "description": "Minimum BM25 relevance score 0-1 of retrieved chunks (default: 0)",
+                    "default": 0
+                },
+                "exact": exact_param()
+            },
+            "required": ["query"]
+        }),
+    }
+}
+
+pub async fn handle_answer(db: &Database, args: Value) -> Result<ToolResult> {
+    use agentroot_core::llm::AnswerGenerator;
+    use agentroot_core::search::context_pack::DEFAULT_TOKEN_BUDGET;
+
     let query = args
         .get("query")
         .and_then(|v| v.as_str())
         .ok_or_else(|| anyhow::anyhow!("Missing query"))?;
+    let token_budget = args
+        .get("token_budget")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as usize)
+        .unwrap_or(DEFAULT_TOKEN_BUDGET);
 
-    let collection = args.get("collection").and_then(|v| v.as_str());
-    let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(10) as usize;
+    let options = SearchOptions {
+        min_score: args.get("minScore").and_then(|v| v.as_f64()).unwrap_or(0.0),
+        collection: args
+            .get("collection")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        exact: parse_exact(&args),
+        ..Default::default()
+    };
 
-    let dirs = db.search_directories_fts(query, collection, limit)?;
+    let generator = AnswerGenerator::from_env()?;
 
-    let summary = format!("Found {} directories matching \"{}\"", dirs.len(), query);
+    // Hybrid retrieval when there are embeddings to search, BM25 otherwise
+    let embedder = if db.has_vector_index() {
+        agentroot_core::default_embedder().await.ok()
+    } else {
+        None
+    };
+    let pack = match embedder {
+        Some(embedder) => {
+            agentroot_core::build_hybrid_context(
+                db,
+                query,
+                token_budget,
+                &options,
+                embedder.as_ref(),
+            )
+            .await?
+        }
+        None => agentroot_core::build_context(db, query, token_budget, &options)?,
+    };
 
-    let structured: Vec<Value> = dirs
+    let answer = generator.answer(query, &pack).await?;
+
+    let mut text = answer.answer.clone();
+    if !answer.sources.is_empty() {
+        text.push_str("\n\nSources:\n");
+        for source in &answer.sources {
+            text.push_str(&format!(
+                "[{}] #{} {}:{}-{}\n",
+                source.citation, source.docid, source.path, source.start_line, source.end_line
+            ));
+        }
+    }
+    let sources: Vec<Value> = answer
+        .sources
         .iter()
-        .map(|d| {
-            let mut dir_json = serde_json::json!({
-                "path": d.path,
-                "collection": d.collection,
-                "file_count": d.file_count,
-                "child_dir_count": d.child_dir_count
-            });
-            if let Some(lang) = &d.dominant_language {
-                dir_json["language"] = Value::String(lang.clone());
-            }
-            if let Some(cat) = &d.dominant_category {
-                dir_json["category"] = Value::String(cat.clone());
+        .map(|c| {
+            serde_json::json!({
+                "citation": c.citation,
+                "docid": format!("#{}", c.docid),
+                "file": c.path,
+                "lines": format!("{}-{}", c.start_line, c.end_line),
+                "breadcrumb": c.breadcrumb,
+                "chunk_hash": c.chunk_hash,
+                "score": (c.score * 100.0).round() / 100.0,
+                "cited": answer.cited.contains(&c.citation)
+            })
+        })
+        .collect();
+
+    Ok(ToolResult {
+        content: vec![Content::Text { text }],
+        structured_content: Some(serde_json::json!({
+            "query": query,
+            "answer": answer.answer,
+            "tokens": pack.tokens,
+            "omitted": pack.omitted,
+            "sources": sources
+        })),
+        is_error: None,
+    })
+}
+
+pub async fn handle_metadata_query(db: &Database, args: Value) -> Result<ToolResult> {
+    use agentroot_core::MetadataFilter;
+
+    let field = args
+        .get("field")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing field"))?
+        .to_string();
+
+    let operator = args
+        .get("operator")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing operator"))?;
+
+    let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
+
+    let filter = match operator {
+        "exists" => MetadataFilter::Exists(field),
+        _ => {
+            let value = args
+                .get("value")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| anyhow::anyhow!("Missing value for operator"))?;
+
+            match operator {
+                "eq" => MetadataFilter::TextEq(field, value.to_string()),
+                "contains" => MetadataFilter::TextContains(field, value.to_string()),
+                "gt" => {
+                    if let Ok(num) = value.parse::<i64>() {
+                        MetadataFilter::IntegerGt(field, num)
+                    } else if let Ok(num) = value.parse::<f64>() {
+                        MetadataFilter::FloatGt(field, num)
+                    } else {
+                        return Err(anyhow::anyhow!("Invalid numeric value for gt"));
+                    }
+                }
+                "lt" => {
+                    if let Ok(num) = value.parse::<i64>() {
+                        MetadataFilter::IntegerLt(field, num)
+                    } else if let Ok(num) = value.parse::<f64>() {
+                        MetadataFilter::FloatLt(field, num)
+                    } else {
+                        return Err(anyhow::anyhow!("Invalid numeric value for lt"));
+                    }
+                }
+                "has" => MetadataFilter::TagsContain(field, value.to_string()),
+                _ => return Err(anyhow::anyhow!("Invalid operator")),
             }
-            if let Some(summary) = &d.summary {
-                dir_json["summary"] = Value::String(summary.clone());
+        }
+    };
+
+    let docids = db.find_by_metadata(&filter, limit)?;
+
+    let summary = if docids.is_empty() {
+        "No documents found matching filter".to_string()
+    } else {
+        format!("Found {} document(s) matching filter", docids.len())
+    };
+
+    Ok(ToolResult {
+        content: vec![Content::Text {
+            text: format!("{}\n{}", summary, docids.join("\n")),
+        }],
+        structured_content: Some(serde_json::json!({
+            "count": docids.len(),
+            "documents": docids
+        })),
+        is_error: None,
+    })
+}
+
+// ============================================================================
+// Session Tools
+// ============================================================================
+
+pub fn session_start_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "session_start".to_string(),
+        description: "Start a new search session for multi-turn context tracking. Returns a session_id to pass to subsequent search calls. Name a session to resume it later with session_resume.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Optional unique session name for resuming across restarts"
+                },
+                "ttl_seconds": {
+                    "type": "integer",
+                    "description": "Session time-to-live in seconds (default: 3600, 0 = never expire)",
+                    "default": 3600
+                }
             }
-            if !d.concepts.is_empty() {
-                dir_json["concepts"] = serde_json::to_value(&d.concepts).unwrap();
+        }),
+    }
+}
+
+pub fn session_get_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "session_get".to_string(),
+        description: "Get session context, query history, and seen document count.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "Session ID from session_start, or session name"
+                }
+            },
+            "required": ["session_id"]
+        }),
+    }
+}
+
+pub fn session_set_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "session_set".to_string(),
+        description: "Set a key-value pair on the session context.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "Session ID from session_start"
+                },
+                "key": {
+                    "type": "string",
+                    "description": "Context key to set"
+                },
+                "value": {
+                    "type": "string",
+                    "description": "Context value to set"
+                }
+            },
+            "required": ["session_id", "key", "value"]
+        }),
+    }
+}
+
+pub fn session_end_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "session_end".to_string(),
+        description: "End a search session and clean up resources.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "Session ID or name to end"
+                }
+            },
+            "required": ["session_id"]
+        }),
+    }
+}
+
+pub fn session_list_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "session_list".to_string(),
+        description: "List live search sessions, most recently active first.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {}
+        }),
+    }
+}
+
+pub fn session_resume_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "session_resume".to_string(),
+        description:
+            "Resume a session by ID or name. Returns its session_id, context, and recent queries."
+                .to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session": {
+                    "type": "string",
+                    "description": "Session ID or name"
+                }
+            },
+            "required": ["session"]
+        }),
+    }
+}
+
+fn session_summary_json(session: &agentroot_core::db::SessionInfo) -> Value {
+    serde_json::json!({
+        "session_id": session.id,
+        "name": session.name,
+        "created_at": session.created_at,
+        "last_active_at": session.last_active_at,
+        "ttl_seconds": session.ttl_seconds
+    })
+}
+
+pub async fn handle_session_start(db: &Database, args: Value) -> Result<ToolResult> {
+    let ttl = args
+        .get("ttl_seconds")
+        .and_then(|v| v.as_i64())
+        .or(Some(3600));
+    let name = args.get("name").and_then(|v| v.as_str());
+
+    let session_id = db.create_named_session(name, ttl)?;
+
+    Ok(ToolResult {
+        content: vec![Content::Text {
+            text: format!("Session started: {}", session_id),
+        }],
+        structured_content: Some(serde_json::json!({
+            "session_id": session_id,
+            "name": name,
+            "ttl_seconds": ttl
+        })),
+        is_error: None,
+    })
+}
+
+pub async fn handle_session_get(db: &Database, args: Value) -> Result<ToolResult> {
+    let session_id = args
+        .get("session_id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing session_id"))?;
+
+    let session = db
+        .find_session(session_id)?
+        .ok_or_else(|| anyhow::anyhow!("Session not found: {}", session_id))?;
+    let session_id = session.id.as_str();
+    let queries = db.get_session_queries(session_id)?;
+    let seen = db.get_seen_hashes(session_id)?;
+
+    let queries_json: Vec<Value> = queries
+        .iter()
+        .map(|q| {
+            serde_json::json!({
+                "query": q.query,
+                "result_count": q.result_count,
+                "created_at": q.created_at
+            })
+        })
+        .collect();
+
+    Ok(ToolResult {
+        content: vec![Content::Text {
+            text: format!(
+                "Session {}: {} queries, {} seen documents",
+                session_id,
+                queries.len(),
+                seen.len()
+            ),
+        }],
+        structured_content: Some(serde_json::json!({
+            "session_id": session_id,
+            "name": session.name,
+            "created_at": session.created_at,
+            "last_active_at": session.last_active_at,
+            "ttl_seconds": session.ttl_seconds,
+            "context": session.context,
+            "queries": queries_json,
+            "seen_count": seen.len()
+        })),
+        is_error: None,
+    })
+}
+
+pub async fn handle_session_set(db: &Database, args: Value) -> Result<ToolResult> {
+    let session_id = args
+        .get("session_id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing session_id"))?;
+    let key = args
+        .get("key")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing key"))?;
+    let value = args
+        .get("value")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing value"))?;
+    let session_id = db
+        .find_session(session_id)?
+        .ok_or_else(|| anyhow::anyhow!("Session not found: {}", session_id))?
+        .id;
+    let session_id = session_id.as_str();
+
+    db.set_session_context(session_id, key, value)?;
+
+    Ok(ToolResult {
+        content: vec![Content::Text {
+            text: format!("Set {}={} on session {}", key, value, session_id),
+        }],
+        structured_content: Some(serde_json::json!({
+            "session_id": session_id,
+            "key": key,
+            "value": value
+        })),
+        is_error: None,
+    })
+}
+
+pub async fn handle_session_end(db: &Database, args: Value) -> Result<ToolResult> {
+    let session_id = args
+        .get("session_id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing session_id"))?;
+    let session_id = db
+        .find_session(session_id)?
+        .map(|s| s.id)
+        .unwrap_or_else(|| session_id.to_string());
+
+    db.delete_session(&session_id)?;
+
+    Ok(ToolResult {
+        content: vec![Content::Text {
+            text: format!("Session ended: {}", session_id),
+        }],
+        structured_content: Some(serde_json::json!({
+            "session_id": session_id,
+            "ended": true
+        })),
+        is_error: None,
+    })
+}
+
+pub async fn handle_session_list(db: &Database, _args: Value) -> Result<ToolResult> {
+    let sessions = db.list_sessions()?;
+
+    let text = if sessions.is_empty() {
+        "No active sessions".to_string()
+    } else {
+        sessions
+            .iter()
+            .map(|s| match &s.name {
+                Some(name) => format!("{} ({}), last active {}", name, s.id, s.last_active_at),
+                None => format!("{}, last active {}", s.id, s.last_active_at),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let sessions_json: Vec<Value> = sessions.iter().map(session_summary_json).collect();
+
+    Ok(ToolResult {
+        content: vec![Content::Text { text }],
+        structured_content: Some(serde_json::json!({ "sessions": sessions_json })),
+        is_error: None,
+    })
+}
+
+pub async fn handle_session_resume(db: &Database, args: Value) -> Result<ToolResult> {
+    let session = args
+        .get("session")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing session"))?;
+
+    let info = db.resume_session(session)?;
+    let queries = db.get_session_queries(&info.id)?;
+
+    let recent: Vec<Value> = queries
+        .iter()
+        .rev()
+        .take(10)
+        .map(|q| {
+            serde_json::json!({
+                "query": q.query,
+                "result_count": q.result_count,
+                "created_at": q.created_at
+            })
+        })
+        .collect();
+
+    let mut structured = session_summary_json(&info);
+    structured["context"] = serde_json::to_value(&info.context)?;
+    structured["recent_queries"] = Value::Array(recent);
+
+    Ok(ToolResult {
+        content: vec![Content::Text {
+            text: format!(
+                "Resumed session {} ({} queries so far)",
+                info.id,
+                queries.len()
+            ),
+        }],
+        structured_content: Some(structured),
+        is_error: None,
+    })
+}
+
+// ============================================================================
+// Directory Browsing Tools
+// ============================================================================
+
+pub fn browse_directory_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "browse_directory".to_string(),
+        description: "Browse the directory structure of indexed collections. Shows files, subdirectories, and metadata for a given path.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "collection": {
+                    "type": "string",
+                    "description": "Collection name to browse"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Directory path within the collection (empty for root)"
+                },
+                "max_depth": {
+                    "type": "integer",
+                    "description": "Maximum depth of subdirectories to return (default: 2)",
+                    "default": 2
+                }
+            },
+            "required": ["collection"]
+        }),
+    }
+}
+
+pub fn search_directories_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "search_directories".to_string(),
+        description: "Search directories by name, concepts, or content using full-text search."
+            .to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "Search query for directories"
+                },
+                "collection": {
+                    "type": "string",
+                    "description": "Filter by collection name"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum results (default: 10)",
+                    "default": 10
+                }
+            },
+            "required": ["query"]
+        }),
+    }
+}
+
+pub async fn handle_browse_directory(db: &Database, args: Value) -> Result<ToolResult> {
+    let collection = args
+        .get("collection")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing collection"))?;
+
+    let path = args.get("path").and_then(|v| v.as_str());
+    let max_depth = args.get("max_depth").and_then(|v| v.as_u64()).unwrap_or(2) as usize;
+
+    let dirs = db.list_directories(collection, path, Some(max_depth))?;
+
+    let summary = format!(
+        "Found {} directories in {}/{}",
+        dirs.len(),
+        collection,
+        path.unwrap_or("")
+    );
+
+    let structured: Vec<Value> = dirs
+        .iter()
+        .map(|d| {
+            let mut dir_json = serde_json::json!({
+                "path": d.path,
+                "depth": d.depth,
+                "file_count": d.file_count,
+                "child_dir_count": d.child_dir_count
+            });
+            if let Some(lang) = &d.dominant_language {
+                dir_json["language"] = Value::String(lang.clone());
+            }
+            if let Some(cat) = &d.dominant_category {
+                dir_json["category"] = Value::String(cat.clone());
+            }
+            if let Some(summary) = &d.summary {
+                dir_json["summary"] = Value::String(summary.clone());
+            }
+            if !d.concepts.is_empty() {
+                dir_json["concepts"] = serde_json::to_value(&d.concepts).unwrap();
+            }
+            dir_json
+        })
+        .collect();
+
+    Ok(ToolResult {
+        content: vec![Content::Text { text: summary }],
+        structured_content: Some(serde_json::json!({ "directories": structured })),
+        is_error: None,
+    })
+}
+
+pub async fn handle_search_directories(db: &Database, args: Value) -> Result<ToolResult> {
+    let query = args
+        .get("query")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing query"))?;
+
+    let collection = args.get("collection").and_then(|v| v.as_str());
+    let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(10) as usize;
+
+    let dirs = db.search_directories_fts(query, collection, limit)?;
+
+    let summary = format!("Found {} directories matching \"{}\"", dirs.len(), query);
+
+    let structured: Vec<Value> = dirs
+        .iter()
+        .map(|d| {
+            let mut dir_json = serde_json::json!({
+                "path": d.path,
+                "collection": d.collection,
+                "file_count": d.file_count,
+                "child_dir_count": d.child_dir_count
+            });
+            if let Some(lang) = &d.dominant_language {
+                dir_json["language"] = Value::String(lang.clone());
+            }
+            if let Some(cat) = &d.dominant_category {
+                dir_json["category"] = Value::String(cat.clone());
+            }
+            if let Some(summary) = &d.summary {
+                dir_json["summary"] = Value::String(summary.clone());
+            }
+            if !d.concepts.is_empty() {
+                dir_json["concepts"] = serde_json::to_value(&d.concepts).unwrap();
+            }
+            dir_json
+        })
+        .collect();
+
+    Ok(ToolResult {
+        content: vec![Content::Text { text: summary }],
+        structured_content: Some(serde_json::json!({ "directories": structured })),
+        is_error: None,
+    })
+}
+
+// ============================================================================
+// Batch & Explore Tools
+// ============================================================================
+
+pub fn batch_search_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "batch_search".to_string(),
+        description: "Execute multiple search queries in a single call. Each query runs independently with its own parameters.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "queries": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "query": {
+                                "type": "string",
+                                "description": "Search query"
+                            },
+                            "limit": {
+                                "type": "integer",
+                                "description": "Maximum results for this query (default: 5)",
+                                "default": 5
+                            },
+                            "collection": {
+                                "type": "string",
+                                "description": "Filter by collection"
+                            }
+                        },
+                        "required": ["query"]
+                    },
+                    "description": "Array of search queries to execute"
+                },
+                "detail": detail_param(),
+                "session_id": session_id_param()
+            },
+            "required": ["queries"]
+        }),
+    }
+}
+
+pub fn explore_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "explore".to_string(),
+        description: "Explore the knowledge base starting from a search query. Returns results plus suggestions for related directories, concepts, documents, and follow-up queries.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "Search query to explore from"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum results (default: 10)",
+                    "default": 10
+                },
+                "collection": {
+                    "type": "string",
+                    "description": "Filter by collection"
+                },
+                "detail": detail_param(),
+                "session_id": session_id_param()
+            },
+            "required": ["query"]
+        }),
+    }
+}
+
+pub async fn handle_batch_search(db: &Database, args: Value) -> Result<ToolResult> {
+    let queries = args
+        .get("queries")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| anyhow::anyhow!("Missing queries array"))?;
+
+    let detail = parse_detail(&args);
+    let session_id = parse_session_id(db, &args);
+
+    let mut all_results: Vec<Value> = Vec::new();
+    let mut seen_docids: std::collections::HashSet<String> = std::collections::HashSet::new();
+
+    for query_obj in queries {
+        let query = query_obj
+            .get("query")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        if query.is_empty() {
+            continue;
+        }
+
+        let limit = query_obj.get("limit").and_then(|v| v.as_u64()).unwrap_or(5) as usize;
+        let collection = query_obj
+            .get("collection")
+            .and_then(|v| v.as_str())
+            .map(String::from);
+
+        let options = SearchOptions {
+            limit,
+            min_score: 0.0,
+            collection,
+            provider: None,
+            detail,
+            session_id: session_id.clone(),
+            ..Default::default()
+        };
+
+        let mut results = db.search_fts(query, &options)?;
+        apply_session_and_project(db, &mut results, detail, session_id.as_deref(), query);
+
+        results.retain(|r| seen_docids.insert(r.docid.clone()));
+        let results_json: Vec<Value> = results.iter().map(result_to_json).collect();
+
+        all_results.push(serde_json::json!({
+            "query": query,
+            "count": results_json.len(),
+            "results": results_json
+        }));
+    }
+
+    let total: usize = all_results
+        .iter()
+        .filter_map(|r| r.get("count").and_then(|c| c.as_u64()))
+        .sum::<u64>() as usize;
+
+    Ok(ToolResult {
+        content: vec![Content::Text {
+            text: format!(
+                "Batch search: {} queries, {} total results",
+                all_results.len(),
+                total
+            ),
+        }],
+        structured_content: Some(serde_json::json!({ "batches": all_results })),
+        is_error: None,
+    })
+}
+
+pub async fn handle_explore(db: &Database, args: Value) -> Result<ToolResult> {
+    let query = args
+        .get("query")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing query"))?;
+
+    let detail = parse_detail(&args);
+    let session_id = parse_session_id(db, &args);
+
+    let options = SearchOptions {
+        limit: args.get("limit").and_then(|v| v.as_u64()).unwrap_or(10) as usize,
+        min_score: 0.0,
+        collection: args
+            .get("collection")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        provider: None,
+
+        detail,
+        session_id: session_id.clone(),
+        ..Default::default()
+    };
+
+    let mut results = agentroot_core::unified_search(db, query, &options).await?;
+    apply_session_and_project(db, &mut results, detail, session_id.as_deref(), query);
+
+    let suggestions = agentroot_core::search::suggestions::compute_suggestions(
+        db,
+        &results,
+        query,
+        session_id.as_deref(),
+    )?;
+
+    let results_json: Vec<Value> = results.iter().map(result_to_json).collect();
+
+    let mut summary_parts = vec![format!("Found {} results for \"{}\"", results.len(), query)];
+    if !suggestions.related_directories.is_empty() {
+        summary_parts.push(format!(
+            "Related dirs: {}",
+            suggestions.related_directories.join(", ")
+        ));
+    }
+    if !suggestions.related_documents.is_empty() {
+        summary_parts.push(format!(
+            "Related docs: {}",
+            suggestions.related_documents.join(", ")
+        ));
+    }
+    if !suggestions.refinement_queries.is_empty() {
+        summary_parts.push(format!(
+            "Try also: {}",
+            suggestions.refinement_queries.join(", ")
+        ));
+    }
+    if suggestions.unseen_related > 0 {
+        summary_parts.push(format!(
+            "{} unseen related documents",
+            suggestions.unseen_related
+        ));
+    }
+
+    Ok(ToolResult {
+        content: vec![Content::Text {
+            text: summary_parts.join("\n"),
+        }],
+        structured_content: Some(serde_json::json!({
+            "results": results_json,
+            "suggestions": {
+                "related_directories": suggestions.related_directories,
+                "related_concepts": suggestions.related_concepts,
+                "related_documents": suggestions.related_documents,
+                "refinement_queries": suggestions.refinement_queries,
+                "unseen_related": suggestions.unseen_related
+            }
+        })),
+        is_error: None,
+    })
+}
+
+// ============================================================================
+// Memory Tools
+// ============================================================================
+
+fn memory_to_json(m: &agentroot_core::MemoryInfo) -> Value {
+    serde_json::json!({
+        "id": m.id,
+        "category": m.category,
+        "content": m.content,
+        "confidence": m.confidence,
+        "session_id": m.session_id,
+        "source_query": m.source_query,
+        "created_at": m.created_at,
+        "updated_at": m.updated_at,
+        "access_count": m.access_count,
+        "last_accessed_at": m.last_accessed_at
+    })
+}
+
+pub fn memory_store_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "memory_store".to_string(),
+        description: "Store a long-term memory".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "content": {
+                    "type": "string",
+                    "description": "Memory content to store"
+                },
+                "category": {
+                    "type": "string",
+                    "enum": ["preference", "entity", "pattern", "fact"],
+                    "description": "Memory category"
+                },
+                "confidence": {
+                    "type": "number",
+                    "description": "Confidence score 0-1 (default: 1.0)",
+                    "default": 1.0
+                },
+                "sessionId": {
+                    "type": "string",
+                    "description": "Optional session ID to associate with this memory"
+                },
+                "sourceQuery": {
+                    "type": "string",
+                    "description": "Optional query that led to this memory"
+                }
+            },
+            "required": ["content", "category"]
+        }),
+    }
+}
+
+pub fn memory_search_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "memory_search".to_string(),
+        description: "Search long-term memories".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "Search query for memories"
+                },
+                "category": {
+                    "type": "string",
+                    "description": "Filter by category (preference, entity, pattern, fact)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum results (default: 20)",
+                    "default": 20
+                }
+            },
+            "required": ["query"]
+        }),
+    }
+}
+
+pub fn memory_list_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "memory_list".to_string(),
+        description: "List stored memories".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "category": {
+                    "type": "string",
+                    "description": "Filter by category (preference, entity, pattern, fact)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum results (default: 20)",
+                    "default": 20
+                },
+                "offset": {
+                    "type": "integer",
+                    "description": "Offset for pagination (default: 0)",
+                    "default": 0
+                }
+            }
+        }),
+    }
+}
+
+pub fn memory_extract_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "memory_extract".to_string(),
+        description: "Extract memories from a session using LLM".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "sessionId": {
+                    "type": "string",
+                    "description": "Session ID to extract memories from"
+                }
+            },
+            "required": ["sessionId"]
+        }),
+    }
+}
+
+pub fn memory_delete_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "memory_delete".to_string(),
+        description: "Delete a memory by ID".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "id": {
+                    "type": "string",
+                    "description": "Memory ID to delete"
+                }
+            },
+            "required": ["id"]
+        }),
+    }
+}
+
+pub async fn handle_memory_store(db: &Database, args: Value) -> Result<ToolResult> {
+    let content = args
+        .get("content")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing content"))?;
+
+    let category = args
+        .get("category")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing category"))?;
+
+    let confidence = args
+        .get("confidence")
+        .and_then(|v| v.as_f64())
+        .unwrap_or(1.0);
+
+    let session_id = args.get("sessionId").and_then(|v| v.as_str());
+    let source_query = args.get("sourceQuery").and_then(|v| v.as_str());
+
+    let id = db.store_memory(session_id, category, content, confidence, source_query)?;
+
+    Ok(ToolResult {
+        content: vec![Content::Text {
+            text: format!("Stored memory: {}", id),
+        }],
+        structured_content: Some(serde_json::json!({
+            "id": id,
+            "category": category,
+            "stored": true
+        })),
+        is_error: None,
+    })
+}
+
+pub async fn handle_memory_search(db: &Database, args: Value) -> Result<ToolResult> {
+    let query = args
+        .get("query")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing query"))?;
+
+    let category = args.get("category").and_then(|v| v.as_str());
+    let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
+
+    let memories = db.search_memories(query, category, limit)?;
+
+    let summary = format!("Found {} memories for \"{}\"", memories.len(), query);
+    let structured: Vec<Value> = memories.iter().map(memory_to_json).collect();
+
+    Ok(ToolResult {
+        content: vec![Content::Text { text: summary }],
+        structured_content: Some(serde_json::json!({ "memories": structured })),
+        is_error: None,
+    })
+}
+
+pub async fn handle_memory_list(db: &Database, args: Value) -> Result<ToolResult> {
+    let category = args.get("category").and_then(|v| v.as_str());
+    let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
+    let offset = args.get("offset").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+
+    let memories = db.list_memories(category, limit, offset)?;
+
+    let summary = format!("Listed {} memories", memories.len());
+    let structured: Vec<Value> = memories.iter().map(memory_to_json).collect();
+
+    Ok(ToolResult {
+        content: vec![Content::Text { text: summary }],
+        structured_content: Some(serde_json::json!({ "memories": structured })),
+        is_error: None,
+    })
+}
+
+pub async fn handle_memory_extract(db: &Database, args: Value) -> Result<ToolResult> {
+    let session_id = args
+        .get("sessionId")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing sessionId"))?;
+
+    let queries = db.get_session_queries(session_id)?;
+    let context = db.get_session_context(session_id)?;
+
+    let extractor = MemoryExtractor::from_env()
+        .map_err(|e| anyhow::anyhow!("Failed to create memory extractor: {}", e))?;
+
+    let extracted = extractor.extract_memories(&queries, &context).await;
+
+    let mut stored_ids: Vec<Value> = Vec::new();
+    for mem in &extracted {
+        let id = db.store_memory(
+            Some(session_id),
+            &mem.category,
+            &mem.content,
+            mem.confidence,
+            None,
+        )?;
+        stored_ids.push(serde_json::json!({
+            "id": id,
+            "category": mem.category,
+            "content": mem.content,
+            "confidence": mem.confidence
+        }));
+    }
+
+    let summary = format!(
+        "Extracted and stored {} memories from session {}",
+        stored_ids.len(),
+        session_id
+    );
+
+    Ok(ToolResult {
+        content: vec![Content::Text { text: summary }],
+        structured_content: Some(serde_json::json!({
+            "count": stored_ids.len(),
+            "memories": stored_ids
+        })),
+        is_error: None,
+    })
+}
+
+pub async fn handle_memory_delete(db: &Database, args: Value) -> Result<ToolResult> {
+    let id = args
+        .get("id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing id"))?;
+
+    let deleted = db.delete_memory(id)?;
+
+    if deleted {
+        Ok(ToolResult {
+            content: vec![Content::Text {
+                text: format!("Deleted memory: {}", id),
+            }],
+            structured_content: Some(serde_json::json!({
+                "id": id,
+                "deleted": true
+            })),
+            is_error: None,
+        })
+    } else {
+        Ok(ToolResult {
+            content: vec![Content::Text {
+                text: format!("Memory not found: {}", id),
+            }],
+            structured_content: Some(serde_json::json!({
+                "id": id,
+                "deleted": false
+            })),
+            is_error: Some(true),
+        })
+    }
+}
+
+// ============================================================================
+// Graph Tools
+// ============================================================================
+
+pub fn backlinks_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "backlinks".to_string(),
+        description: "Find documents that link to a given document (which notes reference it)"
+            .to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "docid": {
+                    "type": "string",
+                    "description": "Document ID (#abc123) or collection/path"
+                }
+            },
+            "required": ["docid"]
+        }),
+    }
+}
+
+pub async fn handle_backlinks(db: &Database, args: Value) -> Result<ToolResult> {
+    let docid = args
+        .get("docid")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing docid"))?;
+
+    let backlinks = db.get_backlinks(docid)?;
+
+    let mut summary = format!("Found {} documents linking to {}", backlinks.len(), docid);
+    if backlinks.is_empty() && db.count_document_links()? == 0 {
+        summary.push_str(" (link graph is empty; run `agentroot pagerank` to build it)");
+    }
+
+    let structured: Vec<Value> = backlinks
+        .iter()
+        .map(|l| {
+            serde_json::json!({
+                "docid": format!("#{}", l.docid),
+                "file": format!("{}/{}", l.collection, l.path),
+                "title": l.title,
+                "linkType": l.link_type
+            })
+        })
+        .collect();
+
+    Ok(ToolResult {
+        content: vec![Content::Text { text: summary }],
+        structured_content: Some(serde_json::json!({
+            "docid": docid,
+            "count": structured.len(),
+            "backlinks": structured
+        })),
+        is_error: None,
+    })
+}
+
+pub fn related_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "related".to_string(),
+        description: "Show how a document connects to the rest of the index: the documents it links to, the documents linking to it (with link types), and related documents ranked by links, shared concepts and embeddings".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "docid": {
+                    "type": "string",
+                    "description": "Document ID (#abc123) or collection/path"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum related documents (default: 10)",
+                    "default": 10
+                }
+            },
+            "required": ["docid"]
+        }),
+    }
+}
+
+pub async fn handle_related(db: &Database, args: Value) -> Result<ToolResult> {
+    let docid = args
+        .get("docid")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing docid"))?;
+    let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(10) as usize;
+
+    let outlinks = db.get_outlinks(docid)?;
+    let backlinks = db.get_backlinks(docid)?;
+    let related = db.related(docid, limit)?;
+
+    let link_json = |links: &[agentroot_core::db::LinkedDocument]| -> Vec<Value> {
+        links
+            .iter()
+            .map(|l| {
+                serde_json::json!({
+                    "docid": format!("#{}", l.docid),
+                    "file": format!("{}/{}", l.collection, l.path),
+                    "title": l.title,
+                    "linkType": l.link_type
+                })
+            })
+            .collect()
+    };
+
+    let mut lines = vec![format!(
+        "{} links to {} documents, {} link to it, {} related",
+        docid,
+        outlinks.len(),
+        backlinks.len(),
+        related.len()
+    )];
+    for (label, links) in [("Links to", &outlinks), ("Linked from", &backlinks)] {
+        for l in links.iter() {
+            lines.push(format!(
+                "{}: #{} {}/{} ({})",
+                label, l.docid, l.collection, l.path, l.link_type
+            ));
+        }
+    }
+    for r in &related {
+        lines.push(format!(
+            "Related {:.2}: #{} {}/{}",
+            r.score, r.docid, r.collection, r.path
+        ));
+    }
+    if outlinks.is_empty() && backlinks.is_empty() && db.count_document_links()? == 0 {
+        lines.push("Link graph is empty; run `agentroot pagerank` to build it".to_string());
+    }
+
+    let related_json: Vec<Value> = related
+        .iter()
+        .map(|r| {
+            serde_json::json!({
+                "docid": format!("#{}", r.docid),
+                "file": format!("{}/{}", r.collection, r.path),
+                "title": r.title,
+                "score": r.score,
+                "linkScore": r.link_score,
+                "conceptScore": r.concept_score,
+                "embeddingScore": r.embedding_score,
+                "sharedConcepts": r.shared_concepts
+            })
+        })
+        .collect();
+
+    Ok(ToolResult {
+        content: vec![Content::Text {
+            text: lines.join("\n"),
+        }],
+        structured_content: Some(serde_json::json!({
+            "docid": docid,
+            "outlinks": link_json(&outlinks),
+            "backlinks": link_json(&backlinks),
+            "related": related_json
+        })),
+        is_error: None,
+    })
+}
+
+pub fn graph_neighborhood_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "graph_neighborhood".to_string(),
+        description: "Get the link subgraph around a document: nodes with titles and importance, plus typed edges".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "docid": {
+                    "type": "string",
+                    "description": "Document ID (#abc123) or collection/path"
+                },
+                "depth": {
+                    "type": "integer",
+                    "description": "Hops to follow in either direction (default: 1, max: 3)",
+                    "default": 1
+                },
+                "edge_types": {
+                    "type": "array",
+                    "items": {
+                        "type": "string",
+                        "enum": ["markdown_link", "wikilink", "imports"]
+                    },
+                    "description": "Only follow these link types (default: all)"
+                }
+            },
+            "required": ["docid"]
+        }),
+    }
+}
+
+pub async fn handle_graph_neighborhood(db: &Database, args: Value) -> Result<ToolResult> {
+    let docid = args
+        .get("docid")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing docid"))?;
+    let depth = args.get("depth").and_then(|v| v.as_u64()).unwrap_or(1) as usize;
+    let edge_types: Vec<String> = args
+        .get("edge_types")
+        .and_then(|v| v.as_array())
+        .map(|types| {
+            types
+                .iter()
+                .filter_map(|t| t.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let hood = db.graph_neighborhood(docid, depth, &edge_types)?;
+
+    let mut summary = format!(
+        "{} nodes, {} edges around {}",
+        hood.nodes.len(),
+        hood.edges.len(),
+        docid
+    );
+    if hood.truncated {
+        summary.push_str(" (truncated)");
+    }
+
+    Ok(ToolResult {
+        content: vec![Content::Text { text: summary }],
+        structured_content: Some(serde_json::to_value(&hood)?),
+        is_error: None,
+    })
+}
+
+pub fn compare_documents_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "compare_documents".to_string(),
+        description: "Compare two documents: embedding similarity, shared concepts and overlapping passages. Use it to spot redundant docs or check whether a new note duplicates an old one".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "a": {
+                    "type": "string",
+                    "description": "First document ID (#abc123) or collection/path"
+                },
+                "b": {
+                    "type": "string",
+                    "description": "Second document ID (#abc123) or collection/path"
+                }
+            },
+            "required": ["a", "b"]
+        }),
+    }
+}
+
+pub async fn handle_compare_documents(db: &Database, args: Value) -> Result<ToolResult> {
+    let a = args
+        .get("a")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing a"))?;
+    let b = args
+        .get("b")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing b"))?;
+
+    let cmp = db.compare(a, b)?;
+
+    let mut summary = format!(
+        "{}/{} vs {}/{}: text overlap {:.2}",
+        cmp.a.collection, cmp.a.path, cmp.b.collection, cmp.b.path, cmp.text_overlap
+    );
+    if let Some(sim) = cmp.embedding_similarity {
+        summary.push_str(&format!(", embedding similarity {:.2}", sim));
+    }
+    if !cmp.shared_concepts.is_empty() {
+        summary.push_str(&format!(
+            ", shared concepts: {}",
+            cmp.shared_concepts.join(", ")
+        ));
+    }
+    summary.push_str(&format!(
+        "\n{} overlapping passages",
+        cmp.overlapping_chunks.len()
+    ));
+    for o in &cmp.overlapping_chunks {
+        summary.push_str(&format!(
+            "\n  {:.2}  A:{}-{}  B:{}-{}  {}",
+            o.similarity, o.a_start_line, o.a_end_line, o.b_start_line, o.b_end_line, o.preview
+        ));
+    }
+
+    Ok(ToolResult {
+        content: vec![Content::Text { text: summary }],
+        structured_content: Some(serde_json::to_value(&cmp)?),
+        is_error: None,
+    })
+}
+
+pub fn dead_links_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "dead_links".to_string(),
+        description: "Report markdown links and wikilinks pointing at documents not in the index"
+            .to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "collection": {
+                    "type": "string",
+                    "description": "Filter by collection"
+                }
+            }
+        }),
+    }
+}
+
+pub async fn handle_dead_links(db: &Database, args: Value) -> Result<ToolResult> {
+    let collection = args.get("collection").and_then(|v| v.as_str());
+    let dangling = db.find_dangling_links(collection)?;
+
+    let structured: Vec<Value> = dangling
+        .iter()
+        .map(|l| {
+            serde_json::json!({
+                "docid": format!("#{}", l.docid),
+                "file": format!("{}/{}", l.collection, l.path),
+                "target": l.target,
+                "linkType": l.link_type,
+                "existsOnDisk": l.exists_on_disk
+            })
+        })
+        .collect();
+
+    Ok(ToolResult {
+        content: vec![Content::Text {
+            text: format!("Found {} dead links", dangling.len()),
+        }],
+        structured_content: Some(serde_json::json!({
+            "count": structured.len(),
+            "deadLinks": structured
+        })),
+        is_error: None,
+    })
+}
+
+pub fn topics_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "topics".to_string(),
+        description: "Corpus overview: list topic clusters, or the documents in one topic"
+            .to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "topic_id": {
+                    "type": "integer",
+                    "description": "Topic ID to list documents for (omit to list all topics)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum documents per topic (default: 5, or 20 with topic_id)"
+                }
+            }
+        }),
+    }
+}
+
+pub async fn handle_topics(db: &Database, args: Value) -> Result<ToolResult> {
+    if let Some(topic_id) = args.get("topic_id").and_then(|v| v.as_i64()) {
+        let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
+        let members = db.get_topic_documents(topic_id, limit)?;
+        let structured: Vec<Value> = members.iter().map(topic_member_to_json).collect();
+
+        return Ok(ToolResult {
+            content: vec![Content::Text {
+                text: format!("Topic {}: {} documents", topic_id, members.len()),
+            }],
+            structured_content: Some(serde_json::json!({
+                "topic_id": topic_id,
+                "documents": structured
+            })),
+            is_error: None,
+        });
+    }
+
+    let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(5) as usize;
+    let topics = db.list_topics()?;
+
+    let mut lines = vec![format!("{} topics", topics.len())];
+    if topics.is_empty() {
+        lines.push("No topics yet; run `agentroot topics build` to detect them".to_string());
+    }
+    let mut structured = Vec::new();
+    for t in &topics {
+        lines.push(format!("{}. {} ({} docs)", t.id, t.label, t.size));
+        let members = db.get_topic_documents(t.id, limit)?;
+        structured.push(serde_json::json!({
+            "id": t.id,
+            "label": t.label,
+            "keywords": t.keywords,
+            "size": t.size,
+            "documents": members.iter().map(topic_member_to_json).collect::<Vec<_>>()
+        }));
+    }
+
+    Ok(ToolResult {
+        content: vec![Content::Text {
+            text: lines.join("\n"),
+        }],
+        structured_content: Some(serde_json::json!({ "topics": structured })),
+        is_error: None,
+    })
+}
+
+fn topic_member_to_json(m: &agentroot_core::db::TopicMember) -> Value {
+    serde_json::json!({
+        "docid": format!("#{}", m.docid),
+        "file": format!("{}/{}", m.collection, m.path),
+        "title": m.title
+    })
+}
+
+// ============================================================================
+// Glossary Tools
+// ============================================================================
+
+pub fn glossary_list_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "glossary_list".to_string(),
+        description:
+            "List glossary concepts by number of linked chunks, or show one concept's chunks"
+                .to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "concept": {
+                    "type": "string",
+                    "description": "Concept ID or term to show chunks for (omit to list concepts)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum concepts or chunks (default: 50, or 20 with concept)"
+                }
+            }
+        }),
+    }
+}
+
+pub fn browse_concepts_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "browse_concepts".to_string(),
+        description: "Topical map of the corpus without a query: top concepts with the concepts they co-occur with, or one concept's documents, chunks and co-occurring concepts"
+            .to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "concept": {
+                    "type": "string",
+                    "description": "Concept ID or term to drill into (omit for the top concepts)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum concepts, documents or chunks (default: 20)"
+                },
+                "include_chunks": {
+                    "type": "boolean",
+                    "description": "Include linked chunk snippets when drilling into a concept (default: false)"
+                }
             }
-            dir_json
-        })
-        .collect();
-
-    Ok(ToolResult {
-        content: vec![Content::Text { text: summary }],
-        structured_content: Some(serde_json::json!({ "directories": structured })),
-        is_error: None,
-    })
+        }),
+    }
 }
 
-// ============================================================================
-// Batch & Explore Tools
-// ============================================================================
-
-pub fn batch_search_tool_definition() -> ToolDefinition {
+pub fn glossary_add_tool_definition() -> ToolDefinition {
     ToolDefinition {
-        name: "batch_search".to_string(),
-        description: "Execute multiple search queries in a single call. Each query runs independently with its own parameters.".to_string(),
+        name: "glossary_add".to_string(),
+        description: "Add a glossary concept and link it to every chunk that mentions it"
+            .to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "queries": {
-                    "type": "array",
-                    "items": {
-                        "type": "object",
-                        "properties": {
-                            "query": {
-                                "type": "string",
-                                "description": "Search query"
-                            },
-                            "limit": {
-                                "type": "integer",
-                                "description": "Maximum results for this query (default: 5)",
-                                "default": 5
-                            },
-                            "collection": {
-                                "type": "string",
-                                "description": "Filter by collection"
-                            }
-                        },
-                        "required": ["query"]
-                    },
-                    "description": "Array of search queries to execute"
-                },
-                "detail": detail_param(),
-                "session_id": session_id_param()
+                "term": {
+                    "type": "string",
+                    "description": "Concept term"
+                }
             },
-            "required": ["queries"]
+            "required": ["term"]
         }),
     }
 }
 
-pub fn explore_tool_definition() -> ToolDefinition {
+pub fn glossary_merge_tool_definition() -> ToolDefinition {
     ToolDefinition {
-        name: "explore".to_string(),
-        description: "Explore the knowledge base starting from a search query. Returns results plus suggestions for related directories, concepts, and follow-up queries.".to_string(),
+        name: "glossary_merge".to_string(),
+        description: "Merge a synonym concept into another, moving its chunk links".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "query": {
+                "from": {
                     "type": "string",
-                    "description": "Search query to explore from"
-                },
-                "limit": {
-                    "type": "integer",
-                    "description": "Maximum results (default: 10)",
-                    "default": 10
+                    "description": "Concept ID or term to merge away"
                 },
-                "collection": {
+                "into": {
                     "type": "string",
-                    "description": "Filter by collection"
-                },
-                "detail": detail_param(),
-                "session_id": session_id_param()
+                    "description": "Concept ID or term to keep"
+                }
             },
-            "required": ["query"]
+            "required": ["from", "into"]
         }),
     }
 }
 
-pub async fn handle_batch_search(db: &Database, args: Value) -> Result<ToolResult> {
-    let queries = args
-        .get("queries")
-        .and_then(|v| v.as_array())
-        .ok_or_else(|| anyhow::anyhow!("Missing queries array"))?;
-
-    let detail = parse_detail(&args);
-    let session_id = parse_session_id(&args);
+pub fn glossary_delete_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "glossary_delete".to_string(),
+        description: "Delete a glossary concept and its chunk links".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "concept": {
+                    "type": "string",
+                    "description": "Concept ID or term"
+                }
+            },
+            "required": ["concept"]
+        }),
+    }
+}
 
-    let mut all_results: Vec<Value> = Vec::new();
-    let mut seen_docids: std::collections::HashSet<String> = std::collections::HashSet::new();
+fn find_concept(db: &Database, args: &Value, key: &str) -> Result<agentroot_core::db::ConceptInfo> {
+    let concept = args
+        .get(key)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing {}", key))?;
+    db.find_concept(concept)?
+        .ok_or_else(|| anyhow::anyhow!("Concept not found: {}", concept))
+}
 
-    for query_obj in queries {
-        let query = query_obj
-            .get("query")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-        if query.is_empty() {
-            continue;
+pub async fn handle_glossary_list(db: &Database, args: Value) -> Result<ToolResult> {
+    if args.get("concept").is_some() {
+        let concept = find_concept(db, &args, "concept")?;
+        let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
+        let mut chunks = db.get_chunks_for_concept(concept.id)?;
+        chunks.truncate(limit);
+
+        let mut lines = vec![format!(
+            "{} '{}' ({} chunks)",
+            concept.id, concept.term, concept.chunk_count
+        )];
+        for c in &chunks {
+            lines.push(format!(
+                "- {} ({}): {}",
+                c.document_path, c.chunk_hash, c.snippet
+            ));
         }
 
-        let limit = query_obj.get("limit").and_then(|v| v.as_u64()).unwrap_or(5) as usize;
-        let collection = query_obj
-            .get("collection")
-            .and_then(|v| v.as_str())
-            .map(String::from);
+        return Ok(ToolResult {
+            content: vec![Content::Text {
+                text: lines.join("\n"),
+            }],
+            structured_content: Some(serde_json::json!({
+                "concept": concept,
+                "chunks": chunks
+            })),
+            is_error: None,
+        });
+    }
 
-        let options = SearchOptions {
-            limit,
-            min_score: 0.0,
-            collection,
-            provider: None,
-            detail,
-            session_id: session_id.clone(),
-            metadata_filters: Vec::new(),
-            ..Default::default()
-        };
+    let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(50) as usize;
+    let concepts = db.top_concepts(limit)?;
+    let (total, links) = db.get_concept_stats()?;
 
-        let mut results = db.search_fts(query, &options)?;
-        apply_session_and_project(db, &mut results, detail, session_id.as_deref(), query);
+    let mut lines = vec![format!("{} concepts, {} chunk links", total, links)];
+    for c in &concepts {
+        lines.push(format!("{}. {} ({} chunks)", c.id, c.term, c.chunk_count));
+    }
 
-        results.retain(|r| seen_docids.insert(r.docid.clone()));
-        let results_json: Vec<Value> = results.iter().map(result_to_json).collect();
+    Ok(ToolResult {
+        content: vec![Content::Text {
+            text: lines.join("\n"),
+        }],
+        structured_content: Some(serde_json::json!({
+            "total_concepts": total,
+            "total_links": links,
+            "concepts": concepts
+        })),
+        is_error: None,
+    })
+}
 
-        all_results.push(serde_json::json!({
-            "query": query,
-            "count": results_json.len(),
-            "results": results_json
-        }));
+pub async fn handle_browse_concepts(db: &Database, args: Value) -> Result<ToolResult> {
+    let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
+
+    if args.get("concept").is_some() {
+        let concept = find_concept(db, &args, "concept")?;
+        let documents = db.get_documents_for_concept(concept.id, limit)?;
+        let related = db.get_cooccurring_concepts(concept.id, limit)?;
+
+        let mut lines = vec![format!(
+            "{} '{}' ({} chunks in {} documents shown)",
+            concept.id,
+            concept.term,
+            concept.chunk_count,
+            documents.len()
+        )];
+        for d in &documents {
+            lines.push(format!(
+                "- #{} {}/{} ({} chunks)",
+                d.docid, d.collection, d.path, d.chunk_count
+            ));
+        }
+        if !related.is_empty() {
+            let terms: Vec<_> = related
+                .iter()
+                .map(|r| format!("{} ({})", r.term, r.shared_chunks))
+                .collect();
+            lines.push(format!("Co-occurs with: {}", terms.join(", ")));
+        }
+
+        let mut structured = serde_json::json!({
+            "concept": concept,
+            "documents": documents,
+            "related": related
+        });
+        if args
+            .get("include_chunks")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+        {
+            let mut chunks = db.get_chunks_for_concept(concept.id)?;
+            chunks.truncate(limit);
+            structured["chunks"] = serde_json::to_value(chunks)?;
+        }
+
+        return Ok(ToolResult {
+            content: vec![Content::Text {
+                text: lines.join("\n"),
+            }],
+            structured_content: Some(structured),
+            is_error: None,
+        });
     }
 
-    let total: usize = all_results
-        .iter()
-        .filter_map(|r| r.get("count").and_then(|c| c.as_u64()))
-        .sum::<u64>() as usize;
+    let concepts = db.top_concepts(limit)?;
+    let mut lines = vec![format!("Top {} concepts", concepts.len())];
+    if concepts.is_empty() {
+        lines.push(
+            "No concepts yet; they are extracted during indexing with an LLM service".to_string(),
+        );
+    }
+    let mut structured = Vec::new();
+    for c in &concepts {
+        let related = db.get_cooccurring_concepts(c.id, 5)?;
+        let terms: Vec<_> = related.iter().map(|r| r.term.as_str()).collect();
+        if terms.is_empty() {
+            lines.push(format!("{}. {} ({} chunks)", c.id, c.term, c.chunk_count));
+        } else {
+            lines.push(format!(
+                "{}. {} ({} chunks) - with {}",
+                c.id,
+                c.term,
+                c.chunk_count,
+                terms.join(", ")
+            ));
+        }
+        structured.push(serde_json::json!({
+            "concept": c,
+            "related": related
+        }));
+    }
 
     Ok(ToolResult {
         content: vec![Content::Text {
-            text: format!(
-                "Batch search: {} queries, {} total results",
-                all_results.len(),
-                total
-            ),
+            text: lines.join("\n"),
         }],
-        structured_content: Some(serde_json::json!({ "batches": all_results })),
+        structured_content: Some(serde_json::json!({ "concepts": structured })),
         is_error: None,
     })
 }
 
-pub async fn handle_explore(db: &Database, args: Value) -> Result<ToolResult> {
-    let query = args
-        .get("query")
+pub async fn handle_glossary_add(db: &Database, args: Value) -> Result<ToolResult> {
+    let term = args
+        .get("term")
         .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Missing query"))?;
-
-    let detail = parse_detail(&args);
-    let session_id = parse_session_id(&args);
-
-    let options = SearchOptions {
-        limit: args.get("limit").and_then(|v| v.as_u64()).unwrap_or(10) as usize,
-        min_score: 0.0,
-        collection: args
-            .get("collection")
-            .and_then(|v| v.as_str())
-            .map(String::from),
-        provider: None,
-
-        detail,
-        session_id: session_id.clone(),
-        metadata_filters: Vec::new(),
-        ..Default::default()
-    };
+        .ok_or_else(|| anyhow::anyhow!("Missing term"))?;
 
-    let mut results = agentroot_core::unified_search(db, query, &options).await?;
-    apply_session_and_project(db, &mut results, detail, session_id.as_deref(), query);
+    let (concept, linked) = db.add_concept(term)?;
 
-    let suggestions = agentroot_core::search::suggestions::compute_suggestions(
-        db,
-        &results,
-        query,
-        session_id.as_deref(),
-    )?;
+    Ok(ToolResult {
+        content: vec![Content::Text {
+            text: format!(
+                "Added concept {} '{}' ({} new chunk links)",
+                concept.id, concept.term, linked
+            ),
+        }],
+        structured_content: Some(serde_json::json!({
+            "concept": concept,
+            "linked": linked
+        })),
+        is_error: None,
+    })
+}
 
-    let results_json: Vec<Value> = results.iter().map(result_to_json).collect();
+pub async fn handle_glossary_merge(db: &Database, args: Value) -> Result<ToolResult> {
+    let from = find_concept(db, &args, "from")?;
+    let into = find_concept(db, &args, "into")?;
 
-    let mut summary_parts = vec![format!("Found {} results for \"{}\"", results.len(), query)];
-    if !suggestions.related_directories.is_empty() {
-        summary_parts.push(format!(
-            "Related dirs: {}",
-            suggestions.related_directories.join(", ")
-        ));
-    }
-    if !suggestions.refinement_queries.is_empty() {
-        summary_parts.push(format!(
-            "Try also: {}",
-            suggestions.refinement_queries.join(", ")
-        ));
-    }
-    if suggestions.unseen_related > 0 {
-        summary_parts.push(format!(
-            "{} unseen related documents",
-            suggestions.unseen_related
-        ));
-    }
+    let moved = db.merge_concepts(from.id, into.id)?;
+    let merged = db.find_concept(&into.id.to_string())?.unwrap_or(into);
 
     Ok(ToolResult {
         content: vec![Content::Text {
-            text: summary_parts.join("\n"),
+            text: format!(
+                "Merged '{}' into '{}' ({} chunk links moved)",
+                from.term, merged.term, moved
+            ),
         }],
         structured_content: Some(serde_json::json!({
-            "results": results_json,
-            "suggestions": {
-                "related_directories": suggestions.related_directories,
-                "related_concepts": suggestions.related_concepts,
-                "refinement_queries": suggestions.refinement_queries,
-                "unseen_related": suggestions.unseen_related
-            }
+            "merged": from.term,
+            "concept": merged,
+            "moved": moved
         })),
         is_error: None,
     })
 }
 
-// ============================================================================
-// Memory Tools
-// ============================================================================
+pub async fn handle_glossary_delete(db: &Database, args: Value) -> Result<ToolResult> {
+    let concept = find_concept(db, &args, "concept")?;
+    db.delete_concept(concept.id)?;
 
-fn memory_to_json(m: &agentroot_core::MemoryInfo) -> Value {
-    serde_json::json!({
-        "id": m.id,
-        "category": m.category,
-        "content": m.content,
-        "confidence": m.confidence,
-        "session_id": m.session_id,
-        "source_query": m.source_query,
-        "created_at": m.created_at,
-        "updated_at": m.updated_at,
-        "access_count": m.access_count,
-        "last_accessed_at": m.last_accessed_at
+    Ok(ToolResult {
+        content: vec![Content::Text {
+            text: format!("Deleted concept {} '{}'", concept.id, concept.term),
+        }],
+        structured_content: Some(serde_json::json!({
+            "id": concept.id,
+            "term": concept.term,
+            "deleted": true
+        })),
+        is_error: None,
     })
 }
 
-pub fn memory_store_tool_definition() -> ToolDefinition {
+// ============================================================================
+// Feedback Tools
+// ============================================================================
+
+pub fn feedback_tool_definition() -> ToolDefinition {
     ToolDefinition {
-        name: "memory_store".to_string(),
-        description: "Store a long-term memory".to_string(),
+        name: "feedback".to_string(),
+        description: "Mark a search result as relevant or irrelevant for a query. Judgments adjust future ranking: documents judged for similar queries are boosted or demoted.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "content": {
+                "query": {
                     "type": "string",
-                    "description": "Memory content to store"
+                    "description": "Query the document was returned for"
                 },
-                "category": {
+                "docid": {
                     "type": "string",
-                    "enum": ["preference", "entity", "pattern", "fact"],
-                    "description": "Memory category"
-                },
-                "confidence": {
-                    "type": "number",
-                    "description": "Confidence score 0-1 (default: 1.0)",
-                    "default": 1.0
+                    "description": "Document ID (#abc123) or path"
                 },
-                "sessionId": {
-                    "type": "string",
-                    "description": "Optional session ID to associate with this memory"
+                "relevant": {
+                    "type": "boolean",
+                    "description": "true if the document answered the query, false otherwise"
                 },
-                "sourceQuery": {
-                    "type": "string",
-                    "description": "Optional query that led to this memory"
-                }
+                "session_id": session_id_param()
             },
-            "required": ["content", "category"]
+            "required": ["query", "docid", "relevant"]
         }),
     }
 }
 
-pub fn memory_search_tool_definition() -> ToolDefinition {
+pub async fn handle_feedback(db: &Database, args: Value) -> Result<ToolResult> {
+    let query = args
+        .get("query")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing query"))?;
+    let docid = args
+        .get("docid")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing docid"))?;
+    let relevant = args
+        .get("relevant")
+        .and_then(|v| v.as_bool())
+        .ok_or_else(|| anyhow::anyhow!("Missing relevant"))?;
+    let session_id = parse_session_id(db, &args);
+
+    let entry = db.record_feedback(query, docid, relevant, session_id.as_deref())?;
+
+    Ok(ToolResult {
+        content: vec![Content::Text {
+            text: format!(
+                "Recorded {} feedback for {}/{}",
+                if relevant { "relevant" } else { "irrelevant" },
+                entry.collection,
+                entry.path
+            ),
+        }],
+        structured_content: Some(serde_json::json!({
+            "id": entry.id,
+            "query": entry.query,
+            "file": format!("{}/{}", entry.collection, entry.path),
+            "relevant": entry.relevant
+        })),
+        is_error: None,
+    })
+}
+
+pub fn job_enqueue_tool_definition() -> ToolDefinition {
     ToolDefinition {
-        name: "memory_search".to_string(),
-        description: "Search long-term memories".to_string(),
+        name: "job_enqueue".to_string(),
+        description: "Queue heavy work (reindex, embed, metadata backfill, PageRank) for the background worker and return immediately. Poll job_list for progress.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "query": {
+                "kind": {
                     "type": "string",
-                    "description": "Search query for memories"
+                    "enum": ["reindex", "embed", "metadata_backfill", "pagerank"],
+                    "description": "Work to queue"
                 },
-                "category": {
+                "collection": {
                     "type": "string",
-                    "description": "Filter by category (preference, entity, pattern, fact)"
+                    "description": "Restrict reindex or metadata_backfill to one collection (default: all)"
                 },
-                "limit": {
+                "force": {
+                    "type": "boolean",
+                    "description": "Re-embed all documents (embed only)",
+                    "default": false
+                },
+                "priority": {
                     "type": "integer",
-                    "description": "Maximum results (default: 20)",
-                    "default": 20
+                    "description": "Higher priorities run first",
+                    "default": 0
                 }
             },
-            "required": ["query"]
+            "required": ["kind"]
         }),
     }
 }
 
-pub fn memory_list_tool_definition() -> ToolDefinition {
+pub fn job_list_tool_definition() -> ToolDefinition {
     ToolDefinition {
-        name: "memory_list".to_string(),
-        description: "List stored memories".to_string(),
+        name: "job_list".to_string(),
+        description: "List background jobs with their state, attempts and last error, newest first. Pass job_id to check a single job.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "category": {
+                "job_id": {
+                    "type": "integer",
+                    "description": "Return only this job"
+                },
+                "status": {
                     "type": "string",
-                    "description": "Filter by category (preference, entity, pattern, fact)"
+                    "enum": ["queued", "running", "succeeded", "failed", "cancelled"],
+                    "description": "Only jobs in this state"
                 },
                 "limit": {
                     "type": "integer",
-                    "description": "Maximum results (default: 20)",
+                    "description": "Maximum jobs to return",
                     "default": 20
-                },
-                "offset": {
-                    "type": "integer",
-                    "description": "Offset for pagination (default: 0)",
-                    "default": 0
                 }
             }
         }),
     }
 }
 
-pub fn memory_extract_tool_definition() -> ToolDefinition {
-    ToolDefinition {
-        name: "memory_extract".to_string(),
-        description: "Extract memories from a session using LLM".to_string(),
-        input_schema: serde_json::json!({
-            "type": "object",
-            "properties": {
-                "sessionId": {
-                    "type": "string",
-                    "description": "Session ID to extract memories from"
-                }
-            },
-            "required": ["sessionId"]
-        }),
-    }
-}
-
-pub fn memory_delete_tool_definition() -> ToolDefinition {
-    ToolDefinition {
-        name: "memory_delete".to_string(),
-        description: "Delete a memory by ID".to_string(),
-        input_schema: serde_json::json!({
-            "type": "object",
-            "properties": {
-                "id": {
-                    "type": "string",
-                    "description": "Memory ID to delete"
-                }
-            },
-            "required": ["id"]
-        }),
-    }
-}
-
-pub async fn handle_memory_store(db: &Database, args: Value) -> Result<ToolResult> {
-    let content = args
-        .get("content")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Missing content"))?;
-
-    let category = args
-        .get("category")
+pub async fn handle_job_enqueue(db: &Database, args: Value) -> Result<ToolResult> {
+    let collection = args
+        .get("collection")
         .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Missing category"))?;
-
-    let confidence = args
-        .get("confidence")
-        .and_then(|v| v.as_f64())
-        .unwrap_or(1.0);
+        .map(String::from);
+    let kind = match args.get("kind").and_then(|v| v.as_str()) {
+        Some("reindex") => JobKind::Reindex { collection },
+        Some("embed") => JobKind::Embed {
+            force: args.get("force").and_then(|v| v.as_bool()).unwrap_or(false),
+        },
+        Some("metadata_backfill") => JobKind::MetadataBackfill { collection },
+        Some("pagerank") => JobKind::Pagerank,
+        Some(other) => anyhow::bail!("Unknown job kind: {}", other),
+        None => anyhow::bail!("Missing kind"),
+    };
+    let priority = args.get("priority").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
 
-    let session_id = args.get("sessionId").and_then(|v| v.as_str());
-    let source_query = args.get("sourceQuery").and_then(|v| v.as_str());
+    job_queued_result(db, &kind, priority)
+}
 
-    let id = db.store_memory(session_id, category, content, confidence, source_query)?;
+fn job_queued_result(db: &Database, kind: &JobKind, priority: i32) -> Result<ToolResult> {
+    let id = db.enqueue_job(kind, priority)?;
+    let job = db
+        .get_job(id)?
+        .ok_or_else(|| anyhow::anyhow!("Job not found: {}", id))?;
 
     Ok(ToolResult {
         content: vec![Content::Text {
-            text: format!("Stored memory: {}", id),
+            text: format!("Queued {} job #{}", kind.name(), id),
         }],
-        structured_content: Some(serde_json::json!({
-            "id": id,
-            "category": category,
-            "stored": true
-        })),
-        is_error: None,
-    })
-}
-
-pub async fn handle_memory_search(db: &Database, args: Value) -> Result<ToolResult> {
-    let query = args
-        .get("query")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Missing query"))?;
-
-    let category = args.get("category").and_then(|v| v.as_str());
-    let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
-
-    let memories = db.search_memories(query, category, limit)?;
-
-    let summary = format!("Found {} memories for \"{}\"", memories.len(), query);
-    let structured: Vec<Value> = memories.iter().map(memory_to_json).collect();
-
-    Ok(ToolResult {
-        content: vec![Content::Text { text: summary }],
-        structured_content: Some(serde_json::json!({ "memories": structured })),
-        is_error: None,
-    })
-}
-
-pub async fn handle_memory_list(db: &Database, args: Value) -> Result<ToolResult> {
-    let category = args.get("category").and_then(|v| v.as_str());
-    let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
-    let offset = args.get("offset").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-
-    let memories = db.list_memories(category, limit, offset)?;
-
-    let summary = format!("Listed {} memories", memories.len());
-    let structured: Vec<Value> = memories.iter().map(memory_to_json).collect();
-
-    Ok(ToolResult {
-        content: vec![Content::Text { text: summary }],
-        structured_content: Some(serde_json::json!({ "memories": structured })),
+        structured_content: Some(serde_json::to_value(&job)?),
         is_error: None,
     })
 }
 
-pub async fn handle_memory_extract(db: &Database, args: Value) -> Result<ToolResult> {
-    let session_id = args
-        .get("sessionId")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Missing sessionId"))?;
-
-    let queries = db.get_session_queries(session_id)?;
-    let context = db.get_session_context(session_id)?;
-
-    let extractor = MemoryExtractor::from_env()
-        .map_err(|e| anyhow::anyhow!("Failed to create memory extractor: {}", e))?;
-
-    let extracted = extractor.extract_memories(&queries, &context).await;
+pub async fn handle_job_list(db: &Database, args: Value) -> Result<ToolResult> {
+    let jobs = match args.get("job_id").and_then(|v| v.as_i64()) {
+        Some(id) => db.get_job(id)?.into_iter().collect(),
+        None => {
+            let status = args
+                .get("status")
+                .and_then(|v| v.as_str())
+                .map(|s| s.parse::<JobStatus>())
+                .transpose()?;
+            let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
+            db.list_jobs(status, limit)?
+        }
+    };
 
-    let mut stored_ids: Vec<Value> = Vec::new();
-    for mem in &extracted {
-        let id = db.store_memory(
-            Some(session_id),
-            &mem.category,
-            &mem.content,
-            mem.confidence,
-            None,
-        )?;
-        stored_ids.push(serde_json::json!({
-            "id": id,
-            "category": mem.category,
-            "content": mem.content,
-            "confidence": mem.confidence
-        }));
+    let mut summary = format!("{} jobs", jobs.len());
+    for job in &jobs {
+        summary.push_str(&format!(
+            "\n#{} {} {} (attempt {}/{})",
+            job.id,
+            job.kind.name(),
+            job.status.as_str(),
+            job.attempts,
+            job.max_attempts
+        ));
+        if let Some(error) = &job.last_error {
+            summary.push_str(&format!(": {}", error));
+        }
     }
 
-    let summary = format!(
-        "Extracted and stored {} memories from session {}",
-        stored_ids.len(),
-        session_id
-    );
-
     Ok(ToolResult {
         content: vec![Content::Text { text: summary }],
-        structured_content: Some(serde_json::json!({
-            "count": stored_ids.len(),
-            "memories": stored_ids
-        })),
+        structured_content: Some(serde_json::json!({ "jobs": jobs })),
         is_error: None,
     })
 }
-
-pub async fn handle_memory_delete(db: &Database, args: Value) -> Result<ToolResult> {
-    let id = args
-        .get("id")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Missing id"))?;
-
-    let deleted = db.delete_memory(id)?;
-
-    if deleted {
-        Ok(ToolResult {
-            content: vec![Content::Text {
-                text: format!("Deleted memory: {}", id),
-            }],
-            structured_content: Some(serde_json::json!({
-                "id": id,
-                "deleted": true
-            })),
-            is_error: None,
-        })
-    } else {
-        Ok(ToolResult {
-            content: vec![Content::Text {
-                text: format!("Memory not found: {}", id),
-            }],
-            structured_content: Some(serde_json::json!({
-                "id": id,
-                "deleted": false
-            })),
-            is_error: Some(true),
-        })
-    }
-}
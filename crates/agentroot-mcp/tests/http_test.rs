@@ -0,0 +1,273 @@
+//! End-to-end test of the streamable HTTP transport over a real socket
+
+use agentroot_core::db::DbPool;
+use agentroot_core::Database;
+use agentroot_mcp::http::{DEFAULT_MAX_SESSIONS, SESSION_HEADER};
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+async fn start_server(db_path: &std::path::Path, token: Option<&str>) -> String {
+    start_server_with_limit(db_path, token, DEFAULT_MAX_SESSIONS).await
+}
+
+async fn start_server_with_limit(
+    db_path: &std::path::Path,
+    token: Option<&str>,
+    max_sessions: usize,
+) -> String {
+    let pool = DbPool::open(db_path, 2).unwrap();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = agentroot_mcp::http::router(pool, token.map(str::to_string), max_sessions);
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    format!("http://{}/mcp", addr)
+}
+
+async fn indexed_db(docs: &TempDir, db_dir: &TempDir) -> std::path::PathBuf {
+    std::fs::write(
+        docs.path().join("tokio.md"),
+        "# Tokio Runtime\n\nThe tokio runtime schedules async tasks.",
+    )
+    .unwrap();
+
+    let db_path = db_dir.path().join("index.sqlite");
+    let db = Database::open(&db_path).unwrap();
+    db.initialize().unwrap();
+    db.add_collection(
+        "notes",
+        docs.path().to_str().unwrap(),
+        "**/*.md",
+        "file",
+        None,
+    )
+    .unwrap();
+    db.reindex_collection("notes").await.unwrap();
+    db_path
+}
+
+async fn initialize(client: &reqwest::Client, url: &str) -> String {
+    let response = client
+        .post(url)
+        .json(&json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let session = response.headers()[SESSION_HEADER]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["result"]["serverInfo"]["name"], "agentroot");
+    session
+}
+
+#[tokio::test]
+async fn test_sessions_are_isolated() {
+    let docs = TempDir::new().unwrap();
+    let db_dir = TempDir::new().unwrap();
+    let db_path = indexed_db(&docs, &db_dir).await;
+    let url = start_server(&db_path, None).await;
+    let client = reqwest::Client::new();
+
+    let first = initialize(&client, &url).await;
+    let second = initialize(&client, &url).await;
+    assert_ne!(first, second);
+
+    // Notifications are accepted without a reply
+    let response = client
+        .post(&url)
+        .header(SESSION_HEADER, &first)
+        .json(&json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 202);
+
+    let response: Value = client
+        .post(&url)
+        .header(SESSION_HEADER, &first)
+        .json(&json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "tools/call",
+            "params": {"name": "search", "arguments": {"query": "tokio"}}
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(response["id"], 2);
+    assert!(response["result"].to_string().contains("tokio.md"));
+
+    let events = client
+        .get(&url)
+        .header(SESSION_HEADER, &second)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(
+        events.headers()["content-type"].to_str().unwrap(),
+        "text/event-stream"
+    );
+    drop(events);
+
+    // Ending one session leaves the other usable
+    let response = client
+        .delete(&url)
+        .header(SESSION_HEADER, &first)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 204);
+    let list = json!({"jsonrpc": "2.0", "id": 3, "method": "resources/list", "params": {}});
+    let response = client
+        .post(&url)
+        .header(SESSION_HEADER, &first)
+        .json(&list)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+    let response = client
+        .post(&url)
+        .header(SESSION_HEADER, &second)
+        .json(&list)
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    // Anything but initialize needs a session
+    let response = client.post(&url).json(&list).send().await.unwrap();
+    assert_eq!(response.status(), 400);
+}
+
+#[tokio::test]
+async fn test_session_limit_refuses_new_sessions() {
+    let docs = TempDir::new().unwrap();
+    let db_dir = TempDir::new().unwrap();
+    let db_path = indexed_db(&docs, &db_dir).await;
+    let url = start_server_with_limit(&db_path, None, 2).await;
+    let client = reqwest::Client::new();
+    let list = json!({"jsonrpc": "2.0", "id": 2, "method": "resources/list", "params": {}});
+    let status = |session: String| {
+        let request = client
+            .post(&url)
+            .header(SESSION_HEADER, session)
+            .json(&list);
+        async move { request.send().await.unwrap().status() }
+    };
+
+    let init = json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}});
+
+    let first = initialize(&client, &url).await;
+    let second = initialize(&client, &url).await;
+    let response = client.post(&url).json(&init).send().await.unwrap();
+    assert_eq!(response.status(), 503);
+    assert!(status(first.clone()).await.is_success());
+    assert!(status(second).await.is_success());
+
+    // Ending a session frees its slot
+    let response = client
+        .delete(&url)
+        .header(SESSION_HEADER, &first)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 204);
+    let third = initialize(&client, &url).await;
+    assert!(status(third).await.is_success());
+}
+
+#[tokio::test]
+async fn test_token_and_origin_are_checked() {
+    let docs = TempDir::new().unwrap();
+    let db_dir = TempDir::new().unwrap();
+    let db_path = indexed_db(&docs, &db_dir).await;
+    let url = start_server(&db_path, Some("secret")).await;
+    let client = reqwest::Client::new();
+    let initialize = json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}});
+
+    let response = client.post(&url).json(&initialize).send().await.unwrap();
+    assert_eq!(response.status(), 401);
+
+    let response = client
+        .post(&url)
+        .bearer_auth("secret")
+        .header("origin", "https://evil.example.com")
+        .json(&initialize)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 403);
+
+    let response = client
+        .post(&url)
+        .bearer_auth("secret")
+        .header("origin", "http://localhost:3000")
+        .json(&initialize)
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+}
+
+#[tokio::test]
+async fn test_progress_is_streamed_before_the_response() {
+    let docs = TempDir::new().unwrap();
+    let db_dir = TempDir::new().unwrap();
+    let db_path = indexed_db(&docs, &db_dir).await;
+    for i in 0..5 {
+        std::fs::write(
+            docs.path().join(format!("extra-{}.md", i)),
+            format!("# Extra {}\n\nMore notes.", i),
+        )
+        .unwrap();
+    }
+    let url = start_server(&db_path, None).await;
+    let client = reqwest::Client::new();
+    let session = initialize(&client, &url).await;
+
+    let body = client
+        .post(&url)
+        .header(SESSION_HEADER, &session)
+        .header("accept", "application/json, text/event-stream")
+        .json(&json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": "tools/call",
+            "params": {
+                "name": "collection_update",
+                "arguments": {"name": "notes"},
+                "_meta": {"progressToken": "reindex-1"}
+            }
+        }))
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    let messages: Vec<Value> = body
+        .lines()
+        .filter_map(|line| line.strip_prefix("data: "))
+        .map(|data| serde_json::from_str(data).unwrap())
+        .collect();
+
+    let (response, progress) = messages.split_last().unwrap();
+    assert_eq!(response["id"], 7);
+    assert!(response["result"].is_object());
+    assert!(!progress.is_empty());
+    for notification in progress {
+        assert_eq!(notification["method"], "notifications/progress");
+        assert_eq!(notification["params"]["progressToken"], "reindex-1");
+    }
+    // Only the new files are reindexed; tokio.md is unchanged
+    let last = &progress.last().unwrap()["params"];
+    assert_eq!(last["progress"], 5);
+    assert_eq!(last["total"], 5);
+    assert_eq!(last["message"], "indexed 5/5 files");
+}
@@ -0,0 +1,79 @@
+//! Integration test for the index_file tool
+
+use agentroot_core::Database;
+use agentroot_mcp::tools::{handle_index_file, handle_search};
+use serde_json::json;
+use std::fs;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_indexed_content_is_searchable_immediately() {
+    let docs_dir = TempDir::new().unwrap();
+    fs::write(docs_dir.path().join("intro.md"), "# Intro\n\nWelcome.").unwrap();
+    let db_dir = TempDir::new().unwrap();
+    let db = Database::open(db_dir.path().join("test.sqlite")).unwrap();
+    db.initialize().unwrap();
+    db.add_collection(
+        "notes",
+        docs_dir.path().to_str().unwrap(),
+        "**/*.md",
+        "file",
+        None,
+    )
+    .unwrap();
+    db.reindex_collection("notes").await.unwrap();
+
+    let result = handle_index_file(
+        &db,
+        json!({
+            "collection": "notes",
+            "title": "Deploy Checklist",
+            "content": "# Deploy Checklist\n\nRotate the flamingo credentials before deploying."
+        }),
+    )
+    .await
+    .unwrap();
+    let structured = result.structured_content.unwrap();
+    assert_eq!(structured["path"], "deploy-checklist.md");
+    assert_eq!(structured["changed"], true);
+    assert_eq!(structured["embedded"], false);
+
+    // A file outside the collection root, under a name of our choosing
+    let outside = TempDir::new().unwrap();
+    let file = outside.path().join("scratch.md");
+    fs::write(&file, "# Scratch\n\nThe pelican migration starts in May.").unwrap();
+    let result = handle_index_file(
+        &db,
+        json!({"collection": "notes", "path": file.to_str().unwrap(), "name": "inbox/scratch.md"}),
+    )
+    .await
+    .unwrap();
+    let structured = result.structured_content.unwrap();
+    assert_eq!(structured["path"], "inbox/scratch.md");
+    assert_eq!(structured["title"], "Scratch");
+
+    for (query, path) in [
+        ("flamingo", "deploy-checklist.md"),
+        ("pelican", "inbox/scratch.md"),
+    ] {
+        let result = handle_search(&db, json!({"query": query})).await.unwrap();
+        let found = result.structured_content.unwrap().to_string();
+        assert!(found.contains(path), "{} not found: {}", query, found);
+    }
+
+    // Reindexing the collection keeps them
+    db.reindex_collection("notes").await.unwrap();
+    assert_eq!(db.list_collections().unwrap()[0].document_count, 3);
+
+    assert!(
+        handle_index_file(&db, json!({"collection": "notes", "content": "untitled"}))
+            .await
+            .is_err()
+    );
+    assert!(handle_index_file(
+        &db,
+        json!({"collection": "missing", "content": "x", "title": "x"})
+    )
+    .await
+    .is_err());
+}
@@ -0,0 +1,45 @@
+//! Integration test for the query tool on an index without embeddings
+
+use agentroot_core::Database;
+use agentroot_mcp::tools::handle_query;
+use serde_json::json;
+use std::fs;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_query_without_vectors_uses_bm25() {
+    let docs_dir = TempDir::new().unwrap();
+    fs::write(
+        docs_dir.path().join("birds.md"),
+        "# Birds\n\nThe pelican migration starts in May.",
+    )
+    .unwrap();
+    fs::write(
+        docs_dir.path().join("deploy.md"),
+        "# Deploy\n\nRotate the flamingo credentials.",
+    )
+    .unwrap();
+    let db_dir = TempDir::new().unwrap();
+    let db = Database::open(db_dir.path().join("test.sqlite")).unwrap();
+    db.initialize().unwrap();
+    db.add_collection(
+        "notes",
+        docs_dir.path().to_str().unwrap(),
+        "**/*.md",
+        "file",
+        None,
+    )
+    .unwrap();
+    db.reindex_collection("notes").await.unwrap();
+    assert!(!db.has_vector_index());
+
+    for args in [
+        json!({"query": "pelican"}),
+        json!({"query": "pelican", "fuse": ["flamingo"]}),
+    ] {
+        let result = handle_query(&db, args.clone()).await.unwrap();
+        assert!(!result.is_error.unwrap_or(false));
+        let found = result.structured_content.unwrap().to_string();
+        assert!(found.contains("birds.md"), "{}: {}", args, found);
+    }
+}
@@ -0,0 +1,103 @@
+//! Integration test for MCP document resources
+
+use agentroot_core::Database;
+use agentroot_mcp::resources::{self, PAGE_SIZE};
+use std::fs;
+use tempfile::TempDir;
+
+async fn indexed_db(files: &[(&str, &str)]) -> (TempDir, TempDir, Database) {
+    let docs_dir = TempDir::new().unwrap();
+    for (name, content) in files {
+        fs::write(docs_dir.path().join(name), content).unwrap();
+    }
+
+    let db_dir = TempDir::new().unwrap();
+    let db = Database::open(db_dir.path().join("test.sqlite")).unwrap();
+    db.initialize().unwrap();
+    db.add_collection(
+        "notes",
+        docs_dir.path().to_str().unwrap(),
+        "**/*",
+        "file",
+        None,
+    )
+    .unwrap();
+    db.reindex_collection_with_metadata("notes", None)
+        .await
+        .unwrap();
+    (docs_dir, db_dir, db)
+}
+
+#[tokio::test]
+async fn test_resources_are_listed_in_pages() {
+    let files: Vec<(String, String)> = (0..PAGE_SIZE + 5)
+        .map(|i| {
+            (
+                format!("note-{:03}.md", i),
+                format!("# Note {}\n\nBody.", i),
+            )
+        })
+        .collect();
+    let files: Vec<(&str, &str)> = files
+        .iter()
+        .map(|(n, c)| (n.as_str(), c.as_str()))
+        .collect();
+    let (_docs, _db_dir, db) = indexed_db(&files).await;
+
+    let first = resources::list_resources(&db, None).unwrap();
+    let page = first["resources"].as_array().unwrap();
+    assert_eq!(page.len(), PAGE_SIZE);
+    assert_eq!(page[0]["uri"], "agentroot://notes/note-000.md");
+    assert_eq!(page[0]["mimeType"], "text/markdown");
+    let cursor = first["nextCursor"].as_str().unwrap();
+
+    let second = resources::list_resources(&db, Some(cursor)).unwrap();
+    let page = second["resources"].as_array().unwrap();
+    assert_eq!(page.len(), 5);
+    assert_eq!(
+        page[0]["uri"],
+        format!("agentroot://notes/note-{:03}.md", PAGE_SIZE)
+    );
+    assert!(second.get("nextCursor").is_none());
+}
+
+#[tokio::test]
+async fn test_read_resource_by_uri() {
+    let (_docs, _db_dir, db) = indexed_db(&[
+        ("release notes.md", "# Release Notes\n\nShipped."),
+        ("data.json", "{\"a\": 1}"),
+    ])
+    .await;
+
+    let listed = resources::list_resources(&db, None).unwrap();
+    let uris: Vec<&str> = listed["resources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["uri"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        uris,
+        vec![
+            "agentroot://notes/data.json",
+            "agentroot://notes/release%20notes.md"
+        ]
+    );
+
+    let content = resources::read_resource(&db, uris[1]).await.unwrap();
+    assert_eq!(content.name, "notes/release notes.md");
+    assert_eq!(content.mime_type, "text/markdown");
+    assert!(content.text.contains("Shipped."));
+
+    let content = resources::read_resource(&db, uris[0]).await.unwrap();
+    assert_eq!(content.mime_type, "application/json");
+
+    assert!(
+        resources::read_resource(&db, "agentroot://notes/missing.md")
+            .await
+            .is_err()
+    );
+    assert!(resources::read_resource(&db, "file:///etc/passwd")
+        .await
+        .is_err());
+}
@@ -1,6 +1,14 @@
 //! TUI application state
 
+use crate::reindex::ReindexTask;
+use agentroot_core::config::virtual_path::resolve_virtual_path;
+use agentroot_core::db::CollectionInfo;
+use agentroot_core::providers::ProviderRegistry;
+use agentroot_core::search::{find_match_ranges, session_aware};
 use agentroot_core::{Database, SearchOptions, SearchResult};
+use ratatui::text::Line;
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::rc::Rc;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -9,6 +17,7 @@ pub enum AppMode {
     Results,
     Preview,
     Collections,
+    AddCollection,
     Help,
 }
 
@@ -19,55 +28,146 @@ pub enum SearchMode {
     Hybrid,
 }
 
+/// A document to open in the editor once the terminal is released
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditRequest {
+    pub path: PathBuf,
+    /// 1-based line to place the cursor on
+    pub line: Option<usize>,
+}
+
+/// One input of the add-collection form
+pub struct FormField {
+    pub label: &'static str,
+    pub value: String,
+    /// Shown greyed out while the field is empty
+    pub hint: &'static str,
+}
+
+/// Input form for a new collection
+pub struct CollectionForm {
+    pub fields: [FormField; 4],
+    pub focused: usize,
+    pub error: Option<String>,
+}
+
+impl CollectionForm {
+    const PATH: usize = 0;
+    const NAME: usize = 1;
+    const PATTERN: usize = 2;
+    const PROVIDER: usize = 3;
+
+    fn value(&self, field: usize) -> &str {
+        self.fields[field].value.trim()
+    }
+}
+
+impl Default for CollectionForm {
+    fn default() -> Self {
+        let field = |label, value: &str, hint| FormField {
+            label,
+            value: value.to_string(),
+            hint,
+        };
+        Self {
+            fields: [
+                field("Path", "", "directory, file or URL"),
+                field("Name", "", "defaults to the last path component"),
+                field("Pattern", "**/*.md", "glob of files to index"),
+                field(
+                    "Provider",
+                    "file",
+                    "file, obsidian, github, url, pdf, csv, json, sql, email",
+                ),
+            ],
+            focused: 0,
+            error: None,
+        }
+    }
+}
+
 pub struct App {
     pub db: Rc<Database>,
+    /// Where `db` lives, for connections opened by background reindexes
+    pub db_path: PathBuf,
     pub mode: AppMode,
     pub search_mode: SearchMode,
 
     pub query: String,
     pub cursor_pos: usize,
+    /// Top completion for the query, shown greyed out after it
+    pub suggestion: Option<String>,
 
     pub results: Vec<SearchResult>,
     pub selected: usize,
     pub scroll_offset: usize,
 
     pub preview_content: Option<String>,
+    /// Preview content split into lines, syntax highlighted
+    pub preview_lines: Vec<Line<'static>>,
     pub preview_scroll: usize,
 
     pub collection_filter: Option<String>,
     pub provider_filter: Option<String>,
-    pub collections: Vec<String>,
+    pub collections: Vec<CollectionInfo>,
     pub collections_selected: usize,
+    pub collection_form: CollectionForm,
+    /// Collection waiting for the removal to be confirmed
+    pub pending_removal: Option<String>,
+    pub reindex: Option<ReindexTask>,
 
     pub status_message: Option<String>,
     pub is_loading: bool,
 
+    /// Set by the editor key, taken by the main loop
+    pub pending_edit: Option<EditRequest>,
+
+    /// Shared session (id, display label) for seen-document demotion
+    pub session: Option<(String, String)>,
+
     pub should_quit: bool,
 }
 
 impl App {
-    pub fn new(db: Database) -> Self {
+    pub fn new(db: Database, db_path: PathBuf) -> Self {
         Self {
             db: Rc::new(db),
+            db_path,
             mode: AppMode::Search,
             search_mode: SearchMode::Bm25,
             query: String::new(),
             cursor_pos: 0,
+            suggestion: None,
             results: Vec::new(),
             selected: 0,
             scroll_offset: 0,
             preview_content: None,
+            preview_lines: Vec::new(),
             preview_scroll: 0,
             collection_filter: None,
             provider_filter: None,
             collections: Vec::new(),
             collections_selected: 0,
+            collection_form: CollectionForm::default(),
+            pending_removal: None,
+            reindex: None,
             status_message: None,
             is_loading: false,
+            pending_edit: None,
+            session: None,
             should_quit: false,
         }
     }
 
+    /// Attach to an existing session by id or name
+    pub fn attach_session(&mut self, id_or_name: &str) -> agentroot_core::Result<()> {
+        let info = self.db.resume_session(id_or_name)?;
+        let label = info.name.clone().unwrap_or_else(|| info.id.clone());
+        self.status_message = Some(format!("Attached to session {}", label));
+        self.session = Some((info.id, label));
+        Ok(())
+    }
+
     pub fn search(&mut self) {
         if self.query.is_empty() {
             self.results.clear();
@@ -86,7 +186,23 @@ impl App {
         };
 
         match self.db.search_fts(&self.query, &options) {
-            Ok(r) => {
+            Ok(mut r) => {
+                if let Some((session_id, _)) = &self.session {
+                    let logged =
+                        session_aware::apply_session_awareness(&self.db, &mut r, session_id)
+                            .and_then(|_| {
+                                session_aware::log_session_results(
+                                    &self.db,
+                                    session_id,
+                                    &self.query,
+                                    &r,
+                                    "L2",
+                                )
+                            });
+                    if let Err(e) = logged {
+                        self.status_message = Some(format!("Session error: {}", e));
+                    }
+                }
                 self.results = r;
                 self.selected = 0;
                 self.scroll_offset = 0;
@@ -99,11 +215,37 @@ impl App {
         self.is_loading = false;
     }
 
+    pub fn update_suggestion(&mut self) {
+        let typed = self.query.to_lowercase();
+        self.suggestion = self
+            .db
+            .suggest(&self.query, 1)
+            .ok()
+            .and_then(|s| s.into_iter().next())
+            .map(|s| s.text)
+            .filter(|text| {
+                text.get(..self.query.len())
+                    .is_some_and(|head| head.to_lowercase() == typed)
+            });
+    }
+
+    /// Replace the query with the shown completion
+    pub fn accept_suggestion(&mut self) {
+        if let Some(text) = self.suggestion.take() {
+            self.query = text;
+            self.cursor_pos = self.query.len();
+            self.search();
+            self.update_suggestion();
+        }
+    }
+
     pub fn load_collections(&mut self) {
         match self.db.list_collections() {
             Ok(colls) => {
-                self.collections = colls.iter().map(|c| c.name.clone()).collect();
-                self.collections_selected = 0;
+                self.collections = colls;
+                self.collections_selected = self
+                    .collections_selected
+                    .min(self.collections.len().saturating_sub(1));
             }
             Err(e) => {
                 self.status_message = Some(format!("Error loading collections: {}", e));
@@ -116,8 +258,12 @@ impl App {
             self.load_collections();
         }
 
-        if let Some(coll) = self.collections.get(self.collections_selected) {
-            if self.collection_filter.as_ref() == Some(coll) {
+        if let Some(coll) = self
+            .collections
+            .get(self.collections_selected)
+            .map(|c| c.name.clone())
+        {
+            if self.collection_filter.as_ref() == Some(&coll) {
                 self.collection_filter = None;
                 self.status_message = Some("Collection filter cleared".to_string());
             } else {
@@ -128,6 +274,152 @@ impl App {
         }
     }
 
+    fn selected_collection(&self) -> Option<String> {
+        self.collections
+            .get(self.collections_selected)
+            .map(|c| c.name.clone())
+    }
+
+    pub fn open_collection_form(&mut self) {
+        self.collection_form = CollectionForm::default();
+        self.mode = AppMode::AddCollection;
+    }
+
+    /// Add the collection described by the form and index it
+    pub fn submit_collection_form(&mut self) {
+        match self.add_collection_from_form() {
+            Ok(name) => {
+                self.mode = AppMode::Collections;
+                self.load_collections();
+                if let Some(i) = self.collections.iter().position(|c| c.name == name) {
+                    self.collections_selected = i;
+                }
+                self.start_reindex(name);
+            }
+            Err(e) => self.collection_form.error = Some(e),
+        }
+    }
+
+    fn add_collection_from_form(&self) -> Result<String, String> {
+        let form = &self.collection_form;
+        let path = form.value(CollectionForm::PATH);
+        if path.is_empty() {
+            return Err("Path is required".to_string());
+        }
+        let provider = form.value(CollectionForm::PROVIDER);
+        if ProviderRegistry::with_defaults().get(provider).is_none() {
+            return Err(format!("Unknown provider: {}", provider));
+        }
+        let name = match form.value(CollectionForm::NAME) {
+            "" => std::path::Path::new(path)
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("unnamed")
+                .to_string(),
+            name => name.to_string(),
+        };
+        // Local providers store the absolute path, as `collection add` does
+        let base_path = if matches!(provider, "file" | "obsidian") {
+            std::path::Path::new(path)
+                .canonicalize()
+                .map_err(|e| format!("{}: {}", path, e))?
+                .to_string_lossy()
+                .into_owned()
+        } else {
+            path.to_string()
+        };
+
+        self.db
+            .add_collection(
+                &name,
+                &base_path,
+                form.value(CollectionForm::PATTERN),
+                provider,
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(name)
+    }
+
+    /// Ask before removing the selected collection
+    pub fn request_removal(&mut self) {
+        if let Some(name) = self.selected_collection() {
+            self.status_message = Some(format!(
+                "Remove collection '{}' and its documents? y to confirm",
+                name
+            ));
+            self.pending_removal = Some(name);
+        }
+    }
+
+    /// Remove the collection awaiting confirmation, if `confirmed`
+    pub fn resolve_removal(&mut self, confirmed: bool) {
+        let Some(name) = self.pending_removal.take() else {
+            return;
+        };
+        if !confirmed {
+            self.status_message = Some("Removal cancelled".to_string());
+            return;
+        }
+        if self.reindex.as_ref().is_some_and(|r| r.collection == name) {
+            self.status_message = Some(format!("'{}' is being reindexed", name));
+            return;
+        }
+        match self.db.remove_collection(&name) {
+            Ok(_) => {
+                if self.collection_filter.as_ref() == Some(&name) {
+                    self.collection_filter = None;
+                }
+                self.status_message = Some(format!("Removed collection '{}'", name));
+                self.load_collections();
+                self.search();
+            }
+            Err(e) => self.status_message = Some(format!("Remove failed: {}", e)),
+        }
+    }
+
+    pub fn reindex_selected(&mut self) {
+        if let Some(name) = self.selected_collection() {
+            self.start_reindex(name);
+        }
+    }
+
+    /// Reindex on a background thread; one collection at a time
+    fn start_reindex(&mut self, name: String) {
+        if let Some(running) = &self.reindex {
+            self.status_message = Some(format!("Already reindexing '{}'", running.collection));
+            return;
+        }
+        match ReindexTask::spawn(self.db_path.clone(), name.clone()) {
+            Ok(task) => {
+                self.status_message = Some(format!("Reindexing '{}'...", name));
+                self.reindex = Some(task);
+            }
+            Err(e) => self.status_message = Some(format!("Reindex failed: {}", e)),
+        }
+    }
+
+    /// Pick up progress of a running reindex; called once per frame
+    pub fn poll_reindex(&mut self) {
+        let Some(task) = self.reindex.as_mut() else {
+            return;
+        };
+        let Some(outcome) = task.poll() else {
+            return;
+        };
+        let name = task.collection.clone();
+        self.reindex = None;
+        self.status_message = Some(match outcome {
+            Ok(stats) => format!(
+                "Reindexed '{}': {} updated, {} removed",
+                name, stats.updated, stats.deleted
+            ),
+            Err(e) => format!("Reindex of '{}' failed: {}", name, e),
+        });
+        self.load_collections();
+        self.search();
+    }
+
     pub fn select_next(&mut self) {
         if self.selected < self.results.len().saturating_sub(1) {
             self.selected += 1;
@@ -154,10 +446,52 @@ impl App {
     pub fn load_preview(&mut self) {
         if let Some(result) = self.results.get(self.selected) {
             self.preview_content = result.body.clone();
+            self.preview_lines = match &self.preview_content {
+                Some(body) => crate::ui::highlight::highlight(
+                    body,
+                    result.chunk_language.as_deref(),
+                    &result.display_path,
+                ),
+                None => Vec::new(),
+            };
             self.preview_scroll = 0;
         }
     }
 
+    /// Queue the selected result for the editor, at its matched line
+    pub fn request_edit(&mut self) {
+        let Some(result) = self.results.get(self.selected) else {
+            return;
+        };
+        let roots: HashMap<String, PathBuf> = match self.db.list_collections() {
+            Ok(colls) => colls
+                .into_iter()
+                .filter(|c| matches!(c.provider_type.as_str(), "file" | "obsidian"))
+                .map(|c| (c.name, PathBuf::from(c.path)))
+                .collect(),
+            Err(e) => {
+                self.status_message = Some(format!("Error loading collections: {}", e));
+                return;
+            }
+        };
+        let path = match resolve_virtual_path(&result.filepath, &roots) {
+            Ok(path) if path.is_file() => path,
+            _ => {
+                self.status_message = Some(format!("{} is not a local file", result.display_path));
+                return;
+            }
+        };
+
+        let line = match (result.is_chunk, result.chunk_start_line) {
+            (true, Some(start)) => Some(start.max(1) as usize),
+            _ => result
+                .body
+                .as_deref()
+                .and_then(|body| find_match_ranges(body, &self.query).first().map(|m| m.line)),
+        };
+        self.pending_edit = Some(EditRequest { path, line });
+    }
+
     pub fn cycle_search_mode(&mut self) {
         self.search_mode = match self.search_mode {
             SearchMode::Bm25 => SearchMode::Vector,
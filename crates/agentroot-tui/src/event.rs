@@ -13,6 +13,7 @@ pub async fn handle_events(app: &mut App) -> Result<()> {
                 AppMode::Results => handle_results_input(app, key),
                 AppMode::Preview => handle_preview_input(app, key),
                 AppMode::Collections => handle_collections_input(app, key),
+                AppMode::AddCollection => handle_form_input(app, key),
                 AppMode::Help => handle_help_input(app, key),
             }
         }
@@ -27,14 +28,13 @@ fn handle_search_input(app: &mut App, key: KeyEvent) {
                 app.query.clear();
                 app.cursor_pos = 0;
                 app.results.clear();
+                app.suggestion = None;
             } else {
                 app.should_quit = true;
             }
         }
-        KeyCode::Enter => {
-            if !app.results.is_empty() {
-                app.mode = AppMode::Results;
-            }
+        KeyCode::Enter if !app.results.is_empty() => {
+            app.mode = AppMode::Results;
         }
         KeyCode::Down => {
             app.mode = AppMode::Results;
@@ -54,23 +54,24 @@ fn handle_search_input(app: &mut App, key: KeyEvent) {
             app.query.insert(app.cursor_pos, c);
             app.cursor_pos += 1;
             app.search();
+            app.update_suggestion();
         }
-        KeyCode::Backspace => {
-            if app.cursor_pos > 0 {
-                app.cursor_pos -= 1;
-                app.query.remove(app.cursor_pos);
-                app.search();
-            }
+        KeyCode::Backspace if app.cursor_pos > 0 => {
+            app.cursor_pos -= 1;
+            app.query.remove(app.cursor_pos);
+            app.search();
+            app.update_suggestion();
         }
-        KeyCode::Left => {
-            if app.cursor_pos > 0 {
-                app.cursor_pos -= 1;
-            }
+        KeyCode::Right | KeyCode::End
+            if app.cursor_pos == app.query.len() && app.suggestion.is_some() =>
+        {
+            app.accept_suggestion();
         }
-        KeyCode::Right => {
-            if app.cursor_pos < app.query.len() {
-                app.cursor_pos += 1;
-            }
+        KeyCode::Left if app.cursor_pos > 0 => {
+            app.cursor_pos -= 1;
+        }
+        KeyCode::Right if app.cursor_pos < app.query.len() => {
+            app.cursor_pos += 1;
         }
         _ => {}
     }
@@ -94,6 +95,9 @@ fn handle_results_input(app: &mut App, key: KeyEvent) {
         KeyCode::Up | KeyCode::Char('k') => {
             app.select_prev();
         }
+        KeyCode::Char('e') | KeyCode::Char('o') => {
+            app.request_edit();
+        }
         KeyCode::Char('y') => {
             if let Some(result) = app.results.get(app.selected) {
                 if let Ok(mut clipboard) = arboard::Clipboard::new() {
@@ -111,6 +115,10 @@ fn handle_results_input(app: &mut App, key: KeyEvent) {
 }
 
 fn handle_collections_input(app: &mut App, key: KeyEvent) {
+    if app.pending_removal.is_some() {
+        app.resolve_removal(key.code == KeyCode::Char('y'));
+        return;
+    }
     match key.code {
         KeyCode::Esc | KeyCode::Char('q') => {
             app.mode = AppMode::Search;
@@ -119,14 +127,51 @@ fn handle_collections_input(app: &mut App, key: KeyEvent) {
             app.toggle_collection_filter();
             app.mode = AppMode::Search;
         }
-        KeyCode::Down | KeyCode::Char('j') => {
-            if app.collections_selected < app.collections.len().saturating_sub(1) {
-                app.collections_selected += 1;
-            }
+        KeyCode::Down | KeyCode::Char('j')
+            if app.collections_selected < app.collections.len().saturating_sub(1) =>
+        {
+            app.collections_selected += 1;
         }
         KeyCode::Up | KeyCode::Char('k') => {
             app.collections_selected = app.collections_selected.saturating_sub(1);
         }
+        KeyCode::Char('a') => {
+            app.open_collection_form();
+        }
+        KeyCode::Char('d') => {
+            app.request_removal();
+        }
+        KeyCode::Char('r') => {
+            app.reindex_selected();
+        }
+        _ => {}
+    }
+}
+
+fn handle_form_input(app: &mut App, key: KeyEvent) {
+    let form = &mut app.collection_form;
+    let field_count = form.fields.len();
+    match key.code {
+        KeyCode::Esc => {
+            app.mode = AppMode::Collections;
+        }
+        KeyCode::Enter => {
+            app.submit_collection_form();
+        }
+        KeyCode::Tab | KeyCode::Down => {
+            form.focused = (form.focused + 1) % field_count;
+        }
+        KeyCode::BackTab | KeyCode::Up => {
+            form.focused = (form.focused + field_count - 1) % field_count;
+        }
+        KeyCode::Char(c) => {
+            form.fields[form.focused].value.push(c);
+            form.error = None;
+        }
+        KeyCode::Backspace => {
+            form.fields[form.focused].value.pop();
+            form.error = None;
+        }
         _ => {}
     }
 }
@@ -157,6 +202,9 @@ fn handle_preview_input(app: &mut App, key: KeyEvent) {
         KeyCode::PageUp => {
             app.preview_scroll = app.preview_scroll.saturating_sub(20);
         }
+        KeyCode::Char('e') | KeyCode::Char('o') => {
+            app.request_edit();
+        }
         _ => {}
     }
 }
@@ -2,6 +2,7 @@
 //!
 //! Terminal user interface for searching your knowledge base.
 
+use agentroot_core::config::editor::editor_command;
 use agentroot_core::Database;
 use anyhow::Result;
 use crossterm::{
@@ -13,12 +14,22 @@ use ratatui::{backend::CrosstermBackend, Terminal};
 
 mod app;
 mod event;
+mod reindex;
 mod ui;
 
-use app::App;
+use app::{App, EditRequest};
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // Resolve the shared session before touching the terminal so errors print cleanly
+    let db_path = Database::default_path();
+    let db = Database::open(&db_path)?;
+    db.initialize()?;
+    let mut app = App::new(db, db_path);
+    if let Some(session) = session_arg() {
+        app.attach_session(&session)?;
+    }
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = std::io::stdout();
@@ -26,11 +37,6 @@ async fn main() -> Result<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // Initialize app
-    let db = Database::open(Database::default_path())?;
-    db.initialize()?;
-    let mut app = App::new(db);
-
     // Main loop
     let result = run_app(&mut terminal, &mut app).await;
 
@@ -46,6 +52,22 @@ async fn main() -> Result<()> {
     result
 }
 
+/// Session to attach to, from `--session <ID|NAME>` or `AGENTROOT_SESSION`
+fn session_arg() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--session" {
+            return args.next();
+        }
+        if let Some(value) = arg.strip_prefix("--session=") {
+            return Some(value.to_string());
+        }
+    }
+    std::env::var("AGENTROOT_SESSION")
+        .ok()
+        .filter(|s| !s.is_empty())
+}
+
 async fn run_app(
     terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
     app: &mut App,
@@ -54,6 +76,15 @@ async fn run_app(
         terminal.draw(|f| ui::render(f, app))?;
 
         event::handle_events(app).await?;
+        app.poll_reindex();
+
+        if let Some(edit) = app.pending_edit.take() {
+            app.status_message = Some(match open_in_editor(terminal, &edit) {
+                Ok(status) if status.success() => format!("Edited {}", edit.path.display()),
+                Ok(status) => format!("Editor exited with {}", status),
+                Err(e) => format!("Could not start editor: {}", e),
+            });
+        }
 
         if app.should_quit {
             break;
@@ -61,3 +92,27 @@ async fn run_app(
     }
     Ok(())
 }
+
+/// Hand the terminal to the user's editor until it exits
+fn open_in_editor(
+    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+    edit: &EditRequest,
+) -> Result<std::process::ExitStatus> {
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
+
+    let status = editor_command(&edit.path, edit.line).status();
+
+    enable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture
+    )?;
+    terminal.clear()?;
+    Ok(status?)
+}
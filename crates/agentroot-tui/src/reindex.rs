@@ -0,0 +1,70 @@
+//! Collection reindexing in the background
+//!
+//! The TUI's connection lives on the UI thread, so a reindex opens its own
+//! and reports back over a channel that the main loop drains between frames.
+
+use agentroot_core::db::ReindexStats;
+use agentroot_core::{Database, ProgressUpdate};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+enum Message {
+    Progress(ProgressUpdate),
+    Done(agentroot_core::Result<ReindexStats>),
+}
+
+/// A reindex running on its own thread
+pub struct ReindexTask {
+    pub collection: String,
+    /// Latest progress, None until the first item is discovered
+    pub progress: Option<ProgressUpdate>,
+    receiver: Receiver<Message>,
+}
+
+impl ReindexTask {
+    pub fn spawn(db_path: PathBuf, collection: String) -> std::io::Result<Self> {
+        let (sender, receiver) = mpsc::channel();
+        let name = collection.clone();
+        std::thread::Builder::new()
+            .name("agentroot-reindex".to_string())
+            .spawn(move || {
+                let result = reindex(&db_path, &name, &sender);
+                let _ = sender.send(Message::Done(result));
+            })?;
+        Ok(Self {
+            collection,
+            progress: None,
+            receiver,
+        })
+    }
+
+    /// Take pending updates; returns the outcome once the reindex is over
+    pub fn poll(&mut self) -> Option<Result<ReindexStats, String>> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(Message::Progress(update)) => self.progress = Some(update),
+                Ok(Message::Done(result)) => return Some(result.map_err(|e| e.to_string())),
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => {
+                    return Some(Err("reindex thread stopped".to_string()))
+                }
+            }
+        }
+    }
+}
+
+fn reindex(
+    db_path: &std::path::Path,
+    collection: &str,
+    sender: &mpsc::Sender<Message>,
+) -> agentroot_core::Result<ReindexStats> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let db = Database::open(db_path)?;
+    db.initialize()?;
+    let sink = |update: &ProgressUpdate| {
+        let _ = sender.send(Message::Progress(update.clone()));
+    };
+    runtime.block_on(db.reindex_collection_with_progress(collection, &sink))
+}
@@ -0,0 +1,115 @@
+//! Syntax highlighting for the preview pane
+
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+use std::path::Path;
+use std::sync::OnceLock;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{FontStyle, Theme, ThemeSet};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+/// Theme from syntect's bundled set
+const THEME: &str = "base16-ocean.dark";
+
+/// Lines highlighted per document; the rest is shown plain
+const MAX_HIGHLIGHT_LINES: usize = 5000;
+
+struct Highlighter {
+    syntaxes: SyntaxSet,
+    theme: Theme,
+}
+
+fn highlighter() -> &'static Highlighter {
+    static HIGHLIGHTER: OnceLock<Highlighter> = OnceLock::new();
+    HIGHLIGHTER.get_or_init(|| {
+        let mut themes = ThemeSet::load_defaults();
+        Highlighter {
+            syntaxes: SyntaxSet::load_defaults_newlines(),
+            theme: themes.themes.remove(THEME).unwrap_or_default(),
+        }
+    })
+}
+
+/// Split `content` into preview lines, colorized when its syntax is known
+///
+/// The syntax comes from `language` (a chunk's language, e.g. "rust") and
+/// otherwise from the extension of `path`.
+pub fn highlight(content: &str, language: Option<&str>, path: &str) -> Vec<Line<'static>> {
+    let hl = highlighter();
+    let syntax = find_syntax(&hl.syntaxes, language, path)
+        .filter(|syntax| syntax.name != hl.syntaxes.find_syntax_plain_text().name);
+    let Some(syntax) = syntax else {
+        return content.lines().map(plain_line).collect();
+    };
+
+    let mut lines = Vec::new();
+    let mut highlighter = HighlightLines::new(syntax, &hl.theme);
+    for (i, line) in LinesWithEndings::from(content).enumerate() {
+        let highlighted = (i < MAX_HIGHLIGHT_LINES)
+            .then(|| highlighter.highlight_line(line, &hl.syntaxes).ok())
+            .flatten();
+        let ranges = match highlighted {
+            Some(ranges) => ranges,
+            None => {
+                lines.extend(content.lines().skip(i).map(plain_line));
+                break;
+            }
+        };
+        let spans = ranges
+            .into_iter()
+            .map(|(style, text)| {
+                let text = text.trim_end_matches(['\n', '\r']);
+                Span::styled(text.to_string(), convert_style(style))
+            })
+            .filter(|span| !span.content.is_empty())
+            .collect::<Vec<_>>();
+        lines.push(Line::from(spans));
+    }
+    lines
+}
+
+fn plain_line(line: &str) -> Line<'static> {
+    Line::from(line.to_string())
+}
+
+fn find_syntax<'a>(
+    syntaxes: &'a SyntaxSet,
+    language: Option<&str>,
+    path: &str,
+) -> Option<&'a SyntaxReference> {
+    let by_language = language.and_then(|lang| {
+        // The bundled syntaxes have no TypeScript; JavaScript is close enough
+        let token = match lang {
+            "typescript" | "tsx" => "javascript",
+            other => other,
+        };
+        syntaxes.find_syntax_by_token(token)
+    });
+    by_language.or_else(|| {
+        let ext = Path::new(path).extension()?.to_str()?;
+        let ext = match ext {
+            "ts" | "mts" | "cts" | "tsx" => "js",
+            other => other,
+        };
+        syntaxes.find_syntax_by_extension(ext)
+    })
+}
+
+/// Foreground and font style only, so the terminal background shows through
+fn convert_style(style: syntect::highlighting::Style) -> Style {
+    let fg = style.foreground;
+    let mut converted = Style::default().fg(Color::Rgb(fg.r, fg.g, fg.b));
+    if style.font_style.contains(FontStyle::BOLD) {
+        converted = converted.add_modifier(Modifier::BOLD);
+    }
+    if style.font_style.contains(FontStyle::ITALIC) {
+        converted = converted.add_modifier(Modifier::ITALIC);
+    }
+    if style.font_style.contains(FontStyle::UNDERLINE) {
+        converted = converted.add_modifier(Modifier::UNDERLINED);
+    }
+    converted
+}
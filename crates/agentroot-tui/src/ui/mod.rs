@@ -1,11 +1,13 @@
 //! TUI rendering
 
+pub mod highlight;
+
 use crate::app::{App, AppMode, SearchMode};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+    widgets::{Block, Borders, Gauge, List, ListItem, Paragraph, Wrap},
     Frame,
 };
 
@@ -31,13 +33,27 @@ fn render_search_bar(frame: &mut Frame, app: &App, area: Rect) {
         SearchMode::Hybrid => "[HYB]",
     };
 
-    let input = Paragraph::new(format!("{} {}", mode_indicator, app.query))
+    let title = match &app.session {
+        Some((_, label)) => format!(" Search [session: {}] (Tab to change mode) ", label),
+        None => " Search (Tab to change mode) ".to_string(),
+    };
+
+    let mut spans = vec![Span::raw(format!("{} {}", mode_indicator, app.query))];
+    if let Some(completion) = app
+        .suggestion
+        .as_deref()
+        .filter(|_| app.mode == AppMode::Search && app.cursor_pos == app.query.len())
+        .and_then(|text| text.get(app.query.len()..))
+    {
+        spans.push(Span::styled(
+            completion,
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+
+    let input = Paragraph::new(Line::from(spans))
         .style(Style::default().fg(Color::Yellow))
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(" Search (Tab to change mode) "),
-        );
+        .block(Block::default().borders(Borders::ALL).title(title));
 
     frame.render_widget(input, area);
 
@@ -66,6 +82,9 @@ fn render_main(frame: &mut Frame, app: &App, area: Rect) {
         AppMode::Collections => {
             render_collections(frame, app, area);
         }
+        AppMode::AddCollection => {
+            render_collection_form(frame, app, area);
+        }
         AppMode::Help => {
             render_help(frame, area);
         }
@@ -120,26 +139,24 @@ fn render_results(frame: &mut Frame, app: &App, area: Rect) {
 }
 
 fn render_preview(frame: &mut Frame, app: &App, area: Rect) {
-    let content = app
-        .preview_content
-        .as_deref()
-        .unwrap_or("No preview available");
-
-    let lines: Vec<Line> = content
-        .lines()
-        .skip(app.preview_scroll)
-        .take(area.height as usize - 2)
-        .enumerate()
-        .map(|(i, line)| {
-            Line::from(vec![
-                Span::styled(
+    let lines: Vec<Line> = if app.preview_content.is_none() {
+        vec![Line::from("No preview available")]
+    } else {
+        app.preview_lines
+            .iter()
+            .skip(app.preview_scroll)
+            .take(area.height as usize - 2)
+            .enumerate()
+            .map(|(i, line)| {
+                let mut spans = vec![Span::styled(
                     format!("{:>4} ", app.preview_scroll + i + 1),
                     Style::default().fg(Color::DarkGray),
-                ),
-                Span::raw(line),
-            ])
-        })
-        .collect();
+                )];
+                spans.extend(line.spans.iter().cloned());
+                Line::from(spans)
+            })
+            .collect()
+    };
 
     let paragraph = Paragraph::new(lines)
         .block(Block::default().borders(Borders::ALL).title(" Preview "))
@@ -149,6 +166,17 @@ fn render_preview(frame: &mut Frame, app: &App, area: Rect) {
 }
 
 fn render_collections(frame: &mut Frame, app: &App, area: Rect) {
+    let (list_area, progress_area) = match &app.reindex {
+        Some(_) => {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(3)])
+                .split(area);
+            (chunks[0], Some(chunks[1]))
+        }
+        None => (area, None),
+    };
+
     let items: Vec<ListItem> = app
         .collections
         .iter()
@@ -162,12 +190,19 @@ fn render_collections(frame: &mut Frame, app: &App, area: Rect) {
                 Style::default()
             };
 
-            let is_filtered = app.collection_filter.as_ref() == Some(coll);
+            let is_filtered = app.collection_filter.as_ref() == Some(&coll.name);
             let marker = if is_filtered { "[*] " } else { "[ ] " };
 
             let line = Line::from(vec![
                 Span::styled(marker, Style::default().fg(Color::Yellow)),
-                Span::raw(coll),
+                Span::raw(&coll.name),
+                Span::styled(
+                    format!(
+                        "  {} docs  {}  {} ({})",
+                        coll.document_count, coll.provider_type, coll.path, coll.pattern
+                    ),
+                    Style::default().fg(Color::DarkGray),
+                ),
             ]);
 
             ListItem::new(line).style(style)
@@ -177,10 +212,83 @@ fn render_collections(frame: &mut Frame, app: &App, area: Rect) {
     let list = List::new(items).block(
         Block::default()
             .borders(Borders::ALL)
-            .title(" Collections (Enter to filter, Esc to close) "),
+            .title(" Collections (Enter: filter, a: add, d: remove, r: reindex) "),
     );
 
-    frame.render_widget(list, area);
+    frame.render_widget(list, list_area);
+
+    if let (Some(task), Some(area)) = (&app.reindex, progress_area) {
+        let (ratio, label) = match &task.progress {
+            Some(update) => (
+                update.fraction().unwrap_or(0.0),
+                format!(
+                    "{}/{} {}",
+                    update.completed(),
+                    update.discovered,
+                    update.message.as_deref().unwrap_or("")
+                ),
+            ),
+            None => (0.0, "discovering...".to_string()),
+        };
+        let gauge = Gauge::default()
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" Reindexing {} ", task.collection)),
+            )
+            .gauge_style(Style::default().fg(Color::Green))
+            .ratio(ratio)
+            .label(label);
+        frame.render_widget(gauge, area);
+    }
+}
+
+fn render_collection_form(frame: &mut Frame, app: &App, area: Rect) {
+    let form = &app.collection_form;
+    let mut lines: Vec<Line> = form
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let focused = i == form.focused;
+            let label_style = if focused {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            let value = if field.value.is_empty() {
+                Span::styled(field.hint, Style::default().fg(Color::DarkGray))
+            } else {
+                Span::raw(field.value.as_str())
+            };
+            Line::from(vec![
+                Span::styled(format!("{:>9}: ", field.label), label_style),
+                value,
+            ])
+        })
+        .collect();
+    if let Some(error) = &form.error {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            error.as_str(),
+            Style::default().fg(Color::Red),
+        )));
+    }
+
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Add collection (Enter: add and index, Esc: cancel) "),
+    );
+    frame.render_widget(paragraph, area);
+
+    let field = &form.fields[form.focused];
+    frame.set_cursor_position((
+        area.x + 1 + 11 + field.value.chars().count() as u16,
+        area.y + 1 + form.focused as u16,
+    ));
 }
 
 fn render_help(frame: &mut Frame, area: Rect) {
@@ -190,6 +298,7 @@ fn render_help(frame: &mut Frame, area: Rect) {
         "Search Mode:",
         "  Type to search",
         "  Tab       - Cycle search mode (BM25/Vector/Hybrid)",
+        "  Right/End - Accept the greyed-out completion",
         "  Enter     - View results",
         "  c         - Collections",
         "  ?         - This help screen",
@@ -198,6 +307,7 @@ fn render_help(frame: &mut Frame, area: Rect) {
         "Results Mode:",
         "  j/k       - Navigate up/down",
         "  Enter     - Preview document",
+        "  e/o       - Open in $EDITOR at the match",
         "  y         - Copy file path to clipboard",
         "  c         - Toggle collection filter",
         "  /         - Return to search",
@@ -206,11 +316,15 @@ fn render_help(frame: &mut Frame, area: Rect) {
         "Preview Mode:",
         "  j/k       - Scroll up/down",
         "  PgUp/PgDn - Page up/down",
+        "  e/o       - Open in $EDITOR at the match",
         "  Esc/q     - Back to results",
         "",
         "Collections Mode:",
         "  j/k       - Navigate",
         "  Enter     - Toggle filter",
+        "  a         - Add a collection (Tab/arrows move between fields)",
+        "  d         - Remove the collection (y to confirm)",
+        "  r         - Reindex the collection in the background",
         "  Esc       - Close",
     ];
 
@@ -233,6 +347,21 @@ fn render_help(frame: &mut Frame, area: Rect) {
 fn render_status(frame: &mut Frame, app: &App, area: Rect) {
     let status = if app.is_loading {
         "Loading...".to_string()
+    } else if let Some(task) = app
+        .reindex
+        .as_ref()
+        .filter(|_| app.mode != AppMode::Collections)
+    {
+        // The collections screen shows a gauge instead
+        match &task.progress {
+            Some(update) => format!(
+                "Reindexing {}: {}/{}",
+                task.collection,
+                update.completed(),
+                update.discovered
+            ),
+            None => format!("Reindexing {}...", task.collection),
+        }
     } else if let Some(ref msg) = app.status_message {
         msg.clone()
     } else {
@@ -240,9 +369,14 @@ fn render_status(frame: &mut Frame, app: &App, area: Rect) {
             AppMode::Search => {
                 "Enter: results | Tab: mode | c: collections | ?: help | Esc: clear/quit"
             }
-            AppMode::Results => "j/k: navigate | Enter: preview | y: copy | c: filter | /: search",
-            AppMode::Preview => "j/k: scroll | q: back",
-            AppMode::Collections => "j/k: navigate | Enter: select | Esc: back",
+            AppMode::Results => {
+                "j/k: navigate | Enter: preview | e: edit | y: copy | c: filter | /: search"
+            }
+            AppMode::Preview => "j/k: scroll | e: edit | q: back",
+            AppMode::Collections => {
+                "j/k: navigate | Enter: filter | a: add | d: remove | r: reindex | Esc: back"
+            }
+            AppMode::AddCollection => "Tab: next field | Enter: add | Esc: cancel",
             AppMode::Help => "q/Esc: back",
         };
         mode_help.to_string()
@@ -84,7 +84,7 @@ mod tests {
         concepts: Vec<&'static str>,
     }
 
-    let chunks = vec![
+    let chunks = [
         ChunkDef {
             content: "use std::collections::HashMap;\nuse serde::{Serialize, Deserialize};",
             chunk_type: "Imports", breadcrumb: "imports",
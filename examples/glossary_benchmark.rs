@@ -15,7 +15,7 @@ use agentroot_core::config::LLMServiceConfig;
 use agentroot_core::db::{hash_content, Database};
 use agentroot_core::llm::{MergeStrategy, Workflow, WorkflowStep};
 use agentroot_core::llm::{MetadataContext, MetadataGenerator, VLLMClient};
-use agentroot_core::search::{execute_workflow, SearchOptions, SearchSource};
+use agentroot_core::search::{execute_workflow, SearchOptions};
 use std::collections::HashSet;
 use std::env;
 use std::fs;
@@ -29,6 +29,7 @@ struct BenchmarkQuery {
     query_type: &'static str,
 }
 
+#[allow(dead_code)]
 struct BenchmarkResults {
     query: String,
     query_type: String,
@@ -66,13 +67,17 @@ async fn main() {
     println!("  Endpoint: {}\n", llm_url);
 
     let config = LLMServiceConfig {
+        provider: Default::default(),
         url: llm_url,
         model: llm_model.clone(),
         embedding_url: None,
         embedding_model: "intfloat/e5-mistral-7b-instruct".to_string(),
         embedding_dimensions: Some(4096),
+        embedding_rpm: None,
+        embedding_tpm: None,
         api_key: None,
         timeout_secs: 60,
+        pii_mask: Default::default(),
     };
 
     let client = VLLMClient::new(config).unwrap();
@@ -102,16 +107,14 @@ async fn main() {
     }
 
     // Add docs directory
-    for entry in fs::read_dir("docs").ok().into_iter().flatten() {
-        if let Ok(entry) = entry {
-            if let Some(ext) = entry.path().extension() {
-                if ext == "md" {
-                    let path = entry.path().display().to_string();
-                    docs_to_index.push((
-                        Box::leak(path.clone().into_boxed_str()) as &'static str,
-                        format!("Doc: {}", entry.file_name().to_string_lossy()),
-                    ));
-                }
+    for entry in fs::read_dir("docs").ok().into_iter().flatten().flatten() {
+        if let Some(ext) = entry.path().extension() {
+            if ext == "md" {
+                let path = entry.path().display().to_string();
+                docs_to_index.push((
+                    Box::leak(path.clone().into_boxed_str()) as &'static str,
+                    format!("Doc: {}", entry.file_name().to_string_lossy()),
+                ));
             }
         }
     }
@@ -205,7 +208,10 @@ async fn main() {
     }
 
     let (unique_concepts, concept_links) = db.get_concept_stats().unwrap();
-    println!("\n  ✓ Indexed {} documents", indexed);
+    println!(
+        "\n  ✓ Indexed {} documents ({} concepts)",
+        indexed, total_concepts
+    );
     println!("  ✓ Extracted {} unique concepts", unique_concepts);
     println!("  ✓ Created {} concept links", concept_links);
 
@@ -30,6 +30,7 @@ use std::path::Path;
 use std::sync::Arc;
 
 #[derive(Debug)]
+#[allow(dead_code)]
 struct QueryTest {
     query: &'static str,
     category: &'static str,
@@ -62,13 +63,17 @@ async fn main() {
     println!("  LLM Model: {}", llm_model);
 
     let config = LLMServiceConfig {
+        provider: Default::default(),
         url: llm_url,
         model: llm_model.clone(),
         embedding_url: None,
         embedding_model: "intfloat/e5-mistral-7b-instruct".to_string(),
         embedding_dimensions: Some(4096),
+        embedding_rpm: None,
+        embedding_tpm: None,
         api_key: None,
         timeout_secs: 60,
+        pii_mask: Default::default(),
     };
 
     let client = match VLLMClient::new(config) {
@@ -299,7 +299,7 @@ See examples/ directory for complete configuration samples.
         .filter(|r| {
             r.llm_difficulty
                 .as_ref()
-                .map_or(false, |d| d.to_lowercase().contains("beginner"))
+                .is_some_and(|d| d.to_lowercase().contains("beginner"))
         })
         .collect();
 
@@ -320,7 +320,7 @@ See examples/ directory for complete configuration samples.
         .filter(|r| {
             r.llm_difficulty
                 .as_ref()
-                .map_or(false, |d| d.to_lowercase().contains("advanced"))
+                .is_some_and(|d| d.to_lowercase().contains("advanced"))
         })
         .collect();
 
@@ -344,7 +344,7 @@ See examples/ directory for complete configuration samples.
         .filter(|r| {
             r.llm_category
                 .as_ref()
-                .map_or(false, |c| c.to_lowercase().contains("config"))
+                .is_some_and(|c| c.to_lowercase().contains("config"))
         })
         .collect();
 
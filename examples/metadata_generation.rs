@@ -303,7 +303,7 @@ fn generate_fallback_metadata(content: &str) -> DocumentMetadata {
     // Extract concepts (capitalized words)
     let concepts: Vec<String> = content
         .split_whitespace()
-        .filter(|w| w.len() > 2 && w.chars().next().map_or(false, |c| c.is_uppercase()))
+        .filter(|w| w.len() > 2 && w.chars().next().is_some_and(|c| c.is_uppercase()))
         .map(|w| w.to_string())
         .take(10)
         .collect();
@@ -177,7 +177,11 @@ fn rrf_fuse<'a>(bm25: &[SearchResult], vec_scored: &[(f32, &'a str, &str)]) -> V
 
     // BM25 results: match on the raw path portion of display_path
     for (rank, r) in bm25.iter().enumerate() {
-        let path = r.display_path.split('/').last().unwrap_or(&r.display_path);
+        let path = r
+            .display_path
+            .split('/')
+            .next_back()
+            .unwrap_or(&r.display_path);
         // Find matching vec entry to use same key
         if let Some((_, vpath, _)) = vec_scored.iter().find(|(_, p, _)| *p == path) {
             *scores.entry(vpath).or_default() += 1.0 / (RRF_K + (rank + 1) as f64);
@@ -54,6 +54,7 @@ async fn main() -> agentroot_core::Result<()> {
     println!("Inserted {} documents\n", documents.len());
 
     // Insert chunks for two documents to enable chunk search
+    #[allow(clippy::type_complexity)]
     let chunk_data: Vec<(&str, &str, Vec<(&str, &str)>)> = vec![
         (
             "BM25 uses term frequency and inverse document frequency for scoring.",